@@ -2,23 +2,60 @@ use bulletproofs::{BulletproofGens, PedersenGens};
 
 #[macro_use]
 extern crate criterion;
-use criterion::Criterion;
+use criterion::{BenchmarkId, Criterion};
 
 fn pc_gens(c: &mut Criterion) {
-    c.bench_function("PedersenGens::new", |b| b.iter(|| PedersenGens::default()));
+    c.bench_function("PedersenGens::new", |b| b.iter(PedersenGens::default));
 }
 
 fn bp_gens(c: &mut Criterion) {
-    c.bench_function_over_inputs(
-        "BulletproofGens::new",
-        |b, size| b.iter(|| BulletproofGens::new(*size, 1)),
-        (0..10).map(|i| 2 << i),
-    );
+    let mut group = c.benchmark_group("BulletproofGens::new");
+
+    for size in (0..10).map(|i| 2 << i) {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| BulletproofGens::new(size, 1))
+        });
+    }
+
+    group.finish();
+}
+
+fn bp_gens_increase_capacity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BulletproofGens::increase_capacity");
+
+    for size in (0..10).map(|i| 2 << i) {
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| {
+                let mut gens = BulletproofGens::new(size, 1);
+                gens.increase_capacity(2 * size);
+                gens
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bp_gens_validate_distinctness(c: &mut Criterion) {
+    let mut group = c.benchmark_group("BulletproofGens::validate_distinctness");
+    group.sample_size(10);
+
+    // 2^20 generators in all, half in G and half in H.
+    for size in [1 << 10, 1 << 19] {
+        let gens = BulletproofGens::new(size, 1);
+        group.bench_with_input(BenchmarkId::from_parameter(2 * size), &gens, |b, gens| {
+            b.iter(|| gens.validate_distinctness().unwrap())
+        });
+    }
+
+    group.finish();
 }
 
 criterion_group! {
     bp,
     bp_gens,
+    bp_gens_increase_capacity,
+    bp_gens_validate_distinctness,
     pc_gens,
 }
 
@@ -0,0 +1,183 @@
+#![allow(non_snake_case)]
+
+#[macro_use]
+extern crate criterion;
+use criterion::{BenchmarkId, Criterion};
+
+use core::iter;
+
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::{inner_product, InnerProductProof, PublicVectorMode};
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use merlin::Transcript;
+
+/// Different inner product proof vector lengths to try
+static TEST_SIZES: [usize; 5] = [64, 256, 1024, 4096, 16384];
+
+/// The public and secret inputs of an inner product proof of length `n`.
+struct Instance {
+    Q: RistrettoPoint,
+    P: RistrettoPoint,
+    G: Vec<RistrettoPoint>,
+    H: Vec<RistrettoPoint>,
+    G_factors: Vec<Scalar>,
+    H_factors: Vec<Scalar>,
+    a: Vec<Scalar>,
+    b: Vec<Scalar>,
+}
+
+impl Instance {
+    fn new(n: usize, rng: &mut ChaChaRng) -> Self {
+        let G: Vec<RistrettoPoint> = (0..n).map(|_| RistrettoPoint::random(rng)).collect();
+        let H: Vec<RistrettoPoint> = (0..n).map(|_| RistrettoPoint::random(rng)).collect();
+        let Q = RistrettoPoint::random(rng);
+
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let c = inner_product(&a, &b);
+
+        // y_inv is (the inverse of) a random challenge
+        let y_inv = Scalar::random(rng);
+        let G_factors: Vec<Scalar> = iter::repeat_n(Scalar::ONE, n).collect();
+        let H_factors: Vec<Scalar> = iter::successors(Some(Scalar::ONE), |y| Some(y * y_inv))
+            .take(n)
+            .collect();
+
+        // P = <a,G> + <b',H> + <a,b> Q, where b' = b \circ y^(-n)
+        let P = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter()
+                .cloned()
+                .chain(b.iter().zip(H_factors.iter()).map(|(b_i, y_i)| b_i * y_i))
+                .chain(iter::once(c)),
+            G.iter().chain(H.iter()).chain(iter::once(&Q)),
+        );
+
+        Instance {
+            Q,
+            P,
+            G,
+            H,
+            G_factors,
+            H_factors,
+            a,
+            b,
+        }
+    }
+
+    fn prove(&self) -> InnerProductProof {
+        let mut transcript = Transcript::new(b"InnerProductBenchmark");
+        InnerProductProof::create(
+            &mut transcript,
+            &self.Q,
+            &self.G_factors,
+            &self.H_factors,
+            self.G.clone(),
+            self.H.clone(),
+            self.a.clone(),
+            self.b.clone(),
+        )
+    }
+}
+
+fn create_ipp(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Inner product proof creation");
+    group.sample_size(10);
+
+    for &n in &TEST_SIZES {
+        let mut rng = ChaChaRng::from_seed([n.trailing_zeros() as u8; 32]);
+        let instance = Instance::new(n, &mut rng);
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, _| {
+            b.iter(|| instance.prove())
+        });
+    }
+
+    group.finish();
+}
+
+fn verify_ipp(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Inner product proof verification");
+    group.sample_size(10);
+
+    for &n in &TEST_SIZES {
+        let mut rng = ChaChaRng::from_seed([n.trailing_zeros() as u8; 32]);
+        let instance = Instance::new(n, &mut rng);
+        let proof = instance.prove();
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"InnerProductBenchmark");
+                proof
+                    .verify(
+                        n,
+                        &mut transcript,
+                        &instance.G_factors,
+                        &instance.H_factors,
+                        &instance.P,
+                        &instance.Q,
+                        &instance.G,
+                        &instance.H,
+                    )
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+/// The length of the public weights table of the public-b benchmark.
+const PUBLIC_B_SIZE: usize = 1 << 16;
+
+fn verify_ipp_public_b(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Inner product proof verification, public b");
+    group.sample_size(10);
+
+    let n = PUBLIC_B_SIZE;
+    let mut rng = ChaChaRng::from_seed([n.trailing_zeros() as u8; 32]);
+    let G: Vec<RistrettoPoint> = (0..n).map(|_| RistrettoPoint::random(&mut rng)).collect();
+    let Q = RistrettoPoint::random(&mut rng);
+    let a: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let weights: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+    let P = RistrettoPoint::vartime_multiscalar_mul(&a, &G) + inner_product(&a, &weights) * Q;
+
+    for (name, mode) in [
+        ("direct", PublicVectorMode::Direct),
+        ("hashed", PublicVectorMode::Hashed),
+    ] {
+        let proof = InnerProductProof::create_with_public_b(
+            &mut Transcript::new(b"PublicBBenchmark"),
+            &Q,
+            G.clone(),
+            a.clone(),
+            &weights,
+            mode,
+        );
+
+        group.bench_with_input(BenchmarkId::new(name, n), &n, |b, _| {
+            b.iter(|| {
+                let mut transcript = Transcript::new(b"PublicBBenchmark");
+                proof
+                    .verify_with_public_b(&mut transcript, &P, &Q, &G, &weights, mode)
+                    .unwrap()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group! {
+    name = ipp;
+    config = Criterion::default();
+    targets =
+    create_ipp,
+    verify_ipp,
+    verify_ipp_public_b,
+}
+
+criterion_main!(ipp);
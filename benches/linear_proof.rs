@@ -2,12 +2,11 @@
 
 #[macro_use]
 extern crate criterion;
-use criterion::Criterion;
+use criterion::{BenchmarkId, Criterion};
 
 extern crate bulletproofs;
 extern crate curve25519_dalek;
 extern crate merlin;
-extern crate rand;
 
 use core::iter;
 
@@ -17,42 +16,45 @@ use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::VartimeMultiscalarMul;
 use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
 
 /// Different linear proof vector lengths to try
 static TEST_SIZES: [usize; 5] = [64, 128, 256, 512, 1024];
 
 fn create_linear_proof_helper(c: &mut Criterion) {
-    c.bench_function_over_inputs(
-        "linear proof creation",
-        move |bench, n| {
-            let mut rng = rand::thread_rng();
-
-            let bp_gens = BulletproofGens::new(*n, 1);
-            // Calls `.G()` on generators, which should be a pub(crate) function only.
-            // For now, make that function public so it can be accessed from benches.
-            // We don't want to use bp_gens directly because we don't need the H generators.
-            let G: Vec<RistrettoPoint> = bp_gens.share(0).G(*n).cloned().collect();
-
-            let pedersen_gens = PedersenGens::default();
-            let F = pedersen_gens.B;
-            let B = pedersen_gens.B_blinding;
-
-            // a and b are the vectors for which we want to prove c = <a,b>
-            let a: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
-            let b: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
-
-            let mut transcript = Transcript::new(b"LinearProofBenchmark");
-
-            // C = <a, G> + r * B + <a, b> * F
-            let r = Scalar::random(&mut rng);
-            let c = inner_product(&a, &b);
-            let C = RistrettoPoint::vartime_multiscalar_mul(
-                a.iter().chain(iter::once(&r)).chain(iter::once(&c)),
-                G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
-            )
-            .compress();
-
-            // Make linear proof
+    let mut group = c.benchmark_group("linear proof creation");
+
+    for n in &TEST_SIZES {
+        let mut rng = ChaChaRng::from_seed([n.trailing_zeros() as u8; 32]);
+
+        let bp_gens = BulletproofGens::new(*n, 1);
+        // Calls `.G()` on generators, which should be a pub(crate) function only.
+        // For now, make that function public so it can be accessed from benches.
+        // We don't want to use bp_gens directly because we don't need the H generators.
+        let G: Vec<RistrettoPoint> = bp_gens.try_share(0).unwrap().G(*n).cloned().collect();
+
+        let pedersen_gens = PedersenGens::default();
+        let F = pedersen_gens.B;
+        let B = pedersen_gens.B_blinding;
+
+        // a and b are the vectors for which we want to prove c = <a,b>
+        let a: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut transcript = Transcript::new(b"LinearProofBenchmark");
+
+        // C = <a, G> + r * B + <a, b> * F
+        let r = Scalar::random(&mut rng);
+        let c = inner_product(&a, &b);
+        let C = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter().chain(iter::once(&r)).chain(iter::once(&c)),
+            G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
+        )
+        .compress();
+
+        // Make linear proof
+        group.bench_with_input(BenchmarkId::from_parameter(n), n, |bench, _| {
             bench.iter(|| {
                 LinearProof::create(
                     &mut transcript,
@@ -67,9 +69,10 @@ fn create_linear_proof_helper(c: &mut Criterion) {
                 )
                 .unwrap();
             })
-        },
-        TEST_SIZES,
-    );
+        });
+    }
+
+    group.finish();
 }
 
 /// Copied from src/inner_product_proof.rs
@@ -99,65 +102,67 @@ criterion_group! {
 }
 
 fn linear_verify(c: &mut Criterion) {
-    c.bench_function_over_inputs(
-        "linear proof verification",
-        move |bench, n| {
-            let bp_gens = BulletproofGens::new(*n, 1);
-            let mut rng = rand::thread_rng();
-
-            // Calls `.G()` on generators, which should be a pub(crate) function only.
-            // For now, make that function public so it can be accessed from benches.
-            // We can't simply use bp_gens directly because we don't need the H generators.
-            let G: Vec<RistrettoPoint> = bp_gens.share(0).G(*n).cloned().collect();
-            let pedersen_gens = PedersenGens::default();
-            let F = pedersen_gens.B;
-            let B = pedersen_gens.B_blinding;
-
-            let b: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
-
-            // Generate the proof in its own scope to prevent reuse of
-            // prover variables by the verifier
-            let (proof, C) = {
-                // a and b are the vectors for which we want to prove c = <a,b>
-                let a: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
-
-                let mut transcript = Transcript::new(b"LinearProofBenchmark");
-
-                // C = <a, G> + r * B + <a, b> * F
-                let r = Scalar::random(&mut rng);
-                let c = inner_product(&a, &b);
-                let C = RistrettoPoint::vartime_multiscalar_mul(
-                    a.iter().chain(iter::once(&r)).chain(iter::once(&c)),
-                    G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
-                )
-                .compress();
+    let mut group = c.benchmark_group("linear proof verification");
 
-                let proof = LinearProof::create(
-                    &mut transcript,
-                    &mut rng,
-                    &C,
-                    r,
-                    a.clone(),
-                    b.clone(),
-                    G.clone(),
-                    &F,
-                    &B,
-                )
-                .unwrap();
+    for n in &TEST_SIZES {
+        let bp_gens = BulletproofGens::new(*n, 1);
+        let mut rng = ChaChaRng::from_seed([n.trailing_zeros() as u8; 32]);
+
+        // Calls `.G()` on generators, which should be a pub(crate) function only.
+        // For now, make that function public so it can be accessed from benches.
+        // We can't simply use bp_gens directly because we don't need the H generators.
+        let G: Vec<RistrettoPoint> = bp_gens.try_share(0).unwrap().G(*n).cloned().collect();
+        let pedersen_gens = PedersenGens::default();
+        let F = pedersen_gens.B;
+        let B = pedersen_gens.B_blinding;
+
+        let b: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+
+        // Generate the proof in its own scope to prevent reuse of
+        // prover variables by the verifier
+        let (proof, C) = {
+            // a and b are the vectors for which we want to prove c = <a,b>
+            let a: Vec<_> = (0..*n).map(|_| Scalar::random(&mut rng)).collect();
+
+            let mut transcript = Transcript::new(b"LinearProofBenchmark");
+
+            // C = <a, G> + r * B + <a, b> * F
+            let r = Scalar::random(&mut rng);
+            let c = inner_product(&a, &b);
+            let C = RistrettoPoint::vartime_multiscalar_mul(
+                a.iter().chain(iter::once(&r)).chain(iter::once(&c)),
+                G.iter().chain(iter::once(&B)).chain(iter::once(&F)),
+            )
+            .compress();
+
+            let proof = LinearProof::create(
+                &mut transcript,
+                &mut rng,
+                &C,
+                r,
+                a.clone(),
+                b.clone(),
+                G.clone(),
+                &F,
+                &B,
+            )
+            .unwrap();
 
-                (proof, C)
-            };
+            (proof, C)
+        };
 
-            // Verify linear proof
+        // Verify linear proof
+        group.bench_with_input(BenchmarkId::from_parameter(n), n, |bench, _| {
             bench.iter(|| {
                 let mut verifier_transcript = Transcript::new(b"LinearProofBenchmark");
                 proof
                     .verify(&mut verifier_transcript, &C, &G, &F, &B, b.clone())
                     .unwrap();
-            });
-        },
-        TEST_SIZES,
-    );
+            })
+        });
+    }
+
+    group.finish();
 }
 
 criterion_group! {
@@ -2,7 +2,7 @@
 
 #[macro_use]
 extern crate criterion;
-use criterion::Criterion;
+use criterion::{BenchmarkId, Criterion};
 
 // Code below copied from ../tests/r1cs.rs
 //
@@ -25,6 +25,8 @@ use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 use rand::seq::SliceRandom;
 use rand::Rng;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
 
 // Shuffle gadget (documented in markdown file)
 
@@ -78,10 +80,10 @@ impl ShuffleProof {
     /// Attempt to construct a proof that `output` is a permutation of `input`.
     ///
     /// Returns a tuple `(proof, input_commitments || output_commitments)`.
-    pub fn prove<'a, 'b>(
+    pub fn prove<'b>(
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
-        transcript: &'a mut Transcript,
+        transcript: &mut Transcript,
         input: &[Scalar],
         output: &[Scalar],
     ) -> Result<
@@ -98,25 +100,25 @@ impl ShuffleProof {
         transcript.append_message(b"dom-sep", b"ShuffleProof");
         transcript.append_u64(b"k", k as u64);
 
-        let mut prover = Prover::new(&pc_gens, transcript);
+        let mut prover = Prover::new(pc_gens, transcript);
 
         // Construct blinding factors using an RNG.
         // Note: a non-example implementation would want to operate on existing commitments.
         let mut blinding_rng = rand::thread_rng();
 
         let (input_commitments, input_vars): (Vec<_>, Vec<_>) = input
-            .into_iter()
+            .iter()
             .map(|v| prover.commit(*v, Scalar::random(&mut blinding_rng)))
             .unzip();
 
         let (output_commitments, output_vars): (Vec<_>, Vec<_>) = output
-            .into_iter()
+            .iter()
             .map(|v| prover.commit(*v, Scalar::random(&mut blinding_rng)))
             .unzip();
 
         ShuffleProof::gadget(&mut prover, input_vars, output_vars)?;
 
-        let proof = prover.prove(&bp_gens)?;
+        let proof = prover.prove(bp_gens)?;
 
         Ok((ShuffleProof(proof), input_commitments, output_commitments))
     }
@@ -124,13 +126,13 @@ impl ShuffleProof {
 
 impl ShuffleProof {
     /// Attempt to verify a `ShuffleProof`.
-    pub fn verify<'a, 'b>(
+    pub fn verify<'b>(
         &self,
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
-        transcript: &'a mut Transcript,
-        input_commitments: &Vec<CompressedRistretto>,
-        output_commitments: &Vec<CompressedRistretto>,
+        transcript: &mut Transcript,
+        input_commitments: &[CompressedRistretto],
+        output_commitments: &[CompressedRistretto],
     ) -> Result<(), R1CSError> {
         // Apply a domain separator with the shuffle parameters to the transcript
         // XXX should this be part of the gadget?
@@ -152,7 +154,7 @@ impl ShuffleProof {
 
         ShuffleProof::gadget(&mut verifier, input_vars, output_vars)?;
 
-        verifier.verify(&self.0, &pc_gens, &bp_gens)
+        verifier.verify(&self.0, pc_gens, bp_gens)
     }
 }
 
@@ -163,34 +165,41 @@ const LG_MAX_SHUFFLE_SIZE: usize = 10;
 /// Maximum shuffle size to benchmark.
 const MAX_SHUFFLE_SIZE: usize = 1 << LG_MAX_SHUFFLE_SIZE;
 
+/// Generates a `k`-element input and a shuffled copy of it, from an RNG
+/// seeded by `k` so that every run benchmarks the same shuffle.
+fn shuffle_inputs(k: usize) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut rng = ChaChaRng::from_seed([k.trailing_zeros() as u8; 32]);
+    let (min, max) = (0u64, u64::MAX);
+    let input: Vec<Scalar> = (0..k)
+        .map(|_| Scalar::from(rng.gen_range(min..max)))
+        .collect();
+    let mut output = input.clone();
+    output.shuffle(&mut rng);
+    (input, output)
+}
+
 fn bench_kshuffle_prove(c: &mut Criterion) {
     // Construct Bulletproof generators externally
     let pc_gens = PedersenGens::default();
     let bp_gens = BulletproofGens::new(2 * MAX_SHUFFLE_SIZE, 1);
 
-    c.bench_function_over_inputs(
-        "k-shuffle proof creation",
-        move |b, k| {
-            // Generate inputs and outputs to kshuffle
-            let mut rng = rand::thread_rng();
-            let (min, max) = (0u64, std::u64::MAX);
-            let input: Vec<Scalar> = (0..*k)
-                .map(|_| Scalar::from(rng.gen_range(min..max)))
-                .collect();
-            let mut output = input.clone();
-            output.shuffle(&mut rand::thread_rng());
-
-            // Make kshuffle proof
+    let mut group = c.benchmark_group("k-shuffle proof creation");
+
+    for k in (1..=LG_MAX_SHUFFLE_SIZE).map(|i| 1 << i) {
+        // Generate inputs and outputs to kshuffle
+        let (input, output) = shuffle_inputs(k);
+
+        // Make kshuffle proof
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, _| {
             b.iter(|| {
                 let mut prover_transcript = Transcript::new(b"ShuffleBenchmark");
                 ShuffleProof::prove(&pc_gens, &bp_gens, &mut prover_transcript, &input, &output)
                     .unwrap();
             })
-        },
-        (1..=LG_MAX_SHUFFLE_SIZE)
-            .map(|i| 1 << i)
-            .collect::<Vec<_>>(),
-    );
+        });
+    }
+
+    group.finish();
 }
 
 criterion_group! {
@@ -207,28 +216,23 @@ fn bench_kshuffle_verify(c: &mut Criterion) {
     let pc_gens = PedersenGens::default();
     let bp_gens = BulletproofGens::new(2 * MAX_SHUFFLE_SIZE, 1);
 
-    c.bench_function_over_inputs(
-        "k-shuffle proof verification",
-        move |b, k| {
-            // Generate the proof in its own scope to prevent reuse of
-            // prover variables by the verifier
-            let (proof, input_commitments, output_commitments) = {
-                // Generate inputs and outputs to kshuffle
-                let mut rng = rand::thread_rng();
-                let (min, max) = (0u64, std::u64::MAX);
-                let input: Vec<Scalar> = (0..*k)
-                    .map(|_| Scalar::from(rng.gen_range(min..max)))
-                    .collect();
-                let mut output = input.clone();
-                output.shuffle(&mut rand::thread_rng());
+    let mut group = c.benchmark_group("k-shuffle proof verification");
 
-                let mut prover_transcript = Transcript::new(b"ShuffleBenchmark");
+    for k in (1..=LG_MAX_SHUFFLE_SIZE).map(|i| 1 << i) {
+        // Generate the proof in its own scope to prevent reuse of
+        // prover variables by the verifier
+        let (proof, input_commitments, output_commitments) = {
+            // Generate inputs and outputs to kshuffle
+            let (input, output) = shuffle_inputs(k);
 
-                ShuffleProof::prove(&pc_gens, &bp_gens, &mut prover_transcript, &input, &output)
-                    .unwrap()
-            };
+            let mut prover_transcript = Transcript::new(b"ShuffleBenchmark");
 
-            // Verify kshuffle proof
+            ShuffleProof::prove(&pc_gens, &bp_gens, &mut prover_transcript, &input, &output)
+                .unwrap()
+        };
+
+        // Verify kshuffle proof
+        group.bench_with_input(BenchmarkId::from_parameter(k), &k, |b, _| {
             b.iter(|| {
                 let mut verifier_transcript = Transcript::new(b"ShuffleBenchmark");
                 proof
@@ -241,11 +245,10 @@ fn bench_kshuffle_verify(c: &mut Criterion) {
                     )
                     .unwrap();
             })
-        },
-        (1..=LG_MAX_SHUFFLE_SIZE)
-            .map(|i| 1 << i)
-            .collect::<Vec<_>>(),
-    );
+        });
+    }
+
+    group.finish();
 }
 
 criterion_group! {
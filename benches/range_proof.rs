@@ -1,138 +1,468 @@
 #![allow(non_snake_case)]
 #[macro_use]
 extern crate criterion;
-use criterion::Criterion;
+use criterion::{BatchSize, BenchmarkId, Criterion};
+
+use std::convert::TryFrom;
 
-use rand;
 use rand::Rng;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
 
+use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 
 use merlin::Transcript;
 
-use bulletproofs::RangeProof;
-use bulletproofs::{BulletproofGens, PedersenGens};
+use bulletproofs::{BatchVerifier, VerificationOptions};
+use bulletproofs::{BitSize, BulletproofGens, PedersenGens};
+use bulletproofs::{RangeProof, RangeProverPrecomp};
 
-static AGGREGATION_SIZES: [usize; 6] = [1, 2, 4, 8, 16, 32];
+static BIT_SIZES: [usize; 4] = [8, 16, 32, 64];
+static AGGREGATION_SIZES: [usize; 3] = [1, 4, 16];
 
-fn create_aggregated_rangeproof_helper(n: usize, c: &mut Criterion) {
-    let label = format!("Aggregated {}-bit rangeproof creation", n);
+/// Generates `m` random `n`-bit values with blindings from a seeded RNG,
+/// so that every run benchmarks the same statements.
+fn random_values(n: usize, m: usize, rng: &mut ChaChaRng) -> (Vec<u64>, Vec<Scalar>) {
+    let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
+    let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
+    let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(rng)).collect();
+    (values, blindings)
+}
 
-    c.bench_function_over_inputs(
-        &label,
-        move |b, &&m| {
-            let pc_gens = PedersenGens::default();
-            let bp_gens = BulletproofGens::new(n, m);
-            let mut rng = rand::thread_rng();
+fn prove_single_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Single rangeproof creation");
+    group.sample_size(10);
 
-            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
-            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
-            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+    for &n in &BIT_SIZES {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 1);
+        let mut rng = ChaChaRng::from_seed([n as u8; 32]);
+        let (values, blindings) = random_values(n, 1, &mut rng);
 
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
             b.iter(|| {
                 // Each proof creation requires a clean transcript.
-                let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+                let mut transcript = Transcript::new(b"SingleRangeProofBenchmark");
 
-                RangeProof::prove_multiple(
+                RangeProof::prove_single_with_rng(
                     &bp_gens,
                     &pc_gens,
                     &mut transcript,
-                    &values,
-                    &blindings,
-                    n,
+                    values[0],
+                    &blindings[0],
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng,
                 )
             })
-        },
-        &AGGREGATION_SIZES,
-    );
-}
+        });
+    }
 
-fn create_aggregated_rangeproof_n_8(c: &mut Criterion) {
-    create_aggregated_rangeproof_helper(8, c);
+    group.finish();
 }
 
-fn create_aggregated_rangeproof_n_16(c: &mut Criterion) {
-    create_aggregated_rangeproof_helper(16, c);
-}
+fn verify_single_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Single rangeproof verification");
 
-fn create_aggregated_rangeproof_n_32(c: &mut Criterion) {
-    create_aggregated_rangeproof_helper(32, c);
-}
+    for &n in &BIT_SIZES {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, 1);
+        let mut rng = ChaChaRng::from_seed([n as u8; 32]);
+        let (values, blindings) = random_values(n, 1, &mut rng);
+
+        let mut transcript = Transcript::new(b"SingleRangeProofBenchmark");
+        let (proof, value_commitment) = RangeProof::prove_single_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            values[0],
+            &blindings[0],
+            BitSize::try_from(n).unwrap(),
+            &mut rng,
+        )
+        .unwrap();
+
+        group.bench_with_input(BenchmarkId::from_parameter(n), &n, |b, &n| {
+            b.iter(|| {
+                // Each proof verification requires a clean transcript.
+                let mut transcript = Transcript::new(b"SingleRangeProofBenchmark");
 
-fn create_aggregated_rangeproof_n_64(c: &mut Criterion) {
-    create_aggregated_rangeproof_helper(64, c);
+                proof.verify_single_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &value_commitment,
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng,
+                )
+            })
+        });
+    }
+
+    group.finish();
 }
 
-fn verify_aggregated_rangeproof_helper(n: usize, c: &mut Criterion) {
-    let label = format!("Aggregated {}-bit rangeproof verification", n);
+fn prove_aggregated_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Aggregated rangeproof creation");
+    group.sample_size(10);
 
-    c.bench_function_over_inputs(
-        &label,
-        move |b, &&m| {
+    for &n in &BIT_SIZES {
+        for &m in &AGGREGATION_SIZES {
             let pc_gens = PedersenGens::default();
             let bp_gens = BulletproofGens::new(n, m);
-            let mut rng = rand::thread_rng();
+            let mut rng = ChaChaRng::from_seed([(n + m) as u8; 32]);
+            let (values, blindings) = random_values(n, m, &mut rng);
 
-            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
-            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
-            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+            let id = BenchmarkId::new(format!("n={}", n), m);
+            group.bench_with_input(id, &(n, m), |b, &(n, _m)| {
+                b.iter(|| {
+                    // Each proof creation requires a clean transcript.
+                    let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
 
+                    RangeProof::prove_multiple_with_rng(
+                        &bp_gens,
+                        &pc_gens,
+                        &mut transcript,
+                        &values,
+                        &blindings,
+                        BitSize::try_from(n).unwrap(),
+                        &mut rng,
+                    )
+                })
+            });
+        }
+    }
+
+    group.finish();
+}
+
+/// Creation of a proof with \\(n \cdot m = 2^{16}\\), in full and in
+/// its online phase only, after a [`RangeProverPrecomp`] done ahead of
+/// time outside of the measurement.
+fn prove_precomputed_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Precomputed rangeproof creation (n*m = 2^16)");
+    group.sample_size(10);
+    let (n, m) = (64, 1024);
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let mut rng = ChaChaRng::from_seed([0u8; 32]);
+    let (values, blindings) = random_values(n, m, &mut rng);
+
+    group.bench_function(BenchmarkId::new("full", m), |b| {
+        b.iter(|| {
+            // Each proof creation requires a clean transcript.
             let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
-            let (proof, value_commitments) = RangeProof::prove_multiple(
+
+            RangeProof::prove_multiple_with_rng(
                 &bp_gens,
                 &pc_gens,
                 &mut transcript,
                 &values,
                 &blindings,
-                n,
+                BitSize::try_from(n).unwrap(),
+                &mut rng,
             )
-            .unwrap();
+        })
+    });
 
-            b.iter(|| {
+    let mut precomp_rng = ChaChaRng::from_seed([1u8; 32]);
+    group.bench_function(BenchmarkId::new("online", m), |b| {
+        b.iter_batched(
+            || {
+                RangeProverPrecomp::new_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    BitSize::try_from(n).unwrap(),
+                    m,
+                    &mut precomp_rng,
+                )
+                .unwrap()
+            },
+            |precomp| {
                 // Each proof creation requires a clean transcript.
                 let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
 
-                proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+                precomp.finish(&mut transcript, &values, &blindings)
+            },
+            BatchSize::LargeInput,
+        )
+    });
+
+    group.finish();
+}
+
+fn verify_aggregated_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Aggregated rangeproof verification");
+
+    for &n in &BIT_SIZES {
+        for &m in &AGGREGATION_SIZES {
+            let pc_gens = PedersenGens::default();
+            let bp_gens = BulletproofGens::new(n, m);
+            let mut rng = ChaChaRng::from_seed([(n + m) as u8; 32]);
+            let (values, blindings) = random_values(n, m, &mut rng);
+
+            let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+            let (proof, value_commitments): (RangeProof, Vec<CompressedRistretto>) =
+                RangeProof::prove_multiple_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng,
+                )
+                .unwrap();
+
+            let id = BenchmarkId::new(format!("n={}", n), m);
+            group.bench_with_input(id, &(n, m), |b, &(n, _m)| {
+                b.iter(|| {
+                    // Each proof verification requires a clean transcript.
+                    let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+
+                    proof.verify_multiple_with_rng(
+                        &bp_gens,
+                        &pc_gens,
+                        &mut transcript,
+                        &value_commitments,
+                        BitSize::try_from(n).unwrap(),
+                        &mut rng,
+                    )
+                })
             });
-        },
-        &AGGREGATION_SIZES,
-    );
+        }
+    }
+
+    group.finish();
 }
 
-fn verify_aggregated_rangeproof_n_8(c: &mut Criterion) {
-    verify_aggregated_rangeproof_helper(8, c);
+fn verify_constant_time_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Constant-time rangeproof verification");
+
+    for &n in &BIT_SIZES {
+        for &m in &AGGREGATION_SIZES {
+            let pc_gens = PedersenGens::default();
+            let bp_gens = BulletproofGens::new(n, m);
+            let mut rng = ChaChaRng::from_seed([(n + m) as u8; 32]);
+            let (values, blindings) = random_values(n, m, &mut rng);
+
+            let mut transcript = Transcript::new(b"ConstantTimeRangeProofBenchmark");
+            let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::try_from(n).unwrap(),
+                &mut rng,
+            )
+            .unwrap();
+
+            let id = BenchmarkId::new(format!("n={}", n), m);
+            group.bench_with_input(id, &(n, m), |b, &(n, _m)| {
+                b.iter(|| {
+                    // Each proof verification requires a clean transcript.
+                    let mut transcript = Transcript::new(b"ConstantTimeRangeProofBenchmark");
+
+                    proof.verify_multiple_ct_with_rng(
+                        &bp_gens,
+                        &pc_gens,
+                        &mut transcript,
+                        &value_commitments,
+                        BitSize::try_from(n).unwrap(),
+                        &mut rng,
+                    )
+                })
+            });
+        }
+    }
+
+    group.finish();
 }
 
-fn verify_aggregated_rangeproof_n_16(c: &mut Criterion) {
-    verify_aggregated_rangeproof_helper(16, c);
+fn verify_decompressed_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Aggregated rangeproof verification (decompressed)");
+    let n = 64;
+
+    for &m in &AGGREGATION_SIZES {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n, m);
+        let mut rng = ChaChaRng::from_seed([(n + m) as u8; 32]);
+        let (values, blindings) = random_values(n, m, &mut rng);
+
+        let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &values,
+            &blindings,
+            BitSize::try_from(n).unwrap(),
+            &mut rng,
+        )
+        .unwrap();
+        // Decompress once, outside of the measured loop.
+        let proof = proof.decompress().unwrap();
+
+        let id = BenchmarkId::new(format!("n={}", n), m);
+        group.bench_with_input(id, &(n, m), |b, &(n, _m)| {
+            b.iter(|| {
+                // Each proof verification requires a clean transcript.
+                let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+
+                proof.verify_multiple_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &value_commitments,
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng,
+                )
+            })
+        });
+    }
+
+    group.finish();
 }
 
-fn verify_aggregated_rangeproof_n_32(c: &mut Criterion) {
-    verify_aggregated_rangeproof_helper(32, c);
+/// Verification of a proof with \\(n \cdot m = 2^{16}\\), where the
+/// \\(2nm\\) generator terms dominate the multiscalar multiplication,
+/// on one thread and sharded between the available threads.
+fn verify_large_aggregated_rangeproof(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Aggregated rangeproof verification (n*m = 2^16)");
+    group.sample_size(10);
+    let (n, m) = (64, 1024);
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let mut rng = ChaChaRng::from_seed([0u8; 32]);
+    let (values, blindings) = random_values(n, m, &mut rng);
+
+    let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+    let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        &values,
+        &blindings,
+        BitSize::try_from(n).unwrap(),
+        &mut rng,
+    )
+    .unwrap();
+
+    group.bench_function(BenchmarkId::new(format!("n={}", n), m), |b| {
+        b.iter(|| {
+            // Each proof verification requires a clean transcript.
+            let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+
+            proof.verify_multiple_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &value_commitments,
+                BitSize::try_from(n).unwrap(),
+                &mut rng,
+            )
+        })
+    });
+
+    // The same proof with its equation split between the available threads.
+    let num_shards = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let id = BenchmarkId::new(format!("n={}, sharded", n), m);
+    group.bench_function(id, |b| {
+        b.iter(|| {
+            // Each proof verification requires a clean transcript.
+            let mut transcript = Transcript::new(b"AggregateRangeProofBenchmark");
+
+            proof.verify_multiple_sharded_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &value_commitments,
+                BitSize::try_from(n).unwrap(),
+                num_shards,
+                &mut rng,
+            )
+        })
+    });
+
+    group.finish();
 }
 
-fn verify_aggregated_rangeproof_n_64(c: &mut Criterion) {
-    verify_aggregated_rangeproof_helper(64, c);
+/// Verification of a batch of 64 aggregated proofs under each choice
+/// of [`VerificationOptions`].
+fn verify_batched_rangeproofs(c: &mut Criterion) {
+    let mut group = c.benchmark_group("Batched rangeproof verification");
+    group.sample_size(10);
+    let (n, m, count) = (64, 4, 64);
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let mut rng = ChaChaRng::from_seed([0u8; 32]);
+    let proofs: Vec<_> = (0..count)
+        .map(|_| {
+            let (values, blindings) = random_values(n, m, &mut rng);
+            let mut transcript = Transcript::new(b"BatchedRangeProofBenchmark");
+            RangeProof::prove_multiple_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::try_from(n).unwrap(),
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    for &msm_chunk_size in &[None, Some(256), Some(4096)] {
+        for &parallel in &[false, true] {
+            let options = VerificationOptions {
+                msm_chunk_size,
+                parallel,
+                max_memory_bytes: None,
+            };
+            let mut batch = BatchVerifier::with_options(options);
+            for (proof, value_commitments) in &proofs {
+                batch.queue_range(
+                    proof,
+                    value_commitments,
+                    BitSize::try_from(n).unwrap(),
+                    Transcript::new(b"BatchedRangeProofBenchmark"),
+                );
+            }
+
+            let id = BenchmarkId::new(format!("chunk={:?}", msm_chunk_size), parallel);
+            group.bench_function(id, |b| {
+                b.iter(|| batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng))
+            });
+        }
+    }
+
+    group.finish();
 }
 
 criterion_group! {
     name = create_rp;
-    config = Criterion::default().sample_size(10);
+    config = Criterion::default();
     targets =
-    create_aggregated_rangeproof_n_8,
-    create_aggregated_rangeproof_n_16,
-    create_aggregated_rangeproof_n_32,
-    create_aggregated_rangeproof_n_64,
+    prove_single_rangeproof,
+    prove_aggregated_rangeproof,
+    prove_precomputed_rangeproof,
 }
 
 criterion_group! {
     name = verify_rp;
     config = Criterion::default();
     targets =
-    verify_aggregated_rangeproof_n_8,
-    verify_aggregated_rangeproof_n_16,
-    verify_aggregated_rangeproof_n_32,
-    verify_aggregated_rangeproof_n_64,
+    verify_single_rangeproof,
+    verify_aggregated_rangeproof,
+    verify_constant_time_rangeproof,
+    verify_decompressed_rangeproof,
+    verify_large_aggregated_rangeproof,
+    verify_batched_rangeproofs,
 }
 
 criterion_main!(create_rp, verify_rp);
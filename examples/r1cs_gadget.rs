@@ -0,0 +1,135 @@
+//! A constraint system proof that a committed value is a member of a
+//! public set.
+//!
+//! The gadget constrains the product \\(\prod_i (v - a_i)\\) to be zero,
+//! which holds exactly when \\(v\\) equals one of the \\(a_i\\), using
+//! one multiplier per element after the first.  The proof is written
+//! to disk and read back before verification.
+//!
+//! Usage: `cargo run --features yoloproofs --example r1cs_gadget -- [set_size] [seed]`,
+//! where `set_size` defaults to 1024.
+
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand::Rng;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::r1cs::{ConstraintSystem, LinearCombination, R1CSError, R1CSProof, Variable};
+use bulletproofs::{app_transcript, ProofParameters};
+
+/// Constrains the value of `v` to be one of the `set` elements.
+fn set_membership_gadget<CS: ConstraintSystem>(cs: &mut CS, v: Variable, set: &[Scalar]) {
+    let factors = set.iter().map(|&a| LinearCombination::from(v) - a);
+    let product = factors
+        .reduce(|product, factor| {
+            let (_, _, o) = cs.multiply(product, factor);
+            o.into()
+        })
+        .expect("the set is not empty");
+    cs.constrain(product);
+}
+
+/// Returns the transcript for a membership proof in `set`, binding the
+/// set to the proof.
+fn transcript(set: &[Scalar]) -> Transcript {
+    let mut transcript = app_transcript(b"SetMembershipExample", 1);
+    transcript.append_u64(b"k", set.len() as u64);
+    for a in set {
+        transcript.append_message(b"a", a.as_bytes());
+    }
+    transcript
+}
+
+/// Proves that the value of the returned commitment is `set[index]`.
+fn prove(
+    params: &ProofParameters,
+    set: &[Scalar],
+    index: usize,
+    rng: &mut ChaChaRng,
+) -> Result<(R1CSProof, CompressedRistretto), R1CSError> {
+    let mut prover = params.r1cs_prover(transcript(set));
+    let (commitment, v) = prover.commit(set[index], Scalar::random(rng));
+    set_membership_gadget(&mut prover, v, set);
+    let proof = prover.prove_with_rng(params.bp_gens(), rng)?;
+    Ok((proof, commitment))
+}
+
+/// Verifies that the value of `commitment` is in `set`.
+fn verify(
+    params: &ProofParameters,
+    set: &[Scalar],
+    proof: &R1CSProof,
+    commitment: CompressedRistretto,
+    rng: &mut ChaChaRng,
+) -> Result<(), R1CSError> {
+    let mut verifier = bulletproofs::r1cs::Verifier::new(transcript(set));
+    let v = verifier.commit(commitment);
+    set_membership_gadget(&mut verifier, v, set);
+    verifier.verify_with_rng(proof, params.pc_gens(), params.bp_gens(), rng)
+}
+
+/// Proves, stores, loads and verifies membership in a set of
+/// `set_size` elements.
+fn run(set_size: usize, seed: u64) -> Result<(), R1CSError> {
+    let mut rng = ChaChaRng::seed_from_u64(seed);
+    let set: Vec<Scalar> = (0..set_size as u64)
+        .map(|i| Scalar::from(1000 + 7 * i))
+        .collect();
+    let params = ProofParameters::for_r1cs(set_size.saturating_sub(1).max(1))?;
+
+    let start = Instant::now();
+    let index = rng.gen_range(0..set_size);
+    let (proof, commitment) = prove(&params, &set, index, &mut rng)?;
+    println!(
+        "proved membership in a set of {} in {:?}",
+        set_size,
+        start.elapsed()
+    );
+
+    let path = env::temp_dir().join(format!("r1cs_gadget-{}.bin", seed));
+    fs::write(&path, proof.to_bytes()).expect("cannot write proof");
+    let bytes = fs::read(&path).expect("cannot read proof");
+    let _ = fs::remove_file(&path);
+    println!("stored proof of {} bytes", bytes.len());
+    let proof = R1CSProof::from_bytes(&bytes)?;
+
+    let start = Instant::now();
+    verify(&params, &set, &proof, commitment, &mut rng)?;
+    println!("verified membership in {:?}", start.elapsed());
+
+    // The proof does not verify for a set without the committed value.
+    let mut other_set = set.clone();
+    other_set[index] = Scalar::ZERO;
+    assert!(verify(&params, &other_set, &proof, commitment, &mut rng).is_err());
+
+    Ok(())
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let set_size = args.next().map_or(1024, |arg| {
+        arg.parse().expect("set_size must be an integer")
+    });
+    let seed = args
+        .next()
+        .map_or(0, |arg| arg.parse().expect("seed must be an integer"));
+    assert!(set_size > 0, "set_size must be positive");
+
+    if let Err(e) = run(set_size, seed) {
+        eprintln!("error: {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+#[test]
+fn set_membership() {
+    for &set_size in &[1, 2, 33] {
+        run(set_size, 1).unwrap();
+    }
+}
@@ -0,0 +1,167 @@
+//! A confidential transaction with two inputs and two outputs.
+//!
+//! The amounts are hidden in Pedersen commitments.  The sender proves
+//! that every output amount is in range with one aggregated rangeproof
+//! in a [`ProofEnvelope`], and that the transaction balances by
+//! revealing only the difference of the blinding factors.  The
+//! envelope is written to disk and read back before verification, as
+//! a ledger would.
+//!
+//! Usage: `cargo run --example range_confidential_tx -- [bits] [seed]`,
+//! where `bits` is the bitsize of the amounts (8, 16, 32 or 64,
+//! default 32).
+
+use std::convert::TryFrom;
+use std::env;
+use std::fs;
+use std::time::Instant;
+
+use curve25519_dalek::scalar::Scalar;
+use rand::Rng;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::{BitSize, Commitment, ProofEnvelope, ProofError, ProofParameters, RangeProof};
+
+/// The context label binding the proof to this transaction.
+const CONTEXT: &[u8] = b"example confidential tx #1";
+
+/// A transaction spending `inputs` into `outputs`, as seen by the ledger.
+struct Transaction {
+    inputs: Vec<Commitment>,
+    envelope: ProofEnvelope<RangeProof>,
+    /// The sum of the input blinding factors minus the sum of the
+    /// output blinding factors.
+    excess_blinding: Scalar,
+}
+
+/// Creates a transaction spending two inputs of `bits`-bit amounts
+/// into two outputs.
+fn create_transaction(
+    params: &ProofParameters,
+    bits: BitSize,
+    rng: &mut ChaChaRng,
+) -> Result<Transaction, ProofError> {
+    // Keep the amounts small enough that their sum fits in `bits` bits.
+    let max = ((1u128 << (bits.bits() - 1)) - 1) as u64;
+    let input_values = [rng.gen_range(0..max), rng.gen_range(0..max)];
+    let input_blindings = [Scalar::random(rng), Scalar::random(rng)];
+
+    let total = input_values[0] + input_values[1];
+    let payment = rng.gen_range(0..=total);
+    let output_values = [payment, total - payment];
+    let output_blindings = [Scalar::random(rng), Scalar::random(rng)];
+
+    let inputs = input_values
+        .iter()
+        .zip(input_blindings.iter())
+        .map(|(&v, &r)| Commitment::new(params.pc_gens(), Scalar::from(v), r))
+        .collect();
+
+    let envelope = ProofEnvelope::prove_range_with_rng(
+        params,
+        CONTEXT,
+        &output_values,
+        &output_blindings,
+        bits,
+        rng,
+    )?;
+
+    let excess_blinding =
+        input_blindings.iter().sum::<Scalar>() - output_blindings.iter().sum::<Scalar>();
+
+    Ok(Transaction {
+        inputs,
+        envelope,
+        excess_blinding,
+    })
+}
+
+/// Checks that the outputs of `tx` are in range and that the inputs
+/// and outputs commit to the same total amount.
+fn verify_transaction(
+    params: &ProofParameters,
+    tx: &Transaction,
+    rng: &mut ChaChaRng,
+) -> Result<(), ProofError> {
+    if tx.envelope.context() != CONTEXT {
+        return Err(ProofError::VerificationError);
+    }
+    tx.envelope.verify_with_rng(params, rng)?;
+
+    // sum(inputs) - sum(outputs) must commit to zero with the excess
+    // blinding factor, which reveals nothing about the amounts.
+    let mut balance = Commitment::default();
+    for input in &tx.inputs {
+        balance = balance.try_add(input)?;
+    }
+    for &output in tx.envelope.commitments() {
+        balance = balance.try_sub(&Commitment::from(output))?;
+    }
+    if balance != Commitment::new(params.pc_gens(), Scalar::ZERO, tx.excess_blinding) {
+        return Err(ProofError::VerificationError);
+    }
+    Ok(())
+}
+
+/// Creates, stores, loads and verifies a transaction.
+fn run(bits: usize, seed: u64) -> Result<(), ProofError> {
+    let params = ProofParameters::for_range_proofs(bits, 2)?;
+    let bits = BitSize::try_from(bits)?;
+    let mut rng = ChaChaRng::seed_from_u64(seed);
+
+    let start = Instant::now();
+    let tx = create_transaction(&params, bits, &mut rng)?;
+    println!(
+        "created {}-bit transaction in {:?}",
+        bits.bits(),
+        start.elapsed()
+    );
+
+    // Store the envelope as the ledger would, and read it back.
+    let path = env::temp_dir().join(format!("range_confidential_tx-{}.bin", seed));
+    fs::write(&path, tx.envelope.to_bytes()).expect("cannot write envelope");
+    let bytes = fs::read(&path).expect("cannot read envelope");
+    let _ = fs::remove_file(&path);
+    println!("stored envelope of {} bytes", bytes.len());
+
+    let stored = Transaction {
+        inputs: tx.inputs,
+        envelope: ProofEnvelope::from_bytes(&bytes)?,
+        excess_blinding: tx.excess_blinding,
+    };
+
+    let start = Instant::now();
+    verify_transaction(&params, &stored, &mut rng)?;
+    println!("verified transaction in {:?}", start.elapsed());
+
+    // Claiming a larger input amount breaks the balance.
+    let one = Commitment::new(params.pc_gens(), Scalar::ONE, Scalar::ZERO);
+    let mut inflated = stored;
+    inflated.inputs[0] = inflated.inputs[0].try_add(&one)?;
+    assert!(verify_transaction(&params, &inflated, &mut rng).is_err());
+
+    Ok(())
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let bits = args
+        .next()
+        .map_or(32, |arg| arg.parse().expect("bits must be an integer"));
+    let seed = args
+        .next()
+        .map_or(0, |arg| arg.parse().expect("seed must be an integer"));
+
+    if let Err(e) = run(bits, seed) {
+        eprintln!("error: {:?}", e);
+        std::process::exit(1);
+    }
+}
+
+#[test]
+fn confidential_tx() {
+    for &bits in &[8, 64] {
+        run(bits, 1).unwrap();
+    }
+}
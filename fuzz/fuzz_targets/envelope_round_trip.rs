@@ -0,0 +1,9 @@
+#![no_main]
+
+use arbitrary::Unstructured;
+use bulletproofs::fuzzing;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = fuzzing::check_envelope_round_trip(&mut Unstructured::new(data));
+});
@@ -0,0 +1,342 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::traits::{Identity, IsIdentity, VartimeMultiscalarMul};
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "prover")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::consts::{BALANCE_PROOF_ELEMENTS, ELEMENT_BYTES};
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::PedersenGens;
+#[cfg(feature = "verifier")]
+use crate::transcript::TranscriptProtocol;
+#[cfg(all(feature = "prover", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+/// Returns the sum of the blinding factors.
+pub fn blinding_sum(blindings: &[Scalar]) -> Scalar {
+    blindings.iter().sum()
+}
+
+/// A proof that a set of input commitments and a set of output
+/// commitments commit to the same total value.
+///
+/// The excess \\(E = \sum\_i C^{\text{in}}\_i - \sum\_j C^{\text{out}}\_j\\)
+/// commits to zero exactly when the values balance, in which case
+/// \\(E = r \cdot \tilde{B}\\) for the excess blinding factor
+/// \\(r = \sum\_i r^{\text{in}}\_i - \sum\_j r^{\text{out}}\_j\\).  The
+/// proof is a Schnorr signature on \\(E\\): the prover commits to a
+/// random \\(k\\) with \\(R = k \cdot \tilde{B}\\), receives a challenge
+/// \\(c\\), and responds with \\(s = k + c r\\).  The verifier checks
+/// \\(s \cdot \tilde{B} = R + c \cdot E\\).
+///
+/// Unlike revealing \\(r\\), the proof can be bound to the rest of a
+/// transaction through the transcript.
+#[derive(Clone, Debug)]
+pub struct BalanceProof {
+    /// Commitment to the mask
+    R: CompressedRistretto,
+    /// Response for the excess blinding factor
+    s: Scalar,
+}
+
+impl BalanceProof {
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the commitments with the
+    /// `input_blindings` and the commitments with the
+    /// `output_blindings` commit to the same total value.
+    ///
+    /// The prover does not check that the values balance: if they do
+    /// not, the proof does not verify.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_blindings: &[Scalar],
+        output_blindings: &[Scalar],
+        rng: &mut T,
+    ) -> BalanceProof {
+        let r = blinding_sum(input_blindings) - blinding_sum(output_blindings);
+        let E = (r * pc_gens.B_blinding).compress();
+
+        transcript.balance_proof_domain_sep(input_blindings.len(), output_blindings.len());
+        pc_gens.append_fingerprint(transcript);
+        transcript.append_point(b"E", &E);
+
+        let k = Scalar::random(rng);
+        let R = (k * pc_gens.B_blinding).compress();
+        transcript.append_point(b"R", &R);
+        let c = transcript.challenge_scalar(b"c");
+
+        BalanceProof { R, s: k + c * r }
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the commitments with the
+    /// `input_blindings` and the commitments with the
+    /// `output_blindings` commit to the same total value.
+    /// This is a convenience wrapper around [`BalanceProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        input_blindings: &[Scalar],
+        output_blindings: &[Scalar],
+    ) -> BalanceProof {
+        BalanceProof::prove_with_rng(
+            pc_gens,
+            transcript,
+            input_blindings,
+            output_blindings,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that the `inputs` and the `outputs` commit to the same
+    /// total value.
+    ///
+    /// Returns [`ProofError::FormatError`] if any commitment is not a
+    /// valid point.  The transcript is left unchanged if verification
+    /// fails.
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        inputs: &[CompressedRistretto],
+        outputs: &[CompressedRistretto],
+    ) -> Result<(), ProofError> {
+        let mut E = RistrettoPoint::identity();
+        for C in inputs {
+            E += C.decompress().ok_or(ProofError::FormatError)?;
+        }
+        for C in outputs {
+            E -= C.decompress().ok_or(ProofError::FormatError)?;
+        }
+
+        let mut working_transcript = transcript.clone();
+        working_transcript.balance_proof_domain_sep(inputs.len(), outputs.len());
+        pc_gens.append_fingerprint(&mut working_transcript);
+        working_transcript.append_point(b"E", &E.compress());
+        working_transcript.validate_and_append_point(b"R", &self.R)?;
+        let c = working_transcript.challenge_scalar(b"c");
+
+        // s * B_blinding - c * E - R == 0
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            [self.s, -c, -Scalar::ONE],
+            [Some(pc_gens.B_blinding), Some(E), self.R.decompress()],
+        )
+        .ok_or(ProofError::VerificationError)?;
+
+        if check.is_identity() {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Serializes the proof into a byte array of 64 bytes: the
+    /// compressed point \\(R\\) followed by the scalar \\(s\\).
+    pub fn to_bytes(&self) -> [u8; BALANCE_PROOF_ELEMENTS * ELEMENT_BYTES] {
+        let mut buf = [0u8; BALANCE_PROOF_ELEMENTS * ELEMENT_BYTES];
+        buf[..32].copy_from_slice(self.R.as_bytes());
+        buf[32..].copy_from_slice(self.s.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice is not 64 bytes
+    /// long or \\(s\\) is not a canonical scalar.
+    pub fn from_bytes(slice: &[u8]) -> Result<BalanceProof, ProofError> {
+        if slice.len() != BALANCE_PROOF_ELEMENTS * ELEMENT_BYTES {
+            return Err(ProofError::FormatError);
+        }
+
+        use crate::util::read32;
+
+        let R = CompressedRistretto(read32(&slice[..32]));
+        let s = Option::from(Scalar::from_canonical_bytes(read32(&slice[32..])))
+            .ok_or(ProofError::FormatError)?;
+        Ok(BalanceProof { R, s })
+    }
+}
+
+impl Serialize for BalanceProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for BalanceProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BalanceProofVisitor;
+
+        impl<'de> Visitor<'de> for BalanceProofVisitor {
+            type Value = BalanceProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid BalanceProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<BalanceProof, E>
+            where
+                E: serde::de::Error,
+            {
+                BalanceProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(BalanceProofVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::util;
+
+    /// Commits to `values` with random blindings.
+    fn commit(pc_gens: &PedersenGens, values: &[u64]) -> (Vec<CompressedRistretto>, Vec<Scalar>) {
+        let mut rng = rand::thread_rng();
+        values
+            .iter()
+            .map(|&v| {
+                let blinding = Scalar::random(&mut rng);
+                (
+                    pc_gens.commit(Scalar::from(v), blinding).compress(),
+                    blinding,
+                )
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn balanced_values_verify() {
+        let pc_gens = PedersenGens::default();
+        let (inputs, input_blindings) = commit(&pc_gens, &[30, 12]);
+        let (outputs, output_blindings) = commit(&pc_gens, &[5, 20, 17]);
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let proof = BalanceProof::prove(
+            &pc_gens,
+            &mut transcript,
+            &input_blindings,
+            &output_blindings,
+        );
+        let proof = BalanceProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        assert!(proof
+            .verify(&pc_gens, &mut transcript, &inputs, &outputs)
+            .is_ok());
+
+        // The proof is bound to the number of inputs and outputs.
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let identity = CompressedRistretto::identity();
+        let padded = [outputs.clone(), vec![identity]].concat();
+        assert!(proof
+            .verify(&pc_gens, &mut transcript, &inputs, &padded)
+            .is_err());
+    }
+
+    #[test]
+    fn unbalanced_values_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let (inputs, input_blindings) = commit(&pc_gens, &[30, 12]);
+        let (outputs, output_blindings) = commit(&pc_gens, &[5, 20, 18]);
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let proof = BalanceProof::prove(
+            &pc_gens,
+            &mut transcript,
+            &input_blindings,
+            &output_blindings,
+        );
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let before = transcript.clone();
+        assert_eq!(
+            proof.verify(&pc_gens, &mut transcript, &inputs, &outputs),
+            Err(ProofError::VerificationError)
+        );
+
+        // The transcript is left unchanged.
+        let (mut a, mut b) = (transcript, before);
+        assert_eq!(a.challenge_scalar(b"x"), b.challenge_scalar(b"x"));
+    }
+
+    #[test]
+    fn blinding_sum_matches_commitment_sum() {
+        let pc_gens = PedersenGens::default();
+        let (commitments, blindings) = commit(&pc_gens, &[1, 2, 3]);
+        let sum: RistrettoPoint = commitments.iter().map(|C| C.decompress().unwrap()).sum();
+        assert_eq!(
+            sum,
+            pc_gens.commit(Scalar::from(6u64), blinding_sum(&blindings))
+        );
+    }
+
+    #[test]
+    fn proof_is_bound_to_pedersen_gens() {
+        use sha3::Sha3_512;
+
+        // The proof only involves B_blinding, so only the transcript
+        // tells these generators apart.
+        let default_gens = PedersenGens::default();
+        let custom_gens = PedersenGens {
+            B: RistrettoPoint::hash_from_bytes::<Sha3_512>(b"custom B"),
+            ..default_gens
+        };
+        let (inputs, input_blindings) = commit(&custom_gens, &[30, 12]);
+        let (outputs, output_blindings) = commit(&custom_gens, &[5, 20, 17]);
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let proof = BalanceProof::prove(
+            &custom_gens,
+            &mut transcript,
+            &input_blindings,
+            &output_blindings,
+        );
+
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        assert!(proof
+            .verify(&custom_gens, &mut transcript, &inputs, &outputs)
+            .is_ok());
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        assert_eq!(
+            proof.verify(&default_gens, &mut transcript, &inputs, &outputs),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn noncanonical_scalars_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let mut transcript = Transcript::new(b"BalanceProofTest");
+        let proof = BalanceProof::prove(&pc_gens, &mut transcript, &[Scalar::ONE], &[]);
+        util::assert_noncanonical_scalars_rejected(
+            &proof.to_bytes(),
+            &[32],
+            BalanceProof::from_bytes,
+        );
+        assert!(BalanceProof::from_bytes(&[0u8; 63]).is_err());
+    }
+}
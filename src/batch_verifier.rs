@@ -0,0 +1,523 @@
+//! Verification of many proofs with a single multiscalar
+//! multiplication.
+
+#![allow(non_snake_case)]
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::mem;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use group::Group;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+use crate::generators::{AsGenerators, BulletproofGens, PedersenGens};
+use crate::range_proof::{BitSize, RangeProof};
+use crate::transcript::TranscriptFactory;
+#[cfg(any(feature = "std", feature = "getrandom"))]
+use crate::util;
+
+/// Verifies a batch of queued proofs at once.
+///
+/// The verification equation of each queued proof is weighted by an
+/// independent random scalar, and all of them are summed into a single
+/// multiscalar multiplication.  The terms of the Pedersen generators
+/// and of the Bulletproofs generators are shared between the proofs,
+/// so the generators are only paid for once, for the largest queued
+/// statement.
+///
+/// A batch verifies only if every queued proof verifies; when it does
+/// not, [`BatchVerifier::invalid_entries_with_rng`] finds the
+/// offending proofs.
+///
+/// Entries that repeat another entry, with the same proof, commitments
+/// and bitsize, are verified like any other, each under its own
+/// weight.  The weights are drawn from the verifier's RNG rather than
+/// derived from the transcripts, so queuing a proof several times, or
+/// proofs whose errors would cancel under predictable weights, cannot
+/// make an invalid proof pass.  The batch does not deduplicate entries,
+/// since repeated entries may still differ in their transcripts;
+/// [`BatchVerifier::duplicate_entries`] reports them for callers that
+/// must reject repeated proofs.
+#[derive(Default)]
+pub struct BatchVerifier<'a> {
+    entries: Vec<RangeEntry<'a>>,
+    options: VerificationOptions,
+}
+
+/// Options for evaluating the multiscalar multiplication of a
+/// [`BatchVerifier`].
+///
+/// The options only affect performance: a batch verifies, or not,
+/// under every choice of options.  The default evaluates the whole
+/// multiplication at once on the calling thread, which is the fastest
+/// choice on a single core, since the cost per term of the vartime
+/// multiplication decreases with its size.  Splitting it into chunks
+/// bounds the memory used by the multiplication, which matters on
+/// embedded targets, at some cost in speed.  With `parallel`, chunks
+/// are evaluated on separate threads, which helps large batches on
+/// machines with several cores.  The `range_proof` benchmarks
+/// compare these choices under "Batched rangeproof verification".
+///
+/// A memory budget is the one option that can change the outcome: a
+/// batch whose verification would need more memory than the budget
+/// allows fails with [`ProofError::ResourceLimitExceeded`] before
+/// allocating any of it.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VerificationOptions {
+    /// The largest number of terms to evaluate in one multiplication,
+    /// or `None` to evaluate all terms at once.  Chunks are evaluated
+    /// separately and summed.
+    pub msm_chunk_size: Option<usize>,
+    /// Whether to evaluate the chunks on separate threads.  Without a
+    /// chunk size, the terms are split evenly between the available
+    /// threads.  This requires the `std` feature, and is ignored
+    /// without it.
+    pub parallel: bool,
+    /// The largest number of bytes that verifying a batch may
+    /// allocate at once, or `None` for no limit.
+    ///
+    /// The allocation is estimated up front from the sizes of the
+    /// queued proofs, which are public, and covers the buffers of the
+    /// verification scalars and of the multiscalar multiplication.  If
+    /// the multiplication as configured would exceed the budget, it is
+    /// evaluated in chunks small enough to fit, one after the other,
+    /// and only if no chunk size fits does verification fail.
+    pub max_memory_bytes: Option<usize>,
+}
+
+/// The bytes of a scalar.
+const SCALAR_BYTES: usize = mem::size_of::<Scalar>();
+
+/// The bytes of one term of the multiscalar multiplication in the
+/// buffers of the batch: a scalar and an optional point.
+const TERM_BYTES: usize = SCALAR_BYTES + mem::size_of::<Option<RistrettoPoint>>();
+
+/// The number of terms below which the vartime multiscalar
+/// multiplication uses Straus's algorithm rather than Pippenger's.
+const STRAUS_TERMS: usize = 190;
+
+/// The bytes of a point in the precomputed form used by the
+/// multiscalar multiplication, four field elements of 40 bytes.
+const NIELS_POINT_BYTES: usize = 160;
+
+/// Returns the bytes that a vartime multiscalar multiplication of
+/// `terms` terms allocates: Straus's algorithm keeps eight multiples of
+/// each point and 256 signed digits of each scalar, and Pippenger's
+/// keeps each point and up to 64 digits of each scalar.
+fn msm_bytes(terms: usize) -> usize {
+    if terms < STRAUS_TERMS {
+        terms * (8 * NIELS_POINT_BYTES + 256)
+    } else {
+        terms * (NIELS_POINT_BYTES + 64)
+    }
+}
+
+/// The memory needed to verify a batch, apart from the working memory
+/// of the multiscalar multiplication.
+struct MemoryEstimate {
+    /// The bytes of the buffers of the verification scalars.
+    fixed: usize,
+    /// The number of terms of the multiscalar multiplication.
+    terms: usize,
+}
+
+impl VerificationOptions {
+    /// Returns the options with which to verify a batch needing the
+    /// `estimate`, which are these options unless their multiscalar
+    /// multiplication does not fit the memory budget, in which case it
+    /// is evaluated sequentially in the largest chunks that fit.
+    fn within_budget(&self, estimate: &MemoryEstimate) -> Result<Self, ProofError> {
+        let limit = match self.max_memory_bytes {
+            Some(limit) => limit,
+            None => return Ok(*self),
+        };
+        let terms = estimate.terms;
+        // Parallel chunks are all evaluated at once.
+        let working = if self.parallel && cfg!(feature = "std") {
+            msm_bytes(terms)
+        } else {
+            msm_bytes(self.msm_chunk_size.unwrap_or(terms).clamp(1, terms.max(1)))
+        };
+        if estimate.fixed.saturating_add(working) <= limit {
+            return Ok(*self);
+        }
+
+        let available = limit.saturating_sub(estimate.fixed);
+        // Pippenger's algorithm needs the least memory per term, if a
+        // chunk large enough to use it fits.
+        let pippenger_terms = available / (NIELS_POINT_BYTES + 64);
+        let chunk_size = if pippenger_terms >= STRAUS_TERMS {
+            pippenger_terms
+        } else {
+            (available / msm_bytes(1)).min(STRAUS_TERMS - 1)
+        };
+        if chunk_size == 0 {
+            return Err(ProofError::ResourceLimitExceeded {
+                required: estimate.fixed.saturating_add(msm_bytes(1)),
+                limit,
+            });
+        }
+        trace_event!(chunk_size, "low-memory multiscalar multiplication");
+        Ok(VerificationOptions {
+            msm_chunk_size: Some(chunk_size.min(self.msm_chunk_size.unwrap_or(chunk_size))),
+            parallel: false,
+            max_memory_bytes: self.max_memory_bytes,
+        })
+    }
+}
+
+struct RangeEntry<'a> {
+    proof: &'a RangeProof,
+    value_commitments: &'a [CompressedRistretto],
+    n: BitSize,
+    transcript: Transcript,
+}
+
+impl<'a> BatchVerifier<'a> {
+    /// Creates an empty batch.
+    pub fn new() -> Self {
+        BatchVerifier::default()
+    }
+
+    /// Creates an empty batch that verifies with the given `options`.
+    pub fn with_options(options: VerificationOptions) -> Self {
+        BatchVerifier {
+            entries: Vec::new(),
+            options,
+        }
+    }
+
+    /// Queues a range proof for `n`-bit values with the given value
+    /// commitments, as for [`RangeProof::verify_multiple_with_rng`].
+    ///
+    /// The `transcript` must be in the state expected by verification.
+    /// Unlike single verification, the batch does not hand it back.
+    pub fn queue_range(
+        &mut self,
+        proof: &'a RangeProof,
+        value_commitments: &'a [CompressedRistretto],
+        n: BitSize,
+        transcript: Transcript,
+    ) {
+        self.entries.push(RangeEntry {
+            proof,
+            value_commitments,
+            n,
+            transcript,
+        });
+    }
+
+    /// Queues a range proof like [`BatchVerifier::queue_range`], on the
+    /// transcript that `transcripts` creates for the `item_context` of
+    /// the proof with [`TranscriptFactory::fresh_with_context`].
+    pub fn queue_range_in_context(
+        &mut self,
+        proof: &'a RangeProof,
+        value_commitments: &'a [CompressedRistretto],
+        n: BitSize,
+        transcripts: &TranscriptFactory,
+        item_context: &[u8],
+    ) {
+        self.queue_range(
+            proof,
+            value_commitments,
+            n,
+            transcripts.fresh_with_context(item_context),
+        );
+    }
+
+    /// Returns the positions, in queuing order, of the entries that
+    /// repeat an earlier entry: the same proof, as compared by
+    /// [`RangeProof::fingerprint`], for the same value commitments and
+    /// bitsize.
+    ///
+    /// The transcripts are not compared, so a repeated entry may verify
+    /// where the earlier one does not, or the other way around.
+    pub fn duplicate_entries(&self) -> Vec<usize> {
+        let mut seen: BTreeMap<([u8; 32], BitSize), Vec<usize>> = BTreeMap::new();
+        let mut duplicates = Vec::new();
+        for (i, entry) in self.entries.iter().enumerate() {
+            let earlier = seen
+                .entry((entry.proof.fingerprint(), entry.n))
+                .or_default();
+            if earlier
+                .iter()
+                .any(|&k| self.entries[k].value_commitments == entry.value_commitments)
+            {
+                duplicates.push(i);
+            } else {
+                earlier.push(i);
+            }
+        }
+        duplicates
+    }
+
+    /// Returns the number of queued proofs.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns `true` if no proofs are queued.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Verifies all queued proofs with one multiscalar multiplication.
+    ///
+    /// Returns [`ProofError::VerificationError`] if any proof does not
+    /// verify, or the error of the first queued statement that is
+    /// invalid, such as one requiring more generators than `bp_gens`
+    /// provides.  An empty batch verifies.
+    pub fn verify_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let indices: Vec<usize> = (0..self.entries.len()).collect();
+        self.check(&indices, bp_gens.as_generators(), pc_gens, rng)
+    }
+
+    /// Verifies all queued proofs with one multiscalar multiplication.
+    /// This is a convenience wrapper around [`BatchVerifier::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+    ) -> Result<(), ProofError> {
+        self.verify_with_rng(bp_gens, pc_gens, &mut util::default_rng())
+    }
+
+    /// Returns the positions, in queuing order, of the proofs that do
+    /// not verify.
+    ///
+    /// Each proof is first screened with [`RangeProof::precheck`], then
+    /// the remaining proofs are verified in halves, recursively, so a
+    /// few invalid proofs among many valid ones cost a logarithmic
+    /// number of batch verifications.
+    pub fn invalid_entries_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        rng: &mut T,
+    ) -> Vec<usize> {
+        let bp_gens = bp_gens.as_generators();
+        let (mut invalid, screened): (Vec<usize>, Vec<usize>) =
+            (0..self.entries.len()).partition(|&i| {
+                let entry = &self.entries[i];
+                entry
+                    .proof
+                    .precheck(
+                        bp_gens,
+                        pc_gens,
+                        &entry.transcript,
+                        entry.value_commitments,
+                        entry.n,
+                    )
+                    .is_err()
+            });
+        self.bisect(&screened, bp_gens, pc_gens, rng, &mut invalid);
+        invalid.sort_unstable();
+        invalid
+    }
+
+    /// Returns the positions of the proofs that do not verify.
+    /// This is a convenience wrapper around [`BatchVerifier::invalid_entries_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn invalid_entries<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+    ) -> Vec<usize> {
+        self.invalid_entries_with_rng(bp_gens, pc_gens, &mut util::default_rng())
+    }
+
+    fn bisect<T: RngCore + CryptoRng>(
+        &self,
+        indices: &[usize],
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        rng: &mut T,
+        invalid: &mut Vec<usize>,
+    ) {
+        if indices.is_empty() || self.check(indices, bp_gens, pc_gens, rng).is_ok() {
+            return;
+        }
+        if indices.len() == 1 {
+            invalid.push(indices[0]);
+            return;
+        }
+        let (left, right) = indices.split_at(indices.len() / 2);
+        self.bisect(left, bp_gens, pc_gens, rng, invalid);
+        self.bisect(right, bp_gens, pc_gens, rng, invalid);
+    }
+
+    /// Estimates the memory needed to verify the entries at `indices`
+    /// together, from the lengths of their inner-product proofs and
+    /// their numbers of value commitments.
+    ///
+    /// The buffers of the batch hold the points of each proof and the
+    /// per-party sums of the generator scalars, which are bounded by
+    /// the sum of the lengths \\(nm\\) of the proofs, and verifying one
+    /// proof needs the vectors \\(\mathbf{s}\\) and the scalars of the
+    /// \\(\mathbf{G}\\) and \\(\mathbf{H}\\) generators.
+    fn memory_estimate(&self, indices: &[usize]) -> MemoryEstimate {
+        let mut terms = 2usize;
+        let mut fixed = 0usize;
+        let mut largest = 0;
+        for &i in indices {
+            let entry = &self.entries[i];
+            let nm = entry.proof.bits();
+            let lg_nm = nm.trailing_zeros() as usize;
+            // A, S, T_1, T_2, the L and R points, and the commitments.
+            let proof_terms = 4 + 2 * lg_nm + entry.value_commitments.len();
+            terms = terms.saturating_add(proof_terms + 2 * nm);
+            fixed = fixed.saturating_add(2 * nm * SCALAR_BYTES);
+            largest = largest.max(nm);
+        }
+        MemoryEstimate {
+            fixed: fixed
+                .saturating_add(terms.saturating_mul(TERM_BYTES))
+                .saturating_add(3 * largest * SCALAR_BYTES),
+            terms,
+        }
+    }
+
+    /// Verifies the entries at `indices` together.
+    fn check<T: RngCore + CryptoRng>(
+        &self,
+        indices: &[usize],
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        trace_phase!("batch.verify", proofs = indices.len());
+        let options = self.options.within_budget(&self.memory_estimate(indices))?;
+        let mut B = Scalar::ZERO;
+        let mut B_blinding = Scalar::ZERO;
+        let mut g: Vec<Vec<Scalar>> = Vec::new();
+        let mut h: Vec<Vec<Scalar>> = Vec::new();
+        let mut scalars = Vec::new();
+        let mut points = Vec::new();
+
+        for &i in indices {
+            let entry = &self.entries[i];
+            let proof_points = entry.proof.decompress_points()?;
+            let mut transcript = entry.transcript.clone();
+            let terms = entry.proof.verification_terms(
+                &proof_points,
+                bp_gens,
+                pc_gens,
+                &mut transcript,
+                entry.value_commitments,
+                entry.n.bits(),
+                rng,
+            )?;
+
+            let weight = Scalar::random(rng);
+            B += weight * terms.B;
+            B_blinding += weight * terms.B_blinding;
+            accumulate(&mut g, &terms.g, terms.n, terms.m, weight);
+            accumulate(&mut h, &terms.h, terms.n, terms.m, weight);
+            scalars.extend(terms.scalars.iter().map(|s| weight * s));
+            points.extend(terms.points);
+        }
+
+        scalars.push(B_blinding);
+        points.push(Some(pc_gens.B_blinding));
+        scalars.push(B);
+        points.push(Some(pc_gens.B));
+        for (j, g_j) in g.iter().enumerate() {
+            scalars.extend_from_slice(g_j);
+            points.extend(
+                bp_gens
+                    .try_share(j)?
+                    .G_checked(g_j.len())?
+                    .map(|&G| Some(G)),
+            );
+        }
+        for (j, h_j) in h.iter().enumerate() {
+            scalars.extend_from_slice(h_j);
+            points.extend(
+                bp_gens
+                    .try_share(j)?
+                    .H_checked(h_j.len())?
+                    .map(|&H| Some(H)),
+            );
+        }
+
+        trace_phase!("batch.msm", size = scalars.len());
+        let mega_check =
+            multiscalar_mul(&scalars, &points, &options).ok_or(ProofError::VerificationError)?;
+
+        if mega_check.is_identity().into() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+}
+
+/// Computes the multiscalar multiplication of `scalars` and `points`
+/// as chosen by `options`, or returns `None` if any point is `None`.
+fn multiscalar_mul(
+    scalars: &[Scalar],
+    points: &[Option<RistrettoPoint>],
+    options: &VerificationOptions,
+) -> Option<RistrettoPoint> {
+    #[cfg(feature = "std")]
+    if options.parallel && !scalars.is_empty() {
+        let chunk_size = options.msm_chunk_size.unwrap_or_else(|| {
+            let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+            scalars.len().div_ceil(threads)
+        });
+        return std::thread::scope(|scope| {
+            let chunks: Vec<_> = scalars
+                .chunks(chunk_size.max(1))
+                .zip(points.chunks(chunk_size.max(1)))
+                .map(|(s, P)| {
+                    scope.spawn(move || {
+                        RistrettoPoint::optional_multiscalar_mul(s, P.iter().cloned())
+                    })
+                })
+                .collect();
+            chunks
+                .into_iter()
+                .map(|chunk| chunk.join().expect("multiscalar multiplication panicked"))
+                .sum()
+        });
+    }
+
+    match options.msm_chunk_size {
+        Some(chunk_size) => scalars
+            .chunks(chunk_size.max(1))
+            .zip(points.chunks(chunk_size.max(1)))
+            .map(|(s, P)| RistrettoPoint::optional_multiscalar_mul(s, P.iter().cloned()))
+            .sum(),
+        None => RistrettoPoint::optional_multiscalar_mul(scalars, points.iter().cloned()),
+    }
+}
+
+/// Adds `weight` times the `scalars` of the first `n` generators of
+/// each of the first `m` parties to the per-party sums in `sums`.
+fn accumulate(sums: &mut Vec<Vec<Scalar>>, scalars: &[Scalar], n: usize, m: usize, weight: Scalar) {
+    if sums.len() < m {
+        sums.resize(m, Vec::new());
+    }
+    for (sums_j, scalars_j) in sums.iter_mut().zip(scalars.chunks(n)) {
+        if sums_j.len() < n {
+            sums_j.resize(n, Scalar::ZERO);
+        }
+        for (sum, scalar) in sums_j.iter_mut().zip(scalars_j) {
+            *sum += weight * scalar;
+        }
+    }
+}
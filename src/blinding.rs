@@ -0,0 +1,153 @@
+//! The `blinding` module contains functions for deriving commitment
+//! blinding factors deterministically from a wallet seed.
+//!
+//! A wallet that derives the blinding factor of each output from its
+//! seed, a purpose label, and the output index never needs to store
+//! blinding factors, and cannot reuse one by accident as long as it
+//! does not reuse an index for the same purpose.  The derivation is
+//! fixed: the same inputs give the same blinding factor in every
+//! version of this crate.
+//!
+//! ```
+//! use bulletproofs::{blinding, BitSize, BulletproofGens, PedersenGens, RangeProof};
+//! use merlin::Transcript;
+//!
+//! let seed = [7u8; 32];
+//! let pc_gens = PedersenGens::default();
+//! let bp_gens = BulletproofGens::new(64, 1);
+//!
+//! let mut transcript = Transcript::new(b"doctest example");
+//! let (proof, commitment) = RangeProof::prove_single_with_derived_blinding(
+//!     &bp_gens,
+//!     &pc_gens,
+//!     &mut transcript,
+//!     1037578891,
+//!     || blinding::derive(&seed, b"outputs", 3),
+//!     BitSize::B64,
+//! )?;
+//!
+//! // The wallet can recompute the opening from the seed alone.
+//! let v_blinding = blinding::derive(&seed, b"outputs", 3);
+//! assert!(pc_gens.verify_opening(&commitment, 1037578891, v_blinding));
+//! # Ok::<(), bulletproofs::ProofError>(())
+//! ```
+
+#![deny(missing_docs)]
+
+use alloc::vec::Vec;
+use clear_on_drop::clear::Clear;
+use core::ops::Range;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+/// Returns the transcript for `seed` and `purpose`, before the index is
+/// appended.
+fn seeded_transcript(seed: &[u8; 32], purpose: &'static [u8]) -> Transcript {
+    let mut transcript = Transcript::new(b"Bulletproofs blinding derivation");
+    transcript.append_message(b"dom-sep", b"blinding v1");
+    transcript.append_message(b"seed", seed);
+    transcript.append_message(b"purpose", purpose);
+    transcript
+}
+
+fn derive_from(mut transcript: Transcript, index: u64) -> Scalar {
+    transcript.append_u64(b"index", index);
+    let mut buf = [0u8; 64];
+    transcript.challenge_bytes(b"blinding", &mut buf);
+    let blinding = Scalar::from_bytes_mod_order_wide(&buf);
+    buf.clear();
+    blinding
+}
+
+/// Derives the blinding factor with the given `index` for `purpose`
+/// from the wallet `seed`.
+///
+/// The seed, the purpose, and the index are appended to a Merlin
+/// transcript, and the blinding factor is 64 challenge bytes from it,
+/// reduced modulo the group order.  Different purposes give
+/// independent blinding factors, so a wallet can use one seed for
+/// several kinds of outputs.
+pub fn derive(seed: &[u8; 32], purpose: &'static [u8], index: u64) -> Scalar {
+    derive_from(seeded_transcript(seed, purpose), index)
+}
+
+/// Derives the blinding factors with the indices in `indices` for
+/// `purpose` from the wallet `seed`, as [`derive()`] does for each of
+/// them.
+pub fn derive_many(seed: &[u8; 32], purpose: &'static [u8], indices: Range<u64>) -> Vec<Scalar> {
+    let transcript = seeded_transcript(seed, purpose);
+    indices
+        .map(|index| derive_from(transcript.clone(), index))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashSet;
+
+    #[test]
+    fn derivation_is_stable() {
+        // Wallets rely on these: changing any of them breaks the
+        // recovery of existing outputs.
+        let vectors: [(&[u8; 32], &'static [u8], u64, &str); 4] = [
+            (
+                &[0; 32],
+                b"outputs",
+                0,
+                "ec2451652d6cf92bf79d73fc9850e56e55275a000a4729a0c9c27045b2cda002",
+            ),
+            (
+                &[0; 32],
+                b"outputs",
+                1,
+                "f2dcf5d6d3cbf7a7028d5abd05588898b7d89d631bf176b6ead96c28b743ad01",
+            ),
+            (
+                &[0; 32],
+                b"change",
+                0,
+                "7c68a01e46e49736077ab155f7a06b51166645da8e444cdb4855b58f6b725900",
+            ),
+            (
+                &[0xab; 32],
+                b"outputs",
+                u64::MAX,
+                "0f179088442d11ea5fc786fe45a0e435594a41dca47518df70549dccd98c9d0c",
+            ),
+        ];
+        for (seed, purpose, index, expected) in vectors.iter() {
+            assert_eq!(
+                hex::encode(derive(seed, purpose, *index).as_bytes()),
+                *expected
+            );
+        }
+    }
+
+    #[test]
+    fn derive_many_matches_derive() {
+        let seed = [5; 32];
+        let blindings = derive_many(&seed, b"outputs", 10..20);
+        assert_eq!(blindings.len(), 10);
+        for (index, blinding) in (10..20).zip(blindings.iter()) {
+            assert_eq!(*blinding, derive(&seed, b"outputs", index));
+        }
+        assert!(derive_many(&seed, b"outputs", 3..3).is_empty());
+    }
+
+    #[test]
+    fn blindings_are_unique() {
+        let seeds = [[0; 32], [1; 32]];
+        let purposes: [&'static [u8]; 2] = [b"outputs", b"change"];
+        let mut seen = HashSet::new();
+        for seed in seeds.iter() {
+            for purpose in purposes.iter() {
+                for blinding in derive_many(seed, purpose, 0..1 << 14) {
+                    assert!(seen.insert(blinding.to_bytes()));
+                }
+            }
+        }
+        assert_eq!(seen.len(), 4 << 14);
+    }
+}
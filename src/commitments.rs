@@ -0,0 +1,187 @@
+//! Homomorphic arithmetic on compressed Pedersen commitments.
+
+#![allow(non_snake_case)]
+
+use core::ops::Neg;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+
+use crate::errors::ProofError;
+use crate::generators::PedersenGens;
+
+/// A compressed Pedersen commitment \\(C = v \cdot B + \tilde{v} \cdot \tilde{B}\\).
+///
+/// Pedersen commitments are additively homomorphic, so commitments
+/// can be combined without knowing their openings: the sum of
+/// commitments to \\(a\\) and \\(b\\) is a commitment to \\(a + b\\)
+/// whose blinding factor is the sum of their blinding factors.
+///
+/// Each operation decompresses its operands, and returns
+/// [`ProofError::FormatError`] if any of them is not a valid encoding
+/// of a Ristretto point.  The results are not checked against the
+/// identity, since e.g. \\(C - C\\) is a valid commitment to zero.
+///
+/// The wrapped point is public, and commitments convert to and from
+/// [`CompressedRistretto`], so they can be passed directly to the
+/// proving and verification functions.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Commitment(pub CompressedRistretto);
+
+impl Commitment {
+    /// Commits to `value` with the `blinding` factor.
+    pub fn new(pc_gens: &PedersenGens, value: Scalar, blinding: Scalar) -> Commitment {
+        Commitment(pc_gens.commit(value, blinding).compress())
+    }
+
+    /// Returns the compressed point of the commitment.
+    pub fn as_compressed(&self) -> &CompressedRistretto {
+        &self.0
+    }
+
+    /// Decompresses the commitment, returning
+    /// [`ProofError::FormatError`] if it is not a valid point.
+    pub fn decompress(&self) -> Result<RistrettoPoint, ProofError> {
+        self.0.decompress().ok_or(ProofError::FormatError)
+    }
+
+    /// Returns \\(C\_1 + C\_2\\), a commitment to the sum of the values
+    /// with the sum of the blinding factors.
+    pub fn try_add(&self, other: &Commitment) -> Result<Commitment, ProofError> {
+        Ok((self.decompress()? + other.decompress()?).into())
+    }
+
+    /// Returns \\(C\_1 - C\_2\\), a commitment to the difference of the
+    /// values with the difference of the blinding factors.
+    pub fn try_sub(&self, other: &Commitment) -> Result<Commitment, ProofError> {
+        Ok((self.decompress()? - other.decompress()?).into())
+    }
+
+    /// Returns \\(C - v \cdot B\\), a commitment to the value minus `v`
+    /// with the same blinding factor.
+    ///
+    /// This moves a statement about the interval \\([v, v + 2^n)\\)
+    /// to the interval \\([0, 2^n)\\) covered by a range proof.
+    pub fn shift_value(&self, v: u64, pc_gens: &PedersenGens) -> Result<Commitment, ProofError> {
+        Ok((self.decompress()? - Scalar::from(v) * pc_gens.B).into())
+    }
+
+    /// Returns \\(k \cdot C\\), a commitment to the value times `k`
+    /// with the blinding factor times `k`.
+    pub fn scale(&self, k: Scalar) -> Result<Commitment, ProofError> {
+        Ok((k * self.decompress()?).into())
+    }
+
+    /// Returns \\(-C\\), a commitment to the negated value with the
+    /// negated blinding factor.
+    pub fn try_neg(&self) -> Result<Commitment, ProofError> {
+        Ok(self.decompress()?.neg().into())
+    }
+}
+
+impl From<CompressedRistretto> for Commitment {
+    fn from(point: CompressedRistretto) -> Commitment {
+        Commitment(point)
+    }
+}
+
+impl From<RistrettoPoint> for Commitment {
+    fn from(point: RistrettoPoint) -> Commitment {
+        Commitment(point.compress())
+    }
+}
+
+impl From<Commitment> for CompressedRistretto {
+    fn from(commitment: Commitment) -> CompressedRistretto {
+        commitment.0
+    }
+}
+
+impl AsRef<CompressedRistretto> for Commitment {
+    fn as_ref(&self) -> &CompressedRistretto {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::traits::Identity;
+
+    /// A 32-byte string that is not the encoding of a Ristretto point.
+    fn invalid() -> Commitment {
+        Commitment(CompressedRistretto([0xff; 32]))
+    }
+
+    #[test]
+    fn arithmetic_matches_openings() {
+        let mut rng = rand::thread_rng();
+        let pc_gens = PedersenGens::default();
+        let (a, a_blinding) = (Scalar::from(37u64), Scalar::random(&mut rng));
+        let (b, b_blinding) = (Scalar::from(5u64), Scalar::random(&mut rng));
+        let k = Scalar::random(&mut rng);
+
+        let A = Commitment::new(&pc_gens, a, a_blinding);
+        let B = Commitment::new(&pc_gens, b, b_blinding);
+
+        assert_eq!(
+            A.try_add(&B).unwrap(),
+            Commitment::new(&pc_gens, a + b, a_blinding + b_blinding)
+        );
+        assert_eq!(
+            A.try_sub(&B).unwrap(),
+            Commitment::new(&pc_gens, a - b, a_blinding - b_blinding)
+        );
+        assert_eq!(
+            A.shift_value(30, &pc_gens).unwrap(),
+            Commitment::new(&pc_gens, Scalar::from(7u64), a_blinding)
+        );
+        assert_eq!(
+            A.scale(k).unwrap(),
+            Commitment::new(&pc_gens, k * a, k * a_blinding)
+        );
+        assert_eq!(
+            A.try_neg().unwrap(),
+            Commitment::new(&pc_gens, -a, -a_blinding)
+        );
+        assert!(pc_gens.verify_opening(
+            A.shift_value(30, &pc_gens).unwrap().as_compressed(),
+            7,
+            a_blinding
+        ));
+    }
+
+    #[test]
+    fn identity_results_are_allowed() {
+        let pc_gens = PedersenGens::default();
+        let A = Commitment::new(&pc_gens, Scalar::from(3u64), Scalar::from(4u64));
+        let identity = Commitment(CompressedRistretto::identity());
+
+        assert_eq!(A.try_sub(&A).unwrap(), identity);
+        assert_eq!(A.scale(Scalar::ZERO).unwrap(), identity);
+        assert_eq!(A.try_add(&identity).unwrap(), A);
+        assert_eq!(
+            Commitment::new(&pc_gens, Scalar::from(3u64), Scalar::ZERO)
+                .shift_value(3, &pc_gens)
+                .unwrap(),
+            identity
+        );
+    }
+
+    #[test]
+    fn invalid_points_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let A = Commitment::new(&pc_gens, Scalar::ONE, Scalar::ONE);
+
+        assert_eq!(invalid().decompress(), Err(ProofError::FormatError));
+        assert_eq!(A.try_add(&invalid()), Err(ProofError::FormatError));
+        assert_eq!(invalid().try_add(&A), Err(ProofError::FormatError));
+        assert_eq!(A.try_sub(&invalid()), Err(ProofError::FormatError));
+        assert_eq!(
+            invalid().shift_value(1, &pc_gens),
+            Err(ProofError::FormatError)
+        );
+        assert_eq!(invalid().scale(Scalar::ONE), Err(ProofError::FormatError));
+        assert_eq!(invalid().try_neg(), Err(ProofError::FormatError));
+    }
+}
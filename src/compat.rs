@@ -0,0 +1,115 @@
+//! The `compat` module verifies range proofs created under earlier
+//! transcript rules, with the `legacy-v0` feature.
+//!
+//! Changes to the transcript of a proof, such as giving the
+//! inner-product proof its own scope, make every proof created before
+//! them fail verification.  An operator that must keep accepting such
+//! historical proofs can verify them here under the rules selected by a
+//! [`TranscriptVersion`], while issuing only proofs under the current
+//! rules: no prover in this crate creates proofs under earlier ones.
+//!
+//! The rules of a proof are not recorded in its encoding.  A
+//! [`ProofEnvelope`](crate::ProofEnvelope) does not record them either,
+//! because the envelope format predates the changes, so the version
+//! must be known from where the proof was stored.  Proofs that only
+//! need the current rules should be verified with the methods of
+//! [`RangeProof`], so that new integrations never depend on this
+//! module.
+//!
+//! ```
+//! use bulletproofs::compat::{self, TranscriptVersion};
+//! use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof};
+//! use curve25519_dalek::scalar::Scalar;
+//! use merlin::Transcript;
+//!
+//! let pc_gens = PedersenGens::default();
+//! let bp_gens = BulletproofGens::new(32, 1);
+//! let blinding = Scalar::from(17u64);
+//!
+//! let mut transcript = Transcript::new(b"doctest example");
+//! let (proof, commitment) =
+//!     RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 7, &blinding, BitSize::B32)?;
+//!
+//! // A proof is only valid under the rules it was created with.
+//! let verify = |version| {
+//!     let mut transcript = Transcript::new(b"doctest example");
+//!     compat::verify_legacy(
+//!         &proof,
+//!         &bp_gens,
+//!         &pc_gens,
+//!         &mut transcript,
+//!         &[commitment],
+//!         BitSize::B32,
+//!         version,
+//!     )
+//! };
+//! assert!(verify(TranscriptVersion::V1).is_ok());
+//! assert!(verify(TranscriptVersion::V0).is_err());
+//! # Ok::<(), bulletproofs::ProofError>(())
+//! ```
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+use crate::generators::{AsGenerators, PedersenGens};
+use crate::range_proof::{BitSize, RangeProof};
+#[cfg(any(feature = "std", feature = "getrandom"))]
+use crate::util;
+
+pub use crate::transcript::TranscriptVersion;
+
+/// Verifies an aggregated range proof for the given value commitments,
+/// replaying the transcript under the rules of `version`.
+///
+/// Apart from the transcript rules, this is
+/// [`RangeProof::verify_multiple_with_rng`], with the same errors, and
+/// the `transcript` is likewise only updated if the proof is valid.
+/// Under [`TranscriptVersion::V1`] the two agree on every proof.
+#[allow(clippy::too_many_arguments)]
+pub fn verify_legacy_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+    proof: &RangeProof,
+    bp_gens: &G,
+    pc_gens: &PedersenGens,
+    transcript: &mut Transcript,
+    value_commitments: &[CompressedRistretto],
+    n: BitSize,
+    version: TranscriptVersion,
+    rng: &mut T,
+) -> Result<(), ProofError> {
+    proof.verify_multiple_versioned_with_rng(
+        bp_gens.as_generators(),
+        pc_gens,
+        transcript,
+        value_commitments,
+        n.bits(),
+        version,
+        rng,
+    )
+}
+
+/// Verifies an aggregated range proof under the rules of `version`.
+/// This is a convenience wrapper around [`verify_legacy_with_rng`],
+/// passing in a threadsafe RNG.
+#[cfg(any(feature = "std", feature = "getrandom"))]
+pub fn verify_legacy<G: AsGenerators + ?Sized>(
+    proof: &RangeProof,
+    bp_gens: &G,
+    pc_gens: &PedersenGens,
+    transcript: &mut Transcript,
+    value_commitments: &[CompressedRistretto],
+    n: BitSize,
+    version: TranscriptVersion,
+) -> Result<(), ProofError> {
+    verify_legacy_with_rng(
+        proof,
+        bp_gens,
+        pc_gens,
+        transcript,
+        value_commitments,
+        n,
+        version,
+        &mut util::default_rng(),
+    )
+}
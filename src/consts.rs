@@ -0,0 +1,107 @@
+//! The `consts` module lists the sizes and bounds of the proof
+//! encodings of this crate, for code that allocates buffers for proofs
+//! or checks their lengths without calling into the crate, such as
+//! bindings for other languages.
+//!
+//! Every encoding is a sequence of [`ELEMENT_BYTES`]-byte elements,
+//! each a compressed point or a canonical scalar, and R1CS proofs
+//! additionally start with a version byte.  The encodings are:
+//!
+//! * a [`RangeProof`](crate::RangeProof) of \\(m\\) values of \\(n\\)
+//!   bits: [`RANGE_HEADER_ELEMENTS`] followed by an inner-product proof
+//!   over vectors of length \\(nm\\);
+//! * an inner-product proof over vectors of length \\(k\\):
+//!   \\(2 \lg k\\) points followed by [`IPP_FINAL_ELEMENTS`];
+//! * a [`LinearProof`](crate::LinearProof) over vectors of length
+//!   \\(k\\): \\(2 \lg k\\) points followed by [`LINEAR_FINAL_ELEMENTS`];
+//! * a [`SmallVecOpeningProof`](crate::SmallVecOpeningProof) of
+//!   \\(k\\) values: \\(k\\) scalars and [`SMALL_VEC_OPENING_FIXED_ELEMENTS`];
+//! * a [`BalanceProof`](crate::BalanceProof): [`BALANCE_PROOF_ELEMENTS`];
+//! * a [`NonZeroProof`](crate::NonZeroProof): [`NONZERO_PROOF_ELEMENTS`];
+//! * a [`CrossCommitmentProof`](crate::CrossCommitmentProof):
+//!   [`CROSS_COMMITMENT_PROOF_ELEMENTS`];
+//! * a [`VecSumProof`](crate::VecSumProof): [`BALANCE_PROOF_ELEMENTS`],
+//!   then a range proof;
+//! * an R1CS proof: [`R1CS_VERSION_BYTES`], then
+//!   [`R1CS_ONE_PHASE_HEADER_ELEMENTS`] or
+//!   [`R1CS_TWO_PHASE_HEADER_ELEMENTS`], followed by an inner-product
+//!   proof over the multipliers rounded up to a power of two.
+//!
+//! The crate serializes and parses proofs with these constants, so they
+//! always match the encodings.  They are part of the stable API: none
+//! of them changes except in a new major version.
+//!
+//! ```
+//! use bulletproofs::consts::{ELEMENT_BYTES, IPP_FINAL_ELEMENTS, RANGE_HEADER_ELEMENTS};
+//! use bulletproofs::RangeProof;
+//!
+//! // A proof of 4 values of 32 bits has an inner-product proof over 128 bits.
+//! let size = (RANGE_HEADER_ELEMENTS + 2 * 7 + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES;
+//! assert_eq!(size, RangeProof::size_const(32, 4));
+//! ```
+
+pub use crate::inner_product_proof::MAX_VECTOR_LENGTH;
+pub use crate::range_proof::{MAX_BITSIZE, MAX_RANGE_PROOF_SIZE};
+
+/// The size in bytes of a compressed Ristretto point.
+pub const POINT_BYTES: usize = 32;
+
+/// The size in bytes of a canonical scalar.
+pub const SCALAR_BYTES: usize = 32;
+
+/// The size in bytes of each element of a proof encoding, which is a
+/// point or a scalar.
+pub const ELEMENT_BYTES: usize = POINT_BYTES;
+
+const _: () = assert!(POINT_BYTES == SCALAR_BYTES);
+
+/// The bitsizes \\(n\\) for which range proofs can be created and
+/// verified, in increasing order.
+pub const SUPPORTED_RANGE_BITS: &[usize] = &[8, 16, 32, 64];
+
+/// The largest number \\(m\\) of values in one aggregated range proof,
+/// which is reached for the smallest bitsize.  For \\(n\\)-bit values
+/// it is [`MAX_VECTOR_LENGTH`]` / n`.
+pub const MAX_AGGREGATION: usize = MAX_VECTOR_LENGTH / SUPPORTED_RANGE_BITS[0];
+
+/// The number of elements of a range proof before its inner-product
+/// proof: the points \\(A, S, T\_1, T\_2\\) and the scalars
+/// \\(t\_x, \tilde{t}\_x, \tilde{e}\\).
+pub const RANGE_HEADER_ELEMENTS: usize = 7;
+
+/// The number of elements of an inner-product proof after its
+/// \\(L\\) and \\(R\\) points: the scalars \\(a, b\\).
+pub const IPP_FINAL_ELEMENTS: usize = 2;
+
+/// The number of elements of a linear proof after its \\(L\\) and
+/// \\(R\\) points: the point \\(S\\) and the scalars \\(a, r\\).
+pub const LINEAR_FINAL_ELEMENTS: usize = 3;
+
+/// The number of elements of a small vector opening proof besides its
+/// scalar per value: the point \\(R\\) and the scalar \\(s\_r\\).
+pub const SMALL_VEC_OPENING_FIXED_ELEMENTS: usize = 2;
+
+/// The number of elements of a balance proof: the point \\(R\\) and
+/// the scalar \\(s\\).
+pub const BALANCE_PROOF_ELEMENTS: usize = 2;
+
+/// The number of elements of a nonzero proof: the point \\(R\\) and
+/// the scalars \\(s\_1, s\_2\\).
+pub const NONZERO_PROOF_ELEMENTS: usize = 3;
+
+/// The number of elements of a cross-commitment proof: the points
+/// \\(R\_1, R\_2\\) and the scalars \\(s\_v, s\_1, s\_2\\).
+pub const CROSS_COMMITMENT_PROOF_ELEMENTS: usize = 5;
+
+/// The number of bytes before the elements of an R1CS proof, which
+/// give the version of its encoding.
+pub const R1CS_VERSION_BYTES: usize = 1;
+
+/// The number of elements of an R1CS proof without second-phase
+/// commitments before its inner-product proof.
+pub const R1CS_ONE_PHASE_HEADER_ELEMENTS: usize = 11;
+
+/// The number of elements of an R1CS proof with second-phase
+/// commitments before its inner-product proof, which adds the points
+/// \\(A\_{I2}, A\_{O2}, S\_2\\).
+pub const R1CS_TWO_PHASE_HEADER_ELEMENTS: usize = R1CS_ONE_PHASE_HEADER_ELEMENTS + 3;
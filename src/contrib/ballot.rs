@@ -0,0 +1,336 @@
+//! Confidential one-of-\\(k\\) voting.
+//!
+//! A voter casts a [`Ballot`] of one Pedersen commitment per
+//! candidate, to one for the chosen candidate and to zero for the
+//! others, together with a [`BallotProof`] that exactly one of the
+//! commitments is to one.  Anyone can add up the commitments of valid
+//! ballots into [`TallyCommitments`], and the tallier, who learns the
+//! blinding factors of the ballots, publishes the counts with a
+//! [`TallyOpeningProof`] that they match the sums.
+//!
+//! The blinding factors of a ballot are derived from a secret
+//! `voter_blinding` with [`blindings`], so that the voter only has to
+//! hand one scalar to the tallier.
+//!
+//! ```
+//! use bulletproofs::contrib::ballot;
+//! use bulletproofs::{BulletproofGens, PedersenGens};
+//! use curve25519_dalek::scalar::Scalar;
+//!
+//! let pc_gens = PedersenGens::default();
+//! let bp_gens = BulletproofGens::new(4, 1);
+//! let mut rng = rand::thread_rng();
+//!
+//! let voter_blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+//! let mut ballots = Vec::new();
+//! for (&choice, voter_blinding) in [2, 0].iter().zip(&voter_blindings) {
+//!     let (ballot, proof) = ballot::cast(&bp_gens, &pc_gens, 3, choice, voter_blinding)?;
+//!     ballot.verify(&bp_gens, &pc_gens, &proof)?;
+//!     ballots.push(ballot);
+//! }
+//!
+//! let tally = ballot::tally(&ballots)?;
+//! let blinding_sums = ballot::blinding_sums(3, &voter_blindings);
+//! let proof = ballot::tally_opening_proof(&pc_gens, &tally, &[1, 0, 1], &blinding_sums)?;
+//! tally.verify_counts(&pc_gens, &[1, 0, 1], &proof)?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::r1cs::gadgets::one_hot;
+use crate::r1cs::{Prover, R1CSError, R1CSProof, Verifier};
+use crate::{AsGenerators, BalanceProof, Commitment, PedersenGens, ProofError};
+
+/// The per-candidate commitments of a ballot.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ballot {
+    commitments: Vec<CompressedRistretto>,
+}
+
+/// A proof that a [`Ballot`] commits to one for exactly one candidate,
+/// and to zero for the others.
+#[derive(Clone, Debug)]
+pub struct BallotProof(R1CSProof);
+
+/// The sums of the commitments of a set of ballots, one per candidate.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TallyCommitments {
+    commitments: Vec<Commitment>,
+}
+
+/// A proof that the [`TallyCommitments`] commit to given counts.
+#[derive(Clone, Debug)]
+pub struct TallyOpeningProof {
+    proofs: Vec<BalanceProof>,
+}
+
+/// Returns the transcript of the ballot proof for `candidates`
+/// candidates.
+fn ballot_transcript(candidates: usize) -> Transcript {
+    let mut transcript = Transcript::new(b"Bulletproofs ballot");
+    transcript.append_u64(b"k", candidates as u64);
+    transcript
+}
+
+/// Returns the transcript of the tally opening proof for `counts`.
+fn tally_transcript(counts: &[u64]) -> Transcript {
+    let mut transcript = Transcript::new(b"Bulletproofs tally");
+    transcript.append_u64(b"k", counts.len() as u64);
+    for &count in counts {
+        transcript.append_u64(b"count", count);
+    }
+    transcript
+}
+
+/// Derives the per-candidate blinding factors of a ballot from the
+/// secret `voter_blinding`.
+pub fn blindings(candidates: usize, voter_blinding: &Scalar) -> Vec<Scalar> {
+    let mut transcript = Transcript::new(b"Bulletproofs ballot blindings");
+    transcript.append_u64(b"k", candidates as u64);
+    transcript.append_message(b"seed", voter_blinding.as_bytes());
+    (0..candidates)
+        .map(|_| {
+            let mut buf = [0u8; 64];
+            transcript.challenge_bytes(b"blinding", &mut buf);
+            Scalar::from_bytes_mod_order_wide(&buf)
+        })
+        .collect()
+}
+
+/// Returns the per-candidate sums of the blinding factors of the
+/// ballots cast with the `voter_blindings`, for
+/// [`tally_opening_proof`].
+pub fn blinding_sums(candidates: usize, voter_blindings: &[Scalar]) -> Vec<Scalar> {
+    let mut sums = vec![Scalar::ZERO; candidates];
+    for voter_blinding in voter_blindings {
+        for (sum, blinding) in sums.iter_mut().zip(blindings(candidates, voter_blinding)) {
+            *sum += blinding;
+        }
+    }
+    sums
+}
+
+/// Casts a ballot for the `choice`-th of `candidates` candidates, with
+/// blinding factors derived from `voter_blinding`.
+///
+/// The proof uses one multiplier per candidate, so `bp_gens` must have
+/// at least `candidates.next_power_of_two()` generators.  Returns
+/// [`R1CSError::GadgetError`] if `choice` is not a candidate.
+pub fn cast_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+    bp_gens: &G,
+    pc_gens: &PedersenGens,
+    candidates: usize,
+    choice: usize,
+    voter_blinding: &Scalar,
+    rng: &mut T,
+) -> Result<(Ballot, BallotProof), R1CSError> {
+    if choice >= candidates {
+        return Err(R1CSError::GadgetError {
+            description: "choice is not a candidate".into(),
+        });
+    }
+
+    let mut prover = Prover::new(pc_gens, ballot_transcript(candidates));
+    let (commitments, selector): (Vec<_>, Vec<_>) = blindings(candidates, voter_blinding)
+        .into_iter()
+        .enumerate()
+        .map(|(j, blinding)| prover.commit(Scalar::from((j == choice) as u64), blinding))
+        .unzip();
+    one_hot(&mut prover, &selector);
+    let proof = prover.prove_with_rng(bp_gens, rng)?;
+
+    Ok((Ballot { commitments }, BallotProof(proof)))
+}
+
+/// Casts a ballot for the `choice`-th of `candidates` candidates.
+/// This is a convenience wrapper around [`cast_with_rng`],
+/// passing in a threadsafe RNG.
+pub fn cast<G: AsGenerators + ?Sized>(
+    bp_gens: &G,
+    pc_gens: &PedersenGens,
+    candidates: usize,
+    choice: usize,
+    voter_blinding: &Scalar,
+) -> Result<(Ballot, BallotProof), R1CSError> {
+    cast_with_rng(
+        bp_gens,
+        pc_gens,
+        candidates,
+        choice,
+        voter_blinding,
+        &mut rand::thread_rng(),
+    )
+}
+
+impl Ballot {
+    /// Creates a ballot from its per-candidate commitments.
+    pub fn new(commitments: Vec<CompressedRistretto>) -> Self {
+        Ballot { commitments }
+    }
+
+    /// Returns the per-candidate commitments.
+    pub fn commitments(&self) -> &[CompressedRistretto] {
+        &self.commitments
+    }
+
+    /// Returns the number of candidates.
+    pub fn candidates(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Verifies that the ballot commits to one for exactly one
+    /// candidate.
+    pub fn verify_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        proof: &BallotProof,
+        rng: &mut T,
+    ) -> Result<(), R1CSError> {
+        let mut verifier = Verifier::new(ballot_transcript(self.candidates()));
+        let selector: Vec<_> = self
+            .commitments
+            .iter()
+            .map(|&V| verifier.commit(V))
+            .collect();
+        one_hot(&mut verifier, &selector);
+        verifier.verify_with_rng(&proof.0, pc_gens, bp_gens, rng)
+    }
+
+    /// Verifies that the ballot commits to one for exactly one
+    /// candidate.
+    /// This is a convenience wrapper around [`Ballot::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    pub fn verify<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        proof: &BallotProof,
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(bp_gens, pc_gens, proof, &mut rand::thread_rng())
+    }
+}
+
+impl BallotProof {
+    /// Serializes the proof, see [`R1CSProof::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Deserializes the proof, see [`R1CSProof::from_bytes`].
+    pub fn from_bytes(slice: &[u8]) -> Result<BallotProof, R1CSError> {
+        R1CSProof::from_bytes(slice).map(BallotProof)
+    }
+}
+
+/// Adds up the commitments of the `ballots`, candidate by candidate.
+///
+/// The ballots should have been verified.  Returns
+/// [`ProofError::InvalidInputLength`] if the ballots have different
+/// numbers of candidates, and [`ProofError::FormatError`] if a
+/// commitment is not a valid point.
+pub fn tally(ballots: &[Ballot]) -> Result<TallyCommitments, ProofError> {
+    let candidates = ballots.first().map_or(0, Ballot::candidates);
+    let mut commitments = vec![Commitment::default(); candidates];
+    for ballot in ballots {
+        if ballot.candidates() != candidates {
+            return Err(ProofError::InvalidInputLength);
+        }
+        for (sum, &V) in commitments.iter_mut().zip(&ballot.commitments) {
+            *sum = sum.try_add(&V.into())?;
+        }
+    }
+    Ok(TallyCommitments { commitments })
+}
+
+/// Proves that the `tally` commits to the `counts`, given the
+/// per-candidate sums of the blinding factors of the ballots.
+///
+/// The proof reveals neither the blinding factors nor any ballot.
+/// Returns [`ProofError::InvalidInputLength`] if the lengths do not
+/// match the number of candidates.
+pub fn tally_opening_proof_with_rng<T: RngCore + CryptoRng>(
+    pc_gens: &PedersenGens,
+    tally: &TallyCommitments,
+    counts: &[u64],
+    blinding_sums: &[Scalar],
+    rng: &mut T,
+) -> Result<TallyOpeningProof, ProofError> {
+    if counts.len() != tally.candidates() || blinding_sums.len() != tally.candidates() {
+        return Err(ProofError::InvalidInputLength);
+    }
+    let mut transcript = tally_transcript(counts);
+    let proofs = blinding_sums
+        .iter()
+        .map(|&blinding_sum| {
+            BalanceProof::prove_with_rng(
+                pc_gens,
+                &mut transcript,
+                &[blinding_sum],
+                &[Scalar::ZERO],
+                rng,
+            )
+        })
+        .collect();
+    Ok(TallyOpeningProof { proofs })
+}
+
+/// Proves that the `tally` commits to the `counts`.
+/// This is a convenience wrapper around [`tally_opening_proof_with_rng`],
+/// passing in a threadsafe RNG.
+pub fn tally_opening_proof(
+    pc_gens: &PedersenGens,
+    tally: &TallyCommitments,
+    counts: &[u64],
+    blinding_sums: &[Scalar],
+) -> Result<TallyOpeningProof, ProofError> {
+    tally_opening_proof_with_rng(
+        pc_gens,
+        tally,
+        counts,
+        blinding_sums,
+        &mut rand::thread_rng(),
+    )
+}
+
+impl TallyCommitments {
+    /// Returns the per-candidate sums.
+    pub fn commitments(&self) -> &[Commitment] {
+        &self.commitments
+    }
+
+    /// Returns the number of candidates.
+    pub fn candidates(&self) -> usize {
+        self.commitments.len()
+    }
+
+    /// Verifies that the tally commits to the `counts`.
+    pub fn verify_counts(
+        &self,
+        pc_gens: &PedersenGens,
+        counts: &[u64],
+        proof: &TallyOpeningProof,
+    ) -> Result<(), ProofError> {
+        if counts.len() != self.candidates() || proof.proofs.len() != self.candidates() {
+            return Err(ProofError::VerificationError);
+        }
+        let mut transcript = tally_transcript(counts);
+        for ((sum, &count), proof) in self.commitments.iter().zip(counts).zip(&proof.proofs) {
+            // The count, committed with a zero blinding factor.
+            let opened = Commitment::new(pc_gens, Scalar::from(count), Scalar::ZERO);
+            proof.verify(
+                pc_gens,
+                &mut transcript,
+                &[*sum.as_compressed()],
+                &[*opened.as_compressed()],
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,8 @@
+//! Applications built on the public API of the crate.
+//!
+//! The modules here use only items that are exported to users of the
+//! crate, so they double as examples of how to combine the proof
+//! systems, and as a check that the public API is sufficient for them.
+
+pub mod ballot;
+pub mod small_alphabet;
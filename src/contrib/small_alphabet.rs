@@ -0,0 +1,236 @@
+//! Proofs that a committed vector has entries from a small alphabet,
+//! with given multiplicities.
+//!
+//! A [`SmallAlphabetProof`] proves the following relation.  The public
+//! statement is a list of Pedersen commitments \\(V\_0, \ldots, V\_{n-1}\\)
+//! and a list of counts \\(m\_0, \ldots, m\_c\\), which defines the
+//! alphabet \\(\\{0, 1, \ldots, c\\}\\).  The prover knows openings
+//! \\(V\_i = v\_i B + \tilde{v}\_i \tilde{B}\\) such that every
+//! \\(v\_i\\) is in the alphabet, and exactly \\(m\_s\\) of the
+//! \\(v\_i\\) are equal to \\(s\\).  In particular, \\(n = \sum\_s m\_s\\).
+//!
+//! For the alphabet \\(\\{0, 1\\}\\) and counts \\((n - k, k)\\), the
+//! proof shows that the committed vector is \\(k\\)-hot.  With the
+//! alphabet \\(\\{0, 1, 2\\}\\), it shows for instance that a vector of
+//! trits has a given number of each trit, and so a given sum.
+//!
+//! The proof is a constraint system proof of the [`multiset`] gadget,
+//! bound to the counts.  Each entry takes \\(c + 1\\) multipliers for
+//! the gadget, and half as many for allocating its one-hot selector,
+//! so see [`SmallAlphabetProof::multipliers`] for the generators it
+//! needs.
+//!
+//! ```
+//! use bulletproofs::contrib::small_alphabet::SmallAlphabetProof;
+//! use bulletproofs::{BulletproofGens, PedersenGens};
+//! use curve25519_dalek::scalar::Scalar;
+//! use merlin::Transcript;
+//!
+//! let pc_gens = PedersenGens::default();
+//! let bp_gens = BulletproofGens::new(32, 1);
+//! let mut rng = rand::thread_rng();
+//!
+//! // Six trits: two zeros, one one, and three twos.
+//! let counts = [2, 1, 3];
+//! let entries = [2, 0, 1, 2, 2, 0];
+//! let blindings: Vec<Scalar> = entries.iter().map(|_| Scalar::random(&mut rng)).collect();
+//! let mut prover_transcript = Transcript::new(b"doctest example");
+//! let (proof, commitments) = SmallAlphabetProof::prove(
+//!     &bp_gens,
+//!     &pc_gens,
+//!     &mut prover_transcript,
+//!     &counts,
+//!     &entries,
+//!     &blindings,
+//! )?;
+//!
+//! let mut verifier_transcript = Transcript::new(b"doctest example");
+//! proof.verify(
+//!     &bp_gens,
+//!     &pc_gens,
+//!     &mut verifier_transcript,
+//!     &counts,
+//!     &commitments,
+//! )?;
+//! # Ok::<(), Box<dyn std::error::Error>>(())
+//! ```
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::r1cs::gadgets::{allocate_one_hot, multiset};
+use crate::r1cs::{Prover, R1CSError, R1CSProof, Verifier};
+use crate::transcript::TranscriptProtocol;
+use crate::{AsGenerators, PedersenGens};
+
+/// A proof that a vector of commitments opens to entries from the
+/// alphabet \\(\\{0, \ldots, c\\}\\) with given multiplicities.
+#[derive(Clone, Debug)]
+pub struct SmallAlphabetProof(R1CSProof);
+
+/// Binds the `transcript` to a proof for the multiplicities `counts`.
+fn append_statement(transcript: &mut Transcript, counts: &[u64]) {
+    transcript.append_message(b"dom-sep", b"small-alphabet v1");
+    transcript.append_len(b"c", counts.len());
+    for &count in counts {
+        transcript.append_u64(b"count", count);
+    }
+}
+
+impl SmallAlphabetProof {
+    /// Returns the number of multipliers of the proof for `n` entries
+    /// from an alphabet of `alphabet_len` symbols.  The Bulletproofs
+    /// generators must have at least its next power of two.
+    pub fn multipliers(n: usize, alphabet_len: usize) -> usize {
+        let selectors = n * alphabet_len;
+        selectors + selectors.div_ceil(2)
+    }
+
+    /// Proves that the `entries`, committed to with the `blindings`,
+    /// are in the alphabet \\(\\{0, \ldots, c\\}\\) of the `counts`,
+    /// and that `counts[s]` of them equal \\(s\\), on the `transcript`.
+    ///
+    /// Returns the proof and the commitments to the entries.  Returns
+    /// [`R1CSError::GadgetError`] if the numbers of entries and
+    /// blinding factors differ, or if the entries are not in the
+    /// alphabet or do not have the multiplicities `counts`.
+    pub fn prove_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        counts: &[u64],
+        entries: &[u64],
+        blindings: &[Scalar],
+        rng: &mut T,
+    ) -> Result<(SmallAlphabetProof, Vec<CompressedRistretto>), R1CSError> {
+        if entries.len() != blindings.len() {
+            return Err(R1CSError::GadgetError {
+                description: "there must be one blinding factor per entry".into(),
+            });
+        }
+        let mut multiplicities = vec![0u64; counts.len()];
+        for &v in entries {
+            match multiplicities.get_mut(v as usize) {
+                Some(m) => *m += 1,
+                None => {
+                    return Err(R1CSError::GadgetError {
+                        description: "entry is not in the alphabet".into(),
+                    })
+                }
+            }
+        }
+        if multiplicities != counts {
+            return Err(R1CSError::GadgetError {
+                description: "entries do not have the given multiplicities".into(),
+            });
+        }
+
+        append_statement(transcript, counts);
+        let mut prover = Prover::new(pc_gens, transcript);
+        let (commitments, vars): (Vec<_>, Vec<_>) = entries
+            .iter()
+            .zip(blindings)
+            .map(|(&v, &blinding)| prover.commit(Scalar::from(v), blinding))
+            .unzip();
+        let selectors = entries
+            .iter()
+            .map(|&v| allocate_one_hot(&mut prover, Some(v as usize), counts.len()))
+            .collect::<Result<_, _>>()?;
+        multiset(&mut prover, counts, &vars, selectors)?;
+        let proof = prover.prove_with_rng(bp_gens, rng)?;
+
+        Ok((SmallAlphabetProof(proof), commitments))
+    }
+
+    /// Proves that the `entries` have the multiplicities `counts`.
+    /// This is a convenience wrapper around [`SmallAlphabetProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    pub fn prove<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        counts: &[u64],
+        entries: &[u64],
+        blindings: &[Scalar],
+    ) -> Result<(SmallAlphabetProof, Vec<CompressedRistretto>), R1CSError> {
+        SmallAlphabetProof::prove_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            counts,
+            entries,
+            blindings,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Verifies that the `commitments` open to entries in the alphabet
+    /// \\(\\{0, \ldots, c\\}\\) of the `counts`, of which `counts[s]`
+    /// equal \\(s\\), on a `transcript` in the state the prover's was in.
+    ///
+    /// Returns [`R1CSError::VerificationError`] if the proof does not
+    /// verify, including when the number of commitments is not the sum
+    /// of the counts.
+    pub fn verify_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        counts: &[u64],
+        commitments: &[CompressedRistretto],
+        rng: &mut T,
+    ) -> Result<(), R1CSError> {
+        let n = counts
+            .iter()
+            .try_fold(0u64, |n, &count| n.checked_add(count))
+            .ok_or(R1CSError::VerificationError)?;
+        if n != commitments.len() as u64 {
+            return Err(R1CSError::VerificationError);
+        }
+
+        append_statement(transcript, counts);
+        let mut verifier = Verifier::new(transcript);
+        let vars: Vec<_> = commitments.iter().map(|&V| verifier.commit(V)).collect();
+        let selectors = vars
+            .iter()
+            .map(|_| allocate_one_hot(&mut verifier, None, counts.len()))
+            .collect::<Result<_, _>>()?;
+        multiset(&mut verifier, counts, &vars, selectors)?;
+        verifier.verify_with_rng(&self.0, pc_gens, bp_gens, rng)
+    }
+
+    /// Verifies that the `commitments` have the multiplicities `counts`.
+    /// This is a convenience wrapper around [`SmallAlphabetProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    pub fn verify<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        counts: &[u64],
+        commitments: &[CompressedRistretto],
+    ) -> Result<(), R1CSError> {
+        self.verify_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            counts,
+            commitments,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Serializes the proof, see [`R1CSProof::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.0.to_bytes()
+    }
+
+    /// Deserializes the proof, see [`R1CSProof::from_bytes`].
+    pub fn from_bytes(slice: &[u8]) -> Result<SmallAlphabetProof, R1CSError> {
+        R1CSProof::from_bytes(slice).map(SmallAlphabetProof)
+    }
+}
@@ -0,0 +1,532 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "prover")]
+use curve25519_dalek::traits::MultiscalarMul;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "prover")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::consts::{CROSS_COMMITMENT_PROOF_ELEMENTS, ELEMENT_BYTES};
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::PedersenGens;
+#[cfg(feature = "verifier")]
+use crate::transcript::TranscriptProtocol;
+#[cfg(all(feature = "prover", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+/// A proof that a Pedersen commitment \\(C\_1 = v B + r\_1 \tilde{B}\\)
+/// and a commitment \\(C\_2 = v G + r\_2 H\\) under other bases
+/// \\(G, H\\) commit to the same value \\(v\\).
+///
+/// This bridges range proofs made here to protocols that hold the
+/// value under their own bases, such as the second component of an
+/// ElGamal ciphertext \\(v G + r\_2 P\\) for a public key \\(P\\).  The
+/// prover commits to random \\(k\_v, k\_1, k\_2\\) with
+/// \\(R\_1 = k\_v B + k\_1 \tilde{B}\\) and \\(R\_2 = k\_v G + k\_2 H\\),
+/// receives a challenge \\(c\\), and responds with
+/// \\(s\_v = k\_v + c v\\), \\(s\_1 = k\_1 + c r\_1\\) and
+/// \\(s\_2 = k\_2 + c r\_2\\).  The verifier checks
+/// \\(s\_v B + s\_1 \tilde{B} = R\_1 + c C\_1\\) and
+/// \\(s\_v G + s\_2 H = R\_2 + c C\_2\\); the shared response
+/// \\(s\_v\\) ties the two values together.
+///
+/// The proof is 160 bytes.  Both commitments and the other bases are
+/// bound in the transcript, so a proof only verifies for the pair of
+/// commitments it was made for:
+///
+/// ```
+/// use bulletproofs::{CrossCommitmentProof, PedersenGens};
+/// use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+/// use curve25519_dalek::ristretto::RistrettoPoint;
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let pc_gens = PedersenGens::default();
+/// let G = RISTRETTO_BASEPOINT_POINT;
+/// let H = RistrettoPoint::hash_from_bytes::<sha3::Sha3_512>(b"other base H");
+/// let value = Scalar::from(1037578891u64);
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// let (proof, C_pedersen, C_other) = CrossCommitmentProof::prove(
+///     &pc_gens,
+///     G,
+///     H,
+///     &mut transcript,
+///     value,
+///     Scalar::from(7u64),
+///     Scalar::from(11u64),
+/// )?;
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// proof.verify(&pc_gens, G, H, &mut transcript, &C_pedersen, &C_other)?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct CrossCommitmentProof {
+    /// Commitment to the masks under the Pedersen generators
+    R_1: CompressedRistretto,
+    /// Commitment to the masks under the other bases
+    R_2: CompressedRistretto,
+    /// Response for the value
+    s_v: Scalar,
+    /// Response for the Pedersen blinding factor
+    s_1: Scalar,
+    /// Response for the blinding factor under the other bases
+    s_2: Scalar,
+}
+
+/// Returns [`ProofError::InvalidGenerators`] if the other bases cannot
+/// bind a value: either is the identity, or they are equal.
+#[cfg(feature = "verifier")]
+fn check_other_bases(G: &RistrettoPoint, H: &RistrettoPoint) -> Result<(), ProofError> {
+    if G.is_identity() || H.is_identity() || G == H {
+        return Err(ProofError::InvalidGenerators);
+    }
+    Ok(())
+}
+
+/// Binds the statement to the transcript: the Pedersen generators,
+/// the other bases and both commitments.
+#[cfg(feature = "verifier")]
+fn append_statement(
+    transcript: &mut Transcript,
+    pc_gens: &PedersenGens,
+    G: &RistrettoPoint,
+    H: &RistrettoPoint,
+    C_1: &CompressedRistretto,
+    C_2: &CompressedRistretto,
+) {
+    transcript.cross_commitment_proof_domain_sep();
+    pc_gens.append_fingerprint(transcript);
+    transcript.append_point(b"G", &G.compress());
+    transcript.append_point(b"H", &H.compress());
+    transcript.append_point(b"C_1", C_1);
+    transcript.append_point(b"C_2", C_2);
+}
+
+impl CrossCommitmentProof {
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the Pedersen commitment to `value` with
+    /// `blinding1` and the commitment to `value` with `blinding2` under
+    /// the bases `other_base_G` and `other_base_H` commit to the same
+    /// value.  Returns the proof and the two commitments, Pedersen
+    /// first.
+    ///
+    /// Returns [`ProofError::InvalidGenerators`] if either other base is
+    /// the identity or the two are equal.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        other_base_G: RistrettoPoint,
+        other_base_H: RistrettoPoint,
+        transcript: &mut Transcript,
+        value: Scalar,
+        blinding1: Scalar,
+        blinding2: Scalar,
+        rng: &mut T,
+    ) -> Result<
+        (
+            CrossCommitmentProof,
+            CompressedRistretto,
+            CompressedRistretto,
+        ),
+        ProofError,
+    > {
+        check_other_bases(&other_base_G, &other_base_H)?;
+        let C_1 = pc_gens.commit(value, blinding1).compress();
+        let C_2 = RistrettoPoint::multiscalar_mul([value, blinding2], [other_base_G, other_base_H])
+            .compress();
+        append_statement(
+            transcript,
+            pc_gens,
+            &other_base_G,
+            &other_base_H,
+            &C_1,
+            &C_2,
+        );
+
+        let k_v = Scalar::random(rng);
+        let k_1 = Scalar::random(rng);
+        let k_2 = Scalar::random(rng);
+        let R_1 = pc_gens.commit(k_v, k_1).compress();
+        let R_2 =
+            RistrettoPoint::multiscalar_mul([k_v, k_2], [other_base_G, other_base_H]).compress();
+        transcript.append_point(b"R_1", &R_1);
+        transcript.append_point(b"R_2", &R_2);
+        let c = transcript.challenge_scalar(b"c");
+
+        let proof = CrossCommitmentProof {
+            R_1,
+            R_2,
+            s_v: k_v + c * value,
+            s_1: k_1 + c * blinding1,
+            s_2: k_2 + c * blinding2,
+        };
+        Ok((proof, C_1, C_2))
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that a Pedersen commitment and a commitment
+    /// under other bases commit to the same value.
+    /// This is a convenience wrapper around [`CrossCommitmentProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        other_base_G: RistrettoPoint,
+        other_base_H: RistrettoPoint,
+        transcript: &mut Transcript,
+        value: Scalar,
+        blinding1: Scalar,
+        blinding2: Scalar,
+    ) -> Result<
+        (
+            CrossCommitmentProof,
+            CompressedRistretto,
+            CompressedRistretto,
+        ),
+        ProofError,
+    > {
+        CrossCommitmentProof::prove_with_rng(
+            pc_gens,
+            other_base_G,
+            other_base_H,
+            transcript,
+            value,
+            blinding1,
+            blinding2,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that `commitment_pedersen` and `commitment_other`, under
+    /// the bases `other_base_G` and `other_base_H`, commit to the same
+    /// value.
+    ///
+    /// Returns [`ProofError::InvalidGenerators`] if either other base is
+    /// the identity or the two are equal, and [`ProofError::FormatError`]
+    /// if a commitment is not a valid point.  The transcript is left
+    /// unchanged if verification fails.
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        other_base_G: RistrettoPoint,
+        other_base_H: RistrettoPoint,
+        transcript: &mut Transcript,
+        commitment_pedersen: &CompressedRistretto,
+        commitment_other: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        check_other_bases(&other_base_G, &other_base_H)?;
+        let C_1 = commitment_pedersen
+            .decompress()
+            .ok_or(ProofError::FormatError)?;
+        let C_2 = commitment_other
+            .decompress()
+            .ok_or(ProofError::FormatError)?;
+
+        let mut working_transcript = transcript.clone();
+        append_statement(
+            &mut working_transcript,
+            pc_gens,
+            &other_base_G,
+            &other_base_H,
+            commitment_pedersen,
+            commitment_other,
+        );
+        working_transcript.validate_and_append_point(b"R_1", &self.R_1)?;
+        working_transcript.validate_and_append_point(b"R_2", &self.R_2)?;
+        let c = working_transcript.challenge_scalar(b"c");
+
+        // s_v * B + s_1 * B_blinding - c * C_1 - R_1 == 0
+        let check_1 = RistrettoPoint::optional_multiscalar_mul(
+            [self.s_v, self.s_1, -c, -Scalar::ONE],
+            [
+                Some(pc_gens.B),
+                Some(pc_gens.B_blinding),
+                Some(C_1),
+                self.R_1.decompress(),
+            ],
+        )
+        .ok_or(ProofError::VerificationError)?;
+        // s_v * G + s_2 * H - c * C_2 - R_2 == 0
+        let check_2 = RistrettoPoint::optional_multiscalar_mul(
+            [self.s_v, self.s_2, -c, -Scalar::ONE],
+            [
+                Some(other_base_G),
+                Some(other_base_H),
+                Some(C_2),
+                self.R_2.decompress(),
+            ],
+        )
+        .ok_or(ProofError::VerificationError)?;
+
+        if check_1.is_identity() && check_2.is_identity() {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Serializes the proof into a byte array of 160 bytes: the
+    /// compressed points \\(R\_1, R\_2\\) followed by the scalars
+    /// \\(s\_v, s\_1, s\_2\\).
+    pub fn to_bytes(&self) -> [u8; CROSS_COMMITMENT_PROOF_ELEMENTS * ELEMENT_BYTES] {
+        let mut buf = [0u8; CROSS_COMMITMENT_PROOF_ELEMENTS * ELEMENT_BYTES];
+        buf[..32].copy_from_slice(self.R_1.as_bytes());
+        buf[32..64].copy_from_slice(self.R_2.as_bytes());
+        buf[64..96].copy_from_slice(self.s_v.as_bytes());
+        buf[96..128].copy_from_slice(self.s_1.as_bytes());
+        buf[128..].copy_from_slice(self.s_2.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice is not 160
+    /// bytes long or any scalar is not canonical.
+    pub fn from_bytes(slice: &[u8]) -> Result<CrossCommitmentProof, ProofError> {
+        if slice.len() != CROSS_COMMITMENT_PROOF_ELEMENTS * ELEMENT_BYTES {
+            return Err(ProofError::FormatError);
+        }
+
+        use crate::util::read32;
+
+        let scalar = |pos: usize| {
+            Option::from(Scalar::from_canonical_bytes(read32(&slice[pos..])))
+                .ok_or(ProofError::FormatError)
+        };
+        Ok(CrossCommitmentProof {
+            R_1: CompressedRistretto(read32(&slice[..32])),
+            R_2: CompressedRistretto(read32(&slice[32..])),
+            s_v: scalar(64)?,
+            s_1: scalar(96)?,
+            s_2: scalar(128)?,
+        })
+    }
+}
+
+impl Serialize for CrossCommitmentProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for CrossCommitmentProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct CrossCommitmentProofVisitor;
+
+        impl<'de> Visitor<'de> for CrossCommitmentProofVisitor {
+            type Value = CrossCommitmentProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid CrossCommitmentProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<CrossCommitmentProof, E>
+            where
+                E: serde::de::Error,
+            {
+                CrossCommitmentProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(CrossCommitmentProofVisitor)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::traits::Identity;
+    use sha3::Sha3_512;
+
+    use crate::util;
+
+    /// ElGamal-style bases: the basepoint and a public key.
+    fn other_bases() -> (RistrettoPoint, RistrettoPoint) {
+        (
+            RISTRETTO_BASEPOINT_POINT,
+            RistrettoPoint::hash_from_bytes::<Sha3_512>(b"CrossCommitmentProofTest public key"),
+        )
+    }
+
+    fn prove(
+        pc_gens: &PedersenGens,
+        value: Scalar,
+    ) -> (
+        CrossCommitmentProof,
+        CompressedRistretto,
+        CompressedRistretto,
+    ) {
+        let (G, H) = other_bases();
+        let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+        CrossCommitmentProof::prove(
+            pc_gens,
+            G,
+            H,
+            &mut transcript,
+            value,
+            Scalar::from(3u64),
+            Scalar::from(5u64),
+        )
+        .unwrap()
+    }
+
+    fn verify(
+        pc_gens: &PedersenGens,
+        proof: &CrossCommitmentProof,
+        C_1: &CompressedRistretto,
+        C_2: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        let (G, H) = other_bases();
+        let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+        proof.verify(pc_gens, G, H, &mut transcript, C_1, C_2)
+    }
+
+    #[test]
+    fn equal_values_verify() {
+        let pc_gens = PedersenGens::default();
+        let mut rng = rand::thread_rng();
+        for value in [
+            Scalar::ZERO,
+            Scalar::from(u64::MAX),
+            Scalar::random(&mut rng),
+        ] {
+            let (proof, C_1, C_2) = prove(&pc_gens, value);
+            let proof = CrossCommitmentProof::from_bytes(&proof.to_bytes()).unwrap();
+            assert!(verify(&pc_gens, &proof, &C_1, &C_2).is_ok());
+        }
+    }
+
+    #[test]
+    fn unequal_values_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let (G, H) = other_bases();
+        let value = Scalar::from(7u64);
+        let (proof, C_1, C_2) = prove(&pc_gens, value);
+
+        // The same blinding factors, but the other commitment holds
+        // another value.
+        let other =
+            RistrettoPoint::multiscalar_mul([value + Scalar::ONE, Scalar::from(5u64)], [G, H])
+                .compress();
+        assert_eq!(
+            verify(&pc_gens, &proof, &C_1, &other),
+            Err(ProofError::VerificationError)
+        );
+        let other = pc_gens
+            .commit(value + Scalar::ONE, Scalar::from(3u64))
+            .compress();
+        assert_eq!(
+            verify(&pc_gens, &proof, &other, &C_2),
+            Err(ProofError::VerificationError)
+        );
+
+        // A prover whose commitments hold different values cannot
+        // answer with a single response for the value.
+        let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+        let (forged, _, C_2) = CrossCommitmentProof::prove(
+            &pc_gens,
+            G,
+            H,
+            &mut transcript,
+            value + Scalar::ONE,
+            Scalar::from(3u64),
+            Scalar::from(5u64),
+        )
+        .unwrap();
+        assert!(verify(&pc_gens, &forged, &C_1, &C_2).is_err());
+    }
+
+    #[test]
+    fn transcript_binds_both_commitments() {
+        let pc_gens = PedersenGens::default();
+        let (G, H) = other_bases();
+        let (proof, C_1, C_2) = prove(&pc_gens, Scalar::from(7u64));
+
+        // Swapping the commitments, or the bases, changes the challenge.
+        assert!(verify(&pc_gens, &proof, &C_2, &C_1).is_err());
+        let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+        assert!(proof
+            .verify(&pc_gens, H, G, &mut transcript, &C_1, &C_2)
+            .is_err());
+
+        // The transcript is unchanged on failure, and advanced by the
+        // statement on success.
+        let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+        let before = transcript.clone();
+        assert!(proof
+            .verify(&pc_gens, G, H, &mut transcript, &C_2, &C_1)
+            .is_err());
+        let (mut a, mut b) = (transcript.clone(), before.clone());
+        assert_eq!(a.challenge_scalar(b"x"), b.challenge_scalar(b"x"));
+        assert!(proof
+            .verify(&pc_gens, G, H, &mut transcript, &C_1, &C_2)
+            .is_ok());
+        let (mut a, mut b) = (transcript, before);
+        assert_ne!(a.challenge_scalar(b"x"), b.challenge_scalar(b"x"));
+    }
+
+    #[test]
+    fn degenerate_bases_and_encodings_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let (G, H) = other_bases();
+        let (proof, C_1, C_2) = prove(&pc_gens, Scalar::from(7u64));
+        for (G, H) in [
+            (RistrettoPoint::identity(), H),
+            (G, RistrettoPoint::identity()),
+            (G, G),
+        ] {
+            let mut transcript = Transcript::new(b"CrossCommitmentProofTest");
+            assert_eq!(
+                CrossCommitmentProof::prove(
+                    &pc_gens,
+                    G,
+                    H,
+                    &mut transcript,
+                    Scalar::ONE,
+                    Scalar::ONE,
+                    Scalar::ONE
+                )
+                .err(),
+                Some(ProofError::InvalidGenerators)
+            );
+            assert_eq!(
+                proof.verify(&pc_gens, G, H, &mut transcript, &C_1, &C_2),
+                Err(ProofError::InvalidGenerators)
+            );
+        }
+
+        let invalid = CompressedRistretto([0xff; 32]);
+        assert_eq!(
+            verify(&pc_gens, &proof, &invalid, &C_2),
+            Err(ProofError::FormatError)
+        );
+        util::assert_noncanonical_scalars_rejected(
+            &proof.to_bytes(),
+            &[64, 96, 128],
+            CrossCommitmentProof::from_bytes,
+        );
+        assert!(CrossCommitmentProof::from_bytes(&[0u8; 159]).is_err());
+    }
+}
@@ -0,0 +1,911 @@
+//! The `envelope` module contains API for storing a proof together
+//! with the statement it proves.
+//!
+//! A [`ProofEnvelope`] records the kind of proof, the statement size,
+//! the commitments, and an application context label, and constructs
+//! the verifier transcript from them, so that applications do not have
+//! to agree on an ad-hoc framing for each of these.
+
+#![deny(missing_docs)]
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::string::String;
+#[cfg(feature = "prover")]
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::marker::PhantomData;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "prover")]
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::{SeqAccess, Visitor};
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::parameters::ProofParameters;
+use crate::range_proof::{BitSize, RangeProof};
+use crate::small_vec_opening_proof::SmallVecOpeningProof;
+use crate::transcript::TranscriptProtocol;
+use crate::util;
+
+/// The version of the envelope encoding written by [`ProofEnvelope::to_bytes`].
+const ENVELOPE_VERSION: u8 = 1;
+
+/// The kinds of proof that can be stored in a [`ProofEnvelope`].
+///
+/// The discriminant is the tag byte of the envelope encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u8)]
+pub enum ProofKind {
+    /// An aggregated [`RangeProof`].
+    Range = 1,
+    /// A [`SmallVecOpeningProof`].
+    SmallVecOpening = 2,
+}
+
+impl ProofKind {
+    /// Reads the kind of proof from the first byte of an envelope
+    /// encoding, without parsing the rest.
+    pub fn of_envelope(bytes: &[u8]) -> Result<ProofKind, ProofError> {
+        match bytes.first() {
+            Some(&tag) => ProofKind::try_from(tag),
+            None => Err(ProofError::FormatError),
+        }
+    }
+}
+
+impl TryFrom<u8> for ProofKind {
+    type Error = ProofError;
+
+    fn try_from(tag: u8) -> Result<ProofKind, ProofError> {
+        match tag {
+            1 => Ok(ProofKind::Range),
+            2 => Ok(ProofKind::SmallVecOpening),
+            _ => Err(ProofError::FormatError),
+        }
+    }
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for crate::range_proof::RangeProof {}
+    impl Sealed for crate::small_vec_opening_proof::SmallVecOpeningProof {}
+}
+
+/// A proof that can be stored in a [`ProofEnvelope`].
+///
+/// This trait is sealed: it is implemented for [`RangeProof`] and
+/// [`SmallVecOpeningProof`], and new kinds of proof are added by this
+/// crate together with a new [`ProofKind`].
+pub trait EnvelopeProof: sealed::Sealed + Sized {
+    /// The kind of proof, written as the tag byte of the envelope.
+    const KIND: ProofKind;
+
+    /// Serializes the proof, see e.g. [`RangeProof::to_bytes`].
+    fn proof_to_bytes(&self) -> Vec<u8>;
+
+    /// Deserializes the proof, see e.g. [`RangeProof::from_bytes`].
+    fn proof_from_bytes(bytes: &[u8]) -> Result<Self, ProofError>;
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof of an `n`-sized statement over the
+    /// `commitments` on the `transcript`.
+    fn verify_statement<T: RngCore + CryptoRng>(
+        &self,
+        params: &ProofParameters,
+        transcript: &mut Transcript,
+        n: usize,
+        commitments: &[CompressedRistretto],
+        rng: &mut T,
+    ) -> Result<(), ProofError>;
+}
+
+impl EnvelopeProof for RangeProof {
+    const KIND: ProofKind = ProofKind::Range;
+
+    fn proof_to_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn proof_from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        RangeProof::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "verifier")]
+    fn verify_statement<T: RngCore + CryptoRng>(
+        &self,
+        params: &ProofParameters,
+        transcript: &mut Transcript,
+        n: usize,
+        commitments: &[CompressedRistretto],
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        params.verify_range_with_rng(self, transcript, commitments, BitSize::try_from(n)?, rng)
+    }
+}
+
+impl EnvelopeProof for SmallVecOpeningProof {
+    const KIND: ProofKind = ProofKind::SmallVecOpening;
+
+    fn proof_to_bytes(&self) -> Vec<u8> {
+        self.to_bytes()
+    }
+
+    fn proof_from_bytes(bytes: &[u8]) -> Result<Self, ProofError> {
+        SmallVecOpeningProof::from_bytes(bytes)
+    }
+
+    #[cfg(feature = "verifier")]
+    fn verify_statement<T: RngCore + CryptoRng>(
+        &self,
+        params: &ProofParameters,
+        transcript: &mut Transcript,
+        n: usize,
+        commitments: &[CompressedRistretto],
+        _rng: &mut T,
+    ) -> Result<(), ProofError> {
+        match commitments {
+            [C] => self.verify(
+                params.pc_gens(),
+                &params.bp_gens().try_share(0)?,
+                transcript,
+                C,
+                n,
+            ),
+            _ => Err(ProofError::InvalidInputLength),
+        }
+    }
+}
+
+/// A proof bundled with the statement it proves, for storage or
+/// transmission.
+///
+/// The statement consists of the size `n` (the bitsize of a
+/// [`RangeProof`], or the vector length of a [`SmallVecOpeningProof`]),
+/// the commitments, and an application `context` label.  The prove
+/// functions and [`ProofEnvelope::verify`] both construct the
+/// transcript from the kind of proof and the context, so a proof only
+/// verifies in an envelope of the same kind and context.
+///
+/// # Encoding
+///
+/// [`ProofEnvelope::to_bytes`] writes:
+/// * the [`ProofKind`] tag byte,
+/// * the version byte \\(1\\),
+/// * \\(n\\) as a 4-byte little-endian integer,
+/// * the number of commitments as a 4-byte little-endian integer,
+/// * the compressed commitments, 32 bytes each,
+/// * the length of the context as a 4-byte little-endian integer,
+/// * the context bytes,
+/// * the serialized proof, up to the end of the encoding.
+#[derive(Clone, Debug)]
+pub struct ProofEnvelope<P: EnvelopeProof> {
+    proof: P,
+    n: usize,
+    commitments: Vec<CompressedRistretto>,
+    context: Vec<u8>,
+}
+
+impl<P: EnvelopeProof> ProofEnvelope<P> {
+    /// Wraps a `proof` of an `n`-sized statement over the `commitments`,
+    /// created on a transcript from [`ProofEnvelope::transcript`] with
+    /// the same `context`.
+    pub fn new(
+        proof: P,
+        n: usize,
+        commitments: Vec<CompressedRistretto>,
+        context: Vec<u8>,
+    ) -> Self {
+        ProofEnvelope {
+            proof,
+            n,
+            commitments,
+            context,
+        }
+    }
+
+    /// Returns the transcript on which proofs of this kind are created
+    /// and verified for the given `context`.
+    pub fn transcript(context: &[u8]) -> Transcript {
+        let mut transcript = Transcript::new(b"Bulletproofs ProofEnvelope");
+        transcript.append_message(b"kind", &[P::KIND as u8]);
+        transcript.append_associated_data(context);
+        transcript
+    }
+
+    /// Returns the proof.
+    pub fn proof(&self) -> &P {
+        &self.proof
+    }
+
+    /// Returns the size of the statement.
+    pub fn n(&self) -> usize {
+        self.n
+    }
+
+    /// Returns the commitments of the statement.
+    pub fn commitments(&self) -> &[CompressedRistretto] {
+        &self.commitments
+    }
+
+    /// Returns the application context label.
+    pub fn context(&self) -> &[u8] {
+        &self.context
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof against the statement in the envelope,
+    /// using the generators of `params`.
+    pub fn verify_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        params: &ProofParameters,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let mut transcript = Self::transcript(&self.context);
+        self.proof
+            .verify_statement(params, &mut transcript, self.n, &self.commitments, rng)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof against the statement in the envelope.
+    /// This is a convenience wrapper around [`ProofEnvelope::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify(&self, params: &ProofParameters) -> Result<(), ProofError> {
+        self.verify_with_rng(params, &mut util::default_rng())
+    }
+
+    /// Serializes the envelope into a byte array, see the
+    /// [encoding](ProofEnvelope#encoding).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let proof = self.proof.proof_to_bytes();
+        let mut buf =
+            Vec::with_capacity(14 + 32 * self.commitments.len() + self.context.len() + proof.len());
+        buf.push(P::KIND as u8);
+        buf.push(ENVELOPE_VERSION);
+        buf.extend_from_slice(&(self.n as u32).to_le_bytes());
+        buf.extend_from_slice(&(self.commitments.len() as u32).to_le_bytes());
+        for C in &self.commitments {
+            buf.extend_from_slice(C.as_bytes());
+        }
+        buf.extend_from_slice(&(self.context.len() as u32).to_le_bytes());
+        buf.extend_from_slice(&self.context);
+        buf.extend_from_slice(&proof);
+        buf
+    }
+
+    /// Deserializes the envelope from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the tag is not that of
+    /// `P`, the version is unknown, the lengths do not match the
+    /// slice, or the proof cannot be parsed.
+    pub fn from_bytes(slice: &[u8]) -> Result<Self, ProofError> {
+        if ProofKind::of_envelope(slice)? != P::KIND {
+            return Err(ProofError::FormatError);
+        }
+        let mut rest = &slice[1..];
+        if take(&mut rest, 1)?[0] != ENVELOPE_VERSION {
+            return Err(ProofError::FormatError);
+        }
+        let n = take_u32(&mut rest)? as usize;
+
+        let num_commitments = take_u32(&mut rest)? as usize;
+        let commitments = take(&mut rest, num_commitments.saturating_mul(32))?
+            .chunks(32)
+            .map(|chunk| CompressedRistretto(util::read32(chunk)))
+            .collect();
+
+        let context_len = take_u32(&mut rest)? as usize;
+        let context = take(&mut rest, context_len)?.to_vec();
+
+        let proof = P::proof_from_bytes(rest)?;
+
+        Ok(ProofEnvelope {
+            proof,
+            n,
+            commitments,
+            context,
+        })
+    }
+}
+
+impl ProofEnvelope<RangeProof> {
+    /// Returns the bitsize of the range statement.
+    ///
+    /// Decoding does not check the size of the statement, so this
+    /// returns an error if it is not a supported bitsize, in which case
+    /// the envelope does not verify either.
+    pub fn bit_size(&self) -> Result<BitSize, ProofError> {
+        BitSize::try_from(self.n)
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ProofEnvelope<RangeProof> {
+    /// Creates an aggregated `n`-bit rangeproof for `values` in an
+    /// envelope with the given `context`.
+    ///
+    /// See [`ProofParameters::prove_range_with_rng`].
+    pub fn prove_range_with_rng<T: RngCore + CryptoRng>(
+        params: &ProofParameters,
+        context: &[u8],
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<Self, ProofError> {
+        let mut transcript = Self::transcript(context);
+        let (proof, commitments) =
+            params.prove_range_with_rng(&mut transcript, values, blindings, n, rng)?;
+        Ok(ProofEnvelope::new(
+            proof,
+            n.bits(),
+            commitments,
+            context.to_vec(),
+        ))
+    }
+
+    /// Creates an aggregated `n`-bit rangeproof for `values` in an
+    /// envelope with the given `context`.
+    /// This is a convenience wrapper around [`ProofEnvelope::prove_range_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_range(
+        params: &ProofParameters,
+        context: &[u8],
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+    ) -> Result<Self, ProofError> {
+        Self::prove_range_with_rng(
+            params,
+            context,
+            values,
+            blindings,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+}
+
+#[cfg(feature = "prover")]
+impl ProofEnvelope<SmallVecOpeningProof> {
+    /// Creates a proof of knowledge of the opening of the commitment to
+    /// `values` with `blinding` in an envelope with the given `context`.
+    ///
+    /// See [`SmallVecOpeningProof::prove_with_rng`].
+    pub fn prove_opening_with_rng<T: RngCore + CryptoRng>(
+        params: &ProofParameters,
+        context: &[u8],
+        values: &[Scalar],
+        blinding: &Scalar,
+        rng: &mut T,
+    ) -> Result<Self, ProofError> {
+        let mut transcript = Self::transcript(context);
+        let (proof, commitment) = SmallVecOpeningProof::prove_with_rng(
+            params.pc_gens(),
+            &params.bp_gens().try_share(0)?,
+            &mut transcript,
+            values,
+            blinding,
+            rng,
+        )?;
+        Ok(ProofEnvelope::new(
+            proof,
+            values.len(),
+            vec![commitment],
+            context.to_vec(),
+        ))
+    }
+
+    /// Creates a proof of knowledge of the opening of the commitment to
+    /// `values` with `blinding` in an envelope with the given `context`.
+    /// This is a convenience wrapper around [`ProofEnvelope::prove_opening_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_opening(
+        params: &ProofParameters,
+        context: &[u8],
+        values: &[Scalar],
+        blinding: &Scalar,
+    ) -> Result<Self, ProofError> {
+        Self::prove_opening_with_rng(params, context, values, blinding, &mut util::default_rng())
+    }
+}
+
+/// A transcript shared by the several proofs of one composite
+/// statement, which frames each proof as a section under a unique key.
+///
+/// The keys and kinds of all sections are registered before any proof
+/// is made or verified, and are bound to the transcript together with
+/// an application context.  Each section appends its key, position, and
+/// kind before its proof, and its position after it, so a proof only
+/// verifies under the key and in the position it was made in: swapping
+/// two proofs fails verification even if each of them is valid on its
+/// own.  Taking the sections under other keys or kinds, or in another
+/// order than they were registered in, fails with
+/// [`ProofError::SectionMismatch`] before the proof is run.
+///
+/// ```
+/// use bulletproofs::{BitSize, BulletproofGens, EnvelopeTranscript, PedersenGens, ProofKind, RangeProof};
+/// use curve25519_dalek::scalar::Scalar;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(32, 1);
+/// let envelope = || {
+///     let mut transcript = EnvelopeTranscript::new(b"tx-42");
+///     transcript.register("amount", ProofKind::Range)?;
+///     transcript.register("fee", ProofKind::Range)?;
+///     Ok::<_, bulletproofs::ProofError>(transcript)
+/// };
+///
+/// let mut transcript = envelope()?;
+/// let (amount, V_amount) = transcript.section("amount", ProofKind::Range, |t| {
+///     RangeProof::prove_single(&bp_gens, &pc_gens, t, 1000, &Scalar::from(3u64), BitSize::B32)
+/// })?;
+/// let (fee, V_fee) = transcript.section("fee", ProofKind::Range, |t| {
+///     RangeProof::prove_single(&bp_gens, &pc_gens, t, 7, &Scalar::from(4u64), BitSize::B32)
+/// })?;
+/// transcript.finish()?;
+///
+/// let mut transcript = envelope()?;
+/// transcript.section("amount", ProofKind::Range, |t| {
+///     amount.verify_single(&bp_gens, &pc_gens, t, &V_amount, BitSize::B32)
+/// })?;
+/// transcript.section("fee", ProofKind::Range, |t| {
+///     fee.verify_single(&bp_gens, &pc_gens, t, &V_fee, BitSize::B32)
+/// })?;
+/// transcript.finish()?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+#[derive(Clone)]
+pub struct EnvelopeTranscript {
+    transcript: Transcript,
+    sections: Vec<(String, ProofKind)>,
+    taken: usize,
+}
+
+impl EnvelopeTranscript {
+    /// Starts an envelope transcript bound to the application `context`.
+    pub fn new(context: &[u8]) -> Self {
+        let mut transcript = Transcript::new(b"Bulletproofs EnvelopeTranscript");
+        transcript.append_associated_data(context);
+        EnvelopeTranscript {
+            transcript,
+            sections: Vec::new(),
+            taken: 0,
+        }
+    }
+
+    /// Registers the next section, for a proof of the given `kind`
+    /// under `key`.
+    ///
+    /// Returns [`ProofError::SectionMismatch`] if `key` is already
+    /// registered, or if a section was already taken.
+    pub fn register(&mut self, key: &str, kind: ProofKind) -> Result<(), ProofError> {
+        if self.taken > 0 || self.sections.iter().any(|(other, _)| other == key) {
+            return Err(ProofError::SectionMismatch {
+                index: self.sections.len(),
+            });
+        }
+        self.sections.push((key.into(), kind));
+        Ok(())
+    }
+
+    /// Returns the keys and kinds of the registered sections, in order.
+    pub fn sections(&self) -> impl Iterator<Item = (&str, ProofKind)> {
+        self.sections
+            .iter()
+            .map(|(key, kind)| (key.as_str(), *kind))
+    }
+
+    /// Runs `proof`, which creates or verifies a proof of the given
+    /// `kind`, on the transcript of the next section, which must have
+    /// been registered under `key` for that kind.
+    ///
+    /// Returns [`ProofError::SectionMismatch`] if the next registered
+    /// section is another, and the result of `proof` otherwise.  A
+    /// section is taken even if its proof fails.
+    pub fn section<R>(
+        &mut self,
+        key: &str,
+        kind: ProofKind,
+        proof: impl FnOnce(&mut Transcript) -> Result<R, ProofError>,
+    ) -> Result<R, ProofError> {
+        let index = self.taken;
+        match self.sections.get(index) {
+            Some((registered, registered_kind))
+                if registered == key && *registered_kind == kind => {}
+            _ => return Err(ProofError::SectionMismatch { index }),
+        }
+        if index == 0 {
+            self.transcript
+                .envelope_transcript_domain_sep(self.sections.len());
+            for (key, kind) in &self.sections {
+                self.transcript.append_len(b"key-len", key.len());
+                self.transcript.append_message(b"key", key.as_bytes());
+                self.transcript.append_message(b"kind", &[*kind as u8]);
+            }
+        }
+        self.taken += 1;
+
+        self.transcript.append_u64(b"section", index as u64);
+        self.transcript.append_len(b"key-len", key.len());
+        self.transcript.append_message(b"key", key.as_bytes());
+        self.transcript.append_message(b"kind", &[kind as u8]);
+        let result = proof(&mut self.transcript);
+        self.transcript.append_u64(b"section-end", index as u64);
+        result
+    }
+
+    /// Checks that every registered section was taken.
+    ///
+    /// Returns [`ProofError::SectionMismatch`] with the position of the
+    /// first section that was not.
+    pub fn finish(self) -> Result<(), ProofError> {
+        if self.taken == self.sections.len() {
+            Ok(())
+        } else {
+            Err(ProofError::SectionMismatch { index: self.taken })
+        }
+    }
+}
+
+/// Splits the first `len` bytes off `rest`.
+fn take<'a>(rest: &mut &'a [u8], len: usize) -> Result<&'a [u8], ProofError> {
+    if rest.len() < len {
+        return Err(ProofError::FormatError);
+    }
+    let (head, tail) = rest.split_at(len);
+    *rest = tail;
+    Ok(head)
+}
+
+/// Splits a 4-byte little-endian integer off `rest`.
+fn take_u32(rest: &mut &[u8]) -> Result<u32, ProofError> {
+    let mut buf = [0u8; 4];
+    buf.copy_from_slice(take(rest, 4)?);
+    Ok(u32::from_le_bytes(buf))
+}
+
+impl<P: EnvelopeProof> Serialize for ProofEnvelope<P> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de, P: EnvelopeProof> Deserialize<'de> for ProofEnvelope<P> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct ProofEnvelopeVisitor<P>(PhantomData<P>);
+
+        impl<'de, P: EnvelopeProof> Visitor<'de> for ProofEnvelopeVisitor<P> {
+            type Value = ProofEnvelope<P>;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid ProofEnvelope")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<ProofEnvelope<P>, E>
+            where
+                E: serde::de::Error,
+            {
+                ProofEnvelope::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+
+            // Formats without a native byte string, such as JSON,
+            // encode the bytes as a sequence.
+            fn visit_seq<A>(self, mut seq: A) -> Result<ProofEnvelope<P>, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0).min(4096));
+                while let Some(byte) = seq.next_element::<u8>()? {
+                    bytes.push(byte);
+                }
+                self.visit_bytes(&bytes)
+            }
+        }
+
+        deserializer.deserialize_bytes(ProofEnvelopeVisitor(PhantomData))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn params() -> ProofParameters {
+        ProofParameters::for_range_proofs(32, 2).unwrap()
+    }
+
+    fn range_envelope(context: &[u8]) -> ProofEnvelope<RangeProof> {
+        ProofEnvelope::prove_range(
+            &params(),
+            context,
+            &[7, 1 << 20],
+            &[Scalar::ONE, Scalar::from(2u64)],
+            BitSize::B32,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn range_envelope_round_trip() {
+        let params = params();
+        let envelope = range_envelope(b"tx-42");
+        assert!(envelope.verify(&params).is_ok());
+
+        let bytes = envelope.to_bytes();
+        assert_eq!(ProofKind::of_envelope(&bytes), Ok(ProofKind::Range));
+        let parsed = ProofEnvelope::<RangeProof>::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.n(), 32);
+        assert_eq!(parsed.commitments(), envelope.commitments());
+        assert_eq!(parsed.context(), b"tx-42");
+        assert_eq!(parsed.to_bytes(), bytes);
+        assert!(parsed.verify(&params).is_ok());
+
+        let json = serde_json::to_string(&envelope).unwrap();
+        let deserialized: ProofEnvelope<RangeProof> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn bit_size_round_trips_through_the_envelope() {
+        let params = ProofParameters::for_range_proofs(64, 1).unwrap();
+        for &bit_size in &BitSize::ALL {
+            let envelope =
+                ProofEnvelope::prove_range(&params, b"ctx", &[3], &[Scalar::ONE], bit_size)
+                    .unwrap();
+            let parsed = ProofEnvelope::<RangeProof>::from_bytes(&envelope.to_bytes()).unwrap();
+            assert_eq!(parsed.bit_size(), Ok(bit_size));
+
+            let json = serde_json::to_string(&envelope).unwrap();
+            let deserialized: ProofEnvelope<RangeProof> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized.bit_size(), Ok(bit_size));
+        }
+
+        let envelope = range_envelope(b"tx-42");
+        let unsupported = ProofEnvelope::new(
+            envelope.proof().clone(),
+            63,
+            envelope.commitments().to_vec(),
+            b"tx-42".to_vec(),
+        );
+        let parsed = ProofEnvelope::<RangeProof>::from_bytes(&unsupported.to_bytes()).unwrap();
+        assert_eq!(parsed.bit_size(), Err(ProofError::InvalidBitsize));
+    }
+
+    #[test]
+    fn proof_kind_json_round_trip() {
+        for &kind in &[ProofKind::Range, ProofKind::SmallVecOpening] {
+            let json = serde_json::to_string(&kind).unwrap();
+            assert_eq!(serde_json::from_str::<ProofKind>(&json).unwrap(), kind);
+        }
+        assert_eq!(
+            serde_json::to_string(&ProofKind::Range).unwrap(),
+            "\"Range\""
+        );
+    }
+
+    #[test]
+    fn opening_envelope_round_trip() {
+        let params = params();
+        let values = [Scalar::from(3u64), Scalar::from(5u64), Scalar::ONE];
+        let envelope =
+            ProofEnvelope::prove_opening(&params, b"ctx", &values, &Scalar::from(9u64)).unwrap();
+        assert!(envelope.verify(&params).is_ok());
+
+        let bytes = envelope.to_bytes();
+        assert_eq!(
+            ProofKind::of_envelope(&bytes),
+            Ok(ProofKind::SmallVecOpening)
+        );
+        let parsed = ProofEnvelope::<SmallVecOpeningProof>::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.n(), 3);
+        assert!(parsed.verify(&params).is_ok());
+    }
+
+    #[test]
+    fn statement_is_bound_to_the_proof() {
+        let params = params();
+        let envelope = range_envelope(b"tx-42");
+
+        // Another context.
+        let moved = ProofEnvelope::new(
+            envelope.proof().clone(),
+            envelope.n(),
+            envelope.commitments().to_vec(),
+            b"tx-43".to_vec(),
+        );
+        assert!(moved.verify(&params).is_err());
+
+        // Another statement size or set of commitments.
+        let resized = ProofEnvelope::new(
+            envelope.proof().clone(),
+            16,
+            envelope.commitments().to_vec(),
+            b"tx-42".to_vec(),
+        );
+        assert!(resized.verify(&params).is_err());
+        let reordered = ProofEnvelope::new(
+            envelope.proof().clone(),
+            32,
+            envelope.commitments().iter().rev().cloned().collect(),
+            b"tx-42".to_vec(),
+        );
+        assert!(reordered.verify(&params).is_err());
+    }
+
+    #[test]
+    fn kinds_cannot_be_confused() {
+        let range_bytes = range_envelope(b"ctx").to_bytes();
+        let opening_bytes =
+            ProofEnvelope::prove_opening(&params(), b"ctx", &[Scalar::ONE], &Scalar::ONE)
+                .unwrap()
+                .to_bytes();
+
+        assert_eq!(
+            ProofEnvelope::<SmallVecOpeningProof>::from_bytes(&range_bytes).err(),
+            Some(ProofError::FormatError)
+        );
+        assert_eq!(
+            ProofEnvelope::<RangeProof>::from_bytes(&opening_bytes).err(),
+            Some(ProofError::FormatError)
+        );
+
+        // Retagging the envelope does not help, since the proof does
+        // not parse as the other kind.
+        let mut retagged = range_bytes.clone();
+        retagged[0] = ProofKind::SmallVecOpening as u8;
+        assert!(ProofEnvelope::<SmallVecOpeningProof>::from_bytes(&retagged).is_err());
+    }
+
+    #[test]
+    fn malformed_envelopes_are_rejected() {
+        let bytes = range_envelope(b"ctx").to_bytes();
+
+        assert_eq!(ProofKind::of_envelope(&[]), Err(ProofError::FormatError));
+        assert_eq!(ProofKind::of_envelope(&[0]), Err(ProofError::FormatError));
+        for len in [0, 1, 2, 6, 10, 10 + 64, 14 + 64 + 2] {
+            assert_eq!(
+                ProofEnvelope::<RangeProof>::from_bytes(&bytes[..len]).err(),
+                Some(ProofError::FormatError)
+            );
+        }
+
+        let mut wrong_version = bytes.clone();
+        wrong_version[1] = 2;
+        assert_eq!(
+            ProofEnvelope::<RangeProof>::from_bytes(&wrong_version).err(),
+            Some(ProofError::FormatError)
+        );
+
+        // A commitment count larger than the encoding.
+        let mut too_many = bytes;
+        too_many[6..10].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(
+            ProofEnvelope::<RangeProof>::from_bytes(&too_many).err(),
+            Some(ProofError::FormatError)
+        );
+    }
+
+    /// An envelope transcript with a range proof registered under each
+    /// of the `keys`.
+    fn envelope_transcript(keys: &[&str]) -> EnvelopeTranscript {
+        let mut transcript = EnvelopeTranscript::new(b"tx-42");
+        for key in keys {
+            transcript.register(key, ProofKind::Range).unwrap();
+        }
+        transcript
+    }
+
+    #[test]
+    fn swapped_sections_are_rejected() {
+        let params = params();
+        let values = [(5u64, Scalar::ONE), (9u64, Scalar::from(2u64))];
+
+        let mut transcript = envelope_transcript(&["amount", "fee"]);
+        let proofs: Vec<(RangeProof, CompressedRistretto)> = ["amount", "fee"]
+            .iter()
+            .zip(&values)
+            .map(|(key, (v, blinding))| {
+                transcript
+                    .section(key, ProofKind::Range, |t| {
+                        RangeProof::prove_single(
+                            params.bp_gens(),
+                            params.pc_gens(),
+                            t,
+                            *v,
+                            blinding,
+                            BitSize::B32,
+                        )
+                    })
+                    .unwrap()
+            })
+            .collect();
+        assert!(transcript.finish().is_ok());
+
+        let verify = |order: [usize; 2]| {
+            let mut transcript = envelope_transcript(&["amount", "fee"]);
+            for (key, &i) in ["amount", "fee"].iter().zip(&order) {
+                let (proof, V) = &proofs[i];
+                transcript.section(key, ProofKind::Range, |t| {
+                    proof.verify_single(params.bp_gens(), params.pc_gens(), t, V, BitSize::B32)
+                })?;
+            }
+            transcript.finish()
+        };
+        assert!(verify([0, 1]).is_ok());
+        assert!(verify([1, 0]).unwrap_err().is_verification_failure());
+
+        // Each proof is valid in its own section, but only there.
+        let mut transcript = envelope_transcript(&["amount", "fee"]);
+        let (proof, V) = &proofs[1];
+        assert!(transcript
+            .section("amount", ProofKind::Range, |t| {
+                proof.verify_single(params.bp_gens(), params.pc_gens(), t, V, BitSize::B32)
+            })
+            .unwrap_err()
+            .is_verification_failure());
+        let mut transcript = envelope_transcript(&["fee", "amount"]);
+        let (proof, V) = &proofs[0];
+        assert!(transcript
+            .section("fee", ProofKind::Range, |t| {
+                proof.verify_single(params.bp_gens(), params.pc_gens(), t, V, BitSize::B32)
+            })
+            .unwrap_err()
+            .is_verification_failure());
+    }
+
+    #[test]
+    fn sections_must_match_the_registration() {
+        let mismatch = |index| Err(ProofError::SectionMismatch { index });
+        let run = |_: &mut Transcript| Ok(());
+
+        // A key registered twice, or after a section was taken.
+        let mut transcript = envelope_transcript(&["amount"]);
+        assert_eq!(
+            transcript.register("amount", ProofKind::SmallVecOpening),
+            mismatch(1)
+        );
+        assert!(transcript.section("amount", ProofKind::Range, run).is_ok());
+        assert_eq!(transcript.register("fee", ProofKind::Range), mismatch(1));
+
+        // Sections under other keys, kinds, or beyond the registration.
+        let mut transcript = envelope_transcript(&["amount", "fee"]);
+        assert_eq!(
+            transcript.sections().collect::<Vec<_>>(),
+            vec![("amount", ProofKind::Range), ("fee", ProofKind::Range)]
+        );
+        assert_eq!(
+            transcript.section("fee", ProofKind::Range, run),
+            mismatch(0)
+        );
+        assert_eq!(
+            transcript.section("amount", ProofKind::SmallVecOpening, run),
+            mismatch(0)
+        );
+        assert!(transcript.section("amount", ProofKind::Range, run).is_ok());
+        assert_eq!(transcript.clone().finish(), mismatch(1));
+        assert!(transcript.section("fee", ProofKind::Range, run).is_ok());
+        assert_eq!(
+            transcript.section("tip", ProofKind::Range, run),
+            mismatch(2)
+        );
+        assert!(transcript.finish().is_ok());
+    }
+}
@@ -1,69 +1,244 @@
 //! Errors related to proving and verifying proofs.
 
 extern crate alloc;
+use alloc::string::String;
 use alloc::vec::Vec;
 
-#[cfg(feature = "std")]
-use thiserror::Error;
+use core::convert::TryFrom;
+use core::fmt;
 
 /// Represents an error in proof creation, verification, or parsing.
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "std", derive(Error))]
+///
+/// With `serde`, errors serialize as their variant name under `kind`,
+/// and the fields of the variant, if any, under `details`.
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "details")]
 pub enum ProofError {
     /// This error occurs when a proof failed to verify.
-    #[cfg_attr(feature = "std", error("Proof verification failed."))]
     VerificationError,
     /// This error occurs when the proof encoding is malformed.
-    #[cfg_attr(feature = "std", error("Proof data could not be parsed."))]
     FormatError,
     /// This error occurs during proving if the number of blinding
     /// factors does not match the number of values.
-    #[cfg_attr(feature = "std", error("Wrong number of blinding factors supplied."))]
     WrongNumBlindingFactors,
     /// This error occurs when attempting to create a proof with
     /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[cfg_attr(feature = "std", error("Invalid bitsize, must have n = 8,16,32,64."))]
     InvalidBitsize,
     /// This error occurs when attempting to create an aggregated
     /// proof with non-power-of-two aggregation size.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid aggregation size, m must be a power of 2.")
-    )]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid generators size, too few generators for proof")
-    )]
     InvalidGeneratorsLength,
+    /// This error occurs when imported generators include the
+    /// identity or the same point twice.
+    InvalidGenerators,
     /// This error occurs when inputs are the incorrect length for the proof.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid input size, incorrect input length for proof")
-    )]
     InvalidInputLength,
+    /// This error occurs when the statement exceeds the largest size
+    /// supported by the implementation, see [`MAX_BITSIZE`](crate::MAX_BITSIZE)
+    /// and [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH).
+    StatementTooLarge {
+        /// The size requested by the caller.
+        requested: usize,
+        /// The largest supported size.
+        max: usize,
+    },
+    /// This error occurs when a range proof is verified for a bitsize
+    /// other than the one it was created for.
+    BitsizeMismatch {
+        /// The bitsize given by the caller.
+        claimed: usize,
+        /// The bitsize of the proof, for each value.
+        proven: usize,
+    },
+    /// This error occurs when requesting the generators of a party
+    /// beyond the party capacity of the generators.
+    ShareOutOfRange {
+        /// The index of the party.
+        index: usize,
+        /// The number of parties the generators support.
+        party_capacity: usize,
+    },
+    /// This error occurs when verification would need more memory than
+    /// [`VerificationOptions::max_memory_bytes`](crate::VerificationOptions::max_memory_bytes)
+    /// allows.
+    ResourceLimitExceeded {
+        /// The estimated number of bytes needed.
+        required: usize,
+        /// The budget in bytes.
+        limit: usize,
+    },
+    /// This error occurs when the sections of an
+    /// [`EnvelopeTranscript`](crate::EnvelopeTranscript) are not taken
+    /// under the keys and kinds they were registered with, in the same
+    /// order, or a key is registered twice.
+    SectionMismatch {
+        /// The position of the offending section.
+        index: usize,
+    },
+    /// This error occurs when a proof created with
+    /// [`ProveOptions::self_verify`](crate::ProveOptions::self_verify)
+    /// does not verify.
+    SelfCheckFailed,
+    /// This error occurs when a
+    /// [`RangeStatement`](crate::RangeStatement) is built with a value
+    /// that is not less than \\(2\^n\\).
+    ValueOutOfRange,
+    /// This error occurs instead of [`ProofError::VerificationError`]
+    /// when a range proof fails its verification equation and the
+    /// `diagnostics` feature is enabled, which checks the two equations
+    /// it combines separately.
+    FailedChecks {
+        /// Whether \(t(x)\) does not open the polynomial commitments
+        /// \(T\_1, T\_2\) and the value commitments.
+        polynomial: bool,
+        /// Whether the inner-product argument does not show \(t(x)\)
+        /// to be the inner product of the committed vectors.
+        inner_product: bool,
+    },
     /// This error results from an internal error during proving.
     ///
     /// The single-party prover is implemented by performing
     /// multiparty computation with ourselves.  However, because the
     /// MPC protocol is not exposed by the single-party API, we
     /// consider its errors to be internal errors.
-    #[cfg_attr(feature = "std", error("Internal error during proof creation: {0}"))]
     ProvingError(MPCError),
 }
 
+impl ProofError {
+    /// Returns whether this error reports a proof that failed to
+    /// verify, as [`ProofError::VerificationError`] or, with the
+    /// `diagnostics` feature, [`ProofError::FailedChecks`].
+    ///
+    /// Since features are unified across a build, callers that only
+    /// need to know whether verification failed should use this
+    /// instead of matching on `VerificationError`.
+    pub fn is_verification_failure(&self) -> bool {
+        matches!(
+            self,
+            ProofError::VerificationError | ProofError::FailedChecks { .. }
+        )
+    }
+}
+
+impl fmt::Display for ProofError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProofError::VerificationError => write!(f, "Proof verification failed."),
+            ProofError::FormatError => write!(f, "Proof data could not be parsed."),
+            ProofError::WrongNumBlindingFactors => {
+                write!(f, "Wrong number of blinding factors supplied.")
+            }
+            ProofError::InvalidBitsize => write!(f, "Invalid bitsize, must have n = 8,16,32,64."),
+            ProofError::InvalidAggregation => {
+                write!(f, "Invalid aggregation size, m must be a power of 2.")
+            }
+            ProofError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            ProofError::InvalidGenerators => {
+                write!(
+                    f,
+                    "Invalid generators, must be distinct and not the identity"
+                )
+            }
+            ProofError::InvalidInputLength => {
+                write!(f, "Invalid input size, incorrect input length for proof")
+            }
+            ProofError::StatementTooLarge { requested, max } => write!(
+                f,
+                "Statement too large: size {} exceeds the maximum of {}.",
+                requested, max
+            ),
+            ProofError::BitsizeMismatch { claimed, proven } => write!(
+                f,
+                "Bitsize mismatch: the proof is for {} bits, but {} were claimed.",
+                proven, claimed
+            ),
+            ProofError::ShareOutOfRange {
+                index,
+                party_capacity,
+            } => write!(
+                f,
+                "Generator share {} is out of range for a party capacity of {}.",
+                index, party_capacity
+            ),
+            ProofError::SectionMismatch { index } => write!(
+                f,
+                "Envelope section {} does not match its registration.",
+                index
+            ),
+            ProofError::ResourceLimitExceeded { required, limit } => write!(
+                f,
+                "Resource limit exceeded: verification needs {} bytes, but the limit is {}.",
+                required, limit
+            ),
+            ProofError::SelfCheckFailed => {
+                write!(
+                    f,
+                    "Proof self-check failed: the created proof does not verify."
+                )
+            }
+            ProofError::ValueOutOfRange => {
+                write!(
+                    f,
+                    "A value of the range statement does not fit in its bitsize."
+                )
+            }
+            ProofError::FailedChecks {
+                polynomial,
+                inner_product,
+            } => write!(
+                f,
+                "Proof verification failed: polynomial check {}, inner-product check {}.",
+                if *polynomial { "failed" } else { "passed" },
+                if *inner_product { "failed" } else { "passed" }
+            ),
+            ProofError::ProvingError(e) => write!(f, "Internal error during proof creation: {}", e),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            ProofError::ProvingError(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl From<MPCError> for ProofError {
     fn from(e: MPCError) -> ProofError {
         match e {
             MPCError::InvalidBitsize => ProofError::InvalidBitsize,
             MPCError::InvalidAggregation => ProofError::InvalidAggregation,
             MPCError::InvalidGeneratorsLength => ProofError::InvalidGeneratorsLength,
+            MPCError::InsufficientPartyCapacity { .. } => ProofError::InvalidGeneratorsLength,
             _ => ProofError::ProvingError(e),
         }
     }
 }
 
+/// Recovers the [`MPCError`] that a [`ProofError`] was converted from.
+///
+/// Errors that did not come from the aggregation protocol are returned
+/// unchanged.
+impl TryFrom<ProofError> for MPCError {
+    type Error = ProofError;
+
+    fn try_from(e: ProofError) -> Result<MPCError, ProofError> {
+        match e {
+            ProofError::InvalidBitsize => Ok(MPCError::InvalidBitsize),
+            ProofError::InvalidAggregation => Ok(MPCError::InvalidAggregation),
+            ProofError::InvalidGeneratorsLength => Ok(MPCError::InvalidGeneratorsLength),
+            ProofError::ProvingError(e) => Ok(e),
+            e => Err(e),
+        }
+    }
+}
+
 /// Represents an error during the multiparty computation protocol for
 /// proof aggregation.
 ///
@@ -71,87 +246,186 @@ impl From<MPCError> for ProofError {
 /// API: although the MPC protocol is used internally for single-party
 /// proving, its API should not expose the complexity of the MPC
 /// protocol.
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "std", derive(Error))]
+///
+/// Errors serialize like [`ProofError`].
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "details")]
 pub enum MPCError {
     /// This error occurs when the dealer gives a zero challenge,
     /// which would annihilate the blinding factors.
-    #[cfg_attr(feature = "std", error("Dealer gave a malicious challenge value."))]
     MaliciousDealer,
     /// This error occurs when attempting to create a proof with
     /// bitsize other than \\(8\\), \\(16\\), \\(32\\), or \\(64\\).
-    #[cfg_attr(feature = "std", error("Invalid bitsize, must have n = 8,16,32,64"))]
     InvalidBitsize,
     /// This error occurs when attempting to create an aggregated
     /// proof with non-power-of-two aggregation size.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid aggregation size, m must be a power of 2")
-    )]
     InvalidAggregation,
     /// This error occurs when there are insufficient generators for the proof.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid generators size, too few generators for proof")
-    )]
     InvalidGeneratorsLength,
+    /// This error occurs when the generators have fewer party shares
+    /// than the aggregation needs.
+    InsufficientPartyCapacity {
+        /// The number of parties the aggregation needs.
+        required_parties: usize,
+        /// The number of parties the generators support.
+        available: usize,
+    },
+    /// This error occurs when the dealer receives bit commitments made
+    /// for positions other than their place in the aggregation, for
+    /// instance because two parties were assigned the same position.
+    MismatchedPositions {
+        /// A vector with the indexes of the bit commitments made at the
+        /// wrong position.
+        bad_positions: Vec<usize>,
+    },
     /// This error occurs when the dealer is given the wrong number of
     /// value commitments.
-    #[cfg_attr(feature = "std", error("Wrong number of value commitments"))]
     WrongNumBitCommitments,
     /// This error occurs when the dealer is given the wrong number of
     /// polynomial commitments.
-    #[cfg_attr(feature = "std", error("Wrong number of value commitments"))]
     WrongNumPolyCommitments,
     /// This error occurs when the dealer is given the wrong number of
     /// proof shares.
-    #[cfg_attr(feature = "std", error("Wrong number of proof shares"))]
     WrongNumProofShares,
     /// This error occurs when one or more parties submit malformed
     /// proof shares.
-    #[cfg_attr(
-        feature = "std",
-        error("Malformed proof shares from parties {bad_shares:?}")
-    )]
     MalformedProofShares {
         /// A vector with the indexes of the parties whose shares were malformed.
         bad_shares: Vec<usize>,
     },
+    /// This error occurs when the dealer aborts the aggregation, for
+    /// instance because a party stopped responding.
+    Aborted {
+        /// The reason given by the dealer.
+        reason: String,
+    },
+    /// This error occurs when the dealer is asked to proceed without
+    /// parties that are out of range or listed twice, or without all
+    /// of the parties.
+    InvalidMissingParties {
+        /// The indexes of the missing parties given to the dealer.
+        missing: Vec<usize>,
+    },
+}
+
+impl fmt::Display for MPCError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MPCError::MaliciousDealer => write!(f, "Dealer gave a malicious challenge value."),
+            MPCError::InvalidBitsize => write!(f, "Invalid bitsize, must have n = 8,16,32,64"),
+            MPCError::InvalidAggregation => {
+                write!(f, "Invalid aggregation size, m must be a power of 2")
+            }
+            MPCError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            MPCError::InsufficientPartyCapacity {
+                required_parties,
+                available,
+            } => write!(
+                f,
+                "Generators support {} parties, but {} are required",
+                available, required_parties
+            ),
+            MPCError::MismatchedPositions { bad_positions } => write!(
+                f,
+                "Bit commitments from parties {:?} were made at the wrong position",
+                bad_positions
+            ),
+            MPCError::WrongNumBitCommitments => write!(f, "Wrong number of value commitments"),
+            MPCError::WrongNumPolyCommitments => {
+                write!(f, "Wrong number of polynomial commitments")
+            }
+            MPCError::WrongNumProofShares => write!(f, "Wrong number of proof shares"),
+            MPCError::MalformedProofShares { bad_shares } => {
+                write!(f, "Malformed proof shares from parties {:?}", bad_shares)
+            }
+            MPCError::Aborted { reason } => {
+                write!(f, "Aggregation aborted by the dealer: {}", reason)
+            }
+            MPCError::InvalidMissingParties { missing } => {
+                write!(f, "Invalid set of missing parties {:?}", missing)
+            }
+        }
+    }
 }
 
+#[cfg(feature = "std")]
+impl std::error::Error for MPCError {}
+
 /// Represents an error during the proving or verifying of a constraint system.
 ///
+/// Errors serialize like [`ProofError`].
+///
 /// XXX: should this be separate from a `ProofError`?
 #[cfg(feature = "yoloproofs")]
-#[derive(Clone, Debug, Eq, PartialEq)]
-#[cfg_attr(feature = "std", derive(Error))]
+#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
+#[serde(tag = "kind", content = "details")]
 pub enum R1CSError {
     /// Occurs when there are insufficient generators for the proof.
-    #[cfg_attr(
-        feature = "std",
-        error("Invalid generators size, too few generators for proof")
-    )]
     InvalidGeneratorsLength,
     /// This error occurs when the proof encoding is malformed.
-    #[cfg_attr(feature = "std", error("Proof data could not be parsed."))]
     FormatError,
     /// Occurs when verification of an
     /// [`R1CSProof`](::r1cs::R1CSProof) fails.
-    #[cfg_attr(feature = "std", error("R1CSProof did not verify correctly."))]
     VerificationError,
 
     /// Occurs when trying to use a missing variable assignment.
     /// Used by gadgets that build the constraint system to signal that
     /// a variable assignment is not provided when the prover needs it.
-    #[cfg_attr(feature = "std", error("Variable does not have a value assignment."))]
     MissingAssignment,
 
+    /// Occurs when the constraint system has more multipliers than
+    /// [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH).
+    StatementTooLarge {
+        /// The number of multipliers requested by the caller.
+        requested: usize,
+        /// The largest supported number of multipliers.
+        max: usize,
+    },
+
     /// Occurs when a gadget receives an inconsistent input.
-    #[cfg_attr(feature = "std", error("Gadget error: {description:?}"))]
     GadgetError {
         /// The description of the reasons for the error.
         description: String,
     },
+
+    /// Occurs when a [`ProofError`] without a counterpart here is
+    /// converted into an `R1CSError`.
+    ProofError(ProofError),
+}
+
+#[cfg(feature = "yoloproofs")]
+impl fmt::Display for R1CSError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            R1CSError::InvalidGeneratorsLength => {
+                write!(f, "Invalid generators size, too few generators for proof")
+            }
+            R1CSError::FormatError => write!(f, "Proof data could not be parsed."),
+            R1CSError::VerificationError => write!(f, "R1CSProof did not verify correctly."),
+            R1CSError::MissingAssignment => write!(f, "Variable does not have a value assignment."),
+            R1CSError::StatementTooLarge { requested, max } => write!(
+                f,
+                "Statement too large: size {} exceeds the maximum of {}.",
+                requested, max
+            ),
+            R1CSError::GadgetError { description } => {
+                write!(f, "Gadget error: {:?}", description)
+            }
+            R1CSError::ProofError(e) => write!(f, "Error in a range or opening proof: {}", e),
+        }
+    }
+}
+
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+impl std::error::Error for R1CSError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            R1CSError::ProofError(e) => Some(e),
+            _ => None,
+        }
+    }
 }
 
 #[cfg(feature = "yoloproofs")]
@@ -161,7 +435,227 @@ impl From<ProofError> for R1CSError {
             ProofError::InvalidGeneratorsLength => R1CSError::InvalidGeneratorsLength,
             ProofError::FormatError => R1CSError::FormatError,
             ProofError::VerificationError => R1CSError::VerificationError,
-            _ => panic!("unexpected error type in conversion"),
+            ProofError::StatementTooLarge { requested, max } => {
+                R1CSError::StatementTooLarge { requested, max }
+            }
+            e => R1CSError::ProofError(e),
+        }
+    }
+}
+
+/// Recovers the [`ProofError`] that an [`R1CSError`] was converted from.
+///
+/// Errors specific to constraint systems are returned unchanged.
+#[cfg(feature = "yoloproofs")]
+impl TryFrom<R1CSError> for ProofError {
+    type Error = R1CSError;
+
+    fn try_from(e: R1CSError) -> Result<ProofError, R1CSError> {
+        match e {
+            R1CSError::InvalidGeneratorsLength => Ok(ProofError::InvalidGeneratorsLength),
+            R1CSError::FormatError => Ok(ProofError::FormatError),
+            R1CSError::VerificationError => Ok(ProofError::VerificationError),
+            R1CSError::StatementTooLarge { requested, max } => {
+                Ok(ProofError::StatementTooLarge { requested, max })
+            }
+            R1CSError::ProofError(e) => Ok(e),
+            e => Err(e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    /// One instance of every `ProofError` variant.
+    fn proof_errors() -> Vec<ProofError> {
+        vec![
+            ProofError::VerificationError,
+            ProofError::FormatError,
+            ProofError::WrongNumBlindingFactors,
+            ProofError::InvalidBitsize,
+            ProofError::InvalidAggregation,
+            ProofError::InvalidGeneratorsLength,
+            ProofError::InvalidGenerators,
+            ProofError::InvalidInputLength,
+            ProofError::StatementTooLarge {
+                requested: 128,
+                max: 64,
+            },
+            ProofError::BitsizeMismatch {
+                claimed: 64,
+                proven: 32,
+            },
+            ProofError::ShareOutOfRange {
+                index: 2,
+                party_capacity: 2,
+            },
+            ProofError::SelfCheckFailed,
+            ProofError::ValueOutOfRange,
+            ProofError::FailedChecks {
+                polynomial: false,
+                inner_product: true,
+            },
+            ProofError::ProvingError(MPCError::MaliciousDealer),
+        ]
+    }
+
+    /// One instance of every `MPCError` variant.
+    fn mpc_errors() -> Vec<MPCError> {
+        vec![
+            MPCError::MaliciousDealer,
+            MPCError::InvalidBitsize,
+            MPCError::InvalidAggregation,
+            MPCError::InvalidGeneratorsLength,
+            MPCError::InsufficientPartyCapacity {
+                required_parties: 8,
+                available: 4,
+            },
+            MPCError::MismatchedPositions {
+                bad_positions: vec![2],
+            },
+            MPCError::WrongNumBitCommitments,
+            MPCError::WrongNumPolyCommitments,
+            MPCError::WrongNumProofShares,
+            MPCError::MalformedProofShares {
+                bad_shares: vec![1],
+            },
+            MPCError::Aborted {
+                reason: "timeout".into(),
+            },
+            MPCError::InvalidMissingParties { missing: vec![9] },
+        ]
+    }
+
+    /// Checks that the messages of `errors` are nonempty and distinct.
+    fn assert_distinct_messages<E: fmt::Display>(errors: &[E]) {
+        let mut messages: Vec<_> = errors.iter().map(|e| e.to_string()).collect();
+        assert!(messages.iter().all(|m| !m.is_empty()));
+        messages.sort();
+        messages.dedup();
+        assert_eq!(messages.len(), errors.len());
+    }
+
+    #[test]
+    fn verification_failures_are_recognized() {
+        for e in proof_errors() {
+            let expected = matches!(
+                e,
+                ProofError::VerificationError | ProofError::FailedChecks { .. }
+            );
+            assert_eq!(e.is_verification_failure(), expected);
+        }
+    }
+
+    #[test]
+    fn messages_are_distinct() {
+        assert_distinct_messages(&proof_errors());
+        assert_distinct_messages(&mpc_errors());
+        assert_eq!(
+            ProofError::StatementTooLarge {
+                requested: 128,
+                max: 64
+            }
+            .to_string(),
+            "Statement too large: size 128 exceeds the maximum of 64."
+        );
+        assert_eq!(
+            ProofError::ProvingError(MPCError::WrongNumProofShares).to_string(),
+            "Internal error during proof creation: Wrong number of proof shares"
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn sources_are_the_wrapped_errors() {
+        use std::error::Error;
+
+        let e = ProofError::ProvingError(MPCError::MaliciousDealer);
+        assert_eq!(
+            e.source().map(|s| s.to_string()),
+            Some(MPCError::MaliciousDealer.to_string())
+        );
+        assert!(ProofError::FormatError.source().is_none());
+        assert!(MPCError::MaliciousDealer.source().is_none());
+    }
+
+    #[test]
+    fn errors_serialize_as_tagged_json() {
+        assert_eq!(
+            serde_json::to_string(&ProofError::VerificationError).unwrap(),
+            r#"{"kind":"VerificationError"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ProofError::StatementTooLarge {
+                requested: 128,
+                max: 64
+            })
+            .unwrap(),
+            r#"{"kind":"StatementTooLarge","details":{"requested":128,"max":64}}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&ProofError::ProvingError(MPCError::MalformedProofShares {
+                bad_shares: vec![1, 3]
+            }))
+            .unwrap(),
+            r#"{"kind":"ProvingError","details":{"kind":"MalformedProofShares","details":{"bad_shares":[1,3]}}}"#
+        );
+        for e in proof_errors() {
+            assert!(serde_json::to_value(&e).unwrap()["kind"].is_string());
+        }
+        for e in mpc_errors() {
+            assert!(serde_json::to_value(&e).unwrap()["kind"].is_string());
+        }
+    }
+
+    #[test]
+    fn mpc_errors_round_trip_through_proof_errors() {
+        for e in mpc_errors() {
+            let recovered = MPCError::try_from(ProofError::from(e.clone())).unwrap();
+            match e {
+                // The single-party API reports too few party generators
+                // like any other shortage of generators.
+                MPCError::InsufficientPartyCapacity { .. } => {
+                    assert_eq!(recovered, MPCError::InvalidGeneratorsLength)
+                }
+                e => assert_eq!(recovered, e),
+            }
+        }
+        assert_eq!(
+            MPCError::try_from(ProofError::FormatError),
+            Err(ProofError::FormatError)
+        );
+    }
+
+    #[cfg(feature = "yoloproofs")]
+    #[test]
+    fn proof_errors_round_trip_through_r1cs_errors() {
+        let mut errors: Vec<R1CSError> = Vec::new();
+        for e in proof_errors() {
+            let r1cs_error = R1CSError::from(e.clone());
+            assert_eq!(ProofError::try_from(r1cs_error.clone()), Ok(e));
+            errors.push(r1cs_error);
+        }
+        errors.push(R1CSError::MissingAssignment);
+        errors.push(R1CSError::GadgetError {
+            description: "bad input".into(),
+        });
+        assert_distinct_messages(&errors);
+
+        assert_eq!(
+            ProofError::try_from(R1CSError::MissingAssignment),
+            Err(R1CSError::MissingAssignment)
+        );
+
+        #[cfg(feature = "std")]
+        {
+            use std::error::Error;
+            let e = R1CSError::from(ProofError::InvalidBitsize);
+            assert!(e.source().is_some());
         }
     }
 }
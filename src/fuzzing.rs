@@ -0,0 +1,234 @@
+//! The `fuzzing` module contains implementations of
+//! [`arbitrary::Arbitrary`] for statements, witnesses and valid proofs,
+//! and the property checks run by the fuzz targets in `fuzz/`.
+//!
+//! The generated values are structurally valid but adversarially
+//! chosen.  Sizes stay within [`MAX_BITSIZE`] and [`MAX_PARTIES`], so
+//! that a fuzzer spends its time in verification rather than in
+//! rejecting oversized statements, and proofs are created with an RNG
+//! seeded from the input, so that a failing input reproduces.
+
+use std::sync::OnceLock;
+
+use arbitrary::{Arbitrary, Unstructured};
+use curve25519_dalek::scalar::Scalar;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+
+use crate::batch_verifier::{BatchVerifier, VerificationOptions};
+use crate::envelope::{ProofEnvelope, ProofKind};
+use crate::parameters::ProofParameters;
+use crate::range_proof::{BitSize, RangeProof};
+
+/// The largest bitsize of the generated range statements.
+pub const MAX_BITSIZE: usize = 64;
+
+/// The largest number of values of the generated range statements.
+pub const MAX_PARTIES: usize = 4;
+
+/// The bitsizes accepted by range proofs.
+const BITSIZES: [usize; 4] = [8, 16, 32, 64];
+
+/// The numbers of values of the generated range statements.
+const PARTIES: [usize; 3] = [1, 2, 4];
+
+/// Returns the parameters that create and verify every
+/// [`SeededRangeProof`].
+pub fn range_parameters() -> &'static ProofParameters {
+    static PARAMETERS: OnceLock<ProofParameters> = OnceLock::new();
+    PARAMETERS.get_or_init(|| {
+        ProofParameters::for_range_proofs(MAX_BITSIZE, MAX_PARTIES)
+            .expect("the largest fuzzing statement is supported")
+    })
+}
+
+impl<'a> Arbitrary<'a> for ProofKind {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        u.choose(&[ProofKind::Range, ProofKind::SmallVecOpening])
+            .copied()
+    }
+}
+
+impl<'a> Arbitrary<'a> for ProofParameters {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let max_bits = *u.choose(&BITSIZES)?;
+        let max_parties = *u.choose(&PARTIES)?;
+        ProofParameters::for_range_proofs(max_bits, max_parties)
+            .map_err(|_| arbitrary::Error::IncorrectFormat)
+    }
+}
+
+impl<'a> Arbitrary<'a> for VerificationOptions {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let msm_chunk_size = if u.arbitrary()? {
+            Some(u.int_in_range(1..=4096)?)
+        } else {
+            None
+        };
+        Ok(VerificationOptions {
+            msm_chunk_size,
+            parallel: u.arbitrary()?,
+            max_memory_bytes: u.arbitrary()?,
+        })
+    }
+}
+
+/// The witness of an aggregated range statement: `n`-bit values and
+/// their blinding factors.
+#[derive(Clone, Debug)]
+pub struct RangeWitness {
+    /// The bitsize of the statement.
+    pub n: BitSize,
+    /// The values, each less than \\(2^n\\).
+    pub values: Vec<u64>,
+    /// The blinding factors of the value commitments.
+    pub blindings: Vec<Scalar>,
+}
+
+impl<'a> Arbitrary<'a> for RangeWitness {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let n = *u.choose(&BitSize::ALL)?;
+        let m = *u.choose(&PARTIES)?;
+        let mut values = Vec::with_capacity(m);
+        let mut blindings = Vec::with_capacity(m);
+        for _ in 0..m {
+            let v: u64 = u.arbitrary()?;
+            values.push(if n == BitSize::B64 {
+                v
+            } else {
+                v & ((1 << n.bits()) - 1)
+            });
+            blindings.push(Scalar::from_bytes_mod_order(u.arbitrary()?));
+        }
+        Ok(RangeWitness {
+            n,
+            values,
+            blindings,
+        })
+    }
+}
+
+/// A valid range proof of a [`RangeWitness`] in an envelope, created
+/// with [`range_parameters`] and an RNG seeded from the input.
+#[derive(Clone, Debug)]
+pub struct SeededRangeProof {
+    /// The witness of the proof.
+    pub witness: RangeWitness,
+    /// The proof, with its statement and a context of up to 32 bytes.
+    pub envelope: ProofEnvelope<RangeProof>,
+}
+
+impl<'a> Arbitrary<'a> for SeededRangeProof {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let witness: RangeWitness = u.arbitrary()?;
+        let context_len = u.int_in_range(0..=32)?;
+        let context = u.bytes(context_len)?;
+        let mut rng = ChaCha20Rng::from_seed(u.arbitrary()?);
+        let envelope = ProofEnvelope::prove_range_with_rng(
+            range_parameters(),
+            context,
+            &witness.values,
+            &witness.blindings,
+            witness.n,
+            &mut rng,
+        )
+        .map_err(|_| arbitrary::Error::IncorrectFormat)?;
+        Ok(SeededRangeProof { witness, envelope })
+    }
+}
+
+/// Checks that a batch of up to four range proofs, some of them
+/// corrupted, is accepted if and only if each proof is accepted on its
+/// own, and that [`BatchVerifier::invalid_entries`] finds exactly the
+/// proofs that are not.
+///
+/// # Panics
+///
+/// Panics if the property does not hold.
+pub fn check_batch_consistency(u: &mut Unstructured<'_>) -> arbitrary::Result<()> {
+    let mut options: VerificationOptions = u.arbitrary()?;
+    // A memory budget may reject a batch of valid proofs.
+    options.max_memory_bytes = None;
+
+    let count = u.int_in_range(0..=4)?;
+    let mut envelopes = Vec::with_capacity(count);
+    for _ in 0..count {
+        let proof: SeededRangeProof = u.arbitrary()?;
+        let mut bytes = proof.envelope.to_bytes();
+        for _ in 0..u.int_in_range(0..=2)? {
+            let i = u.choose_index(bytes.len())?;
+            bytes[i] ^= u.int_in_range(1..=255)?;
+        }
+        // A batch only queues statements of a supported bitsize.
+        if let Ok(envelope) = ProofEnvelope::<RangeProof>::from_bytes(&bytes) {
+            if let Ok(n) = envelope.bit_size() {
+                envelopes.push((envelope, n));
+            }
+        }
+    }
+
+    let params = range_parameters();
+    let mut rng = ChaCha20Rng::from_seed(u.arbitrary()?);
+    let invalid: Vec<usize> = envelopes
+        .iter()
+        .enumerate()
+        .filter(|(_, (envelope, _))| envelope.verify_with_rng(params, &mut rng).is_err())
+        .map(|(i, _)| i)
+        .collect();
+
+    let mut batch = BatchVerifier::with_options(options);
+    for (envelope, n) in &envelopes {
+        batch.queue_range(
+            envelope.proof(),
+            envelope.commitments(),
+            *n,
+            ProofEnvelope::<RangeProof>::transcript(envelope.context()),
+        );
+    }
+    let accepted = batch
+        .verify_with_rng(params.bp_gens(), params.pc_gens(), &mut rng)
+        .is_ok();
+    assert_eq!(
+        accepted,
+        invalid.is_empty(),
+        "batch and single verification disagree"
+    );
+    assert_eq!(
+        batch.invalid_entries_with_rng(params.bp_gens(), params.pc_gens(), &mut rng),
+        invalid,
+        "invalid entries of the batch are not the proofs that fail alone"
+    );
+    Ok(())
+}
+
+/// Checks that a valid range proof envelope decodes from its encoding,
+/// re-encodes to the same bytes and verifies, and that any bytes that
+/// decode as an envelope re-encode to themselves.
+///
+/// # Panics
+///
+/// Panics if the property does not hold.
+pub fn check_envelope_round_trip(u: &mut Unstructured<'_>) -> arbitrary::Result<()> {
+    let proof: SeededRangeProof = u.arbitrary()?;
+    let bytes = proof.envelope.to_bytes();
+    let decoded =
+        ProofEnvelope::<RangeProof>::from_bytes(&bytes).expect("an encoded envelope decodes");
+    assert_eq!(decoded.to_bytes(), bytes, "decoding changed the envelope");
+    let mut rng = ChaCha20Rng::from_seed(u.arbitrary()?);
+    assert!(
+        decoded
+            .verify_with_rng(range_parameters(), &mut rng)
+            .is_ok(),
+        "a decoded valid envelope does not verify"
+    );
+
+    let data: Vec<u8> = u.arbitrary()?;
+    if let Ok(envelope) = ProofEnvelope::<RangeProof>::from_bytes(&data) {
+        assert_eq!(
+            envelope.to_bytes(),
+            data,
+            "decoding changed arbitrary bytes"
+        );
+    }
+    Ok(())
+}
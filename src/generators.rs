@@ -6,14 +6,35 @@
 
 extern crate alloc;
 
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::convert::TryFrom;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_COMPRESSED;
 use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
-use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
-use curve25519_dalek::traits::MultiscalarMul;
+use curve25519_dalek::traits::{Identity, MultiscalarMul};
 use digest::{ExtendableOutput, Update, XofReader};
-use sha3::{Sha3_512, Shake256, Shake256Reader};
+use merlin::Transcript;
+use sha3::{Sha3_256, Sha3_512, Shake256, Shake256Reader};
+use subtle::ConstantTimeEq;
+
+use crate::errors::ProofError;
+use crate::transcript::TranscriptProtocol;
+
+#[cfg(feature = "mmap")]
+mod mapped;
+
+#[cfg(feature = "mmap")]
+pub use self::mapped::MappedBulletproofGens;
+
+/// The compressed encoding of the default `B_blinding`, so that the
+/// default generators can be recognized without hashing to the group.
+#[cfg(feature = "verifier")]
+const DEFAULT_B_BLINDING_COMPRESSED: CompressedRistretto = CompressedRistretto([
+    140, 146, 64, 180, 86, 169, 230, 220, 101, 195, 119, 161, 4, 141, 116, 95, 148, 160, 140, 219,
+    127, 68, 203, 205, 123, 70, 243, 64, 72, 135, 17, 52,
+]);
 
 /// Represents a pair of base points for Pedersen commitments.
 ///
@@ -25,8 +46,13 @@ use sha3::{Sha3_512, Shake256, Shake256Reader};
 ///
 /// * `B`: the `ristretto255` basepoint;
 /// * `B_blinding`: the result of `ristretto255` SHA3-512
-/// hash-to-group on input `B_bytes`.
-#[derive(Copy, Clone)]
+///   hash-to-group on input `B_bytes`.
+///
+/// With `serde`, the generators are stored as compressed points.
+/// Deserialization rejects encodings that are not canonical, the
+/// identity, and equal generators.
+#[derive(Copy, Clone, Serialize, Deserialize)]
+#[serde(into = "PedersenGensRepr", try_from = "PedersenGensRepr")]
 pub struct PedersenGens {
     /// Base for the committed value
     pub B: RistrettoPoint,
@@ -35,10 +61,84 @@ pub struct PedersenGens {
 }
 
 impl PedersenGens {
+    /// Creates generators that commit to values with the asset-specific
+    /// generator `value_generator` in place of `B`, and to blinding
+    /// factors with the default `B_blinding`.
+    ///
+    /// Range proofs and other proofs made with these generators bind
+    /// `value_generator` in their transcript, so a proof for one asset
+    /// does not verify under another asset's generators.
+    ///
+    /// Returns [`ProofError::InvalidGenerators`] if `value_generator`
+    /// is the identity or the default `B_blinding`.
+    pub fn with_value_generator(value_generator: RistrettoPoint) -> Result<Self, ProofError> {
+        let default = PedersenGens::default();
+        if value_generator == RistrettoPoint::identity() || value_generator == default.B_blinding {
+            return Err(ProofError::InvalidGenerators);
+        }
+        Ok(PedersenGens {
+            B: value_generator,
+            ..default
+        })
+    }
+
     /// Creates a Pedersen commitment using the value scalar and a blinding factor.
     pub fn commit(&self, value: Scalar, blinding: Scalar) -> RistrettoPoint {
         RistrettoPoint::multiscalar_mul(&[value, blinding], &[self.B, self.B_blinding])
     }
+
+    /// Checks in constant time that `commitment` opens to `value`
+    /// with the given `blinding` factor.
+    pub fn verify_opening(
+        &self,
+        commitment: &CompressedRistretto,
+        value: u64,
+        blinding: Scalar,
+    ) -> bool {
+        let expected = self.commit(Scalar::from(value), blinding).compress();
+        expected.ct_eq(commitment).into()
+    }
+
+    /// Commits to each of the `values`, deriving the blinding factors
+    /// deterministically from the secret `blinding_seed`.
+    ///
+    /// Returns the commitments and their blinding factors, which are
+    /// challenges of a transcript over the seed and the values.  The
+    /// seed must be uniformly random and kept secret, like a blinding
+    /// factor: anyone who knows it can open the commitments.
+    pub fn commit_many(
+        &self,
+        values: &[Scalar],
+        blinding_seed: &Scalar,
+    ) -> (Vec<RistrettoPoint>, Vec<Scalar>) {
+        let mut transcript = Transcript::new(b"Bulletproofs commit_many");
+        transcript.append_len(b"m", values.len());
+        transcript.append_scalar(b"seed", blinding_seed);
+        for v in values {
+            transcript.append_scalar(b"v", v);
+        }
+
+        let blindings: Vec<Scalar> = values
+            .iter()
+            .map(|_| transcript.challenge_scalar(b"blinding"))
+            .collect();
+        let commitments = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(v, blinding)| self.commit(*v, *blinding))
+            .collect();
+        (commitments, blindings)
+    }
+
+    /// Binds the transcript to custom generators; the default
+    /// generators leave it unchanged, so existing proofs still verify.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn append_fingerprint<T: TranscriptProtocol>(&self, transcript: &mut T) {
+        let B_blinding = self.B_blinding.compress();
+        if self.B != RISTRETTO_BASEPOINT_POINT || B_blinding != DEFAULT_B_BLINDING_COMPRESSED {
+            transcript.pedersen_gens_domain_sep(&self.B.compress(), &B_blinding);
+        }
+    }
 }
 
 impl Default for PedersenGens {
@@ -52,6 +152,38 @@ impl Default for PedersenGens {
     }
 }
 
+/// The serialized form of [`PedersenGens`].
+#[derive(Serialize, Deserialize)]
+struct PedersenGensRepr {
+    B: CompressedRistretto,
+    B_blinding: CompressedRistretto,
+}
+
+impl From<PedersenGens> for PedersenGensRepr {
+    fn from(gens: PedersenGens) -> Self {
+        PedersenGensRepr {
+            B: gens.B.compress(),
+            B_blinding: gens.B_blinding.compress(),
+        }
+    }
+}
+
+impl TryFrom<PedersenGensRepr> for PedersenGens {
+    type Error = ProofError;
+
+    fn try_from(repr: PedersenGensRepr) -> Result<Self, ProofError> {
+        let identity = CompressedRistretto::default();
+        if repr.B == identity || repr.B_blinding == identity || repr.B == repr.B_blinding {
+            return Err(ProofError::InvalidGenerators);
+        }
+        let decompress = |P: &CompressedRistretto| P.decompress().ok_or(ProofError::FormatError);
+        Ok(PedersenGens {
+            B: decompress(&repr.B)?,
+            B_blinding: decompress(&repr.B_blinding)?,
+        })
+    }
+}
+
 /// The `GeneratorsChain` creates an arbitrary-long sequence of
 /// orthogonal generators.  The sequence can be deterministically
 /// produced starting with an arbitrary point.
@@ -99,7 +231,7 @@ impl Iterator for GeneratorsChain {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (usize::max_value(), None)
+        (usize::MAX, None)
     }
 }
 
@@ -139,6 +271,9 @@ pub struct BulletproofGens {
     G_vec: Vec<Vec<RistrettoPoint>>,
     /// Precomputed \\(\mathbf H\\) generators for each party.
     H_vec: Vec<Vec<RistrettoPoint>>,
+    /// Digest of the generators, if they were imported with
+    /// [`BulletproofGens::from_points`] rather than derived.
+    fingerprint: Option<[u8; 32]>,
 }
 
 impl BulletproofGens {
@@ -147,39 +282,164 @@ impl BulletproofGens {
     /// # Inputs
     ///
     /// * `gens_capacity` is the number of generators to precompute
-    ///    for each party.  For rangeproofs, it is sufficient to pass
-    ///    `64`, the maximum bitsize of the rangeproofs.  For circuit
-    ///    proofs, the capacity must be greater than the number of
-    ///    multipliers, rounded up to the next power of two.
+    ///   for each party.  For rangeproofs, it is sufficient to pass
+    ///   `64`, the maximum bitsize of the rangeproofs.  For circuit
+    ///   proofs, the capacity must be greater than the number of
+    ///   multipliers, rounded up to the next power of two.
     ///
     /// * `party_capacity` is the maximum number of parties that can
-    ///    produce an aggregated proof.
+    ///   produce an aggregated proof.
     pub fn new(gens_capacity: usize, party_capacity: usize) -> Self {
         let mut gens = BulletproofGens {
             gens_capacity: 0,
             party_capacity,
             G_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
             H_vec: (0..party_capacity).map(|_| Vec::new()).collect(),
+            fingerprint: None,
         };
         gens.increase_capacity(gens_capacity);
         gens
     }
 
+    /// Creates a `BulletproofGens` object from generators fixed
+    /// elsewhere, for instance by a protocol specification.
+    ///
+    /// `G` and `H` list the generators of party \\(0\\), then those of
+    /// party \\(1\\), and so on, so each party has
+    /// `G.len() / party_capacity` generators.
+    ///
+    /// Returns [`ProofError::InvalidGeneratorsLength`] if `G` and `H`
+    /// have different lengths or cannot be split evenly between
+    /// `party_capacity` parties, and [`ProofError::InvalidGenerators`]
+    /// if any generator is the identity or appears twice.
+    ///
+    /// Proofs made with imported generators bind their
+    /// [`fingerprint`](BulletproofGens::fingerprint), so they do not
+    /// verify against other generators, even ones that happen to
+    /// start with the same points.  The capacity of imported
+    /// generators cannot be increased.
+    pub fn from_points(
+        G: Vec<RistrettoPoint>,
+        H: Vec<RistrettoPoint>,
+        party_capacity: usize,
+    ) -> Result<Self, ProofError> {
+        if G.len() != H.len() || party_capacity == 0 || !G.len().is_multiple_of(party_capacity) {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        let gens_capacity = G.len() / party_capacity;
+
+        let compressed: Vec<CompressedRistretto> =
+            G.iter().chain(H.iter()).map(|P| P.compress()).collect();
+        check_distinct(&compressed)?;
+
+        let mut hash = Sha3_256::default();
+        hash.update(b"Bulletproofs imported generators");
+        hash.update(&(gens_capacity as u64).to_le_bytes());
+        hash.update(&(party_capacity as u64).to_le_bytes());
+        for P in &compressed {
+            hash.update(P.as_bytes());
+        }
+        let mut fingerprint = [0u8; 32];
+        fingerprint.copy_from_slice(&digest::Digest::finalize(hash));
+
+        let split = |points: Vec<RistrettoPoint>| -> Vec<Vec<RistrettoPoint>> {
+            points
+                .chunks(gens_capacity.max(1))
+                .map(|chunk| chunk.to_vec())
+                .chain(core::iter::repeat(Vec::new()))
+                .take(party_capacity)
+                .collect()
+        };
+
+        Ok(BulletproofGens {
+            gens_capacity,
+            party_capacity,
+            G_vec: split(G),
+            H_vec: split(H),
+            fingerprint: Some(fingerprint),
+        })
+    }
+
+    /// Returns the digest of the generators if they were imported with
+    /// [`BulletproofGens::from_points`], or `None` if they were
+    /// derived by this crate.
+    ///
+    /// Since `from_points` rejects repeated generators, a verifier that
+    /// requires a fingerprint only accepts generators that were checked
+    /// with [`validate_distinctness`](BulletproofGens::validate_distinctness).
+    /// Generators read from a table with `MappedBulletproofGens` keep
+    /// the fingerprint stored in the table without checking it again.
+    pub fn fingerprint(&self) -> Option<&[u8; 32]> {
+        self.fingerprint.as_ref()
+    }
+
+    /// Checks that no generator is the identity or appears twice, in
+    /// \(G\) or \(H\) of any party.
+    ///
+    /// [`BulletproofGens::from_points`] already runs this check, and
+    /// generators derived by this crate pass it, so it is only needed
+    /// for generators from elsewhere, such as a mapped table.  It
+    /// compresses and sorts all the generators, which takes time
+    /// \(O(n \log n)\) in their number \(n\).
+    ///
+    /// Returns [`ProofError::InvalidGenerators`] if the check fails.
+    pub fn validate_distinctness(&self) -> Result<(), ProofError> {
+        let compressed: Vec<CompressedRistretto> = self
+            .G_vec
+            .iter()
+            .chain(self.H_vec.iter())
+            .flat_map(|points| points.iter().take(self.gens_capacity))
+            .map(|P| P.compress())
+            .collect();
+        check_distinct(&compressed)
+    }
+
+    /// Binds the transcript to imported generators; derived generators
+    /// leave it unchanged, so existing proofs still verify.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn append_fingerprint<T: TranscriptProtocol>(&self, transcript: &mut T) {
+        if let Some(fingerprint) = &self.fingerprint {
+            transcript.generators_domain_sep(fingerprint);
+        }
+    }
+
     /// Returns j-th share of generators, with an appropriate
     /// slice of vectors G and H for the j-th range proof.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `j` is not less than the party capacity.
+    #[deprecated(note = "use `try_share`, which returns an error for an out-of-range party")]
     pub fn share(&self, j: usize) -> BulletproofGensShare<'_> {
-        BulletproofGensShare {
-            gens: &self,
-            share: j,
+        self.try_share(j)
+            .expect("party index must be less than the party capacity")
+    }
+
+    /// Returns j-th share of generators, with an appropriate
+    /// slice of vectors G and H for the j-th range proof.
+    ///
+    /// Returns [`ProofError::ShareOutOfRange`] if `j` is not less than
+    /// the party capacity.
+    pub fn try_share(&self, j: usize) -> Result<BulletproofGensShare<'_>, ProofError> {
+        if j >= self.party_capacity {
+            return Err(ProofError::ShareOutOfRange {
+                index: j,
+                party_capacity: self.party_capacity,
+            });
         }
+        Ok(BulletproofGensShare {
+            gens: self,
+            share: j,
+        })
     }
 
     /// Increases the generators' capacity to the amount specified.
-    /// If less than or equal to the current capacity, does nothing.
+    /// If less than or equal to the current capacity, or if the
+    /// generators were imported, does nothing.
     pub fn increase_capacity(&mut self, new_capacity: usize) {
         use byteorder::{ByteOrder, LittleEndian};
 
-        if self.gens_capacity >= new_capacity {
+        if self.gens_capacity >= new_capacity || self.fingerprint.is_some() {
             return;
         }
 
@@ -204,6 +464,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' G generators with given size `n`.
+    #[cfg(feature = "verifier")]
     pub(crate) fn G(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
         AggregatedGensIter {
             n,
@@ -215,6 +476,7 @@ impl BulletproofGens {
     }
 
     /// Return an iterator over the aggregation of the parties' H generators with given size `n`.
+    #[cfg(feature = "verifier")]
     pub(crate) fn H(&self, n: usize, m: usize) -> impl Iterator<Item = &RistrettoPoint> {
         AggregatedGensIter {
             n,
@@ -226,6 +488,19 @@ impl BulletproofGens {
     }
 }
 
+/// Checks that none of the `compressed` generators is the identity or
+/// appears twice, by sorting their encodings.
+fn check_distinct(compressed: &[CompressedRistretto]) -> Result<(), ProofError> {
+    let mut sorted: Vec<&[u8; 32]> = compressed.iter().map(|P| P.as_bytes()).collect();
+    sorted.sort_unstable();
+    let identity = CompressedRistretto::default();
+    if sorted.windows(2).any(|w| w[0] == w[1]) || compressed.contains(&identity) {
+        return Err(ProofError::InvalidGenerators);
+    }
+    Ok(())
+}
+
+#[cfg(feature = "verifier")]
 struct AggregatedGensIter<'a> {
     array: &'a Vec<Vec<RistrettoPoint>>,
     n: usize,
@@ -234,6 +509,7 @@ struct AggregatedGensIter<'a> {
     gen_idx: usize,
 }
 
+#[cfg(feature = "verifier")]
 impl<'a> Iterator for AggregatedGensIter<'a> {
     type Item = &'a RistrettoPoint;
 
@@ -275,14 +551,136 @@ pub struct BulletproofGensShare<'a> {
 }
 
 impl<'a> BulletproofGensShare<'a> {
+    /// Binds the transcript to the generators if they were imported.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn append_fingerprint<T: TranscriptProtocol>(&self, transcript: &mut T) {
+        self.gens.append_fingerprint(transcript)
+    }
+
     /// Return an iterator over this party's G generators with given size `n`.
+    ///
+    /// The iterator yields only the generators the share has, which
+    /// may be fewer than `n`.  Zipped with `n` scalars, it silently
+    /// drops the scalars beyond the capacity, so unless the capacity
+    /// was checked beforehand, use [`BulletproofGensShare::G_checked`].
     pub fn G(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
         self.gens.G_vec[self.share].iter().take(n)
     }
 
-    /// Return an iterator over this party's H generators with given size `n`.
-    pub(crate) fn H(&self, n: usize) -> impl Iterator<Item = &'a RistrettoPoint> {
-        self.gens.H_vec[self.share].iter().take(n)
+    /// Return an iterator over exactly `n` of this party's G generators.
+    ///
+    /// Returns [`ProofError::InvalidGeneratorsLength`] if the share has
+    /// fewer than `n` generators.
+    pub fn G_checked(
+        &self,
+        n: usize,
+    ) -> Result<impl ExactSizeIterator<Item = &'a RistrettoPoint>, ProofError> {
+        checked_prefix(&self.gens.G_vec[self.share], n)
+    }
+
+    #[cfg(any(feature = "verifier", feature = "yoloproofs"))]
+    /// Return an iterator over exactly `n` of this party's H generators,
+    /// or [`ProofError::InvalidGeneratorsLength`] if the share has fewer.
+    pub(crate) fn H_checked(
+        &self,
+        n: usize,
+    ) -> Result<impl ExactSizeIterator<Item = &'a RistrettoPoint>, ProofError> {
+        checked_prefix(&self.gens.H_vec[self.share], n)
+    }
+}
+
+/// Returns an iterator over the first `n` of the `generators`, or
+/// [`ProofError::InvalidGeneratorsLength`] if there are fewer.
+fn checked_prefix(
+    generators: &[RistrettoPoint],
+    n: usize,
+) -> Result<core::slice::Iter<'_, RistrettoPoint>, ProofError> {
+    generators
+        .get(..n)
+        .map(|prefix| prefix.iter())
+        .ok_or(ProofError::InvalidGeneratorsLength)
+}
+
+/// A cheaply clonable handle to a [`BulletproofGens`] table.
+///
+/// Clones share the same precomputed generators, so a handle can be
+/// moved into threads or tasks without borrowing the table or copying
+/// its points.  The generators cannot be modified once shared.
+///
+/// The handle is produced by [`BulletproofGens::into_shared()`].
+#[derive(Clone)]
+pub struct SharedBulletproofGens {
+    gens: Arc<BulletproofGens>,
+}
+
+impl SharedBulletproofGens {
+    /// The maximum number of usable generators for each party.
+    pub fn gens_capacity(&self) -> usize {
+        self.gens.gens_capacity
+    }
+
+    /// Number of values or parties.
+    pub fn party_capacity(&self) -> usize {
+        self.gens.party_capacity
+    }
+
+    /// See [`BulletproofGens::fingerprint`].
+    pub fn fingerprint(&self) -> Option<&[u8; 32]> {
+        self.gens.fingerprint()
+    }
+
+    /// Returns j-th share of generators, with an appropriate
+    /// slice of vectors G and H for the j-th range proof.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `j` is not less than the party capacity.
+    #[deprecated(note = "use `try_share`, which returns an error for an out-of-range party")]
+    pub fn share(&self, j: usize) -> BulletproofGensShare<'_> {
+        #[allow(deprecated)]
+        self.gens.share(j)
+    }
+
+    /// See [`BulletproofGens::try_share`].
+    pub fn try_share(&self, j: usize) -> Result<BulletproofGensShare<'_>, ProofError> {
+        self.gens.try_share(j)
+    }
+}
+
+impl BulletproofGens {
+    /// Moves the generators behind a [`SharedBulletproofGens`] handle.
+    pub fn into_shared(self) -> SharedBulletproofGens {
+        SharedBulletproofGens {
+            gens: Arc::new(self),
+        }
+    }
+}
+
+impl From<BulletproofGens> for SharedBulletproofGens {
+    fn from(gens: BulletproofGens) -> Self {
+        gens.into_shared()
+    }
+}
+
+/// Types that provide a [`BulletproofGens`] table.
+///
+/// The proving and verification entry points accept any implementor,
+/// so they can be called with either a [`BulletproofGens`] or a
+/// [`SharedBulletproofGens`].
+pub trait AsGenerators {
+    /// Returns the underlying generators.
+    fn as_generators(&self) -> &BulletproofGens;
+}
+
+impl AsGenerators for BulletproofGens {
+    fn as_generators(&self) -> &BulletproofGens {
+        self
+    }
+}
+
+impl AsGenerators for SharedBulletproofGens {
+    fn as_generators(&self) -> &BulletproofGens {
+        &self.gens
     }
 }
 
@@ -353,4 +751,233 @@ mod tests {
         helper(32, 8);
         helper(16, 8);
     }
+
+    #[test]
+    fn imported_gens_match_the_given_points() {
+        let gens = BulletproofGens::new(8, 2);
+        let G: Vec<RistrettoPoint> = gens.G(8, 2).cloned().collect();
+        let H: Vec<RistrettoPoint> = gens.H(8, 2).cloned().collect();
+
+        let mut imported = BulletproofGens::from_points(G.clone(), H.clone(), 2).unwrap();
+        assert_eq!(imported.gens_capacity, 8);
+        assert_eq!(imported.party_capacity, 2);
+        assert_eq!(imported.G(8, 2).cloned().collect::<Vec<_>>(), G);
+        assert_eq!(imported.H(8, 2).cloned().collect::<Vec<_>>(), H);
+        assert!(imported.fingerprint().is_some());
+        assert!(gens.fingerprint().is_none());
+
+        // Imported generators are not extended with derived ones.
+        imported.increase_capacity(16);
+        assert_eq!(imported.gens_capacity, 8);
+
+        // The fingerprint depends on how the points are split.
+        let one_party = BulletproofGens::from_points(G, H, 1).unwrap();
+        assert_ne!(one_party.fingerprint(), imported.fingerprint());
+    }
+
+    #[test]
+    fn shares_are_checked_against_the_party_capacity() {
+        let gens = BulletproofGens::new(8, 2);
+        let out_of_range = Some(ProofError::ShareOutOfRange {
+            index: 2,
+            party_capacity: 2,
+        });
+        assert!(gens.try_share(1).is_ok());
+        assert_eq!(gens.try_share(2).err(), out_of_range);
+
+        let shared = gens.into_shared();
+        assert!(shared.try_share(1).is_ok());
+        assert_eq!(shared.try_share(2).err(), out_of_range);
+
+        assert_eq!(
+            BulletproofGens::new(8, 0).try_share(0).err(),
+            Some(ProofError::ShareOutOfRange {
+                index: 0,
+                party_capacity: 0,
+            })
+        );
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    #[should_panic(expected = "party index must be less than the party capacity")]
+    fn out_of_range_share_panics() {
+        BulletproofGens::new(8, 2).share(2);
+    }
+
+    #[test]
+    fn share_generators_are_checked_against_the_capacity() {
+        use crate::util::{bits_to_choices, ct_select_commit};
+
+        let gens = BulletproofGens::new(8, 1);
+        let share = gens.try_share(0).unwrap();
+
+        // The unchecked accessor yields only the generators it has, so
+        // a commitment to 16 bits silently covers the low 8 bits.
+        assert_eq!(share.G(16).count(), 8);
+        let G: Vec<RistrettoPoint> = share.G(16).cloned().collect();
+        let H: Vec<RistrettoPoint> = share.H_checked(8).unwrap().cloned().collect();
+        let identity = RistrettoPoint::identity();
+        assert_eq!(
+            ct_select_commit(bits_to_choices(0x1ff, 16), &G, &H, identity),
+            ct_select_commit(bits_to_choices(0xff, 16), &G, &H, identity)
+        );
+
+        // The checked accessors refuse instead.
+        assert_eq!(
+            share.G_checked(16).err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+        assert_eq!(
+            share.H_checked(9).err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+        assert_eq!(share.G_checked(8).unwrap().len(), 8);
+        assert!(share.G_checked(8).unwrap().eq(share.G(8)));
+        assert_eq!(share.H_checked(0).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn imported_gens_are_validated() {
+        let gens = BulletproofGens::new(4, 1);
+        let G: Vec<RistrettoPoint> = gens.G(4, 1).cloned().collect();
+        let H: Vec<RistrettoPoint> = gens.H(4, 1).cloned().collect();
+
+        let import = |G: &[RistrettoPoint], H: &[RistrettoPoint], m: usize| {
+            BulletproofGens::from_points(G.to_vec(), H.to_vec(), m).err()
+        };
+        assert_eq!(
+            import(&G[..3], &H, 1),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+        assert_eq!(import(&G, &H, 0), Some(ProofError::InvalidGeneratorsLength));
+        assert_eq!(import(&G, &H, 3), Some(ProofError::InvalidGeneratorsLength));
+
+        let mut with_identity = G.clone();
+        with_identity[2] = RistrettoPoint::default();
+        assert_eq!(
+            import(&with_identity, &H, 1),
+            Some(ProofError::InvalidGenerators)
+        );
+
+        let mut repeated = H.clone();
+        repeated[3] = G[0];
+        assert_eq!(
+            import(&G, &repeated, 1),
+            Some(ProofError::InvalidGenerators)
+        );
+    }
+
+    #[test]
+    fn distinctness_is_checked_across_parties() {
+        let gens = BulletproofGens::new(8, 4);
+        assert_eq!(gens.validate_distinctness(), Ok(()));
+
+        let G: Vec<RistrettoPoint> = gens.G(8, 4).cloned().collect();
+        let H: Vec<RistrettoPoint> = gens.H(8, 4).cloned().collect();
+        let imported = BulletproofGens::from_points(G.clone(), H.clone(), 4).unwrap();
+        assert_eq!(imported.validate_distinctness(), Ok(()));
+
+        // The last generator of party 3 repeats the first of party 0.
+        let mut duplicated = imported.clone();
+        duplicated.G_vec[3][7] = G[0];
+        assert_eq!(
+            duplicated.validate_distinctness(),
+            Err(ProofError::InvalidGenerators)
+        );
+        let mut repeated = G;
+        repeated[31] = repeated[0];
+        assert_eq!(
+            BulletproofGens::from_points(repeated, H, 4).err(),
+            Some(ProofError::InvalidGenerators)
+        );
+
+        let mut with_identity = imported;
+        with_identity.H_vec[1][2] = RistrettoPoint::default();
+        assert_eq!(
+            with_identity.validate_distinctness(),
+            Err(ProofError::InvalidGenerators)
+        );
+    }
+
+    #[test]
+    fn asset_gens_are_validated() {
+        let default = PedersenGens::default();
+        let asset = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"asset A");
+
+        let gens = PedersenGens::with_value_generator(asset).unwrap();
+        assert_eq!(gens.B, asset);
+        assert_eq!(gens.B_blinding, default.B_blinding);
+
+        for &bad in &[RistrettoPoint::identity(), default.B_blinding] {
+            assert_eq!(
+                PedersenGens::with_value_generator(bad).err(),
+                Some(ProofError::InvalidGenerators)
+            );
+        }
+    }
+
+    #[test]
+    fn default_b_blinding_encoding_matches_derivation() {
+        assert_eq!(
+            PedersenGens::default().B_blinding.compress(),
+            DEFAULT_B_BLINDING_COMPRESSED
+        );
+    }
+
+    #[test]
+    fn pedersen_gens_json_roundtrip() {
+        let asset = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"asset A");
+        let gens = PedersenGens::with_value_generator(asset).unwrap();
+
+        let json = serde_json::to_string(&gens).unwrap();
+        let decoded: PedersenGens = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.B, gens.B);
+        assert_eq!(decoded.B_blinding, gens.B_blinding);
+    }
+
+    #[test]
+    fn tampered_pedersen_gens_json_is_rejected() {
+        let mut json = serde_json::to_value(PedersenGens::default()).unwrap();
+        let B_blinding = json["B_blinding"].clone();
+
+        // The identity.
+        json["B"] = serde_json::to_value([0u8; 32]).unwrap();
+        assert!(serde_json::from_value::<PedersenGens>(json.clone()).is_err());
+
+        // A repeated generator.
+        json["B"] = B_blinding;
+        assert!(serde_json::from_value::<PedersenGens>(json.clone()).is_err());
+
+        // A non-canonical encoding, the field element p.
+        let mut p = [0xffu8; 32];
+        p[0] = 0xed;
+        p[31] = 0x7f;
+        json["B"] = serde_json::to_value(p).unwrap();
+        assert!(serde_json::from_value::<PedersenGens>(json).is_err());
+    }
+
+    #[test]
+    fn commit_many_derives_distinct_blindings() {
+        let pc_gens = PedersenGens::default();
+        let values: Vec<Scalar> = (0..4u64).map(Scalar::from).collect();
+        let seed = Scalar::from(42u64);
+
+        let (commitments, blindings) = pc_gens.commit_many(&values, &seed);
+        assert_eq!(commitments.len(), 4);
+        for i in 0..4 {
+            assert_eq!(commitments[i], pc_gens.commit(values[i], blindings[i]));
+            for j in 0..i {
+                assert_ne!(blindings[i], blindings[j]);
+            }
+        }
+
+        // The derivation is deterministic in the seed and the values.
+        assert_eq!(pc_gens.commit_many(&values, &seed).1, blindings);
+        assert_ne!(pc_gens.commit_many(&values, &Scalar::ONE).1, blindings);
+        assert_ne!(
+            pc_gens.commit_many(&values[..3], &seed).1[..],
+            blindings[..3]
+        );
+    }
 }
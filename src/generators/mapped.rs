@@ -0,0 +1,261 @@
+//! Generator tables stored in files and read through a memory map.
+
+use std::convert::TryFrom;
+use std::fs::File;
+use std::io::{self, BufWriter, Seek, SeekFrom, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use digest::Digest;
+use memmap2::Mmap;
+use sha3::Sha3_256;
+
+use super::BulletproofGens;
+use crate::errors::ProofError;
+
+/// The first bytes of a generator table file.
+const TABLE_MAGIC: &[u8; 8] = b"BPGENTBL";
+
+/// The version of the table encoding written by
+/// [`BulletproofGens::write_table`].
+const TABLE_VERSION: u8 = 1;
+
+/// The length of the header: the magic bytes, the version, the two
+/// capacities, the fingerprint flag and fingerprint, and the checksum.
+const HEADER_LEN: usize = 8 + 1 + 8 + 8 + 1 + 32 + 32;
+
+/// The number of materialized generators kept by a
+/// [`MappedBulletproofGens`].
+const CACHE_LEN: usize = 4;
+
+/// Returns a hash of the table with the given header fields, to be
+/// updated with the body to compute the checksum.
+fn table_hash(header: &[u8]) -> Sha3_256 {
+    let mut hash = Sha3_256::new();
+    hash.update(b"Bulletproofs generator table");
+    hash.update(header);
+    hash
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+impl BulletproofGens {
+    /// Writes the generators to the file at `path` as a table that
+    /// [`MappedBulletproofGens::open`] can map.
+    ///
+    /// The table stores the compressed generators of each party,
+    /// \\(\mathbf{G}\\) then \\(\mathbf{H}\\), after a header that
+    /// holds the capacities, the [`fingerprint`](BulletproofGens::fingerprint)
+    /// of imported generators, and a SHA3-256 checksum of the whole
+    /// table.
+    pub fn write_table<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut header = Vec::with_capacity(HEADER_LEN);
+        header.extend_from_slice(TABLE_MAGIC);
+        header.push(TABLE_VERSION);
+        header.extend_from_slice(&(self.gens_capacity as u64).to_le_bytes());
+        header.extend_from_slice(&(self.party_capacity as u64).to_le_bytes());
+        header.push(self.fingerprint.is_some() as u8);
+        header.extend_from_slice(&self.fingerprint.unwrap_or_default());
+
+        let mut hash = table_hash(&header);
+
+        // The checksum is only known once the body is written, so
+        // reserve its place and fill it in afterwards.
+        let mut file = BufWriter::new(File::create(path)?);
+        file.write_all(&header)?;
+        file.write_all(&[0u8; 32])?;
+        for (G_j, H_j) in self.G_vec.iter().zip(self.H_vec.iter()) {
+            for P in G_j.iter().chain(H_j.iter()) {
+                let bytes = P.compress().to_bytes();
+                hash.update(bytes);
+                file.write_all(&bytes)?;
+            }
+        }
+
+        let mut file = file.into_inner().map_err(|e| e.into_error())?;
+        file.seek(SeekFrom::Start((HEADER_LEN - 32) as u64))?;
+        file.write_all(&hash.finalize())?;
+        file.sync_all()
+    }
+}
+
+/// Generators read from a table written by
+/// [`BulletproofGens::write_table`], for capacities whose decompressed
+/// points do not fit comfortably in memory.
+///
+/// The table is memory-mapped, so only the pages holding the
+/// generators that are used are read.  [`MappedBulletproofGens::gens`]
+/// decompresses the generators for a given proof size into a
+/// [`BulletproofGens`], which is used with provers and verifiers as
+/// usual, and keeps the last few of them for proofs of the same size.
+///
+/// Generators materialized from a table are the ones it was written
+/// from, truncated to the requested capacities, so proofs verify
+/// against them exactly as against the original generators.
+///
+/// The file must not be modified while it is mapped.
+///
+/// ```
+/// use bulletproofs::{BitSize, BulletproofGens, MappedBulletproofGens, PedersenGens, RangeProof};
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let path = std::env::temp_dir().join(format!("bp-gens-doctest-{}", std::process::id()));
+/// BulletproofGens::new(64, 4).write_table(&path)?;
+/// let table = MappedBulletproofGens::open(&path)?;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = table.gens(64, 1)?;
+/// let blinding = Scalar::random(&mut rand::thread_rng());
+/// let mut transcript = Transcript::new(b"doctest example");
+/// let (proof, committed_value) =
+///     RangeProof::prove_single(&*bp_gens, &pc_gens, &mut transcript, 1037578891, &blinding, BitSize::B64)?;
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// proof.verify_single(&*bp_gens, &pc_gens, &mut transcript, &committed_value, BitSize::B64)?;
+/// # std::fs::remove_file(&path)?;
+/// # Ok::<(), Box<dyn std::error::Error>>(())
+/// ```
+pub struct MappedBulletproofGens {
+    map: Mmap,
+    gens_capacity: usize,
+    party_capacity: usize,
+    fingerprint: Option<[u8; 32]>,
+    /// The most recently materialized generators, oldest first.
+    cache: Mutex<Vec<Arc<BulletproofGens>>>,
+}
+
+impl MappedBulletproofGens {
+    /// Maps the generator table at `path` and checks its checksum.
+    ///
+    /// Returns an error of kind [`io::ErrorKind::InvalidData`] if the
+    /// file is not a generator table, has an unknown version, does not
+    /// have the length given by its header, or does not match its
+    /// checksum.  Checking the checksum reads the whole file once.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let file = File::open(path)?;
+        // Safety: the map is only read, and the caller must not modify
+        // the file while it is mapped, as documented above.
+        let map = unsafe { Mmap::map(&file)? };
+
+        if map.len() < HEADER_LEN || &map[..8] != TABLE_MAGIC {
+            return Err(invalid_data("not a generator table"));
+        }
+        if map[8] != TABLE_VERSION {
+            return Err(invalid_data("unknown generator table version"));
+        }
+        let read_u64 = |offset: usize| {
+            let mut buf = [0u8; 8];
+            buf.copy_from_slice(&map[offset..offset + 8]);
+            usize::try_from(u64::from_le_bytes(buf))
+                .map_err(|_| invalid_data("generator table is too large"))
+        };
+        let gens_capacity = read_u64(9)?;
+        let party_capacity = read_u64(17)?;
+        let fingerprint = match map[25] {
+            0 => None,
+            1 => {
+                let mut fingerprint = [0u8; 32];
+                fingerprint.copy_from_slice(&map[26..58]);
+                Some(fingerprint)
+            }
+            _ => return Err(invalid_data("invalid generator table header")),
+        };
+
+        let body_len = gens_capacity
+            .checked_mul(party_capacity)
+            .and_then(|len| len.checked_mul(2 * 32))
+            .ok_or_else(|| invalid_data("generator table is too large"))?;
+        if map.len() - HEADER_LEN != body_len {
+            return Err(invalid_data("generator table has the wrong length"));
+        }
+        let mut hash = table_hash(&map[..HEADER_LEN - 32]);
+        hash.update(&map[HEADER_LEN..]);
+        let checksum = hash.finalize();
+        if checksum[..] != map[HEADER_LEN - 32..HEADER_LEN] {
+            return Err(invalid_data("generator table checksum mismatch"));
+        }
+
+        Ok(MappedBulletproofGens {
+            map,
+            gens_capacity,
+            party_capacity,
+            fingerprint,
+            cache: Mutex::new(Vec::new()),
+        })
+    }
+
+    /// Returns the number of generators of each party in the table.
+    pub fn gens_capacity(&self) -> usize {
+        self.gens_capacity
+    }
+
+    /// Returns the number of parties in the table.
+    pub fn party_capacity(&self) -> usize {
+        self.party_capacity
+    }
+
+    /// See [`BulletproofGens::fingerprint`].
+    pub fn fingerprint(&self) -> Option<&[u8; 32]> {
+        self.fingerprint.as_ref()
+    }
+
+    /// Returns the first `gens_capacity` generators of each of the
+    /// first `party_capacity` parties, decompressed from the table.
+    ///
+    /// Returns [`ProofError::InvalidGeneratorsLength`] if the table
+    /// has fewer generators or parties, and
+    /// [`ProofError::InvalidGenerators`] if a generator in the table is
+    /// not a valid point.
+    pub fn gens(
+        &self,
+        gens_capacity: usize,
+        party_capacity: usize,
+    ) -> Result<Arc<BulletproofGens>, ProofError> {
+        if gens_capacity > self.gens_capacity || party_capacity > self.party_capacity {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(gens) = cache.iter().find(|gens| {
+            gens.gens_capacity == gens_capacity && gens.party_capacity == party_capacity
+        }) {
+            return Ok(gens.clone());
+        }
+
+        // The generators of party j are at index 2j of the table for G
+        // and 2j + 1 for H.
+        let points = |vector: usize| -> Result<Vec<RistrettoPoint>, ProofError> {
+            let start = HEADER_LEN + vector * self.gens_capacity * 32;
+            self.map[start..start + gens_capacity * 32]
+                .chunks(32)
+                .map(|bytes| {
+                    CompressedRistretto::from_slice(bytes)
+                        .ok()
+                        .and_then(|P| P.decompress())
+                        .ok_or(ProofError::InvalidGenerators)
+                })
+                .collect()
+        };
+        let gens = Arc::new(BulletproofGens {
+            gens_capacity,
+            party_capacity,
+            G_vec: (0..party_capacity)
+                .map(|j| points(2 * j))
+                .collect::<Result<_, _>>()?,
+            H_vec: (0..party_capacity)
+                .map(|j| points(2 * j + 1))
+                .collect::<Result<_, _>>()?,
+            fingerprint: self.fingerprint,
+        });
+
+        if cache.len() == CACHE_LEN {
+            cache.remove(0);
+        }
+        cache.push(gens.clone());
+        Ok(gens)
+    }
+}
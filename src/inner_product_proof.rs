@@ -3,27 +3,107 @@
 
 extern crate alloc;
 
+#[cfg(feature = "verifier")]
 use alloc::borrow::Borrow;
 use alloc::vec::Vec;
 
+use core::fmt;
+use core::hash::{Hash, Hasher};
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
 use curve25519_dalek::traits::VartimeMultiscalarMul;
+#[cfg(feature = "verifier")]
 use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use subtle::{Choice, ConstantTimeEq};
 
+use digest::Digest;
+use sha3::Sha3_256;
+
+use crate::consts::{ELEMENT_BYTES, IPP_FINAL_ELEMENTS};
 use crate::errors::ProofError;
-use crate::transcript::TranscriptProtocol;
+use crate::leakage::NoWitnessDebug;
+#[cfg(feature = "verifier")]
+use crate::transcript::{PublicVectorMode, TranscriptProtocol, TranscriptVersion};
+#[cfg(any(feature = "verifier", feature = "std"))]
+use crate::util;
+
+#[cfg(all(test, feature = "prover"))]
+pub(crate) mod reference;
+
+#[cfg(feature = "ipp-arity4")]
+mod arity4;
+#[cfg(feature = "ipp-arity4")]
+pub use self::arity4::{InnerProductProofArity4, IppArity};
+
+/// The largest vector length supported by proofs built on the
+/// inner-product argument, \\(2\^{31}\\).
+///
+/// This bounds the number of folding rounds of the argument, so that
+/// \\(2\^{\lg n}\\) fits in a `usize` even on 32-bit targets.
+pub const MAX_VECTOR_LENGTH: usize = 1 << MAX_LG_VECTOR_LENGTH;
+
+/// Binary logarithm of [`MAX_VECTOR_LENGTH`].
+const MAX_LG_VECTOR_LENGTH: usize = 31;
+
+/// The size in bytes of the largest inner-product proof accepted by
+/// [`InnerProductProof::from_bytes`].
+#[cfg(feature = "std")]
+pub(crate) const MAX_PROOF_SIZE: usize =
+    (2 * MAX_LG_VECTOR_LENGTH + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES;
+
+/// Returns an error if vectors of length `n` exceed [`MAX_VECTOR_LENGTH`].
+pub(crate) fn check_vector_length(n: usize) -> Result<(), ProofError> {
+    if n > MAX_VECTOR_LENGTH {
+        return Err(ProofError::StatementTooLarge {
+            requested: n,
+            max: MAX_VECTOR_LENGTH,
+        });
+    }
+    Ok(())
+}
+
+/// Returns an error if a proof with `lg_n` folding rounds would
+/// exceed [`MAX_VECTOR_LENGTH`].
+pub(crate) fn check_lg_vector_length(lg_n: usize) -> Result<(), ProofError> {
+    if lg_n > MAX_LG_VECTOR_LENGTH {
+        return Err(ProofError::StatementTooLarge {
+            requested: 1usize.checked_shl(lg_n as u32).unwrap_or(usize::MAX),
+            max: MAX_VECTOR_LENGTH,
+        });
+    }
+    Ok(())
+}
 
-#[derive(Clone, Debug)]
+/// Equality and hashing are bytewise over the canonical encoding
+/// returned by [`InnerProductProof::to_bytes`].
+///
+/// The fields are private, so every proof comes from
+/// [`InnerProductProof::create`] or [`InnerProductProof::from_bytes`],
+/// which guarantee that `L_vec` and `R_vec` have the same length, of
+/// at most \(\lg\) [`MAX_VECTOR_LENGTH`].  The verifiers check these
+/// again, so a proof built with `new_unchecked` is rejected rather
+/// than causing a panic.
+#[derive(Clone)]
 pub struct InnerProductProof {
-    pub(crate) L_vec: Vec<CompressedRistretto>,
-    pub(crate) R_vec: Vec<CompressedRistretto>,
-    pub(crate) a: Scalar,
-    pub(crate) b: Scalar,
+    L_vec: Vec<CompressedRistretto>,
+    R_vec: Vec<CompressedRistretto>,
+    a: Scalar,
+    b: Scalar,
 }
 
+/// The transcript scope of the inner-product proof.  The `L` and `R`
+/// points and `u` challenges of the proof are appended within this
+/// scope, separating them from the messages of the parent protocol.
+#[cfg(feature = "verifier")]
+const IPP_SCOPE: &[u8] = b"ipp";
+
 impl InnerProductProof {
+    #[cfg(feature = "prover")]
     /// Create an inner-product proof.
     ///
     /// The proof is created with respect to the bases \\(G\\), \\(H'\\),
@@ -34,7 +114,12 @@ impl InnerProductProof {
     /// protocols).
     ///
     /// The lengths of the vectors must all be the same, and must all be
-    /// either 0 or a power of 2.
+    /// a power of 2.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ or are not a power of 2.
+    #[allow(clippy::too_many_arguments)]
     pub fn create(
         transcript: &mut Transcript,
         Q: &RistrettoPoint,
@@ -65,8 +150,10 @@ impl InnerProductProof {
 
         // All of the input vectors must have a length that is a power of two.
         assert!(n.is_power_of_two());
+        trace_phase!("ipp.create", n);
 
-        transcript.innerproduct_domain_sep(n as u64);
+        transcript.subprotocol_domain_sep(IPP_SCOPE);
+        transcript.innerproduct_domain_sep(n);
 
         let lg_n = n.next_power_of_two().trailing_zeros() as usize;
         let mut L_vec = Vec::with_capacity(lg_n);
@@ -75,22 +162,23 @@ impl InnerProductProof {
         // If it's the first iteration, unroll the Hprime = H*y_inv scalar mults
         // into multiscalar muls, for performance.
         if n != 1 {
-            n = n / 2;
+            n /= 2;
+            trace_event!(n, "ipp round");
             let (a_L, a_R) = a.split_at_mut(n);
             let (b_L, b_R) = b.split_at_mut(n);
             let (G_L, G_R) = G.split_at_mut(n);
             let (H_L, H_R) = H.split_at_mut(n);
 
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
+            let c_L = inner_product(a_L, b_R);
+            let c_R = inner_product(a_R, b_L);
 
             let L = RistrettoPoint::vartime_multiscalar_mul(
                 a_L.iter()
-                    .zip(G_factors[n..2 * n].into_iter())
+                    .zip(G_factors[n..2 * n].iter())
                     .map(|(a_L_i, g)| a_L_i * g)
                     .chain(
                         b_R.iter()
-                            .zip(H_factors[0..n].into_iter())
+                            .zip(H_factors[0..n].iter())
                             .map(|(b_R_i, h)| b_R_i * h),
                     )
                     .chain(iter::once(c_L)),
@@ -100,11 +188,11 @@ impl InnerProductProof {
 
             let R = RistrettoPoint::vartime_multiscalar_mul(
                 a_R.iter()
-                    .zip(G_factors[0..n].into_iter())
+                    .zip(G_factors[0..n].iter())
                     .map(|(a_R_i, g)| a_R_i * g)
                     .chain(
                         b_L.iter()
-                            .zip(H_factors[n..2 * n].into_iter())
+                            .zip(H_factors[n..2 * n].iter())
                             .map(|(b_L_i, h)| b_L_i * h),
                     )
                     .chain(iter::once(c_R)),
@@ -141,14 +229,15 @@ impl InnerProductProof {
         }
 
         while n != 1 {
-            n = n / 2;
+            n /= 2;
+            trace_event!(n, "ipp round");
             let (a_L, a_R) = a.split_at_mut(n);
             let (b_L, b_R) = b.split_at_mut(n);
             let (G_L, G_R) = G.split_at_mut(n);
             let (H_L, H_R) = H.split_at_mut(n);
 
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
+            let c_L = inner_product(a_L, b_R);
+            let c_R = inner_product(a_R, b_L);
 
             let L = RistrettoPoint::vartime_multiscalar_mul(
                 a_L.iter().chain(b_R.iter()).chain(iter::once(&c_L)),
@@ -185,39 +274,225 @@ impl InnerProductProof {
         }
 
         InnerProductProof {
-            L_vec: L_vec,
-            R_vec: R_vec,
+            L_vec,
+            R_vec,
             a: a[0],
             b: b[0],
         }
     }
 
+    #[cfg(feature = "prover")]
+    /// Create an inner-product proof against a public vector
+    /// \\(\mathbf{b}\\).
+    ///
+    /// The proof shows knowledge of \\(\mathbf{a}\\) such that
+    /// \\[
+    ///    P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{a}, \mathbf{b} \rangle \cdot Q,
+    /// \\]
+    /// where \\(\mathbf{b}\\) is known to the verifier, for example
+    /// chosen by it after the prover committed to \\(\mathbf{a}\\).
+    /// With \\(\mathbf{a}\\) the coefficients of a polynomial and
+    /// \\(\mathbf{b} = (1, z, z\^2, \ldots)\\), this is an argument that
+    /// the committed polynomial evaluates to
+    /// \\(\langle \mathbf{a}, \mathbf{b} \rangle\\) at \\(z\\).
+    ///
+    /// Unlike [`InnerProductProof::create`], where both vectors are
+    /// witnesses committed to with the bases \\(G\\) and \\(H'\\), there
+    /// are no \\(H\\) bases: \\(\mathbf{b}\\) is appended to the
+    /// transcript before the first challenge, as selected by `mode`,
+    /// and the verifier folds it itself.  The final \\(b\\) of the proof is the folded vector,
+    /// which [`InnerProductProof::verify_with_public_b`] recomputes
+    /// rather than trusts.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ or are not a power of 2.
+    #[allow(dead_code)]
+    pub fn create_with_public_b(
+        transcript: &mut Transcript,
+        Q: &RistrettoPoint,
+        mut G_vec: Vec<RistrettoPoint>,
+        mut a_vec: Vec<Scalar>,
+        b_vec: &[Scalar],
+        mode: PublicVectorMode,
+    ) -> InnerProductProof {
+        let mut b_vec = b_vec.to_vec();
+        let mut G = &mut G_vec[..];
+        let mut a = &mut a_vec[..];
+        let mut b = &mut b_vec[..];
+
+        let mut n = G.len();
+        assert_eq!(a.len(), n);
+        assert_eq!(b.len(), n);
+        assert!(n.is_power_of_two());
+        trace_phase!("ipp.create_with_public_b", n);
+
+        transcript.subprotocol_domain_sep(IPP_SCOPE);
+        transcript.innerproduct_public_b_domain_sep(n);
+        transcript.append_public_scalars(b"b", b, mode);
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut L_vec = Vec::with_capacity(lg_n);
+        let mut R_vec = Vec::with_capacity(lg_n);
+
+        while n != 1 {
+            n /= 2;
+            trace_event!(n, "ipp round");
+            let (a_L, a_R) = a.split_at_mut(n);
+            let (b_L, b_R) = b.split_at_mut(n);
+            let (G_L, G_R) = G.split_at_mut(n);
+
+            let c_L = inner_product(a_L, b_R);
+            let c_R = inner_product(a_R, b_L);
+
+            let L = RistrettoPoint::vartime_multiscalar_mul(
+                a_L.iter().chain(iter::once(&c_L)),
+                G_R.iter().chain(iter::once(Q)),
+            )
+            .compress();
+
+            let R = RistrettoPoint::vartime_multiscalar_mul(
+                a_R.iter().chain(iter::once(&c_R)),
+                G_L.iter().chain(iter::once(Q)),
+            )
+            .compress();
+
+            L_vec.push(L);
+            R_vec.push(R);
+
+            transcript.append_point(b"L", &L);
+            transcript.append_point(b"R", &R);
+
+            let u = transcript.challenge_scalar(b"u");
+            let u_inv = u.invert();
+
+            for i in 0..n {
+                a_L[i] = a_L[i] * u + u_inv * a_R[i];
+                b_L[i] = b_L[i] * u_inv + u * b_R[i];
+                G_L[i] = RistrettoPoint::vartime_multiscalar_mul(&[u_inv, u], &[G_L[i], G_R[i]]);
+            }
+
+            a = a_L;
+            b = b_L;
+            G = G_L;
+        }
+
+        InnerProductProof {
+            L_vec,
+            R_vec,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    /// Creates a proof from its parts without checking them.
+    ///
+    /// Unlike [`InnerProductProof::from_bytes`], this accepts `L_vec`
+    /// and `R_vec` of different or excessive lengths, so that tests can
+    /// check that verifiers reject such proofs.  It is only available
+    /// with the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn new_unchecked(
+        L_vec: Vec<CompressedRistretto>,
+        R_vec: Vec<CompressedRistretto>,
+        a: Scalar,
+        b: Scalar,
+    ) -> Self {
+        InnerProductProof { L_vec, R_vec, a, b }
+    }
+
+    /// Returns the \(L\) points of the proof, one per folding round.
+    #[allow(dead_code)]
+    pub fn L_vec(&self) -> &[CompressedRistretto] {
+        &self.L_vec
+    }
+
+    /// Returns the \(R\) points of the proof, one per folding round.
+    #[allow(dead_code)]
+    pub fn R_vec(&self) -> &[CompressedRistretto] {
+        &self.R_vec
+    }
+
+    /// Returns the final scalar \(a\) of the proof.
+    #[allow(dead_code)]
+    pub fn a(&self) -> Scalar {
+        self.a
+    }
+
+    /// Returns the final scalar \(b\) of the proof.
+    #[allow(dead_code)]
+    pub fn b(&self) -> Scalar {
+        self.b
+    }
+
+    #[cfg(feature = "verifier")]
     /// Computes three vectors of verification scalars \\([u\_{i}^{2}]\\), \\([u\_{i}^{-2}]\\) and \\([s\_{i}]\\) for combined multiscalar multiplication
     /// in a parent protocol. See [inner product protocol notes](index.html#verification-equation) for details.
     /// The verifier must provide the input length \\(n\\) explicitly to avoid unbounded allocation within the inner product proof.
-    pub(crate) fn verification_scalars(
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn verification_scalars<T: TranscriptProtocol>(
         &self,
         n: usize,
-        transcript: &mut Transcript,
+        transcript: &mut T,
     ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
-        let lg_n = self.L_vec.len();
-        if lg_n >= 32 {
-            // 4 billion multiplications should be enough for anyone
-            // and this check prevents overflow in 1<<lg_n below.
+        let (u_sq, u_inv_sq, s, nonidentity) =
+            self.verification_scalars_ct(n, transcript, TranscriptVersion::V1)?;
+        if bool::from(!nonidentity) {
             return Err(ProofError::VerificationError);
         }
-        if n != (1 << lg_n) {
+        Ok((u_sq, u_inv_sq, s))
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Computes the verification scalars like
+    /// [`verification_scalars`](InnerProductProof::verification_scalars),
+    /// but without branching on the proof: every \(L\) and \(R\)
+    /// point is appended to the transcript, and whether none of them
+    /// is the identity is returned instead of checked.  Only the
+    /// public length \(n\) is checked.  The transcript is replayed
+    /// under the rules of `version`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn verification_scalars_ct<T: TranscriptProtocol>(
+        &self,
+        n: usize,
+        transcript: &mut T,
+        version: TranscriptVersion,
+    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Choice), ProofError> {
+        check_vector_length(n)?;
+        let lg_n = self.L_vec.len();
+        // This check prevents overflow in 1<<lg_n below.
+        check_lg_vector_length(lg_n)?;
+        if n != (1 << lg_n) || self.R_vec.len() != lg_n {
             return Err(ProofError::VerificationError);
         }
+        trace_phase!("ipp.verification_scalars", n);
+
+        if version != TranscriptVersion::V0 {
+            transcript.subprotocol_domain_sep(IPP_SCOPE);
+        }
+        transcript.innerproduct_domain_sep(n);
+
+        Ok(self.replay_challenges(transcript))
+    }
 
-        transcript.innerproduct_domain_sep(n as u64);
+    #[cfg(feature = "verifier")]
+    /// Appends the \(L\) and \(R\) points of the proof to the
+    /// transcript and computes the verification scalars from the
+    /// challenges, as in [`verification_scalars_ct`](InnerProductProof::verification_scalars_ct),
+    /// whose length checks the caller must have done.
+    fn replay_challenges<T: TranscriptProtocol>(
+        &self,
+        transcript: &mut T,
+    ) -> (Vec<Scalar>, Vec<Scalar>, Vec<Scalar>, Choice) {
+        let lg_n = self.L_vec.len();
 
         // 1. Recompute x_k,...,x_1 based on the proof transcript
 
         let mut challenges = Vec::with_capacity(lg_n);
+        let mut nonidentity = Choice::from(1);
         for (L, R) in self.L_vec.iter().zip(self.R_vec.iter()) {
-            transcript.validate_and_append_point(b"L", L)?;
-            transcript.validate_and_append_point(b"R", R)?;
+            nonidentity &= transcript.append_and_check_point(b"L", L);
+            nonidentity &= transcript.append_and_check_point(b"R", R);
             challenges.push(transcript.challenge_scalar(b"u"));
         }
 
@@ -236,27 +511,20 @@ impl InnerProductProof {
         let challenges_sq = challenges;
         let challenges_inv_sq = challenges_inv;
 
-        // 4. Compute s values inductively.
-
-        let mut s = Vec::with_capacity(n);
-        s.push(allinv);
-        for i in 1..n {
-            let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
-            let k = 1 << lg_i;
-            // The challenges are stored in "creation order" as [u_k,...,u_1],
-            // so u_{lg(i)+1} = is indexed by (lg_n-1) - lg_i
-            let u_lg_i_sq = challenges_sq[(lg_n - 1) - lg_i];
-            s.push(s[i - k] * u_lg_i_sq);
-        }
+        // 4. Compute s values from tables of partial products.
+
+        let s = SValues::new(allinv, &challenges_sq).collect();
 
-        Ok((challenges_sq, challenges_inv_sq, s))
+        (challenges_sq, challenges_inv_sq, s, nonidentity)
     }
 
+    #[cfg(feature = "verifier")]
     /// This method is for testing that proof generation work,
     /// but for efficiency the actual protocols would use `verification_scalars`
     /// method to combine inner product verification with other checks
     /// in a single multiscalar multiplication.
     #[allow(dead_code)]
+    #[allow(clippy::too_many_arguments)]
     pub fn verify<IG, IH>(
         &self,
         n: usize,
@@ -274,7 +542,89 @@ impl InnerProductProof {
         IH: IntoIterator,
         IH::Item: Borrow<Scalar>,
     {
-        let (u_sq, u_inv_sq, s) = self.verification_scalars(n, transcript)?;
+        // Work on a copy of the transcript, so that it is left unchanged
+        // if verification fails.
+        let mut working_transcript = transcript.clone();
+        let (u_sq, u_inv_sq, s) = self.verification_scalars(n, &mut working_transcript)?;
+        let (Ls, Rs) = self.decompress_points()?;
+
+        // Compute all the scalars before the multiplication, so that it
+        // reads them and the points as contiguous slices.
+        let mut scalars = Vec::with_capacity(1 + G.len() + H.len() + 2 * u_sq.len());
+        scalars.push(self.a * self.b);
+        scalars.extend(
+            G_factors
+                .into_iter()
+                .zip(s.iter())
+                .map(|(g_i, s_i)| (self.a * s_i) * g_i.borrow())
+                .take(G.len()),
+        );
+        // 1/s[i] is s[!i], and !i runs from n-1 to 0 as i runs from 0 to n-1
+        scalars.extend(
+            H_factors
+                .into_iter()
+                .zip(s.iter().rev())
+                .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow()),
+        );
+        scalars.extend(u_sq.iter().map(|ui| -ui));
+        scalars.extend(u_inv_sq.iter().map(|ui| -ui));
+
+        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
+            &scalars,
+            iter::once(Q)
+                .chain(G.iter())
+                .chain(H.iter())
+                .chain(Ls.iter())
+                .chain(Rs.iter()),
+        );
+
+        if expect_P == *P {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof like [`InnerProductProof::verify`], in time
+    /// independent of whether and where the proof is invalid.
+    ///
+    /// Every point is decompressed and absorbed into the transcript
+    /// before any check, the verification equation is evaluated with a
+    /// constant-time multiscalar multiplication, and the only branch on
+    /// the proof is on the final result.  Errors for a vector length
+    /// that does not match the proof depend only on public sizes and
+    /// are returned early.
+    ///
+    /// This is for verifiers for whom the validity of a proof is itself
+    /// secret.  The constant-time multiplication is markedly slower
+    /// than the variable-time one used by [`InnerProductProof::verify`],
+    /// increasingly so for longer vectors.
+    #[allow(dead_code)]
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_ct<IG, IH>(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+        G_factors: IG,
+        H_factors: IH,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        G: &[RistrettoPoint],
+        H: &[RistrettoPoint],
+    ) -> Result<(), ProofError>
+    where
+        IG: IntoIterator,
+        IG::Item: Borrow<Scalar>,
+        IH: IntoIterator,
+        IH::Item: Borrow<Scalar>,
+    {
+        let (Ls, Rs, decompressed) = self.decompress_points_ct();
+
+        let mut working_transcript = transcript.clone();
+        let (u_sq, u_inv_sq, s, nonidentity) =
+            self.verification_scalars_ct(n, &mut working_transcript, TranscriptVersion::V1)?;
 
         let g_times_a_times_s = G_factors
             .into_iter()
@@ -293,19 +643,8 @@ impl InnerProductProof {
         let neg_u_sq = u_sq.iter().map(|ui| -ui);
         let neg_u_inv_sq = u_inv_sq.iter().map(|ui| -ui);
 
-        let Ls = self
-            .L_vec
-            .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let Rs = self
-            .R_vec
-            .iter()
-            .map(|p| p.decompress().ok_or(ProofError::VerificationError))
-            .collect::<Result<Vec<_>, _>>()?;
-
-        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
+        use curve25519_dalek::traits::MultiscalarMul;
+        let expect_P = RistrettoPoint::multiscalar_mul(
             iter::once(self.a * self.b)
                 .chain(g_times_a_times_s)
                 .chain(h_times_b_div_s)
@@ -318,20 +657,146 @@ impl InnerProductProof {
                 .chain(Rs.iter()),
         );
 
+        if bool::from(decompressed & nonidentity & expect_P.ct_eq(P)) {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies a proof created by
+    /// [`InnerProductProof::create_with_public_b`] that
+    /// \\(P = \langle \mathbf{a}, \mathbf{G} \rangle + \langle \mathbf{a}, \mathbf{b} \rangle \cdot Q\\)
+    /// for the public vector `b_vec`, absorbed into the transcript as
+    /// selected by `mode`.
+    ///
+    /// The \\(\mathbf{b}\\) side of the verification equation is
+    /// computed by folding `b_vec` with the challenges of the proof, and
+    /// the proof is rejected if its final \\(b\\) differs.  The
+    /// transcript is left unchanged if verification fails.
+    #[allow(dead_code)]
+    pub fn verify_with_public_b(
+        &self,
+        transcript: &mut Transcript,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        G: &[RistrettoPoint],
+        b_vec: &[Scalar],
+        mode: PublicVectorMode,
+    ) -> Result<(), ProofError> {
+        let n = b_vec.len();
+        check_vector_length(n)?;
+        let lg_n = self.L_vec.len();
+        check_lg_vector_length(lg_n)?;
+        if G.len() != n || n != (1 << lg_n) || self.R_vec.len() != lg_n {
+            return Err(ProofError::VerificationError);
+        }
+
+        let mut working_transcript = transcript.clone();
+        working_transcript.subprotocol_domain_sep(IPP_SCOPE);
+        working_transcript.innerproduct_public_b_domain_sep(n);
+        working_transcript.append_public_scalars(b"b", b_vec, mode);
+        let (u_sq, u_inv_sq, s, nonidentity) = self.replay_challenges(&mut working_transcript);
+        if bool::from(!nonidentity) {
+            return Err(ProofError::VerificationError);
+        }
+        let (Ls, Rs) = self.decompress_points()?;
+
+        // b is folded like G, so it ends up as <b, s>
+        let b: Scalar = b_vec.iter().zip(s.iter()).map(|(b_i, s_i)| b_i * s_i).sum();
+        if b != self.b {
+            return Err(ProofError::VerificationError);
+        }
+
+        let mut scalars = Vec::with_capacity(1 + n + 2 * lg_n);
+        scalars.push(self.a * b);
+        scalars.extend(s.iter().map(|s_i| self.a * s_i));
+        scalars.extend(u_sq.iter().map(|ui| -ui));
+        scalars.extend(u_inv_sq.iter().map(|ui| -ui));
+
+        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
+            &scalars,
+            iter::once(Q)
+                .chain(G.iter())
+                .chain(Ls.iter())
+                .chain(Rs.iter()),
+        );
+
         if expect_P == *P {
+            *transcript = working_transcript;
             Ok(())
         } else {
             Err(ProofError::VerificationError)
         }
     }
 
+    /// Decompresses the \\(L\\) and \\(R\\) points of the proof.
+    ///
+    /// Parent protocols decompress these once, together with their own
+    /// points, and reuse them for every verification.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn decompress_points(
+        &self,
+    ) -> Result<(Vec<RistrettoPoint>, Vec<RistrettoPoint>), ProofError> {
+        let decompress =
+            |P: &CompressedRistretto| P.decompress().ok_or(ProofError::VerificationError);
+        let Ls = self
+            .L_vec
+            .iter()
+            .map(decompress)
+            .collect::<Result<Vec<_>, _>>()?;
+        let Rs = self
+            .R_vec
+            .iter()
+            .map(decompress)
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok((Ls, Rs))
+    }
+
+    /// Decompresses every \\(L\\) and \\(R\\) point of the proof
+    /// without returning early, substituting the identity for invalid
+    /// encodings, and returns whether all of them were valid.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn decompress_points_ct(
+        &self,
+    ) -> (Vec<RistrettoPoint>, Vec<RistrettoPoint>, Choice) {
+        let mut valid = Choice::from(1);
+        let Ls = self
+            .L_vec
+            .iter()
+            .map(|L| util::decompress_ct(L, &mut valid))
+            .collect();
+        let Rs = self
+            .R_vec
+            .iter()
+            .map(|R| util::decompress_ct(R, &mut valid))
+            .collect();
+        (Ls, Rs, valid)
+    }
+
+    /// Returns the length \\(n\\) of the vectors the proof is for.
+    pub(crate) fn vector_length(&self) -> usize {
+        1usize
+            .checked_shl(self.L_vec.len() as u32)
+            .unwrap_or(usize::MAX)
+    }
+
+    /// Returns the SHA3-256 hash of the serialized proof, for
+    /// correlating log entries without logging the whole proof.
+    #[allow(dead_code)]
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha3_256::digest(self.to_bytes()).into()
+    }
+
     /// Returns the size in bytes required to serialize the inner
     /// product proof.
     ///
     /// For vectors of length `n` the proof size is
     /// \\(32 \cdot (2\lg n+2)\\) bytes.
     pub fn serialized_size(&self) -> usize {
-        (self.L_vec.len() * 2 + 2) * 32
+        (self.L_vec.len() * 2 + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES
     }
 
     /// Serializes the proof into a byte array of \\(2n+2\\) 32-byte elements.
@@ -340,52 +805,66 @@ impl InnerProductProof {
     /// * two scalars \\(a, b\\).
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
-        for (l, r) in self.L_vec.iter().zip(self.R_vec.iter()) {
-            buf.extend_from_slice(l.as_bytes());
-            buf.extend_from_slice(r.as_bytes());
-        }
-        buf.extend_from_slice(self.a.as_bytes());
-        buf.extend_from_slice(self.b.as_bytes());
+        buf.extend(self.elements().flatten());
         buf
     }
 
-    /// Converts the proof into a byte iterator over serialized view of the proof.
-    /// The layout of the inner product proof is:
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0, R_0 \dots, L_{n-1}, R_{n-1}\\),
-    /// * two scalars \\(a, b\\).
-    #[inline]
-    pub(crate) fn to_bytes_iter(&self) -> impl Iterator<Item = u8> + '_ {
+    /// Returns the 32-byte elements of the encoding of the proof, in
+    /// the layout of [`InnerProductProof::to_bytes`].
+    pub(crate) fn elements(&self) -> impl Iterator<Item = &[u8; 32]> + '_ {
         self.L_vec
             .iter()
             .zip(self.R_vec.iter())
-            .flat_map(|(l, r)| l.as_bytes().iter().chain(r.as_bytes()))
-            .chain(self.a.as_bytes())
-            .chain(self.b.as_bytes())
-            .copied()
+            .flat_map(|(l, r)| iter::once(l.as_bytes()).chain(iter::once(r.as_bytes())))
+            .chain(iter::once(self.a.as_bytes()))
+            .chain(iter::once(self.b.as_bytes()))
+    }
+
+    /// Writes the encoding of [`InnerProductProof::to_bytes`] to
+    /// `writer` one element at a time, without buffering the proof,
+    /// and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    #[allow(dead_code)]
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> std::io::Result<usize> {
+        util::write_elements(writer, self.elements())
+    }
+
+    /// Reads a proof encoded as by [`InnerProductProof::to_bytes`] from
+    /// `reader`, which must end where the proof ends.
+    ///
+    /// At most one byte more than the largest valid proof is read
+    /// before an oversized input is rejected.  Encodings that
+    /// [`InnerProductProof::from_bytes`] rejects are errors of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).
+    #[cfg(feature = "std")]
+    #[allow(dead_code)]
+    pub fn read_from<R: std::io::Read>(reader: R) -> std::io::Result<InnerProductProof> {
+        util::read_bounded(reader, MAX_PROOF_SIZE, InnerProductProof::from_bytes)
     }
 
     /// Deserializes the proof from a byte slice.
     /// Returns an error in the following cases:
     /// * the slice does not have \\(2n+2\\) 32-byte elements,
-    /// * \\(n\\) is larger or equal to 32 (proof is too big),
+    /// * \\(2^n\\) is larger than [`MAX_VECTOR_LENGTH`] (proof is too big),
     /// * any of \\(2n\\) points are not valid compressed Ristretto points,
     /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
+    ///
+    /// Since every scalar must be canonically encoded, a proof has
+    /// exactly one valid byte representation.
     pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProof, ProofError> {
         let b = slice.len();
-        if b % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        let num_elements = b / 32;
-        if num_elements < 2 {
+        if !b.is_multiple_of(ELEMENT_BYTES) {
             return Err(ProofError::FormatError);
         }
-        if (num_elements - 2) % 2 != 0 {
+        let num_elements = b / ELEMENT_BYTES;
+        if num_elements < IPP_FINAL_ELEMENTS {
             return Err(ProofError::FormatError);
         }
-        let lg_n = (num_elements - 2) / 2;
-        if lg_n >= 32 {
+        if !(num_elements - IPP_FINAL_ELEMENTS).is_multiple_of(2) {
             return Err(ProofError::FormatError);
         }
+        let lg_n = (num_elements - IPP_FINAL_ELEMENTS) / 2;
+        check_lg_vector_length(lg_n)?;
 
         use crate::util::read32;
 
@@ -407,6 +886,120 @@ impl InnerProductProof {
     }
 }
 
+/// Number of low-order index bits covered by the inner table of
+/// [`SValues`], chosen so that the table stays in the L1 cache.
+#[cfg(feature = "verifier")]
+const S_BLOCK_LG: usize = 8;
+
+/// Iterator over the verification scalars
+/// \(s\_i = u\_k^{-1} \cdots u\_1^{-1} \prod\_{j} u\_{j+1}^{2 b(i,j)}\),
+/// where \(b(i,j)\) is bit \(j\) of \(i\).
+///
+/// Instead of building \(\mathbf{s}\) by repeated doubling passes
+/// over the whole vector, each \(s\_i\) is the product of an entry of
+/// a table over the low bits of \(i\) and an entry of a table over
+/// its high bits.  Both tables are \(O(\sqrt{n})\) in size, so the
+/// values are produced in order with one multiplication each and
+/// without revisiting earlier output.
+#[cfg(feature = "verifier")]
+struct SValues {
+    low: Vec<Scalar>,
+    high: Vec<Scalar>,
+    block_lg: usize,
+    i: usize,
+}
+
+#[cfg(feature = "verifier")]
+impl SValues {
+    /// Takes the squared challenges in "creation order" \([u\_k^2,
+    /// \ldots, u\_1^2]\), so bit \(j\) of the index selects
+    /// `challenges_sq[lg_n - 1 - j]`.
+    fn new(allinv: Scalar, challenges_sq: &[Scalar]) -> Self {
+        let lg_n = challenges_sq.len();
+        let block_lg = lg_n.min(S_BLOCK_LG);
+        let products = |bits: core::ops::Range<usize>, init: Scalar| {
+            let mut table = Vec::with_capacity(1 << bits.len());
+            table.push(init);
+            for j in bits {
+                let u_sq = challenges_sq[lg_n - 1 - j];
+                for t in 0..table.len() {
+                    let product = table[t] * u_sq;
+                    table.push(product);
+                }
+            }
+            table
+        };
+        SValues {
+            low: products(0..block_lg, Scalar::ONE),
+            high: products(block_lg..lg_n, allinv),
+            block_lg,
+            i: 0,
+        }
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl Iterator for SValues {
+    type Item = Scalar;
+
+    fn next(&mut self) -> Option<Scalar> {
+        let high = self.high.get(self.i >> self.block_lg)?;
+        let low = self.low[self.i & (self.low.len() - 1)];
+        self.i += 1;
+        Some(high * low)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.low.len() * self.high.len() - self.i;
+        (size, Some(size))
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl ExactSizeIterator for SValues {}
+
+impl PartialEq for InnerProductProof {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for InnerProductProof {}
+
+impl Hash for InnerProductProof {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl fmt::Debug for InnerProductProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("InnerProductProof")
+            .field("lg_n", &self.L_vec.len())
+            .field("bytes", &self.serialized_size())
+            .finish()
+    }
+}
+
+impl fmt::Display for InnerProductProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "InnerProductProof {{ n={}, bytes={} }}",
+            self.vector_length(),
+            self.serialized_size()
+        )
+    }
+}
+
+/// Every element of the proof is derived from the folded vectors.
+impl NoWitnessDebug for InnerProductProof {
+    fn witness_derived_elements(&self) -> Vec<[u8; 32]> {
+        self.elements().copied().collect()
+    }
+}
+
+#[cfg(any(feature = "prover", feature = "yoloproofs"))]
 /// Computes an inner product of two vectors
 /// \\[
 ///    {\langle {\mathbf{a}}, {\mathbf{b}} \rangle} = \sum\_{i=0}^{n-1} a\_i \cdot b\_i.
@@ -435,8 +1028,20 @@ mod tests {
 
         use crate::generators::BulletproofGens;
         let bp_gens = BulletproofGens::new(n, 1);
-        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
-        let H: Vec<RistrettoPoint> = bp_gens.share(0).H(n).cloned().collect();
+        let G: Vec<RistrettoPoint> = bp_gens
+            .try_share(0)
+            .unwrap()
+            .G_checked(n)
+            .unwrap()
+            .cloned()
+            .collect();
+        let H: Vec<RistrettoPoint> = bp_gens
+            .try_share(0)
+            .unwrap()
+            .H_checked(n)
+            .unwrap()
+            .cloned()
+            .collect();
 
         // Q would be determined upstream in the protocol, so we pick a random one.
         let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
@@ -446,7 +1051,7 @@ mod tests {
         let b: Vec<_> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
         let c = inner_product(&a, &b);
 
-        let G_factors: Vec<Scalar> = iter::repeat(Scalar::ONE).take(n).collect();
+        let G_factors: Vec<Scalar> = iter::repeat_n(Scalar::ONE, n).collect();
 
         // y_inv is (the inverse of) a random challenge
         let y_inv = Scalar::random(&mut rng);
@@ -483,7 +1088,7 @@ mod tests {
             .verify(
                 n,
                 &mut verifier,
-                iter::repeat(Scalar::ONE).take(n),
+                iter::repeat_n(Scalar::ONE, n),
                 util::exp_iter(y_inv).take(n),
                 &P,
                 &Q,
@@ -492,13 +1097,26 @@ mod tests {
             )
             .is_ok());
 
-        let proof = InnerProductProof::from_bytes(proof.to_bytes().as_slice()).unwrap();
+        let bytes = proof.to_bytes();
+        util::assert_noncanonical_scalars_rejected(
+            &bytes,
+            &[bytes.len() - 64, bytes.len() - 32],
+            InnerProductProof::from_bytes,
+        );
+
+        let decoded = InnerProductProof::from_bytes(bytes.as_slice()).unwrap();
+        assert_eq!(decoded, proof);
+        let mut written = Vec::new();
+        assert_eq!(proof.write_to(&mut written).unwrap(), bytes.len());
+        assert_eq!(written, bytes);
+        assert_eq!(InnerProductProof::read_from(&bytes[..]).unwrap(), proof);
+        let proof = decoded;
         let mut verifier = Transcript::new(b"innerproducttest");
         assert!(proof
             .verify(
                 n,
                 &mut verifier,
-                iter::repeat(Scalar::ONE).take(n),
+                iter::repeat_n(Scalar::ONE, n),
                 util::exp_iter(y_inv).take(n),
                 &P,
                 &Q,
@@ -506,6 +1124,47 @@ mod tests {
                 &H
             )
             .is_ok());
+
+        // The constant-time verifier agrees with the variable-time one
+        // on the proof and on corruptions of each of its elements.
+        let both = |proof: &InnerProductProof, P: &RistrettoPoint| {
+            let verify = |ct: bool| {
+                let mut verifier = Transcript::new(b"innerproducttest");
+                let G_factors = iter::repeat_n(Scalar::ONE, n);
+                let H_factors = util::exp_iter(y_inv).take(n);
+                if ct {
+                    proof.verify_ct(n, &mut verifier, G_factors, H_factors, P, &Q, &G, &H)
+                } else {
+                    proof.verify(n, &mut verifier, G_factors, H_factors, P, &Q, &G, &H)
+                }
+            };
+            let result = verify(false);
+            assert_eq!(verify(true), result);
+            result
+        };
+        assert!(both(&proof, &P).is_ok());
+        assert!(both(&proof, &(P + Q)).is_err());
+        for i in 0..bytes.len() / 32 {
+            for corrupt in [|byte: &mut u8| *byte ^= 1, |byte: &mut u8| *byte = 0] {
+                let mut corrupted = bytes.clone();
+                corrupted[32 * i..32 * (i + 1)].iter_mut().for_each(corrupt);
+                if let Ok(corrupted) = InnerProductProof::from_bytes(&corrupted) {
+                    assert!(both(&corrupted, &P).is_err());
+                }
+            }
+        }
+
+        // Proofs whose L and R points differ in number cannot be
+        // parsed, and are rejected rather than causing a panic.
+        if n > 1 {
+            let truncated = InnerProductProof::new_unchecked(
+                proof.L_vec().to_vec(),
+                proof.R_vec()[1..].to_vec(),
+                proof.a(),
+                proof.b(),
+            );
+            assert!(both(&truncated, &P).is_err());
+        }
     }
 
     #[test]
@@ -533,6 +1192,183 @@ mod tests {
         test_helper_create(64);
     }
 
+    #[test]
+    fn public_b_proves_a_polynomial_evaluation() {
+        let mut rng = rand::thread_rng();
+        let n = 16;
+
+        use crate::generators::BulletproofGens;
+        let bp_gens = BulletproofGens::new(n, 1);
+        let G: Vec<RistrettoPoint> = bp_gens
+            .try_share(0)
+            .unwrap()
+            .G_checked(n)
+            .unwrap()
+            .cloned()
+            .collect();
+
+        // The prover commits to the coefficients of a degree-15 polynomial
+        // f, and the verifier replies with an evaluation point z and a
+        // base Q for the evaluation.
+        let f: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let C = RistrettoPoint::vartime_multiscalar_mul(&f, &G);
+        let challenges = |transcript: &mut Transcript| {
+            transcript.append_point(b"C", &C.compress());
+            let z = transcript.challenge_scalar(b"z");
+            let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(
+                transcript.challenge_scalar(b"Q").as_bytes(),
+            );
+            (z, Q)
+        };
+
+        let mut prover = Transcript::new(b"polyevaltest");
+        let (z, Q) = challenges(&mut prover);
+        let z_powers: Vec<Scalar> = util::exp_iter(z).take(n).collect();
+        let y = inner_product(&f, &z_powers);
+        let proof = InnerProductProof::create_with_public_b(
+            &mut prover,
+            &Q,
+            G.clone(),
+            f,
+            &z_powers,
+            PublicVectorMode::Direct,
+        );
+
+        let verify = |y: Scalar| {
+            let mut verifier = Transcript::new(b"polyevaltest");
+            let (z, Q) = challenges(&mut verifier);
+            let z_powers: Vec<Scalar> = util::exp_iter(z).take(n).collect();
+            proof.verify_with_public_b(
+                &mut verifier,
+                &(C + y * Q),
+                &Q,
+                &G,
+                &z_powers,
+                PublicVectorMode::Direct,
+            )
+        };
+        assert!(verify(y).is_ok());
+        assert!(verify(y + Scalar::ONE).is_err());
+
+        // The proof is bound to b, not only to its folded value.
+        let mut verifier = Transcript::new(b"polyevaltest");
+        let (_, Q) = challenges(&mut verifier);
+        let other: Vec<Scalar> = util::exp_iter(z + Scalar::ONE).take(n).collect();
+        assert!(proof
+            .verify_with_public_b(
+                &mut verifier,
+                &(C + y * Q),
+                &Q,
+                &G,
+                &other,
+                PublicVectorMode::Direct
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn public_b_modes_must_agree() {
+        let mut rng = rand::thread_rng();
+        let n = 64;
+        let G: Vec<RistrettoPoint> = (0..n).map(|_| RistrettoPoint::random(&mut rng)).collect();
+        let a: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
+        let P = RistrettoPoint::vartime_multiscalar_mul(&a, &G) + inner_product(&a, &b) * Q;
+
+        let modes = [PublicVectorMode::Direct, PublicVectorMode::Hashed];
+        for &prover_mode in &modes {
+            let proof = InnerProductProof::create_with_public_b(
+                &mut Transcript::new(b"publicbtest"),
+                &Q,
+                G.clone(),
+                a.clone(),
+                &b,
+                prover_mode,
+            );
+            for &verifier_mode in &modes {
+                let result = proof.verify_with_public_b(
+                    &mut Transcript::new(b"publicbtest"),
+                    &P,
+                    &Q,
+                    &G,
+                    &b,
+                    verifier_mode,
+                );
+                assert_eq!(result.is_ok(), prover_mode == verifier_mode);
+            }
+
+            // A hashed b is bound as tightly as an appended one.
+            let mut other = b.clone();
+            other[n - 1] += Scalar::ONE;
+            let P = P + a[n - 1] * Q;
+            assert!(proof
+                .verify_with_public_b(
+                    &mut Transcript::new(b"publicbtest"),
+                    &P,
+                    &Q,
+                    &G,
+                    &other,
+                    prover_mode
+                )
+                .is_err());
+        }
+    }
+
+    #[test]
+    fn public_b_rejects_mismatched_lengths() {
+        let mut rng = rand::thread_rng();
+        let G: Vec<RistrettoPoint> = (0..4).map(|_| RistrettoPoint::random(&mut rng)).collect();
+        let a: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let b: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+        let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"test point");
+        let P = RistrettoPoint::vartime_multiscalar_mul(&a, &G) + inner_product(&a, &b) * Q;
+        let proof = InnerProductProof::create_with_public_b(
+            &mut Transcript::new(b"publicbtest"),
+            &Q,
+            G.clone(),
+            a,
+            &b,
+            PublicVectorMode::Direct,
+        );
+        let verify = |G: &[RistrettoPoint], b: &[Scalar]| {
+            proof.verify_with_public_b(
+                &mut Transcript::new(b"publicbtest"),
+                &P,
+                &Q,
+                G,
+                b,
+                PublicVectorMode::Direct,
+            )
+        };
+        assert!(verify(&G, &b).is_ok());
+        assert!(verify(&G[..2], &b).is_err());
+        assert!(verify(&G[..2], &b[..2]).is_err());
+        assert!(verify(&G[..3], &b[..3]).is_err());
+    }
+
+    #[test]
+    fn s_values_match_inductive_construction() {
+        let mut rng = rand::thread_rng();
+        for lg_n in 0..=12 {
+            let n = 1usize << lg_n;
+            let allinv = Scalar::random(&mut rng);
+            let challenges_sq: Vec<Scalar> = (0..lg_n).map(|_| Scalar::random(&mut rng)).collect();
+
+            let mut expected = Vec::with_capacity(n);
+            expected.push(allinv);
+            for i in 1..n {
+                let lg_i = (32 - 1 - (i as u32).leading_zeros()) as usize;
+                let k = 1 << lg_i;
+                expected.push(expected[i - k] * challenges_sq[(lg_n - 1) - lg_i]);
+            }
+
+            let s = SValues::new(allinv, &challenges_sq);
+            assert_eq!(s.len(), n);
+            assert_eq!(s.collect::<Vec<_>>(), expected);
+        }
+    }
+
     #[test]
     fn test_inner_product() {
         let a = vec![
@@ -549,4 +1385,65 @@ mod tests {
         ];
         assert_eq!(Scalar::from(40u64), inner_product(&a, &b));
     }
+
+    #[test]
+    fn debug_output_is_bounded() {
+        let proof = |lg_n| InnerProductProof {
+            L_vec: vec![CompressedRistretto::default(); lg_n],
+            R_vec: vec![CompressedRistretto::default(); lg_n],
+            a: Scalar::ONE,
+            b: Scalar::ONE,
+        };
+        assert_eq!(
+            format!("{:?}", proof(31)),
+            "InnerProductProof { lg_n: 31, bytes: 2048 }"
+        );
+        assert_eq!(
+            format!("{}", proof(31)),
+            "InnerProductProof { n=2147483648, bytes=2048 }"
+        );
+        assert_eq!(
+            proof(5).fingerprint(),
+            <[u8; 32]>::from(Sha3_256::digest(proof(5).to_bytes()))
+        );
+        assert_ne!(proof(5).fingerprint(), proof(6).fingerprint());
+    }
+
+    #[test]
+    fn oversized_statements_are_rejected() {
+        let too_large = |requested| {
+            Err(ProofError::StatementTooLarge {
+                requested,
+                max: MAX_VECTOR_LENGTH,
+            })
+        };
+
+        // A proof claiming 2^40 folding rounds, built without allocating
+        // anything proportional to 2^40.
+        let proof = InnerProductProof::new_unchecked(
+            vec![CompressedRistretto::default(); 40],
+            vec![CompressedRistretto::default(); 40],
+            Scalar::ONE,
+            Scalar::ONE,
+        );
+        for &n in &[usize::MAX, 1 << 40, MAX_VECTOR_LENGTH + 1] {
+            let mut transcript = Transcript::new(b"innerproducttest");
+            assert_eq!(
+                proof.verification_scalars(n, &mut transcript).map(|_| ()),
+                too_large(n)
+            );
+        }
+        let mut transcript = Transcript::new(b"innerproducttest");
+        assert_eq!(
+            proof.verification_scalars(4, &mut transcript).map(|_| ()),
+            too_large(1 << 40)
+        );
+
+        // An encoding with 32 rounds is just over the limit.
+        let bytes = vec![0u8; (2 * 32 + 2) * 32];
+        assert_eq!(
+            InnerProductProof::from_bytes(&bytes).map(|_| ()),
+            too_large(1 << 32)
+        );
+    }
 }
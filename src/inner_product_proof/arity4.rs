@@ -0,0 +1,656 @@
+//! An experimental inner-product argument that folds the vectors to a
+//! quarter of their length in each round, available with the
+//! `ipp-arity4` feature.
+//!
+//! A round of arity \\(k\\) splits each vector into \\(k\\) parts
+//! \\(\mathbf{a}\_0, \ldots, \mathbf{a}\_{k-1}\\), sends one point
+//! \\(X\_d\\) for each offset \\(d = i - j \neq 0\\) between parts,
+//!
+//! \\[
+//! X\_d = \sum\_{i - j = d} \left( \langle \mathbf{a}\_i, \mathbf{G}\_j \rangle + \langle \mathbf{a}\_i, \mathbf{b}\_j \rangle Q \right) + \sum\_{j - i = d} \langle \mathbf{b}\_i, \mathbf{H}\_j \rangle,
+//! \\]
+//!
+//! and folds with the challenge \\(x\\) to
+//! \\(\mathbf{a}' = \sum\_i x\^i \mathbf{a}\_i\\),
+//! \\(\mathbf{b}' = \sum\_i x\^{-i} \mathbf{b}\_i\\),
+//! \\(\mathbf{G}' = \sum\_i x\^{-i} \mathbf{G}\_i\\), and
+//! \\(\mathbf{H}' = \sum\_i x\^i \mathbf{H}\_i\\), for which
+//! \\(P' = P + \sum\_d x\^d X\_d\\).
+//!
+//! A 4-ary round sends 6 points and divides the length by 4, where two
+//! binary rounds send 4 points, so the proof has \\(3 \lg n\\) points
+//! instead of \\(2 \lg n\\), but only half as many challenges.  If
+//! \\(\lg n\\) is odd, the first round is binary.
+//!
+//! The proof uses its own transcript scope, and its encoding starts
+//! with a tag byte, so it is never mistaken for a binary proof; see
+//! [`IppArity::of_encoding`].
+
+#![allow(non_snake_case)]
+
+use alloc::borrow::Borrow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::iter;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::VartimeMultiscalarMul;
+use merlin::Transcript;
+
+#[cfg(feature = "prover")]
+use super::inner_product;
+use super::{check_lg_vector_length, check_vector_length};
+use crate::consts::{ELEMENT_BYTES, IPP_FINAL_ELEMENTS};
+use crate::errors::ProofError;
+use crate::transcript::TranscriptProtocol;
+
+/// The transcript scope of the 4-ary inner-product proof.
+pub(super) const ARITY4_SCOPE: &[u8] = b"ipp-arity4 v1";
+
+/// The first byte of the encoding of a 4-ary inner-product proof.
+const ARITY4_TAG: u8 = 4;
+
+/// The arity of an encoded inner-product proof.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum IppArity {
+    /// An [`InnerProductProof`](super::InnerProductProof), which halves
+    /// the vectors in each round.
+    Two,
+    /// An [`InnerProductProofArity4`], which quarters the vectors in
+    /// each round.
+    Four,
+}
+
+impl IppArity {
+    /// Returns the arity of the proof encoded by `bytes`.
+    ///
+    /// Binary encodings are a whole number of 32-byte elements, and
+    /// 4-ary encodings have an additional tag byte, so their lengths
+    /// never coincide.  The encoding is not otherwise checked.
+    /// Returns [`ProofError::FormatError`] if it has neither length.
+    pub fn of_encoding(bytes: &[u8]) -> Result<IppArity, ProofError> {
+        match (bytes.len() % ELEMENT_BYTES, bytes.first()) {
+            (0, _) => Ok(IppArity::Two),
+            (1, Some(&ARITY4_TAG)) => Ok(IppArity::Four),
+            _ => Err(ProofError::FormatError),
+        }
+    }
+}
+
+/// An inner-product proof with 4-ary folding.
+///
+/// The points \\(X\_d\\) of each round are stored in the order of
+/// their offsets \\(d = -(k-1), \ldots, -1, 1, \ldots, k-1\\).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InnerProductProofArity4 {
+    pub(super) points: Vec<CompressedRistretto>,
+    pub(super) a: Scalar,
+    pub(super) b: Scalar,
+}
+
+/// Returns the arities of the rounds for vectors of length
+/// \\(2\^{\lg n}\\): one binary round if \\(\lg n\\) is odd, then
+/// 4-ary rounds.
+pub(super) fn arities(lg_n: usize) -> impl Iterator<Item = usize> {
+    iter::repeat_n(2, lg_n % 2).chain(iter::repeat_n(4, lg_n / 2))
+}
+
+/// Returns the offsets \\(d\\) of the points of a round of arity `k`.
+pub(super) fn offsets(k: usize) -> impl Iterator<Item = isize> {
+    (1 - k as isize..k as isize).filter(|&d| d != 0)
+}
+
+/// Returns \\(\lg n\\) for a proof with `num_points` points, or `None`
+/// if no vector length gives that many.
+fn lg_n_of(num_points: usize) -> Option<usize> {
+    match num_points % 6 {
+        0 => Some(2 * (num_points / 6)),
+        2 => Some(2 * (num_points / 6) + 1),
+        _ => None,
+    }
+}
+
+/// Returns \\(x\^d\\), given \\(x\\) and \\(x\^{-1}\\).
+pub(super) fn power(x: &Scalar, x_inv: &Scalar, d: isize) -> Scalar {
+    let base = if d < 0 { x_inv } else { x };
+    iter::repeat_n(base, d.unsigned_abs()).product()
+}
+
+impl InnerProductProofArity4 {
+    #[cfg(feature = "prover")]
+    /// Creates a 4-ary inner-product proof, with the same statement
+    /// and requirements as [`InnerProductProof::create`](super::InnerProductProof::create).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the lengths differ or are not a power of 2.
+    #[allow(clippy::too_many_arguments)]
+    pub fn create(
+        transcript: &mut Transcript,
+        Q: &RistrettoPoint,
+        G_factors: &[Scalar],
+        H_factors: &[Scalar],
+        G_vec: Vec<RistrettoPoint>,
+        H_vec: Vec<RistrettoPoint>,
+        a_vec: Vec<Scalar>,
+        b_vec: Vec<Scalar>,
+    ) -> InnerProductProofArity4 {
+        let n = G_vec.len();
+        assert_eq!(H_vec.len(), n);
+        assert_eq!(a_vec.len(), n);
+        assert_eq!(b_vec.len(), n);
+        assert_eq!(G_factors.len(), n);
+        assert_eq!(H_factors.len(), n);
+        assert!(n.is_power_of_two());
+        trace_phase!("ipp.create_arity4", n);
+
+        transcript.subprotocol_domain_sep(ARITY4_SCOPE);
+        transcript.innerproduct_domain_sep(n);
+
+        // Unlike the binary prover, apply the factors to the generators
+        // up front rather than in the first round.
+        let mut G: Vec<RistrettoPoint> = G_vec
+            .iter()
+            .zip(G_factors)
+            .map(|(G_i, g_i)| g_i * G_i)
+            .collect();
+        let mut H: Vec<RistrettoPoint> = H_vec
+            .iter()
+            .zip(H_factors)
+            .map(|(H_i, h_i)| h_i * H_i)
+            .collect();
+        let mut a = a_vec;
+        let mut b = b_vec;
+
+        let lg_n = n.trailing_zeros() as usize;
+        let mut points = Vec::with_capacity(3 * lg_n);
+        for k in arities(lg_n) {
+            let m = a.len() / k;
+            let part = |i: usize| i * m..(i + 1) * m;
+            for d in offsets(k) {
+                // The parts i and j with i - j = d, among 0..k.
+                let pairs: Vec<(usize, usize)> = (0..k)
+                    .filter_map(|i| {
+                        let j = i as isize - d;
+                        (0..k as isize).contains(&j).then_some((i, j as usize))
+                    })
+                    .collect();
+                let c: Scalar = pairs
+                    .iter()
+                    .map(|&(i, j)| inner_product(&a[part(i)], &b[part(j)]))
+                    .sum();
+                // The b terms pair b_j with H_i, where j - i = d.
+                let scalars: Vec<Scalar> = pairs
+                    .iter()
+                    .flat_map(|&(i, _)| a[part(i)].iter())
+                    .chain(pairs.iter().flat_map(|&(_, j)| b[part(j)].iter()))
+                    .chain(iter::once(&c))
+                    .cloned()
+                    .collect();
+                let bases: Vec<RistrettoPoint> = pairs
+                    .iter()
+                    .flat_map(|&(_, j)| G[part(j)].iter())
+                    .chain(pairs.iter().flat_map(|&(i, _)| H[part(i)].iter()))
+                    .chain(iter::once(Q))
+                    .cloned()
+                    .collect();
+                let X = RistrettoPoint::vartime_multiscalar_mul(scalars, bases).compress();
+                transcript.append_point(b"X", &X);
+                points.push(X);
+            }
+
+            let x = transcript.challenge_scalar(b"x");
+            let x_inv = x.invert();
+            let x_pow: Vec<Scalar> = (0..k as isize).map(|i| power(&x, &x_inv, i)).collect();
+            let x_inv_pow: Vec<Scalar> = (0..k as isize).map(|i| power(&x, &x_inv, -i)).collect();
+
+            let fold_scalars = |v: &[Scalar], weights: &[Scalar]| -> Vec<Scalar> {
+                (0..m)
+                    .map(|t| (0..k).map(|i| weights[i] * v[i * m + t]).sum())
+                    .collect()
+            };
+            let fold_points = |v: &[RistrettoPoint], weights: &[Scalar]| -> Vec<RistrettoPoint> {
+                (0..m)
+                    .map(|t| {
+                        RistrettoPoint::vartime_multiscalar_mul(
+                            weights,
+                            (0..k).map(|i| v[i * m + t]),
+                        )
+                    })
+                    .collect()
+            };
+            a = fold_scalars(&a, &x_pow);
+            b = fold_scalars(&b, &x_inv_pow);
+            G = fold_points(&G, &x_inv_pow);
+            H = fold_points(&H, &x_pow);
+        }
+
+        InnerProductProofArity4 {
+            points,
+            a: a[0],
+            b: b[0],
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Computes the verification scalars of the proof for vectors of
+    /// length `n`: the weight \\(x\^d\\) of each point \\(X\_d\\), and
+    /// the scalars \\(s\_i\\) and \\(s\_i\^{-1}\\) of the folded
+    /// generators \\(\sum\_i s\_i G\_i\\) and \\(\sum\_i s\_i\^{-1} H\_i\\).
+    ///
+    /// Returns [`ProofError::VerificationError`] if the proof is not
+    /// for vectors of length `n` or has an invalid or identity point.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn verification_scalars<T: TranscriptProtocol>(
+        &self,
+        n: usize,
+        transcript: &mut T,
+    ) -> Result<(Vec<Scalar>, Vec<Scalar>, Vec<Scalar>), ProofError> {
+        check_vector_length(n)?;
+        let lg_n = lg_n_of(self.points.len()).ok_or(ProofError::VerificationError)?;
+        check_lg_vector_length(lg_n)?;
+        if n != 1 << lg_n {
+            return Err(ProofError::VerificationError);
+        }
+
+        transcript.subprotocol_domain_sep(ARITY4_SCOPE);
+        transcript.innerproduct_domain_sep(n);
+
+        let mut weights = Vec::with_capacity(self.points.len());
+        let mut rounds = Vec::with_capacity(lg_n);
+        let mut points = self.points.iter();
+        for k in arities(lg_n) {
+            for X in points.by_ref().take(2 * (k - 1)) {
+                transcript.validate_and_append_point(b"X", X)?;
+            }
+            let x = transcript.challenge_scalar(b"x");
+            let x_inv = x.invert();
+            weights.extend(offsets(k).map(|d| power(&x, &x_inv, d)));
+            rounds.push((k, x, x_inv));
+        }
+
+        // The first round selects the most significant digit of the
+        // index, so build the scalars from the last round outwards.
+        let mut s = vec![Scalar::ONE];
+        let mut s_inv = vec![Scalar::ONE];
+        for &(k, x, x_inv) in rounds.iter().rev() {
+            s = (0..k as isize)
+                .flat_map(|j| {
+                    let w = power(&x, &x_inv, -j);
+                    s.iter().map(move |s_i| w * s_i)
+                })
+                .collect();
+            s_inv = (0..k as isize)
+                .flat_map(|j| {
+                    let w = power(&x, &x_inv, j);
+                    s_inv.iter().map(move |s_i| w * s_i)
+                })
+                .collect();
+        }
+
+        Ok((weights, s, s_inv))
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof like [`InnerProductProof::verify`](super::InnerProductProof::verify),
+    /// in a single multiscalar multiplication.
+    ///
+    /// The `transcript` is only updated if the proof verifies.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify<IG, IH>(
+        &self,
+        n: usize,
+        transcript: &mut Transcript,
+        G_factors: IG,
+        H_factors: IH,
+        P: &RistrettoPoint,
+        Q: &RistrettoPoint,
+        G: &[RistrettoPoint],
+        H: &[RistrettoPoint],
+    ) -> Result<(), ProofError>
+    where
+        IG: IntoIterator,
+        IG::Item: Borrow<Scalar>,
+        IH: IntoIterator,
+        IH::Item: Borrow<Scalar>,
+    {
+        if G.len() != n || H.len() != n {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        let mut working_transcript = transcript.clone();
+        let (weights, s, s_inv) = self.verification_scalars(n, &mut working_transcript)?;
+        let Xs = self
+            .points
+            .iter()
+            .map(|X| X.decompress().ok_or(ProofError::VerificationError))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let expect_P = RistrettoPoint::vartime_multiscalar_mul(
+            iter::once(self.a * self.b)
+                .chain(
+                    G_factors
+                        .into_iter()
+                        .zip(s.iter())
+                        .map(|(g_i, s_i)| (self.a * s_i) * g_i.borrow()),
+                )
+                .chain(
+                    H_factors
+                        .into_iter()
+                        .zip(s_inv.iter())
+                        .map(|(h_i, s_i_inv)| (self.b * s_i_inv) * h_i.borrow()),
+                )
+                .chain(weights.iter().map(|w| -w)),
+            iter::once(Q)
+                .chain(G.iter())
+                .chain(H.iter())
+                .chain(Xs.iter()),
+        );
+
+        if expect_P == *P {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Returns the points \\(X\_d\\) of the proof, round by round.
+    pub fn points(&self) -> &[CompressedRistretto] {
+        &self.points
+    }
+
+    /// Returns the size in bytes required to serialize the proof.
+    ///
+    /// For vectors of length `n` with \\(\lg n\\) even, the proof size
+    /// is \\(1 + 32 \cdot (3 \lg n + 2)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        1 + (self.points.len() + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES
+    }
+
+    /// Serializes the proof: the tag byte `4`, the points of the
+    /// proof, and the scalars \\(a, b\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.push(ARITY4_TAG);
+        for X in &self.points {
+            buf.extend_from_slice(X.as_bytes());
+        }
+        buf.extend_from_slice(self.a.as_bytes());
+        buf.extend_from_slice(self.b.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice does not start
+    /// with the tag byte, does not have a number of points that some
+    /// vector length gives, or has a scalar that is not canonical, and
+    /// [`ProofError::StatementTooLarge`] if the vectors would be longer
+    /// than [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH).
+    pub fn from_bytes(slice: &[u8]) -> Result<InnerProductProofArity4, ProofError> {
+        if IppArity::of_encoding(slice)? != IppArity::Four {
+            return Err(ProofError::FormatError);
+        }
+        let num_elements = (slice.len() - 1) / ELEMENT_BYTES;
+        if num_elements < IPP_FINAL_ELEMENTS {
+            return Err(ProofError::FormatError);
+        }
+        let num_points = num_elements - IPP_FINAL_ELEMENTS;
+        let lg_n = lg_n_of(num_points).ok_or(ProofError::FormatError)?;
+        check_lg_vector_length(lg_n)?;
+
+        use crate::util::read32;
+
+        let elements: Vec<[u8; 32]> = slice[1..].chunks(ELEMENT_BYTES).map(read32).collect();
+        let scalar = |bytes: [u8; 32]| {
+            Option::from(Scalar::from_canonical_bytes(bytes)).ok_or(ProofError::FormatError)
+        };
+        Ok(InnerProductProofArity4 {
+            points: elements[..num_points]
+                .iter()
+                .map(|&bytes| CompressedRistretto(bytes))
+                .collect(),
+            a: scalar(elements[num_points])?,
+            b: scalar(elements[num_points + 1])?,
+        })
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+    use sha3::{Digest, Sha3_256, Sha3_512};
+
+    use crate::generators::BulletproofGens;
+    use crate::inner_product_proof::{reference, InnerProductProof};
+    use crate::util;
+
+    /// A statement of length `n` with its proofs of both arities.
+    struct Statement {
+        n: usize,
+        G: Vec<RistrettoPoint>,
+        H: Vec<RistrettoPoint>,
+        Q: RistrettoPoint,
+        H_factors: Vec<Scalar>,
+        P: RistrettoPoint,
+        binary: InnerProductProof,
+        arity4: InnerProductProofArity4,
+    }
+
+    impl Statement {
+        fn new(n: usize, a: Vec<Scalar>, b: Vec<Scalar>, y_inv: Scalar) -> Self {
+            let bp_gens = BulletproofGens::new(n, 1);
+            let share = bp_gens.try_share(0).unwrap();
+            let G: Vec<RistrettoPoint> = share.G_checked(n).unwrap().cloned().collect();
+            let H: Vec<RistrettoPoint> = share.H_checked(n).unwrap().cloned().collect();
+            let Q = RistrettoPoint::hash_from_bytes::<Sha3_512>(b"arity4 test point");
+            let G_factors = vec![Scalar::ONE; n];
+            let H_factors: Vec<Scalar> = util::exp_iter(y_inv).take(n).collect();
+            let P = RistrettoPoint::vartime_multiscalar_mul(
+                a.iter()
+                    .cloned()
+                    .chain(b.iter().zip(H_factors.iter()).map(|(b_i, h_i)| b_i * h_i))
+                    .chain(iter::once(inner_product(&a, &b))),
+                G.iter().chain(H.iter()).chain(iter::once(&Q)),
+            );
+            let binary = InnerProductProof::create(
+                &mut Transcript::new(b"Arity4Test"),
+                &Q,
+                &G_factors,
+                &H_factors,
+                G.clone(),
+                H.clone(),
+                a.clone(),
+                b.clone(),
+            );
+            let arity4 = InnerProductProofArity4::create(
+                &mut Transcript::new(b"Arity4Test"),
+                &Q,
+                &G_factors,
+                &H_factors,
+                G.clone(),
+                H.clone(),
+                a,
+                b,
+            );
+            Statement {
+                n,
+                G,
+                H,
+                Q,
+                H_factors,
+                P,
+                binary,
+                arity4,
+            }
+        }
+
+        fn random(n: usize, rng: &mut ChaChaRng) -> Self {
+            let a = (0..n).map(|_| Scalar::random(rng)).collect();
+            let b = (0..n).map(|_| Scalar::random(rng)).collect();
+            Statement::new(n, a, b, Scalar::random(rng))
+        }
+
+        fn verify_binary(&self) -> Result<(), ProofError> {
+            self.binary.verify(
+                self.n,
+                &mut Transcript::new(b"Arity4Test"),
+                iter::repeat_n(Scalar::ONE, self.n),
+                self.H_factors.iter(),
+                &self.P,
+                &self.Q,
+                &self.G,
+                &self.H,
+            )
+        }
+
+        fn verify(&self, proof: &InnerProductProofArity4) -> Result<(), ProofError> {
+            proof.verify(
+                self.n,
+                &mut Transcript::new(b"Arity4Test"),
+                iter::repeat_n(Scalar::ONE, self.n),
+                self.H_factors.iter(),
+                &self.P,
+                &self.Q,
+                &self.G,
+                &self.H,
+            )
+        }
+
+        fn verify_reference(&self, proof: &InnerProductProofArity4) -> Result<(), ProofError> {
+            reference::verify_arity4(
+                proof,
+                self.n,
+                &mut Transcript::new(b"Arity4Test"),
+                &vec![Scalar::ONE; self.n],
+                &self.H_factors,
+                &self.P,
+                &self.Q,
+                &self.G,
+                &self.H,
+            )
+        }
+    }
+
+    #[test]
+    fn both_arities_verify() {
+        let mut rng = ChaChaRng::from_seed([4u8; 32]);
+        for n in [1, 2, 4, 8, 16, 32, 64] {
+            let statement = Statement::random(n, &mut rng);
+            statement.verify_binary().unwrap();
+            statement.verify(&statement.arity4).unwrap();
+            statement.verify_reference(&statement.arity4).unwrap();
+
+            let binary_bytes = statement.binary.to_bytes();
+            let arity4_bytes = statement.arity4.to_bytes();
+            assert_eq!(IppArity::of_encoding(&binary_bytes), Ok(IppArity::Two));
+            assert_eq!(IppArity::of_encoding(&arity4_bytes), Ok(IppArity::Four));
+            assert_eq!(
+                InnerProductProofArity4::from_bytes(&arity4_bytes).unwrap(),
+                statement.arity4
+            );
+            assert!(InnerProductProofArity4::from_bytes(&binary_bytes).is_err());
+            assert!(InnerProductProof::from_bytes(&arity4_bytes).is_err());
+        }
+    }
+
+    #[test]
+    fn sizes_of_both_arities() {
+        let mut rng = ChaChaRng::from_seed([5u8; 32]);
+        for lg_n in 0..=7 {
+            let statement = Statement::random(1 << lg_n, &mut rng);
+            let binary_points = 2 * lg_n;
+            let arity4_points = 2 * (lg_n % 2) + 6 * (lg_n / 2);
+            assert_eq!(statement.binary.to_bytes().len(), (binary_points + 2) * 32);
+            assert_eq!(
+                statement.arity4.to_bytes().len(),
+                1 + (arity4_points + 2) * 32
+            );
+            assert_eq!(
+                statement.arity4.serialized_size(),
+                statement.arity4.to_bytes().len()
+            );
+        }
+    }
+
+    #[test]
+    fn verifiers_agree_on_corrupted_proofs() {
+        let mut rng = ChaChaRng::from_seed([6u8; 32]);
+        for n in [2, 8, 16] {
+            let statement = Statement::random(n, &mut rng);
+            let proof = &statement.arity4;
+            let mut corpus = vec![proof.clone()];
+
+            let bytes = proof.to_bytes();
+            for i in 0..(bytes.len() - 1) / 32 {
+                let mut corrupted = bytes.clone();
+                corrupted[1 + 32 * i] ^= 4;
+                if let Ok(corrupted) = InnerProductProofArity4::from_bytes(&corrupted) {
+                    corpus.push(corrupted);
+                }
+            }
+            let mut swapped = proof.clone();
+            swapped.points.swap(0, 1);
+            corpus.push(swapped);
+            let mut wrong_b = proof.clone();
+            wrong_b.b += Scalar::ONE;
+            corpus.push(wrong_b);
+
+            let mut accepted = 0;
+            for (i, proof) in corpus.iter().enumerate() {
+                let optimized = statement.verify(proof).is_ok();
+                let reference = statement.verify_reference(proof).is_ok();
+                assert_eq!(optimized, reference, "n = {}, case {}", n, i);
+                accepted += optimized as usize;
+            }
+            assert_eq!(accepted, 1);
+        }
+    }
+
+    #[test]
+    fn malformed_encodings_are_rejected() {
+        let mut rng = ChaChaRng::from_seed([7u8; 32]);
+        let bytes = Statement::random(16, &mut rng).arity4.to_bytes();
+
+        // Points for no vector length: 12 points is lg n = 4, 13 is none.
+        assert!(InnerProductProofArity4::from_bytes(&bytes[..bytes.len() - 32]).is_err());
+        let mut extra = bytes.clone();
+        extra.extend_from_slice(&bytes[1..33]);
+        assert!(InnerProductProofArity4::from_bytes(&extra).is_err());
+
+        let mut untagged = bytes.clone();
+        untagged[0] = 2;
+        assert_eq!(
+            IppArity::of_encoding(&untagged),
+            Err(ProofError::FormatError)
+        );
+
+        let mut noncanonical = bytes;
+        let len = noncanonical.len();
+        noncanonical[len - 32..].copy_from_slice(&[0xff; 32]);
+        assert_eq!(
+            InnerProductProofArity4::from_bytes(&noncanonical),
+            Err(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    fn proofs_match_fixtures() {
+        // Both provers are deterministic, so fixed witnesses give fixed
+        // proofs.
+        let n = 32;
+        let a = (0..n as u64).map(|i| Scalar::from(i + 1)).collect();
+        let b = (0..n as u64).map(|i| Scalar::from(2 * i + 3)).collect();
+        let statement = Statement::new(n, a, b, Scalar::from(5u64));
+        let digest = |bytes: Vec<u8>| hex::encode(Sha3_256::digest(bytes));
+        assert_eq!(digest(statement.binary.to_bytes()), BINARY_FIXTURE);
+        assert_eq!(digest(statement.arity4.to_bytes()), ARITY4_FIXTURE);
+    }
+
+    const BINARY_FIXTURE: &str = "2eb34e6ad13127538a591249cf986200f3184a3c7559512b27790c8b5a97b9d4";
+    const ARITY4_FIXTURE: &str = "6a54b9f4190756c29d85ec7e51fcb13ffe88c3ef058be8c5589549987a1f9922";
+}
@@ -0,0 +1,440 @@
+//! A reference verifier for inner-product proofs, used as an oracle
+//! for differential testing of [`InnerProductProof::verify`], and of
+//! the 4-ary verifier with the `ipp-arity4` feature.
+//!
+//! It follows the description of the protocol: the generators are
+//! folded round by round, one group operation at a time, with no
+//! multiscalar multiplication and none of the algebra that lets the
+//! optimized verifier check the proof in a single one.  It is slow,
+//! and compiled only for tests.
+
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use merlin::Transcript;
+
+use super::{InnerProductProof, IPP_SCOPE};
+use crate::errors::ProofError;
+use crate::transcript::TranscriptProtocol;
+
+/// Verifies that \\(P = \langle \mathbf{a}, \mathbf{G}' \rangle + \langle \mathbf{b}, \mathbf{H}' \rangle + \langle \mathbf{a}, \mathbf{b} \rangle Q\\),
+/// where \\(\mathbf{G}'\\) and \\(\mathbf{H}'\\) are `G` and `H`
+/// scaled by `G_factors` and `H_factors`, and \\(\mathbf{a}\\) and
+/// \\(\mathbf{b}\\) are the vectors of length `n` the proof folds.
+///
+/// Like [`InnerProductProof::verify`], the `transcript` is only
+/// updated if the proof verifies.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify(
+    proof: &InnerProductProof,
+    n: usize,
+    transcript: &mut Transcript,
+    G_factors: &[Scalar],
+    H_factors: &[Scalar],
+    P: &RistrettoPoint,
+    Q: &RistrettoPoint,
+    G: &[RistrettoPoint],
+    H: &[RistrettoPoint],
+) -> Result<(), ProofError> {
+    let lg_n = proof.L_vec.len();
+    if proof.R_vec.len() != lg_n || lg_n >= 32 || n != 1 << lg_n {
+        return Err(ProofError::VerificationError);
+    }
+    if [G.len(), H.len(), G_factors.len(), H_factors.len()] != [n; 4] {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+
+    let mut working_transcript = transcript.clone();
+    working_transcript.subprotocol_domain_sep(IPP_SCOPE);
+    working_transcript.innerproduct_domain_sep(n);
+
+    let mut G: Vec<RistrettoPoint> = G
+        .iter()
+        .zip(G_factors)
+        .map(|(G_i, g_i)| g_i * G_i)
+        .collect();
+    let mut H: Vec<RistrettoPoint> = H
+        .iter()
+        .zip(H_factors)
+        .map(|(H_i, h_i)| h_i * H_i)
+        .collect();
+    let mut P = *P;
+
+    // Each round replaces the statement with one of half the length:
+    // P' = u^2 L + P + u^-2 R, G' = u^-1 G_lo + u G_hi, and
+    // H' = u H_lo + u^-1 H_hi.
+    for (L, R) in proof.L_vec.iter().zip(proof.R_vec.iter()) {
+        working_transcript.append_point(b"L", L);
+        working_transcript.append_point(b"R", R);
+        let u = working_transcript.challenge_scalar(b"u");
+        let u_inv = u.invert();
+
+        let L = L.decompress().ok_or(ProofError::VerificationError)?;
+        let R = R.decompress().ok_or(ProofError::VerificationError)?;
+        if L.is_identity() || R.is_identity() {
+            return Err(ProofError::VerificationError);
+        }
+
+        P = (u * u) * L + P + (u_inv * u_inv) * R;
+        let half = G.len() / 2;
+        G = (0..half).map(|i| u_inv * G[i] + u * G[half + i]).collect();
+        H = (0..half).map(|i| u * H[i] + u_inv * H[half + i]).collect();
+    }
+
+    // The statement now has length one, and the proof opens it.
+    let (a, b) = (proof.a, proof.b);
+    if P == a * G[0] + b * H[0] + (a * b) * Q {
+        *transcript = working_transcript;
+        Ok(())
+    } else {
+        Err(ProofError::VerificationError)
+    }
+}
+
+/// Verifies a 4-ary proof of the same statement as [`verify`], folding
+/// the generators and \\(P\\) round by round.
+#[cfg(feature = "ipp-arity4")]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn verify_arity4(
+    proof: &super::InnerProductProofArity4,
+    n: usize,
+    transcript: &mut Transcript,
+    G_factors: &[Scalar],
+    H_factors: &[Scalar],
+    P: &RistrettoPoint,
+    Q: &RistrettoPoint,
+    G: &[RistrettoPoint],
+    H: &[RistrettoPoint],
+) -> Result<(), ProofError> {
+    use super::arity4::{arities, offsets, power, ARITY4_SCOPE};
+
+    if !n.is_power_of_two() || n >= 1 << 32 {
+        return Err(ProofError::VerificationError);
+    }
+    let lg_n = n.trailing_zeros() as usize;
+    let num_points: usize = arities(lg_n).map(|k| 2 * (k - 1)).sum();
+    if proof.points.len() != num_points {
+        return Err(ProofError::VerificationError);
+    }
+    if [G.len(), H.len(), G_factors.len(), H_factors.len()] != [n; 4] {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+
+    let mut working_transcript = transcript.clone();
+    working_transcript.subprotocol_domain_sep(ARITY4_SCOPE);
+    working_transcript.innerproduct_domain_sep(n);
+
+    let mut G: Vec<RistrettoPoint> = G
+        .iter()
+        .zip(G_factors)
+        .map(|(G_i, g_i)| g_i * G_i)
+        .collect();
+    let mut H: Vec<RistrettoPoint> = H
+        .iter()
+        .zip(H_factors)
+        .map(|(H_i, h_i)| h_i * H_i)
+        .collect();
+    let mut P = *P;
+
+    // Each round replaces the statement with one of a k-th of the
+    // length: P' = P + sum_d x^d X_d, G' = sum_i x^-i G_i, and
+    // H' = sum_i x^i H_i.
+    let mut points = proof.points.iter();
+    for k in arities(lg_n) {
+        let Xs: Vec<_> = points.by_ref().take(2 * (k - 1)).collect();
+        for X in &Xs {
+            working_transcript.append_point(b"X", X);
+        }
+        let x = working_transcript.challenge_scalar(b"x");
+        let x_inv = x.invert();
+
+        for (X, d) in Xs.into_iter().zip(offsets(k)) {
+            let X = X.decompress().ok_or(ProofError::VerificationError)?;
+            if X.is_identity() {
+                return Err(ProofError::VerificationError);
+            }
+            P += power(&x, &x_inv, d) * X;
+        }
+        let m = G.len() / k;
+        G = (0..m)
+            .map(|t| {
+                (0..k)
+                    .map(|i| power(&x, &x_inv, -(i as isize)) * G[i * m + t])
+                    .sum()
+            })
+            .collect();
+        H = (0..m)
+            .map(|t| {
+                (0..k)
+                    .map(|i| power(&x, &x_inv, i as isize) * H[i * m + t])
+                    .sum()
+            })
+            .collect();
+    }
+
+    let (a, b) = (proof.a, proof.b);
+    if P == a * G[0] + b * H[0] + (a * b) * Q {
+        *transcript = working_transcript;
+        Ok(())
+    } else {
+        Err(ProofError::VerificationError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::iter;
+    use curve25519_dalek::traits::VartimeMultiscalarMul;
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+    use sha3::Sha3_512;
+
+    use crate::generators::BulletproofGens;
+    use crate::inner_product_proof::inner_product;
+    use crate::util;
+
+    /// An inner-product statement and a proof for it, valid or not.
+    struct Case {
+        name: String,
+        proof: InnerProductProof,
+        n: usize,
+        H_factors: Vec<Scalar>,
+        P: RistrettoPoint,
+    }
+
+    /// The generators and points shared by the statements of length `n`.
+    struct Setup {
+        G: Vec<RistrettoPoint>,
+        H: Vec<RistrettoPoint>,
+        Q: RistrettoPoint,
+    }
+
+    impl Setup {
+        fn new(n: usize) -> Self {
+            let bp_gens = BulletproofGens::new(n, 1);
+            let share = bp_gens.try_share(0).unwrap();
+            Setup {
+                G: share.G_checked(n).unwrap().cloned().collect(),
+                H: share.H_checked(n).unwrap().cloned().collect(),
+                Q: RistrettoPoint::hash_from_bytes::<Sha3_512>(b"reference test point"),
+            }
+        }
+    }
+
+    /// Creates a valid proof of length `n` and the corruptions of it
+    /// that the verifiers must agree to reject.
+    fn corpus(setup: &Setup, n: usize, rng: &mut ChaChaRng) -> Vec<Case> {
+        let a: Vec<_> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let b: Vec<_> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let y_inv = Scalar::random(rng);
+        let H_factors: Vec<Scalar> = util::exp_iter(y_inv).take(n).collect();
+        let P = RistrettoPoint::vartime_multiscalar_mul(
+            a.iter()
+                .cloned()
+                .chain(b.iter().zip(H_factors.iter()).map(|(b_i, h_i)| b_i * h_i))
+                .chain(iter::once(inner_product(&a, &b))),
+            setup
+                .G
+                .iter()
+                .chain(setup.H.iter())
+                .chain(iter::once(&setup.Q)),
+        );
+        let proof = InnerProductProof::create(
+            &mut Transcript::new(b"ReferenceTest"),
+            &setup.Q,
+            &vec![Scalar::ONE; n],
+            &H_factors,
+            setup.G.clone(),
+            setup.H.clone(),
+            a,
+            b,
+        );
+
+        let case = |name: String, proof: InnerProductProof, H_factors: &[Scalar], P| Case {
+            name,
+            proof,
+            n,
+            H_factors: H_factors.to_vec(),
+            P,
+        };
+        let mut cases = vec![
+            case("valid".into(), proof.clone(), &H_factors, P),
+            case("P + Q".into(), proof.clone(), &H_factors, P + setup.Q),
+            case("P - Q".into(), proof.clone(), &H_factors, P - setup.Q),
+        ];
+        let mut other_factors = H_factors.clone();
+        other_factors[n - 1] = -other_factors[n - 1];
+        cases.push(case("-h_n".into(), proof.clone(), &other_factors, P));
+
+        // The last check is the only one that a wrong a can fail.
+        let mut wrong_a = proof.clone();
+        wrong_a.a += Scalar::ONE;
+        cases.push(case("a + 1".into(), wrong_a, &H_factors, P));
+
+        let bytes = proof.to_bytes();
+        for i in 0..bytes.len() / 32 {
+            let mut corrupted = bytes.clone();
+            corrupted[32 * i] ^= 4;
+            if let Ok(corrupted) = InnerProductProof::from_bytes(&corrupted) {
+                cases.push(case(format!("element {}", i), corrupted, &H_factors, P));
+            }
+        }
+        if n > 1 {
+            let mut swapped = proof;
+            core::mem::swap(&mut swapped.L_vec[0], &mut swapped.R_vec[0]);
+            cases.push(case("L and R swapped".into(), swapped, &H_factors, P));
+        }
+        cases
+    }
+
+    /// A verifier under test, which returns whether it accepts a case.
+    type Candidate = (&'static str, fn(&Setup, &Case) -> bool);
+
+    /// Runs the reference verifier and each of the `candidates` on every
+    /// case, and returns the number of cases accepted by all of them, or
+    /// the first candidate and case on which they disagree.
+    fn differential(
+        setup: &Setup,
+        cases: &[Case],
+        candidates: &[Candidate],
+    ) -> Result<usize, String> {
+        let mut accepted = 0;
+        for case in cases {
+            let reference = verify(
+                &case.proof,
+                case.n,
+                &mut Transcript::new(b"ReferenceTest"),
+                &vec![Scalar::ONE; case.n],
+                &case.H_factors,
+                &case.P,
+                &setup.Q,
+                &setup.G,
+                &setup.H,
+            )
+            .is_ok();
+            for (name, candidate) in candidates {
+                if candidate(setup, case) != reference {
+                    return Err(format!("{} on {}", name, case.name));
+                }
+            }
+            accepted += reference as usize;
+        }
+        Ok(accepted)
+    }
+
+    /// The optimized verifiers, which must all agree with the reference
+    /// verifier.
+    const CANDIDATES: [Candidate; 2] = [("verify", optimized), ("verify_ct", optimized_ct)];
+
+    fn optimized(setup: &Setup, case: &Case) -> bool {
+        case.proof
+            .verify(
+                case.n,
+                &mut Transcript::new(b"ReferenceTest"),
+                iter::repeat_n(Scalar::ONE, case.n),
+                case.H_factors.iter(),
+                &case.P,
+                &setup.Q,
+                &setup.G,
+                &setup.H,
+            )
+            .is_ok()
+    }
+
+    fn optimized_ct(setup: &Setup, case: &Case) -> bool {
+        case.proof
+            .verify_ct(
+                case.n,
+                &mut Transcript::new(b"ReferenceTest"),
+                iter::repeat_n(Scalar::ONE, case.n),
+                case.H_factors.iter(),
+                &case.P,
+                &setup.Q,
+                &setup.G,
+                &setup.H,
+            )
+            .is_ok()
+    }
+
+    /// The verification equation of `InnerProductProof::verify`, with
+    /// the sign of the \\(R\\) terms flipped if `flip` is set.
+    fn msm_verifier(setup: &Setup, case: &Case, flip: bool) -> bool {
+        let proof = &case.proof;
+        let mut transcript = Transcript::new(b"ReferenceTest");
+        let (u_sq, u_inv_sq, s) = match proof.verification_scalars(case.n, &mut transcript) {
+            Ok(scalars) => scalars,
+            Err(_) => return false,
+        };
+        let (Ls, Rs) = match proof.decompress_points() {
+            Ok(points) => points,
+            Err(_) => return false,
+        };
+        let sign = if flip { Scalar::ONE } else { -Scalar::ONE };
+        let check = RistrettoPoint::vartime_multiscalar_mul(
+            iter::once(proof.a * proof.b)
+                .chain(s.iter().map(|s_i| proof.a * s_i))
+                .chain(
+                    case.H_factors
+                        .iter()
+                        .zip(s.iter().rev())
+                        .map(|(h_i, s_i_inv)| proof.b * s_i_inv * h_i),
+                )
+                .chain(u_sq.iter().map(|u| -u))
+                .chain(u_inv_sq.iter().map(|u| sign * u)),
+            iter::once(&setup.Q)
+                .chain(setup.G.iter())
+                .chain(setup.H.iter())
+                .chain(Ls.iter())
+                .chain(Rs.iter()),
+        );
+        check == case.P
+    }
+
+    #[test]
+    fn optimized_verifiers_agree_with_reference() {
+        let mut rng = ChaChaRng::from_seed([1; 32]);
+        for lg_n in 0..=5 {
+            let n = 1 << lg_n;
+            let setup = Setup::new(n);
+            let cases = corpus(&setup, n, &mut rng);
+            assert_eq!(
+                differential(&setup, &cases, &CANDIDATES),
+                Ok(1),
+                "n = {}",
+                n
+            );
+        }
+    }
+
+    #[test]
+    fn optimized_verifiers_agree_with_reference_for_1024_generators() {
+        let n = 1024;
+        let setup = Setup::new(n);
+        // The reference verifier is slow at this length, so only check
+        // the valid proof and one that is rejected by the last check.
+        let cases: Vec<Case> = corpus(&setup, n, &mut ChaChaRng::from_seed([2; 32]))
+            .into_iter()
+            .filter(|case| ["valid", "a + 1"].contains(&&*case.name))
+            .collect();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(differential(&setup, &cases, &CANDIDATES), Ok(1));
+    }
+
+    #[test]
+    fn harness_catches_a_sign_flip() {
+        let setup = Setup::new(16);
+        let cases = corpus(&setup, 16, &mut ChaChaRng::from_seed([3; 32]));
+        let msm: Candidate = ("msm", |setup, case| msm_verifier(setup, case, false));
+        assert_eq!(differential(&setup, &cases, &[msm]), Ok(1));
+        let flipped: Candidate = ("flipped", |setup, case| msm_verifier(setup, case, true));
+        assert_eq!(
+            differential(&setup, &cases, &[flipped]),
+            Err("flipped on valid".into())
+        );
+    }
+}
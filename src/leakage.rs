@@ -0,0 +1,244 @@
+//! The `leakage` module contains the [`NoWitnessDebug`] trait, and the
+//! regression tests checking that serialized proofs carry no witness
+//! information beyond what the protocol reveals.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A proof type whose `Debug` and `Display` output contains nothing
+/// derived from the vectors \\(\mathbf{l}(x)\\) and \\(\mathbf{r}(x)\\)
+/// of the prover.
+///
+/// Those vectors hide the witness only as long as they stay inside the
+/// inner-product argument, so the formatted output of a proof shows its
+/// commitments and sizes, never \\(t(x)\\), the points \\(L\_j, R\_j\\)
+/// or the final scalars \\(a, b\\) of the inner-product proof.
+pub trait NoWitnessDebug: fmt::Debug + fmt::Display {
+    /// Returns the 32-byte encodings of the elements of the proof that
+    /// are derived from \\(\mathbf{l}(x)\\) and \\(\mathbf{r}(x)\\), none
+    /// of which may appear in the formatted output.
+    fn witness_derived_elements(&self) -> Vec<[u8; 32]>;
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    use core::convert::TryFrom;
+
+    use curve25519_dalek::scalar::Scalar;
+    use merlin::Transcript;
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+    use crate::range_proof::{BitSize, RangeProof};
+
+    /// The number of distinct witnesses proved for each statement.
+    const WITNESSES: usize = 64;
+
+    /// The seed of the randomness that is not derived from the witness,
+    /// shared by every proof of a statement.
+    const SEED: [u8; 32] = [7u8; 32];
+
+    /// Returns the `i`-th of the witnesses of `n`-bit values: the edges
+    /// of the range and then values spread over it.
+    fn value(i: usize, n: usize) -> u64 {
+        let max = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+        match i {
+            0 => 0,
+            1 => max,
+            _ => (i as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) & max,
+        }
+    }
+
+    /// Returns the `i`-th 32-byte element of `bytes`.
+    fn element(bytes: &[u8], i: usize) -> [u8; 32] {
+        <[u8; 32]>::try_from(&bytes[32 * i..32 * (i + 1)]).unwrap()
+    }
+
+    /// Checks that scalars sampled across witnesses look uniform: they
+    /// are pairwise distinct, and the bits below the top byte are set
+    /// about half of the time.
+    fn assert_uniform(name: &str, samples: &[[u8; 32]]) {
+        for (i, a) in samples.iter().enumerate() {
+            assert!(
+                samples[..i].iter().all(|b| a != b),
+                "{} repeats across witnesses",
+                name
+            );
+        }
+        let bits = samples.len() * 31 * 8;
+        let ones: u32 = samples
+            .iter()
+            .flat_map(|sample| sample[..31].iter())
+            .map(|byte| byte.count_ones())
+            .sum();
+        let ratio = f64::from(ones) / bits as f64;
+        assert!(
+            (0.45..0.55).contains(&ratio),
+            "{} has {} of its bits set",
+            name,
+            ratio
+        );
+    }
+
+    /// Checks that no element of `proof` derived from the witness vectors
+    /// appears in its formatted output, either in hex or as the decimal
+    /// bytes of a derived `Debug`.
+    fn assert_no_witness_debug<P: NoWitnessDebug>(proof: &P) {
+        let output = format!("{:?} {}", proof, proof);
+        let elements = proof.witness_derived_elements();
+        assert!(!elements.is_empty());
+        for element in elements {
+            let hex: String = element[..4]
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .collect();
+            let decimal = format!("{:?}", &element[..4]);
+            assert!(!output.contains(&hex), "{} contains {}", output, hex);
+            assert!(
+                !output.contains(&decimal[1..decimal.len() - 1]),
+                "{} contains {}",
+                output,
+                decimal
+            );
+        }
+    }
+
+    /// Proves `values` of `n` bits with blindings derived from `i` and
+    /// the randomness of the prover seeded with [`SEED`].
+    fn range_proof(bp_gens: &BulletproofGens, n: BitSize, values: &[u64], i: usize) -> RangeProof {
+        let blindings: Vec<Scalar> = (0..values.len())
+            .map(|j| Scalar::from((i * values.len() + j + 1) as u64))
+            .collect();
+        RangeProof::prove_multiple_with_rng(
+            bp_gens,
+            &PedersenGens::default(),
+            &mut Transcript::new(b"LeakageTest"),
+            values,
+            &blindings,
+            n,
+            &mut ChaChaRng::from_seed(SEED),
+        )
+        .unwrap()
+        .0
+    }
+
+    #[test]
+    fn range_proofs_do_not_leak_the_witness() {
+        let bp_gens = BulletproofGens::new(64, 2);
+        for &n in &[BitSize::B8, BitSize::B64] {
+            for m in [1, 2] {
+                let proofs: Vec<Vec<u8>> = (0..WITNESSES)
+                    .map(|i| {
+                        let values: Vec<u64> = (0..m).map(|j| value(i + j, n.bits())).collect();
+                        range_proof(&bp_gens, n, &values, i).to_bytes()
+                    })
+                    .collect();
+
+                // The encoding has the same length for every witness.
+                let size = RangeProof::size_const(n.bits(), m);
+                assert!(proofs.iter().all(|proof| proof.len() == size));
+
+                // S commits to the random vectors s_L, s_R only, so it is
+                // the same for every witness under the same seed.
+                assert!(proofs
+                    .iter()
+                    .all(|proof| element(proof, 1) == element(&proofs[0], 1)));
+
+                // The scalars are masked by the randomness, the
+                // challenges or both, so they do not follow the witness.
+                let lg_nm = (n.bits() * m).trailing_zeros() as usize;
+                let scalars = [
+                    ("t_x", 4),
+                    ("t_x_blinding", 5),
+                    ("e_blinding", 6),
+                    ("a", 7 + 2 * lg_nm),
+                    ("b", 8 + 2 * lg_nm),
+                ];
+                for &(name, i) in &scalars {
+                    let samples: Vec<[u8; 32]> =
+                        proofs.iter().map(|proof| element(proof, i)).collect();
+                    assert_uniform(name, &samples);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn range_proof_formatting_has_no_witness_data() {
+        let bp_gens = BulletproofGens::new(64, 2);
+        for i in 0..8 {
+            let values = [value(i, 32), value(i + 1, 32)];
+            assert_no_witness_debug(&range_proof(&bp_gens, BitSize::B32, &values, i));
+        }
+    }
+
+    #[cfg(feature = "yoloproofs")]
+    mod r1cs {
+        use super::*;
+
+        use crate::r1cs::{ConstraintSystem, Prover, R1CSProof};
+
+        /// Proves knowledge of committed `x`, `y` and `z` with
+        /// \\(x \cdot y = z\\), with the randomness of the prover seeded
+        /// with [`SEED`] and independent of the witness.
+        fn product_proof(bp_gens: &BulletproofGens, x: u64, y: u64) -> R1CSProof {
+            let pc_gens = PedersenGens::default();
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"LeakageTest"));
+            prover.use_witness_independent_rng();
+            let blinding = |k: u64| Scalar::from(x.wrapping_mul(3) ^ y ^ k);
+            let (_, x_var) = prover.commit(Scalar::from(x), blinding(1));
+            let (_, y_var) = prover.commit(Scalar::from(y), blinding(2));
+            let (_, z_var) = prover.commit(Scalar::from(x) * Scalar::from(y), blinding(3));
+            let (_, _, product) = prover.multiply(x_var.into(), y_var.into());
+            prover.constrain(product - z_var);
+            for _ in 0..3 {
+                prover.multiply(x_var.into(), x_var.into());
+            }
+            prover
+                .prove_with_rng(bp_gens, &mut ChaChaRng::from_seed(SEED))
+                .unwrap()
+        }
+
+        #[test]
+        fn r1cs_proofs_do_not_leak_the_witness() {
+            let bp_gens = BulletproofGens::new(8, 1);
+            let proofs: Vec<Vec<u8>> = (0..WITNESSES)
+                .map(|i| product_proof(&bp_gens, value(i, 32), value(i + 7, 16)).to_bytes())
+                .collect();
+            assert!(proofs.iter().all(|proof| proof.len() == proofs[0].len()));
+
+            // After the version byte, the commitment S1 to the random
+            // vectors is the same for every witness under the same seed.
+            let elements: Vec<&[u8]> = proofs.iter().map(|proof| &proof[1..]).collect();
+            assert!(elements
+                .iter()
+                .all(|proof| element(proof, 2) == element(elements[0], 2)));
+
+            // A one-phase proof has 11 elements before the inner-product
+            // proof of 4 multipliers.
+            let scalars = [
+                ("t_x", 8),
+                ("t_x_blinding", 9),
+                ("e_blinding", 10),
+                ("a", 15),
+                ("b", 16),
+            ];
+            for &(name, i) in &scalars {
+                let samples: Vec<[u8; 32]> =
+                    elements.iter().map(|proof| element(proof, i)).collect();
+                assert_uniform(name, &samples);
+            }
+        }
+
+        #[test]
+        fn r1cs_proof_formatting_has_no_witness_data() {
+            let bp_gens = BulletproofGens::new(8, 1);
+            for i in 0..8 {
+                assert_no_witness_debug(&product_proof(&bp_gens, value(i, 32), 5));
+            }
+        }
+    }
+}
@@ -12,6 +12,9 @@ extern crate alloc;
 #[macro_use]
 extern crate serde_derive;
 
+#[cfg(feature = "verifier")]
+#[macro_use]
+mod trace;
 mod util;
 
 #[cfg_attr(feature = "docs", doc(include = "../docs/notes-intro.md"))]
@@ -24,18 +27,77 @@ mod notes {
     mod r1cs_proof {}
 }
 
+mod balance_proof;
+#[cfg(feature = "verifier")]
+mod batch_verifier;
+mod commitments;
+mod cross_commitment_proof;
+mod envelope;
 mod errors;
 mod generators;
 mod inner_product_proof;
+mod leakage;
 mod linear_proof;
+mod nonzero_proof;
+mod parameters;
 mod range_proof;
+mod small_vec_opening_proof;
 mod transcript;
+mod vec_sum_proof;
+mod view_key;
 
+pub use crate::balance_proof::{blinding_sum, BalanceProof};
+#[cfg(feature = "verifier")]
+pub use crate::batch_verifier::{BatchVerifier, VerificationOptions};
+pub use crate::commitments::Commitment;
+pub use crate::cross_commitment_proof::CrossCommitmentProof;
+pub use crate::envelope::{EnvelopeProof, EnvelopeTranscript, ProofEnvelope, ProofKind};
 pub use crate::errors::ProofError;
-pub use crate::generators::{BulletproofGens, BulletproofGensShare, PedersenGens};
+#[cfg(feature = "mmap")]
+pub use crate::generators::MappedBulletproofGens;
+pub use crate::generators::{
+    AsGenerators, BulletproofGens, BulletproofGensShare, PedersenGens, SharedBulletproofGens,
+};
+pub use crate::inner_product_proof::MAX_VECTOR_LENGTH;
+pub use crate::leakage::NoWitnessDebug;
 pub use crate::linear_proof::LinearProof;
-pub use crate::range_proof::RangeProof;
+pub use crate::nonzero_proof::NonZeroProof;
+pub use crate::parameters::ProofParameters;
+#[cfg(feature = "verifier")]
+pub use crate::range_proof::{
+    AuditChallenge, AuditTrace, DecompressedRangeProof, VerificationShard,
+};
+pub use crate::range_proof::{
+    BitSize, ProveOptions, RangeProof, VerifiedRange, MAX_BITSIZE, MAX_RANGE_PROOF_SIZE,
+};
+#[cfg(feature = "prover")]
+pub use crate::range_proof::{RangeProverPrecomp, RangeStatement, RangeStatementBuilder};
+pub use crate::small_vec_opening_proof::SmallVecOpeningProof;
+pub use crate::transcript::{app_transcript, StatementSession, TranscriptFactory, TranscriptOp};
+pub use crate::util::{
+    bits_to_choices, ct_select_commit, exp_iter, exp_iter_from, geometric_sum, ScalarExp,
+};
+pub use crate::vec_sum_proof::VecSumProof;
+pub use crate::view_key::ViewKey;
+
+/// Internal types exposed only for the benchmark suite.
+#[cfg(feature = "bench")]
+#[doc(hidden)]
+pub use crate::inner_product_proof::inner_product;
+/// Internal types exposed only for the benchmark suite and for
+/// negative tests of verifiers.
+#[cfg(any(feature = "bench", feature = "test-utils"))]
+#[doc(hidden)]
+pub use crate::inner_product_proof::InnerProductProof;
+/// Experimental inner-product proofs with 4-ary folding.
+#[cfg(feature = "ipp-arity4")]
+#[doc(hidden)]
+pub use crate::inner_product_proof::{InnerProductProofArity4, IppArity};
+#[cfg(any(feature = "bench", feature = "test-utils"))]
+#[doc(hidden)]
+pub use crate::transcript::PublicVectorMode;
 
+#[cfg(feature = "prover")]
 #[cfg_attr(feature = "docs", doc(include = "../docs/aggregation-api.md"))]
 pub mod range_proof_mpc {
     pub use crate::errors::MPCError;
@@ -47,3 +109,15 @@ pub mod range_proof_mpc {
 #[cfg(feature = "yoloproofs")]
 #[cfg(feature = "std")]
 pub mod r1cs;
+
+pub mod blinding;
+#[cfg(feature = "legacy-v0")]
+pub mod compat;
+pub mod consts;
+#[cfg(all(feature = "std", feature = "prover"))]
+pub mod simple;
+
+#[cfg(feature = "contrib")]
+pub mod contrib;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
@@ -4,15 +4,27 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 
+#[cfg(feature = "prover")]
 use core::iter;
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
 use curve25519_dalek::traits::VartimeMultiscalarMul;
+#[cfg(feature = "verifier")]
 use merlin::Transcript;
+#[cfg(feature = "prover")]
 use rand_core::{CryptoRng, RngCore};
 
+use crate::consts::{ELEMENT_BYTES, LINEAR_FINAL_ELEMENTS};
 use crate::errors::ProofError;
+use crate::inner_product_proof::check_lg_vector_length;
+#[cfg(feature = "verifier")]
+use crate::inner_product_proof::check_vector_length;
+#[cfg(feature = "prover")]
 use crate::inner_product_proof::inner_product;
+#[cfg(feature = "verifier")]
 use crate::transcript::TranscriptProtocol;
 
 /// A linear proof, which is an "lightweight" version of a Bulletproofs inner-product proof
@@ -32,11 +44,14 @@ pub struct LinearProof {
 }
 
 impl LinearProof {
+    #[cfg(feature = "prover")]
     /// Create a linear proof, a lightweight variant of a Bulletproofs inner-product proof.
     /// This proves that <a, b> = c where a is secret and b is public.
     ///
-    /// The lengths of the vectors must all be the same, and must all be either 0 or a power of 2.
+    /// The lengths of the vectors must all be the same, and must all be a power of 2;
+    /// in particular, empty vectors are rejected with [`ProofError::InvalidInputLength`].
     /// The proof is created with respect to the bases \\(G\\).
+    #[allow(clippy::too_many_arguments)]
     pub fn create<T: RngCore + CryptoRng>(
         transcript: &mut Transcript,
         rng: &mut T,
@@ -56,6 +71,7 @@ impl LinearProof {
         B: &RistrettoPoint,
     ) -> Result<LinearProof, ProofError> {
         let mut n = b_vec.len();
+        check_vector_length(n)?;
         // All of the input vectors must have the same length.
         if G_vec.len() != n {
             return Err(ProofError::InvalidGeneratorsLength);
@@ -69,8 +85,8 @@ impl LinearProof {
         }
 
         // Append all public data to the transcript
-        transcript.innerproduct_domain_sep(n as u64);
-        transcript.append_point(b"C", &C);
+        transcript.innerproduct_domain_sep(n);
+        transcript.append_point(b"C", C);
         for b_i in &b_vec {
             transcript.append_scalar(b"b_i", b_i);
         }
@@ -92,13 +108,13 @@ impl LinearProof {
         let mut R_vec = Vec::with_capacity(lg_n);
 
         while n != 1 {
-            n = n / 2;
+            n /= 2;
             let (a_L, a_R) = a.split_at_mut(n);
             let (b_L, b_R) = b.split_at_mut(n);
             let (G_L, G_R) = G.split_at_mut(n);
 
-            let c_L = inner_product(&a_L, &b_R);
-            let c_R = inner_product(&a_R, &b_L);
+            let c_L = inner_product(a_L, b_R);
+            let c_R = inner_product(a_R, b_L);
 
             let s_j = Scalar::random(rng);
             let t_j = Scalar::random(rng);
@@ -128,9 +144,9 @@ impl LinearProof {
 
             for i in 0..n {
                 // a_L = a_L + x_j^{-1} * a_R
-                a_L[i] = a_L[i] + x_j_inv * a_R[i];
+                a_L[i] += x_j_inv * a_R[i];
                 // b_L = b_L + x_j * b_R
-                b_L[i] = b_L[i] + x_j * b_R[i];
+                b_L[i] += x_j * b_R[i];
                 // G_L = G_L + x_j * G_R
                 G_L[i] =
                     RistrettoPoint::vartime_multiscalar_mul(&[Scalar::ONE, x_j], &[G_L[i], G_R[i]]);
@@ -159,6 +175,12 @@ impl LinearProof {
         })
     }
 
+    #[cfg(feature = "verifier")]
+    /// Verifies the linear proof for the commitment `C` and public vector `b_vec`.
+    ///
+    /// Verification runs on a copy of the `transcript`, which is only
+    /// updated if the proof is valid, so a failed verification can be
+    /// retried with another proof against the same transcript.
     pub fn verify(
         &self,
         transcript: &mut Transcript,
@@ -172,15 +194,37 @@ impl LinearProof {
         B: &RistrettoPoint,
         // Public scalar vector b
         b_vec: Vec<Scalar>,
+    ) -> Result<(), ProofError> {
+        let mut working_transcript = transcript.clone();
+        self.verify_on_transcript(&mut working_transcript, C, G, F, B, b_vec)?;
+        *transcript = working_transcript;
+        Ok(())
+    }
+
+    #[cfg(feature = "verifier")]
+    fn verify_on_transcript(
+        &self,
+        transcript: &mut Transcript,
+        // Commitment to witness
+        C: &CompressedRistretto,
+        // Generator vector
+        G: &[RistrettoPoint],
+        // Pedersen generator F, for committing to the secret value
+        F: &RistrettoPoint,
+        // Pedersen generator B, for committing to the blinding value
+        B: &RistrettoPoint,
+        // Public scalar vector b
+        b_vec: Vec<Scalar>,
     ) -> Result<(), ProofError> {
         let n = b_vec.len();
+        check_vector_length(n)?;
         if G.len() != n {
             return Err(ProofError::InvalidGeneratorsLength);
         }
 
         // Append all public data to the transcript
-        transcript.innerproduct_domain_sep(n as u64);
-        transcript.append_point(b"C", &C);
+        transcript.innerproduct_domain_sep(n);
+        transcript.append_point(b"C", C);
         for b_i in &b_vec {
             transcript.append_scalar(b"b_i", b_i);
         }
@@ -241,6 +285,7 @@ impl LinearProof {
         }
     }
 
+    #[cfg(feature = "verifier")]
     /// Computes the vector of challenge scalars \\([x\_{i}]\\), and its inverse \\([x\_{i}^{-1}]\\)
     /// for combined multiscalar multiplication in a parent protocol.
     /// Also computes \\(b_0\\) which is the base case for public vector \\(b\\).
@@ -253,11 +298,8 @@ impl LinearProof {
         mut b_vec: Vec<Scalar>,
     ) -> Result<(Vec<Scalar>, Vec<Scalar>, Scalar), ProofError> {
         let lg_n = self.L_vec.len();
-        if lg_n >= 32 {
-            // 4 billion multiplications should be enough for anyone
-            // and this check prevents overflow in 1<<lg_n below.
-            return Err(ProofError::VerificationError);
-        }
+        // This check prevents overflow in 1<<lg_n below.
+        check_lg_vector_length(lg_n)?;
         if n != (1 << lg_n) {
             return Err(ProofError::VerificationError);
         }
@@ -272,10 +314,10 @@ impl LinearProof {
             transcript.validate_and_append_point(b"R", R)?;
             let x_j = transcript.challenge_scalar(b"x_j");
             challenges.push(x_j);
-            n_mut = n_mut / 2;
+            n_mut /= 2;
             let (b_L, b_R) = b.split_at_mut(n_mut);
             for i in 0..n_mut {
-                b_L[i] = b_L[i] + x_j * b_R[i];
+                b_L[i] += x_j * b_R[i];
             }
             b = b_L;
         }
@@ -294,6 +336,7 @@ impl LinearProof {
     ///
     /// Note that this is different from the Bulletproofs \\(s_i\\) generation,
     /// where \\(b(i, j)\\) = 1 if the jth bit of (i-1) is 1, and -1 otherwise.
+    #[cfg(feature = "verifier")]
     fn subset_product(&self, n: usize, challenges: Vec<Scalar>) -> Vec<Scalar> {
         let lg_n = self.L_vec.len();
 
@@ -316,7 +359,7 @@ impl LinearProof {
     /// For vectors of length `n` the proof size is
     /// \\(32 \cdot (2\lg n+3)\\) bytes.
     pub fn serialized_size(&self) -> usize {
-        (self.L_vec.len() * 2 + 3) * 32
+        (self.L_vec.len() * 2 + LINEAR_FINAL_ELEMENTS) * ELEMENT_BYTES
     }
 
     /// Serializes the proof into a byte array of \\(2n+3\\) 32-byte elements.
@@ -357,25 +400,26 @@ impl LinearProof {
     /// Deserializes the proof from a byte slice.
     /// Returns an error in the following cases:
     /// * the slice does not have \\(2n+3\\) 32-byte elements,
-    /// * \\(n\\) is larger or equal to 32 (proof is too big),
+    /// * \\(2^n\\) is larger than [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH) (proof is too big),
     /// * any of \\(2n + 1\\) points are not valid compressed Ristretto points,
     /// * any of 2 scalars are not canonical scalars modulo Ristretto group order.
+    ///
+    /// Since every scalar must be canonically encoded, a proof has
+    /// exactly one valid byte representation.
     pub fn from_bytes(slice: &[u8]) -> Result<LinearProof, ProofError> {
         let b = slice.len();
-        if b % 32 != 0 {
+        if !b.is_multiple_of(ELEMENT_BYTES) {
             return Err(ProofError::FormatError);
         }
-        let num_elements = b / 32;
-        if num_elements < 3 {
+        let num_elements = b / ELEMENT_BYTES;
+        if num_elements < LINEAR_FINAL_ELEMENTS {
             return Err(ProofError::FormatError);
         }
-        if (num_elements - 3) % 2 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        let lg_n = (num_elements - 3) / 2;
-        if lg_n >= 32 {
+        if !(num_elements - LINEAR_FINAL_ELEMENTS).is_multiple_of(2) {
             return Err(ProofError::FormatError);
         }
+        let lg_n = (num_elements - LINEAR_FINAL_ELEMENTS) / 2;
+        check_lg_vector_length(lg_n)?;
 
         use crate::util::read32;
 
@@ -413,7 +457,7 @@ mod tests {
 
         use crate::generators::{BulletproofGens, PedersenGens};
         let bp_gens = BulletproofGens::new(n, 1);
-        let G: Vec<RistrettoPoint> = bp_gens.share(0).G(n).cloned().collect();
+        let G: Vec<RistrettoPoint> = bp_gens.try_share(0).unwrap().G(n).cloned().collect();
 
         let pedersen_gens = PedersenGens::default();
         let F = pedersen_gens.B;
@@ -456,6 +500,22 @@ mod tests {
         // Test serialization and deserialization
         let serialized_proof = proof.to_bytes();
         assert_eq!(proof.serialized_size(), serialized_proof.len());
+        crate::util::assert_noncanonical_scalars_rejected(
+            &serialized_proof,
+            &[serialized_proof.len() - 64, serialized_proof.len() - 32],
+            LinearProof::from_bytes,
+        );
+
+        // A failed verification leaves the transcript as it was, so the
+        // same transcript can be used to verify another proof.
+        let mut retry_transcript = Transcript::new(b"linearprooftest");
+        let wrong_C = (C.decompress().unwrap() + B).compress();
+        assert!(proof
+            .verify(&mut retry_transcript, &wrong_C, &G, &F, &B, b.clone())
+            .is_err());
+        assert!(proof
+            .verify(&mut retry_transcript, &C, &G, &F, &B, b.clone())
+            .is_ok());
 
         let deserialized_proof = LinearProof::from_bytes(&serialized_proof).unwrap();
         let mut serde_verifier_transcript = Transcript::new(b"linearprooftest");
@@ -483,4 +543,17 @@ mod tests {
     fn test_linear_proof_64() {
         test_helper(64);
     }
+
+    #[test]
+    fn oversized_statements_are_rejected() {
+        // An encoding with 32 rounds is just over the limit.
+        let bytes = vec![0u8; (2 * 32 + 3) * 32];
+        assert_eq!(
+            LinearProof::from_bytes(&bytes).map(|_| ()),
+            Err(ProofError::StatementTooLarge {
+                requested: 1 << 32,
+                max: crate::MAX_VECTOR_LENGTH,
+            })
+        );
+    }
 }
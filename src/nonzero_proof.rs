@@ -0,0 +1,438 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "prover")]
+use curve25519_dalek::traits::MultiscalarMul;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "prover")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::consts::{ELEMENT_BYTES, NONZERO_PROOF_ELEMENTS};
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::PedersenGens;
+#[cfg(feature = "verifier")]
+use crate::transcript::{StatementSession, TranscriptProtocol};
+#[cfg(all(feature = "prover", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+/// A proof that a Pedersen commitment \\(C = v B + \tilde{v} \tilde{B}\\)
+/// commits to a nonzero value \\(v\\).
+///
+/// The value is nonzero exactly when it has an inverse \\(u = v^{-1}\\),
+/// in which case \\(u C - u \tilde{v} \tilde{B} = B\\).  The proof shows
+/// knowledge of such a representation of \\(B\\) in the bases \\(C\\)
+/// and \\(\tilde{B}\\): the prover commits to random \\(k\_1, k\_2\\)
+/// with \\(R = k\_1 C + k\_2 \tilde{B}\\), receives a challenge
+/// \\(c\\), and responds with \\(s\_1 = k\_1 + c u\\) and
+/// \\(s\_2 = k\_2 - c u \tilde{v}\\).  The verifier checks
+/// \\(s\_1 C + s\_2 \tilde{B} = R + c B\\).  A commitment to zero is a
+/// multiple of \\(\tilde{B}\\), so a proof for one would reveal the
+/// discrete log of \\(B\\) with respect to \\(\tilde{B}\\).
+///
+/// The proof is 96 bytes.  Created and verified on the same transcript
+/// as a range proof for the same commitment, it shows that
+/// \\(0 < v < 2\^n\\):
+///
+/// ```
+/// use bulletproofs::{BitSize, BulletproofGens, NonZeroProof, PedersenGens, RangeProof};
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+/// let (v, v_blinding) = (1037578891u64, Scalar::from(7u64));
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// let (range_proof, commitment) =
+///     RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, v, &v_blinding, BitSize::B64)?;
+/// let nonzero_proof =
+///     NonZeroProof::prove(&pc_gens, &mut transcript, Scalar::from(v), v_blinding);
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// range_proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &commitment, BitSize::B64)?;
+/// nonzero_proof.verify(&pc_gens, &mut transcript, &commitment)?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+#[derive(Clone, Debug)]
+pub struct NonZeroProof {
+    /// Commitment to the masks
+    R: CompressedRistretto,
+    /// Response for the inverse of the value
+    s_1: Scalar,
+    /// Response for the coefficient of \\(\tilde{B}\\)
+    s_2: Scalar,
+}
+
+impl NonZeroProof {
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the commitment to `v` with the blinding
+    /// factor `v_blinding` commits to a nonzero value.
+    ///
+    /// The prover does not check that `v` is nonzero: if it is zero,
+    /// the proof does not verify.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: Scalar,
+        v_blinding: Scalar,
+        rng: &mut T,
+    ) -> NonZeroProof {
+        let C = pc_gens.commit(v, v_blinding);
+        let u = v.invert();
+
+        transcript.nonzero_proof_domain_sep();
+        pc_gens.append_fingerprint(transcript);
+        transcript.append_point(b"C", &C.compress());
+
+        let k_1 = Scalar::random(rng);
+        let k_2 = Scalar::random(rng);
+        let R = RistrettoPoint::multiscalar_mul([k_1, k_2], [C, pc_gens.B_blinding]).compress();
+        transcript.append_point(b"R", &R);
+        let c = transcript.challenge_scalar(b"c");
+
+        NonZeroProof {
+            R,
+            s_1: k_1 + c * u,
+            s_2: k_2 - c * u * v_blinding,
+        }
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the commitment to `v` with the blinding
+    /// factor `v_blinding` commits to a nonzero value.
+    /// This is a convenience wrapper around [`NonZeroProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: Scalar,
+        v_blinding: Scalar,
+    ) -> NonZeroProof {
+        NonZeroProof::prove_with_rng(pc_gens, transcript, v, v_blinding, &mut util::default_rng())
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the `index`-th commitment of `session`,
+    /// to `v` with the blinding factor `v_blinding`, commits to a
+    /// nonzero value, on the next branch of the session.
+    ///
+    /// The proof only verifies with [`NonZeroProof::verify_in_session`]
+    /// on the branch in the same position of the same session.  Returns
+    /// [`ProofError::InvalidInputLength`] if the session has no
+    /// `index`-th commitment, without taking a branch.
+    pub fn prove_in_session_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        v: Scalar,
+        v_blinding: Scalar,
+        rng: &mut T,
+    ) -> Result<NonZeroProof, ProofError> {
+        session.commitment(index)?;
+        let mut transcript = session.branch(b"nonzero-proof");
+        transcript.append_len(b"index", index);
+        Ok(NonZeroProof::prove_with_rng(
+            pc_gens,
+            &mut transcript,
+            v,
+            v_blinding,
+            rng,
+        ))
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the `index`-th commitment of `session`
+    /// commits to a nonzero value, on the next branch of the session.
+    /// This is a convenience wrapper around [`NonZeroProof::prove_in_session_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_in_session(
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        v: Scalar,
+        v_blinding: Scalar,
+    ) -> Result<NonZeroProof, ProofError> {
+        NonZeroProof::prove_in_session_with_rng(
+            pc_gens,
+            session,
+            index,
+            v,
+            v_blinding,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that `commitment` commits to a nonzero value.
+    ///
+    /// Returns [`ProofError::FormatError`] if the commitment is not a
+    /// valid point.  The transcript is left unchanged if verification
+    /// fails.
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        commitment: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        let C = commitment.decompress().ok_or(ProofError::FormatError)?;
+
+        let mut working_transcript = transcript.clone();
+        working_transcript.nonzero_proof_domain_sep();
+        pc_gens.append_fingerprint(&mut working_transcript);
+        working_transcript.append_point(b"C", commitment);
+        working_transcript.validate_and_append_point(b"R", &self.R)?;
+        let c = working_transcript.challenge_scalar(b"c");
+
+        // s_1 * C + s_2 * B_blinding - c * B - R == 0
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            [self.s_1, self.s_2, -c, -Scalar::ONE],
+            [
+                Some(C),
+                Some(pc_gens.B_blinding),
+                Some(pc_gens.B),
+                self.R.decompress(),
+            ],
+        )
+        .ok_or(ProofError::VerificationError)?;
+
+        if check.is_identity() {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that the `index`-th commitment of `session` commits to
+    /// a nonzero value, on the next branch of the session.
+    ///
+    /// Returns [`ProofError::InvalidInputLength`] if the session has no
+    /// `index`-th commitment, without taking a branch.
+    pub fn verify_in_session(
+        &self,
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+    ) -> Result<(), ProofError> {
+        let commitment = session.commitment(index)?;
+        let mut transcript = session.branch(b"nonzero-proof");
+        transcript.append_len(b"index", index);
+        self.verify(pc_gens, &mut transcript, &commitment)
+    }
+
+    /// Serializes the proof into a byte array of 96 bytes: the
+    /// compressed point \\(R\\) followed by the scalars \\(s\_1\\)
+    /// and \\(s\_2\\).
+    pub fn to_bytes(&self) -> [u8; NONZERO_PROOF_ELEMENTS * ELEMENT_BYTES] {
+        let mut buf = [0u8; NONZERO_PROOF_ELEMENTS * ELEMENT_BYTES];
+        buf[..32].copy_from_slice(self.R.as_bytes());
+        buf[32..64].copy_from_slice(self.s_1.as_bytes());
+        buf[64..].copy_from_slice(self.s_2.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice is not 96 bytes
+    /// long or either scalar is not canonical.
+    pub fn from_bytes(slice: &[u8]) -> Result<NonZeroProof, ProofError> {
+        if slice.len() != NONZERO_PROOF_ELEMENTS * ELEMENT_BYTES {
+            return Err(ProofError::FormatError);
+        }
+
+        use crate::util::read32;
+
+        let R = CompressedRistretto(read32(&slice[..32]));
+        let s_1 = Option::from(Scalar::from_canonical_bytes(read32(&slice[32..])))
+            .ok_or(ProofError::FormatError)?;
+        let s_2 = Option::from(Scalar::from_canonical_bytes(read32(&slice[64..])))
+            .ok_or(ProofError::FormatError)?;
+        Ok(NonZeroProof { R, s_1, s_2 })
+    }
+}
+
+impl Serialize for NonZeroProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for NonZeroProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NonZeroProofVisitor;
+
+        impl<'de> Visitor<'de> for NonZeroProofVisitor {
+            type Value = NonZeroProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid NonZeroProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<NonZeroProof, E>
+            where
+                E: serde::de::Error,
+            {
+                NonZeroProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(NonZeroProofVisitor)
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    use crate::util;
+
+    fn prove_and_verify(
+        pc_gens: &PedersenGens,
+        v: Scalar,
+        v_blinding: Scalar,
+        commitment: &CompressedRistretto,
+    ) -> Result<(), ProofError> {
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        let proof = NonZeroProof::prove(pc_gens, &mut transcript, v, v_blinding);
+        let proof = NonZeroProof::from_bytes(&proof.to_bytes()).unwrap();
+
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        proof.verify(pc_gens, &mut transcript, commitment)
+    }
+
+    #[test]
+    fn nonzero_values_verify() {
+        let pc_gens = PedersenGens::default();
+        let mut rng = rand::thread_rng();
+        for v in [
+            Scalar::ONE,
+            -Scalar::ONE,
+            Scalar::from(u64::MAX),
+            Scalar::random(&mut rng),
+        ] {
+            let v_blinding = Scalar::random(&mut rng);
+            let commitment = pc_gens.commit(v, v_blinding).compress();
+            assert!(prove_and_verify(&pc_gens, v, v_blinding, &commitment).is_ok());
+        }
+    }
+
+    #[test]
+    fn zero_is_rejected() {
+        let pc_gens = PedersenGens::default();
+        let v_blinding = Scalar::random(&mut rand::thread_rng());
+        let commitment = pc_gens.commit(Scalar::ZERO, v_blinding).compress();
+        assert_eq!(
+            prove_and_verify(&pc_gens, Scalar::ZERO, v_blinding, &commitment),
+            Err(ProofError::VerificationError)
+        );
+    }
+
+    #[test]
+    fn mismatched_commitments_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let (v, v_blinding) = (Scalar::from(5u64), Scalar::from(11u64));
+        for commitment in [
+            pc_gens.commit(Scalar::from(6u64), v_blinding),
+            pc_gens.commit(v, v_blinding + Scalar::ONE),
+            // A commitment to zero, which has no proof.
+            pc_gens.commit(Scalar::ZERO, v_blinding),
+        ] {
+            let commitment = commitment.compress();
+            assert_eq!(
+                prove_and_verify(&pc_gens, v, v_blinding, &commitment),
+                Err(ProofError::VerificationError)
+            );
+        }
+
+        let invalid = CompressedRistretto([0xff; 32]);
+        assert_eq!(
+            prove_and_verify(&pc_gens, v, v_blinding, &invalid),
+            Err(ProofError::FormatError)
+        );
+    }
+
+    #[test]
+    fn transcript_is_unchanged_on_failure() {
+        let pc_gens = PedersenGens::default();
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        let proof = NonZeroProof::prove(&pc_gens, &mut transcript, Scalar::ONE, Scalar::ONE);
+        let other = pc_gens.commit(Scalar::ONE, Scalar::ZERO).compress();
+
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        let before = transcript.clone();
+        assert!(proof.verify(&pc_gens, &mut transcript, &other).is_err());
+        let (mut a, mut b) = (transcript, before);
+        assert_eq!(a.challenge_scalar(b"x"), b.challenge_scalar(b"x"));
+    }
+
+    #[test]
+    fn proof_composes_with_range_proof() {
+        use crate::{BitSize, BulletproofGens, RangeProof};
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let v_blinding = Scalar::from(3u64);
+
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        let (range_proof, commitment) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            200,
+            &v_blinding,
+            BitSize::B8,
+        )
+        .unwrap();
+        let proof =
+            NonZeroProof::prove(&pc_gens, &mut transcript, Scalar::from(200u64), v_blinding);
+
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        range_proof
+            .verify_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitment,
+                BitSize::B8,
+            )
+            .unwrap();
+        assert!(proof.verify(&pc_gens, &mut transcript, &commitment).is_ok());
+
+        // The proof is bound to the range proof before it.
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        assert!(proof
+            .verify(&pc_gens, &mut transcript, &commitment)
+            .is_err());
+    }
+
+    #[test]
+    fn noncanonical_scalars_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let mut transcript = Transcript::new(b"NonZeroProofTest");
+        let proof = NonZeroProof::prove(&pc_gens, &mut transcript, Scalar::ONE, Scalar::ONE);
+        util::assert_noncanonical_scalars_rejected(
+            &proof.to_bytes(),
+            &[32, 64],
+            NonZeroProof::from_bytes,
+        );
+        assert!(NonZeroProof::from_bytes(&[0u8; 95]).is_err());
+    }
+}
@@ -0,0 +1,473 @@
+//! The `parameters` module contains API for bundling the generators
+//! of a proof system with the statement sizes they were built for.
+//!
+//! A `ProofParameters` value owns one `PedersenGens` and one
+//! `BulletproofGens`, sized once at construction, so that provers and
+//! verifiers sharing it cannot disagree on the generators or silently
+//! use a generator set that is too small for the statement.
+
+#![deny(missing_docs)]
+
+extern crate alloc;
+
+use alloc::string::String;
+#[cfg(feature = "prover")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "prover")]
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use rand_core::{CryptoRng, RngCore};
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::inner_product_proof::check_vector_length;
+#[cfg(feature = "verifier")]
+use crate::range_proof::{BitSize, RangeProof};
+#[cfg(all(feature = "verifier", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+use crate::errors::R1CSError;
+#[cfg(all(feature = "yoloproofs", feature = "std", feature = "prover"))]
+use crate::r1cs::Prover;
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+use crate::r1cs::R1CSProof;
+#[cfg(all(feature = "yoloproofs", feature = "std", feature = "verifier"))]
+use crate::r1cs::Verifier;
+#[cfg(all(feature = "yoloproofs", feature = "std"))]
+use core::borrow::BorrowMut;
+
+/// A registered set of generators together with the largest
+/// statement they support.
+///
+/// Construct one with [`ProofParameters::for_range_proofs`] or
+/// [`ProofParameters::for_r1cs`] and share it between the prover and
+/// the verifier.  The prove and verify methods delegate to the
+/// existing APIs, after checking that the statement fits the
+/// registered sizes.
+///
+/// With `serde`, the parameters are stored as their label, Pedersen
+/// generators, and sizes; the Bulletproofs generators are derived
+/// again on deserialization, which rejects sizes that the
+/// constructors would not accept.
+#[derive(Clone)]
+pub struct ProofParameters {
+    label: &'static str,
+    pc_gens: PedersenGens,
+    bp_gens: BulletproofGens,
+    max_n: usize,
+    max_parties: usize,
+}
+
+impl ProofParameters {
+    /// Creates parameters for range proofs of up to `max_bits` bits,
+    /// aggregated over up to `max_parties` values.
+    ///
+    /// Returns an error if `max_bits` is not one of \\(8\\), \\(16\\),
+    /// \\(32\\), \\(64\\), if `max_parties` is not a power of two, or
+    /// if the generators would exceed [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH).
+    pub fn for_range_proofs(max_bits: usize, max_parties: usize) -> Result<Self, ProofError> {
+        if !(max_bits == 8 || max_bits == 16 || max_bits == 32 || max_bits == 64) {
+            return Err(ProofError::InvalidBitsize);
+        }
+        if !max_parties.is_power_of_two() {
+            return Err(ProofError::InvalidAggregation);
+        }
+        check_vector_length(max_bits.saturating_mul(max_parties))?;
+        Ok(ProofParameters {
+            label: "range-proof",
+            pc_gens: PedersenGens::default(),
+            bp_gens: BulletproofGens::new(max_bits, max_parties),
+            max_n: max_bits,
+            max_parties,
+        })
+    }
+
+    /// Creates parameters for constraint system proofs with up to
+    /// `max_multipliers` multiplication gates, including those added
+    /// in the randomization phase.
+    ///
+    /// The generator capacity is rounded up to the next power of two.
+    /// Returns an error if it would exceed
+    /// [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH).
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    pub fn for_r1cs(max_multipliers: usize) -> Result<Self, ProofError> {
+        check_vector_length(max_multipliers)?;
+        let max_n = max_multipliers.next_power_of_two();
+        Ok(ProofParameters {
+            label: "r1cs",
+            pc_gens: PedersenGens::default(),
+            bp_gens: BulletproofGens::new(max_n, 1),
+            max_n,
+            max_parties: 1,
+        })
+    }
+
+    /// Returns the label of the kind of proof these parameters were
+    /// created for.
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    /// Returns the Pedersen generators.
+    pub fn pc_gens(&self) -> &PedersenGens {
+        &self.pc_gens
+    }
+
+    /// Returns the Bulletproofs generators.
+    pub fn bp_gens(&self) -> &BulletproofGens {
+        &self.bp_gens
+    }
+
+    /// Returns the largest supported bitsize (for range proofs) or
+    /// padded number of multipliers (for constraint system proofs).
+    pub fn max_n(&self) -> usize {
+        self.max_n
+    }
+
+    /// Returns the largest supported number of aggregated parties.
+    pub fn max_parties(&self) -> usize {
+        self.max_parties
+    }
+
+    /// Checks that an `n`-bit statement over `m` values fits these
+    /// parameters.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn check_range_statement(&self, n: usize, m: usize) -> Result<(), ProofError> {
+        if n > self.max_n || m > self.max_parties {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates an aggregated `n`-bit rangeproof for `values`, using
+    /// the registered generators.
+    ///
+    /// See [`RangeProof::prove_multiple_with_rng`].
+    pub fn prove_range_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        self.check_range_statement(n.bits(), values.len())?;
+        RangeProof::prove_multiple_with_rng(
+            &self.bp_gens,
+            &self.pc_gens,
+            transcript,
+            values,
+            blindings,
+            n,
+            rng,
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates an aggregated `n`-bit rangeproof for `values`.
+    /// This is a convenience wrapper around [`ProofParameters::prove_range_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_range(
+        &self,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        self.prove_range_with_rng(transcript, values, blindings, n, &mut util::default_rng())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated `n`-bit rangeproof for
+    /// `value_commitments`, using the registered generators.
+    ///
+    /// See [`RangeProof::verify_multiple_with_rng`].
+    pub fn verify_range_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        proof: &RangeProof,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        self.check_range_statement(n.bits(), value_commitments.len())?;
+        proof.verify_multiple_with_rng(
+            &self.bp_gens,
+            &self.pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated `n`-bit rangeproof.
+    /// This is a convenience wrapper around [`ProofParameters::verify_range_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_range(
+        &self,
+        proof: &RangeProof,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        self.verify_range_with_rng(
+            proof,
+            transcript,
+            value_commitments,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a [`Prover`] that commits with the registered Pedersen
+    /// generators.
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    pub fn r1cs_prover<T: BorrowMut<Transcript>>(&self, transcript: T) -> Prover<'_, T> {
+        Prover::new(&self.pc_gens, transcript)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Consumes the `prover` and creates a constraint system proof
+    /// with the registered generators.
+    ///
+    /// Returns [`R1CSError::InvalidGeneratorsLength`] if the circuit
+    /// has more multipliers than these parameters were created for.
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    pub fn prove_r1cs<T: BorrowMut<Transcript>>(
+        &self,
+        prover: Prover<'_, T>,
+    ) -> Result<R1CSProof, R1CSError> {
+        prover.prove(&self.bp_gens)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Consumes the `verifier` and checks the constraint system
+    /// `proof` with the registered generators.
+    #[cfg(all(feature = "yoloproofs", feature = "std"))]
+    pub fn verify_r1cs<T: BorrowMut<Transcript>>(
+        &self,
+        verifier: Verifier<T>,
+        proof: &R1CSProof,
+    ) -> Result<(), R1CSError> {
+        verifier.verify(proof, &self.pc_gens, &self.bp_gens)
+    }
+}
+
+/// The serialized form of [`ProofParameters`].
+#[derive(Serialize, Deserialize)]
+struct ProofParametersRepr {
+    label: String,
+    pc_gens: PedersenGens,
+    max_n: usize,
+    max_parties: usize,
+}
+
+impl Serialize for ProofParameters {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        ProofParametersRepr {
+            label: self.label.into(),
+            pc_gens: self.pc_gens,
+            max_n: self.max_n,
+            max_parties: self.max_parties,
+        }
+        .serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for ProofParameters {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let repr = ProofParametersRepr::deserialize(deserializer)?;
+        ProofParameters::try_from(repr).map_err(serde::de::Error::custom)
+    }
+}
+
+impl TryFrom<ProofParametersRepr> for ProofParameters {
+    type Error = ProofError;
+
+    fn try_from(repr: ProofParametersRepr) -> Result<Self, ProofError> {
+        check_vector_length(repr.max_n.saturating_mul(repr.max_parties))?;
+        let params = match repr.label.as_str() {
+            "range-proof" => ProofParameters::for_range_proofs(repr.max_n, repr.max_parties)?,
+            #[cfg(all(feature = "yoloproofs", feature = "std"))]
+            "r1cs" if repr.max_n.is_power_of_two() && repr.max_parties == 1 => {
+                ProofParameters::for_r1cs(repr.max_n)?
+            }
+            _ => return Err(ProofError::FormatError),
+        };
+        Ok(ProofParameters {
+            pc_gens: repr.pc_gens,
+            ..params
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_proof_roundtrip() {
+        let params = ProofParameters::for_range_proofs(32, 4).unwrap();
+        assert_eq!(params.label(), "range-proof");
+
+        let values = [1u64, 2, 3, 4];
+        let blindings: Vec<_> = (0..4)
+            .map(|_| Scalar::random(&mut rand::thread_rng()))
+            .collect();
+
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        let (proof, commitments) = params
+            .prove_range(&mut transcript, &values, &blindings, BitSize::B32)
+            .unwrap();
+
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        assert!(params
+            .verify_range(&proof, &mut transcript, &commitments, BitSize::B32)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_invalid_parameters() {
+        assert_eq!(
+            ProofParameters::for_range_proofs(24, 1).err(),
+            Some(ProofError::InvalidBitsize)
+        );
+        assert_eq!(
+            ProofParameters::for_range_proofs(64, 3).err(),
+            Some(ProofError::InvalidAggregation)
+        );
+        assert_eq!(
+            ProofParameters::for_range_proofs(64, 0).err(),
+            Some(ProofError::InvalidAggregation)
+        );
+    }
+
+    #[test]
+    fn rejects_statements_exceeding_parameters() {
+        let params = ProofParameters::for_range_proofs(16, 2).unwrap();
+        let blindings: Vec<_> = (0..4)
+            .map(|_| Scalar::random(&mut rand::thread_rng()))
+            .collect();
+
+        // Bitsize above the registered maximum.
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        assert_eq!(
+            params
+                .prove_range(&mut transcript, &[1], &blindings[..1], BitSize::B32)
+                .err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+
+        // More parties than registered.
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        assert_eq!(
+            params
+                .prove_range(&mut transcript, &[1, 2, 3, 4], &blindings, BitSize::B16)
+                .err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+
+        // A proof made with larger parameters is rejected up front
+        // by the verifier rather than failing inside the verifier.
+        let large = ProofParameters::for_range_proofs(64, 4).unwrap();
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        let (proof, commitments) = large
+            .prove_range(&mut transcript, &[1, 2, 3, 4], &blindings, BitSize::B64)
+            .unwrap();
+        let mut transcript = Transcript::new(b"ProofParametersTest");
+        assert_eq!(
+            params
+                .verify_range(&proof, &mut transcript, &commitments, BitSize::B64)
+                .err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
+    }
+
+    #[test]
+    fn parameters_json_roundtrip() {
+        let params = ProofParameters::for_range_proofs(32, 4).unwrap();
+        let json = serde_json::to_string(&params).unwrap();
+        let decoded: ProofParameters = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.label(), "range-proof");
+        assert_eq!(decoded.max_n(), 32);
+        assert_eq!(decoded.max_parties(), 4);
+        assert_eq!(decoded.pc_gens().B, params.pc_gens().B);
+        assert_eq!(decoded.pc_gens().B_blinding, params.pc_gens().B_blinding);
+        assert_eq!(
+            decoded.bp_gens().G(32, 4).last(),
+            params.bp_gens().G(32, 4).last()
+        );
+
+        let mut json = serde_json::to_value(&params).unwrap();
+        json["max_parties"] = 3.into();
+        assert!(serde_json::from_value::<ProofParameters>(json.clone()).is_err());
+        json["max_parties"] = 4.into();
+        json["label"] = "other".into();
+        assert!(serde_json::from_value::<ProofParameters>(json).is_err());
+    }
+
+    #[cfg(feature = "yoloproofs")]
+    #[test]
+    fn r1cs_roundtrip_and_capacity() {
+        use crate::r1cs::ConstraintSystem;
+
+        // Proves knowledge of `k` pairs of committed values whose
+        // products are public, using `k` multipliers.
+        fn prove_and_verify(params: &ProofParameters, k: usize) -> Result<(), R1CSError> {
+            let proof = {
+                let mut prover = params.r1cs_prover(Transcript::new(b"ProofParametersTest"));
+                for i in 0..k {
+                    let (_, a) = prover.commit(Scalar::from(i as u64 + 2), Scalar::ONE);
+                    let (_, b) = prover.commit(Scalar::from(3u64), Scalar::ONE);
+                    let (_, _, o) = prover.multiply(a.into(), b.into());
+                    prover.constrain(o - Scalar::from(3 * (i as u64 + 2)));
+                }
+                params.prove_r1cs(prover)?
+            };
+
+            let pc_gens = params.pc_gens();
+            let mut verifier = Verifier::new(Transcript::new(b"ProofParametersTest"));
+            for i in 0..k {
+                let a = verifier.commit(
+                    pc_gens
+                        .commit(Scalar::from(i as u64 + 2), Scalar::ONE)
+                        .compress(),
+                );
+                let b = verifier.commit(pc_gens.commit(Scalar::from(3u64), Scalar::ONE).compress());
+                let (_, _, o) = verifier.multiply(a.into(), b.into());
+                verifier.constrain(o - Scalar::from(3 * (i as u64 + 2)));
+            }
+            params.verify_r1cs(verifier, &proof)
+        }
+
+        let params = ProofParameters::for_r1cs(3).unwrap();
+        assert_eq!(params.label(), "r1cs");
+        assert_eq!(params.max_n(), 4);
+
+        assert!(prove_and_verify(&params, 4).is_ok());
+        assert_eq!(
+            prove_and_verify(&params, 5),
+            Err(R1CSError::InvalidGeneratorsLength)
+        );
+    }
+}
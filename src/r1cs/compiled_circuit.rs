@@ -0,0 +1,580 @@
+//! Definition of compiled circuits, whose constraints are recorded once
+//! and reused to verify many proofs.
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::{
+    ConstraintSystem, LinearCombination, Metrics, R1CSError, RandomizableConstraintSystem,
+    RandomizedConstraintSystem, Variable,
+};
+use crate::transcript::TranscriptProtocol;
+
+/// A term of a compiled constraint.
+///
+/// The coefficient of `var` is `constant + Σ c_j·z_j`, where `z_j` is
+/// the `j`-th challenge drawn by the randomized constraints and the sum
+/// runs over the `(j, c_j)` pairs of `challenge_coeffs`.
+#[derive(Clone, Debug)]
+struct CompiledTerm {
+    var: Variable,
+    constant: Scalar,
+    challenge_coeffs: Vec<(usize, Scalar)>,
+}
+
+/// The constraints of a gadget, recorded once so that proofs can be
+/// verified without running the gadget code again.
+///
+/// A `CompiledCircuit` is produced by [`CompiledCircuit::compile`],
+/// which runs the gadget against a [`CircuitRecorder`], and is consumed
+/// by [`Verifier::verify_compiled`](super::Verifier::verify_compiled),
+/// which substitutes the public inputs and the challenges of each proof
+/// into the recorded constraints.
+///
+/// The gadget sees its public inputs as variables, so it must use them
+/// only inside linear combinations; a public input that decides the
+/// shape of the circuit, or that is multiplied with a challenge in
+/// scalar arithmetic, cannot be compiled.  Randomized constraints are
+/// supported when their coefficients are affine in the challenges, as
+/// in the shuffle gadget.
+#[derive(Clone, Debug)]
+pub struct CompiledCircuit {
+    num_commitments: usize,
+    num_public_inputs: usize,
+    phase_one_multipliers: usize,
+    multipliers: usize,
+    randomized: bool,
+    challenge_labels: Vec<&'static [u8]>,
+    phase_one_constraints: usize,
+    constraints: Vec<Vec<CompiledTerm>>,
+}
+
+/// A callback adding randomized constraints to a [`CircuitRecorder`].
+type DeferredConstraint = Box<dyn FnOnce(&mut RandomizingRecorder) -> Result<(), R1CSError>>;
+
+/// A [`ConstraintSystem`] implementation that records the constraints
+/// of a gadget for [`CompiledCircuit::compile`].
+///
+/// The recorder does not bind anything to a proof transcript: the
+/// transcript it exposes is a scratch transcript, and the challenges it
+/// returns are placeholders.  Gadgets compiled with it must therefore
+/// draw challenges only through
+/// [`RandomizedConstraintSystem::challenge_scalar`].
+pub struct CircuitRecorder {
+    transcript: Transcript,
+    constraints: Vec<LinearCombination>,
+    num_vars: usize,
+    pending_multiplier: Option<usize>,
+    deferred_constraints: Vec<DeferredConstraint>,
+    /// Values returned for the challenges, in the order they are drawn.
+    challenges: Vec<Scalar>,
+    challenge_labels: Vec<&'static [u8]>,
+    /// Source of the values of challenges beyond `challenges`.
+    challenge_source: Transcript,
+}
+
+/// Recorder in the randomizing phase.
+///
+/// Like the verifier in the randomizing phase, this type cannot be
+/// instantiated by the user and is only available within the callback
+/// provided to `specify_randomized_constraints`.
+pub struct RandomizingRecorder {
+    recorder: CircuitRecorder,
+}
+
+/// The constraints of a single run of a gadget against a recorder.
+struct Recording {
+    constraints: Vec<LinearCombination>,
+    phase_one_constraints: usize,
+    phase_one_multipliers: usize,
+    multipliers: usize,
+    randomized: bool,
+    challenges: Vec<Scalar>,
+    challenge_labels: Vec<&'static [u8]>,
+}
+
+impl ConstraintSystem for CircuitRecorder {
+    fn transcript(&mut self) -> &mut Transcript {
+        &mut self.transcript
+    }
+
+    fn multiply(
+        &mut self,
+        mut left: LinearCombination,
+        mut right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        let var = self.num_vars;
+        self.num_vars += 1;
+
+        let l_var = Variable::MultiplierLeft(var);
+        let r_var = Variable::MultiplierRight(var);
+        let o_var = Variable::MultiplierOutput(var);
+
+        left.terms.push((l_var, -Scalar::ONE));
+        right.terms.push((r_var, -Scalar::ONE));
+        self.constrain(left);
+        self.constrain(right);
+
+        (l_var, r_var, o_var)
+    }
+
+    fn allocate(&mut self, _: Option<Scalar>) -> Result<Variable, R1CSError> {
+        match self.pending_multiplier {
+            None => {
+                let i = self.num_vars;
+                self.num_vars += 1;
+                self.pending_multiplier = Some(i);
+                Ok(Variable::MultiplierLeft(i))
+            }
+            Some(i) => {
+                self.pending_multiplier = None;
+                Ok(Variable::MultiplierRight(i))
+            }
+        }
+    }
+
+    fn allocate_multiplier(
+        &mut self,
+        _: Option<(Scalar, Scalar)>,
+    ) -> Result<(Variable, Variable, Variable), R1CSError> {
+        let var = self.num_vars;
+        self.num_vars += 1;
+
+        Ok((
+            Variable::MultiplierLeft(var),
+            Variable::MultiplierRight(var),
+            Variable::MultiplierOutput(var),
+        ))
+    }
+
+    fn metrics(&self) -> Metrics {
+        Metrics {
+            multipliers: self.num_vars,
+            constraints: self.constraints.len() + self.deferred_constraints.len(),
+            phase_one_constraints: self.constraints.len(),
+            phase_two_constraints: self.deferred_constraints.len(),
+        }
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.constraints.push(lc);
+    }
+}
+
+impl RandomizableConstraintSystem for CircuitRecorder {
+    type RandomizedCS = RandomizingRecorder;
+
+    fn specify_randomized_constraints<F>(&mut self, callback: F) -> Result<(), R1CSError>
+    where
+        F: 'static + FnOnce(&mut Self::RandomizedCS) -> Result<(), R1CSError>,
+    {
+        self.deferred_constraints.push(Box::new(callback));
+        Ok(())
+    }
+}
+
+impl ConstraintSystem for RandomizingRecorder {
+    fn transcript(&mut self) -> &mut Transcript {
+        self.recorder.transcript()
+    }
+
+    fn multiply(
+        &mut self,
+        left: LinearCombination,
+        right: LinearCombination,
+    ) -> (Variable, Variable, Variable) {
+        self.recorder.multiply(left, right)
+    }
+
+    fn allocate(&mut self, assignment: Option<Scalar>) -> Result<Variable, R1CSError> {
+        self.recorder.allocate(assignment)
+    }
+
+    fn allocate_multiplier(
+        &mut self,
+        input_assignments: Option<(Scalar, Scalar)>,
+    ) -> Result<(Variable, Variable, Variable), R1CSError> {
+        self.recorder.allocate_multiplier(input_assignments)
+    }
+
+    fn metrics(&self) -> Metrics {
+        self.recorder.metrics()
+    }
+
+    fn constrain(&mut self, lc: LinearCombination) {
+        self.recorder.constrain(lc)
+    }
+}
+
+impl RandomizedConstraintSystem for RandomizingRecorder {
+    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
+        let recorder = &mut self.recorder;
+        let j = recorder.challenge_labels.len();
+        recorder.challenge_labels.push(label);
+        if j == recorder.challenges.len() {
+            let challenge = recorder.challenge_source.challenge_scalar(b"z");
+            recorder.challenges.push(challenge);
+        }
+        recorder.challenges[j]
+    }
+}
+
+impl CircuitRecorder {
+    /// Runs `gadget` with the given challenge values, drawing further
+    /// challenges from a fixed source once they run out.
+    fn record<F>(
+        num_commitments: usize,
+        num_public_inputs: usize,
+        gadget: &F,
+        challenges: Vec<Scalar>,
+    ) -> Result<Recording, R1CSError>
+    where
+        F: Fn(&mut CircuitRecorder, &[Variable], &[Variable]) -> Result<(), R1CSError>,
+    {
+        let mut recorder = CircuitRecorder {
+            transcript: Transcript::new(b"CircuitRecorder"),
+            constraints: Vec::new(),
+            num_vars: 0,
+            pending_multiplier: None,
+            deferred_constraints: Vec::new(),
+            challenges,
+            challenge_labels: Vec::new(),
+            challenge_source: Transcript::new(b"CircuitRecorder challenges"),
+        };
+        let commitments: Vec<Variable> = (0..num_commitments).map(Variable::Committed).collect();
+        let public_inputs: Vec<Variable> = (num_commitments..)
+            .take(num_public_inputs)
+            .map(Variable::Committed)
+            .collect();
+        gadget(&mut recorder, &commitments, &public_inputs)?;
+
+        let phase_one_constraints = recorder.constraints.len();
+        let phase_one_multipliers = recorder.num_vars;
+        let randomized = !recorder.deferred_constraints.is_empty();
+
+        // As in the verifier, the pending multiplier was committed in
+        // the first phase.
+        recorder.pending_multiplier = None;
+        let callbacks = std::mem::take(&mut recorder.deferred_constraints);
+        let mut wrapped = RandomizingRecorder { recorder };
+        for callback in callbacks {
+            callback(&mut wrapped)?;
+        }
+        let recorder = wrapped.recorder;
+
+        Ok(Recording {
+            constraints: recorder.constraints,
+            phase_one_constraints,
+            phase_one_multipliers,
+            multipliers: recorder.num_vars,
+            randomized,
+            challenges: recorder.challenges,
+            challenge_labels: recorder.challenge_labels,
+        })
+    }
+}
+
+impl Recording {
+    /// Checks that `other` has the same multipliers, challenges and
+    /// constraint terms as `self`, up to the coefficients.
+    fn check_same_shape(&self, other: &Recording) -> Result<(), R1CSError> {
+        let same_shape = self.phase_one_constraints == other.phase_one_constraints
+            && self.phase_one_multipliers == other.phase_one_multipliers
+            && self.multipliers == other.multipliers
+            && self.randomized == other.randomized
+            && self.challenge_labels == other.challenge_labels
+            && self.constraints.len() == other.constraints.len()
+            && self
+                .constraints
+                .iter()
+                .zip(&other.constraints)
+                .all(|(a, b)| {
+                    a.terms.len() == b.terms.len()
+                        && a.terms.iter().zip(&b.terms).all(|(s, t)| s.0 == t.0)
+                });
+        if same_shape {
+            Ok(())
+        } else {
+            Err(R1CSError::GadgetError {
+                description: "circuit shape depends on the challenges".into(),
+            })
+        }
+    }
+}
+
+impl CompiledCircuit {
+    /// Records the constraints of `gadget` over `num_commitments`
+    /// committed variables and `num_public_inputs` public inputs.
+    ///
+    /// The gadget is called with a [`CircuitRecorder`], the variables
+    /// of the commitments and the variables standing for the public
+    /// inputs, which are replaced by their values at verification.  It
+    /// is called once more for each challenge it draws, and once to
+    /// check that the coefficients of its randomized constraints are
+    /// affine in the challenges.
+    ///
+    /// Returns [`R1CSError::GadgetError`] if the circuit cannot be
+    /// compiled, and any error returned by the gadget.
+    pub fn compile<F>(
+        num_commitments: usize,
+        num_public_inputs: usize,
+        gadget: F,
+    ) -> Result<CompiledCircuit, R1CSError>
+    where
+        F: Fn(&mut CircuitRecorder, &[Variable], &[Variable]) -> Result<(), R1CSError>,
+    {
+        let base = CircuitRecorder::record(num_commitments, num_public_inputs, &gadget, vec![])?;
+
+        // Shift each challenge by one in turn to find the coefficient of
+        // that challenge in every term.
+        let mut shifted = Vec::with_capacity(base.challenges.len());
+        for j in 0..base.challenges.len() {
+            let mut challenges = base.challenges.clone();
+            challenges[j] += Scalar::ONE;
+            let recording =
+                CircuitRecorder::record(num_commitments, num_public_inputs, &gadget, challenges)?;
+            base.check_same_shape(&recording)?;
+            shifted.push(recording);
+        }
+
+        let constraints: Vec<Vec<CompiledTerm>> = base
+            .constraints
+            .iter()
+            .enumerate()
+            .map(|(q, lc)| {
+                lc.terms
+                    .iter()
+                    .enumerate()
+                    .map(|(t, (var, coeff))| {
+                        let challenge_coeffs: Vec<(usize, Scalar)> = shifted
+                            .iter()
+                            .map(|recording| recording.constraints[q].terms[t].1 - coeff)
+                            .enumerate()
+                            .filter(|(_, c)| *c != Scalar::ZERO)
+                            .collect();
+                        let constant = challenge_coeffs
+                            .iter()
+                            .fold(*coeff, |acc, (j, c)| acc - c * base.challenges[*j]);
+                        CompiledTerm {
+                            var: *var,
+                            constant,
+                            challenge_coeffs,
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let circuit = CompiledCircuit {
+            num_commitments,
+            num_public_inputs,
+            phase_one_multipliers: base.phase_one_multipliers,
+            multipliers: base.multipliers,
+            randomized: base.randomized,
+            challenge_labels: base.challenge_labels.clone(),
+            phase_one_constraints: base.phase_one_constraints,
+            constraints,
+        };
+
+        // Check the interpolated coefficients at an independent point.
+        if !base.challenges.is_empty() {
+            let mut source = Transcript::new(b"CompiledCircuit check");
+            let challenges: Vec<Scalar> = base
+                .challenges
+                .iter()
+                .map(|_| source.challenge_scalar(b"z"))
+                .collect();
+            let recording = CircuitRecorder::record(
+                num_commitments,
+                num_public_inputs,
+                &gadget,
+                challenges.clone(),
+            )?;
+            base.check_same_shape(&recording)?;
+            let affine =
+                circuit
+                    .constraints
+                    .iter()
+                    .zip(&recording.constraints)
+                    .all(|(terms, lc)| {
+                        terms
+                            .iter()
+                            .zip(&lc.terms)
+                            .all(|(term, (_, coeff))| term.coefficient(&challenges) == *coeff)
+                    });
+            if !affine {
+                return Err(R1CSError::GadgetError {
+                    description: "randomized constraints are not affine in the challenges".into(),
+                });
+            }
+        }
+
+        Ok(circuit)
+    }
+
+    /// Returns the number of committed variables of the circuit.
+    pub fn num_commitments(&self) -> usize {
+        self.num_commitments
+    }
+
+    /// Returns the number of public inputs of the circuit.
+    pub fn num_public_inputs(&self) -> usize {
+        self.num_public_inputs
+    }
+
+    /// Returns the number of multipliers and constraints of the circuit.
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            multipliers: self.multipliers,
+            constraints: self.constraints.len(),
+            phase_one_constraints: self.phase_one_constraints,
+            phase_two_constraints: self.constraints.len() - self.phase_one_constraints,
+        }
+    }
+
+    pub(super) fn phase_one_multipliers(&self) -> usize {
+        self.phase_one_multipliers
+    }
+
+    pub(super) fn multipliers(&self) -> usize {
+        self.multipliers
+    }
+
+    pub(super) fn is_randomized(&self) -> bool {
+        self.randomized
+    }
+
+    pub(super) fn challenge_labels(&self) -> &[&'static [u8]] {
+        &self.challenge_labels
+    }
+
+    /// Instantiates the first-phase constraints with `public_inputs`.
+    pub(super) fn phase_one_constraints(&self, public_inputs: &[Scalar]) -> Vec<LinearCombination> {
+        self.instantiate(
+            &self.constraints[..self.phase_one_constraints],
+            public_inputs,
+            &[],
+        )
+    }
+
+    /// Instantiates the second-phase constraints with `public_inputs`
+    /// and `challenges`.
+    pub(super) fn randomized_constraints(
+        &self,
+        public_inputs: &[Scalar],
+        challenges: &[Scalar],
+    ) -> Vec<LinearCombination> {
+        self.instantiate(
+            &self.constraints[self.phase_one_constraints..],
+            public_inputs,
+            challenges,
+        )
+    }
+
+    fn instantiate(
+        &self,
+        constraints: &[Vec<CompiledTerm>],
+        public_inputs: &[Scalar],
+        challenges: &[Scalar],
+    ) -> Vec<LinearCombination> {
+        constraints
+            .iter()
+            .map(|terms| {
+                terms
+                    .iter()
+                    .map(|term| {
+                        let coeff = term.coefficient(challenges);
+                        match term.var {
+                            Variable::Committed(i) if i >= self.num_commitments => (
+                                Variable::One(),
+                                coeff * public_inputs[i - self.num_commitments],
+                            ),
+                            var => (var, coeff),
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl CompiledTerm {
+    fn coefficient(&self, challenges: &[Scalar]) -> Scalar {
+        self.challenge_coeffs
+            .iter()
+            .fold(self.constant, |acc, (j, c)| acc + c * challenges[*j])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squared_challenge_gadget(
+        cs: &mut CircuitRecorder,
+        committed: &[Variable],
+        _: &[Variable],
+    ) -> Result<(), R1CSError> {
+        let x = committed[0];
+        cs.specify_randomized_constraints(move |cs| {
+            let z = cs.challenge_scalar(b"z");
+            cs.constrain(x * (z * z));
+            Ok(())
+        })
+    }
+
+    fn affine_challenge_gadget(
+        cs: &mut CircuitRecorder,
+        committed: &[Variable],
+        public_inputs: &[Variable],
+    ) -> Result<(), R1CSError> {
+        let (x, y) = (committed[0], public_inputs[0]);
+        cs.specify_randomized_constraints(move |cs| {
+            let z = cs.challenge_scalar(b"z");
+            let w = cs.challenge_scalar(b"w");
+            let (_, _, o) = cs.multiply(x - z, y + w * Scalar::from(3u64));
+            cs.constrain(o - z);
+            Ok(())
+        })
+    }
+
+    fn challenge_dependent_shape_gadget(
+        cs: &mut CircuitRecorder,
+        committed: &[Variable],
+        _: &[Variable],
+    ) -> Result<(), R1CSError> {
+        let x = committed[0];
+        cs.specify_randomized_constraints(move |cs| {
+            let z = cs.challenge_scalar(b"z");
+            if z.as_bytes()[0] & 1 == 1 {
+                cs.constrain(x.into());
+            }
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn affine_challenges_are_interpolated() {
+        let circuit = CompiledCircuit::compile(1, 1, affine_challenge_gadget).unwrap();
+        assert_eq!(circuit.challenge_labels(), &[&b"z"[..], &b"w"[..]]);
+        assert_eq!(circuit.metrics().multipliers, 1);
+
+        let (public, z, w) = (Scalar::from(5u64), Scalar::from(7u64), Scalar::from(11u64));
+        let constraints = circuit.randomized_constraints(&[public], &[z, w]);
+        assert_eq!(
+            constraints,
+            vec![
+                Variable::Committed(0) - z - Variable::MultiplierLeft(0),
+                LinearCombination::from(public) + Scalar::from(33u64)
+                    - Variable::MultiplierRight(0),
+                Variable::MultiplierOutput(0) - z,
+            ]
+        );
+    }
+
+    #[test]
+    fn uncompilable_circuits_are_rejected() {
+        assert!(CompiledCircuit::compile(1, 0, squared_challenge_gadget).is_err());
+        assert!(CompiledCircuit::compile(1, 0, challenge_dependent_shape_gadget).is_err());
+    }
+}
@@ -0,0 +1,553 @@
+//! Reusable gadgets for constraint systems.
+//!
+//! Gadgets are written against the [`ConstraintSystem`] trait, so the
+//! prover and the verifier build identical constraints by calling the
+//! same function, with the prover additionally supplying assignments.
+
+use curve25519_dalek::scalar::Scalar;
+
+use super::{ConstraintSystem, LinearCombination, R1CSError, Variable};
+
+/// Enforces that exactly one of the `selector` variables is one, and
+/// the others are zero.
+///
+/// The gadget uses one multiplier per variable.  The variables may be
+/// committed, so that a one-hot vector of commitments can be proven.
+pub fn one_hot<CS: ConstraintSystem>(cs: &mut CS, selector: &[Variable]) {
+    let mut sum = LinearCombination::default();
+    for &s_j in selector {
+        // Enforce s_j * (1 - s_j) = 0, so s_j is 0 or 1.
+        let (_, _, o) = cs.multiply(s_j.into(), Scalar::ONE - s_j);
+        cs.constrain(o.into());
+
+        sum = sum + s_j;
+    }
+
+    // Enforce that exactly one selector is set.
+    cs.constrain(sum - Scalar::ONE);
+}
+
+/// Enforces that exactly one of the `selector` variables is one, at a
+/// position where the public mask `allowed` is `true`, and the others
+/// are zero.
+///
+/// Disallowed variables are constrained to zero with linear
+/// constraints, so the gadget uses one multiplier per allowed
+/// variable.  The mask is part of the statement: the verifier must
+/// pass the same mask, and it is not carried by the proof.
+///
+/// Returns [`R1CSError::GadgetError`] if `allowed` and `selector` have
+/// different lengths, or if the mask allows no position, in which case
+/// no selector could satisfy the constraints.
+pub fn one_hot_masked<CS: ConstraintSystem>(
+    cs: &mut CS,
+    selector: &[Variable],
+    allowed: &[bool],
+) -> Result<(), R1CSError> {
+    if allowed.len() != selector.len() {
+        return Err(R1CSError::GadgetError {
+            description: "the mask must have one entry per selector".into(),
+        });
+    }
+    if !allowed.contains(&true) {
+        return Err(R1CSError::GadgetError {
+            description: "the mask must allow at least one position".into(),
+        });
+    }
+
+    let mut sum = LinearCombination::default();
+    for (&s_j, &allowed_j) in selector.iter().zip(allowed) {
+        if allowed_j {
+            // Enforce s_j * (1 - s_j) = 0, so s_j is 0 or 1.
+            let (_, _, o) = cs.multiply(s_j.into(), Scalar::ONE - s_j);
+            cs.constrain(o.into());
+
+            sum = sum + s_j;
+        } else {
+            cs.constrain(s_j.into());
+        }
+    }
+
+    // Enforce that exactly one allowed selector is set.
+    cs.constrain(sum - Scalar::ONE);
+    Ok(())
+}
+
+/// Enforces that `out` equals `table[i]`, where `i` is the position of
+/// the single `1` in the one-hot `selector`.
+///
+/// The gadget constrains the selector with [`one_hot`], then constrains
+/// \\(\texttt{out} = \sum\_j \texttt{selector}\_j \cdot \texttt{table}\_j\\).
+/// Since the table is public, the last constraint is linear, so the
+/// gadget uses exactly one multiplier per table entry.
+///
+/// The selector can be allocated by the prover with [`allocate_one_hot`].
+pub fn lookup<CS: ConstraintSystem>(
+    cs: &mut CS,
+    table: &[Scalar],
+    selector: Vec<Variable>,
+    out: LinearCombination,
+) -> Result<(), R1CSError> {
+    if selector.len() != table.len() {
+        return Err(R1CSError::GadgetError {
+            description: "selector length must match table length".into(),
+        });
+    }
+
+    one_hot(cs, &selector);
+
+    let mut selected = out;
+    for (&s_j, t_j) in selector.iter().zip(table.iter()) {
+        selected = selected - s_j * *t_j;
+    }
+
+    // Enforce that out = Sum(s_j * t_j, j = 0..len-1)
+    cs.constrain(selected);
+
+    Ok(())
+}
+
+/// Enforces that `out` equals `table[i]`, where `i` is the
+/// little-endian number whose bits are `index_bits`.
+///
+/// The bits are constrained to be boolean and expanded into a one-hot
+/// selector over \\(2\^k\\) entries, where \\(k\\) is the number of bits;
+/// entries past the end of the table are constrained to be unselected.
+/// This takes \\(k + 2\^k - 2\\) multipliers, so it only pays off over
+/// [`lookup`] for small tables, where fewer variables need to be committed.
+///
+/// The bits can be allocated by the prover with [`allocate_index_bits`].
+pub fn lookup_bits<CS: ConstraintSystem>(
+    cs: &mut CS,
+    table: &[Scalar],
+    index_bits: Vec<Variable>,
+    out: LinearCombination,
+) -> Result<(), R1CSError> {
+    let capacity = 1usize
+        .checked_shl(index_bits.len() as u32)
+        .ok_or(R1CSError::GadgetError {
+            description: "too many index bits".into(),
+        })?;
+    if index_bits.is_empty() || table.len() > capacity {
+        return Err(R1CSError::GadgetError {
+            description: "table does not fit the index bits".into(),
+        });
+    }
+
+    // Enforce b_i * (1 - b_i) = 0, so each b_i is 0 or 1.
+    for &b_i in &index_bits {
+        let (_, _, o) = cs.multiply(b_i.into(), Scalar::ONE - b_i);
+        cs.constrain(o.into());
+    }
+
+    // After processing bits b_0..b_i, selector[j] is the product over
+    // those bits of b_t if bit t of j is set, and 1 - b_t otherwise.
+    let mut selector: Vec<LinearCombination> =
+        vec![Scalar::ONE - index_bits[0], index_bits[0].into()];
+    for &b_i in &index_bits[1..] {
+        let (low, high): (Vec<_>, Vec<_>) = selector
+            .into_iter()
+            .map(|s_j| {
+                let (_, _, s_j_b_i) = cs.multiply(s_j.clone(), b_i.into());
+                (s_j - s_j_b_i, LinearCombination::from(s_j_b_i))
+            })
+            .unzip();
+        selector = low.into_iter().chain(high).collect();
+    }
+
+    // Entries past the end of the table cannot be selected.
+    let mut selected = out;
+    for (j, s_j) in selector.into_iter().enumerate() {
+        match table.get(j) {
+            Some(t_j) => selected = selected - s_j * *t_j,
+            None => cs.constrain(s_j),
+        }
+    }
+
+    // Enforce that out = Sum(s_j * t_j, j = 0..len-1)
+    cs.constrain(selected);
+
+    Ok(())
+}
+
+/// Enforces that each of the `entries` is one of \\(0, 1, \ldots, c\\),
+/// where \\(c + 1\\) is the length of `counts`, and that exactly
+/// `counts[s]` of the entries equal \\(s\\).
+///
+/// Each entry is looked up with [`lookup`] from the table of symbols
+/// \\(0, \ldots, c\\) with its one-hot selector from `selectors`,
+/// and the selector bits of each symbol are constrained to add up to
+/// its count.  Since the counts are public, the last constraints are
+/// linear, so the gadget uses \\(c + 1\\) multipliers per entry.
+///
+/// The selectors can be allocated by the prover with
+/// [`allocate_one_hot`], using each entry as the index.
+pub fn multiset<CS: ConstraintSystem>(
+    cs: &mut CS,
+    counts: &[u64],
+    entries: &[Variable],
+    selectors: Vec<Vec<Variable>>,
+) -> Result<(), R1CSError> {
+    if selectors.len() != entries.len() {
+        return Err(R1CSError::GadgetError {
+            description: "there must be one selector per entry".into(),
+        });
+    }
+
+    let alphabet: Vec<Scalar> = (0..counts.len() as u64).map(Scalar::from).collect();
+    let mut sums = vec![LinearCombination::default(); counts.len()];
+    for (&entry, selector) in entries.iter().zip(selectors) {
+        for (sum, &s_j) in sums.iter_mut().zip(&selector) {
+            *sum = core::mem::take(sum) + s_j;
+        }
+        lookup(cs, &alphabet, selector, entry.into())?;
+    }
+
+    // Enforce that symbol s is selected counts[s] times.
+    for (sum, &count) in sums.into_iter().zip(counts) {
+        cs.constrain(sum - Scalar::from(count));
+    }
+
+    Ok(())
+}
+
+/// Allocates a one-hot selector of length `len` with the `index`-th
+/// entry set, for use with [`lookup`].
+///
+/// The prover passes `Some(index)` and the verifier passes `None`.
+/// The variables are unconstrained until passed to [`lookup`].
+pub fn allocate_one_hot<CS: ConstraintSystem>(
+    cs: &mut CS,
+    index: Option<usize>,
+    len: usize,
+) -> Result<Vec<Variable>, R1CSError> {
+    if let Some(i) = index {
+        if i >= len {
+            return Err(R1CSError::GadgetError {
+                description: "index out of range".into(),
+            });
+        }
+    }
+    (0..len)
+        .map(|j| cs.allocate(index.map(|i| Scalar::from((i == j) as u64))))
+        .collect()
+}
+
+/// Allocates the `num_bits` little-endian bits of `index`, for use
+/// with [`lookup_bits`].
+///
+/// The prover passes `Some(index)` and the verifier passes `None`.
+/// The variables are unconstrained until passed to [`lookup_bits`].
+pub fn allocate_index_bits<CS: ConstraintSystem>(
+    cs: &mut CS,
+    index: Option<usize>,
+    num_bits: usize,
+) -> Result<Vec<Variable>, R1CSError> {
+    if let Some(i) = index {
+        if num_bits < usize::BITS as usize && i >> num_bits != 0 {
+            return Err(R1CSError::GadgetError {
+                description: "index out of range".into(),
+            });
+        }
+    }
+    (0..num_bits)
+        .map(|k| cs.allocate(index.map(|i| Scalar::from(((i >> k) & 1) as u64))))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+    use crate::r1cs::{Prover, R1CSProof, Verifier};
+    use merlin::Transcript;
+
+    fn table() -> Vec<Scalar> {
+        (0..16u64).map(|j| Scalar::from(j * j + 7)).collect()
+    }
+
+    /// Proves that the committed `out` is looked up from `table` with
+    /// a selector allocated by `prover_selector`, and verifies it.
+    fn lookup_helper<F>(table: &[Scalar], out: Scalar, prover_selector: F) -> Result<(), R1CSError>
+    where
+        F: FnOnce(&mut Prover<'_, Transcript>) -> Result<Vec<Variable>, R1CSError>,
+    {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+
+        let (proof, commitment) = {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"LookupTest"));
+            prover.skip_witness_check();
+            let (commitment, out_var) = prover.commit(out, Scalar::from(5u64));
+            let selector = prover_selector(&mut prover)?;
+            lookup(&mut prover, table, selector, out_var.into())?;
+            (prover.prove(&bp_gens)?, commitment)
+        };
+
+        let mut verifier = Verifier::new(Transcript::new(b"LookupTest"));
+        let out_var = verifier.commit(commitment);
+        let selector = allocate_one_hot(&mut verifier, None, table.len())?;
+        lookup(&mut verifier, table, selector, out_var.into())?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    /// Proves and verifies that the committed `out` is `table[index]`
+    /// with [`lookup_bits`].
+    fn lookup_bits_helper(
+        table: &[Scalar],
+        out: Scalar,
+        index: usize,
+        num_bits: usize,
+    ) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+
+        let (proof, commitment) = {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"LookupBitsTest"));
+            prover.skip_witness_check();
+            let (commitment, out_var) = prover.commit(out, Scalar::from(5u64));
+            let bits = allocate_index_bits(&mut prover, Some(index), num_bits)?;
+            lookup_bits(&mut prover, table, bits, out_var.into())?;
+            (prover.prove(&bp_gens)?, commitment)
+        };
+
+        let mut verifier = Verifier::new(Transcript::new(b"LookupBitsTest"));
+        let out_var = verifier.commit(commitment);
+        let bits = allocate_index_bits(&mut verifier, None, num_bits)?;
+        lookup_bits(&mut verifier, table, bits, out_var.into())?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    #[test]
+    fn lookup_every_entry() {
+        let table = table();
+        for i in 0..table.len() {
+            assert!(lookup_helper(&table, table[i], |cs| {
+                allocate_one_hot(cs, Some(i), table.len())
+            })
+            .is_ok());
+        }
+        // The committed output must match the selected entry.
+        assert!(lookup_helper(&table, table[4], |cs| allocate_one_hot(cs, Some(3), 16)).is_err());
+        assert!(lookup_helper(&table, table[0], |cs| allocate_one_hot(cs, Some(16), 16)).is_err());
+    }
+
+    #[test]
+    fn lookup_rejects_forged_selectors() {
+        let table = table();
+        let forged = |assignments: Vec<Scalar>| {
+            move |cs: &mut Prover<'_, Transcript>| -> Result<Vec<Variable>, R1CSError> {
+                assignments.iter().map(|&s| cs.allocate(Some(s))).collect()
+            }
+        };
+        let mut selector = vec![Scalar::ZERO; 16];
+
+        // Two entries selected.
+        selector[2] = Scalar::ONE;
+        selector[9] = Scalar::ONE;
+        assert!(lookup_helper(&table, table[2] + table[9], forged(selector.clone())).is_err());
+
+        // Sums to one, but is not boolean.
+        selector[5] = -Scalar::ONE;
+        assert!(lookup_helper(&table, table[2] + table[9] - table[5], forged(selector)).is_err());
+
+        // Nothing selected.
+        assert!(lookup_helper(&table, Scalar::ZERO, forged(vec![Scalar::ZERO; 16])).is_err());
+    }
+
+    #[test]
+    fn lookup_bits_every_entry() {
+        let table = table();
+        for i in 0..table.len() {
+            assert!(lookup_bits_helper(&table, table[i], i, 4).is_ok());
+        }
+        assert!(lookup_bits_helper(&table, table[4], 3, 4).is_err());
+
+        // Indices past the end of a short table cannot be selected.
+        let short = &table[..11];
+        assert!(lookup_bits_helper(short, table[10], 10, 4).is_ok());
+        assert!(lookup_bits_helper(short, Scalar::ZERO, 11, 4).is_err());
+        assert!(lookup_bits_helper(&table, table[0], 0, 3).is_err());
+    }
+
+    /// Proves that committed `entries` have the multiplicities `counts`
+    /// with selectors allocated by `prover_selectors`, and verifies it.
+    fn multiset_helper<F>(
+        counts: &[u64],
+        entries: &[u64],
+        prover_selectors: F,
+    ) -> Result<(), R1CSError>
+    where
+        F: FnOnce(&mut Prover<'_, Transcript>) -> Result<Vec<Vec<Variable>>, R1CSError>,
+    {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 1);
+
+        let (proof, commitments) = {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"MultisetTest"));
+            prover.skip_witness_check();
+            let (commitments, vars): (Vec<_>, Vec<_>) = entries
+                .iter()
+                .map(|&v| prover.commit(Scalar::from(v), Scalar::from(5u64)))
+                .unzip();
+            let selectors = prover_selectors(&mut prover)?;
+            multiset(&mut prover, counts, &vars, selectors)?;
+            (prover.prove(&bp_gens)?, commitments)
+        };
+
+        let mut verifier = Verifier::new(Transcript::new(b"MultisetTest"));
+        let vars: Vec<_> = commitments.iter().map(|&c| verifier.commit(c)).collect();
+        let selectors = vars
+            .iter()
+            .map(|_| allocate_one_hot(&mut verifier, None, counts.len()))
+            .collect::<Result<_, _>>()?;
+        multiset(&mut verifier, counts, &vars, selectors)?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    /// Allocates the selectors of `entries` honestly.
+    fn honest_selectors(
+        entries: &[u64],
+        len: usize,
+    ) -> impl FnOnce(&mut Prover<'_, Transcript>) -> Result<Vec<Vec<Variable>>, R1CSError> + '_
+    {
+        move |cs| {
+            entries
+                .iter()
+                .map(|&v| allocate_one_hot(cs, Some(v as usize), len))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn multiset_of_trits() {
+        let entries = [2, 0, 1, 2, 2, 0];
+        let counts = [2, 1, 3];
+        assert!(multiset_helper(&counts, &entries, honest_selectors(&entries, 3)).is_ok());
+
+        // The counts must match the entries.
+        assert!(multiset_helper(&[3, 0, 3], &entries, honest_selectors(&entries, 3)).is_err());
+        assert!(multiset_helper(&[2, 1, 3, 0], &entries, honest_selectors(&entries, 4)).is_ok());
+
+        // An entry outside the alphabet has no selector.
+        let entries = [3, 0, 1, 2, 2, 0];
+        assert!(multiset_helper(&counts, &entries, honest_selectors(&entries, 3)).is_err());
+    }
+
+    #[test]
+    fn multiset_rejects_forged_selectors() {
+        // The entry 3 selects symbols 1 and 2, which keeps the counts.
+        let entries = [3, 0, 0, 2];
+        let forged = |cs: &mut Prover<'_, Transcript>| -> Result<Vec<Vec<Variable>>, R1CSError> {
+            [[0, 1, 1], [1, 0, 0], [1, 0, 0], [0, 0, 0]]
+                .iter()
+                .map(|row| {
+                    row.iter()
+                        .map(|&s| cs.allocate(Some(Scalar::from(s as u64))))
+                        .collect()
+                })
+                .collect()
+        };
+        assert!(multiset_helper(&[2, 1, 1], &entries, forged).is_err());
+
+        assert_eq!(
+            multiset_helper(&[2, 1, 1], &entries, |_| Ok(vec![])),
+            Err(R1CSError::GadgetError {
+                description: "there must be one selector per entry".into(),
+            })
+        );
+    }
+
+    /// Proves that the `index`-th of `n` committed selectors is set,
+    /// at a position allowed by `prover_mask`, and verifies it against
+    /// `verifier_mask`.
+    fn one_hot_masked_helper(
+        index: usize,
+        prover_mask: &[bool],
+        verifier_mask: &[bool],
+    ) -> Result<(), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 1);
+        let n = prover_mask.len();
+
+        let (proof, commitments) = {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"OneHotMaskedTest"));
+            prover.skip_witness_check();
+            let (commitments, selector): (Vec<_>, Vec<_>) = (0..n)
+                .map(|j| prover.commit(Scalar::from((j == index) as u64), Scalar::from(j as u64)))
+                .unzip();
+            one_hot_masked(&mut prover, &selector, prover_mask)?;
+            (prover.prove(&bp_gens)?, commitments)
+        };
+
+        // The mask is not part of the encoding.
+        let proof = R1CSProof::from_bytes(&proof.to_bytes())?;
+
+        let mut verifier = Verifier::new(Transcript::new(b"OneHotMaskedTest"));
+        let selector: Vec<_> = commitments.iter().map(|&c| verifier.commit(c)).collect();
+        one_hot_masked(&mut verifier, &selector, verifier_mask)?;
+        verifier.verify(&proof, &pc_gens, &bp_gens)
+    }
+
+    #[test]
+    fn one_hot_masked_positions() {
+        let mask = [true, false, true, true, false, false, true, false];
+        for index in 0..mask.len() {
+            assert_eq!(
+                one_hot_masked_helper(index, &mask, &mask).is_ok(),
+                mask[index]
+            );
+        }
+
+        // A proof only verifies against the mask it was made with, even
+        // if the other mask also allows the index.
+        let wider = [true; 8];
+        assert!(one_hot_masked_helper(0, &wider, &wider).is_ok());
+        assert!(one_hot_masked_helper(0, &mask, &wider).is_err());
+        assert!(one_hot_masked_helper(0, &wider, &mask).is_err());
+    }
+
+    #[test]
+    fn one_hot_masked_rejects_bad_masks() {
+        assert_eq!(
+            one_hot_masked_helper(0, &[false; 4], &[false; 4]),
+            Err(R1CSError::GadgetError {
+                description: "the mask must allow at least one position".into(),
+            })
+        );
+
+        let pc_gens = PedersenGens::default();
+        let mut prover = Prover::new(&pc_gens, Transcript::new(b"OneHotMaskedTest"));
+        let selector = allocate_one_hot(&mut prover, Some(1), 4).unwrap();
+        assert_eq!(
+            one_hot_masked(&mut prover, &selector, &[true, true, true]),
+            Err(R1CSError::GadgetError {
+                description: "the mask must have one entry per selector".into(),
+            })
+        );
+        assert_eq!(prover.metrics().multipliers, 2);
+    }
+
+    #[test]
+    fn circuit_sizes() {
+        let pc_gens = PedersenGens::default();
+        let table = table();
+        let mut prover = Prover::new(&pc_gens, Transcript::new(b"LookupTest"));
+        let (_, out) = prover.commit(table[3], Scalar::ONE);
+
+        // Allocating 16 variables takes 8 multipliers.
+        let selector = allocate_one_hot(&mut prover, Some(3), 16).unwrap();
+        assert_eq!(prover.metrics().multipliers, 8);
+
+        // The lookup takes one multiplier per table entry.
+        lookup(&mut prover, &table, selector, out.into()).unwrap();
+        assert_eq!(prover.metrics().multipliers, 8 + 16);
+
+        // With 4 bits: 4 for booleanity and 2 + 4 + 8 for the expansion,
+        // plus 2 for allocating the bits.
+        let bits = allocate_index_bits(&mut prover, Some(3), 4).unwrap();
+        lookup_bits(&mut prover, &table, bits, out.into()).unwrap();
+        assert_eq!(prover.metrics().multipliers, 8 + 16 + 2 + 18);
+    }
+}
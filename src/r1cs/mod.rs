@@ -3,20 +3,29 @@
 #[cfg_attr(feature = "docs", doc(include = "../../docs/cs-proof.md"))]
 mod notes {}
 
+#[cfg(feature = "verifier")]
+mod compiled_circuit;
 mod constraint_system;
+pub mod gadgets;
 mod linear_combination;
 mod metrics;
 mod proof;
+#[cfg(feature = "prover")]
 mod prover;
+#[cfg(feature = "verifier")]
 mod verifier;
 
+#[cfg(feature = "verifier")]
+pub use self::compiled_circuit::{CircuitRecorder, CompiledCircuit};
 pub use self::constraint_system::{
     ConstraintSystem, RandomizableConstraintSystem, RandomizedConstraintSystem,
 };
 pub use self::linear_combination::{LinearCombination, Variable};
 pub use self::metrics::Metrics;
 pub use self::proof::R1CSProof;
+#[cfg(feature = "prover")]
 pub use self::prover::Prover;
+#[cfg(feature = "verifier")]
 pub use self::verifier::Verifier;
 
 pub use crate::errors::R1CSError;
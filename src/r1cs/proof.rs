@@ -1,12 +1,19 @@
 #![allow(non_snake_case)]
 //! Definition of the proof struct.
 
+use core::fmt;
+
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::{Identity, IsIdentity};
 
+use crate::consts::{
+    ELEMENT_BYTES, R1CS_ONE_PHASE_HEADER_ELEMENTS, R1CS_TWO_PHASE_HEADER_ELEMENTS,
+    R1CS_VERSION_BYTES,
+};
 use crate::errors::R1CSError;
-use crate::inner_product_proof::InnerProductProof;
+use crate::inner_product_proof::{self, InnerProductProof};
+use crate::leakage::NoWitnessDebug;
 use crate::util;
 
 use serde::de::Visitor;
@@ -15,6 +22,14 @@ use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 const ONE_PHASE_COMMITMENTS: u8 = 0;
 const TWO_PHASE_COMMITMENTS: u8 = 1;
 
+/// The size in bytes of the largest proof accepted by
+/// [`R1CSProof::from_bytes`]: the version byte, the two-phase header,
+/// and the largest inner-product proof.
+#[cfg(feature = "std")]
+const MAX_PROOF_SIZE: usize = R1CS_VERSION_BYTES
+    + R1CS_TWO_PHASE_HEADER_ELEMENTS * ELEMENT_BYTES
+    + inner_product_proof::MAX_PROOF_SIZE;
+
 /// A proof of some statement specified by a
 /// [`ConstraintSystem`](::r1cs::ConstraintSystem).
 ///
@@ -31,7 +46,7 @@ const TWO_PHASE_COMMITMENTS: u8 = 1;
 /// the constraint system using
 /// [`VerifierCS::verify`](::r1cs::VerifierCS::verify) to verify the
 /// proof.
-#[derive(Clone, Debug)]
+#[derive(Clone)]
 #[allow(non_snake_case)]
 pub struct R1CSProof {
     /// Commitment to the values of input wires in the first phase.
@@ -74,7 +89,7 @@ impl R1CSProof {
     /// # Layout
     ///
     /// The layout of the r1cs proof encoding is:
-    /// * 1 version byte indicating whether the proof contains second-phase commitments or not,
+    /// * 1 version byte, \\(0\\) if the proof has no second-phase commitments and \\(1\\) otherwise,
     /// * 8 or 11 compressed Ristretto points \\(A_{I1},A_{O1},S_1,(A_{I2},A_{O2},S_2),T_1,...,T_6\\)
     ///   (\\(A_{I2},A_{O2},S_2\\) are skipped if there were no multipliers added in the randomized phase),
     /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
@@ -82,41 +97,80 @@ impl R1CSProof {
     /// * two scalars \\(a, b\\).
     pub fn to_bytes(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.push(self.version());
+        buf.extend(self.elements().flatten());
+        buf
+    }
+
+    /// Returns the version byte of the encoding of the proof.
+    fn version(&self) -> u8 {
         if self.missing_phase2_commitments() {
-            buf.push(ONE_PHASE_COMMITMENTS);
-            buf.extend_from_slice(self.A_I1.as_bytes());
-            buf.extend_from_slice(self.A_O1.as_bytes());
-            buf.extend_from_slice(self.S1.as_bytes());
+            ONE_PHASE_COMMITMENTS
         } else {
-            buf.push(TWO_PHASE_COMMITMENTS);
-            buf.extend_from_slice(self.A_I1.as_bytes());
-            buf.extend_from_slice(self.A_O1.as_bytes());
-            buf.extend_from_slice(self.S1.as_bytes());
-            buf.extend_from_slice(self.A_I2.as_bytes());
-            buf.extend_from_slice(self.A_O2.as_bytes());
-            buf.extend_from_slice(self.S2.as_bytes());
+            TWO_PHASE_COMMITMENTS
         }
-        buf.extend_from_slice(self.T_1.as_bytes());
-        buf.extend_from_slice(self.T_3.as_bytes());
-        buf.extend_from_slice(self.T_4.as_bytes());
-        buf.extend_from_slice(self.T_5.as_bytes());
-        buf.extend_from_slice(self.T_6.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
-        buf.extend(self.ipp_proof.to_bytes_iter());
-        buf
+    }
+
+    /// Returns the 32-byte elements of the encoding of the proof after
+    /// the version byte, in the layout of [`R1CSProof::to_bytes`].
+    fn elements(&self) -> impl Iterator<Item = &[u8; 32]> + '_ {
+        let phase2 = if self.missing_phase2_commitments() {
+            None
+        } else {
+            Some([
+                self.A_I2.as_bytes(),
+                self.A_O2.as_bytes(),
+                self.S2.as_bytes(),
+            ])
+        };
+        IntoIterator::into_iter([
+            self.A_I1.as_bytes(),
+            self.A_O1.as_bytes(),
+            self.S1.as_bytes(),
+        ])
+        .chain(phase2.into_iter().flat_map(IntoIterator::into_iter))
+        .chain(IntoIterator::into_iter([
+            self.T_1.as_bytes(),
+            self.T_3.as_bytes(),
+            self.T_4.as_bytes(),
+            self.T_5.as_bytes(),
+            self.T_6.as_bytes(),
+            self.t_x.as_bytes(),
+            self.t_x_blinding.as_bytes(),
+            self.e_blinding.as_bytes(),
+        ]))
+        .chain(self.ipp_proof.elements())
+    }
+
+    /// Writes the encoding of [`R1CSProof::to_bytes`] to `writer`, the
+    /// version byte and then one 32-byte element at a time, without
+    /// buffering the proof, and returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<usize> {
+        writer.write_all(&[self.version()])?;
+        Ok(1 + util::write_elements(writer, self.elements())?)
+    }
+
+    /// Reads a proof encoded as by [`R1CSProof::to_bytes`] from
+    /// `reader`, which must end where the proof ends.
+    ///
+    /// At most one byte more than the largest valid proof is read
+    /// before an oversized input is rejected.  Encodings that
+    /// [`R1CSProof::from_bytes`] rejects are errors of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).
+    #[cfg(feature = "std")]
+    pub fn read_from<R: std::io::Read>(reader: R) -> std::io::Result<R1CSProof> {
+        util::read_bounded(reader, MAX_PROOF_SIZE, R1CSProof::from_bytes)
     }
 
     /// Returns the size in bytes required to serialize the `R1CSProof`.
     pub fn serialized_size(&self) -> usize {
-        // version tag + (11 or 14) elements + the ipp
         let elements = if self.missing_phase2_commitments() {
-            11
+            R1CS_ONE_PHASE_HEADER_ELEMENTS
         } else {
-            14
+            R1CS_TWO_PHASE_HEADER_ELEMENTS
         };
-        1 + elements * 32 + self.ipp_proof.serialized_size()
+        R1CS_VERSION_BYTES + elements * ELEMENT_BYTES + self.ipp_proof.serialized_size()
     }
 
     fn missing_phase2_commitments(&self) -> bool {
@@ -126,20 +180,29 @@ impl R1CSProof {
     /// Deserializes the proof from a byte slice.
     ///
     /// Returns an error if the byte slice cannot be parsed into a `R1CSProof`.
+    ///
+    /// The encoding must be the one [`R1CSProof::to_bytes`] produces: a
+    /// proof tagged as having second-phase commitments that are all the
+    /// identity is rejected, since it is written without them.
+    ///
+    /// Every scalar, including those of the inner-product proof, must be
+    /// canonically encoded, otherwise a `FormatError` is returned.  This
+    /// also applies to proofs deserialized with `serde`, which goes
+    /// through this function.
     pub fn from_bytes(slice: &[u8]) -> Result<R1CSProof, R1CSError> {
-        if slice.len() < 1 {
+        if slice.is_empty() {
             return Err(R1CSError::FormatError);
         }
         let version = slice[0];
-        let mut slice = &slice[1..];
+        let mut slice = &slice[R1CS_VERSION_BYTES..];
 
-        if slice.len() % 32 != 0 {
+        if !slice.len().is_multiple_of(ELEMENT_BYTES) {
             return Err(R1CSError::FormatError);
         }
 
         let minlength = match version {
-            ONE_PHASE_COMMITMENTS => 11 * 32,
-            TWO_PHASE_COMMITMENTS => 14 * 32,
+            ONE_PHASE_COMMITMENTS => R1CS_ONE_PHASE_HEADER_ELEMENTS * ELEMENT_BYTES,
+            TWO_PHASE_COMMITMENTS => R1CS_TWO_PHASE_HEADER_ELEMENTS * ELEMENT_BYTES,
             _ => return Err(R1CSError::FormatError),
         };
 
@@ -166,11 +229,15 @@ impl R1CSProof {
                 CompressedRistretto::identity(),
             )
         } else {
-            (
+            let (A_I2, A_O2, S2) = (
                 CompressedRistretto(read32!()),
                 CompressedRistretto(read32!()),
                 CompressedRistretto(read32!()),
-            )
+            );
+            if A_I2.is_identity() && A_O2.is_identity() && S2.is_identity() {
+                return Err(R1CSError::FormatError);
+            }
+            (A_I2, A_O2, S2)
         };
         let T_1 = CompressedRistretto(read32!());
         let T_3 = CompressedRistretto(read32!());
@@ -184,8 +251,7 @@ impl R1CSProof {
         let e_blinding =
             Option::from(Scalar::from_canonical_bytes(read32!())).ok_or(R1CSError::FormatError)?;
 
-        // XXX: IPPProof from_bytes gives ProofError.
-        let ipp_proof = InnerProductProof::from_bytes(slice).map_err(|_| R1CSError::FormatError)?;
+        let ipp_proof = InnerProductProof::from_bytes(slice)?;
 
         Ok(R1CSProof {
             A_I1,
@@ -207,6 +273,44 @@ impl R1CSProof {
     }
 }
 
+impl fmt::Debug for R1CSProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use crate::util::TruncatedHex;
+
+        f.debug_struct("R1CSProof")
+            .field("A_I1", &TruncatedHex(self.A_I1.as_bytes()))
+            .field("A_O1", &TruncatedHex(self.A_O1.as_bytes()))
+            .field("S1", &TruncatedHex(self.S1.as_bytes()))
+            .field("A_I2", &TruncatedHex(self.A_I2.as_bytes()))
+            .field("A_O2", &TruncatedHex(self.A_O2.as_bytes()))
+            .field("S2", &TruncatedHex(self.S2.as_bytes()))
+            .field("lg_n", &self.ipp_proof.L_vec().len())
+            .field("bytes", &self.serialized_size())
+            .finish()
+    }
+}
+
+impl fmt::Display for R1CSProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "R1CSProof {{ n={}, bytes={} }}",
+            self.ipp_proof.vector_length(),
+            self.serialized_size()
+        )
+    }
+}
+
+/// \\(t(x)\\) and the inner-product proof are derived from the witness
+/// vectors.
+impl NoWitnessDebug for R1CSProof {
+    fn witness_derived_elements(&self) -> Vec<[u8; 32]> {
+        let mut elements = self.ipp_proof.witness_derived_elements();
+        elements.push(self.t_x.to_bytes());
+        elements
+    }
+}
+
 impl Serialize for R1CSProof {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
@@ -234,17 +338,100 @@ impl<'de> Deserialize<'de> for R1CSProof {
             where
                 E: serde::de::Error,
             {
-                // Using Error::custom requires T: Display, which our error
-                // type only implements when it implements std::error::Error.
-                #[cfg(feature = "std")]
-                return R1CSProof::from_bytes(v).map_err(serde::de::Error::custom);
-                // In no-std contexts, drop the error message.
-                #[cfg(not(feature = "std"))]
-                return R1CSProof::from_bytes(v)
-                    .map_err(|_| serde::de::Error::custom("deserialization error"));
+                R1CSProof::from_bytes(v).map_err(serde::de::Error::custom)
             }
         }
 
         deserializer.deserialize_bytes(R1CSProofVisitor)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+    use crate::r1cs::{
+        ConstraintSystem, Prover, RandomizableConstraintSystem, RandomizedConstraintSystem,
+    };
+    use merlin::Transcript;
+
+    /// Proves knowledge of `x, y` with `x * y = 6`, optionally adding a
+    /// randomized constraint so that the proof uses two phases.
+    fn make_proof(two_phase: bool) -> R1CSProof {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut transcript = Transcript::new(b"R1CSProofSerializationTest");
+        let mut prover = Prover::new(&pc_gens, &mut transcript);
+
+        let mut rng = rand::thread_rng();
+        let (_, x) = prover.commit(Scalar::from(2u64), Scalar::random(&mut rng));
+        let (_, y) = prover.commit(Scalar::from(3u64), Scalar::random(&mut rng));
+        let (_, _, o) = prover.multiply(x.into(), y.into());
+        prover.constrain(o - Scalar::from(6u64));
+
+        if two_phase {
+            prover
+                .specify_randomized_constraints(move |cs| {
+                    let z = cs.challenge_scalar(b"z");
+                    let (_, _, o) = cs.multiply(x * z, y.into());
+                    cs.constrain(o - Scalar::from(6u64) * z);
+                    Ok(())
+                })
+                .unwrap();
+        }
+
+        prover.prove(&bp_gens).unwrap()
+    }
+
+    #[test]
+    fn noncanonical_scalars_are_rejected() {
+        for &two_phase in &[false, true] {
+            let bytes = make_proof(two_phase).to_bytes();
+
+            // t_x, t_x_blinding, e_blinding follow the version byte and
+            // the 8 (or 11) commitments; the IPP's a and b come last.
+            let first = if two_phase { 1 + 11 * 32 } else { 1 + 8 * 32 };
+            let offsets = [
+                first,
+                first + 32,
+                first + 64,
+                bytes.len() - 64,
+                bytes.len() - 32,
+            ];
+            util::assert_noncanonical_scalars_rejected(&bytes, &offsets, R1CSProof::from_bytes);
+        }
+    }
+
+    #[test]
+    fn encoding_is_canonical() {
+        let proof = make_proof(false);
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), proof.serialized_size());
+        assert_eq!(R1CSProof::from_bytes(&bytes).unwrap().to_bytes(), bytes);
+
+        // The same proof, tagged as two-phase with identity commitments.
+        let mut two_phase = vec![TWO_PHASE_COMMITMENTS];
+        two_phase.extend_from_slice(&bytes[1..1 + 3 * 32]);
+        two_phase.extend_from_slice(&[0u8; 3 * 32]);
+        two_phase.extend_from_slice(&bytes[1 + 3 * 32..]);
+        assert_eq!(
+            R1CSProof::from_bytes(&two_phase).map(|_| ()),
+            Err(R1CSError::FormatError)
+        );
+    }
+
+    #[test]
+    fn oversized_statements_are_rejected() {
+        // A one-phase proof whose inner-product proof claims 32 rounds.
+        let mut bytes = vec![0u8; 1 + (11 + 2 * 32 + 2) * 32];
+        bytes[0] = ONE_PHASE_COMMITMENTS;
+        assert_eq!(
+            R1CSProof::from_bytes(&bytes).map(|_| ()),
+            Err(R1CSError::StatementTooLarge {
+                requested: 1 << 32,
+                max: crate::MAX_VECTOR_LENGTH,
+            })
+        );
+    }
+}
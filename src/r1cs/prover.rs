@@ -2,11 +2,11 @@
 
 use clear_on_drop::clear::Clear;
 use core::borrow::BorrowMut;
-use core::mem;
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::{Identity, MultiscalarMul};
 use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
 
 use super::{
     ConstraintSystem, LinearCombination, R1CSProof, RandomizableConstraintSystem,
@@ -14,11 +14,15 @@ use super::{
 };
 
 use crate::errors::R1CSError;
-use crate::generators::{BulletproofGens, PedersenGens};
-use crate::inner_product_proof::InnerProductProof;
+use crate::generators::{AsGenerators, PedersenGens};
+use crate::inner_product_proof::{check_vector_length, InnerProductProof};
 use crate::r1cs::Metrics;
 use crate::transcript::TranscriptProtocol;
 
+/// A callback adding randomized constraints to a [`Prover`].
+type DeferredConstraint<'g, T> =
+    Box<dyn FnOnce(&mut RandomizingProver<'g, T>) -> Result<(), R1CSError>>;
+
 /// A [`ConstraintSystem`] implementation for use by the prover.
 ///
 /// The prover commits high-level variables and their blinding factors `(v, v_blinding)`,
@@ -38,11 +42,19 @@ pub struct Prover<'g, T: BorrowMut<Transcript>> {
 
     /// This list holds closures that will be called in the second phase of the protocol,
     /// when non-randomized variables are committed.
-    deferred_constraints:
-        Vec<Box<dyn FnOnce(&mut RandomizingProver<'g, T>) -> Result<(), R1CSError>>>,
+    deferred_constraints: Vec<DeferredConstraint<'g, T>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
+
+    /// Whether the blinding factors are sampled from the external RNG
+    /// alone, rather than from the transcript RNG rekeyed with the
+    /// witness.
+    witness_independent_rng: bool,
+
+    /// Whether builds with debug assertions check that the assignment
+    /// satisfies the constraints before proving.
+    check_witness: bool,
 }
 
 /// Separate struct to implement Drop trait for (for zeroing),
@@ -243,9 +255,10 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     ///
     /// The `bp_gens` and `pc_gens` are generators for Bulletproofs
     /// and for the Pedersen commitments, respectively.  The
-    /// [`BulletproofGens`] should have `gens_capacity` greater than
-    /// the number of multiplication constraints that will eventually
-    /// be added into the constraint system.
+    /// [`BulletproofGens`](crate::BulletproofGens) should have
+    /// `gens_capacity` greater than the number of multiplication
+    /// constraints that will eventually be added into the constraint
+    /// system.
     ///
     /// The `transcript` parameter is a Merlin proof transcript.  The
     /// `ProverCS` holds onto the `&mut Transcript` until it consumes
@@ -272,9 +285,35 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             constraints: Vec::new(),
             deferred_constraints: Vec::new(),
             pending_multiplier: None,
+            witness_independent_rng: false,
+            check_witness: true,
         }
     }
 
+    /// Disables the check that the assignment satisfies every
+    /// constraint, which builds with debug assertions make before
+    /// proving.
+    ///
+    /// A proof of an unsatisfied constraint system does not verify;
+    /// skipping the check allows creating one, for example to test
+    /// that a verifier rejects it.
+    pub fn skip_witness_check(&mut self) {
+        self.check_witness = false;
+    }
+
+    /// Samples the blinding factors of the proof from the external RNG
+    /// alone, so that proofs of distinct witnesses created with the same
+    /// seed share their randomness, as the leakage tests require.
+    ///
+    /// This is only available for tests and with the `test-utils`
+    /// feature: proofs of two witnesses with the same randomness reveal
+    /// the difference of the witnesses.
+    #[cfg(any(test, feature = "test-utils"))]
+    #[doc(hidden)]
+    pub fn use_witness_independent_rng(&mut self) {
+        self.witness_independent_rng = true;
+    }
+
     /// Creates commitment to a high-level variable and adds it to the transcript.
     ///
     /// # Inputs
@@ -371,13 +410,32 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             .sum()
     }
 
+    /// Returns the index of the first multiplication gate whose output
+    /// is not the product of its inputs, or of the first constraint
+    /// that does not evaluate to zero, counting gates first.
+    ///
+    /// This branches on the witness, so it is only called by the
+    /// debug assertions of proving.
+    fn unsatisfied_constraint(&self) -> Option<usize> {
+        let s = &self.secrets;
+        let gates = s.a_L.len();
+        (0..gates)
+            .find(|&i| s.a_L[i] * s.a_R[i] != s.a_O[i])
+            .or_else(|| {
+                self.constraints
+                    .iter()
+                    .position(|lc| self.eval(lc) != Scalar::ZERO)
+                    .map(|i| gates + i)
+            })
+    }
+
     /// Calls all remembered callbacks with an API that
     /// allows generating challenge scalars.
     fn create_randomized_constraints(mut self) -> Result<Self, R1CSError> {
         // Clear the pending multiplier (if any) because it was committed into A_L/A_R/S.
         self.pending_multiplier = None;
 
-        if self.deferred_constraints.len() == 0 {
+        if self.deferred_constraints.is_empty() {
             self.transcript.borrow_mut().r1cs_1phase_domain_sep();
             Ok(self)
         } else {
@@ -385,7 +443,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             // Note: the wrapper could've used &mut instead of ownership,
             // but specifying lifetimes for boxed closures is not going to be nice,
             // so we move the self into wrapper and then move it back out afterwards.
-            let mut callbacks = mem::replace(&mut self.deferred_constraints, Vec::new());
+            let mut callbacks = std::mem::take(&mut self.deferred_constraints);
             let mut wrapped_self = RandomizingProver { prover: self };
             for callback in callbacks.drain(..) {
                 callback(&mut wrapped_self)?;
@@ -395,18 +453,45 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
     }
 
     /// Consume this `ConstraintSystem` to produce a proof.
-    pub fn prove(self, bp_gens: &BulletproofGens) -> Result<R1CSProof, R1CSError> {
+    pub fn prove<G: AsGenerators + ?Sized>(self, bp_gens: &G) -> Result<R1CSProof, R1CSError> {
         self.prove_and_return_transcript(bp_gens)
             .map(|(proof, _transcript)| proof)
     }
 
+    /// Consume this `ConstraintSystem` to produce a proof, using the
+    /// provided RNG as the external randomness for the transcript RNG.
+    pub fn prove_with_rng<G: AsGenerators + ?Sized, R: RngCore + CryptoRng>(
+        self,
+        bp_gens: &G,
+        rng: &mut R,
+    ) -> Result<R1CSProof, R1CSError> {
+        self.prove_and_return_transcript_with_rng(bp_gens, rng)
+            .map(|(proof, _transcript)| proof)
+    }
+
     /// Consume this `ConstraintSystem` to produce a proof. Returns the proof and the transcript passed in `Prover::new`.
-    pub fn prove_and_return_transcript(
+    pub fn prove_and_return_transcript<G: AsGenerators + ?Sized>(
+        self,
+        bp_gens: &G,
+    ) -> Result<(R1CSProof, T), R1CSError> {
+        use rand::thread_rng;
+        self.prove_and_return_transcript_with_rng(bp_gens, &mut thread_rng())
+    }
+
+    /// Same as `prove_and_return_transcript`, but uses the provided RNG
+    /// as the external randomness for the transcript RNG.
+    pub fn prove_and_return_transcript_with_rng<
+        G: AsGenerators + ?Sized,
+        R: RngCore + CryptoRng,
+    >(
         mut self,
-        bp_gens: &BulletproofGens,
+        bp_gens: &G,
+        external_rng: &mut R,
     ) -> Result<(R1CSProof, T), R1CSError> {
+        let bp_gens = bp_gens.as_generators();
         use crate::util;
         use std::iter;
+        trace_phase!("r1cs.prove", m = self.secrets.v.len());
 
         // Commit a length _suffix_ for the number of high-level variables.
         // We cannot do this in advance because user can commit variables one-by-one,
@@ -414,7 +499,10 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         // is prefixed with a separate label.
         self.transcript
             .borrow_mut()
-            .append_u64(b"m", self.secrets.v.len() as u64);
+            .append_len(b"m", self.secrets.v.len());
+        bp_gens.append_fingerprint(self.transcript.borrow_mut());
+        self.pc_gens
+            .append_fingerprint(self.transcript.borrow_mut());
 
         // Create a `TranscriptRng` from the high-level witness data
         //
@@ -429,7 +517,12 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         // Since the v_blindings should be random scalars (in order to
         // protect the v's in the commitments), we don't gain much by
         // committing the v's as well as the v_blinding's.
-        let mut rng = {
+        let mut rng = if self.witness_independent_rng {
+            // A transcript without statement or witness data.
+            Transcript::new(b"WitnessIndependentRng")
+                .build_rng()
+                .finalize(external_rng)
+        } else {
             let mut builder = self.transcript.borrow_mut().build_rng();
 
             // Commit the blinding factors for the input wires
@@ -437,8 +530,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
                 builder = builder.rekey_with_witness_bytes(b"v_blinding", v_b.as_bytes());
             }
 
-            use rand::thread_rng;
-            builder.finalize(&mut thread_rng())
+            builder.finalize(external_rng)
         };
 
         // Commit to the first-phase low-level witness variables.
@@ -449,7 +541,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         }
 
         // We are performing a single-party circuit proof, so party index is 0.
-        let gens = bp_gens.share(0);
+        let gens = bp_gens.try_share(0)?;
 
         let i_blinding1 = Scalar::random(&mut rng);
         let o_blinding1 = Scalar::random(&mut rng);
@@ -464,15 +556,15 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
                 .chain(self.secrets.a_L.iter())
                 .chain(self.secrets.a_R.iter()),
             iter::once(&self.pc_gens.B_blinding)
-                .chain(gens.G(n1))
-                .chain(gens.H(n1)),
+                .chain(gens.G_checked(n1)?)
+                .chain(gens.H_checked(n1)?),
         )
         .compress();
 
         // A_O = <a_O, G> + o_blinding * B_blinding
         let A_O1 = RistrettoPoint::multiscalar_mul(
             iter::once(&o_blinding1).chain(self.secrets.a_O.iter()),
-            iter::once(&self.pc_gens.B_blinding).chain(gens.G(n1)),
+            iter::once(&self.pc_gens.B_blinding).chain(gens.G_checked(n1)?),
         )
         .compress();
 
@@ -482,8 +574,8 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
                 .chain(s_L1.iter())
                 .chain(s_R1.iter()),
             iter::once(&self.pc_gens.B_blinding)
-                .chain(gens.G(n1))
-                .chain(gens.H(n1)),
+                .chain(gens.G_checked(n1)?)
+                .chain(gens.H_checked(n1)?),
         )
         .compress();
 
@@ -491,14 +583,26 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         transcript.append_point(b"A_I1", &A_I1);
         transcript.append_point(b"A_O1", &A_O1);
         transcript.append_point(b"S1", &S1);
+        trace_event!(n1, "phase 1 committed");
 
         // Process the remaining constraints.
         self = self.create_randomized_constraints()?;
 
+        // Catch gadget and witness bugs here rather than as a proof
+        // that fails to verify.
+        if self.check_witness {
+            debug_assert_eq!(
+                self.unsatisfied_constraint(),
+                None,
+                "the assignment does not satisfy the constraint system"
+            );
+        }
+
         // Pad zeros to the next power of two (or do that implicitly when creating vectors)
 
         // If the number of multiplications is not 0 or a power of 2, then pad the circuit.
         let n = self.secrets.a_L.len();
+        check_vector_length(n)?;
         let n2 = n - n1;
         let padded_n = self.secrets.a_L.len().next_power_of_two();
         let pad = padded_n - n;
@@ -532,14 +636,14 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
                         .chain(self.secrets.a_L.iter().skip(n1))
                         .chain(self.secrets.a_R.iter().skip(n1)),
                     iter::once(&self.pc_gens.B_blinding)
-                        .chain(gens.G(n).skip(n1))
-                        .chain(gens.H(n).skip(n1)),
+                        .chain(gens.G_checked(n)?.skip(n1))
+                        .chain(gens.H_checked(n)?.skip(n1)),
                 )
                 .compress(),
                 // A_O = <a_O, G> + o_blinding * B_blinding
                 RistrettoPoint::multiscalar_mul(
                     iter::once(&o_blinding2).chain(self.secrets.a_O.iter().skip(n1)),
-                    iter::once(&self.pc_gens.B_blinding).chain(gens.G(n).skip(n1)),
+                    iter::once(&self.pc_gens.B_blinding).chain(gens.G_checked(n)?.skip(n1)),
                 )
                 .compress(),
                 // S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
@@ -548,8 +652,8 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
                         .chain(s_L2.iter())
                         .chain(s_R2.iter()),
                     iter::once(&self.pc_gens.B_blinding)
-                        .chain(gens.G(n).skip(n1))
-                        .chain(gens.H(n).skip(n1)),
+                        .chain(gens.G_checked(n)?.skip(n1))
+                        .chain(gens.H_checked(n)?.skip(n1)),
                 )
                 .compress(),
             )
@@ -569,6 +673,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         transcript.append_point(b"A_I2", &A_I2);
         transcript.append_point(b"A_O2", &A_O2);
         transcript.append_point(b"S2", &S2);
+        trace_event!(n2, padded_n, "phase 2 committed");
 
         // 4. Compute blinded vector polynomials l(x) and r(x)
 
@@ -604,7 +709,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             // r_poly.3 = y^n * s_R
             r_poly.3[i] = exp_y * sr;
 
-            exp_y = exp_y * y; // y^i -> y^(i+1)
+            exp_y *= y; // y^i -> y^(i+1)
         }
 
         let t_poly = util::VecPoly3::special_inner_product(&l_poly, &r_poly);
@@ -630,6 +735,7 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
 
         let u = transcript.challenge_scalar(b"u");
         let x = transcript.challenge_scalar(b"x");
+        trace_event!("polynomial committed");
 
         // t_2_blinding = <z*z^Q, W_V * v_blinding>
         // in the t_x_blinding calculations, line 76.
@@ -657,9 +763,9 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         r_vec.append(&mut vec![Scalar::ZERO; pad]);
 
         // XXX this should refer to the notes to explain why this is correct
-        for i in n..padded_n {
-            r_vec[i] = -exp_y;
-            exp_y = exp_y * y; // y^i -> y^(i+1)
+        for r_i in &mut r_vec[n..padded_n] {
+            *r_i = -exp_y;
+            exp_y *= y; // y^i -> y^(i+1)
         }
 
         let i_blinding = i_blinding1 + u * i_blinding2;
@@ -676,9 +782,8 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
         let w = transcript.challenge_scalar(b"w");
         let Q = w * self.pc_gens.B;
 
-        let G_factors = iter::repeat(Scalar::ONE)
-            .take(n1)
-            .chain(iter::repeat(u).take(n2 + pad))
+        let G_factors = std::iter::repeat_n(Scalar::ONE, n1)
+            .chain(std::iter::repeat_n(u, n2 + pad))
             .collect::<Vec<_>>();
         let H_factors = exp_y_inv
             .into_iter()
@@ -691,8 +796,8 @@ impl<'g, T: BorrowMut<Transcript>> Prover<'g, T> {
             &Q,
             &G_factors,
             &H_factors,
-            gens.G(padded_n).cloned().collect(),
-            gens.H(padded_n).cloned().collect(),
+            gens.G_checked(padded_n)?.cloned().collect(),
+            gens.H_checked(padded_n)?.cloned().collect(),
             l_vec,
             r_vec,
         );
@@ -1,22 +1,26 @@
 #![allow(non_snake_case)]
 
 use core::borrow::BorrowMut;
-use core::mem;
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use curve25519_dalek::traits::VartimeMultiscalarMul;
 use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
 
 use super::{
-    ConstraintSystem, LinearCombination, R1CSProof, RandomizableConstraintSystem,
+    CompiledCircuit, ConstraintSystem, LinearCombination, R1CSProof, RandomizableConstraintSystem,
     RandomizedConstraintSystem, Variable,
 };
 
 use crate::errors::R1CSError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{AsGenerators, BulletproofGens, PedersenGens};
+use crate::inner_product_proof::check_vector_length;
 use crate::r1cs::Metrics;
 use crate::transcript::TranscriptProtocol;
 
+/// A callback adding randomized constraints to a [`Verifier`].
+type DeferredConstraint<T> = Box<dyn FnOnce(&mut RandomizingVerifier<T>) -> Result<(), R1CSError>>;
+
 /// A [`ConstraintSystem`] implementation for use by the verifier.
 ///
 /// The verifier adds high-level variable commitments to the transcript,
@@ -28,6 +32,9 @@ use crate::transcript::TranscriptProtocol;
 /// that instantiate the randomized constraints, and verifies the proof.
 pub struct Verifier<T: BorrowMut<Transcript>> {
     transcript: T,
+    /// Copy of the transcript as it was passed in, restored if
+    /// verification fails.
+    initial_transcript: Transcript,
     constraints: Vec<LinearCombination>,
 
     /// Records the number of low-level variables allocated in the
@@ -44,8 +51,7 @@ pub struct Verifier<T: BorrowMut<Transcript>> {
     /// when non-randomized variables are committed.
     /// After that, the option will flip to None and additional calls to `randomize_constraints`
     /// will invoke closures immediately.
-    deferred_constraints:
-        Vec<Box<dyn FnOnce(&mut RandomizingVerifier<T>) -> Result<(), R1CSError>>>,
+    deferred_constraints: Vec<DeferredConstraint<T>>,
 
     /// Index of a pending multiplier that's not fully assigned yet.
     pending_multiplier: Option<usize>,
@@ -201,6 +207,9 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     /// itself during [`VerifierCS::verify`], releasing its borrow of the
     /// transcript.  This ensures that the transcript cannot be
     /// altered except by the `VerifierCS` before proving is complete.
+    /// If verification fails, the transcript is restored to the state
+    /// it was in when passed to `new`, so that another proof can be
+    /// verified against it.
     ///
     /// The `commitments` parameter is a list of Pedersen commitments
     /// to the external variables for the constraint system.  All
@@ -217,10 +226,12 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     /// The second element is a list of [`Variable`]s corresponding to
     /// the external inputs, which can be used to form constraints.
     pub fn new(mut transcript: T) -> Self {
+        let initial_transcript = transcript.borrow_mut().clone();
         transcript.borrow_mut().r1cs_domain_sep();
 
         Verifier {
             transcript,
+            initial_transcript,
             num_vars: 0,
             V: Vec::new(),
             constraints: Vec::new(),
@@ -310,11 +321,13 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
 
     /// Calls all remembered callbacks with an API that
     /// allows generating challenge scalars.
+    ///
+    /// On failure the transcript is restored before the verifier is dropped.
     fn create_randomized_constraints(mut self) -> Result<Self, R1CSError> {
         // Clear the pending multiplier (if any) because it was committed into A_L/A_R/S.
         self.pending_multiplier = None;
 
-        if self.deferred_constraints.len() == 0 {
+        if self.deferred_constraints.is_empty() {
             self.transcript.borrow_mut().r1cs_1phase_domain_sep();
             Ok(self)
         } else {
@@ -322,10 +335,13 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             // Note: the wrapper could've used &mut instead of ownership,
             // but specifying lifetimes for boxed closures is not going to be nice,
             // so we move the self into wrapper and then move it back out afterwards.
-            let mut callbacks = mem::replace(&mut self.deferred_constraints, Vec::new());
+            let mut callbacks = std::mem::take(&mut self.deferred_constraints);
             let mut wrapped_self = RandomizingVerifier { verifier: self };
             for callback in callbacks.drain(..) {
-                callback(&mut wrapped_self)?;
+                if let Err(e) = callback(&mut wrapped_self) {
+                    wrapped_self.verifier.restore_transcript();
+                    return Err(e);
+                }
             }
             Ok(wrapped_self.verifier)
         }
@@ -337,41 +353,220 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
     /// [`BulletproofGens`] should have `gens_capacity` greater than
     /// the number of multiplication constraints that will eventually
     /// be added into the constraint system.
-    pub fn verify(
+    pub fn verify<G: AsGenerators + ?Sized>(
         self,
         proof: &R1CSProof,
         pc_gens: &PedersenGens,
-        bp_gens: &BulletproofGens,
+        bp_gens: &G,
     ) -> Result<(), R1CSError> {
         self.verify_and_return_transcript(proof, pc_gens, bp_gens)
             .map(|_| ())
     }
+
+    /// Same as `verify`, but uses the provided RNG as the external
+    /// randomness for the transcript RNG.
+    pub fn verify_with_rng<G: AsGenerators + ?Sized, R: RngCore + CryptoRng>(
+        self,
+        proof: &R1CSProof,
+        pc_gens: &PedersenGens,
+        bp_gens: &G,
+        rng: &mut R,
+    ) -> Result<(), R1CSError> {
+        self.verify_and_return_transcript_with_rng(proof, pc_gens, bp_gens, rng)
+            .map(|_| ())
+    }
+
     /// Same as `verify`, but also returns the transcript back to the user.
-    pub fn verify_and_return_transcript(
+    pub fn verify_and_return_transcript<G: AsGenerators + ?Sized>(
+        self,
+        proof: &R1CSProof,
+        pc_gens: &PedersenGens,
+        bp_gens: &G,
+    ) -> Result<T, R1CSError> {
+        use rand::thread_rng;
+        self.verify_and_return_transcript_with_rng(proof, pc_gens, bp_gens, &mut thread_rng())
+    }
+
+    /// Same as `verify_and_return_transcript`, but uses the provided RNG
+    /// as the external randomness for the transcript RNG.
+    pub fn verify_and_return_transcript_with_rng<
+        G: AsGenerators + ?Sized,
+        R: RngCore + CryptoRng,
+    >(
         mut self,
         proof: &R1CSProof,
         pc_gens: &PedersenGens,
-        bp_gens: &BulletproofGens,
+        bp_gens: &G,
+        external_rng: &mut R,
     ) -> Result<T, R1CSError> {
+        let bp_gens = bp_gens.as_generators();
+        trace_phase!("r1cs.verify", m = self.V.len());
+        let n1 = self.num_vars;
+        if let Err(e) = self.append_first_phase(proof, pc_gens, bp_gens) {
+            self.restore_transcript();
+            return Err(e);
+        }
+
+        // Process the remaining constraints.
+        self = self.create_randomized_constraints()?;
+
+        match self.verify_randomized(proof, pc_gens, bp_gens, n1, external_rng) {
+            Ok(()) => Ok(self.transcript),
+            Err(e) => {
+                self.restore_transcript();
+                Err(e)
+            }
+        }
+    }
+
+    /// Consume this `Verifier` and attempt to verify the supplied `proof`
+    /// against a [`CompiledCircuit`], without running the gadget code.
+    ///
+    /// The verifier must be freshly created: the `commitments` are
+    /// committed here, in order, and `public_inputs` are substituted
+    /// for the public inputs of the circuit.  The transcript must be
+    /// in the same state as for the uncompiled verification, so that
+    /// the same proofs are accepted.
+    ///
+    /// Returns [`R1CSError::GadgetError`] if the verifier already has
+    /// commitments or constraints, or if the number of commitments or
+    /// public inputs does not match the circuit.
+    pub fn verify_compiled<G: AsGenerators + ?Sized>(
+        self,
+        circuit: &CompiledCircuit,
+        public_inputs: &[Scalar],
+        proof: &R1CSProof,
+        commitments: &[CompressedRistretto],
+        pc_gens: &PedersenGens,
+        bp_gens: &G,
+    ) -> Result<(), R1CSError> {
+        use rand::thread_rng;
+        self.verify_compiled_with_rng(
+            circuit,
+            public_inputs,
+            proof,
+            commitments,
+            pc_gens,
+            bp_gens,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Same as `verify_compiled`, but uses the provided RNG as the
+    /// external randomness for the transcript RNG.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_compiled_with_rng<G: AsGenerators + ?Sized, R: RngCore + CryptoRng>(
+        mut self,
+        circuit: &CompiledCircuit,
+        public_inputs: &[Scalar],
+        proof: &R1CSProof,
+        commitments: &[CompressedRistretto],
+        pc_gens: &PedersenGens,
+        bp_gens: &G,
+        external_rng: &mut R,
+    ) -> Result<(), R1CSError> {
+        let bp_gens = bp_gens.as_generators();
+        trace_phase!("r1cs.verify", m = commitments.len());
+        let description = if !self.V.is_empty()
+            || self.num_vars != 0
+            || !self.constraints.is_empty()
+            || !self.deferred_constraints.is_empty()
+        {
+            Some("verifier already has commitments or constraints")
+        } else if commitments.len() != circuit.num_commitments() {
+            Some("wrong number of commitments for the compiled circuit")
+        } else if public_inputs.len() != circuit.num_public_inputs() {
+            Some("wrong number of public inputs for the compiled circuit")
+        } else {
+            None
+        };
+        if let Some(description) = description {
+            self.restore_transcript();
+            return Err(R1CSError::GadgetError {
+                description: description.into(),
+            });
+        }
+
+        for commitment in commitments {
+            self.commit(*commitment);
+        }
+        self.constraints = circuit.phase_one_constraints(public_inputs);
+        self.num_vars = circuit.phase_one_multipliers();
+
+        let n1 = self.num_vars;
+        if let Err(e) = self.append_first_phase(proof, pc_gens, bp_gens) {
+            self.restore_transcript();
+            return Err(e);
+        }
+
+        let transcript = self.transcript.borrow_mut();
+        if circuit.is_randomized() {
+            transcript.r1cs_2phase_domain_sep();
+            let challenges: Vec<Scalar> = circuit
+                .challenge_labels()
+                .iter()
+                .map(|label| transcript.challenge_scalar(label))
+                .collect();
+            let randomized = circuit.randomized_constraints(public_inputs, &challenges);
+            self.constraints.extend(randomized);
+            self.num_vars = circuit.multipliers();
+        } else {
+            transcript.r1cs_1phase_domain_sep();
+        }
+
+        match self.verify_randomized(proof, pc_gens, bp_gens, n1, external_rng) {
+            Ok(()) => Ok(()),
+            Err(e) => {
+                self.restore_transcript();
+                Err(e)
+            }
+        }
+    }
+
+    /// Appends the statement and the first-phase commitments of `proof`
+    /// to the transcript.
+    fn append_first_phase(
+        &mut self,
+        proof: &R1CSProof,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+    ) -> Result<(), R1CSError> {
         // Commit a length _suffix_ for the number of high-level variables.
         // We cannot do this in advance because user can commit variables one-by-one,
         // but this suffix provides safe disambiguation because each variable
         // is prefixed with a separate label.
         let transcript = self.transcript.borrow_mut();
-        transcript.append_u64(b"m", self.V.len() as u64);
+        transcript.append_len(b"m", self.V.len());
+        bp_gens.append_fingerprint(transcript);
+        pc_gens.append_fingerprint(transcript);
 
-        let n1 = self.num_vars;
         transcript.validate_and_append_point(b"A_I1", &proof.A_I1)?;
         transcript.validate_and_append_point(b"A_O1", &proof.A_O1)?;
         transcript.validate_and_append_point(b"S1", &proof.S1)?;
+        Ok(())
+    }
 
-        // Process the remaining constraints.
-        self = self.create_randomized_constraints()?;
+    /// Resets the transcript to the state it was in when passed to `new`,
+    /// so that a failed verification can be retried with another proof.
+    fn restore_transcript(&mut self) {
+        *self.transcript.borrow_mut() = self.initial_transcript.clone();
+    }
 
+    /// Checks the proof once all constraints, including the randomized
+    /// ones, have been added; `n1` is the number of first-phase multipliers.
+    fn verify_randomized<R: RngCore + CryptoRng>(
+        &mut self,
+        proof: &R1CSProof,
+        pc_gens: &PedersenGens,
+        bp_gens: &BulletproofGens,
+        n1: usize,
+        external_rng: &mut R,
+    ) -> Result<(), R1CSError> {
         let transcript = self.transcript.borrow_mut();
 
         // If the number of multiplications is not 0 or a power of 2, then pad the circuit.
         let n = self.num_vars;
+        check_vector_length(n)?;
         let n2 = n - n1;
         let padded_n = self.num_vars.next_power_of_two();
         let pad = padded_n - n;
@@ -384,7 +579,7 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             return Err(R1CSError::InvalidGeneratorsLength);
         }
         // We are performing a single-party circuit proof, so party index is 0.
-        let gens = bp_gens.share(0);
+        let gens = bp_gens.try_share(0)?;
 
         // These points are the identity in the 1-phase unrandomized case.
         transcript.append_point(b"A_I2", &proof.A_I2);
@@ -417,8 +612,8 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             .verification_scalars(padded_n, self.transcript.borrow_mut())
             .map_err(|_| R1CSError::VerificationError)?;
 
-        let a = proof.ipp_proof.a;
-        let b = proof.ipp_proof.b;
+        let a = proof.ipp_proof.a();
+        let b = proof.ipp_proof.b();
 
         let y_inv = y.invert();
         let y_inv_vec = util::exp_iter(y_inv)
@@ -428,14 +623,12 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             .into_iter()
             .zip(y_inv_vec.iter())
             .map(|(wRi, exp_y_inv)| wRi * exp_y_inv)
-            .chain(iter::repeat(Scalar::ZERO).take(pad))
+            .chain(std::iter::repeat_n(Scalar::ZERO, pad))
             .collect::<Vec<Scalar>>();
 
         let delta = inner_product(&yneg_wR[0..n], &wL);
 
-        let u_for_g = iter::repeat(Scalar::ONE)
-            .take(n1)
-            .chain(iter::repeat(u).take(n2 + pad));
+        let u_for_g = std::iter::repeat_n(Scalar::ONE, n1).chain(std::iter::repeat_n(u, n2 + pad));
         let u_for_h = u_for_g.clone();
 
         // define parameters for P check
@@ -449,8 +642,8 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             .iter()
             .zip(u_for_h)
             .zip(s.iter().rev().take(padded_n))
-            .zip(wL.into_iter().chain(iter::repeat(Scalar::ZERO).take(pad)))
-            .zip(wO.into_iter().chain(iter::repeat(Scalar::ZERO).take(pad)))
+            .zip(wL.into_iter().chain(std::iter::repeat_n(Scalar::ZERO, pad)))
+            .zip(wO.into_iter().chain(std::iter::repeat_n(Scalar::ZERO, pad)))
             .map(|((((y_inv_i, u_or_1), s_i_inv), wLi), wOi)| {
                 u_or_1 * (y_inv_i * (x * wLi + wOi - b * s_i_inv) - Scalar::ONE)
             });
@@ -458,12 +651,11 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
         // Create a `TranscriptRng` from the transcript. The verifier
         // has no witness data to commit, so this just mixes external
         // randomness into the existing transcript.
-        use rand::thread_rng;
         let mut rng = self
             .transcript
             .borrow_mut()
             .build_rng()
-            .finalize(&mut thread_rng());
+            .finalize(external_rng);
         let r = Scalar::random(&mut rng);
 
         let xx = x * x;
@@ -474,6 +666,7 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
         let T_scalars = [r * x, rxx * x, rxx * xx, rxx * xxx, rxx * xx * xx];
         let T_points = [proof.T_1, proof.T_3, proof.T_4, proof.T_5, proof.T_6];
 
+        trace_phase!("r1cs.msm", padded_n);
         let mega_check = RistrettoPoint::optional_multiscalar_mul(
             iter::once(x) // A_I1
                 .chain(iter::once(xx)) // A_O1
@@ -501,12 +694,12 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
                 .chain(T_points.iter().map(|T_i| T_i.decompress()))
                 .chain(iter::once(Some(pc_gens.B)))
                 .chain(iter::once(Some(pc_gens.B_blinding)))
-                .chain(gens.G(padded_n).map(|&G_i| Some(G_i)))
-                .chain(gens.H(padded_n).map(|&H_i| Some(H_i)))
-                .chain(proof.ipp_proof.L_vec.iter().map(|L_i| L_i.decompress()))
-                .chain(proof.ipp_proof.R_vec.iter().map(|R_i| R_i.decompress())),
+                .chain(gens.G_checked(padded_n)?.map(|&G_i| Some(G_i)))
+                .chain(gens.H_checked(padded_n)?.map(|&H_i| Some(H_i)))
+                .chain(proof.ipp_proof.L_vec().iter().map(|L_i| L_i.decompress()))
+                .chain(proof.ipp_proof.R_vec().iter().map(|R_i| R_i.decompress())),
         )
-        .ok_or_else(|| R1CSError::VerificationError)?;
+        .ok_or(R1CSError::VerificationError)?;
 
         use curve25519_dalek::traits::IsIdentity;
 
@@ -514,6 +707,6 @@ impl<T: BorrowMut<Transcript>> Verifier<T> {
             return Err(R1CSError::VerificationError);
         }
 
-        Ok(self.transcript)
+        Ok(())
     }
 }
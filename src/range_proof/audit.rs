@@ -0,0 +1,280 @@
+//! Challenge traces of range proof verification, for audits.
+
+extern crate alloc;
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use super::{BitSize, RangeProof};
+use crate::errors::ProofError;
+use crate::generators::{AsGenerators, PedersenGens};
+use crate::transcript::TranscriptOp;
+
+/// One challenge derived during verification.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditChallenge {
+    /// The transcript label of the challenge.
+    pub label: String,
+    /// The challenge scalar.
+    pub challenge: Scalar,
+}
+
+/// The challenges derived while verifying a range proof with
+/// [`RangeProof::verify_with_audit`], in order.
+///
+/// These are \\(y\\), \\(z\\), \\(x\\), and \\(w\\), the challenge
+/// \\(u\\) of each round of the inner-product argument, and finally the
+/// weight `c` with which the polynomial check is added to the
+/// inner-product check.  All of them are determined by the proof and
+/// the statement, so anyone holding those can check the trace with
+/// [`AuditTrace::recheck`].
+///
+/// With `serde`, the trace is a list of objects with the fields
+/// `label` and `challenge`, and can be stored as JSON.  Deserialization
+/// rejects scalars that are not canonical.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditTrace {
+    challenges: Vec<AuditChallenge>,
+}
+
+impl AuditTrace {
+    /// Collects the challenge scalars among the transcript operations
+    /// `ops`, followed by the weight `c`.
+    pub(super) fn new(ops: &[TranscriptOp], c: Scalar) -> Self {
+        let challenges = ops
+            .iter()
+            .filter_map(|op| match op {
+                TranscriptOp::Challenge { label, bytes } => Some(AuditChallenge {
+                    label: String::from_utf8_lossy(label).into_owned(),
+                    challenge: Scalar::from_bytes_mod_order_wide(
+                        bytes[..]
+                            .try_into()
+                            .expect("challenge scalars use 64 bytes"),
+                    ),
+                }),
+                TranscriptOp::Append { .. } => None,
+            })
+            .chain(core::iter::once(AuditChallenge {
+                label: String::from("c"),
+                challenge: c,
+            }))
+            .collect();
+        AuditTrace { challenges }
+    }
+
+    /// Returns the challenges in the order they were derived.
+    pub fn challenges(&self) -> &[AuditChallenge] {
+        &self.challenges
+    }
+
+    /// Verifies the `proof` for the statement again, from the state of
+    /// `transcript` before verification, and checks that it derives
+    /// exactly the challenges of this trace.
+    ///
+    /// Returns the error of [`RangeProof::verify_with_audit`] if the
+    /// proof does not verify, and [`ProofError::VerificationError`] if
+    /// it does but the trace differs.
+    pub fn recheck<G: AsGenerators + ?Sized>(
+        &self,
+        proof: &RangeProof,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        let trace = proof.verify_with_audit(
+            bp_gens,
+            pc_gens,
+            &mut transcript.clone(),
+            value_commitments,
+            n,
+        )?;
+        if trace == *self {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+}
+
+#[cfg(all(test, feature = "prover"))]
+mod tests {
+    use super::*;
+
+    use crate::generators::BulletproofGens;
+    use crate::range_proof::BitSize;
+    use crate::transcript::TranscriptProtocol;
+    use core::convert::TryFrom;
+
+    struct Statement {
+        bp_gens: BulletproofGens,
+        pc_gens: PedersenGens,
+        proof: RangeProof,
+        value_commitments: Vec<CompressedRistretto>,
+        n: BitSize,
+    }
+
+    fn statement(n: usize, m: usize) -> Statement {
+        let n = BitSize::try_from(n).unwrap();
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+        let values: Vec<u64> = (0..m as u64).map(|i| 3 * i + 1).collect();
+        let blindings: Vec<Scalar> = (0..m as u64).map(|i| Scalar::from(i + 7)).collect();
+        let (proof, value_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut Transcript::new(b"AuditTraceTest"),
+            &values,
+            &blindings,
+            n,
+        )
+        .unwrap();
+        Statement {
+            bp_gens,
+            pc_gens,
+            proof,
+            value_commitments,
+            n,
+        }
+    }
+
+    impl Statement {
+        fn audit(&self) -> Result<AuditTrace, ProofError> {
+            self.proof.verify_with_audit(
+                &self.bp_gens,
+                &self.pc_gens,
+                &mut Transcript::new(b"AuditTraceTest"),
+                &self.value_commitments,
+                self.n,
+            )
+        }
+
+        fn recheck(&self, trace: &AuditTrace) -> Result<(), ProofError> {
+            trace.recheck(
+                &self.proof,
+                &self.bp_gens,
+                &self.pc_gens,
+                &Transcript::new(b"AuditTraceTest"),
+                &self.value_commitments,
+                self.n,
+            )
+        }
+    }
+
+    #[test]
+    fn trace_has_the_challenges_of_verification() {
+        let statement = statement(16, 2);
+        let trace = statement.audit().unwrap();
+        let labels: Vec<&str> = trace
+            .challenges()
+            .iter()
+            .map(|challenge| challenge.label.as_str())
+            .collect();
+        assert_eq!(labels, ["y", "z", "x", "w", "u", "u", "u", "u", "u", "c"]);
+
+        // The challenges before the weight are those of the transcript
+        // trace, and the trace does not depend on any randomness.
+        let ops = statement
+            .proof
+            .transcript_trace(
+                &statement.bp_gens,
+                &statement.pc_gens,
+                &Transcript::new(b"AuditTraceTest"),
+                &statement.value_commitments,
+                statement.n,
+            )
+            .unwrap();
+        let c = trace.challenges().last().unwrap().challenge;
+        assert_eq!(AuditTrace::new(&ops, c), trace);
+        assert_eq!(statement.audit().unwrap(), trace);
+        statement.recheck(&trace).unwrap();
+    }
+
+    #[test]
+    fn transcript_matches_verification() {
+        let statement = statement(8, 1);
+        let mut audited = Transcript::new(b"AuditTraceTest");
+        statement
+            .proof
+            .verify_with_audit(
+                &statement.bp_gens,
+                &statement.pc_gens,
+                &mut audited,
+                &statement.value_commitments,
+                statement.n,
+            )
+            .unwrap();
+        let mut verified = Transcript::new(b"AuditTraceTest");
+        statement
+            .proof
+            .verify_multiple(
+                &statement.bp_gens,
+                &statement.pc_gens,
+                &mut verified,
+                &statement.value_commitments,
+                statement.n,
+            )
+            .unwrap();
+        assert_eq!(
+            audited.challenge_scalar(b"test"),
+            verified.challenge_scalar(b"test")
+        );
+    }
+
+    #[test]
+    fn trace_roundtrips_through_json() {
+        let statement = statement(8, 1);
+        let trace = statement.audit().unwrap();
+        let json = serde_json::to_string(&trace).unwrap();
+        let decoded: AuditTrace = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, trace);
+        statement.recheck(&decoded).unwrap();
+
+        // Challenges must be canonical scalars.
+        let mut value = serde_json::to_value(&trace).unwrap();
+        value["challenges"][0]["challenge"] = serde_json::json!([0xffu8; 32].to_vec());
+        assert!(serde_json::from_value::<AuditTrace>(value).is_err());
+    }
+
+    #[test]
+    fn tampered_traces_are_rejected() {
+        let statement = statement(8, 2);
+        let trace = statement.audit().unwrap();
+        let tampered: [fn(&mut Vec<AuditChallenge>); 5] = [
+            |challenges| challenges[0].challenge += Scalar::ONE,
+            |challenges| challenges.last_mut().unwrap().challenge = Scalar::ONE,
+            |challenges| challenges[4].label = String::from("x"),
+            |challenges| challenges.swap(4, 5),
+            |challenges| {
+                challenges.pop();
+            },
+        ];
+        for tamper in tampered.iter() {
+            let mut challenges = trace.challenges().to_vec();
+            tamper(&mut challenges);
+            let trace = AuditTrace { challenges };
+            assert_eq!(
+                statement.recheck(&trace),
+                Err(ProofError::VerificationError)
+            );
+        }
+
+        // Nor does a trace recheck against another statement.
+        let mut other = statement;
+        other.value_commitments.swap(0, 1);
+        assert!(other.recheck(&trace).is_err());
+    }
+
+    #[test]
+    fn invalid_proofs_have_no_trace() {
+        let mut statement = statement(8, 1);
+        statement.proof.t_x += Scalar::ONE;
+        assert!(statement.audit().is_err());
+    }
+}
@@ -0,0 +1,346 @@
+//! The `bit_size` module contains the typed bitsize of range proofs,
+//! and the deprecated range proof APIs that take it as a `usize`.
+
+#![allow(non_snake_case)]
+
+#[cfg(feature = "prover")]
+extern crate alloc;
+
+#[cfg(feature = "prover")]
+use alloc::vec::Vec;
+use core::convert::TryFrom;
+use core::fmt;
+
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "prover")]
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::{AsGenerators, PedersenGens};
+
+#[cfg(feature = "verifier")]
+use super::{DecompressedRangeProof, VerifiedRange};
+use super::{RangeProof, MAX_BITSIZE};
+
+/// The bitsize \\(n\\) of a range proof, which shows that each value
+/// lies in \\([0, 2^n)\\).
+///
+/// The range proof APIs take a `BitSize` rather than a `usize`, so that
+/// an unsupported bitsize such as \\(0\\), \\(63\\) or \\(65\\) is
+/// ruled out by the type.  Dynamic inputs are converted with
+/// [`BitSize::try_from`].
+///
+/// With `serde`, a bitsize is stored as its number of bits, and
+/// deserialization rejects unsupported values.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "usize", into = "usize")]
+pub enum BitSize {
+    /// Values in \\([0, 2^8)\\).
+    B8,
+    /// Values in \\([0, 2^{16})\\).
+    B16,
+    /// Values in \\([0, 2^{32})\\).
+    B32,
+    /// Values in \\([0, 2^{64})\\).
+    B64,
+}
+
+impl BitSize {
+    /// All supported bitsizes, in increasing order.
+    pub const ALL: [BitSize; 4] = [BitSize::B8, BitSize::B16, BitSize::B32, BitSize::B64];
+
+    /// Returns the number of bits.
+    pub const fn bits(self) -> usize {
+        match self {
+            BitSize::B8 => 8,
+            BitSize::B16 => 16,
+            BitSize::B32 => 32,
+            BitSize::B64 => 64,
+        }
+    }
+}
+
+impl TryFrom<usize> for BitSize {
+    type Error = ProofError;
+
+    /// Converts a number of bits into a `BitSize`.
+    ///
+    /// Returns [`ProofError::StatementTooLarge`] if `n` exceeds
+    /// [`MAX_BITSIZE`], and [`ProofError::InvalidBitsize`] if it is
+    /// not one of \\(8\\), \\(16\\), \\(32\\), \\(64\\).
+    fn try_from(n: usize) -> Result<BitSize, ProofError> {
+        if n > MAX_BITSIZE {
+            return Err(ProofError::StatementTooLarge {
+                requested: n,
+                max: MAX_BITSIZE,
+            });
+        }
+        BitSize::ALL
+            .iter()
+            .copied()
+            .find(|b| b.bits() == n)
+            .ok_or(ProofError::InvalidBitsize)
+    }
+}
+
+impl From<BitSize> for usize {
+    fn from(n: BitSize) -> usize {
+        n.bits()
+    }
+}
+
+impl fmt::Display for BitSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} bits", self.bits())
+    }
+}
+
+// Migration helpers for callers of the range proof APIs that took the
+// bitsize as a `usize`: those APIs now take a `BitSize` under the same
+// names, and these variants convert it with `BitSize::try_from`.
+
+impl RangeProof {
+    #[cfg(feature = "prover")]
+    /// Same as [`RangeProof::prove_single_with_rng`], with the bitsize
+    /// as a number of bits.
+    #[deprecated(note = "use `prove_single_with_rng` with a `BitSize`")]
+    pub fn prove_single_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let n = BitSize::try_from(n)?;
+        RangeProof::prove_single_with_rng(bp_gens, pc_gens, transcript, v, v_blinding, n, rng)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Same as [`RangeProof::prove_single`], with the bitsize as a
+    /// number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `prove_single` with a `BitSize`")]
+    pub fn prove_single_usize<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        v_blinding: &Scalar,
+        n: usize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let n = BitSize::try_from(n)?;
+        RangeProof::prove_single(bp_gens, pc_gens, transcript, v, v_blinding, n)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Same as [`RangeProof::prove_multiple_with_rng`], with the bitsize
+    /// as a number of bits.
+    #[deprecated(note = "use `prove_multiple_with_rng` with a `BitSize`")]
+    pub fn prove_multiple_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let n = BitSize::try_from(n)?;
+        RangeProof::prove_multiple_with_rng(bp_gens, pc_gens, transcript, values, blindings, n, rng)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Same as [`RangeProof::prove_multiple`], with the bitsize as a
+    /// number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `prove_multiple` with a `BitSize`")]
+    pub fn prove_multiple_usize<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: usize,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let n = BitSize::try_from(n)?;
+        RangeProof::prove_multiple(bp_gens, pc_gens, transcript, values, blindings, n)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Same as [`RangeProof::verify_single_with_rng`], with the bitsize
+    /// as a number of bits.
+    #[deprecated(note = "use `verify_single_with_rng` with a `BitSize`")]
+    pub fn verify_single_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<VerifiedRange, ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, rng)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Same as [`RangeProof::verify_single`], with the bitsize as a
+    /// number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `verify_single` with a `BitSize`")]
+    pub fn verify_single_usize<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+    ) -> Result<VerifiedRange, ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_single(bp_gens, pc_gens, transcript, V, n)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Same as [`RangeProof::verify_multiple_with_rng`], with the
+    /// bitsize as a number of bits.
+    #[deprecated(note = "use `verify_multiple_with_rng` with a `BitSize`")]
+    pub fn verify_multiple_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, value_commitments, n, rng)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Same as [`RangeProof::verify_multiple`], with the bitsize as a
+    /// number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `verify_multiple` with a `BitSize`")]
+    pub fn verify_multiple_usize<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_multiple(bp_gens, pc_gens, transcript, value_commitments, n)
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl DecompressedRangeProof {
+    /// Same as [`DecompressedRangeProof::verify_single_with_rng`], with
+    /// the bitsize as a number of bits.
+    #[deprecated(note = "use `verify_single_with_rng` with a `BitSize`")]
+    pub fn verify_single_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<VerifiedRange, ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, rng)
+    }
+
+    /// Same as [`DecompressedRangeProof::verify_single`], with the
+    /// bitsize as a number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `verify_single` with a `BitSize`")]
+    pub fn verify_single_usize<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: usize,
+    ) -> Result<VerifiedRange, ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_single(bp_gens, pc_gens, transcript, V, n)
+    }
+
+    /// Same as [`DecompressedRangeProof::verify_multiple_with_rng`],
+    /// with the bitsize as a number of bits.
+    #[deprecated(note = "use `verify_multiple_with_rng` with a `BitSize`")]
+    pub fn verify_multiple_usize_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, value_commitments, n, rng)
+    }
+
+    /// Same as [`DecompressedRangeProof::verify_multiple`], with the
+    /// bitsize as a number of bits.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    #[deprecated(note = "use `verify_multiple` with a `BitSize`")]
+    pub fn verify_multiple_usize<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let n = BitSize::try_from(n)?;
+        self.verify_multiple(bp_gens, pc_gens, transcript, value_commitments, n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn try_from_accepts_exactly_the_supported_bitsizes() {
+        for n in 0..=MAX_BITSIZE {
+            let expected = BitSize::ALL.iter().copied().find(|b| b.bits() == n);
+            assert_eq!(BitSize::try_from(n).ok(), expected);
+        }
+        assert_eq!(BitSize::try_from(0), Err(ProofError::InvalidBitsize));
+        assert_eq!(BitSize::try_from(63), Err(ProofError::InvalidBitsize));
+        for n in [MAX_BITSIZE + 1, 1 << 40, usize::MAX] {
+            assert_eq!(
+                BitSize::try_from(n),
+                Err(ProofError::StatementTooLarge {
+                    requested: n,
+                    max: MAX_BITSIZE,
+                })
+            );
+        }
+    }
+
+    #[test]
+    fn serde_uses_the_number_of_bits() {
+        for n in BitSize::ALL {
+            let json = serde_json::to_string(&n).unwrap();
+            assert_eq!(json, n.bits().to_string());
+            assert_eq!(serde_json::from_str::<BitSize>(&json).unwrap(), n);
+        }
+        assert!(serde_json::from_str::<BitSize>("63").is_err());
+        assert!(serde_json::from_str::<BitSize>("65").is_err());
+    }
+}
@@ -4,8 +4,6 @@
 //! For more explanation of how the `dealer`, `party`, and `messages` modules orchestrate the protocol execution, see
 //! [the API for the aggregated multiparty computation protocol](../aggregation/index.html#api-for-the-aggregated-multiparty-computation-protocol).
 
-use core::iter;
-
 extern crate alloc;
 
 use alloc::vec::Vec;
@@ -14,19 +12,19 @@ use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 
+use core::convert::TryFrom;
+
 use crate::errors::MPCError;
 use crate::generators::{BulletproofGens, PedersenGens};
 use crate::inner_product_proof;
-use crate::range_proof::RangeProof;
+use crate::range_proof::{BitSize, FactorSet, RangeProof, StandardFactors};
 use crate::transcript::TranscriptProtocol;
 
 use rand_core::{CryptoRng, RngCore};
 
+#[cfg(any(feature = "std", feature = "getrandom"))]
 use crate::util;
 
-#[cfg(feature = "std")]
-use rand::thread_rng;
-
 use super::messages::*;
 
 /// Used to construct a dealer for the aggregated rangeproof MPC protocol.
@@ -34,24 +32,27 @@ pub struct Dealer {}
 
 impl Dealer {
     /// Creates a new dealer coordinating `m` parties proving `n`-bit ranges.
+    ///
+    /// The number of parties `m` must be a nonzero power of two.
+    #[allow(clippy::new_ret_no_self)]
     pub fn new<'a, 'b>(
         bp_gens: &'b BulletproofGens,
         pc_gens: &'b PedersenGens,
         transcript: &'a mut Transcript,
-        n: usize,
+        n: BitSize,
         m: usize,
     ) -> Result<DealerAwaitingBitCommitments<'a, 'b>, MPCError> {
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(MPCError::InvalidBitsize);
-        }
         if !m.is_power_of_two() {
             return Err(MPCError::InvalidAggregation);
         }
-        if bp_gens.gens_capacity < n {
+        if bp_gens.gens_capacity < n.bits() {
             return Err(MPCError::InvalidGeneratorsLength);
         }
         if bp_gens.party_capacity < m {
-            return Err(MPCError::InvalidGeneratorsLength);
+            return Err(MPCError::InsufficientPartyCapacity {
+                required_parties: m,
+                available: bp_gens.party_capacity,
+            });
         }
 
         // At the end of the protocol, the dealer will attempt to
@@ -68,7 +69,9 @@ impl Dealer {
         // state.
         let initial_transcript = transcript.clone();
 
-        transcript.rangeproof_domain_sep(n as u64, m as u64);
+        transcript.rangeproof_domain_sep(n.bits(), m);
+        bp_gens.append_fingerprint(transcript);
+        pc_gens.append_fingerprint(transcript);
 
         Ok(DealerAwaitingBitCommitments {
             bp_gens,
@@ -79,6 +82,23 @@ impl Dealer {
             m,
         })
     }
+
+    /// Creates a new dealer coordinating `m` parties proving `n`-bit
+    /// ranges, with the bitsize as a number of bits.
+    ///
+    /// Returns [`MPCError::InvalidBitsize`] if `n` is not 8, 16, 32,
+    /// or 64.
+    #[deprecated(note = "use `new` with a `BitSize`")]
+    pub fn new_usize<'a, 'b>(
+        bp_gens: &'b BulletproofGens,
+        pc_gens: &'b PedersenGens,
+        transcript: &'a mut Transcript,
+        n: usize,
+        m: usize,
+    ) -> Result<DealerAwaitingBitCommitments<'a, 'b>, MPCError> {
+        let n = BitSize::try_from(n).map_err(|_| MPCError::InvalidBitsize)?;
+        Dealer::new(bp_gens, pc_gens, transcript, n, m)
+    }
 }
 
 /// A dealer waiting for the parties to send their [`BitCommitment`]s.
@@ -89,12 +109,16 @@ pub struct DealerAwaitingBitCommitments<'a, 'b> {
     /// The dealer keeps a copy of the initial transcript state, so
     /// that it can attempt to verify the aggregated proof at the end.
     initial_transcript: Transcript,
-    n: usize,
+    n: BitSize,
     m: usize,
 }
 
 impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
     /// Receive each party's [`BitCommitment`]s and compute the [`BitChallenge`].
+    ///
+    /// The commitment of the party at position `j` must be at index
+    /// `j`; otherwise, the dealer returns
+    /// [`MPCError::MismatchedPositions`].
     pub fn receive_bit_commitments(
         self,
         bit_commitments: Vec<BitCommitment>,
@@ -103,6 +127,18 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
             return Err(MPCError::WrongNumBitCommitments);
         }
 
+        // Each party must have committed at its place in the
+        // aggregation, so that no position is taken twice.
+        let bad_positions: Vec<usize> = bit_commitments
+            .iter()
+            .enumerate()
+            .filter(|(j, vc)| vc.j != *j)
+            .map(|(j, _)| j)
+            .collect();
+        if !bad_positions.is_empty() {
+            return Err(MPCError::MismatchedPositions { bad_positions });
+        }
+
         // Commit each V_j individually
         for vc in bit_commitments.iter() {
             self.transcript.append_point(b"V", &vc.V_j);
@@ -118,6 +154,7 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
         let y = self.transcript.challenge_scalar(b"y");
         let z = self.transcript.challenge_scalar(b"z");
         let bit_challenge = BitChallenge { y, z };
+        trace_event!(m = self.m, "bit challenge derived");
 
         Ok((
             DealerAwaitingPolyCommitments {
@@ -140,7 +177,7 @@ impl<'a, 'b> DealerAwaitingBitCommitments<'a, 'b> {
 /// A dealer which has sent the [`BitChallenge`] to the parties and
 /// is waiting for their [`PolyCommitment`]s.
 pub struct DealerAwaitingPolyCommitments<'a, 'b> {
-    n: usize,
+    n: BitSize,
     m: usize,
     transcript: &'a mut Transcript,
     initial_transcript: Transcript,
@@ -174,6 +211,7 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
 
         let x = self.transcript.challenge_scalar(b"x");
         let poly_challenge = PolyChallenge { x };
+        trace_event!(m = self.m, "poly challenge derived");
 
         Ok((
             DealerAwaitingProofShares {
@@ -195,13 +233,75 @@ impl<'a, 'b> DealerAwaitingPolyCommitments<'a, 'b> {
             poly_challenge,
         ))
     }
+
+    /// Aborts the aggregation, for instance because a party stopped
+    /// responding, and returns an [`MPCError::Aborted`] with the
+    /// `reason` to pass on to the remaining parties.
+    ///
+    /// The caller's transcript is restored to its state before
+    /// [`Dealer::new`], so it can be reused for another proof.
+    pub fn abort(self, reason: &str) -> MPCError {
+        *self.transcript = self.initial_transcript;
+        MPCError::Aborted {
+            reason: reason.into(),
+        }
+    }
+
+    /// Restarts the aggregation without the parties at the `missing`
+    /// positions, which did not send their [`PolyCommitment`]s.
+    ///
+    /// Since the statement changes, the aggregation starts over from
+    /// the caller's transcript as it was before [`Dealer::new`].  The
+    /// transcript is restored even if `missing` is invalid.
+    /// Returns a dealer awaiting bit commitments, together with the
+    /// old positions of the remaining parties in their new order: the
+    /// party previously at `positions[i]` must call
+    /// [`reassign_position`](super::party::PartyAwaitingPolyChallenge::reassign_position)
+    /// with the new position `i`.
+    ///
+    /// The number of parties in an aggregated proof must be a power of
+    /// two, so if fewer parties remain, the positions from
+    /// `positions.len()` onwards must be filled with parties proving
+    /// the value \\(0\\) with the blinding factor \\(0\\), whose value
+    /// commitments are the identity.  The proof is then verified
+    /// against the remaining value commitments followed by one
+    /// identity point per filler party.
+    pub fn proceed_without(
+        self,
+        missing: &[usize],
+    ) -> Result<(DealerAwaitingBitCommitments<'a, 'b>, Vec<usize>), MPCError> {
+        let m = self.m;
+        *self.transcript = self.initial_transcript;
+
+        let positions: Vec<usize> = (0..m).filter(|j| !missing.contains(j)).collect();
+        let mut sorted_missing = missing.to_vec();
+        sorted_missing.sort_unstable();
+        sorted_missing.dedup();
+        if positions.is_empty()
+            || sorted_missing.len() != missing.len()
+            || missing.iter().any(|&j| j >= m)
+        {
+            return Err(MPCError::InvalidMissingParties {
+                missing: missing.to_vec(),
+            });
+        }
+
+        let dealer = Dealer::new(
+            self.bp_gens,
+            self.pc_gens,
+            self.transcript,
+            self.n,
+            positions.len().next_power_of_two(),
+        )?;
+        Ok((dealer, positions))
+    }
 }
 
 /// A dealer which has sent the [`PolyChallenge`] to the parties and
 /// is waiting to aggregate their [`ProofShare`]s into a
 /// [`RangeProof`].
 pub struct DealerAwaitingProofShares<'a, 'b> {
-    n: usize,
+    n: BitSize,
     m: usize,
     transcript: &'a mut Transcript,
     initial_transcript: Transcript,
@@ -224,6 +324,7 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// just hands back the result) and `receive_shares` (which
     /// validates the proof shares.
     fn assemble_shares(&mut self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
+        trace_phase!("rangeproof.assemble_shares", m = self.m);
         if self.m != proof_shares.len() {
             return Err(MPCError::WrongNumProofShares);
         }
@@ -232,13 +333,13 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
         let mut bad_shares = Vec::<usize>::new(); // no allocations until we append
         for (j, share) in proof_shares.iter().enumerate() {
             share
-                .check_size(self.n, &self.bp_gens, j)
+                .check_size(self.n.bits(), self.bp_gens, j)
                 .unwrap_or_else(|_| {
                     bad_shares.push(j);
                 });
         }
 
-        if bad_shares.len() > 0 {
+        if !bad_shares.is_empty() {
             return Err(MPCError::MalformedProofShares { bad_shares });
         }
 
@@ -255,10 +356,9 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
         let w = self.transcript.challenge_scalar(b"w");
         let Q = w * self.pc_gens.B;
 
-        let G_factors: Vec<Scalar> = iter::repeat(Scalar::ONE).take(self.n * self.m).collect();
-        let H_factors: Vec<Scalar> = util::exp_iter(self.bit_challenge.y.invert())
-            .take(self.n * self.m)
-            .collect();
+        let factors = StandardFactors::new(&self.bit_challenge.y);
+        let G_factors: Vec<Scalar> = factors.G_factors(self.n.bits() * self.m).collect();
+        let H_factors: Vec<Scalar> = factors.H_factors(self.n.bits() * self.m).collect();
 
         let l_vec: Vec<Scalar> = proof_shares
             .iter()
@@ -274,8 +374,8 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
             &Q,
             &G_factors,
             &H_factors,
-            self.bp_gens.G(self.n, self.m).cloned().collect(),
-            self.bp_gens.H(self.n, self.m).cloned().collect(),
+            self.bp_gens.G(self.n.bits(), self.m).cloned().collect(),
+            self.bp_gens.H(self.n.bits(), self.m).cloned().collect(),
             l_vec,
             r_vec,
         );
@@ -298,9 +398,9 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     ///
     /// This is a convenience wrapper around receive_shares_with_rng
     ///
-    #[cfg(feature = "std")]
+    #[cfg(any(feature = "std", feature = "getrandom"))]
     pub fn receive_shares(self, proof_shares: &[ProofShare]) -> Result<RangeProof, MPCError> {
-        self.receive_shares_with_rng(proof_shares, &mut thread_rng())
+        self.receive_shares_with_rng(proof_shares, &mut util::default_rng())
     }
 
     /// Assemble the final aggregated [`RangeProof`] from the given
@@ -335,10 +435,10 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
         } else {
             // Proof verification failed. Now audit the parties:
             let mut bad_shares = Vec::new();
-            for j in 0..self.m {
-                match proof_shares[j].audit_share(
-                    &self.bp_gens,
-                    &self.pc_gens,
+            for (j, share) in proof_shares.iter().enumerate() {
+                match share.audit_share(
+                    self.bp_gens,
+                    self.pc_gens,
                     j,
                     &self.bit_commitments[j],
                     &self.bit_challenge,
@@ -367,10 +467,28 @@ impl<'a, 'b> DealerAwaitingProofShares<'a, 'b> {
     /// [`receive_shares`](DealerAwaitingProofShares::receive_shares),
     /// which validates that all shares are well-formed, or else
     /// detects which party(ies) submitted malformed shares.
+    ///
+    /// Builds with debug assertions still check that each share opens
+    /// the bit commitments, and panic if one does not: a trusted share
+    /// that fails this check comes from a bug in the party.
     pub fn receive_trusted_shares(
         mut self,
         proof_shares: &[ProofShare],
     ) -> Result<RangeProof, MPCError> {
-        self.assemble_shares(proof_shares)
+        let proof = self.assemble_shares(proof_shares)?;
+        debug_assert!(
+            proof_shares.iter().enumerate().all(|(j, share)| share
+                .audit_vectors(
+                    self.bp_gens,
+                    self.pc_gens,
+                    j,
+                    &self.bit_commitments[j],
+                    &self.bit_challenge,
+                    &self.poly_challenge,
+                )
+                .is_ok()),
+            "a trusted proof share does not open its bit commitments"
+        );
+        Ok(proof)
     }
 }
@@ -0,0 +1,82 @@
+//! The `factors` module contains the factors by which range proofs
+//! scale the generators of their inner-product proof.
+
+#![allow(non_snake_case)]
+
+use core::iter;
+
+use curve25519_dalek::scalar::Scalar;
+
+use crate::util;
+
+/// The factors \\(\mathbf{g}\\) and \\(\mathbf{h}\\) of the generators
+/// \\(G'\_i = g\_i G\_i\\) and \\(H'\_i = h\_i H\_i\\) over which a range
+/// proof runs its inner-product proof.
+///
+/// The prover passes the factors to the inner-product proof, and the
+/// verifier folds them into the scalars of \\(\mathbf{G}\\) and
+/// \\(\mathbf{H}\\) in the verification equation, so both sides must
+/// use the same set. The factors are produced lazily, so that the
+/// verifier can fold them in without allocating.
+pub(crate) trait FactorSet {
+    /// The iterator over the factors \\(g\_i\\).
+    type GFactors: Iterator<Item = Scalar>;
+
+    /// The iterator over the factors \\(h\_i\\).
+    type HFactors: Iterator<Item = Scalar>;
+
+    /// Returns the first `len` factors \\(g\_i\\).
+    fn G_factors(&self, len: usize) -> Self::GFactors;
+
+    /// Returns the first `len` factors \\(h\_i\\).
+    fn H_factors(&self, len: usize) -> Self::HFactors;
+}
+
+/// The factors of the range proof protocol, \\(g\_i = 1\\) and
+/// \\(h\_i = y^{-i}\\) for the challenge \\(y\\).
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct StandardFactors {
+    y_inv: Scalar,
+}
+
+impl StandardFactors {
+    /// Returns the factors for the challenge `y`.
+    pub(crate) fn new(y: &Scalar) -> Self {
+        StandardFactors { y_inv: y.invert() }
+    }
+}
+
+impl FactorSet for StandardFactors {
+    type GFactors = iter::RepeatN<Scalar>;
+    type HFactors = iter::Take<util::ScalarExp>;
+
+    fn G_factors(&self, len: usize) -> Self::GFactors {
+        iter::repeat_n(Scalar::ONE, len)
+    }
+
+    fn H_factors(&self, len: usize) -> Self::HFactors {
+        util::exp_iter(self.y_inv).take(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn standard_factors_match_definition() {
+        let y = Scalar::random(&mut rand::thread_rng());
+        let factors = StandardFactors::new(&y);
+        assert_eq!(
+            factors.G_factors(16).collect::<Vec<_>>(),
+            vec![Scalar::ONE; 16]
+        );
+        let H_factors: Vec<Scalar> = factors.H_factors(16).collect();
+        assert_eq!(H_factors.len(), 16);
+        for (i, h_i) in H_factors.iter().enumerate() {
+            assert_eq!(h_i * util::scalar_exp_vartime(&y, i as u64), Scalar::ONE);
+        }
+        assert_eq!(factors.G_factors(0).count(), 0);
+        assert_eq!(factors.H_factors(0).count(), 0);
+    }
+}
@@ -17,11 +17,20 @@ use crate::generators::{BulletproofGens, PedersenGens};
 /// A commitment to the bits of a party's value.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct BitCommitment {
+    /// The position the party was assigned.
+    pub(super) j: usize,
     pub(super) V_j: CompressedRistretto,
     pub(super) A_j: RistrettoPoint,
     pub(super) S_j: RistrettoPoint,
 }
 
+impl BitCommitment {
+    /// Returns the position in the aggregation the party was assigned.
+    pub fn position(&self) -> usize {
+        self.j
+    }
+}
+
 /// Challenge values derived from all parties' [`BitCommitment`]s.
 #[derive(Serialize, Deserialize, Copy, Clone, Debug)]
 pub struct BitChallenge {
@@ -82,6 +91,7 @@ impl ProofShare {
 
     /// Audit an individual proof share to determine whether it is
     /// malformed.
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn audit_share(
         &self,
         bp_gens: &BulletproofGens,
@@ -91,15 +101,74 @@ impl ProofShare {
         bit_challenge: &BitChallenge,
         poly_commitment: &PolyCommitment,
         poly_challenge: &PolyChallenge,
+    ) -> Result<(), ()> {
+        use crate::util;
+        use group::Group;
+
+        self.audit_vectors(
+            bp_gens,
+            pc_gens,
+            j,
+            bit_commitment,
+            bit_challenge,
+            poly_challenge,
+        )?;
+
+        let n = self.l_vec.len();
+        let (y, z) = (&bit_challenge.y, &bit_challenge.z);
+        let x = &poly_challenge.x;
+        let zz = z * z;
+        let z_j = util::scalar_exp_vartime(z, j as u64); // z^j
+        let y_jn = util::scalar_exp_vartime(y, (j * n) as u64); // y^(j*n)
+
+        let V_j = bit_commitment.V_j.decompress().ok_or(())?;
+
+        let sum_y = util::geometric_sum(y, n);
+        let sum_2 = util::geometric_sum(&Scalar::from(2u64), n);
+        let delta = (z - zz) * sum_y * y_jn - z * zz * sum_2 * z_j;
+        let t_check = RistrettoPoint::vartime_multiscalar_mul(
+            iter::once(zz * z_j)
+                .chain(iter::once(*x))
+                .chain(iter::once(x * x))
+                .chain(iter::once(delta - self.t_x))
+                .chain(iter::once(-self.t_x_blinding)),
+            iter::once(&V_j)
+                .chain(iter::once(&poly_commitment.T_1_j))
+                .chain(iter::once(&poly_commitment.T_2_j))
+                .chain(iter::once(&pc_gens.B))
+                .chain(iter::once(&pc_gens.B_blinding)),
+        );
+
+        if t_check.is_identity().into() {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    /// Audits the part of a proof share that does not depend on the
+    /// value: that \\(t(x) = \langle \mathbf{l}(x), \mathbf{r}(x)
+    /// \rangle\\), and that the vectors and `e_blinding` open
+    /// \\(A + xS\\).
+    ///
+    /// A share passes this audit even if the value is out of range.
+    pub(super) fn audit_vectors(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        j: usize,
+        bit_commitment: &BitCommitment,
+        bit_challenge: &BitChallenge,
+        poly_challenge: &PolyChallenge,
     ) -> Result<(), ()> {
         use crate::inner_product_proof::inner_product;
         use crate::util;
-        use core::ops::Not;
         use group::Group;
 
         let n = self.l_vec.len();
 
         self.check_size(n, bp_gens, j)?;
+        let bp_share = bp_gens.try_share(j).map_err(|_| ())?;
 
         let (y, z) = (&bit_challenge.y, &bit_challenge.z);
         let x = &poly_challenge.x;
@@ -108,8 +177,6 @@ impl ProofShare {
         let zz = z * z;
         let minus_z = -z;
         let z_j = util::scalar_exp_vartime(z, j as u64); // z^j
-        let y_jn = util::scalar_exp_vartime(y, (j * n) as u64); // y^(j*n)
-        let y_jn_inv = y_jn.invert(); // y^(-j*n)
         let y_inv = y.invert(); // y^(-1)
 
         if self.t_x != inner_product(&self.l_vec, &self.r_vec) {
@@ -121,10 +188,8 @@ impl ProofShare {
             .r_vec
             .iter()
             .zip(util::exp_iter(Scalar::from(2u64)))
-            .zip(util::exp_iter(y_inv))
-            .map(|((r_i, exp_2), exp_y_inv)| {
-                z + exp_y_inv * y_jn_inv * (-r_i) + exp_y_inv * y_jn_inv * (zz * z_j * exp_2)
-            });
+            .zip(util::exp_iter_from(y_inv, (j * n) as u64))
+            .map(|((r_i, exp_2), exp_y_inv)| z + exp_y_inv * (zz * z_j * exp_2 - r_i));
 
         let P_check = RistrettoPoint::vartime_multiscalar_mul(
             iter::once(Scalar::ONE)
@@ -135,32 +200,10 @@ impl ProofShare {
             iter::once(&bit_commitment.A_j)
                 .chain(iter::once(&bit_commitment.S_j))
                 .chain(iter::once(&pc_gens.B_blinding))
-                .chain(bp_gens.share(j).G(n))
-                .chain(bp_gens.share(j).H(n)),
-        );
-        if P_check.is_identity().not().into() {
-            return Err(());
-        }
-
-        let V_j = bit_commitment.V_j.decompress().ok_or(())?;
-
-        let sum_of_powers_y = util::sum_of_powers(&y, n);
-        let sum_of_powers_2 = util::sum_of_powers(&Scalar::from(2u64), n);
-        let delta = (z - zz) * sum_of_powers_y * y_jn - z * zz * sum_of_powers_2 * z_j;
-        let t_check = RistrettoPoint::vartime_multiscalar_mul(
-            iter::once(zz * z_j)
-                .chain(iter::once(*x))
-                .chain(iter::once(x * x))
-                .chain(iter::once(delta - self.t_x))
-                .chain(iter::once(-self.t_x_blinding)),
-            iter::once(&V_j)
-                .chain(iter::once(&poly_commitment.T_1_j))
-                .chain(iter::once(&poly_commitment.T_2_j))
-                .chain(iter::once(&pc_gens.B))
-                .chain(iter::once(&pc_gens.B_blinding)),
+                .chain(bp_share.G_checked(n).map_err(|_| ())?)
+                .chain(bp_share.H_checked(n).map_err(|_| ())?),
         );
-
-        if t_check.is_identity().into() {
+        if P_check.is_identity().into() {
             Ok(())
         } else {
             Err(())
@@ -5,31 +5,110 @@ extern crate alloc;
 #[cfg(feature = "std")]
 extern crate rand;
 
-#[cfg(feature = "std")]
-use self::rand::thread_rng;
+use alloc::string::String;
 use alloc::vec::Vec;
 
+#[cfg(feature = "prover")]
+use clear_on_drop::clear::Clear;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+#[cfg(feature = "verifier")]
 use core::iter;
+#[cfg(feature = "verifier")]
+use core::mem;
+#[cfg(feature = "verifier")]
+use core::ops::Range;
 
-use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use digest::Digest;
+#[cfg(feature = "verifier")]
 use merlin::Transcript;
+use sha3::Sha3_256;
+#[cfg(feature = "verifier")]
+use subtle::{Choice, ConstantTimeEq};
 
+#[cfg(feature = "verifier")]
+use crate::consts::SUPPORTED_RANGE_BITS;
+use crate::consts::{ELEMENT_BYTES, IPP_FINAL_ELEMENTS, RANGE_HEADER_ELEMENTS};
 use crate::errors::ProofError;
-use crate::generators::{BulletproofGens, PedersenGens};
-use crate::inner_product_proof::InnerProductProof;
-use crate::transcript::TranscriptProtocol;
+#[cfg(feature = "verifier")]
+use crate::generators::{AsGenerators, BulletproofGens, PedersenGens};
+#[cfg(feature = "verifier")]
+use crate::inner_product_proof::check_vector_length;
+use crate::inner_product_proof::{InnerProductProof, MAX_VECTOR_LENGTH};
+use crate::leakage::NoWitnessDebug;
+#[cfg(feature = "verifier")]
+use crate::transcript::{
+    StatementSession, TranscriptOp, TranscriptProtocol, TranscriptRecorder, TranscriptVersion,
+};
 use crate::util;
+#[cfg(feature = "prover")]
+use crate::view_key::ViewKey;
 
+#[cfg(feature = "verifier")]
 use rand_core::{CryptoRng, RngCore};
 use serde::de::Visitor;
 use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
 
+/// The largest bitsize supported by range proofs.
+pub const MAX_BITSIZE: usize = 64;
+
+/// The size in bytes of the largest range proof supported by the
+/// implementation, which proves [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH)
+/// bits in total.
+///
+/// No proof serializes to more bytes, and
+/// [`RangeProof::from_bytes`] rejects longer inputs before parsing
+/// them, so a fixed buffer of this size holds any proof.  Protocols
+/// that bound the aggregation size can use
+/// [`RangeProof::size_const`] for a tighter bound, and both can be
+/// checked at compile time:
+///
+/// ```
+/// use bulletproofs::{RangeProof, MAX_RANGE_PROOF_SIZE};
+///
+/// const PROOF_BUFFER: usize = 2304;
+/// const _: () = assert!(MAX_RANGE_PROOF_SIZE <= PROOF_BUFFER);
+/// const _: () = assert!(RangeProof::size_const(64, 16) <= MAX_RANGE_PROOF_SIZE);
+/// ```
+pub const MAX_RANGE_PROOF_SIZE: usize =
+    RangeProof::size_const(MAX_BITSIZE, MAX_VECTOR_LENGTH / MAX_BITSIZE);
+
 // Modules for MPC protocol
 
+#[cfg(feature = "prover")]
 pub mod dealer;
+#[cfg(feature = "prover")]
 pub mod messages;
+#[cfg(feature = "prover")]
 pub mod party;
+#[cfg(feature = "prover")]
+mod precomp;
+#[cfg(all(test, feature = "prover"))]
+mod reference;
+#[cfg(feature = "prover")]
+mod resumable_rng;
+#[cfg(feature = "prover")]
+mod statement;
+
+#[cfg(feature = "verifier")]
+mod audit;
+mod bit_size;
+#[cfg(feature = "verifier")]
+mod factors;
+
+#[cfg(feature = "verifier")]
+pub use self::audit::{AuditChallenge, AuditTrace};
+pub use self::bit_size::BitSize;
+#[cfg(feature = "verifier")]
+pub(crate) use self::factors::{FactorSet, StandardFactors};
+#[cfg(feature = "prover")]
+pub use self::precomp::RangeProverPrecomp;
+#[cfg(feature = "prover")]
+pub use self::statement::{RangeStatement, RangeStatementBuilder};
 
 /// The `RangeProof` struct represents a proof that one or more values
 /// are in a range.
@@ -54,7 +133,45 @@ pub mod party;
 /// protocol locally.  That API is exposed in the [`aggregation`](::range_proof_mpc)
 /// module and can be used to perform online aggregation between
 /// parties without revealing secret values to each other.
-#[derive(Clone, Debug)]
+///
+/// Equality and hashing are bytewise over the canonical encoding
+/// returned by [`RangeProof::to_bytes`], so a proof equals its
+/// deserialized copy.
+///
+/// # Serialization
+///
+/// [`RangeProof::to_bytes`] and [`RangeProof::from_bytes`] define the
+/// encoding of a proof, and are the interface to use for storing or
+/// sending it.  With `serde`, a proof is serialized as a byte string
+/// holding that encoding, and the framing around it is up to the
+/// format.  The supported formats are bincode 1 with its default
+/// options, and bincode 2 with the `standard` or `legacy`
+/// configuration, with fixed or variable integer encoding and either
+/// byte order; bincode 2's `legacy` configuration gives the bytes of
+/// bincode 1.  Formats without a byte string type, such as JSON, are
+/// not supported: use [`ProofEnvelope`](crate::ProofEnvelope) there.
+///
+/// # Re-randomized commitments
+///
+/// A proof cannot be updated to verify against a re-randomized
+/// commitment \\(V' = V + r \tilde{B}\\).  For fixed challenges
+/// it could be: the value commitment \\(V\_j\\) only enters the check
+/// \\(t(x) B + \tilde{t}(x) \tilde{B} = \delta(y, z) B + \sum\_j z^{2+j} V\_j + x T\_1 + x^2 T\_2\\),
+/// so adding \\(z^{2+j} r\\) to \\(\tilde{t}(x)\\) would absorb
+/// the change.  But the commitments are appended to the transcript
+/// before any challenge is derived, so \\(V'\\) gives different
+/// challenges \\(y, z, x\\), and the other terms of the proof no longer
+/// match them.  This binding is what stops a proof from being reused
+/// for a commitment it was not created for, so it cannot be lifted
+/// without breaking soundness.
+///
+/// A party that re-randomizes a commitment must instead create a new
+/// proof, with the blinding factor \\(\tilde{v} + r\\), which
+/// requires the value.  A party that only knows \\(r\\) can
+/// show that \\(V\\) and \\(V'\\) commit to the same value with a
+/// [`BalanceProof`](crate::BalanceProof) from the blinding factors
+/// \\(0\\) and \\(r\\), which reveals the link between them.
+#[derive(Clone)]
 pub struct RangeProof {
     /// Commitment to the bits of the value
     A: CompressedRistretto,
@@ -75,10 +192,15 @@ pub struct RangeProof {
 }
 
 impl RangeProof {
+    #[cfg(feature = "prover")]
     /// Create a rangeproof for a given pair of value `v` and
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_multiple`].
     ///
+    /// The value `v` must be less than \\(2^n\\).  The value \\(0\\)
+    /// and a zero `v_blinding` are allowed, in which case the commitment
+    /// is the identity.
+    ///
     /// # Example
     /// ```
     /// extern crate rand;
@@ -91,7 +213,7 @@ impl RangeProof {
     /// use merlin::Transcript;
     ///
     /// extern crate bulletproofs;
-    /// use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+    /// use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof};
     ///
     /// # fn main() {
     /// // Generators for Pedersen commitments.  These can be selected
@@ -119,25 +241,25 @@ impl RangeProof {
     ///     &mut prover_transcript,
     ///     secret_value,
     ///     &blinding,
-    ///     32,
+    ///     BitSize::B32,
     /// ).expect("A real program could handle errors");
     ///
     /// // Verification requires a transcript with identical initial state:
     /// let mut verifier_transcript = Transcript::new(b"doctest example");
     /// assert!(
     ///     proof
-    ///         .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, 32)
+    ///         .verify_single(&bp_gens, &pc_gens, &mut verifier_transcript, &committed_value, BitSize::B32)
     ///         .is_ok()
     /// );
     /// # }
     /// ```
-    pub fn prove_single_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
+    pub fn prove_single_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         v: u64,
         v_blinding: &Scalar,
-        n: usize,
+        n: BitSize,
         rng: &mut T,
     ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
         let (p, Vs) = RangeProof::prove_multiple_with_rng(
@@ -152,18 +274,19 @@ impl RangeProof {
         Ok((p, Vs[0]))
     }
 
+    #[cfg(feature = "prover")]
     /// Create a rangeproof for a given pair of value `v` and
     /// blinding scalar `v_blinding`.
     /// This is a convenience wrapper around [`RangeProof::prove_single_with_rng`],
     /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn prove_single(
-        bp_gens: &BulletproofGens,
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_single<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         v: u64,
         v_blinding: &Scalar,
-        n: usize,
+        n: BitSize,
     ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
         RangeProof::prove_single_with_rng(
             bp_gens,
@@ -172,12 +295,230 @@ impl RangeProof {
             v,
             v_blinding,
             n,
-            &mut thread_rng(),
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a value `v`, committed with the blinding
+    /// factor derived from `view_key` for the commitment `index`.
+    ///
+    /// Holders of the view key can later check the opening of the
+    /// returned commitment with [`ViewKey::verify_opening`].
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_with_view_key_and_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        view_key: &ViewKey,
+        index: u64,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let v_blinding = view_key.derive_blinding(index);
+        RangeProof::prove_single_with_rng(bp_gens, pc_gens, transcript, v, &v_blinding, n, rng)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a value `v`, committed with the blinding
+    /// factor derived from `view_key` for the commitment `index`.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_with_view_key_and_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_single_with_view_key<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        view_key: &ViewKey,
+        index: u64,
+        n: BitSize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_with_view_key_and_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            view_key,
+            index,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a value `v`, committed with the blinding
+    /// factor returned by `derive_blinding`, such as
+    /// [`blinding::derive`](crate::blinding::derive).
+    ///
+    /// The blinding factor is cleared once the proof is created, so a
+    /// caller that derives it from a seed never holds it.
+    pub fn prove_single_with_derived_blinding_and_rng<G, F, T>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        derive_blinding: F,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError>
+    where
+        G: AsGenerators + ?Sized,
+        F: FnOnce() -> Scalar,
+        T: RngCore + CryptoRng,
+    {
+        let mut v_blinding = derive_blinding();
+        let result =
+            RangeProof::prove_single_with_rng(bp_gens, pc_gens, transcript, v, &v_blinding, n, rng);
+        v_blinding.clear();
+        result
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a value `v`, committed with the blinding
+    /// factor returned by `derive_blinding`.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_with_derived_blinding_and_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_single_with_derived_blinding<G, F>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        v: u64,
+        derive_blinding: F,
+        n: BitSize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError>
+    where
+        G: AsGenerators + ?Sized,
+        F: FnOnce() -> Scalar,
+    {
+        RangeProof::prove_single_with_derived_blinding_and_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            v,
+            derive_blinding,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, bound to the `associated_data`.
+    ///
+    /// The transcript is created internally from `label`, and the
+    /// associated data is appended with its length before the range
+    /// proof domain separator, so the proof only verifies with
+    /// [`RangeProof::verify_single_with_ad`] given the same `label`
+    /// and `associated_data`.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_single_with_ad_and_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        label: &'static [u8],
+        associated_data: &[u8],
+        v: u64,
+        v_blinding: &Scalar,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        let mut transcript = Transcript::new(label);
+        transcript.append_associated_data(associated_data);
+        RangeProof::prove_single_with_rng(bp_gens, pc_gens, &mut transcript, v, v_blinding, n, rng)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a given pair of value `v` and
+    /// blinding scalar `v_blinding`, bound to the `associated_data`.
+    /// This is a convenience wrapper around [`RangeProof::prove_single_with_ad_and_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_single_with_ad<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        label: &'static [u8],
+        associated_data: &[u8],
+        v: u64,
+        v_blinding: &Scalar,
+        n: BitSize,
+    ) -> Result<(RangeProof, CompressedRistretto), ProofError> {
+        RangeProof::prove_single_with_ad_and_rng(
+            bp_gens,
+            pc_gens,
+            label,
+            associated_data,
+            v,
+            v_blinding,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a given pair of value `v` and blinding
+    /// scalar `v_blinding`, on the next branch of `session`, for its
+    /// `index`-th commitment.
+    ///
+    /// The proof only verifies with [`RangeProof::verify_in_session`]
+    /// on the branch in the same position of the same session, and if
+    /// the commitment opens to `v` and `v_blinding`, which the prover
+    /// does not check.  Returns [`ProofError::InvalidInputLength`] if
+    /// the session has no `index`-th commitment, without taking a
+    /// branch.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_in_session_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        v: u64,
+        v_blinding: &Scalar,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<RangeProof, ProofError> {
+        session.commitment(index)?;
+        let mut transcript = session.branch(b"range-proof");
+        transcript.append_len(b"index", index);
+        RangeProof::prove_single_with_rng(bp_gens, pc_gens, &mut transcript, v, v_blinding, n, rng)
+            .map(|(proof, _)| proof)
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a given pair of value `v` and blinding
+    /// scalar `v_blinding`, on the next branch of `session`.
+    /// This is a convenience wrapper around [`RangeProof::prove_in_session_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_in_session<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        v: u64,
+        v_blinding: &Scalar,
+        n: BitSize,
+    ) -> Result<RangeProof, ProofError> {
+        RangeProof::prove_in_session_with_rng(
+            bp_gens,
+            pc_gens,
+            session,
+            index,
+            v,
+            v_blinding,
+            n,
+            &mut util::default_rng(),
         )
     }
 
+    #[cfg(feature = "prover")]
     /// Create a rangeproof for a set of values.
     ///
+    /// The number of values \\(m\\) must be a nonzero power of two;
+    /// otherwise an [`InvalidAggregation`](ProofError::InvalidAggregation)
+    /// error is returned.
+    ///
     /// # Example
     /// ```
     /// extern crate rand;
@@ -190,7 +531,7 @@ impl RangeProof {
     /// use merlin::Transcript;
     ///
     /// extern crate bulletproofs;
-    /// use bulletproofs::{BulletproofGens, PedersenGens, RangeProof};
+    /// use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof};
     ///
     /// # fn main() {
     /// // Generators for Pedersen commitments.  These can be selected
@@ -218,30 +559,34 @@ impl RangeProof {
     ///     &mut prover_transcript,
     ///     &secrets,
     ///     &blindings,
-    ///     32,
+    ///     BitSize::B32,
     /// ).expect("A real program could handle errors");
     ///
     /// // Verification requires a transcript with identical initial state:
     /// let mut verifier_transcript = Transcript::new(b"doctest example");
     /// assert!(
     ///     proof
-    ///         .verify_multiple(&bp_gens, &pc_gens, &mut verifier_transcript, &commitments, 32)
+    ///         .verify_multiple(&bp_gens, &pc_gens, &mut verifier_transcript, &commitments, BitSize::B32)
     ///         .is_ok()
     /// );
     /// # }
     /// ```
-    pub fn prove_multiple_with_rng<T: RngCore + CryptoRng>(
-        bp_gens: &BulletproofGens,
+    pub fn prove_multiple_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         values: &[u64],
         blindings: &[Scalar],
-        n: usize,
+        n: BitSize,
         rng: &mut T,
     ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let bp_gens = bp_gens.as_generators();
         use self::dealer::*;
         use self::party::*;
 
+        check_statement_size(n.bits(), values.len())?;
+        trace_phase!("rangeproof.prove", n = n.bits(), m = values.len());
+
         if values.len() != blindings.len() {
             return Err(ProofError::WrongNumBlindingFactors);
         }
@@ -286,17 +631,18 @@ impl RangeProof {
         Ok((proof, value_commitments))
     }
 
+    #[cfg(feature = "prover")]
     /// Create a rangeproof for a set of values.
     /// This is a convenience wrapper around [`RangeProof::prove_multiple_with_rng`],
     /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn prove_multiple(
-        bp_gens: &BulletproofGens,
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_multiple<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         values: &[u64],
         blindings: &[Scalar],
-        n: usize,
+        n: BitSize,
     ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
         RangeProof::prove_multiple_with_rng(
             bp_gens,
@@ -305,44 +651,263 @@ impl RangeProof {
             values,
             blindings,
             n,
-            &mut thread_rng(),
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a set of values, as for
+    /// [`RangeProof::prove_multiple_with_rng`], with the given `options`.
+    ///
+    /// With [`ProveOptions::self_verify`], the proof is verified against
+    /// a copy of the `transcript` taken before proving, so the caller
+    /// does not need to supply the verifier's transcript.  A proof that
+    /// does not verify is discarded and
+    /// [`ProofError::SelfCheckFailed`] is returned; the `transcript` is
+    /// left in the state reached by proving.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_multiple_with_options_and_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+        options: &ProveOptions,
+        rng: &mut T,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        let mut verifier_transcript = transcript.clone();
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+            bp_gens, pc_gens, transcript, values, blindings, n, rng,
+        )?;
+
+        if options.self_verify {
+            trace_phase!("rangeproof.self_verify", n = n.bits(), m = values.len());
+            proof
+                .verify_multiple_with_rng(
+                    bp_gens,
+                    pc_gens,
+                    &mut verifier_transcript,
+                    &value_commitments,
+                    n,
+                    rng,
+                )
+                .map_err(|_| ProofError::SelfCheckFailed)?;
+        }
+
+        Ok((proof, value_commitments))
+    }
+
+    #[cfg(feature = "prover")]
+    /// Create a rangeproof for a set of values with the given `options`.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::prove_multiple_with_options_and_rng`], passing in a
+    /// threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove_multiple_with_options<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+        n: BitSize,
+        options: &ProveOptions,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        RangeProof::prove_multiple_with_options_and_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            blindings,
+            n,
+            options,
+            &mut util::default_rng(),
         )
     }
 
+    #[cfg(feature = "verifier")]
     /// Verifies a rangeproof for a given value commitment \\(V\\).
     ///
     /// This is a convenience wrapper around `verify_multiple` for the `m=1` case.
-    pub fn verify_single_with_rng<T: RngCore + CryptoRng>(
+    ///
+    /// Returns the bitsize that the proof certifies, which is bound to
+    /// the proof by its transcript and its length.  If `n` is not the
+    /// bitsize of the proof, verification fails with
+    /// [`ProofError::BitsizeMismatch`].
+    pub fn verify_single_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
         &self,
-        bp_gens: &BulletproofGens,
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         V: &CompressedRistretto,
-        n: usize,
+        n: BitSize,
         rng: &mut T,
-    ) -> Result<(), ProofError> {
-        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)?;
+        Ok(VerifiedRange { bits: self.bits() })
     }
 
+    #[cfg(feature = "verifier")]
     /// Verifies a rangeproof for a given value commitment \\(V\\).
     ///
     /// This is a convenience wrapper around [`RangeProof::verify_single_with_rng`],
     /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn verify_single(
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_single<G: AsGenerators + ?Sized>(
         &self,
-        bp_gens: &BulletproofGens,
+        bp_gens: &G,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
         V: &CompressedRistretto,
-        n: usize,
-    ) -> Result<(), ProofError> {
-        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut thread_rng())
+        n: BitSize,
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut util::default_rng())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies a rangeproof for a given value commitment \\(V\\),
+    /// created with [`RangeProof::prove_single_with_ad`].
+    ///
+    /// Verification fails unless `label` and `associated_data` match
+    /// the ones used by the prover.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_single_with_ad_and_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        label: &'static [u8],
+        associated_data: &[u8],
+        V: &CompressedRistretto,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<VerifiedRange, ProofError> {
+        let mut transcript = Transcript::new(label);
+        transcript.append_associated_data(associated_data);
+        self.verify_single_with_rng(bp_gens, pc_gens, &mut transcript, V, n, rng)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies a rangeproof for a given value commitment \\(V\\),
+    /// created with [`RangeProof::prove_single_with_ad`].
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_single_with_ad_and_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_single_with_ad<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        label: &'static [u8],
+        associated_data: &[u8],
+        V: &CompressedRistretto,
+        n: BitSize,
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_single_with_ad_and_rng(
+            bp_gens,
+            pc_gens,
+            label,
+            associated_data,
+            V,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies a rangeproof for the `index`-th commitment of
+    /// `session`, created with [`RangeProof::prove_in_session`], on the
+    /// next branch of the session.
+    ///
+    /// Returns [`ProofError::InvalidInputLength`] if the session has no
+    /// `index`-th commitment, without taking a branch.
+    pub fn verify_in_session_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<VerifiedRange, ProofError> {
+        let V = session.commitment(index)?;
+        let mut transcript = session.branch(b"range-proof");
+        transcript.append_len(b"index", index);
+        self.verify_single_with_rng(bp_gens, pc_gens, &mut transcript, &V, n, rng)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies a rangeproof for the `index`-th commitment of
+    /// `session`, created with [`RangeProof::prove_in_session`].
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_in_session_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_in_session<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        session: &mut StatementSession,
+        index: usize,
+        n: BitSize,
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_in_session_with_rng(
+            bp_gens,
+            pc_gens,
+            session,
+            index,
+            n,
+            &mut util::default_rng(),
+        )
     }
 
+    #[cfg(feature = "verifier")]
     /// Verifies an aggregated rangeproof for the given value commitments.
-    pub fn verify_multiple_with_rng<T: RngCore + CryptoRng>(
+    ///
+    /// As when proving, the number of value commitments must be a
+    /// nonzero power of two.  A commitment
+    /// may appear more than once, as for parties that committed to equal
+    /// values with equal blinding factors; each position is still a
+    /// separate party of the proof.
+    ///
+    /// Returns [`ProofError::VerificationError`] if the proof does not
+    /// verify, or [`ProofError::InvalidGeneratorsLength`] if `bp_gens`
+    /// has too few generators for `n` and the number of commitments.
+    /// With the `diagnostics` feature, a proof that fails the
+    /// verification equation is reported as [`ProofError::FailedChecks`]
+    /// instead, at the cost of evaluating the equation again in parts.
+    ///
+    /// Verification runs on a copy of the `transcript`, which is only
+    /// updated if the proof is valid, so a failed verification can be
+    /// retried with another proof against the same transcript.
+    pub fn verify_multiple_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let bp_gens = bp_gens.as_generators();
+        let points = self.decompress_points()?;
+        self.verify_multiple_with_points(
+            &points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n.bits(),
+            rng,
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof whose points have already
+    /// been decompressed into `points`.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_multiple_with_points<T: RngCore + CryptoRng>(
         &self,
+        points: &RangeProofPoints,
         bp_gens: &BulletproofGens,
         pc_gens: &PedersenGens,
         transcript: &mut Transcript,
@@ -350,13 +915,79 @@ impl RangeProof {
         n: usize,
         rng: &mut T,
     ) -> Result<(), ProofError> {
+        let mut working_transcript = transcript.clone();
+        self.verify_multiple_on_transcript(
+            points,
+            bp_gens,
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            n,
+            rng,
+        )?;
+        *transcript = working_transcript;
+        Ok(())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Checks the statement, absorbs it and the proof's commitments
+    /// into the `transcript`, and returns the challenges
+    /// \\(y, z, x\\).
+    fn replay_poly_challenges<T: TranscriptProtocol>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut T,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(Scalar, Scalar, Scalar), ProofError> {
+        let (challenges, nonidentity) = self.replay_poly_challenges_ct(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            TranscriptVersion::V1,
+        )?;
+        if bool::from(!nonidentity) {
+            return Err(ProofError::VerificationError);
+        }
+        Ok(challenges)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Replays the polynomial challenges like `replay_poly_challenges`,
+    /// but without branching on the proof: whether none of its
+    /// commitments is the identity is returned instead of checked.
+    /// Only the public statement is checked.  The generators are
+    /// bound to the transcript except under the rules of
+    /// [`TranscriptVersion::V0`].
+    fn replay_poly_challenges_ct<T: TranscriptProtocol>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut T,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        version: TranscriptVersion,
+    ) -> Result<((Scalar, Scalar, Scalar), Choice), ProofError> {
         let m = value_commitments.len();
+        check_statement_size(n, m)?;
 
         // First, replay the "interactive" protocol using the proof
         // data to recompute all challenges.
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
+        if !SUPPORTED_RANGE_BITS.contains(&n) {
             return Err(ProofError::InvalidBitsize);
         }
+        if !m.is_power_of_two() {
+            return Err(ProofError::InvalidAggregation);
+        }
+        if self.bits() != n * m {
+            return Err(ProofError::BitsizeMismatch {
+                claimed: n,
+                proven: self.bits() / m,
+            });
+        }
         if bp_gens.gens_capacity < n {
             return Err(ProofError::InvalidGeneratorsLength);
         }
@@ -364,7 +995,11 @@ impl RangeProof {
             return Err(ProofError::InvalidGeneratorsLength);
         }
 
-        transcript.rangeproof_domain_sep(n as u64, m as u64);
+        transcript.rangeproof_domain_sep(n, m);
+        if version != TranscriptVersion::V0 {
+            bp_gens.append_fingerprint(transcript);
+            pc_gens.append_fingerprint(transcript);
+        }
 
         for V in value_commitments.iter() {
             // Allow the commitments to be zero (0 value, 0 blinding)
@@ -372,471 +1007,3347 @@ impl RangeProof {
             transcript.append_point(b"V", V);
         }
 
-        transcript.validate_and_append_point(b"A", &self.A)?;
-        transcript.validate_and_append_point(b"S", &self.S)?;
+        let mut nonidentity = transcript.append_and_check_point(b"A", &self.A);
+        nonidentity &= transcript.append_and_check_point(b"S", &self.S);
 
         let y = transcript.challenge_scalar(b"y");
         let z = transcript.challenge_scalar(b"z");
-        let zz = z * z;
-        let minus_z = -z;
 
-        transcript.validate_and_append_point(b"T_1", &self.T_1)?;
-        transcript.validate_and_append_point(b"T_2", &self.T_2)?;
+        nonidentity &= transcript.append_and_check_point(b"T_1", &self.T_1);
+        nonidentity &= transcript.append_and_check_point(b"T_2", &self.T_2);
 
         let x = transcript.challenge_scalar(b"x");
 
+        Ok(((y, z, x), nonidentity))
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Absorbs the opening of \\(t(x)\\) into the `transcript` and
+    /// returns the challenge \\(w\\) binding the inner-product proof
+    /// to it.
+    fn replay_ipp_challenge<T: TranscriptProtocol>(&self, transcript: &mut T) -> Scalar {
         transcript.append_scalar(b"t_x", &self.t_x);
         transcript.append_scalar(b"t_x_blinding", &self.t_x_blinding);
         transcript.append_scalar(b"e_blinding", &self.e_blinding);
 
-        let w = transcript.challenge_scalar(b"w");
+        transcript.challenge_scalar(b"w")
+    }
 
+    #[cfg(feature = "verifier")]
+    #[allow(clippy::too_many_arguments)]
+    fn verify_multiple_on_transcript<T: RngCore + CryptoRng>(
+        &self,
+        points: &RangeProofPoints,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
         // Challenge value for batching statements to be verified
         let c = Scalar::random(rng);
+        self.verify_multiple_with_weight(
+            points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            c,
+            TranscriptVersion::V1,
+        )
+    }
 
-        let (x_sq, x_inv_sq, s) = self.ipp_proof.verification_scalars(n * m, transcript)?;
-        let s_inv = s.iter().rev();
-
-        let a = self.ipp_proof.a;
-        let b = self.ipp_proof.b;
-
-        // Construct concat_z_and_2, an iterator of the values of
-        // z^0 * \vec(2)^n || z^1 * \vec(2)^n || ... || z^(m-1) * \vec(2)^n
-        let powers_of_2: Vec<Scalar> = util::exp_iter(Scalar::from(2u64)).take(n).collect();
-        let concat_z_and_2: Vec<Scalar> = util::exp_iter(z)
-            .take(m)
-            .flat_map(|exp_z| powers_of_2.iter().map(move |exp_2| exp_2 * exp_z))
-            .collect();
-
-        let g = s.iter().map(|s_i| minus_z - a * s_i);
-        let h = s_inv
-            .zip(util::exp_iter(y.invert()))
-            .zip(concat_z_and_2.iter())
-            .map(|((s_i_inv, exp_y_inv), z_and_2)| z + exp_y_inv * (zz * z_and_2 - b * s_i_inv));
-
-        let value_commitment_scalars = util::exp_iter(z).take(m).map(|z_exp| c * zz * z_exp);
-        let basepoint_scalar = w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x);
-
-        use curve25519_dalek::traits::VartimeMultiscalarMul;
+    #[cfg(feature = "verifier")]
+    /// Verifies the proof on the `transcript` like
+    /// `verify_multiple_on_transcript`, with the polynomial check added
+    /// to the inner-product check with the weight `c`, replaying the
+    /// transcript under the rules of `version`.
+    #[allow(clippy::too_many_arguments)]
+    fn verify_multiple_with_weight(
+        &self,
+        points: &RangeProofPoints,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        c: Scalar,
+        version: TranscriptVersion,
+    ) -> Result<(), ProofError> {
+        let m = value_commitments.len();
+        trace_phase!("rangeproof.verify", n, m);
+        #[cfg(feature = "diagnostics")]
+        let initial_transcript = transcript.clone();
+        let (terms, nonidentity) = self.verification_terms_ct(
+            points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            c,
+            version,
+        )?;
+        if bool::from(!nonidentity) {
+            return Err(ProofError::VerificationError);
+        }
+
+        trace_phase!(
+            "rangeproof.msm",
+            size = terms.scalars.len() + terms.g.len() + terms.h.len() + 2
+        );
+        use curve25519_dalek::traits::VartimeMultiscalarMul;
         let mega_check = RistrettoPoint::optional_multiscalar_mul(
-            iter::once(Scalar::ONE)
-                .chain(iter::once(x))
-                .chain(iter::once(c * x))
-                .chain(iter::once(c * x * x))
-                .chain(x_sq.iter().cloned())
-                .chain(x_inv_sq.iter().cloned())
-                .chain(iter::once(-self.e_blinding - c * self.t_x_blinding))
-                .chain(iter::once(basepoint_scalar))
-                .chain(g)
-                .chain(h)
-                .chain(value_commitment_scalars),
-            iter::once(self.A.decompress())
-                .chain(iter::once(self.S.decompress()))
-                .chain(iter::once(self.T_1.decompress()))
-                .chain(iter::once(self.T_2.decompress()))
-                .chain(self.ipp_proof.L_vec.iter().map(|L| L.decompress()))
-                .chain(self.ipp_proof.R_vec.iter().map(|R| R.decompress()))
+            terms
+                .scalars
+                .into_iter()
+                .chain(iter::once(terms.B_blinding))
+                .chain(iter::once(terms.B))
+                .chain(terms.g)
+                .chain(terms.h),
+            terms
+                .points
+                .into_iter()
                 .chain(iter::once(Some(pc_gens.B_blinding)))
                 .chain(iter::once(Some(pc_gens.B)))
                 .chain(bp_gens.G(n, m).map(|&x| Some(x)))
-                .chain(bp_gens.H(n, m).map(|&x| Some(x)))
-                .chain(value_commitments.iter().map(|V| V.decompress())),
-        )
-        .ok_or_else(|| ProofError::VerificationError)?;
+                .chain(bp_gens.H(n, m).map(|&x| Some(x))),
+        );
 
         use group::Group;
-        if mega_check.is_identity().into() {
-            Ok(())
-        } else {
-            Err(ProofError::VerificationError)
+        match mega_check {
+            Some(mega_check) if mega_check.is_identity().into() => Ok(()),
+            // The diagnosis replays the transcript under the current
+            // rules only.
+            #[cfg(feature = "diagnostics")]
+            _ if version == TranscriptVersion::V1 => Err(self.diagnose(
+                points,
+                bp_gens,
+                pc_gens,
+                &initial_transcript,
+                value_commitments,
+                n,
+            )),
+            _ => Err(ProofError::VerificationError),
         }
     }
 
-    /// Verifies an aggregated rangeproof for the given value commitments.
-    /// This is a convenience wrapper around [`RangeProof::verify_multiple_with_rng`],
-    /// passing in a threadsafe RNG.
-    #[cfg(feature = "std")]
-    pub fn verify_multiple(
+    #[cfg(any(test, feature = "diagnostics"))]
+    /// Evaluates separately the two equations that verification checks
+    /// at once, combined with a random weight, and reports which of
+    /// them the proof fails as [`ProofError::FailedChecks`]: the
+    /// polynomial check of [`RangeProof::precheck`], and the
+    /// inner-product check that \(t(x)\) is the inner product of the
+    /// vectors committed to by \(A\) and \(S\).
+    ///
+    /// The `transcript` must be in the state expected by verification.
+    fn diagnose(
         &self,
+        points: &RangeProofPoints,
         bp_gens: &BulletproofGens,
         pc_gens: &PedersenGens,
-        transcript: &mut Transcript,
+        transcript: &Transcript,
         value_commitments: &[CompressedRistretto],
         n: usize,
-    ) -> Result<(), ProofError> {
-        self.verify_multiple_with_rng(
+    ) -> ProofError {
+        let m = value_commitments.len();
+        let polynomial = self
+            .check_polynomial(bp_gens, pc_gens, transcript, value_commitments, n)
+            .is_err();
+
+        // Without the weight of the polynomial check, only the
+        // inner-product check remains, and the value commitments have
+        // zero scalars.
+        let mut transcript = transcript.clone();
+        let inner_product = match self.verification_terms_ct(
+            points,
             bp_gens,
             pc_gens,
-            transcript,
+            &mut transcript,
             value_commitments,
             n,
-            &mut thread_rng(),
-        )
-    }
+            Scalar::ZERO,
+            TranscriptVersion::V1,
+        ) {
+            Ok((terms, nonidentity)) => {
+                use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+                let check = RistrettoPoint::vartime_multiscalar_mul(
+                    terms
+                        .scalars
+                        .iter()
+                        .chain(iter::once(&terms.B_blinding))
+                        .chain(iter::once(&terms.B))
+                        .chain(terms.g.iter())
+                        .chain(terms.h.iter()),
+                    terms
+                        .points
+                        .iter()
+                        .map(|P| P.unwrap_or_default())
+                        .chain(iter::once(pc_gens.B_blinding))
+                        .chain(iter::once(pc_gens.B))
+                        .chain(bp_gens.G(n, m).cloned())
+                        .chain(bp_gens.H(n, m).cloned()),
+                );
+                !bool::from(nonidentity) || !check.is_identity()
+            }
+            Err(_) => true,
+        };
 
-    /// Serializes the proof into a byte array of \\(2 \lg n + 9\\)
-    /// 32-byte elements, where \\(n\\) is the number of secret bits.
-    ///
-    /// # Layout
-    ///
-    /// The layout of the range proof encoding is:
-    ///
-    /// * four compressed Ristretto points \\(A,S,T_1,T_2\\),
-    /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
-    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
-    /// * two scalars \\(a, b\\).
-    pub fn to_bytes(&self) -> Vec<u8> {
-        // 7 elements: points A, S, T1, T2, scalars tx, tx_bl, e_bl.
-        let mut buf = Vec::with_capacity(7 * 32 + self.ipp_proof.serialized_size());
-        buf.extend_from_slice(self.A.as_bytes());
-        buf.extend_from_slice(self.S.as_bytes());
-        buf.extend_from_slice(self.T_1.as_bytes());
-        buf.extend_from_slice(self.T_2.as_bytes());
-        buf.extend_from_slice(self.t_x.as_bytes());
-        buf.extend_from_slice(self.t_x_blinding.as_bytes());
-        buf.extend_from_slice(self.e_blinding.as_bytes());
-        buf.extend(self.ipp_proof.to_bytes_iter());
-        buf
+        ProofError::FailedChecks {
+            polynomial,
+            inner_product,
+        }
     }
 
-    /// Deserializes the proof from a byte slice.
-    ///
-    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
-    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
-        if slice.len() % 32 != 0 {
-            return Err(ProofError::FormatError);
-        }
-        if slice.len() < 7 * 32 {
-            return Err(ProofError::FormatError);
+    #[cfg(feature = "verifier")]
+    /// Replays the proof on the `transcript` and computes the scalars
+    /// of its verification equation, without evaluating it.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verification_terms<T: RngCore + CryptoRng>(
+        &self,
+        points: &RangeProofPoints,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        rng: &mut T,
+    ) -> Result<VerificationTerms, ProofError> {
+        // Challenge value for batching statements to be verified
+        let c = Scalar::random(rng);
+
+        let (terms, nonidentity) = self.verification_terms_ct(
+            points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            c,
+            TranscriptVersion::V1,
+        )?;
+        if bool::from(!nonidentity) {
+            return Err(ProofError::VerificationError);
         }
+        Ok(terms)
+    }
 
-        use crate::util::read32;
+    #[cfg(feature = "verifier")]
+    /// Computes the verification terms like `verification_terms`, but
+    /// without branching on the proof: whether none of its points is
+    /// the identity is returned instead of checked.  The polynomial
+    /// check is added to the inner-product check with the weight `c`,
+    /// and the transcript is replayed under the rules of `version`.
+    #[allow(clippy::too_many_arguments)]
+    fn verification_terms_ct(
+        &self,
+        points: &RangeProofPoints,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        c: Scalar,
+        version: TranscriptVersion,
+    ) -> Result<(VerificationTerms, Choice), ProofError> {
+        let m = value_commitments.len();
+        let ((y, z, x), poly_nonidentity) = self.replay_poly_challenges_ct(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            version,
+        )?;
 
-        let A = CompressedRistretto(read32(&slice[0 * 32..]));
-        let S = CompressedRistretto(read32(&slice[1 * 32..]));
-        let T_1 = CompressedRistretto(read32(&slice[2 * 32..]));
-        let T_2 = CompressedRistretto(read32(&slice[3 * 32..]));
+        let w = self.replay_ipp_challenge(transcript);
 
-        let t_x = Option::from(Scalar::from_canonical_bytes(read32(&slice[4 * 32..])))
-            .ok_or(ProofError::FormatError)?;
-        let t_x_blinding = Option::from(Scalar::from_canonical_bytes(read32(&slice[5 * 32..])))
-            .ok_or(ProofError::FormatError)?;
-        let e_blinding = Option::from(Scalar::from_canonical_bytes(read32(&slice[6 * 32..])))
-            .ok_or(ProofError::FormatError)?;
+        let (x_sq, x_inv_sq, s, ipp_nonidentity) =
+            self.ipp_proof
+                .verification_scalars_ct(n * m, transcript, version)?;
+        trace_event!("challenges derived");
 
-        let ipp_proof = InnerProductProof::from_bytes(&slice[7 * 32..])?;
+        let a = self.ipp_proof.a();
+        let b = self.ipp_proof.b();
 
-        Ok(RangeProof {
-            A,
-            S,
-            T_1,
-            T_2,
-            t_x,
-            t_x_blinding,
-            e_blinding,
-            ipp_proof,
-        })
-    }
-}
+        let (g, h) = generator_scalars(s, a, b, &StandardFactors::new(&y), &z, n, m);
+        let basepoint_scalar = w * (self.t_x - a * b) + c * (delta(n, m, &y, &z) - self.t_x);
 
-impl Serialize for RangeProof {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: Serializer,
-    {
-        serializer.serialize_bytes(&self.to_bytes()[..])
+        let terms = VerificationTerms {
+            n,
+            m,
+            B: basepoint_scalar,
+            B_blinding: -self.e_blinding - c * self.t_x_blinding,
+            g,
+            h,
+            scalars: proof_point_scalars(&x, &c)
+                .iter()
+                .cloned()
+                .chain(x_sq)
+                .chain(x_inv_sq)
+                .chain(value_commitment_scalars(c, z, m))
+                .collect(),
+            points: iter::once(Some(points.A))
+                .chain(iter::once(Some(points.S)))
+                .chain(iter::once(Some(points.T_1)))
+                .chain(iter::once(Some(points.T_2)))
+                .chain(points.L_vec.iter().map(|&L| Some(L)))
+                .chain(points.R_vec.iter().map(|&R| Some(R)))
+                .chain(value_commitments.iter().map(|V| V.decompress()))
+                .collect(),
+        };
+        Ok((terms, poly_nonidentity & ipp_nonidentity))
     }
-}
 
-impl<'de> Deserialize<'de> for RangeProof {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: Deserializer<'de>,
-    {
-        struct RangeProofVisitor;
+    #[cfg(feature = "verifier")]
+    /// Cheaply screens the proof by checking only that \\(t(x)\\) is
+    /// consistent with the polynomial commitments \\(T\_1, T\_2\\)
+    /// and the value commitments.  This takes a multiscalar
+    /// multiplication of \\(m + 4\\) terms, instead of the
+    /// \\(2nm + m + 2\lg(nm) + 6\\) terms of full verification.
+    ///
+    /// The check is necessary but not sufficient: a proof that fails it
+    /// is invalid, but a proof that passes it must still be verified
+    /// with [`RangeProof::verify_multiple_with_rng`].  The `transcript`
+    /// must be in the state expected by verification, and is not
+    /// modified.
+    ///
+    /// Returns the same errors as verification for invalid statements,
+    /// and [`ProofError::VerificationError`] if the check fails.
+    pub fn precheck<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        self.check_polynomial(
+            bp_gens.as_generators(),
+            pc_gens,
+            transcript,
+            value_commitments,
+            n.bits(),
+        )
+    }
 
-        impl<'de> Visitor<'de> for RangeProofVisitor {
-            type Value = RangeProof;
+    #[cfg(feature = "verifier")]
+    /// Checks only the polynomial equation of the proof, as described
+    /// for [`RangeProof::precheck`].
+    fn check_polynomial(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<(), ProofError> {
+        let m = value_commitments.len();
+        let mut transcript = transcript.clone();
+        let (y, z, x) =
+            self.replay_poly_challenges(bp_gens, pc_gens, &mut transcript, value_commitments, n)?;
 
-            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
-                formatter.write_str("a valid RangeProof")
-            }
+        // t_x B + t_x_blinding B_blinding
+        //     == delta B + sum z^(j+2) V_j + x T_1 + x^2 T_2
+        use curve25519_dalek::traits::VartimeMultiscalarMul;
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            [
+                self.t_x - delta(n, m, &y, &z),
+                self.t_x_blinding,
+                -x,
+                -(x * x),
+            ]
+            .iter()
+            .cloned()
+            .chain(value_commitment_scalars(-Scalar::ONE, z, m)),
+            [
+                Some(pc_gens.B),
+                Some(pc_gens.B_blinding),
+                self.T_1.decompress(),
+                self.T_2.decompress(),
+            ]
+            .iter()
+            .cloned()
+            .chain(value_commitments.iter().map(|V| V.decompress())),
+        )
+        .ok_or(ProofError::VerificationError)?;
 
-            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof, E>
-            where
-                E: serde::de::Error,
-            {
-                // Using Error::custom requires T: Display, which our error
-                // type only implements when it implements std::error::Error.
-                #[cfg(feature = "std")]
-                return RangeProof::from_bytes(v).map_err(serde::de::Error::custom);
-                // In no-std contexts, drop the error message.
-                #[cfg(not(feature = "std"))]
-                return RangeProof::from_bytes(v)
-                    .map_err(|_| serde::de::Error::custom("deserialization error"));
-            }
+        use group::Group;
+        if check.is_identity().into() {
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
         }
-
-        deserializer.deserialize_bytes(RangeProofVisitor)
     }
-}
-
-/// Compute
-/// \\[
-/// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n \cdot m} \rangle
-/// \\]
-fn delta(n: usize, m: usize, y: &Scalar, z: &Scalar) -> Scalar {
-    let sum_y = util::sum_of_powers(y, n * m);
-    let sum_2 = util::sum_of_powers(&Scalar::from(2u64), n);
-    let sum_z = util::sum_of_powers(z, m);
-
-    (z - z * z) * sum_y - z * z * z * sum_2 * sum_z
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[cfg(feature = "verifier")]
+    /// Returns the operations that verification performs on the
+    /// `transcript`, in order, so that verifiers in other languages can
+    /// reproduce the Fiat-Shamir challenges.
+    ///
+    /// The trace starts from the state of `transcript`, which is not
+    /// modified, and ends with the last inner-product challenge; the
+    /// verifier derives no further challenges.  It is recorded by the
+    /// same code that verification runs, so the two cannot disagree.
+    ///
+    /// Returns the same errors as verification for invalid statements,
+    /// and [`ProofError::VerificationError`] if a point of the proof is
+    /// the identity.  The proof itself is not verified.
+    pub fn transcript_trace<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<Vec<TranscriptOp>, ProofError> {
+        let n = n.bits();
+        let bp_gens = bp_gens.as_generators();
+        let recorder =
+            self.record_challenges(bp_gens, pc_gens, transcript, value_commitments, n)?;
+        Ok(recorder.into_parts().1)
+    }
 
-    use crate::generators::PedersenGens;
+    #[cfg(feature = "verifier")]
+    /// Replays the proof on a copy of the `transcript`, recording the
+    /// operations up to the last inner-product challenge.
+    fn record_challenges(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+    ) -> Result<TranscriptRecorder, ProofError> {
+        let mut recorder = TranscriptRecorder::new(transcript);
+        self.replay_poly_challenges(bp_gens, pc_gens, &mut recorder, value_commitments, n)?;
+        self.replay_ipp_challenge(&mut recorder);
+        self.ipp_proof
+            .verification_scalars(n * value_commitments.len(), &mut recorder)?;
+        Ok(recorder)
+    }
 
-    #[test]
-    fn test_delta() {
-        let mut rng = rand::thread_rng();
-        let y = Scalar::random(&mut rng);
-        let z = Scalar::random(&mut rng);
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof for the given value commitments
+    /// without an RNG, and on success returns an [`AuditTrace`] of the
+    /// challenges derived during verification.
+    ///
+    /// [`RangeProof::verify_multiple_with_rng`] adds the polynomial
+    /// check to the inner-product check with a random weight, which no
+    /// one else can reproduce.  Here the weight is a challenge squeezed
+    /// from a copy of the transcript after the last inner-product
+    /// challenge and the entire encoding of the proof, so the trace
+    /// depends only on the proof and the statement, and a third party
+    /// can derive it again with [`AuditTrace::recheck`].  Since the
+    /// weight is fixed after the proof, this is as sound as verifying
+    /// with a random one.
+    ///
+    /// On success the `transcript` is left in the same state as after
+    /// `verify_multiple_with_rng`; on failure it is not modified.
+    pub fn verify_with_audit<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<AuditTrace, ProofError> {
+        let n = n.bits();
+        let bp_gens = bp_gens.as_generators();
+        let points = self.decompress_points()?;
+        let (working_transcript, ops) = self
+            .record_challenges(bp_gens, pc_gens, transcript, value_commitments, n)?
+            .into_parts();
 
-        // Choose n = 256 to ensure we overflow the group order during
-        // the computation, to check that that's done correctly
-        let n = 256;
+        let mut weight_transcript = working_transcript.clone();
+        weight_transcript.append_message(b"proof", &self.to_bytes());
+        let c = weight_transcript.challenge_scalar(b"c");
 
-        // code copied from previous implementation
-        let z2 = z * z;
-        let z3 = z2 * z;
-        let mut power_g = Scalar::ZERO;
-        let mut exp_y = Scalar::ONE; // start at y^0 = 1
-        let mut exp_2 = Scalar::ONE; // start at 2^0 = 1
-        for _ in 0..n {
-            power_g += (z - z2) * exp_y - z3 * exp_2;
+        self.verify_multiple_with_weight(
+            &points,
+            bp_gens,
+            pc_gens,
+            &mut transcript.clone(),
+            value_commitments,
+            n,
+            c,
+            TranscriptVersion::V1,
+        )?;
+        *transcript = working_transcript;
+        Ok(AuditTrace::new(&ops, c))
+    }
 
-            exp_y = exp_y * y; // y^i -> y^(i+1)
-            exp_2 = exp_2 + exp_2; // 2^i -> 2^(i+1)
-        }
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof for the given value commitments.
+    /// This is a convenience wrapper around [`RangeProof::verify_multiple_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_multiple<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut util::default_rng(),
+        )
+    }
 
-        assert_eq!(power_g, delta(n, 1, &y, &z),);
+    #[cfg(feature = "legacy-v0")]
+    /// Verifies an aggregated rangeproof like
+    /// [`RangeProof::verify_multiple_with_rng`], replaying the
+    /// transcript under the rules of `version`.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn verify_multiple_versioned_with_rng<T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: usize,
+        version: TranscriptVersion,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let points = self.decompress_points()?;
+        let mut working_transcript = transcript.clone();
+        self.verify_multiple_with_weight(
+            &points,
+            bp_gens,
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            n,
+            Scalar::random(rng),
+            version,
+        )?;
+        *transcript = working_transcript;
+        Ok(())
     }
 
-    /// Given a bitsize `n`, test the following:
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof like
+    /// [`RangeProof::verify_multiple_with_rng`], in time independent of
+    /// whether and where the proof is invalid.
     ///
-    /// 1. Generate `m` random values and create a proof they are all in range;
-    /// 2. Serialize to wire format;
-    /// 3. Deserialize from wire format;
-    /// 4. Verify the proof.
-    fn singleparty_create_and_verify_helper(n: usize, m: usize) {
+    /// Every point of the proof and every value commitment is
+    /// decompressed and absorbed into the transcript before any check,
+    /// the verification equation is evaluated with a constant-time
+    /// multiscalar multiplication, and the only branch on the proof is
+    /// on the final result.  Errors for an invalid statement, such as
+    /// [`ProofError::BitsizeMismatch`], depend only on public sizes and
+    /// are returned early.  A proof is accepted exactly when
+    /// `verify_multiple_with_rng` accepts it.
+    ///
+    /// This is for verifiers for whom the validity of a proof is itself
+    /// secret, such as a server screening anonymous credentials.  It
+    /// costs a great deal: verification takes about 1.5 times as long
+    /// for a single 64-bit proof and over twice as long for eight
+    /// aggregated ones, and the gap widens with the size of the proof.
+    /// The `range_proof` benchmarks measure it under "Constant-time
+    /// rangeproof verification".
+    pub fn verify_multiple_ct_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let n = n.bits();
+        let bp_gens = bp_gens.as_generators();
+        let m = value_commitments.len();
+        trace_phase!("rangeproof.verify_ct", n, m);
+        let (points, mut valid) = self.decompress_points_ct();
+
+        let mut working_transcript = transcript.clone();
+        let (terms, nonidentity) = self.verification_terms_ct(
+            &points,
+            bp_gens,
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            n,
+            Scalar::random(rng),
+            TranscriptVersion::V1,
+        )?;
+        valid &= nonidentity;
+
+        let points: Vec<RistrettoPoint> = terms
+            .points
+            .into_iter()
+            .map(|P| {
+                valid &= Choice::from(P.is_some() as u8);
+                P.unwrap_or_default()
+            })
+            .collect();
+
+        use curve25519_dalek::traits::{Identity, MultiscalarMul};
+        let mega_check = RistrettoPoint::multiscalar_mul(
+            terms
+                .scalars
+                .iter()
+                .chain(iter::once(&terms.B_blinding))
+                .chain(iter::once(&terms.B))
+                .chain(terms.g.iter())
+                .chain(terms.h.iter()),
+            points
+                .iter()
+                .chain(iter::once(&pc_gens.B_blinding))
+                .chain(iter::once(&pc_gens.B))
+                .chain(bp_gens.G(n, m))
+                .chain(bp_gens.H(n, m)),
+        );
+        valid &= mega_check.ct_eq(&RistrettoPoint::identity());
+
+        if valid.into() {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof in constant time.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::verify_multiple_ct_with_rng`], passing in a
+    /// threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_multiple_ct<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_ct_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Replays the proof on the `transcript` and splits its verification
+    /// equation into up to `num_shards` independent parts, for
+    /// evaluating one large aggregated proof on several threads.
+    ///
+    /// The proof is valid exactly when the results of
+    /// [`VerificationShard::evaluate`] for all shards sum to the
+    /// identity.  Each shard holds the terms of the generators of a
+    /// contiguous range of parties, which make up most of the equation
+    /// for large \\(n \cdot m\\), and the first shard also holds the
+    /// terms of the points of the proof and of the value commitments.
+    /// There is at most one shard per party, so `num_shards` is clamped
+    /// to \\([1, m]\\).
+    ///
+    /// Unlike verification, this leaves the `transcript` in the state
+    /// after the proof whether or not the proof turns out to be valid.
+    /// [`RangeProof::verify_multiple_sharded_with_rng`] evaluates the
+    /// shards on separate threads.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verification_shards_with_rng<'a, G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &'a G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        num_shards: usize,
+        rng: &mut T,
+    ) -> Result<Vec<VerificationShard<'a>>, ProofError> {
+        let n = n.bits();
+        let bp_gens = bp_gens.as_generators();
+        let m = value_commitments.len();
+        let points = self.decompress_points()?;
+        let mut terms = self.verification_terms(
+            &points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            rng,
+        )?;
+        terms.scalars.push(terms.B_blinding);
+        terms.points.push(Some(pc_gens.B_blinding));
+        terms.scalars.push(terms.B);
+        terms.points.push(Some(pc_gens.B));
+
+        let num_shards = num_shards.clamp(1, m);
+        Ok((0..num_shards)
+            .map(|k| {
+                let parties = (k * m / num_shards)..((k + 1) * m / num_shards);
+                let generators = (parties.start * n)..(parties.end * n);
+                let (scalars, points) = if k == 0 {
+                    (mem::take(&mut terms.scalars), mem::take(&mut terms.points))
+                } else {
+                    (Vec::new(), Vec::new())
+                };
+                VerificationShard {
+                    bp_gens,
+                    n,
+                    parties,
+                    scalars,
+                    points,
+                    g: terms.g[generators.clone()].to_vec(),
+                    h: terms.h[generators].to_vec(),
+                }
+            })
+            .collect())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof like
+    /// [`RangeProof::verify_multiple_with_rng`], evaluating the
+    /// verification equation in up to `num_shards` parts from
+    /// [`RangeProof::verification_shards_with_rng`] on separate threads.
+    ///
+    /// This speeds up the verification of one proof with a large
+    /// \\(n \cdot m\\) on a machine with several cores; to verify
+    /// many proofs, a [`BatchVerifier`](crate::BatchVerifier) with
+    /// parallel [`VerificationOptions`](crate::VerificationOptions)
+    /// does better.  Without the `std` feature, the parts are evaluated
+    /// one after the other.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_multiple_sharded_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        num_shards: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let mut working_transcript = transcript.clone();
+        let shards = self.verification_shards_with_rng(
+            bp_gens,
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            n,
+            num_shards,
+            rng,
+        )?;
+        trace_phase!(
+            "rangeproof.verify_sharded",
+            n = n.bits(),
+            shards = shards.len()
+        );
+
+        #[cfg(feature = "std")]
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = shards[1..]
+                .iter()
+                .map(|shard| scope.spawn(move || shard.evaluate()))
+                .collect();
+            iter::once(shards[0].evaluate())
+                .chain(
+                    handles
+                        .into_iter()
+                        .map(|handle| handle.join().expect("shard evaluation panicked")),
+                )
+                .collect()
+        });
+        #[cfg(not(feature = "std"))]
+        let results: Vec<_> = shards.iter().map(VerificationShard::evaluate).collect();
+
+        use curve25519_dalek::traits::IsIdentity;
+        if results
+            .into_iter()
+            .sum::<Result<RistrettoPoint, _>>()?
+            .is_identity()
+        {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof in up to `num_shards` parts
+    /// on separate threads.
+    /// This is a convenience wrapper around
+    /// [`RangeProof::verify_multiple_sharded_with_rng`], passing in a
+    /// threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_multiple_sharded<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        num_shards: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_sharded_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            num_shards,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof for the given value commitments,
+    /// and on success returns a 32-byte digest of the verified statement.
+    ///
+    /// The digest is a final challenge squeezed from the transcript after
+    /// the whole proof has been absorbed, so it commits to the proof, the
+    /// value commitments, \\(n\\), \\(m\\), and whatever the caller
+    /// appended to the transcript beforehand.  It can be used as a cache
+    /// key for verification results.
+    pub fn verify_and_digest_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<[u8; 32], ProofError> {
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, value_commitments, n, rng)?;
+
+        // The verifier does not absorb the IPP scalars, so bind the
+        // entire encoding of the proof before squeezing the digest.
+        transcript.append_message(b"proof", &self.to_bytes());
+        let mut digest = [0u8; 32];
+        transcript.challenge_bytes(b"statement-digest", &mut digest);
+        Ok(digest)
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies an aggregated rangeproof for the given value commitments,
+    /// and on success returns a 32-byte digest of the verified statement.
+    ///
+    /// This is a convenience wrapper around [`RangeProof::verify_and_digest_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_and_digest<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<[u8; 32], ProofError> {
+        self.verify_and_digest_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    /// Decompresses the points of the proof, so that it can be
+    /// verified repeatedly without decompressing them again.
+    ///
+    /// Returns a `VerificationError` if any point is not a valid
+    /// compressed Ristretto point, since such a proof can never verify.
+    #[cfg(feature = "verifier")]
+    pub fn decompress(self) -> Result<DecompressedRangeProof, ProofError> {
+        Ok(DecompressedRangeProof {
+            points: self.decompress_points()?,
+            proof: self,
+        })
+    }
+
+    #[cfg(feature = "verifier")]
+    pub(crate) fn decompress_points(&self) -> Result<RangeProofPoints, ProofError> {
+        let decompress =
+            |P: &CompressedRistretto| P.decompress().ok_or(ProofError::VerificationError);
+        let (L_vec, R_vec) = self.ipp_proof.decompress_points()?;
+        Ok(RangeProofPoints {
+            A: decompress(&self.A)?,
+            S: decompress(&self.S)?,
+            T_1: decompress(&self.T_1)?,
+            T_2: decompress(&self.T_2)?,
+            L_vec,
+            R_vec,
+        })
+    }
+
+    /// Decompresses every point of the proof without returning early,
+    /// substituting the identity for invalid encodings, and returns
+    /// whether all of them were valid.
+    #[cfg(feature = "verifier")]
+    fn decompress_points_ct(&self) -> (RangeProofPoints, Choice) {
+        let mut valid = Choice::from(1);
+        let A = util::decompress_ct(&self.A, &mut valid);
+        let S = util::decompress_ct(&self.S, &mut valid);
+        let T_1 = util::decompress_ct(&self.T_1, &mut valid);
+        let T_2 = util::decompress_ct(&self.T_2, &mut valid);
+        let (L_vec, R_vec, ipp_valid) = self.ipp_proof.decompress_points_ct();
+        let points = RangeProofPoints {
+            A,
+            S,
+            T_1,
+            T_2,
+            L_vec,
+            R_vec,
+        };
+        (points, valid & ipp_valid)
+    }
+
+    /// Returns the number of bits covered by the proof, \\(n \cdot m\\)
+    /// for an aggregated proof of \\(m\\) values of \\(n\\) bits each,
+    /// as given by the length of its inner-product proof.
+    ///
+    /// For a proof of a single value, this is its bitsize \\(n\\).
+    pub fn bits(&self) -> usize {
+        1 << self.ipp_proof.L_vec().len()
+    }
+
+    /// Returns the size in bytes required to serialize the range proof.
+    ///
+    /// For an aggregated proof of \\(m\\) values of \\(n\\) bits
+    /// each, the proof size is \\(32 \cdot (2\lg(nm) + 9)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        RANGE_HEADER_ELEMENTS * ELEMENT_BYTES + self.ipp_proof.serialized_size()
+    }
+
+    /// Returns the size in bytes of an aggregated proof of \(m\)
+    /// values of \(n\) bits each, as a constant expression that can
+    /// be used as an array length.
+    ///
+    /// The result is only meaningful for valid statements, where
+    /// \(nm\) is a power of two.
+    pub const fn size_const(n: usize, m: usize) -> usize {
+        let lg_nm = (n * m).trailing_zeros() as usize;
+        (RANGE_HEADER_ELEMENTS + 2 * lg_nm + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES
+    }
+
+    /// Returns the SHA3-256 hash of the serialized proof, for
+    /// correlating log entries without logging the whole proof.
+    pub fn fingerprint(&self) -> [u8; 32] {
+        Sha3_256::digest(self.to_bytes()).into()
+    }
+
+    /// Serializes the proof into a byte array of \\(2 \lg n + 9\\)
+    /// 32-byte elements, where \\(n\\) is the number of secret bits.
+    ///
+    /// # Layout
+    ///
+    /// The layout of the range proof encoding is:
+    ///
+    /// * four compressed Ristretto points \\(A,S,T_1,T_2\\),
+    /// * three scalars \\(t_x, \tilde{t}_x, \tilde{e}\\),
+    /// * \\(n\\) pairs of compressed Ristretto points \\(L_0,R_0\dots,L_{n-1},R_{n-1}\\),
+    /// * two scalars \\(a, b\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.extend(self.to_bytes_iter());
+        debug_assert!(buf.len() <= MAX_RANGE_PROOF_SIZE);
+        buf
+    }
+
+    /// Serializes the proof into an array of `SIZE` bytes without
+    /// allocating, where `SIZE` is usually given by
+    /// [`RangeProof::size_const`].  The layout is that of
+    /// [`RangeProof::to_bytes`].
+    ///
+    /// Returns [`ProofError::FormatError`] if the proof does not
+    /// serialize to exactly `SIZE` bytes.
+    pub fn to_bytes_array<const SIZE: usize>(&self) -> Result<[u8; SIZE], ProofError> {
+        if self.serialized_size() != SIZE {
+            return Err(ProofError::FormatError);
+        }
+        let mut buf = [0u8; SIZE];
+        for (byte, value) in buf.iter_mut().zip(self.to_bytes_iter()) {
+            *byte = value;
+        }
+        Ok(buf)
+    }
+
+    fn to_bytes_iter(&self) -> impl Iterator<Item = u8> + '_ {
+        self.elements().flatten().copied()
+    }
+
+    /// Returns the 32-byte elements of the encoding of the proof, in
+    /// the layout of [`RangeProof::to_bytes`].
+    fn elements(&self) -> impl Iterator<Item = &[u8; 32]> + '_ {
+        IntoIterator::into_iter([
+            self.A.as_bytes(),
+            self.S.as_bytes(),
+            self.T_1.as_bytes(),
+            self.T_2.as_bytes(),
+            self.t_x.as_bytes(),
+            self.t_x_blinding.as_bytes(),
+            self.e_blinding.as_bytes(),
+        ])
+        .chain(self.ipp_proof.elements())
+    }
+
+    /// Writes the encoding of [`RangeProof::to_bytes`] to `writer` one
+    /// 32-byte element at a time, without buffering the proof, and
+    /// returns the number of bytes written.
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, writer: W) -> std::io::Result<usize> {
+        util::write_elements(writer, self.elements())
+    }
+
+    /// Reads a proof encoded as by [`RangeProof::to_bytes`] from
+    /// `reader`, which must end where the proof ends.  To read a proof
+    /// from a longer stream, limit the reader to the size of the proof
+    /// with [`Read::take`](std::io::Read::take) and
+    /// [`RangeProof::size_const`].
+    ///
+    /// At most [`MAX_RANGE_PROOF_SIZE`] bytes plus one are read before
+    /// an oversized input is rejected.  Encodings that
+    /// [`RangeProof::from_bytes`] rejects are errors of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData).
+    #[cfg(feature = "std")]
+    pub fn read_from<R: std::io::Read>(reader: R) -> std::io::Result<RangeProof> {
+        util::read_bounded(reader, MAX_RANGE_PROOF_SIZE, RangeProof::from_bytes)
+    }
+
+    /// Returns the proof of `n`-bit values with the given value
+    /// commitments as a JSON object, for verifiers in other languages.
+    ///
+    /// The object has the fields `commitments`, the compressed value
+    /// commitments, `m`, the number of values, `n`, `proof`, the
+    /// encoding of [`RangeProof::to_bytes`], and `protocol`, the range
+    /// proof domain separator.  Byte strings are lowercase hex.  The
+    /// encoding is canonical: the keys are sorted and there is no
+    /// whitespace, so equal statements give equal strings.
+    ///
+    /// The statement is not checked; see
+    /// [`RangeProof::transcript_trace`] for how it is absorbed into the
+    /// transcript.
+    pub fn to_json_statement(
+        &self,
+        n: BitSize,
+        value_commitments: &[CompressedRistretto],
+    ) -> String {
+        use core::fmt::Write;
+        use util::Hex;
+
+        let mut json = String::from("{\"commitments\":[");
+        for (i, V) in value_commitments.iter().enumerate() {
+            if i > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, "\"{}\"", Hex(V.as_bytes()));
+        }
+        let _ = write!(
+            json,
+            "],\"m\":{},\"n\":{},\"proof\":\"{}\",\"protocol\":\"rangeproof v1\"}}",
+            value_commitments.len(),
+            n.bits(),
+            Hex(&self.to_bytes())
+        );
+        json
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns an error if the byte slice cannot be parsed into a `RangeProof`.
+    ///
+    /// Every scalar, including those of the inner-product proof, must be
+    /// canonically encoded, otherwise a `FormatError` is returned.  This
+    /// also applies to proofs deserialized with `serde`, which goes
+    /// through this function.
+    ///
+    /// Inputs longer than [`MAX_RANGE_PROOF_SIZE`] are rejected with a
+    /// `FormatError` before any parsing.
+    #[allow(clippy::erasing_op)]
+    pub fn from_bytes(slice: &[u8]) -> Result<RangeProof, ProofError> {
+        if slice.len() > MAX_RANGE_PROOF_SIZE {
+            return Err(ProofError::FormatError);
+        }
+        if !slice.len().is_multiple_of(ELEMENT_BYTES) {
+            return Err(ProofError::FormatError);
+        }
+        if slice.len() < RANGE_HEADER_ELEMENTS * ELEMENT_BYTES {
+            return Err(ProofError::FormatError);
+        }
+
+        use crate::util::read32;
+
+        let A = CompressedRistretto(read32(&slice[0 * 32..]));
+        let S = CompressedRistretto(read32(&slice[32..]));
+        let T_1 = CompressedRistretto(read32(&slice[2 * 32..]));
+        let T_2 = CompressedRistretto(read32(&slice[3 * 32..]));
+
+        let t_x = Option::from(Scalar::from_canonical_bytes(read32(&slice[4 * 32..])))
+            .ok_or(ProofError::FormatError)?;
+        let t_x_blinding = Option::from(Scalar::from_canonical_bytes(read32(&slice[5 * 32..])))
+            .ok_or(ProofError::FormatError)?;
+        let e_blinding = Option::from(Scalar::from_canonical_bytes(read32(&slice[6 * 32..])))
+            .ok_or(ProofError::FormatError)?;
+
+        let ipp_proof =
+            InnerProductProof::from_bytes(&slice[RANGE_HEADER_ELEMENTS * ELEMENT_BYTES..])?;
+
+        Ok(RangeProof {
+            A,
+            S,
+            T_1,
+            T_2,
+            t_x,
+            t_x_blinding,
+            e_blinding,
+            ipp_proof,
+        })
+    }
+}
+
+/// Checks that an aggregated proof for `m` values of `n` bits each
+/// does not exceed [`MAX_BITSIZE`] or [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH),
+/// before any size-dependent allocation or arithmetic.
+#[cfg(feature = "verifier")]
+fn check_statement_size(n: usize, m: usize) -> Result<(), ProofError> {
+    if n > MAX_BITSIZE {
+        return Err(ProofError::StatementTooLarge {
+            requested: n,
+            max: MAX_BITSIZE,
+        });
+    }
+    check_vector_length(n.saturating_mul(m))
+}
+
+/// The decompressed points of a [`RangeProof`].
+#[derive(Clone)]
+#[cfg(feature = "verifier")]
+pub(crate) struct RangeProofPoints {
+    A: RistrettoPoint,
+    S: RistrettoPoint,
+    T_1: RistrettoPoint,
+    T_2: RistrettoPoint,
+    L_vec: Vec<RistrettoPoint>,
+    R_vec: Vec<RistrettoPoint>,
+}
+
+#[cfg(feature = "verifier")]
+/// The verification equation of a range proof for \\(m\\) values of
+/// \\(n\\) bits, as the scalars of a multiscalar multiplication that
+/// must be the identity.
+pub(crate) struct VerificationTerms {
+    pub(crate) n: usize,
+    pub(crate) m: usize,
+    /// Scalar of the Pedersen generator \\(B\\).
+    pub(crate) B: Scalar,
+    /// Scalar of the Pedersen generator \\(\tilde{B}\\).
+    pub(crate) B_blinding: Scalar,
+    /// Scalars of the first \\(n\\) \\(\mathbf{G}\\) generators of
+    /// each of the first \\(m\\) parties, party by party.
+    pub(crate) g: Vec<Scalar>,
+    /// Scalars of the \\(\mathbf{H}\\) generators, as for `g`.
+    pub(crate) h: Vec<Scalar>,
+    /// Scalars of the points of the proof and of the value commitments.
+    pub(crate) scalars: Vec<Scalar>,
+    /// The points of the proof and the value commitments, which are
+    /// `None` if a commitment does not decompress.
+    pub(crate) points: Vec<Option<RistrettoPoint>>,
+}
+
+#[cfg(feature = "verifier")]
+/// One part of the verification equation of a [`RangeProof`], created
+/// by [`RangeProof::verification_shards_with_rng`].
+pub struct VerificationShard<'a> {
+    bp_gens: &'a BulletproofGens,
+    n: usize,
+    /// The parties whose generators have terms in the shard.
+    parties: Range<usize>,
+    /// Scalars of the points of the proof, the value commitments, and
+    /// the Pedersen generators, which are only in the first shard.
+    scalars: Vec<Scalar>,
+    points: Vec<Option<RistrettoPoint>>,
+    /// Scalars of the \\(\mathbf G\\) generators of the parties.
+    g: Vec<Scalar>,
+    /// Scalars of the \\(\mathbf H\\) generators of the parties.
+    h: Vec<Scalar>,
+}
+
+#[cfg(feature = "verifier")]
+impl<'a> VerificationShard<'a> {
+    /// Returns the parties whose generators have terms in the shard.
+    pub fn parties(&self) -> Range<usize> {
+        self.parties.clone()
+    }
+
+    /// Returns the number of terms of the shard.
+    pub fn len(&self) -> usize {
+        self.scalars.len() + self.g.len() + self.h.len()
+    }
+
+    /// Returns whether the shard has no terms.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Evaluates the multiscalar multiplication of the terms of the
+    /// shard.  Returns [`ProofError::VerificationError`] if a value
+    /// commitment of the first shard is not a valid point.
+    pub fn evaluate(&self) -> Result<RistrettoPoint, ProofError> {
+        // The multiplication needs exact lengths, which skipping the
+        // generators of the earlier parties preserves.
+        let skip = self.parties.start * self.n;
+        let G = self.bp_gens.G(self.n, self.parties.end).skip(skip);
+        let H = self.bp_gens.H(self.n, self.parties.end).skip(skip);
+
+        use curve25519_dalek::traits::VartimeMultiscalarMul;
+        RistrettoPoint::optional_multiscalar_mul(
+            self.scalars
+                .iter()
+                .chain(self.g.iter())
+                .chain(self.h.iter()),
+            self.points
+                .iter()
+                .cloned()
+                .chain(G.map(|&P| Some(P)))
+                .chain(H.map(|&P| Some(P))),
+        )
+        .ok_or(ProofError::VerificationError)
+    }
+}
+
+/// Options for creating a [`RangeProof`] with
+/// [`RangeProof::prove_multiple_with_options_and_rng`].
+///
+/// With `self_verify`, the prover verifies each proof against the
+/// transcript state it was created from before returning it, and
+/// fails with [`ProofError::SelfCheckFailed`] if it does not verify.
+/// This roughly doubles the cost of proving, but catches proofs broken
+/// by a faulty RNG, corrupted memory, or a value outside the range
+/// when they are created rather than when they reach a verifier.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProveOptions {
+    /// Whether to verify the proof before returning it.
+    pub self_verify: bool,
+}
+
+/// The statement certified by a successfully verified [`RangeProof`]
+/// of a single value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedRange {
+    /// The bitsize \\(n\\): the value is in \\([0, 2\^n)\\).
+    pub bits: usize,
+}
+
+/// A [`RangeProof`] together with its decompressed points, created by
+/// [`RangeProof::decompress`].
+///
+/// Verifying a `RangeProof` decompresses each of its points; a
+/// `DecompressedRangeProof` does this once, so a proof that is parsed
+/// and validated early can later be verified (possibly several times)
+/// without paying for decompression again.
+///
+/// The points are derived from the wrapped proof, which cannot be
+/// modified, and are never serialized: use
+/// [`DecompressedRangeProof::proof`] to serialize the proof.
+#[derive(Clone)]
+#[cfg(feature = "verifier")]
+pub struct DecompressedRangeProof {
+    proof: RangeProof,
+    points: RangeProofPoints,
+}
+
+#[cfg(feature = "verifier")]
+impl DecompressedRangeProof {
+    /// Returns the underlying proof.
+    pub fn proof(&self) -> &RangeProof {
+        &self.proof
+    }
+
+    /// Discards the decompressed points and returns the underlying proof.
+    pub fn into_proof(self) -> RangeProof {
+        self.proof
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the rangeproof for a given value commitment \\(V\\).
+    ///
+    /// See [`RangeProof::verify_single_with_rng`].
+    pub fn verify_single_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_multiple_with_rng(bp_gens, pc_gens, transcript, &[*V], n, rng)?;
+        Ok(VerifiedRange {
+            bits: self.proof.bits(),
+        })
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the rangeproof for a given value commitment \\(V\\).
+    /// This is a convenience wrapper around [`DecompressedRangeProof::verify_single_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_single<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        V: &CompressedRistretto,
+        n: BitSize,
+    ) -> Result<VerifiedRange, ProofError> {
+        self.verify_single_with_rng(bp_gens, pc_gens, transcript, V, n, &mut util::default_rng())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the aggregated rangeproof for the given value commitments.
+    ///
+    /// See [`RangeProof::verify_multiple_with_rng`].
+    pub fn verify_multiple_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let bp_gens = bp_gens.as_generators();
+        self.proof.verify_multiple_with_points(
+            &self.points,
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n.bits(),
+            rng,
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies the aggregated rangeproof for the given value commitments.
+    /// This is a convenience wrapper around [`DecompressedRangeProof::verify_multiple_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify_multiple<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        n: BitSize,
+    ) -> Result<(), ProofError> {
+        self.verify_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl fmt::Debug for DecompressedRangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DecompressedRangeProof")
+            .field(&self.proof)
+            .finish()
+    }
+}
+
+impl fmt::Debug for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        use crate::util::TruncatedHex;
+
+        f.debug_struct("RangeProof")
+            .field("A", &TruncatedHex(self.A.as_bytes()))
+            .field("S", &TruncatedHex(self.S.as_bytes()))
+            .field("T_1", &TruncatedHex(self.T_1.as_bytes()))
+            .field("T_2", &TruncatedHex(self.T_2.as_bytes()))
+            .field("lg_n", &self.ipp_proof.L_vec().len())
+            .field("bytes", &self.serialized_size())
+            .finish()
+    }
+}
+
+impl fmt::Display for RangeProof {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "RangeProof {{ nm={}, bytes={} }}",
+            self.ipp_proof.vector_length(),
+            self.serialized_size()
+        )
+    }
+}
+
+/// \\(t(x) = \langle \mathbf{l}(x), \mathbf{r}(x) \rangle\\) and the
+/// inner-product proof are derived from the witness vectors.
+impl NoWitnessDebug for RangeProof {
+    fn witness_derived_elements(&self) -> Vec<[u8; 32]> {
+        let mut elements = self.ipp_proof.witness_derived_elements();
+        elements.push(self.t_x.to_bytes());
+        elements
+    }
+}
+
+impl PartialEq for RangeProof {
+    fn eq(&self, other: &Self) -> bool {
+        self.to_bytes() == other.to_bytes()
+    }
+}
+
+impl Eq for RangeProof {}
+
+impl Hash for RangeProof {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.to_bytes().hash(state)
+    }
+}
+
+impl Serialize for RangeProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for RangeProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct RangeProofVisitor;
+
+        impl<'de> Visitor<'de> for RangeProofVisitor {
+            type Value = RangeProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid RangeProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<RangeProof, E>
+            where
+                E: serde::de::Error,
+            {
+                RangeProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(RangeProofVisitor)
+    }
+}
+
+#[cfg(feature = "verifier")]
+/// Compute the scalars \\(1, x, c x, c x^2\\) of the proof points
+/// \\(A, S, T\_1, T\_2\\) in the verification equation.
+fn proof_point_scalars(x: &Scalar, c: &Scalar) -> [Scalar; 4] {
+    let c_x = c * x;
+    [Scalar::ONE, *x, c_x, c_x * x]
+}
+
+#[cfg(feature = "verifier")]
+/// Compute the scalars of the generators \\({\mathbf{G}}\\) and
+/// \\({\mathbf{H}}\\) in the verification equation,
+/// \\[
+/// g\_i = -z - a g'\_i s\_i, \quad
+/// h\_i = z + h'\_i (z^{2 + \lfloor i / n \rfloor} 2^{i \bmod n} - b s\_{nm - 1 - i}),
+/// \\]
+/// where \\(s\\) is the vector of inner-product verification scalars,
+/// whose memory is reused for the \\(g\\) scalars, and \\(g'\_i\\),
+/// \\(h'\_i\\) are the `factors` of the inner-product proof, which are
+/// \\(1\\) and \\(y^{-i}\\) for [`StandardFactors`].
+fn generator_scalars<F: FactorSet>(
+    s: Vec<Scalar>,
+    a: Scalar,
+    b: Scalar,
+    factors: &F,
+    z: &Scalar,
+    n: usize,
+    m: usize,
+) -> (Vec<Scalar>, Vec<Scalar>) {
+    let mut H_factors = factors.H_factors(n * m);
+    let mut h = Vec::with_capacity(n * m);
+    let mut exp_z = z * z;
+    for j in 0..m {
+        // z^(2+j) * 2^k, doubled in place for each k.
+        let mut z_and_2 = exp_z;
+        for (k, H_factor) in (0..n).zip(&mut H_factors) {
+            let s_i_inv = s[n * m - 1 - (j * n + k)];
+            h.push(z + H_factor * (z_and_2 - b * s_i_inv));
+            z_and_2 += z_and_2;
+        }
+        exp_z *= z;
+    }
+
+    let minus_z = -z;
+    let mut g = s;
+    for (s_i, G_factor) in g.iter_mut().zip(factors.G_factors(n * m)) {
+        *s_i = minus_z - a * G_factor * *s_i;
+    }
+    (g, h)
+}
+
+#[cfg(feature = "verifier")]
+/// Compute the scalars \\(c z^{2}, \ldots, c z^{m+1}\\) of the value
+/// commitments in the verification equation.
+fn value_commitment_scalars(c: Scalar, z: Scalar, m: usize) -> impl Iterator<Item = Scalar> {
+    util::exp_iter_from(z, 2)
+        .take(m)
+        .map(move |exp_z| c * exp_z)
+}
+
+#[cfg(feature = "verifier")]
+/// Compute
+/// \\[
+/// \delta(y,z) = (z - z^{2}) \langle \mathbf{1}, {\mathbf{y}}^{n \cdot m} \rangle - \sum_{j=0}^{m-1} z^{j+3} \cdot \langle \mathbf{1}, {\mathbf{2}}^{n \cdot m} \rangle
+/// \\]
+fn delta(n: usize, m: usize, y: &Scalar, z: &Scalar) -> Scalar {
+    let sum_y = util::geometric_sum(y, n * m);
+    let sum_2 = util::geometric_sum(&Scalar::from(2u64), n);
+    let sum_z = util::geometric_sum(z, m);
+
+    (z - z * z) * sum_y - z * z * z * sum_2 * sum_z
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use core::convert::TryFrom;
+
+    use crate::generators::PedersenGens;
+
+    #[test]
+    fn test_delta() {
+        let mut rng = rand::thread_rng();
+        let y = Scalar::random(&mut rng);
+        let z = Scalar::random(&mut rng);
+
+        // Choose n = 256 to ensure we overflow the group order during
+        // the computation, to check that that's done correctly
+        let n = 256;
+
+        // code copied from previous implementation
+        let z2 = z * z;
+        let z3 = z2 * z;
+        let mut power_g = Scalar::ZERO;
+        let mut exp_y = Scalar::ONE; // start at y^0 = 1
+        let mut exp_2 = Scalar::ONE; // start at 2^0 = 1
+        for _ in 0..n {
+            power_g += (z - z2) * exp_y - z3 * exp_2;
+
+            exp_y *= y; // y^i -> y^(i+1)
+            exp_2 = exp_2 + exp_2; // 2^i -> 2^(i+1)
+        }
+
+        assert_eq!(power_g, delta(n, 1, &y, &z),);
+    }
+
+    #[test]
+    fn proof_point_scalars_match_definition() {
+        let mut rng = rand::thread_rng();
+        let x = Scalar::random(&mut rng);
+        let c = Scalar::random(&mut rng);
+
+        assert_eq!(
+            proof_point_scalars(&x, &c),
+            [Scalar::ONE, x, c * x, c * x * x]
+        );
+    }
+
+    #[test]
+    fn generator_scalars_match_definition() {
+        let mut rng = rand::thread_rng();
+        let (n, m) = (8, 4);
+        let s: Vec<Scalar> = (0..n * m).map(|_| Scalar::random(&mut rng)).collect();
+        let a = Scalar::random(&mut rng);
+        let b = Scalar::random(&mut rng);
+        let y = Scalar::random(&mut rng);
+        let z = Scalar::random(&mut rng);
+
+        let (g, h) = generator_scalars(s.clone(), a, b, &StandardFactors::new(&y), &z, n, m);
+        assert_eq!(g.len(), n * m);
+        assert_eq!(h.len(), n * m);
+        for j in 0..m {
+            for k in 0..n {
+                let i = j * n + k;
+                let z_and_2 = util::scalar_exp_vartime(&z, 2 + j as u64)
+                    * util::scalar_exp_vartime(&Scalar::from(2u64), k as u64);
+                let exp_y_inv = util::scalar_exp_vartime(&y.invert(), i as u64);
+                assert_eq!(g[i], -z - a * s[i]);
+                assert_eq!(h[i], z + exp_y_inv * (z_and_2 - b * s[n * m - 1 - i]));
+            }
+        }
+    }
+
+    #[test]
+    fn generator_scalars_scale_by_the_factors() {
+        struct Fixed<'a>(&'a [Scalar], &'a [Scalar]);
+        impl<'a> FactorSet for Fixed<'a> {
+            type GFactors = iter::Copied<std::slice::Iter<'a, Scalar>>;
+            type HFactors = iter::Copied<std::slice::Iter<'a, Scalar>>;
+
+            fn G_factors(&self, len: usize) -> Self::GFactors {
+                self.0[..len].iter().copied()
+            }
+            fn H_factors(&self, len: usize) -> Self::HFactors {
+                self.1[..len].iter().copied()
+            }
+        }
+
+        let mut rng = rand::thread_rng();
+        let (n, m) = (8, 2);
+        let mut random =
+            |len: usize| -> Vec<Scalar> { (0..len).map(|_| Scalar::random(&mut rng)).collect() };
+        let s = random(n * m);
+        let (G_factors, H_factors) = (random(n * m), random(n * m));
+        let factors = Fixed(&G_factors, &H_factors);
+        let (a, b, z) = (random(1)[0], random(1)[0], random(1)[0]);
+
+        let (g, h) = generator_scalars(s.clone(), a, b, &factors, &z, n, m);
+        let (g_1, h_1) = generator_scalars(
+            s.clone(),
+            a,
+            b,
+            &Fixed(&vec![Scalar::ONE; n * m], &vec![Scalar::ONE; n * m]),
+            &z,
+            n,
+            m,
+        );
+        for i in 0..n * m {
+            assert_eq!(g[i], -z - a * factors.0[i] * s[i]);
+            assert_eq!(g[i] + z, (g_1[i] + z) * factors.0[i]);
+            assert_eq!(h[i] - z, (h_1[i] - z) * factors.1[i]);
+        }
+    }
+
+    #[test]
+    fn value_commitment_scalars_match_definition() {
+        let mut rng = rand::thread_rng();
+        let c = Scalar::random(&mut rng);
+        let z = Scalar::random(&mut rng);
+
+        let scalars: Vec<Scalar> = value_commitment_scalars(c, z, 4).collect();
+        assert_eq!(
+            scalars,
+            vec![
+                c * z * z,
+                c * z * z * z,
+                c * z * z * z * z,
+                c * z * z * z * z * z
+            ]
+        );
+    }
+
+    /// Given a bitsize `n`, test the following:
+    ///
+    /// 1. Generate `m` random values and create a proof they are all in range;
+    /// 2. Serialize to wire format;
+    /// 3. Deserialize from wire format;
+    /// 4. Verify the proof.
+    fn singleparty_create_and_verify_helper(n: usize, m: usize) {
         // Split the test into two scopes, so that it's explicit what
         // data is shared between the prover and the verifier.
 
-        // Use bincode for serialization
-        //use bincode; // already present in lib.rs
+        // Both prover and verifier have access to the generators and the proof
+        let max_bitsize = 64;
+        let max_parties = 8;
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(max_bitsize, max_parties);
+
+        // Prover's scope
+        let (proof_bytes, value_commitments) = {
+            use self::rand::Rng;
+            let mut rng = rand::thread_rng();
+
+            // 0. Create witness data
+            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
+            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+
+            // 1. Create the proof
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+            let (proof, value_commitments) = RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::try_from(n).unwrap(),
+            )
+            .unwrap();
+
+            // 2. Return serialized proof and value commitments
+            (proof.to_bytes(), value_commitments)
+        };
+
+        // Verifier's scope
+        {
+            // 3. Deserialize
+            let proof = RangeProof::from_bytes(&proof_bytes).unwrap();
+
+            // 4. Verify with the same customization label as above
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+            assert!(proof
+                .verify_multiple(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &value_commitments,
+                    BitSize::try_from(n).unwrap()
+                )
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_1() {
+        singleparty_create_and_verify_helper(32, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_2() {
+        singleparty_create_and_verify_helper(32, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_4() {
+        singleparty_create_and_verify_helper(32, 4);
+    }
+
+    #[test]
+    fn create_and_verify_n_32_m_8() {
+        singleparty_create_and_verify_helper(32, 8);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_1() {
+        singleparty_create_and_verify_helper(64, 1);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_2() {
+        singleparty_create_and_verify_helper(64, 2);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_4() {
+        singleparty_create_and_verify_helper(64, 4);
+    }
+
+    #[test]
+    fn create_and_verify_n_64_m_8() {
+        singleparty_create_and_verify_helper(64, 8);
+    }
+
+    #[test]
+    fn detect_dishonest_party_during_aggregation() {
+        use self::dealer::*;
+        use self::party::*;
+
+        use crate::errors::MPCError;
+
+        // Simulate four parties, two of which will be dishonest and use a 64-bit value.
+        let m = 4;
+        let n = BitSize::B32;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        // Parties 0, 2 are honest and use a 32-bit value
+        let v0 = rng.gen::<u32>() as u64;
+        let v0_blinding = Scalar::random(&mut rng);
+        let party0 = Party::new(&bp_gens, &pc_gens, v0, v0_blinding, n).unwrap();
+
+        let v2 = rng.gen::<u32>() as u64;
+        let v2_blinding = Scalar::random(&mut rng);
+        let party2 = Party::new(&bp_gens, &pc_gens, v2, v2_blinding, n).unwrap();
+
+        // Parties 1, 3 are dishonest and use a 64-bit value
+        let v1 = rng.gen::<u64>();
+        let v1_blinding = Scalar::random(&mut rng);
+        let party1 = Party::new(&bp_gens, &pc_gens, v1, v1_blinding, n).unwrap();
+
+        let v3 = rng.gen::<u64>();
+        let v3_blinding = Scalar::random(&mut rng);
+        let party3 = Party::new(&bp_gens, &pc_gens, v3, v3_blinding, n).unwrap();
+
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+        let (party0, bit_com0) = party0.assign_position(0).unwrap();
+        let (party1, bit_com1) = party1.assign_position(1).unwrap();
+        let (party2, bit_com2) = party2.assign_position(2).unwrap();
+        let (party3, bit_com3) = party3.assign_position(3).unwrap();
+
+        let (dealer, bit_challenge) = dealer
+            .receive_bit_commitments(vec![bit_com0, bit_com1, bit_com2, bit_com3])
+            .unwrap();
+
+        let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
+        let (party1, poly_com1) = party1.apply_challenge(&bit_challenge);
+        let (party2, poly_com2) = party2.apply_challenge(&bit_challenge);
+        let (party3, poly_com3) = party3.apply_challenge(&bit_challenge);
+
+        let (dealer, poly_challenge) = dealer
+            .receive_poly_commitments(vec![poly_com0, poly_com1, poly_com2, poly_com3])
+            .unwrap();
+
+        let share0 = party0.apply_challenge(&poly_challenge).unwrap();
+        let share1 = party1.apply_challenge(&poly_challenge).unwrap();
+        let share2 = party2.apply_challenge(&poly_challenge).unwrap();
+        let share3 = party3.apply_challenge(&poly_challenge).unwrap();
+
+        match dealer.receive_shares(&[share0, share1, share2, share3]) {
+            Err(MPCError::MalformedProofShares { bad_shares }) => {
+                assert_eq!(bad_shares, vec![1, 3]);
+            }
+            Err(_) => {
+                panic!("Got wrong error type from malformed shares");
+            }
+            Ok(_) => {
+                panic!("The proof was malformed, but it was not detected");
+            }
+        }
+    }
+
+    #[cfg(debug_assertions)]
+    #[test]
+    fn trusted_shares_are_audited_in_debug_builds() {
+        use self::dealer::*;
+        use self::party::*;
+
+        use std::panic::{self, AssertUnwindSafe};
+
+        let m = 2;
+        let n = BitSize::B32;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        let party0 = Party::new(&bp_gens, &pc_gens, 7, Scalar::from(1u64), n).unwrap();
+        let party1 = Party::new(&bp_gens, &pc_gens, 9, Scalar::from(2u64), n).unwrap();
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+        let (party0, bit_com0) = party0.assign_position(0).unwrap();
+        let (party1, bit_com1) = party1.assign_position(1).unwrap();
+        let (dealer, bit_challenge) = dealer
+            .receive_bit_commitments(vec![bit_com0, bit_com1])
+            .unwrap();
+
+        let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
+        let (mut party1, poly_com1) = party1.apply_challenge(&bit_challenge);
+        let (dealer, poly_challenge) = dealer
+            .receive_poly_commitments(vec![poly_com0, poly_com1])
+            .unwrap();
+
+        // Party 1 computes its share with a blinding factor that does
+        // not match its bit commitment.
+        party1.perturb_a_blinding();
+        let shares = [
+            party0.apply_challenge(&poly_challenge).unwrap(),
+            party1.apply_challenge(&poly_challenge).unwrap(),
+        ];
+
+        let result =
+            panic::catch_unwind(AssertUnwindSafe(|| dealer.receive_trusted_shares(&shares)));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn detect_dishonest_dealer_during_aggregation() {
+        use self::dealer::*;
+        use self::party::*;
+        use crate::errors::MPCError;
+
+        // Simulate one party
+        let m = 1;
+        let n = BitSize::B32;
 
-        // Both prover and verifier have access to the generators and the proof
-        let max_bitsize = 64;
-        let max_parties = 8;
         let pc_gens = PedersenGens::default();
-        let bp_gens = BulletproofGens::new(max_bitsize, max_parties);
+        let bp_gens = BulletproofGens::new(n.bits(), m);
 
-        // Prover's scope
-        let (proof_bytes, value_commitments) = {
-            use self::rand::Rng;
-            let mut rng = rand::thread_rng();
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        let v0 = rng.gen::<u32>() as u64;
+        let v0_blinding = Scalar::random(&mut rng);
+        let party0 = Party::new(&bp_gens, &pc_gens, v0, v0_blinding, n).unwrap();
+
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+
+        // Now do the protocol flow as normal....
+
+        let (party0, bit_com0) = party0.assign_position(0).unwrap();
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(vec![bit_com0]).unwrap();
+
+        let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
+
+        let (_dealer, mut poly_challenge) =
+            dealer.receive_poly_commitments(vec![poly_com0]).unwrap();
+
+        // But now simulate a malicious dealer choosing x = 0
+        poly_challenge.x = Scalar::ZERO;
+
+        let maybe_share0 = party0.apply_challenge(&poly_challenge);
+
+        assert!(maybe_share0.unwrap_err() == MPCError::MaliciousDealer);
+    }
+
+    #[test]
+    fn detect_duplicate_positions_during_aggregation() {
+        use self::dealer::*;
+        use self::party::*;
+        use crate::errors::MPCError;
+
+        let m = 4;
+        let n = BitSize::B8;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        // Parties 1 and 2 were both assigned position 1.
+        let bit_commitments = [0, 1, 1, 3]
+            .iter()
+            .map(|&j| {
+                let party = Party::new(&bp_gens, &pc_gens, 1, Scalar::ONE, n).unwrap();
+                party.assign_position(j).unwrap().1
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(bit_commitments[2].position(), 1);
+
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        assert_eq!(
+            dealer.receive_bit_commitments(bit_commitments).err(),
+            Some(MPCError::MismatchedPositions {
+                bad_positions: vec![2]
+            })
+        );
+    }
+
+    #[test]
+    fn aggregation_proceeds_without_missing_party() {
+        use self::dealer::*;
+        use self::party::*;
+        use curve25519_dalek::traits::Identity;
+
+        let m = 4;
+        let n = BitSize::B32;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+
+        use self::rand::Rng;
+        let mut rng = rand::thread_rng();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+
+        let values: Vec<u64> = (0..m).map(|_| rng.gen::<u32>() as u64).collect();
+        let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = values
+            .iter()
+            .zip(blindings.iter())
+            .enumerate()
+            .map(|(j, (&v, &v_blinding))| {
+                Party::new(&bp_gens, &pc_gens, v, v_blinding, n)
+                    .unwrap()
+                    .assign_position(j)
+                    .unwrap()
+            })
+            .unzip();
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+
+        // Party 2 drops out after sending its bit commitment.
+        let parties: Vec<_> = parties
+            .into_iter()
+            .enumerate()
+            .filter(|&(j, _)| j != 2)
+            .map(|(_, p)| p.apply_challenge(&bit_challenge).0)
+            .collect();
+
+        let (dealer, positions) = dealer.proceed_without(&[2]).unwrap();
+        assert_eq!(positions, vec![0, 1, 3]);
+
+        // The remaining parties start over at their new positions, and
+        // the fourth position is filled with a zero-value party.
+        let filler = Party::new(&bp_gens, &pc_gens, 0, Scalar::ZERO, n).unwrap();
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .enumerate()
+            .map(|(i, p)| p.reassign_position(&bp_gens, &pc_gens, i).unwrap())
+            .chain(iter::once(filler.assign_position(3).unwrap()))
+            .unzip();
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|c| c.V_j).collect();
+        assert_eq!(value_commitments[3], CompressedRistretto::identity());
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&bit_challenge))
+            .unzip();
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments).unwrap();
+        let proof_shares: Vec<_> = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&poly_challenge).unwrap())
+            .collect();
+        let proof = dealer.receive_shares(&proof_shares).unwrap();
+
+        for (i, &j) in positions.iter().enumerate() {
+            assert!(pc_gens.verify_opening(&value_commitments[i], values[j], blindings[j]));
+        }
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+    }
+
+    #[test]
+    fn dealer_rejects_invalid_missing_parties() {
+        use self::dealer::*;
+        use self::party::*;
+        use crate::errors::MPCError;
+
+        let (n, m) = (BitSize::B8, 2);
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+
+        fn dealer_awaiting_poly_commitments<'a, 'b>(
+            bp_gens: &'b BulletproofGens,
+            pc_gens: &'b PedersenGens,
+            transcript: &'a mut Transcript,
+            n: BitSize,
+            m: usize,
+        ) -> DealerAwaitingPolyCommitments<'a, 'b> {
+            let dealer = Dealer::new(bp_gens, pc_gens, transcript, n, m).unwrap();
+            let bit_commitments = (0..m)
+                .map(|j| {
+                    let party = Party::new(bp_gens, pc_gens, 1, Scalar::ONE, n).unwrap();
+                    party.assign_position(j).unwrap().1
+                })
+                .collect();
+            dealer.receive_bit_commitments(bit_commitments).unwrap().0
+        }
+
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        for missing in [&[2][..], &[0, 0][..], &[0, 1][..]] {
+            let dealer =
+                dealer_awaiting_poly_commitments(&bp_gens, &pc_gens, &mut transcript, n, m);
+            assert_eq!(
+                dealer.proceed_without(missing).err(),
+                Some(MPCError::InvalidMissingParties {
+                    missing: missing.to_vec()
+                })
+            );
+        }
+
+        // Aborting restores the transcript.
+        let mut expected = Transcript::new(b"AggregatedRangeProofTest");
+        let dealer = dealer_awaiting_poly_commitments(&bp_gens, &pc_gens, &mut transcript, n, m);
+        assert_eq!(
+            dealer.abort("party 1 timed out"),
+            MPCError::Aborted {
+                reason: "party 1 timed out".into()
+            }
+        );
+        assert_eq!(
+            transcript.challenge_scalar(b"test"),
+            expected.challenge_scalar(b"test")
+        );
+    }
+
+    #[test]
+    fn repeated_commitments_verify() {
+        // Equal values under equal blindings give equal commitments,
+        // which an aggregated proof treats as distinct parties.
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 4);
+        let values = [1037u64; 4];
+        let blindings = [Scalar::from(7u64); 4];
+
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let (proof, value_commitments) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &values,
+            &blindings,
+            BitSize::B32,
+        )
+        .unwrap();
+        assert!(value_commitments.iter().all(|V| *V == value_commitments[0]));
+
+        let verify = |value_commitments: &[CompressedRistretto]| {
+            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+            proof.verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                value_commitments,
+                BitSize::B32,
+            )
+        };
+        assert!(verify(&value_commitments).is_ok());
+
+        // Replacing one of the repeats by another commitment, or
+        // repeating fewer of them, fails.
+        let mut other = value_commitments.clone();
+        other[3] = pc_gens
+            .commit(Scalar::from(1037u64), Scalar::from(8u64))
+            .compress();
+        assert!(verify(&other).is_err());
+        assert!(verify(&value_commitments[..2]).is_err());
+    }
+
+    #[test]
+    fn party_resumes_after_crash() {
+        use self::dealer::*;
+        use self::party::*;
+
+        let (m, n) = (2, BitSize::B32);
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+        let values = [7u64, 1 << 31];
+        let blindings = [Scalar::from(3u64), Scalar::from(5u64)];
+
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        let (party_0, bit_commitment_0) =
+            Party::new(&bp_gens, &pc_gens, values[0], blindings[0], n)
+                .unwrap()
+                .assign_position(0)
+                .unwrap();
+
+        // Party 1 persists its state before committing to its bits,
+        // and crashes after sending the bit commitment.
+        let mut rng = rand::thread_rng();
+        let state = ResumableRng::from_rng(&mut rng).state();
+        let party_1 = PartyAwaitingPosition::new_with_rng_state(
+            &bp_gens,
+            &pc_gens,
+            values[1],
+            blindings[1],
+            n,
+            &state,
+        )
+        .unwrap();
+        let saved = party_1.rng_state().unwrap().to_bytes();
+        let (crashed, bit_commitment_1) = party_1.assign_position(1).unwrap();
+        let crashed_state = crashed.rng_state().unwrap();
+        drop(crashed);
+
+        let (dealer, bit_challenge) = dealer
+            .receive_bit_commitments(vec![bit_commitment_0, bit_commitment_1])
+            .unwrap();
+
+        // The party is reconstructed from its saved state, and replays
+        // the bit commitment it already sent.
+        let state = ResumableRngState::from_bytes(&saved).unwrap();
+        let (party_1, replayed) = PartyAwaitingPosition::new_with_rng_state(
+            &bp_gens,
+            &pc_gens,
+            values[1],
+            blindings[1],
+            n,
+            &state,
+        )
+        .unwrap()
+        .assign_position(1)
+        .unwrap();
+        assert_eq!(replayed.A_j, bit_commitment_1.A_j);
+        assert_eq!(replayed.S_j, bit_commitment_1.S_j);
+        assert!(party_1.rng_state().unwrap() == crashed_state);
+
+        let (party_0, poly_commitment_0) = party_0.apply_challenge(&bit_challenge);
+        let (party_1, poly_commitment_1) = party_1.apply_challenge(&bit_challenge);
+        assert!(party_1.rng_state().unwrap() != crashed_state);
+        let (dealer, poly_challenge) = dealer
+            .receive_poly_commitments(vec![poly_commitment_0, poly_commitment_1])
+            .unwrap();
+        let proof_shares = [
+            party_0.apply_challenge(&poly_challenge).unwrap(),
+            party_1.apply_challenge(&poly_challenge).unwrap(),
+        ];
+        let proof = dealer.receive_shares(&proof_shares).unwrap();
+
+        let value_commitments: Vec<_> = values
+            .iter()
+            .zip(blindings.iter())
+            .map(|(&v, &v_blinding)| pc_gens.commit(v.into(), v_blinding).compress())
+            .collect();
+        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
+            .is_ok());
+    }
+
+    #[test]
+    fn associated_data_binds_proof() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+        let v_blinding = Scalar::random(&mut rng);
+
+        let (proof, V) = RangeProof::prove_single_with_ad(
+            &bp_gens,
+            &pc_gens,
+            b"AssociatedDataTest",
+            b"txA",
+            1037578891u64,
+            &v_blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_single_with_ad(
+                &bp_gens,
+                &pc_gens,
+                b"AssociatedDataTest",
+                b"txA",
+                &V,
+                BitSize::B32
+            )
+            .is_ok());
+        assert!(proof
+            .verify_single_with_ad(
+                &bp_gens,
+                &pc_gens,
+                b"AssociatedDataTest",
+                b"txB",
+                &V,
+                BitSize::B32
+            )
+            .is_err());
+    }
+
+    #[test]
+    fn empty_associated_data_is_distinct_from_none() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+        let v_blinding = Scalar::random(&mut rng);
+
+        let (proof, V) = RangeProof::prove_single_with_ad(
+            &bp_gens,
+            &pc_gens,
+            b"AssociatedDataTest",
+            b"",
+            1037578891u64,
+            &v_blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+
+        assert!(proof
+            .verify_single_with_ad(
+                &bp_gens,
+                &pc_gens,
+                b"AssociatedDataTest",
+                b"",
+                &V,
+                BitSize::B32
+            )
+            .is_ok());
+        let mut transcript = Transcript::new(b"AssociatedDataTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B32)
+            .is_err());
+    }
+
+    #[test]
+    fn proof_is_bound_to_pedersen_gens() {
+        use sha3::Sha3_512;
+
+        let default_gens = PedersenGens::default();
+        let custom_gens = PedersenGens {
+            B: RistrettoPoint::hash_from_bytes::<Sha3_512>(b"custom B"),
+            ..default_gens
+        };
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+        let v_blinding = Scalar::random(&mut rng);
+
+        let mut transcript = Transcript::new(b"PedersenGensTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &custom_gens,
+            &mut transcript,
+            1037578891u64,
+            &v_blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"PedersenGensTest");
+        assert!(proof
+            .verify_single(&bp_gens, &custom_gens, &mut transcript, &V, BitSize::B32)
+            .is_ok());
+        let mut transcript = Transcript::new(b"PedersenGensTest");
+        assert!(proof
+            .verify_single(&bp_gens, &default_gens, &mut transcript, &V, BitSize::B32)
+            .unwrap_err()
+            .is_verification_failure());
+    }
+
+    #[test]
+    fn proof_is_bound_to_asset() {
+        use sha3::Sha3_512;
+
+        let asset_gens = |label: &[u8]| {
+            PedersenGens::with_value_generator(RistrettoPoint::hash_from_bytes::<Sha3_512>(label))
+                .unwrap()
+        };
+        let (gens_a, gens_b) = (asset_gens(b"asset A"), asset_gens(b"asset B"));
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+        let v_blinding = Scalar::random(&mut rng);
+
+        let mut transcript = Transcript::new(b"AssetTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &gens_a,
+            &mut transcript,
+            1000,
+            &v_blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"AssetTest");
+        assert!(proof
+            .verify_single(&bp_gens, &gens_a, &mut transcript, &V, BitSize::B32)
+            .is_ok());
+        let mut transcript = Transcript::new(b"AssetTest");
+        assert!(proof
+            .verify_single(&bp_gens, &gens_b, &mut transcript, &V, BitSize::B32)
+            .unwrap_err()
+            .is_verification_failure());
+    }
+
+    #[test]
+    fn noncanonical_scalars_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
+        let mut rng = rand::thread_rng();
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+
+        let mut transcript = Transcript::new(b"NoncanonicalScalarTest");
+        let (proof, _) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[7, 1 << 20],
+            &blindings,
+            BitSize::B32,
+        )
+        .unwrap();
+
+        // t_x, t_x_blinding, e_blinding, and the IPP's a and b.
+        let bytes = proof.to_bytes();
+        let offsets = [4 * 32, 5 * 32, 6 * 32, bytes.len() - 64, bytes.len() - 32];
+        util::assert_noncanonical_scalars_rejected(&bytes, &offsets, RangeProof::from_bytes);
+    }
+
+    #[test]
+    fn verify_and_digest_binds_statement() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
+        let mut rng = rand::thread_rng();
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+
+        let prove = |values: &[u64]| {
+            let mut transcript = Transcript::new(b"DigestTest");
+            RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                values,
+                &blindings,
+                BitSize::B32,
+            )
+            .unwrap()
+        };
+        let digest = |proof: &RangeProof, Vs: &[CompressedRistretto]| {
+            let mut transcript = Transcript::new(b"DigestTest");
+            proof.verify_and_digest(&bp_gens, &pc_gens, &mut transcript, Vs, BitSize::B32)
+        };
+
+        let (proof_a, Vs_a) = prove(&[1, 2]);
+        let (proof_b, Vs_b) = prove(&[1, 2]);
+        let (proof_c, Vs_c) = prove(&[3, 4]);
+
+        // Deterministic for the same proof and statement.
+        let digest_a = digest(&proof_a, &Vs_a).unwrap();
+        assert_eq!(digest_a, digest(&proof_a, &Vs_a).unwrap());
+
+        // Different proofs of the same statement, and proofs of
+        // different statements, have different digests.
+        assert_ne!(digest_a, digest(&proof_b, &Vs_b).unwrap());
+        assert_ne!(digest_a, digest(&proof_c, &Vs_c).unwrap());
+
+        // No digest is produced for a proof that does not verify.
+        assert!(digest(&proof_a, &Vs_c)
+            .unwrap_err()
+            .is_verification_failure());
+    }
+
+    #[test]
+    fn retry_after_failed_verification() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let mut rng = rand::thread_rng();
+
+        let prove = |blinding: Scalar| {
+            let mut transcript = Transcript::new(b"RetryTest");
+            RangeProof::prove_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                7,
+                &blinding,
+                BitSize::B32,
+            )
+            .unwrap()
+        };
+        let (proof_a, _) = prove(Scalar::random(&mut rng));
+        let (proof_b, V) = prove(Scalar::random(&mut rng));
+
+        // A failed verification leaves the transcript as it was, so the
+        // same transcript can be used to verify another proof.
+        let mut transcript = Transcript::new(b"RetryTest");
+        assert!(proof_a
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B32)
+            .is_err());
+        assert!(proof_b
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B32)
+            .is_ok());
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn oversized_statements_are_rejected() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+
+        for &n in &[usize::MAX, 1 << 40, MAX_BITSIZE + 1] {
+            let mut transcript = Transcript::new(b"StatementTooLargeTest");
+            assert_eq!(
+                RangeProof::prove_single_usize(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    7,
+                    &blinding,
+                    n
+                )
+                .map(|_| ()),
+                Err(ProofError::StatementTooLarge {
+                    requested: n,
+                    max: MAX_BITSIZE,
+                })
+            );
+        }
+
+        let mut transcript = Transcript::new(b"StatementTooLargeTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+        let mut transcript = Transcript::new(b"StatementTooLargeTest");
+        assert_eq!(
+            proof.verify_single_usize(&bp_gens, &pc_gens, &mut transcript, &V, 1 << 40),
+            Err(ProofError::StatementTooLarge {
+                requested: 1 << 40,
+                max: MAX_BITSIZE,
+            })
+        );
+    }
+
+    #[test]
+    fn equality_is_bytewise() {
+        use std::collections::HashSet;
+
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut rng = rand::thread_rng();
+        let proofs: Vec<RangeProof> = (0..10u64)
+            .map(|v| {
+                let mut transcript = Transcript::new(b"EqualityTest");
+                let blinding = Scalar::random(&mut rng);
+                RangeProof::prove_single(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    v,
+                    &blinding,
+                    BitSize::B8,
+                )
+                .unwrap()
+                .0
+            })
+            .collect();
+
+        let bytes = proofs[0].to_bytes();
+        assert_eq!(RangeProof::from_bytes(&bytes).unwrap(), proofs[0]);
+        for i in 0..bytes.len() {
+            let mut tampered = bytes.clone();
+            tampered[i] ^= 1;
+            if let Ok(tampered) = RangeProof::from_bytes(&tampered) {
+                assert_ne!(tampered, proofs[0]);
+            }
+        }
+
+        let copies: HashSet<RangeProof> = (0..1000)
+            .map(|i| RangeProof::from_bytes(&proofs[i % 10].to_bytes()).unwrap())
+            .collect();
+        assert_eq!(copies.len(), 10);
+        assert!(proofs.iter().all(|proof| copies.contains(proof)));
+    }
 
-            // 0. Create witness data
-            let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
-            let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
-            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+    #[test]
+    fn const_sizes_match_runtime_sizes() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let mut rng = rand::thread_rng();
 
-            // 1. Create the proof
-            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
-            let (proof, value_commitments) = RangeProof::prove_multiple(
+        for &(n, m) in &[(8, 1), (8, 2), (32, 2), (64, 1)] {
+            let values: Vec<u64> = (0..m as u64).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+            let mut transcript = Transcript::new(b"ConstSizeTest");
+            let (proof, _) = RangeProof::prove_multiple(
                 &bp_gens,
                 &pc_gens,
                 &mut transcript,
                 &values,
                 &blindings,
-                n,
+                BitSize::try_from(n).unwrap(),
             )
             .unwrap();
+            assert_eq!(RangeProof::size_const(n, m), proof.serialized_size());
+            assert_eq!(RangeProof::size_const(n, m), proof.to_bytes().len());
+        }
 
-            // 2. Return serialized proof and value commitments
-            (bincode::serialize(&proof).unwrap(), value_commitments)
-        };
+        const SIZE: usize = RangeProof::size_const(64, 1);
+        let mut transcript = Transcript::new(b"ConstSizeTest");
+        let blinding = Scalar::random(&mut rng);
+        let (proof, _) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            7,
+            &blinding,
+            BitSize::B64,
+        )
+        .unwrap();
+        let bytes: [u8; SIZE] = proof.to_bytes_array().unwrap();
+        assert_eq!(&bytes[..], &proof.to_bytes()[..]);
+        assert_eq!(
+            proof.to_bytes_array::<{ RangeProof::size_const(32, 1) }>(),
+            Err(ProofError::FormatError)
+        );
+    }
 
-        // Verifier's scope
-        {
-            // 3. Deserialize
-            let proof: RangeProof = bincode::deserialize(&proof_bytes).unwrap();
+    #[test]
+    fn shards_sum_to_the_verification_equation() {
+        use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+        use rand_chacha::ChaChaRng;
+        use rand_core::SeedableRng;
 
-            // 4. Verify with the same customization label as above
-            let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let (n, m) = (BitSize::B16, 8);
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(n.bits(), m);
+        let mut rng = rand::thread_rng();
+        let values: Vec<u64> = (0..m as u64).map(|j| 1000 * j).collect();
+        let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+        let mut transcript = Transcript::new(b"ShardTest");
+        let (proof, mut V) =
+            RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript, &values, &blindings, n)
+                .unwrap();
 
-            assert!(proof
-                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &value_commitments, n)
-                .is_ok());
+        // The monolithic equation and its shards, with the same weight.
+        let monolithic = |V: &[CompressedRistretto]| {
+            let terms = proof
+                .verification_terms(
+                    &proof.decompress_points().unwrap(),
+                    &bp_gens,
+                    &pc_gens,
+                    &mut Transcript::new(b"ShardTest"),
+                    V,
+                    n.bits(),
+                    &mut ChaChaRng::from_seed([7u8; 32]),
+                )
+                .unwrap();
+            RistrettoPoint::optional_multiscalar_mul(
+                terms
+                    .scalars
+                    .into_iter()
+                    .chain([terms.B_blinding, terms.B])
+                    .chain(terms.g)
+                    .chain(terms.h),
+                terms
+                    .points
+                    .into_iter()
+                    .chain([Some(pc_gens.B_blinding), Some(pc_gens.B)])
+                    .chain(bp_gens.G(n.bits(), m).map(|&P| Some(P)))
+                    .chain(bp_gens.H(n.bits(), m).map(|&P| Some(P))),
+            )
+            .unwrap()
+        };
+        let sharded = |V: &[CompressedRistretto], num_shards: usize| {
+            let shards = proof
+                .verification_shards_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut Transcript::new(b"ShardTest"),
+                    V,
+                    n,
+                    num_shards,
+                    &mut ChaChaRng::from_seed([7u8; 32]),
+                )
+                .unwrap();
+            assert_eq!(shards.len(), num_shards.clamp(1, m));
+            assert_eq!(shards.iter().map(|s| s.parties().len()).sum::<usize>(), m);
+            shards
+                .iter()
+                .map(|s| s.evaluate().unwrap())
+                .sum::<RistrettoPoint>()
+        };
+
+        for &valid in &[true, false] {
+            if !valid {
+                V.swap(0, 1);
+            }
+            let expected = monolithic(&V);
+            assert_eq!(expected.is_identity(), valid);
+            for num_shards in [0, 1, 2, 3, 5, 8, 100] {
+                assert_eq!(sharded(&V, num_shards), expected);
+            }
+            let mut transcript = Transcript::new(b"ShardTest");
+            assert_eq!(
+                proof
+                    .verify_multiple_sharded(&bp_gens, &pc_gens, &mut transcript, &V, n, 3)
+                    .is_ok(),
+                valid
+            );
         }
     }
 
     #[test]
-    fn create_and_verify_n_32_m_1() {
-        singleparty_create_and_verify_helper(32, 1);
-    }
+    fn app_transcripts_are_not_interchangeable() {
+        use crate::app_transcript;
 
-    #[test]
-    fn create_and_verify_n_32_m_2() {
-        singleparty_create_and_verify_helper(32, 2);
-    }
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::random(&mut rand::thread_rng());
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut app_transcript(b"wallet", 1),
+            7,
+            &blinding,
+            BitSize::B32,
+        )
+        .unwrap();
 
-    #[test]
-    fn create_and_verify_n_32_m_4() {
-        singleparty_create_and_verify_helper(32, 4);
+        let verify = |mut transcript: Transcript| {
+            proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B32)
+        };
+        assert!(verify(app_transcript(b"wallet", 1)).is_ok());
+        // Another application, or another version of the same one.
+        assert!(verify(app_transcript(b"exchange", 1)).is_err());
+        assert!(verify(app_transcript(b"wallet", 2)).is_err());
+        // A plain transcript with the application label is different too.
+        assert!(verify(Transcript::new(b"wallet")).is_err());
     }
 
     #[test]
-    fn create_and_verify_n_32_m_8() {
-        singleparty_create_and_verify_helper(32, 8);
-    }
+    fn precomputed_proofs_verify() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 4);
+        let mut rng = rand::thread_rng();
+        let values = [0u64, 1, 7, u32::MAX as u64];
+        let blindings: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
 
-    #[test]
-    fn create_and_verify_n_64_m_1() {
-        singleparty_create_and_verify_helper(64, 1);
+        let precomp = RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B32, 4).unwrap();
+        assert_eq!(
+            (precomp.bitsize(), precomp.aggregation_size()),
+            (BitSize::B32, 4)
+        );
+        let mut transcript = Transcript::new(b"PrecompTest");
+        let (proof, commitments) = precomp
+            .finish(&mut transcript, &values, &blindings)
+            .unwrap();
+        let mut transcript = Transcript::new(b"PrecompTest");
+        assert!(proof
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitments,
+                BitSize::B32
+            )
+            .is_ok());
+
+        // The precomputation is for a fixed statement size.
+        let precomp = RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B32, 4).unwrap();
+        assert_eq!(
+            precomp
+                .finish(&mut transcript, &values[..2], &blindings[..2])
+                .unwrap_err(),
+            ProofError::InvalidAggregation
+        );
+        let precomp = RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B32, 4).unwrap();
+        assert_eq!(
+            precomp
+                .finish(&mut transcript, &values, &blindings[..3])
+                .unwrap_err(),
+            ProofError::WrongNumBlindingFactors
+        );
+        assert_eq!(
+            RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B32, 3).err(),
+            Some(ProofError::InvalidAggregation)
+        );
+        assert_eq!(
+            RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B64, 4).err(),
+            Some(ProofError::InvalidGeneratorsLength)
+        );
     }
 
     #[test]
-    fn create_and_verify_n_64_m_2() {
-        singleparty_create_and_verify_helper(64, 2);
+    fn largest_proofs_fit_the_maximum_size() {
+        // 2 lg(2^31) + 9 elements of 32 bytes.
+        assert_eq!(MAX_RANGE_PROOF_SIZE, 71 * 32);
+
+        // The largest statement that can be proven here: 64 bits for
+        // each of 16 values.
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 16);
+        let mut rng = rand::thread_rng();
+        let values: Vec<u64> = (0..16).map(|j| u64::MAX - j).collect();
+        let blindings: Vec<Scalar> = (0..16).map(|_| Scalar::random(&mut rng)).collect();
+        let mut transcript = Transcript::new(b"MaxSizeTest");
+        let (proof, _) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &values,
+            &blindings,
+            BitSize::B64,
+        )
+        .unwrap();
+        assert!(proof.to_bytes().len() <= MAX_RANGE_PROOF_SIZE);
+
+        // The largest supported encoding parses, and reserializes to
+        // the maximum size.
+        let largest = RangeProof::from_bytes(&[0u8; MAX_RANGE_PROOF_SIZE]).unwrap();
+        assert_eq!(largest.to_bytes().len(), MAX_RANGE_PROOF_SIZE);
+        assert_eq!(largest.bits(), MAX_VECTOR_LENGTH);
+
+        // Anything longer is rejected without being parsed, even if
+        // it is well formed up to its length.
+        for len in [MAX_RANGE_PROOF_SIZE + 1, MAX_RANGE_PROOF_SIZE + 64, 1 << 20] {
+            assert_eq!(
+                RangeProof::from_bytes(&vec![0u8; len]).err(),
+                Some(ProofError::FormatError)
+            );
+        }
     }
 
     #[test]
-    fn create_and_verify_n_64_m_4() {
-        singleparty_create_and_verify_helper(64, 4);
+    fn verification_returns_the_certified_bitsize() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
+        let mut rng = rand::thread_rng();
+
+        for &bit_size in &BitSize::ALL {
+            let n = bit_size.bits();
+            let mut transcript = Transcript::new(b"VerifiedRangeTest");
+            let (proof, V) = RangeProof::prove_single(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                3,
+                &Scalar::random(&mut rng),
+                bit_size,
+            )
+            .unwrap();
+            assert_eq!(proof.bits(), n);
+
+            let mut transcript = Transcript::new(b"VerifiedRangeTest");
+            assert_eq!(
+                proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, bit_size),
+                Ok(VerifiedRange { bits: n })
+            );
+            let mut transcript = Transcript::new(b"VerifiedRangeTest");
+            assert_eq!(
+                proof.decompress().unwrap().verify_single(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &V,
+                    bit_size
+                ),
+                Ok(VerifiedRange { bits: n })
+            );
+        }
+
+        let mut transcript = Transcript::new(b"VerifiedRangeTest");
+        let (proof, _) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[1, 2],
+            &[Scalar::random(&mut rng), Scalar::random(&mut rng)],
+            BitSize::B32,
+        )
+        .unwrap();
+        assert_eq!(proof.bits(), 64);
     }
 
     #[test]
-    fn create_and_verify_n_64_m_8() {
-        singleparty_create_and_verify_helper(64, 8);
+    fn self_verification_catches_invalid_witnesses() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 2);
+        let blindings = [Scalar::ONE, Scalar::from(2u64)];
+        let options = ProveOptions { self_verify: true };
+
+        let mut transcript = Transcript::new(b"SelfCheckTest");
+        let (proof, V) = RangeProof::prove_multiple_with_options(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[3, 255],
+            &blindings,
+            BitSize::B8,
+            &options,
+        )
+        .unwrap();
+        let mut transcript = Transcript::new(b"SelfCheckTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B8)
+            .is_ok());
+
+        // The second value does not fit in 8 bits.
+        let mut transcript = Transcript::new(b"SelfCheckTest");
+        assert_eq!(
+            RangeProof::prove_multiple_with_options(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[3, 256],
+                &blindings,
+                BitSize::B8,
+                &options,
+            ),
+            Err(ProofError::SelfCheckFailed)
+        );
+
+        // Without the self-check, the invalid proof is only caught by
+        // the verifier.
+        let mut transcript = Transcript::new(b"SelfCheckTest");
+        let (proof, V) = RangeProof::prove_multiple_with_options(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[3, 256],
+            &blindings,
+            BitSize::B8,
+            &ProveOptions::default(),
+        )
+        .unwrap();
+        let mut transcript = Transcript::new(b"SelfCheckTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B8)
+            .unwrap_err()
+            .is_verification_failure());
     }
 
     #[test]
-    fn detect_dishonest_party_during_aggregation() {
-        use self::dealer::*;
-        use self::party::*;
+    fn proofs_do_not_carry_over_to_rerandomized_commitments() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let blinding = Scalar::from(7u64);
+        let shift = Scalar::from(1000u64);
 
-        use crate::errors::MPCError;
+        let mut transcript = Transcript::new(b"RerandomizeTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            77,
+            &blinding,
+            BitSize::B32,
+        )
+        .unwrap();
+        let V_prime = (V.decompress().unwrap() + shift * pc_gens.B_blinding).compress();
 
-        // Simulate four parties, two of which will be dishonest and use a 64-bit value.
-        let m = 4;
-        let n = 32;
+        // Absorbing the change in the blinding of t(x) with the
+        // challenge z of the original commitment does not help, since
+        // the new commitment changes the challenges.
+        let (_, z, _) = proof
+            .replay_poly_challenges(
+                &bp_gens,
+                &pc_gens,
+                &mut Transcript::new(b"RerandomizeTest"),
+                &[V],
+                32,
+            )
+            .unwrap();
+        let mut adjusted = proof.clone();
+        adjusted.t_x_blinding += z * z * shift;
+        for proof in [&proof, &adjusted] {
+            let mut transcript = Transcript::new(b"RerandomizeTest");
+            assert!(proof
+                .verify_single(&bp_gens, &pc_gens, &mut transcript, &V_prime, BitSize::B32)
+                .unwrap_err()
+                .is_verification_failure());
+        }
+
+        // A new proof with the shifted blinding factor commits to V'.
+        let mut transcript = Transcript::new(b"RerandomizeTest");
+        let (proof, V_new) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            77,
+            &(blinding + shift),
+            BitSize::B32,
+        )
+        .unwrap();
+        assert_eq!(V_new, V_prime);
+        let mut transcript = Transcript::new(b"RerandomizeTest");
+        assert!(proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &V_prime, BitSize::B32)
+            .is_ok());
+
+        // Knowing only the shift is enough to link V and V'.
+        let link = crate::BalanceProof::prove(
+            &pc_gens,
+            &mut Transcript::new(b"RerandomizeTest"),
+            &[Scalar::ZERO],
+            &[shift],
+        );
+        assert!(link
+            .verify(
+                &pc_gens,
+                &mut Transcript::new(b"RerandomizeTest"),
+                &[V],
+                &[V_prime]
+            )
+            .is_ok());
+    }
 
+    #[test]
+    fn diagnostics_name_the_failing_check() {
         let pc_gens = PedersenGens::default();
-        let bp_gens = BulletproofGens::new(n, m);
+        let bp_gens = BulletproofGens::new(8, 2);
+        let blindings = [Scalar::ONE, Scalar::from(2u64)];
 
-        use self::rand::Rng;
-        let mut rng = rand::thread_rng();
-        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let prove = |values: &[u64]| {
+            let mut transcript = Transcript::new(b"DiagnosticsTest");
+            RangeProof::prove_multiple_with_options(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                values,
+                &blindings,
+                BitSize::B8,
+                &ProveOptions::default(),
+            )
+            .unwrap()
+        };
+        let diagnose = |proof: &RangeProof, V: &[CompressedRistretto]| {
+            let points = proof.decompress_points().unwrap();
+            let transcript = Transcript::new(b"DiagnosticsTest");
+            proof.diagnose(&points, &bp_gens, &pc_gens, &transcript, V, 8)
+        };
+        let failed = |polynomial, inner_product| ProofError::FailedChecks {
+            polynomial,
+            inner_product,
+        };
 
-        // Parties 0, 2 are honest and use a 32-bit value
-        let v0 = rng.gen::<u32>() as u64;
-        let v0_blinding = Scalar::random(&mut rng);
-        let party0 = Party::new(&bp_gens, &pc_gens, v0, v0_blinding, n).unwrap();
+        let (proof, V) = prove(&[3, 255]);
+        assert_eq!(diagnose(&proof, &V), failed(false, false));
 
-        let v2 = rng.gen::<u32>() as u64;
-        let v2_blinding = Scalar::random(&mut rng);
-        let party2 = Party::new(&bp_gens, &pc_gens, v2, v2_blinding, n).unwrap();
+        // Changing t(x) also changes the challenges of the
+        // inner-product argument.
+        let mut bad = proof.clone();
+        bad.t_x += Scalar::ONE;
+        assert_eq!(diagnose(&bad, &V), failed(true, true));
 
-        // Parties 1, 3 are dishonest and use a 64-bit value
-        let v1 = rng.gen::<u64>();
-        let v1_blinding = Scalar::random(&mut rng);
-        let party1 = Party::new(&bp_gens, &pc_gens, v1, v1_blinding, n).unwrap();
+        let mut bad = proof.clone();
+        bad.e_blinding += Scalar::ONE;
+        assert_eq!(diagnose(&bad, &V), failed(false, true));
 
-        let v3 = rng.gen::<u64>();
-        let v3_blinding = Scalar::random(&mut rng);
-        let party3 = Party::new(&bp_gens, &pc_gens, v3, v3_blinding, n).unwrap();
+        let ipp = &proof.ipp_proof;
+        let mut L_vec = ipp.L_vec().to_vec();
+        L_vec[0] = pc_gens.B.compress();
+        let mut bad = proof.clone();
+        bad.ipp_proof =
+            InnerProductProof::new_unchecked(L_vec, ipp.R_vec().to_vec(), ipp.a(), ipp.b());
+        assert_eq!(diagnose(&bad, &V), failed(false, true));
 
-        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        let mut bad = proof.clone();
+        bad.ipp_proof = InnerProductProof::new_unchecked(
+            ipp.L_vec().to_vec(),
+            ipp.R_vec().to_vec(),
+            ipp.a() + Scalar::ONE,
+            ipp.b(),
+        );
+        assert_eq!(diagnose(&bad, &V), failed(false, true));
 
-        let (party0, bit_com0) = party0.assign_position(0).unwrap();
-        let (party1, bit_com1) = party1.assign_position(1).unwrap();
-        let (party2, bit_com2) = party2.assign_position(2).unwrap();
-        let (party3, bit_com3) = party3.assign_position(3).unwrap();
+        // The bits of an out-of-range value are well formed, but do
+        // not add up to the committed value.
+        let (proof, V) = prove(&[3, 256]);
+        assert_eq!(diagnose(&proof, &V), failed(true, false));
+    }
 
-        let (dealer, bit_challenge) = dealer
-            .receive_bit_commitments(vec![bit_com0, bit_com1, bit_com2, bit_com3])
-            .unwrap();
+    #[cfg(feature = "diagnostics")]
+    #[test]
+    fn verification_reports_the_failing_check() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
 
-        let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
-        let (party1, poly_com1) = party1.apply_challenge(&bit_challenge);
-        let (party2, poly_com2) = party2.apply_challenge(&bit_challenge);
-        let (party3, poly_com3) = party3.apply_challenge(&bit_challenge);
+        let mut transcript = Transcript::new(b"DiagnosticsTest");
+        let (mut proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            3,
+            &Scalar::ONE,
+            BitSize::B8,
+        )
+        .unwrap();
+        proof.e_blinding += Scalar::ONE;
 
-        let (dealer, poly_challenge) = dealer
-            .receive_poly_commitments(vec![poly_com0, poly_com1, poly_com2, poly_com3])
-            .unwrap();
+        let mut transcript = Transcript::new(b"DiagnosticsTest");
+        assert_eq!(
+            proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B8),
+            Err(ProofError::FailedChecks {
+                polynomial: false,
+                inner_product: true,
+            })
+        );
+    }
 
-        let share0 = party0.apply_challenge(&poly_challenge).unwrap();
-        let share1 = party1.apply_challenge(&poly_challenge).unwrap();
-        let share2 = party2.apply_challenge(&poly_challenge).unwrap();
-        let share3 = party3.apply_challenge(&poly_challenge).unwrap();
+    #[test]
+    fn constant_time_verification_agrees_with_vartime() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 2);
+        let mut rng = rand::thread_rng();
 
-        match dealer.receive_shares(&[share0, share1, share2, share3]) {
-            Err(MPCError::MalformedProofShares { bad_shares }) => {
-                assert_eq!(bad_shares, vec![1, 3]);
-            }
-            Err(_) => {
-                panic!("Got wrong error type from malformed shares");
+        let mut transcript = Transcript::new(b"ConstantTimeTest");
+        let (proof, V) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[7, 60000],
+            &[Scalar::random(&mut rng), Scalar::random(&mut rng)],
+            BitSize::B16,
+        )
+        .unwrap();
+
+        let both = |proof: &RangeProof, V: &[CompressedRistretto], n: BitSize, label| {
+            let mut transcript = Transcript::new(label);
+            let mut ct_transcript = transcript.clone();
+            let result = proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, V, n);
+            // The constant-time verifier does not diagnose failures.
+            let ct_result = proof.verify_multiple_ct(&bp_gens, &pc_gens, &mut ct_transcript, V, n);
+            match (&result, &ct_result) {
+                (Err(e), Err(ct_e)) if e.is_verification_failure() => {
+                    assert!(ct_e.is_verification_failure())
+                }
+                _ => assert_eq!(ct_result, result),
             }
-            Ok(_) => {
-                panic!("The proof was malformed, but it was not detected");
+            let mut challenge = [0u8; 32];
+            let mut ct_challenge = [0u8; 32];
+            transcript.challenge_bytes(b"check", &mut challenge);
+            ct_transcript.challenge_bytes(b"check", &mut ct_challenge);
+            assert_eq!(challenge, ct_challenge);
+            result
+        };
+
+        assert_eq!(both(&proof, &V, BitSize::B16, b"ConstantTimeTest"), Ok(()));
+
+        // Invalid statements.
+        assert!(both(&proof, &V, BitSize::B16, b"OtherTest").is_err());
+        assert!(both(&proof, &[V[1], V[0]], BitSize::B16, b"ConstantTimeTest").is_err());
+        assert!(both(
+            &proof,
+            &[V[0], CompressedRistretto([0xff; 32])],
+            BitSize::B16,
+            b"ConstantTimeTest"
+        )
+        .is_err());
+        assert_eq!(
+            both(&proof, &V, BitSize::B8, b"ConstantTimeTest"),
+            Err(ProofError::BitsizeMismatch {
+                claimed: 8,
+                proven: 16
+            })
+        );
+        assert_eq!(
+            both(&proof, &V[..1], BitSize::B16, b"ConstantTimeTest"),
+            Err(ProofError::BitsizeMismatch {
+                claimed: 16,
+                proven: 32
+            })
+        );
+
+        // Every element of the proof, corrupted or set to zero, which
+        // is the encoding of the identity.
+        let bytes = proof.to_bytes();
+        for i in 0..bytes.len() / 32 {
+            for corrupt in [|byte: &mut u8| *byte ^= 1, |byte: &mut u8| *byte = 0] {
+                let mut corrupted = bytes.clone();
+                corrupted[32 * i..32 * (i + 1)].iter_mut().for_each(corrupt);
+                if let Ok(corrupted) = RangeProof::from_bytes(&corrupted) {
+                    assert!(both(&corrupted, &V, BitSize::B16, b"ConstantTimeTest")
+                        .unwrap_err()
+                        .is_verification_failure());
+                }
             }
         }
     }
 
     #[test]
-    fn detect_dishonest_dealer_during_aggregation() {
-        use self::dealer::*;
-        use self::party::*;
-        use crate::errors::MPCError;
+    fn mislabeled_bitsize_is_reported() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 2);
 
-        // Simulate one party
-        let m = 1;
-        let n = 32;
+        let mut transcript = Transcript::new(b"VerifiedRangeTest");
+        let (proof, V) = RangeProof::prove_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            3,
+            &Scalar::ONE,
+            BitSize::B16,
+        )
+        .unwrap();
 
-        let pc_gens = PedersenGens::default();
-        let bp_gens = BulletproofGens::new(n, m);
+        for &bit_size in &[BitSize::B8, BitSize::B32, BitSize::B64] {
+            let mut transcript = Transcript::new(b"VerifiedRangeTest");
+            assert_eq!(
+                proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &V, bit_size),
+                Err(ProofError::BitsizeMismatch {
+                    claimed: bit_size.bits(),
+                    proven: 16
+                })
+            );
+        }
 
-        use self::rand::Rng;
+        // Two 8-bit values also take 16 bits.
+        let mut transcript = Transcript::new(b"VerifiedRangeTest");
+        assert!(proof
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &[V, V], BitSize::B8)
+            .unwrap_err()
+            .is_verification_failure());
+        let mut transcript = Transcript::new(b"VerifiedRangeTest");
+        assert_eq!(
+            proof.verify_multiple(&bp_gens, &pc_gens, &mut transcript, &[V, V], BitSize::B16),
+            Err(ProofError::BitsizeMismatch {
+                claimed: 16,
+                proven: 8
+            })
+        );
+    }
+
+    #[test]
+    fn precheck_screens_the_polynomial_commitments() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
         let mut rng = rand::thread_rng();
-        let mut transcript = Transcript::new(b"AggregatedRangeProofTest");
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
 
-        let v0 = rng.gen::<u32>() as u64;
-        let v0_blinding = Scalar::random(&mut rng);
-        let party0 = Party::new(&bp_gens, &pc_gens, v0, v0_blinding, n).unwrap();
+        let mut transcript = Transcript::new(b"PrecheckTest");
+        let (proof, Vs) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[5, 6],
+            &blindings,
+            BitSize::B32,
+        )
+        .unwrap();
+        let transcript = Transcript::new(b"PrecheckTest");
+        assert!(proof
+            .precheck(&bp_gens, &pc_gens, &transcript, &Vs, BitSize::B32)
+            .is_ok());
 
-        let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, n, m).unwrap();
+        let tamper = |offset: usize| {
+            let mut bytes = proof.to_bytes();
+            bytes[offset] ^= 1;
+            RangeProof::from_bytes(&bytes).unwrap()
+        };
 
-        // Now do the protocol flow as normal....
+        // t_x follows the four points.
+        let bad_t_x = tamper(4 * 32);
+        assert!(bad_t_x
+            .precheck(&bp_gens, &pc_gens, &transcript, &Vs, BitSize::B32)
+            .unwrap_err()
+            .is_verification_failure());
 
-        let (party0, bit_com0) = party0.assign_position(0).unwrap();
+        // The IPP scalar a is second to last.
+        let bytes_len = proof.serialized_size();
+        let bad_ipp = tamper(bytes_len - 64);
+        assert!(bad_ipp
+            .precheck(&bp_gens, &pc_gens, &transcript, &Vs, BitSize::B32)
+            .is_ok());
+        let mut verifier_transcript = transcript.clone();
+        assert!(bad_ipp
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut verifier_transcript,
+                &Vs,
+                BitSize::B32
+            )
+            .unwrap_err()
+            .is_verification_failure());
 
-        let (dealer, bit_challenge) = dealer.receive_bit_commitments(vec![bit_com0]).unwrap();
+        // The precheck is bound to the statement.
+        assert!(proof
+            .precheck(
+                &bp_gens,
+                &pc_gens,
+                &transcript,
+                &[Vs[1], Vs[0]],
+                BitSize::B32
+            )
+            .is_err());
+    }
 
-        let (party0, poly_com0) = party0.apply_challenge(&bit_challenge);
+    #[test]
+    fn debug_output_is_bounded() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 16);
+        let mut rng = rand::thread_rng();
 
-        let (_dealer, mut poly_challenge) =
-            dealer.receive_poly_commitments(vec![poly_com0]).unwrap();
+        let mut prove = |m: usize| {
+            let values: Vec<u64> = (0..m as u64).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+            let mut transcript = Transcript::new(b"DebugTest");
+            RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::B64,
+            )
+            .unwrap()
+            .0
+        };
+        let small = prove(1);
+        let large = prove(16);
 
-        // But now simulate a malicious dealer choosing x = 0
-        poly_challenge.x = Scalar::ZERO;
+        // The Debug output only depends on the proof size through lg_n
+        // and the byte count.
+        let debug_small = format!("{:?}", small);
+        let debug_large = format!("{:?}", large);
+        assert!(debug_small.len() < 200);
+        assert!(debug_large.len() <= debug_small.len() + 2);
+        assert!(debug_large.contains("lg_n: 10, bytes: 928"));
 
-        let maybe_share0 = party0.apply_challenge(&poly_challenge);
+        assert_eq!(format!("{}", small), "RangeProof { nm=64, bytes=672 }");
+        assert_eq!(small.serialized_size(), small.to_bytes().len());
 
-        assert!(maybe_share0.unwrap_err() == MPCError::MaliciousDealer);
+        // Fingerprints are stable across serialization and distinguish proofs.
+        let reparsed = RangeProof::from_bytes(&small.to_bytes()).unwrap();
+        assert_eq!(small.fingerprint(), reparsed.fingerprint());
+        assert_ne!(small.fingerprint(), large.fingerprint());
+    }
+
+    #[test]
+    fn decompressed_proof_verifies_like_the_original() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 2);
+        let mut rng = rand::thread_rng();
+        let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+
+        let mut transcript = Transcript::new(b"DecompressTest");
+        let (proof, Vs) = RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[5, 1 << 30],
+            &blindings,
+            BitSize::B32,
+        )
+        .unwrap();
+        let bytes = proof.to_bytes();
+
+        // The decompressed points are reused across verifications.
+        let decompressed = RangeProof::from_bytes(&bytes)
+            .unwrap()
+            .decompress()
+            .unwrap();
+        for _ in 0..2 {
+            let mut transcript = Transcript::new(b"DecompressTest");
+            assert!(decompressed
+                .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &Vs, BitSize::B32)
+                .is_ok());
+        }
+        let mut transcript = Transcript::new(b"DecompressTest");
+        assert!(decompressed
+            .verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[Vs[1], Vs[0]],
+                BitSize::B32
+            )
+            .unwrap_err()
+            .is_verification_failure());
+
+        // Serialization only ever sees the compressed proof.
+        assert_eq!(decompressed.proof().to_bytes(), bytes);
+        assert_eq!(decompressed.proof(), &proof);
+
+        // A modified encoding is a new proof that must be decompressed
+        // again, and a point that does not decompress is rejected
+        // there as well as by the plain verifier.
+        let mut bad_bytes = bytes.clone();
+        bad_bytes[..32].copy_from_slice(&[0xff; 32]);
+        let bad = RangeProof::from_bytes(&bad_bytes).unwrap();
+        let mut transcript = Transcript::new(b"DecompressTest");
+        assert!(bad
+            .verify_multiple(&bp_gens, &pc_gens, &mut transcript, &Vs, BitSize::B32)
+            .unwrap_err()
+            .is_verification_failure());
+        assert_eq!(bad.decompress().err(), Some(ProofError::VerificationError));
     }
 }
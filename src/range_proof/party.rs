@@ -14,35 +14,39 @@ extern crate alloc;
 
 use alloc::vec::Vec;
 use clear_on_drop::clear::Clear;
+use core::convert::TryFrom;
 use core::iter;
+use core::mem;
 use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
 use curve25519_dalek::scalar::Scalar;
 use rand_core::{CryptoRng, RngCore};
 
 use crate::errors::MPCError;
-use crate::generators::{BulletproofGens, PedersenGens};
+use crate::generators::{BulletproofGens, BulletproofGensShare, PedersenGens};
 use crate::util;
 
-#[cfg(feature = "std")]
-use rand::thread_rng;
-
 use super::messages::*;
+use super::BitSize;
+
+pub use super::resumable_rng::{ResumableRng, ResumableRngState};
 
 /// Used to construct a party for the aggregated rangeproof MPC protocol.
 pub struct Party {}
 
 impl Party {
     /// Constructs a `PartyAwaitingPosition` with the given rangeproof parameters.
+    ///
+    /// Any `v` is accepted, but the proof only verifies if `v` is less
+    /// than \\(2^n\\).
+    #[allow(clippy::new_ret_no_self)]
     pub fn new<'a>(
         bp_gens: &'a BulletproofGens,
         pc_gens: &'a PedersenGens,
         v: u64,
         v_blinding: Scalar,
-        n: usize,
+        n: BitSize,
     ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
-        if !(n == 8 || n == 16 || n == 32 || n == 64) {
-            return Err(MPCError::InvalidBitsize);
-        }
+        let n = n.bits();
         if bp_gens.gens_capacity < n {
             return Err(MPCError::InvalidGeneratorsLength);
         }
@@ -56,8 +60,26 @@ impl Party {
             v,
             v_blinding,
             V,
+            rng: None,
         })
     }
+
+    /// Constructs a `PartyAwaitingPosition` like [`Party::new`], with
+    /// the bitsize as a number of bits.
+    ///
+    /// Returns [`MPCError::InvalidBitsize`] if `n` is not 8, 16, 32,
+    /// or 64.
+    #[deprecated(note = "use `new` with a `BitSize`")]
+    pub fn new_usize<'a>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        v: u64,
+        v_blinding: Scalar,
+        n: usize,
+    ) -> Result<PartyAwaitingPosition<'a>, MPCError> {
+        let n = BitSize::try_from(n).map_err(|_| MPCError::InvalidBitsize)?;
+        Party::new(bp_gens, pc_gens, v, v_blinding, n)
+    }
 }
 
 /// A party waiting for the dealer to assign their position in the aggregation.
@@ -68,67 +90,112 @@ pub struct PartyAwaitingPosition<'a> {
     v: u64,
     v_blinding: Scalar,
     V: CompressedRistretto,
+    rng: Option<ResumableRng>,
 }
 
 impl<'a> PartyAwaitingPosition<'a> {
+    /// Constructs a party like [`Party::new`], which samples all of its
+    /// randomness from a [`ResumableRng`] restored from `state`, rather
+    /// than from the RNGs passed to its steps.
+    ///
+    /// A party that saved its [`rng_state`](PartyAwaitingPosition::rng_state)
+    /// before a step, together with its value, blinding factor and
+    /// position, can be reconstructed after a crash and replay the step
+    /// to the same message, so the commitments the dealer already holds
+    /// stay valid.
+    pub fn new_with_rng_state(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        v: u64,
+        v_blinding: Scalar,
+        n: BitSize,
+        state: &ResumableRngState,
+    ) -> Result<Self, MPCError> {
+        let mut party = Party::new(bp_gens, pc_gens, v, v_blinding, n)?;
+        party.rng = Some(ResumableRng::from_state(state));
+        Ok(party)
+    }
+
+    /// Returns the state of the party's [`ResumableRng`], or `None` if
+    /// it samples from the RNGs passed to its steps.
+    pub fn rng_state(&self) -> Option<ResumableRngState> {
+        self.rng.as_ref().map(ResumableRng::state)
+    }
+
     /// Assigns a position in the aggregated proof to this party,
     /// allowing the party to commit to the bits of their value.
-    #[cfg(feature = "std")]
+    #[cfg(any(feature = "std", feature = "getrandom"))]
     pub fn assign_position(
         self,
         j: usize,
     ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
-        self.assign_position_with_rng(j, &mut thread_rng())
+        self.assign_position_with_rng(j, &mut util::default_rng())
     }
 
     /// Assigns a position in the aggregated proof to this party,
     /// allowing the party to commit to the bits of their value.
+    ///
+    /// A party with a [`ResumableRng`] samples from it instead of `rng`.
     pub fn assign_position_with_rng<T: RngCore + CryptoRng>(
-        self,
+        mut self,
         j: usize,
         rng: &mut T,
     ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
-        if self.bp_gens.party_capacity <= j {
-            return Err(MPCError::InvalidGeneratorsLength);
-        }
+        trace_phase!("rangeproof.commit_bits", j, n = self.n);
+        let precomputation = match self.rng.as_mut() {
+            Some(resumable) => {
+                PartyPrecomputation::new_with_rng(self.bp_gens, self.pc_gens, j, self.n, resumable)?
+            }
+            None => PartyPrecomputation::new_with_rng(self.bp_gens, self.pc_gens, j, self.n, rng)?,
+        };
+        Ok(self.assign_precomputed(j, precomputation))
+    }
 
-        let bp_share = self.bp_gens.share(j);
+    /// Assigns position `j` to this party, committing to the bits of
+    /// its value with randomness sampled by [`PartyPrecomputation`] for
+    /// the same position and bitsize.
+    pub(crate) fn assign_precomputed(
+        mut self,
+        j: usize,
+        mut precomputation: PartyPrecomputation,
+    ) -> (PartyAwaitingBitChallenge<'a>, BitCommitment) {
+        let bp_share = self
+            .bp_gens
+            .try_share(j)
+            .expect("the precomputation checked the position");
+
+        let G = bp_share
+            .G_checked(self.n)
+            .expect("the party checked the generators capacity");
+        let H = bp_share
+            .H_checked(self.n)
+            .expect("the party checked the generators capacity");
 
-        let a_blinding = Scalar::random(rng);
         // Compute A = <a_L, G> + <a_R, H> + a_blinding * B_blinding
-        let mut A = self.pc_gens.B_blinding * a_blinding;
-
-        use subtle::Choice;
-        use subtle::ConditionallySelectable;
-        let mut i = 0;
-        for (G_i, H_i) in bp_share.G(self.n).zip(bp_share.H(self.n)) {
-            // If v_i = 0, we add a_L[i] * G[i] + a_R[i] * H[i] = - H[i]
-            // If v_i = 1, we add a_L[i] * G[i] + a_R[i] * H[i] =   G[i]
-            let v_i = Choice::from(((self.v >> i) & 1) as u8);
-            let mut point = -H_i;
-            let point = RistrettoPoint::conditional_select(&point, G_i, v_i);
-            A += point;
-            i += 1;
-        }
-
-        let s_blinding = Scalar::random(rng);
-        let s_L: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
-        let s_R: Vec<Scalar> = (0..self.n).map(|_| Scalar::random(rng)).collect();
-
-        // Compute S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
-        use curve25519_dalek::traits::MultiscalarMul;
-        let S = RistrettoPoint::multiscalar_mul(
-            iter::once(&s_blinding).chain(s_L.iter()).chain(s_R.iter()),
-            iter::once(&self.pc_gens.B_blinding)
-                .chain(bp_share.G(self.n))
-                .chain(bp_share.H(self.n)),
+        let A = util::ct_select_commit(
+            util::bits_to_choices(self.v, self.n),
+            G,
+            H,
+            self.pc_gens.B_blinding * precomputation.a_blinding,
+        );
+        debug_assert_eq!(
+            A,
+            bit_commitment_vartime(
+                &bp_share,
+                self.pc_gens,
+                self.v,
+                self.n,
+                &precomputation.a_blinding
+            ),
+            "the selected bit commitment differs from its definition"
         );
 
         // Return next state and all commitments
         let bit_commitment = BitCommitment {
+            j,
             V_j: self.V,
             A_j: A,
-            S_j: S,
+            S_j: precomputation.S,
         };
         let next_state = PartyAwaitingBitChallenge {
             n: self.n,
@@ -136,12 +203,120 @@ impl<'a> PartyAwaitingPosition<'a> {
             v_blinding: self.v_blinding,
             pc_gens: self.pc_gens,
             j,
+            a_blinding: precomputation.a_blinding,
+            s_blinding: precomputation.s_blinding,
+            s_L: mem::take(&mut precomputation.s_L),
+            s_R: mem::take(&mut precomputation.s_R),
+            rng: self.rng.take(),
+        };
+        (next_state, bit_commitment)
+    }
+}
+
+/// Computes \\(A = \langle \mathbf{a}\_L, \mathbf{G} \rangle + \langle
+/// \mathbf{a}\_R, \mathbf{H} \rangle + \tilde{a} \tilde{B}\\) from the
+/// bits of `v` as a multiscalar multiplication, which branches on the
+/// bits, for the debug assertions of [`Party`].
+fn bit_commitment_vartime(
+    bp_share: &BulletproofGensShare,
+    pc_gens: &PedersenGens,
+    v: u64,
+    n: usize,
+    a_blinding: &Scalar,
+) -> RistrettoPoint {
+    use curve25519_dalek::traits::VartimeMultiscalarMul;
+
+    let a_L: Vec<Scalar> = (0..n).map(|i| Scalar::from((v >> i) & 1)).collect();
+    let a_R = a_L.iter().map(|a_L_i| a_L_i - Scalar::ONE);
+    RistrettoPoint::vartime_multiscalar_mul(
+        iter::once(*a_blinding)
+            .chain(a_L.iter().copied())
+            .chain(a_R),
+        iter::once(&pc_gens.B_blinding)
+            .chain(
+                bp_share
+                    .G_checked(n)
+                    .expect("the party checked the generators capacity"),
+            )
+            .chain(
+                bp_share
+                    .H_checked(n)
+                    .expect("the party checked the generators capacity"),
+            ),
+    )
+}
+
+/// The randomness of the bit commitment of the party at position `j`,
+/// and the commitment \(S\) to the blinding vectors, none of which
+/// depend on the party's value.
+pub(crate) struct PartyPrecomputation {
+    a_blinding: Scalar,
+    s_blinding: Scalar,
+    s_L: Vec<Scalar>,
+    s_R: Vec<Scalar>,
+    S: RistrettoPoint,
+}
+
+impl PartyPrecomputation {
+    /// Samples the blinding factors and vectors of an `n`-bit party at
+    /// position `j`, and computes \(S\).
+    pub(crate) fn new_with_rng<T: RngCore + CryptoRng>(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        j: usize,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<Self, MPCError> {
+        let bp_share = bp_gens
+            .try_share(j)
+            .map_err(|_| MPCError::InsufficientPartyCapacity {
+                required_parties: j.saturating_add(1),
+                available: bp_gens.party_capacity,
+            })?;
+
+        let a_blinding = Scalar::random(rng);
+        let s_blinding = Scalar::random(rng);
+        let s_L: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let s_R: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+
+        // Compute S = <s_L, G> + <s_R, H> + s_blinding * B_blinding
+        use curve25519_dalek::traits::MultiscalarMul;
+        let S = RistrettoPoint::multiscalar_mul(
+            iter::once(&s_blinding).chain(s_L.iter()).chain(s_R.iter()),
+            iter::once(&pc_gens.B_blinding)
+                .chain(
+                    bp_share
+                        .G_checked(n)
+                        .map_err(|_| MPCError::InvalidGeneratorsLength)?,
+                )
+                .chain(
+                    bp_share
+                        .H_checked(n)
+                        .map_err(|_| MPCError::InvalidGeneratorsLength)?,
+                ),
+        );
+
+        Ok(PartyPrecomputation {
             a_blinding,
             s_blinding,
             s_L,
             s_R,
-        };
-        Ok((next_state, bit_commitment))
+            S,
+        })
+    }
+}
+
+/// Overwrite secrets with null bytes when they go out of scope.
+impl Drop for PartyPrecomputation {
+    fn drop(&mut self) {
+        self.a_blinding.clear();
+        self.s_blinding.clear();
+        for e in self.s_L.iter_mut() {
+            e.clear();
+        }
+        for e in self.s_R.iter_mut() {
+            e.clear();
+        }
     }
 }
 
@@ -165,55 +340,83 @@ pub struct PartyAwaitingBitChallenge<'a> {
     s_blinding: Scalar,
     s_L: Vec<Scalar>,
     s_R: Vec<Scalar>,
+    rng: Option<ResumableRng>,
 }
 
 impl<'a> PartyAwaitingBitChallenge<'a> {
+    /// Returns the state of the party's [`ResumableRng`], or `None` if
+    /// it samples from the RNGs passed to its steps.
+    pub fn rng_state(&self) -> Option<ResumableRngState> {
+        self.rng.as_ref().map(ResumableRng::state)
+    }
+
     /// Receive a [`BitChallenge`] from the dealer and use it to
     /// compute commitments to the party's polynomial coefficients.
-    #[cfg(feature = "std")]
+    #[cfg(any(feature = "std", feature = "getrandom"))]
     pub fn apply_challenge(
         self,
         vc: &BitChallenge,
     ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
-        self.apply_challenge_with_rng(vc, &mut thread_rng())
+        self.apply_challenge_with_rng(vc, &mut util::default_rng())
     }
 
     /// Receive a [`BitChallenge`] from the dealer and use it to
     /// compute commitments to the party's polynomial coefficients.
+    ///
+    /// A party with a [`ResumableRng`] samples from it instead of `rng`.
     pub fn apply_challenge_with_rng<T: RngCore + CryptoRng>(
-        self,
+        mut self,
         vc: &BitChallenge,
         rng: &mut T,
     ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+        let (t_1_blinding, t_2_blinding) = match self.rng.as_mut() {
+            Some(resumable) => (Scalar::random(resumable), Scalar::random(resumable)),
+            None => (Scalar::random(rng), Scalar::random(rng)),
+        };
+        self.apply_challenge_with_blindings(vc, t_1_blinding, t_2_blinding)
+    }
+
+    /// Receive a [`BitChallenge`] from the dealer and commit to the
+    /// party's polynomial coefficients with the given blinding factors.
+    pub(crate) fn apply_challenge_with_blindings(
+        mut self,
+        vc: &BitChallenge,
+        t_1_blinding: Scalar,
+        t_2_blinding: Scalar,
+    ) -> (PartyAwaitingPolyChallenge, PolyCommitment) {
+        trace_phase!("rangeproof.commit_poly", j = self.j, n = self.n);
         let n = self.n;
-        let offset_y = util::scalar_exp_vartime(&vc.y, (self.j * n) as u64);
-        let offset_z = util::scalar_exp_vartime(&vc.z, self.j as u64);
 
-        // Calculate t by calculating vectors l0, l1, r0, r1 and multiplying
-        let mut l_poly = util::VecPoly1::zero(n);
+        // Calculate t from the vectors l0, l1, r0, r1.  Since a_L is a
+        // bit vector, l0 = a_L - z only takes the values -z and 1 - z,
+        // so it is computed on the fly rather than stored, and l1 is
+        // s_L itself.
+        let s_L = mem::take(&mut self.s_L);
         let mut r_poly = util::VecPoly1::zero(n);
+        let mut t_poly = util::Poly2(Scalar::ZERO, Scalar::ZERO, Scalar::ZERO);
 
-        let offset_zz = vc.z * vc.z * offset_z;
-        let mut exp_y = offset_y; // start at y^j
-        let mut exp_2 = Scalar::ONE; // start at 2^0 = 1
-        for i in 0..n {
+        // z^(2+j), and y^(j*n + i), 2^i for i = 0..n-1
+        let offset_zz = util::scalar_exp_vartime(&vc.z, 2 + self.j as u64);
+        let exp_y = util::exp_iter_from(vc.y, (self.j * n) as u64);
+        let exp_2 = util::exp_iter(Scalar::from(2u64));
+        for (i, (exp_y, exp_2)) in exp_y.zip(exp_2).take(n).enumerate() {
             let a_L_i = Scalar::from((self.v >> i) & 1);
             let a_R_i = a_L_i - Scalar::ONE;
 
-            l_poly.0[i] = a_L_i - vc.z;
-            l_poly.1[i] = self.s_L[i];
+            let l_0 = a_L_i - vc.z;
+            let l_1 = s_L[i];
             r_poly.0[i] = exp_y * (a_R_i + vc.z) + offset_zz * exp_2;
             r_poly.1[i] = exp_y * self.s_R[i];
 
-            exp_y *= vc.y; // y^i -> y^(i+1)
-            exp_2 = exp_2 + exp_2; // 2^i -> 2^(i+1)
+            // Karatsuba's method, as in VecPoly1::inner_product.
+            let t_0 = l_0 * r_poly.0[i];
+            let t_2 = l_1 * r_poly.1[i];
+            t_poly.0 += t_0;
+            t_poly.1 += (l_0 + l_1) * (r_poly.0[i] + r_poly.1[i]) - t_0 - t_2;
+            t_poly.2 += t_2;
         }
 
-        let t_poly = l_poly.inner_product(&r_poly);
-
         // Generate x by committing to T_1, T_2 (line 49-54)
-        let t_1_blinding = Scalar::random(rng);
-        let t_2_blinding = Scalar::random(rng);
         let T_1 = self.pc_gens.commit(t_poly.1, t_1_blinding);
         let T_2 = self.pc_gens.commit(t_poly.2, t_2_blinding);
 
@@ -223,15 +426,19 @@ impl<'a> PartyAwaitingBitChallenge<'a> {
         };
 
         let papc = PartyAwaitingPolyChallenge {
+            n,
+            v: self.v,
             v_blinding: self.v_blinding,
             a_blinding: self.a_blinding,
             s_blinding: self.s_blinding,
+            z: vc.z,
             offset_zz,
-            l_poly,
+            s_L,
             r_poly,
             t_poly,
             t_1_blinding,
             t_2_blinding,
+            rng: self.rng.take(),
         };
 
         (papc, poly_commitment)
@@ -263,8 +470,13 @@ impl<'a> Drop for PartyAwaitingBitChallenge<'a> {
 /// A party which has committed to their polynomial coefficents
 /// and is waiting for the polynomial challenge from the dealer.
 pub struct PartyAwaitingPolyChallenge {
+    n: usize,
+    v: u64,
+    z: Scalar,
     offset_zz: Scalar,
-    l_poly: util::VecPoly1,
+    /// The coefficients of \\(x\\) in \\(l(x)\\); the constant terms
+    /// are recomputed from `v` and `z`.
+    s_L: Vec<Scalar>,
     r_poly: util::VecPoly1,
     t_poly: util::Poly2,
     v_blinding: Scalar,
@@ -272,12 +484,29 @@ pub struct PartyAwaitingPolyChallenge {
     s_blinding: Scalar,
     t_1_blinding: Scalar,
     t_2_blinding: Scalar,
+    rng: Option<ResumableRng>,
 }
 
 impl PartyAwaitingPolyChallenge {
+    /// Returns the state of the party's [`ResumableRng`], or `None` if
+    /// it samples from the RNGs passed to its steps.
+    pub fn rng_state(&self) -> Option<ResumableRngState> {
+        self.rng.as_ref().map(ResumableRng::state)
+    }
+
+    /// Changes the blinding factor of \\(A\\), so that the proof share
+    /// no longer opens the bit commitment.  Only available in tests and
+    /// with the `test-utils` feature.
+    #[cfg(any(test, feature = "test-utils"))]
+    #[doc(hidden)]
+    pub fn perturb_a_blinding(&mut self) {
+        self.a_blinding += Scalar::ONE;
+    }
+
     /// Receive a [`PolyChallenge`] from the dealer and compute the
     /// party's proof share.
     pub fn apply_challenge(self, pc: &PolyChallenge) -> Result<ProofShare, MPCError> {
+        trace_phase!("rangeproof.proof_share", n = self.n);
         // Prevent a malicious dealer from annihilating the blinding
         // factors by supplying a zero challenge.
         if pc.x == Scalar::ZERO {
@@ -292,9 +521,16 @@ impl PartyAwaitingPolyChallenge {
 
         let t_x = self.t_poly.eval(pc.x);
         let t_x_blinding = t_blinding_poly.eval(pc.x);
-        let e_blinding = self.a_blinding + self.s_blinding * &pc.x;
-        let l_vec = self.l_poly.eval(pc.x);
+        let e_blinding = self.a_blinding + self.s_blinding * pc.x;
+        let l_vec: Vec<Scalar> = (0..self.n)
+            .map(|i| Scalar::from((self.v >> i) & 1) - self.z + pc.x * self.s_L[i])
+            .collect();
         let r_vec = self.r_poly.eval(pc.x);
+        debug_assert_eq!(
+            crate::inner_product_proof::inner_product(&l_vec, &r_vec),
+            t_x,
+            "t(x) differs from the inner product of l(x) and r(x)"
+        );
 
         Ok(ProofShare {
             t_x_blinding,
@@ -304,18 +540,59 @@ impl PartyAwaitingPolyChallenge {
             r_vec,
         })
     }
+
+    /// Moves the party to position `j` of an aggregation restarted
+    /// by [`DealerAwaitingPolyCommitments::proceed_without`](super::dealer::DealerAwaitingPolyCommitments::proceed_without),
+    /// committing to the bits of its value again.
+    ///
+    /// The party's value and blinding factor are kept, but all other
+    /// randomness is sampled afresh, since reusing it for a different
+    /// statement would leak the value.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn reassign_position<'a>(
+        self,
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        j: usize,
+    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+        self.reassign_position_with_rng(bp_gens, pc_gens, j, &mut util::default_rng())
+    }
+
+    /// Moves the party to position `j` of an aggregation restarted
+    /// by [`DealerAwaitingPolyCommitments::proceed_without`](super::dealer::DealerAwaitingPolyCommitments::proceed_without),
+    /// committing to the bits of its value again.
+    ///
+    /// The party's value and blinding factor are kept, but all other
+    /// randomness is sampled afresh, since reusing it for a different
+    /// statement would leak the value.
+    pub fn reassign_position_with_rng<'a, T: RngCore + CryptoRng>(
+        mut self,
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        j: usize,
+        rng: &mut T,
+    ) -> Result<(PartyAwaitingBitChallenge<'a>, BitCommitment), MPCError> {
+        let n = BitSize::try_from(self.n).map_err(|_| MPCError::InvalidBitsize)?;
+        let mut party = Party::new(bp_gens, pc_gens, self.v, self.v_blinding, n)?;
+        party.rng = self.rng.take();
+        party.assign_position_with_rng(j, rng)
+    }
 }
 
 /// Overwrite secrets with null bytes when they go out of scope.
 impl Drop for PartyAwaitingPolyChallenge {
     fn drop(&mut self) {
+        self.v.clear();
         self.v_blinding.clear();
         self.a_blinding.clear();
         self.s_blinding.clear();
         self.t_1_blinding.clear();
         self.t_2_blinding.clear();
+        for e in self.s_L.iter_mut() {
+            e.clear();
+        }
 
-        // Note: polynomials r_poly, l_poly and t_poly
+        // Note: polynomials r_poly and t_poly
         // are cleared within their own Drop impls.
     }
 }
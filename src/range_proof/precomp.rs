@@ -0,0 +1,227 @@
+//! Witness-independent precomputation for range proof creation.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use clear_on_drop::clear::Clear;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use super::dealer::Dealer;
+use super::party::{Party, PartyPrecomputation};
+use super::{check_statement_size, BitSize, RangeProof};
+use crate::errors::ProofError;
+use crate::generators::{AsGenerators, BulletproofGens, PedersenGens};
+#[cfg(any(feature = "std", feature = "getrandom"))]
+use crate::util;
+
+/// The witness-independent part of an aggregated range proof of `m`
+/// values of `n` bits, computed before the values are known.
+///
+/// Most of the first round of the range proof does not depend on the
+/// values being proven: each party samples its blinding vectors
+/// \\(\mathbf{s}\_L, \mathbf{s}\_R\\) and commits to them in
+/// \\(S = \langle \mathbf{s}\_L, \mathbf{G} \rangle + \langle \mathbf{s}\_R, \mathbf{H} \rangle + \tilde{s} \tilde{B}\\),
+/// a multiscalar multiplication of size \\(2n + 1\\).  The blinding
+/// factors \\(\tilde{a}\\), \\(\tau\_1\\), and \\(\tau\_2\\) of \\(A\\),
+/// \\(T\_1\\), and \\(T\_2\\) are also independent of the values.
+///
+/// A `RangeProverPrecomp` does this work ahead of time, so that
+/// creating the proof once the values are known only computes the
+/// commitments \\(V\\) and \\(A\\), the polynomials, and the
+/// inner-product proof.
+///
+/// # Transcript order
+///
+/// The precomputation appends nothing to the transcript: \\(S\\) is
+/// held back and appended by [`RangeProverPrecomp::finish`] after
+/// \\(V\\) and \\(A\\), exactly where the interactive protocol appends
+/// it.  Proofs created from a precomputation are therefore
+/// indistinguishable from, and verified like, those of
+/// [`RangeProof::prove_multiple`](crate::RangeProof::prove_multiple).
+///
+/// \\(A\\) commits to the bits of the values, so it must be computed
+/// online; no challenge depends on \\(S\\) alone, so computing \\(S\\)
+/// earlier changes nothing.  Committing \\(S\\) to the transcript
+/// *before* \\(A\\) would be a different protocol, with proofs that
+/// existing verifiers reject, and would need a new domain separator.
+/// It is not needed here, since computing \\(S\\) is the expensive part
+/// and appending it is not.
+///
+/// The challenges \\(y\\), \\(z\\), and \\(x\\) depend on \\(A\\), so
+/// the polynomials and the inner-product proof cannot be precomputed.
+/// The inner-product proof dominates the cost of proving, so for large
+/// aggregations the precomputation saves a fraction of the total time
+/// rather than most of it.
+///
+/// A precomputation holds secret randomness and is consumed by
+/// [`RangeProverPrecomp::finish`], so it can only be used for one
+/// proof: finishing two proofs with the same randomness would reveal
+/// the values.
+///
+/// ```
+/// use bulletproofs::RangeProverPrecomp;
+/// use bulletproofs::{BitSize, BulletproofGens, PedersenGens};
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+///
+/// // Ahead of time, before the value is known.
+/// let precomp = RangeProverPrecomp::new(&bp_gens, &pc_gens, BitSize::B64, 1)?;
+///
+/// // Once the value is known.
+/// let blinding = Scalar::random(&mut rand::thread_rng());
+/// let mut transcript = Transcript::new(b"doctest example");
+/// let (proof, commitments) = precomp.finish(&mut transcript, &[1037578891], &[blinding])?;
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &commitments[0], BitSize::B64)?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+pub struct RangeProverPrecomp<'a> {
+    bp_gens: &'a BulletproofGens,
+    pc_gens: &'a PedersenGens,
+    n: BitSize,
+    parties: Vec<PartyPrecomputation>,
+    t_blindings: Vec<(Scalar, Scalar)>,
+}
+
+impl<'a> RangeProverPrecomp<'a> {
+    /// Precomputes an aggregated range proof of `m` values of `n` bits.
+    ///
+    /// Fails with the errors of [`RangeProof::prove_multiple`] for the
+    /// bitsize, the aggregation size, and the generators.
+    pub fn new_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &'a G,
+        pc_gens: &'a PedersenGens,
+        n: BitSize,
+        m: usize,
+        rng: &mut T,
+    ) -> Result<Self, ProofError> {
+        let bp_gens = bp_gens.as_generators();
+        check_statement_size(n.bits(), m)?;
+        if !m.is_power_of_two() {
+            return Err(ProofError::InvalidAggregation);
+        }
+        if bp_gens.gens_capacity < n.bits() || bp_gens.party_capacity < m {
+            return Err(ProofError::InvalidGeneratorsLength);
+        }
+        trace_phase!("rangeproof.precompute", n = n.bits(), m);
+
+        let parties = (0..m)
+            .map(|j| PartyPrecomputation::new_with_rng(bp_gens, pc_gens, j, n.bits(), rng))
+            .collect::<Result<Vec<_>, _>>()?;
+        let t_blindings = (0..m)
+            .map(|_| (Scalar::random(rng), Scalar::random(rng)))
+            .collect();
+
+        Ok(RangeProverPrecomp {
+            bp_gens,
+            pc_gens,
+            n,
+            parties,
+            t_blindings,
+        })
+    }
+
+    /// Precomputes an aggregated range proof of `m` values of `n` bits.
+    /// This is a convenience wrapper around [`RangeProverPrecomp::new_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn new<G: AsGenerators + ?Sized>(
+        bp_gens: &'a G,
+        pc_gens: &'a PedersenGens,
+        n: BitSize,
+        m: usize,
+    ) -> Result<Self, ProofError> {
+        RangeProverPrecomp::new_with_rng(bp_gens, pc_gens, n, m, &mut util::default_rng())
+    }
+
+    /// Returns the bitsize of the precomputed proof.
+    pub fn bitsize(&self) -> BitSize {
+        self.n
+    }
+
+    /// Returns the number of values of the precomputed proof.
+    pub fn aggregation_size(&self) -> usize {
+        self.parties.len()
+    }
+
+    /// Creates the range proof of the `values`, committed to with the
+    /// `blindings`, and returns it with the commitments to the values,
+    /// as [`RangeProof::prove_multiple`] does.
+    ///
+    /// Returns [`ProofError::WrongNumBlindingFactors`] if the numbers
+    /// of values and blinding factors differ, and
+    /// [`ProofError::InvalidAggregation`] if they are not the
+    /// aggregation size of the precomputation.
+    pub fn finish(
+        mut self,
+        transcript: &mut Transcript,
+        values: &[u64],
+        blindings: &[Scalar],
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        if values.len() != blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        if values.len() != self.parties.len() {
+            return Err(ProofError::InvalidAggregation);
+        }
+        trace_phase!("rangeproof.finish", n = self.n.bits(), m = values.len());
+
+        let (bp_gens, pc_gens, n) = (self.bp_gens, self.pc_gens, self.n);
+        let dealer = Dealer::new(bp_gens, pc_gens, transcript, n, values.len())?;
+
+        let (parties, bit_commitments): (Vec<_>, Vec<_>) = values
+            .iter()
+            .zip(blindings.iter())
+            .zip(self.parties.drain(..))
+            .enumerate()
+            .map(|(j, ((&v, &v_blinding), precomputation))| {
+                Party::new(bp_gens, pc_gens, v, v_blinding, n)
+                    .map(|p| p.assign_precomputed(j, precomputation))
+            })
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter()
+            .unzip();
+
+        let value_commitments: Vec<_> = bit_commitments.iter().map(|c| c.V_j).collect();
+
+        let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
+
+        let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+            .into_iter()
+            .zip(self.t_blindings.iter())
+            .map(|(p, &(t_1_blinding, t_2_blinding))| {
+                p.apply_challenge_with_blindings(&bit_challenge, t_1_blinding, t_2_blinding)
+            })
+            .unzip();
+
+        let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
+
+        let proof_shares: Vec<_> = parties
+            .into_iter()
+            .map(|p| p.apply_challenge(&poly_challenge))
+            // Collect the iterator of Results into a Result<Vec>, then unwrap it
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let proof = dealer.receive_trusted_shares(&proof_shares)?;
+
+        Ok((proof, value_commitments))
+    }
+}
+
+/// Overwrite secrets with null bytes when they go out of scope.
+impl<'a> Drop for RangeProverPrecomp<'a> {
+    fn drop(&mut self) {
+        // The party precomputations clear themselves.
+        for (t_1_blinding, t_2_blinding) in self.t_blindings.iter_mut() {
+            t_1_blinding.clear();
+            t_2_blinding.clear();
+        }
+    }
+}
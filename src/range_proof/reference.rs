@@ -0,0 +1,456 @@
+//! A reference verifier for range proofs, used as an oracle for
+//! differential testing of the optimized verifiers.
+//!
+//! It checks the two equations of the protocol separately, computing
+//! every term with explicit group operations, and checks the
+//! inner-product proof with the reference verifier of
+//! [`inner_product_proof::reference`](crate::inner_product_proof::reference).
+//! None of the optimizations of [`RangeProof::verify_multiple`] (the
+//! single multiscalar multiplication, the random weighting of the two
+//! equations, the derived \\(s\_i\\) scalars, or sharding) is used.  It
+//! is slow, and compiled only for tests.
+
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::IsIdentity;
+use merlin::Transcript;
+
+use super::RangeProof;
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, PedersenGens};
+use crate::inner_product_proof::reference as ipp_reference;
+use crate::transcript::TranscriptProtocol;
+
+/// Returns \\(1, x, \ldots, x\^{k-1}\\), one multiplication at a time.
+fn powers(x: Scalar, k: usize) -> Vec<Scalar> {
+    let mut powers = Vec::with_capacity(k);
+    let mut x_i = Scalar::ONE;
+    for _ in 0..k {
+        powers.push(x_i);
+        x_i *= x;
+    }
+    powers
+}
+
+/// Verifies `proof` for the `value_commitments` and bitsize `n`, like
+/// [`RangeProof::verify_multiple`].
+pub(crate) fn verify(
+    proof: &RangeProof,
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+    transcript: &mut Transcript,
+    value_commitments: &[CompressedRistretto],
+    n: usize,
+) -> Result<(), ProofError> {
+    let m = value_commitments.len();
+    if !(n == 8 || n == 16 || n == 32 || n == 64) {
+        return Err(ProofError::InvalidBitsize);
+    }
+    if !m.is_power_of_two() {
+        return Err(ProofError::InvalidAggregation);
+    }
+    if bp_gens.gens_capacity < n || bp_gens.party_capacity < m {
+        return Err(ProofError::InvalidGeneratorsLength);
+    }
+
+    // Replay the interactive protocol to recompute the challenges.
+    let mut working_transcript = transcript.clone();
+    let t = &mut working_transcript;
+    t.rangeproof_domain_sep(n, m);
+    bp_gens.append_fingerprint(t);
+    pc_gens.append_fingerprint(t);
+    for V in value_commitments {
+        t.append_point(b"V", V);
+    }
+    t.append_point(b"A", &proof.A);
+    t.append_point(b"S", &proof.S);
+    let y = t.challenge_scalar(b"y");
+    let z = t.challenge_scalar(b"z");
+    t.append_point(b"T_1", &proof.T_1);
+    t.append_point(b"T_2", &proof.T_2);
+    let x = t.challenge_scalar(b"x");
+    t.append_scalar(b"t_x", &proof.t_x);
+    t.append_scalar(b"t_x_blinding", &proof.t_x_blinding);
+    t.append_scalar(b"e_blinding", &proof.e_blinding);
+    let w = t.challenge_scalar(b"w");
+
+    let decompress = |P: &CompressedRistretto| {
+        P.decompress()
+            .filter(|P| !P.is_identity())
+            .ok_or(ProofError::VerificationError)
+    };
+    let A = decompress(&proof.A)?;
+    let S = decompress(&proof.S)?;
+    let T_1 = decompress(&proof.T_1)?;
+    let T_2 = decompress(&proof.T_2)?;
+    // The value commitments may be the identity.
+    let V = value_commitments
+        .iter()
+        .map(|V| V.decompress().ok_or(ProofError::VerificationError))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let nm = n * m;
+    let y_pow = powers(y, nm);
+    let y_inv_pow = powers(y.invert(), nm);
+    let z_pow = powers(z, m + 3);
+    let two_pow = powers(Scalar::from(2u64), n);
+    let B = pc_gens.B;
+    let B_blinding = pc_gens.B_blinding;
+
+    // First, t(x) = t_0 + t_1 x + t_2 x^2 must be consistent with the
+    // value commitments and T_1, T_2:
+    // t_x B + t_x_blinding B~ = sum_j z^(j+2) V_j + delta(y, z) B + x T_1 + x^2 T_2,
+    // where delta(y, z) = (z - z^2) <1, y^nm> - sum_j z^(j+3) <1, 2^n>.
+    let sum_y: Scalar = y_pow.iter().sum();
+    let sum_2: Scalar = two_pow.iter().sum();
+    let mut delta = (z - z * z) * sum_y;
+    for j in 0..m {
+        delta -= z_pow[j + 3] * sum_2;
+    }
+    let mut rhs = delta * B + x * T_1 + (x * x) * T_2;
+    for j in 0..m {
+        rhs += z_pow[j + 2] * V[j];
+    }
+    if proof.t_x * B + proof.t_x_blinding * B_blinding != rhs {
+        return Err(ProofError::VerificationError);
+    }
+
+    // Second, A + x S, shifted by z, commits to l(x) and r(x) with the
+    // generators G and H' = y^-i H, and t_x is their inner product:
+    // P = A + x S - z <1, G> + <z y^nm + sum_j z^(j+2) 2^n_j, H'>
+    //   = e_blinding B~ + <l(x), G> + <r(x), H'>.
+    let G: Vec<RistrettoPoint> = bp_gens.G(n, m).cloned().collect();
+    let H: Vec<RistrettoPoint> = bp_gens.H(n, m).cloned().collect();
+    let mut P = A + x * S;
+    for i in 0..nm {
+        let (j, k) = (i / n, i % n);
+        P -= z * G[i];
+        P += (z * y_pow[i] + z_pow[j + 2] * two_pow[k]) * (y_inv_pow[i] * H[i]);
+    }
+
+    // The inner-product proof shows that P - e_blinding B~ + t_x Q,
+    // with Q = w B, commits to vectors whose inner product is t_x.
+    let Q = w * B;
+    let P_ipp = P - proof.e_blinding * B_blinding + proof.t_x * Q;
+    ipp_reference::verify(
+        &proof.ipp_proof,
+        nm,
+        &mut working_transcript,
+        &vec![Scalar::ONE; nm],
+        &y_inv_pow,
+        &P_ipp,
+        &Q,
+        &G,
+        &H,
+    )?;
+
+    *transcript = working_transcript;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::inner_product_proof::InnerProductProof;
+    use crate::range_proof::BitSize;
+    use core::convert::TryFrom;
+    use core::iter;
+    use curve25519_dalek::traits::VartimeMultiscalarMul;
+    use rand_chacha::ChaChaRng;
+    use rand_core::SeedableRng;
+
+    /// A range proof statement and a proof for it, valid or not.
+    struct Case {
+        name: String,
+        proof: RangeProof,
+        value_commitments: Vec<CompressedRistretto>,
+        n: usize,
+    }
+
+    /// Creates a valid proof of `m` values of `n` bits and the
+    /// corruptions of it that the verifiers must agree to reject.
+    fn corpus(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        n: usize,
+        m: usize,
+        rng: &mut ChaChaRng,
+    ) -> Vec<Case> {
+        let max = if n == 64 { u64::MAX } else { (1 << n) - 1 };
+        let values: Vec<u64> = (0..m).map(|j| max >> j).collect();
+        let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(rng)).collect();
+        let (proof, value_commitments) = RangeProof::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            &mut Transcript::new(b"ReferenceTest"),
+            &values,
+            &blindings,
+            BitSize::try_from(n).unwrap(),
+            rng,
+        )
+        .unwrap();
+
+        let case =
+            |name: String, proof: RangeProof, value_commitments: &[CompressedRistretto]| Case {
+                name,
+                proof,
+                value_commitments: value_commitments.to_vec(),
+                n,
+            };
+        let mut cases = vec![case("valid".into(), proof.clone(), &value_commitments)];
+
+        // Each element of the proof, corrupted.
+        let bytes = proof.to_bytes();
+        for i in 0..bytes.len() / 32 {
+            let mut corrupted = bytes.clone();
+            corrupted[32 * i] ^= 4;
+            if let Ok(corrupted) = RangeProof::from_bytes(&corrupted) {
+                cases.push(case(
+                    format!("element {}", i),
+                    corrupted,
+                    &value_commitments,
+                ));
+            }
+        }
+
+        // The inner-product proof is checked last, so a wrong a only
+        // fails at the very end of verification.
+        let mut wrong_a = proof.clone();
+        let ipp = &proof.ipp_proof;
+        wrong_a.ipp_proof = InnerProductProof::new_unchecked(
+            ipp.L_vec().to_vec(),
+            ipp.R_vec().to_vec(),
+            ipp.a() + Scalar::ONE,
+            ipp.b(),
+        );
+        cases.push(case("a + 1".into(), wrong_a, &value_commitments));
+
+        // The statement, corrupted.
+        let mut other = value_commitments.clone();
+        other[m - 1] = (pc_gens.B + other[m - 1].decompress().unwrap()).compress();
+        cases.push(case("V + B".into(), proof.clone(), &other));
+        if m > 1 {
+            let mut swapped = value_commitments.clone();
+            swapped.swap(0, m - 1);
+            cases.push(case("V swapped".into(), proof.clone(), &swapped));
+        }
+        cases.push(Case {
+            name: "2n".into(),
+            n: 2 * n,
+            ..case(String::new(), proof, &value_commitments)
+        });
+        cases
+    }
+
+    /// A verifier under test, which returns whether it accepts a case.
+    type Candidate<'a> = (&'static str, Box<dyn Fn(&Case) -> bool + 'a>);
+
+    /// Runs the reference verifier and each of the `candidates` on every
+    /// case, and returns the number of cases accepted by all of them, or
+    /// the first candidate and case on which they disagree.
+    fn differential(
+        bp_gens: &BulletproofGens,
+        pc_gens: &PedersenGens,
+        cases: &[Case],
+        candidates: &[Candidate],
+    ) -> Result<usize, String> {
+        let mut accepted = 0;
+        for case in cases {
+            let reference = verify(
+                &case.proof,
+                bp_gens,
+                pc_gens,
+                &mut Transcript::new(b"ReferenceTest"),
+                &case.value_commitments,
+                case.n,
+            )
+            .is_ok();
+            for (name, candidate) in candidates {
+                if candidate(case) != reference {
+                    return Err(format!("{} on {}", name, case.name));
+                }
+            }
+            accepted += reference as usize;
+        }
+        Ok(accepted)
+    }
+
+    /// The optimized verifiers, which must all agree with the reference
+    /// verifier.  The bitsize of a case may be unsupported, so they take
+    /// it as a `usize`.
+    #[allow(deprecated)]
+    fn candidates<'a>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+    ) -> Vec<Candidate<'a>> {
+        vec![
+            (
+                "verify_multiple",
+                Box::new(move |case: &Case| {
+                    case.proof
+                        .verify_multiple_usize_with_rng(
+                            bp_gens,
+                            pc_gens,
+                            &mut Transcript::new(b"ReferenceTest"),
+                            &case.value_commitments,
+                            case.n,
+                            &mut ChaChaRng::from_seed([0; 32]),
+                        )
+                        .is_ok()
+                }),
+            ),
+            (
+                "verify_multiple_sharded",
+                Box::new(move |case: &Case| {
+                    BitSize::try_from(case.n).is_ok_and(|n| {
+                        case.proof
+                            .verify_multiple_sharded_with_rng(
+                                bp_gens,
+                                pc_gens,
+                                &mut Transcript::new(b"ReferenceTest"),
+                                &case.value_commitments,
+                                n,
+                                2,
+                                &mut ChaChaRng::from_seed([0; 32]),
+                            )
+                            .is_ok()
+                    })
+                }),
+            ),
+            (
+                "decompressed",
+                Box::new(move |case: &Case| {
+                    case.proof.clone().decompress().is_ok_and(|proof| {
+                        proof
+                            .verify_multiple_usize_with_rng(
+                                bp_gens,
+                                pc_gens,
+                                &mut Transcript::new(b"ReferenceTest"),
+                                &case.value_commitments,
+                                case.n,
+                                &mut ChaChaRng::from_seed([0; 32]),
+                            )
+                            .is_ok()
+                    })
+                }),
+            ),
+        ]
+    }
+
+    /// The verification equation of `RangeProof::verify_multiple`, with
+    /// the sign of the \\(\tilde{B}\\) term flipped if `flip` is set.
+    fn msm_verifier<'a>(
+        bp_gens: &'a BulletproofGens,
+        pc_gens: &'a PedersenGens,
+        flip: bool,
+    ) -> Candidate<'a> {
+        let name = if flip { "flipped" } else { "msm" };
+        let verifier = move |case: &Case| {
+            let points = match case.proof.decompress_points() {
+                Ok(points) => points,
+                Err(_) => return false,
+            };
+            let terms = match case.proof.verification_terms(
+                &points,
+                bp_gens,
+                pc_gens,
+                &mut Transcript::new(b"ReferenceTest"),
+                &case.value_commitments,
+                case.n,
+                &mut ChaChaRng::from_seed([0; 32]),
+            ) {
+                Ok(terms) => terms,
+                Err(_) => return false,
+            };
+            let B_blinding = if flip {
+                -terms.B_blinding
+            } else {
+                terms.B_blinding
+            };
+            let m = case.value_commitments.len();
+            RistrettoPoint::optional_multiscalar_mul(
+                terms
+                    .scalars
+                    .into_iter()
+                    .chain(iter::once(B_blinding))
+                    .chain(iter::once(terms.B))
+                    .chain(terms.g)
+                    .chain(terms.h),
+                terms
+                    .points
+                    .into_iter()
+                    .chain(iter::once(Some(pc_gens.B_blinding)))
+                    .chain(iter::once(Some(pc_gens.B)))
+                    .chain(bp_gens.G(case.n, m).map(|&G| Some(G)))
+                    .chain(bp_gens.H(case.n, m).map(|&H| Some(H))),
+            )
+            .is_some_and(|check| check.is_identity())
+        };
+        (name, Box::new(verifier))
+    }
+
+    #[test]
+    fn optimized_verifiers_agree_with_reference() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 4);
+        let candidates = candidates(&bp_gens, &pc_gens);
+        let mut rng = ChaChaRng::from_seed([4; 32]);
+        for (n, m) in [(8, 1), (8, 2), (16, 1), (8, 4), (64, 1)] {
+            let cases = corpus(&bp_gens, &pc_gens, n, m, &mut rng);
+            assert_eq!(
+                differential(&bp_gens, &pc_gens, &cases, &candidates),
+                Ok(1),
+                "n = {}, m = {}",
+                n,
+                m
+            );
+        }
+    }
+
+    #[test]
+    fn optimized_verifiers_agree_with_reference_for_1024_bits() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(64, 16);
+        // The reference verifier is slow for this size, so only check
+        // the valid proof and one that is rejected by the last check.
+        let cases: Vec<Case> = corpus(
+            &bp_gens,
+            &pc_gens,
+            64,
+            16,
+            &mut ChaChaRng::from_seed([5; 32]),
+        )
+        .into_iter()
+        .filter(|case| ["valid", "a + 1"].contains(&&*case.name))
+        .collect();
+        assert_eq!(cases.len(), 2);
+        assert_eq!(
+            differential(&bp_gens, &pc_gens, &cases, &candidates(&bp_gens, &pc_gens)),
+            Ok(1)
+        );
+    }
+
+    #[test]
+    fn harness_catches_a_sign_flip() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 2);
+        let cases = corpus(
+            &bp_gens,
+            &pc_gens,
+            16,
+            2,
+            &mut ChaChaRng::from_seed([6; 32]),
+        );
+        let msm = msm_verifier(&bp_gens, &pc_gens, false);
+        assert_eq!(differential(&bp_gens, &pc_gens, &cases, &[msm]), Ok(1));
+        let flipped = msm_verifier(&bp_gens, &pc_gens, true);
+        assert_eq!(
+            differential(&bp_gens, &pc_gens, &cases, &[flipped]),
+            Err("flipped on valid".into())
+        );
+    }
+}
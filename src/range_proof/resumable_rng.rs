@@ -0,0 +1,149 @@
+//! The `resumable_rng` module contains a random number generator whose
+//! position can be saved and restored, so that a party of the
+//! aggregation MPC protocol can resume after a crash with the same
+//! randomness.
+
+use clear_on_drop::clear::Clear;
+use rand_chacha::rand_core::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::{CryptoRng, RngCore};
+use serde_derive::{Deserialize, Serialize};
+
+use crate::errors::ProofError;
+
+/// A ChaCha20 random number generator with an explicit 128-bit
+/// counter, whose [`ResumableRngState`] can be saved and restored.
+///
+/// A party of the aggregation protocol that crashes mid-protocol must
+/// resume with the same blinding factors, or the commitments it already
+/// sent no longer match its proof share.  A party created with
+/// [`PartyAwaitingPosition::new_with_rng_state`](super::party::PartyAwaitingPosition::new_with_rng_state)
+/// samples all of its randomness from a `ResumableRng`, so replaying
+/// its steps from a saved state reproduces the same messages.
+pub struct ResumableRng {
+    rng: ChaCha20Rng,
+}
+
+impl ResumableRng {
+    /// Creates a generator at the start of the stream of `seed`.
+    pub fn from_seed(seed: [u8; 32]) -> Self {
+        ResumableRng {
+            rng: ChaCha20Rng::from_seed(seed),
+        }
+    }
+
+    /// Creates a generator with a seed sampled from `rng`.
+    pub fn from_rng<T: RngCore + CryptoRng>(rng: &mut T) -> Self {
+        let mut seed = [0u8; 32];
+        rng.fill_bytes(&mut seed);
+        let resumable = ResumableRng::from_seed(seed);
+        seed.clear();
+        resumable
+    }
+
+    /// Creates a generator at the position recorded in `state`.
+    pub fn from_state(state: &ResumableRngState) -> Self {
+        let mut resumable = ResumableRng::from_seed(state.seed);
+        resumable.rng.set_word_pos(state.counter);
+        resumable
+    }
+
+    /// Returns the seed and position of the generator.
+    pub fn state(&self) -> ResumableRngState {
+        ResumableRngState {
+            seed: self.rng.get_seed(),
+            counter: self.rng.get_word_pos(),
+        }
+    }
+}
+
+impl RngCore for ResumableRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.rng.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.rng.try_fill_bytes(dest)
+    }
+}
+
+impl CryptoRng for ResumableRng {}
+
+/// The seed and position of a [`ResumableRng`].
+///
+/// The state determines every value the generator produces from then
+/// on, including the blinding factors of a party, so it is as secret
+/// as the party's value: the caller must encrypt it before persisting
+/// it.  The seed is overwritten with null bytes when the state is
+/// dropped.
+#[derive(Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResumableRngState {
+    seed: [u8; 32],
+    /// The offset into the stream of the seed, in 32-bit words.
+    counter: u128,
+}
+
+impl ResumableRngState {
+    /// Serializes the state into 48 bytes: the seed followed by the
+    /// little-endian counter.
+    pub fn to_bytes(&self) -> [u8; 48] {
+        let mut buf = [0u8; 48];
+        buf[..32].copy_from_slice(&self.seed);
+        buf[32..].copy_from_slice(&self.counter.to_le_bytes());
+        buf
+    }
+
+    /// Deserializes the state from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice is not 48
+    /// bytes long.
+    pub fn from_bytes(slice: &[u8]) -> Result<ResumableRngState, ProofError> {
+        if slice.len() != 48 {
+            return Err(ProofError::FormatError);
+        }
+        let mut seed = [0u8; 32];
+        seed.copy_from_slice(&slice[..32]);
+        let mut counter = [0u8; 16];
+        counter.copy_from_slice(&slice[32..]);
+        Ok(ResumableRngState {
+            seed,
+            counter: u128::from_le_bytes(counter),
+        })
+    }
+}
+
+/// Overwrite the seed with null bytes when it goes out of scope.
+impl Drop for ResumableRngState {
+    fn drop(&mut self) {
+        self.seed.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn restored_generator_continues_the_stream() {
+        let mut rng = ResumableRng::from_seed([7u8; 32]);
+        let mut odd = [0u8; 13];
+        rng.fill_bytes(&mut odd);
+        rng.next_u32();
+
+        let state = ResumableRngState::from_bytes(&rng.state().to_bytes()).unwrap();
+        let mut restored = ResumableRng::from_state(&state);
+        for _ in 0..100 {
+            assert_eq!(rng.next_u64(), restored.next_u64());
+        }
+        assert!(rng.state() == restored.state());
+        assert!(ResumableRngState::from_bytes(&[0u8; 47]).is_err());
+    }
+}
@@ -0,0 +1,311 @@
+//! The `statement` module contains [`RangeStatement`], the witness of
+//! a range proof assembled with a builder, and the range proof APIs
+//! that take it.
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use clear_on_drop::clear::Clear;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_core::{CryptoRng, RngCore};
+
+use crate::errors::ProofError;
+use crate::parameters::ProofParameters;
+#[cfg(any(feature = "std", feature = "getrandom"))]
+use crate::util;
+
+use super::{check_statement_size, BitSize, RangeProof};
+
+/// The values of a range proof, their blinding factors and the
+/// bitsize, checked to form a statement that can be proven.
+///
+/// A `RangeStatement` is assembled with [`RangeStatement::builder`],
+/// which names each argument, and proven with [`RangeProof::prove`]:
+///
+/// ```
+/// use bulletproofs::{BitSize, ProofParameters, RangeProof, RangeStatement};
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let params = ProofParameters::for_range_proofs(64, 2)?;
+/// let statement = RangeStatement::builder()
+///     .value(1037578891)
+///     .blinding(Scalar::from(7u64))
+///     .bits(BitSize::B32)
+///     .build()?;
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// let (proof, commitments) = RangeProof::prove(&params, &statement, &mut transcript)?;
+///
+/// let mut transcript = Transcript::new(b"doctest example");
+/// params.verify_range(&proof, &mut transcript, &commitments, BitSize::B32)?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+///
+/// The statement holds the witness, which is cleared when it is
+/// dropped, and its `Debug` output shows only the bitsize and the
+/// number of values.
+#[derive(Clone)]
+pub struct RangeStatement {
+    values: Vec<u64>,
+    blindings: Vec<Scalar>,
+    bits: BitSize,
+}
+
+/// A builder for a [`RangeStatement`], created by
+/// [`RangeStatement::builder`].
+///
+/// Each call to [`value`](RangeStatementBuilder::value) adds a value,
+/// and each call to [`blinding`](RangeStatementBuilder::blinding) the
+/// blinding factor of the value at the same position.
+#[derive(Clone, Default)]
+pub struct RangeStatementBuilder {
+    values: Vec<u64>,
+    blindings: Vec<Scalar>,
+    bits: Option<BitSize>,
+}
+
+impl RangeStatement {
+    /// Returns an empty builder.
+    pub fn builder() -> RangeStatementBuilder {
+        RangeStatementBuilder::default()
+    }
+
+    /// Returns the bitsize of the statement.
+    pub fn bits(&self) -> BitSize {
+        self.bits
+    }
+
+    /// Returns the number of values of the statement.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns whether the statement has no values, which a built
+    /// statement never does.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+}
+
+impl fmt::Debug for RangeStatement {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangeStatement")
+            .field("bits", &self.bits)
+            .field("len", &self.values.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl Drop for RangeStatement {
+    fn drop(&mut self) {
+        for v in self.values.iter_mut() {
+            v.clear();
+        }
+        for blinding in self.blindings.iter_mut() {
+            blinding.clear();
+        }
+    }
+}
+
+impl RangeStatementBuilder {
+    /// Adds a value to the statement.
+    pub fn value(mut self, v: u64) -> Self {
+        self.values.push(v);
+        self
+    }
+
+    /// Adds the blinding factor of the next value without one.
+    pub fn blinding(mut self, v_blinding: Scalar) -> Self {
+        self.blindings.push(v_blinding);
+        self
+    }
+
+    /// Sets the bitsize of the statement.
+    pub fn bits(mut self, n: BitSize) -> Self {
+        self.bits = Some(n);
+        self
+    }
+
+    /// Checks the statement and returns it.
+    ///
+    /// Returns [`ProofError::InvalidBitsize`] if the bitsize was not
+    /// set, [`ProofError::WrongNumBlindingFactors`] if the values and
+    /// blinding factors differ in number,
+    /// [`ProofError::InvalidAggregation`] if the number of values is not
+    /// a power of 2, [`ProofError::StatementTooLarge`] if the statement
+    /// is too large to be proven, and [`ProofError::ValueOutOfRange`] if
+    /// a value is not less than \\(2\^n\\).  The values are checked
+    /// without branching on any one of them, so the error does not say
+    /// which value is out of range.
+    pub fn build(mut self) -> Result<RangeStatement, ProofError> {
+        let bits = self.bits.ok_or(ProofError::InvalidBitsize)?;
+        if self.values.len() != self.blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        if !self.values.len().is_power_of_two() {
+            return Err(ProofError::InvalidAggregation);
+        }
+        check_statement_size(bits.bits(), self.values.len())?;
+
+        let n = bits.bits();
+        let high_bits = self
+            .values
+            .iter()
+            .fold(0, |acc, &v| acc | if n == 64 { 0 } else { v >> n });
+        if high_bits != 0 {
+            return Err(ProofError::ValueOutOfRange);
+        }
+
+        Ok(RangeStatement {
+            values: core::mem::take(&mut self.values),
+            blindings: core::mem::take(&mut self.blindings),
+            bits,
+        })
+    }
+}
+
+impl Drop for RangeStatementBuilder {
+    fn drop(&mut self) {
+        for v in self.values.iter_mut() {
+            v.clear();
+        }
+        for blinding in self.blindings.iter_mut() {
+            blinding.clear();
+        }
+    }
+}
+
+impl RangeProof {
+    /// Creates a rangeproof of `statement` with the generators of
+    /// `params`, returning the proof and the commitments to the values.
+    ///
+    /// This proves the same statement as
+    /// [`RangeProof::prove_multiple_with_rng`], whose values and
+    /// blinding factors are passed by position and are not checked to
+    /// be in range.  Returns [`ProofError::InvalidGeneratorsLength`] if
+    /// the statement does not fit `params`.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        params: &ProofParameters,
+        statement: &RangeStatement,
+        transcript: &mut Transcript,
+        rng: &mut T,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        params.check_range_statement(statement.bits.bits(), statement.len())?;
+        RangeProof::prove_multiple_with_rng(
+            params.bp_gens(),
+            params.pc_gens(),
+            transcript,
+            &statement.values,
+            &statement.blindings,
+            statement.bits,
+            rng,
+        )
+    }
+
+    /// Creates a rangeproof of `statement`.
+    /// This is a convenience wrapper around [`RangeProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove(
+        params: &ProofParameters,
+        statement: &RangeStatement,
+        transcript: &mut Transcript,
+    ) -> Result<(RangeProof, Vec<CompressedRistretto>), ProofError> {
+        RangeProof::prove_with_rng(params, statement, transcript, &mut util::default_rng())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::{BulletproofGens, PedersenGens};
+
+    fn builder(values: &[u64], n: BitSize) -> RangeStatementBuilder {
+        values
+            .iter()
+            .enumerate()
+            .fold(RangeStatement::builder().bits(n), |b, (i, &v)| {
+                b.value(v).blinding(Scalar::from(i as u64 + 1))
+            })
+    }
+
+    #[test]
+    fn builder_rejects_invalid_statements() {
+        let no_bits = RangeStatement::builder()
+            .value(1)
+            .blinding(Scalar::ONE)
+            .build();
+        assert_eq!(no_bits.unwrap_err(), ProofError::InvalidBitsize);
+
+        let no_blinding = builder(&[1], BitSize::B8).value(2).build();
+        assert_eq!(
+            no_blinding.unwrap_err(),
+            ProofError::WrongNumBlindingFactors
+        );
+        let extra_blinding = builder(&[1], BitSize::B8).blinding(Scalar::ONE).build();
+        assert_eq!(
+            extra_blinding.unwrap_err(),
+            ProofError::WrongNumBlindingFactors
+        );
+
+        for values in [&[][..], &[1, 2, 3]] {
+            let result = builder(values, BitSize::B8).build();
+            assert_eq!(result.unwrap_err(), ProofError::InvalidAggregation);
+        }
+
+        for &n in &BitSize::ALL {
+            if n != BitSize::B64 {
+                let max = (1u64 << n.bits()) - 1;
+                assert!(builder(&[max, 0], n).build().is_ok());
+                let result = builder(&[0, max + 1], n).build();
+                assert_eq!(result.unwrap_err(), ProofError::ValueOutOfRange);
+            }
+        }
+        assert!(builder(&[u64::MAX], BitSize::B64).build().is_ok());
+    }
+
+    #[test]
+    fn statements_prove_as_the_positional_api() {
+        let params = ProofParameters::for_range_proofs(32, 2).unwrap();
+        let values = [3, 1 << 20];
+        let statement = builder(&values, BitSize::B32).build().unwrap();
+        assert_eq!(statement.bits(), BitSize::B32);
+        assert_eq!(statement.len(), 2);
+        assert!(!format!("{:?}", statement).contains("1048576"));
+
+        let mut transcript = Transcript::new(b"RangeStatementTest");
+        let (proof, commitments) = RangeProof::prove(&params, &statement, &mut transcript).unwrap();
+
+        let mut transcript = Transcript::new(b"RangeStatementTest");
+        let (_, positional) = RangeProof::prove_multiple(
+            &BulletproofGens::new(32, 2),
+            &PedersenGens::default(),
+            &mut transcript,
+            &values,
+            &[Scalar::from(1u64), Scalar::from(2u64)],
+            BitSize::B32,
+        )
+        .unwrap();
+        assert_eq!(commitments, positional);
+
+        let mut transcript = Transcript::new(b"RangeStatementTest");
+        assert!(params
+            .verify_range(&proof, &mut transcript, &commitments, BitSize::B32)
+            .is_ok());
+
+        // A statement too large for the parameters is rejected before
+        // proving.
+        let statement = builder(&[1, 2], BitSize::B64).build().unwrap();
+        let mut transcript = Transcript::new(b"RangeStatementTest");
+        assert_eq!(
+            RangeProof::prove(&params, &statement, &mut transcript).unwrap_err(),
+            ProofError::InvalidGeneratorsLength
+        );
+    }
+}
@@ -0,0 +1,253 @@
+//! The `simple` module contains top-level functions that prove and
+//! verify common statements with default parameters, for applications
+//! that do not need to choose them.
+//!
+//! Each function builds its transcript with [`app_transcript`] from the
+//! application `label`, uses generators that are created on first use
+//! and shared by every call, and exchanges proofs in their serialized
+//! form.  A proof verifies only under the label it was created with.
+//!
+//! ```
+//! use bulletproofs::simple;
+//!
+//! let (proof, commitment, _blinding) = simple::prove_range(1037578891, b"example wallet");
+//! assert!(simple::verify_range(&proof, &commitment, b"example wallet").is_ok());
+//! assert!(simple::verify_range(&proof, &commitment, b"example exchange").is_err());
+//! ```
+//!
+//! The full API takes the generators, the transcript and the blinding
+//! factors as arguments: see [`RangeProof`] for range proofs of other
+//! bitsizes, aggregated over several values or verified in batches, and
+//! the `r1cs` module of the `yoloproofs` feature for arbitrary
+//! constraint systems.
+
+use std::sync::OnceLock;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "yoloproofs")]
+use merlin::Transcript;
+
+use crate::errors::ProofError;
+use crate::generators::{BulletproofGens, PedersenGens};
+#[cfg(feature = "yoloproofs")]
+use crate::r1cs::{gadgets::one_hot, Prover, R1CSError, R1CSProof, Verifier};
+use crate::range_proof::{BitSize, RangeProof};
+use crate::transcript::app_transcript;
+
+/// The largest length of the vectors proven one-hot by
+/// `prove_one_hot`, which needs the `yoloproofs` feature.
+pub const MAX_ONE_HOT_LEN: usize = 256;
+
+/// The version passed to [`app_transcript`] with the label of the
+/// application.
+const TRANSCRIPT_VERSION: u64 = 1;
+
+/// Returns the generators shared by the functions of this module,
+/// creating them on first use.
+fn generators() -> &'static (PedersenGens, BulletproofGens) {
+    static GENERATORS: OnceLock<(PedersenGens, BulletproofGens)> = OnceLock::new();
+    GENERATORS.get_or_init(|| {
+        let capacity = BitSize::B64.bits().max(MAX_ONE_HOT_LEN);
+        (PedersenGens::default(), BulletproofGens::new(capacity, 1))
+    })
+}
+
+/// Proves that `value` is a 64-bit value, under the application
+/// `label`.
+///
+/// Returns the serialized [`RangeProof`], the commitment to `value`
+/// and its blinding factor, which is drawn at random and needed to
+/// open the commitment later.
+pub fn prove_range(value: u64, label: &'static [u8]) -> (Vec<u8>, CompressedRistretto, Scalar) {
+    let (pc_gens, bp_gens) = generators();
+    let blinding = Scalar::random(&mut rand::thread_rng());
+    let (proof, commitment) = RangeProof::prove_single(
+        bp_gens,
+        pc_gens,
+        &mut app_transcript(label, TRANSCRIPT_VERSION),
+        value,
+        &blinding,
+        BitSize::B64,
+    )
+    .expect("the default generators support 64-bit range proofs");
+    (proof.to_bytes(), commitment, blinding)
+}
+
+/// Verifies a proof created by [`prove_range`] that `commitment`
+/// commits to a 64-bit value, under the application `label`.
+///
+/// Returns [`ProofError::FormatError`] if `proof_bytes` is not a
+/// 64-bit range proof, and [`ProofError::VerificationError`] if the
+/// proof is invalid.
+pub fn verify_range(
+    proof_bytes: &[u8],
+    commitment: &CompressedRistretto,
+    label: &'static [u8],
+) -> Result<(), ProofError> {
+    let (pc_gens, bp_gens) = generators();
+    let proof = RangeProof::from_bytes(proof_bytes)?;
+    proof.verify_single(
+        bp_gens,
+        pc_gens,
+        &mut app_transcript(label, TRANSCRIPT_VERSION),
+        commitment,
+        BitSize::B64,
+    )?;
+    Ok(())
+}
+
+/// Returns the transcript of a one-hot proof of length `len` under the
+/// application `label`.
+#[cfg(feature = "yoloproofs")]
+fn one_hot_transcript(label: &'static [u8], len: usize) -> Transcript {
+    let mut transcript = app_transcript(label, TRANSCRIPT_VERSION);
+    transcript.append_message(b"dom-sep", b"simple-one-hot v1");
+    transcript.append_u64(b"len", len as u64);
+    transcript
+}
+
+/// Proves that a vector of `len` commitments commits to one at position
+/// `index` and to zero elsewhere, without revealing `index`, under the
+/// application `label`.
+///
+/// Returns the serialized [`R1CSProof`], the commitments and their
+/// blinding factors, which are drawn at random.  Returns
+/// [`R1CSError::GadgetError`] if `index` is not less than `len`, or if
+/// `len` is greater than [`MAX_ONE_HOT_LEN`].
+#[cfg(feature = "yoloproofs")]
+#[allow(clippy::type_complexity)]
+pub fn prove_one_hot(
+    index: usize,
+    len: usize,
+    label: &'static [u8],
+) -> Result<(Vec<u8>, Vec<CompressedRistretto>, Vec<Scalar>), R1CSError> {
+    if len > MAX_ONE_HOT_LEN {
+        return Err(R1CSError::GadgetError {
+            description: "the vector is longer than MAX_ONE_HOT_LEN".into(),
+        });
+    }
+    if index >= len {
+        return Err(R1CSError::GadgetError {
+            description: "the index is not a position of the vector".into(),
+        });
+    }
+
+    let (pc_gens, bp_gens) = generators();
+    let mut rng = rand::thread_rng();
+    let blindings: Vec<Scalar> = (0..len).map(|_| Scalar::random(&mut rng)).collect();
+    let mut prover = Prover::new(pc_gens, one_hot_transcript(label, len));
+    let (commitments, selector): (Vec<_>, Vec<_>) = blindings
+        .iter()
+        .enumerate()
+        .map(|(j, blinding)| prover.commit(Scalar::from((j == index) as u64), *blinding))
+        .unzip();
+    one_hot(&mut prover, &selector);
+    let proof = prover.prove_with_rng(bp_gens, &mut rng)?;
+
+    Ok((proof.to_bytes(), commitments, blindings))
+}
+
+/// Verifies a proof created by [`prove_one_hot`] that `commitments`
+/// commit to one at a single position and to zero elsewhere, under the
+/// application `label`.
+///
+/// Returns [`R1CSError::GadgetError`] if there are more than
+/// [`MAX_ONE_HOT_LEN`] commitments, and
+/// [`R1CSError::VerificationError`] if the proof is invalid.
+#[cfg(feature = "yoloproofs")]
+pub fn verify_one_hot(
+    proof_bytes: &[u8],
+    commitments: &[CompressedRistretto],
+    label: &'static [u8],
+) -> Result<(), R1CSError> {
+    if commitments.len() > MAX_ONE_HOT_LEN {
+        return Err(R1CSError::GadgetError {
+            description: "the vector is longer than MAX_ONE_HOT_LEN".into(),
+        });
+    }
+
+    let (pc_gens, bp_gens) = generators();
+    let proof = R1CSProof::from_bytes(proof_bytes)?;
+    let mut verifier = Verifier::new(one_hot_transcript(label, commitments.len()));
+    let selector: Vec<_> = commitments.iter().map(|&c| verifier.commit(c)).collect();
+    one_hot(&mut verifier, &selector);
+    verifier.verify(&proof, pc_gens, bp_gens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn range_proofs_verify_under_their_label() {
+        for &value in &[0, 1, 1037578891, u64::MAX] {
+            let (proof, commitment, blinding) = prove_range(value, b"simple test");
+            assert!(verify_range(&proof, &commitment, b"simple test").is_ok());
+            assert!(verify_range(&proof, &commitment, b"other test")
+                .unwrap_err()
+                .is_verification_failure());
+
+            // The blinding factor opens the commitment to the value.
+            let pc_gens = PedersenGens::default();
+            assert_eq!(
+                pc_gens.commit(Scalar::from(value), blinding).compress(),
+                commitment
+            );
+        }
+    }
+
+    #[test]
+    fn range_proofs_reject_other_commitments_and_bytes() {
+        let (proof, commitment, _) = prove_range(5, b"simple test");
+        let (_, other, _) = prove_range(5, b"simple test");
+        assert!(verify_range(&proof, &other, b"simple test").is_err());
+        assert!(verify_range(&proof[1..], &commitment, b"simple test").is_err());
+        assert!(verify_range(&[], &commitment, b"simple test").is_err());
+    }
+
+    #[test]
+    fn generators_are_shared_across_threads() {
+        let handles: Vec<_> = (0..8u64)
+            .map(|i| {
+                thread::spawn(move || {
+                    let (proof, commitment, _) = prove_range(i, b"simple thread test");
+                    verify_range(&proof, &commitment, b"simple thread test").unwrap();
+                    generators()
+                })
+            })
+            .collect();
+        let generators: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(generators.iter().all(|g| std::ptr::eq(*g, generators[0])));
+    }
+
+    #[cfg(feature = "yoloproofs")]
+    #[test]
+    fn one_hot_proofs_verify_under_their_label() {
+        for &(index, len) in &[(0, 1), (2, 3), (7, 8), (255, MAX_ONE_HOT_LEN)] {
+            let (proof, commitments, _) = prove_one_hot(index, len, b"simple test").unwrap();
+            assert!(verify_one_hot(&proof, &commitments, b"simple test").is_ok());
+            assert!(verify_one_hot(&proof, &commitments, b"other test").is_err());
+
+            let mut swapped = commitments.clone();
+            swapped.reverse();
+            if len > 1 {
+                assert!(verify_one_hot(&proof, &swapped, b"simple test").is_err());
+            }
+        }
+    }
+
+    #[cfg(feature = "yoloproofs")]
+    #[test]
+    fn one_hot_proofs_reject_invalid_statements() {
+        assert!(prove_one_hot(3, 3, b"simple test").is_err());
+        assert!(prove_one_hot(0, MAX_ONE_HOT_LEN + 1, b"simple test").is_err());
+
+        let (proof, commitments, _) = prove_one_hot(1, 4, b"simple test").unwrap();
+        assert!(verify_one_hot(&proof, &commitments[..3], b"simple test").is_err());
+        let too_many = vec![commitments[0]; MAX_ONE_HOT_LEN + 1];
+        assert!(verify_one_hot(&proof, &too_many, b"simple test").is_err());
+    }
+}
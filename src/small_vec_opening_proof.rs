@@ -0,0 +1,401 @@
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::vec::Vec;
+
+#[cfg(feature = "verifier")]
+use core::iter;
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "prover")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::consts::{ELEMENT_BYTES, SMALL_VEC_OPENING_FIXED_ELEMENTS};
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::{BulletproofGensShare, PedersenGens};
+use crate::inner_product_proof::check_vector_length;
+#[cfg(feature = "verifier")]
+use crate::transcript::TranscriptProtocol;
+#[cfg(all(feature = "prover", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+/// A proof of knowledge of the opening of a vector commitment
+/// \\(C = \langle \mathbf{v}, \mathbf{G} \rangle + r \cdot \tilde{B}\\),
+/// for short vectors \\(\mathbf{v}\\).
+///
+/// This is a Schnorr-style sigma protocol made non-interactive with
+/// the transcript: the prover commits to random masks \\(\mathbf{k},
+/// k\_r\\) with \\(R = \langle \mathbf{k}, \mathbf{G} \rangle + k\_r
+/// \cdot \tilde{B}\\), receives a challenge \\(c\\), and responds with
+/// \\(\mathbf{s} = \mathbf{k} + c \mathbf{v}\\) and \\(s\_r = k\_r + c
+/// r\\).  The verifier checks
+/// \\(\langle \mathbf{s}, \mathbf{G} \rangle + s\_r \cdot \tilde{B} = R + c \cdot C\\)
+/// with a single multiscalar multiplication of \\(n + 3\\) points.
+///
+/// The proof has a constant number of rounds and takes \\(32 \cdot
+/// (n + 2)\\) bytes, while an inner-product based argument takes
+/// \\(32 \cdot (2 \lg n + 2)\\) bytes and a logarithmic number of
+/// rounds.  The two sizes meet around \\(n = 8\\), so this proof is
+/// recommended for vectors of length up to 8, where it is also faster
+/// to verify.
+#[derive(Clone, Debug)]
+pub struct SmallVecOpeningProof {
+    /// Commitment to the masks
+    R: CompressedRistretto,
+    /// Responses for the vector entries
+    s: Vec<Scalar>,
+    /// Response for the blinding factor
+    s_blinding: Scalar,
+}
+
+impl SmallVecOpeningProof {
+    #[cfg(feature = "prover")]
+    /// Creates a proof of knowledge of the opening of the commitment to
+    /// `values` with `blinding`, using the generators \\(\mathbf{G}\\)
+    /// of `gens` and \\(\tilde{B}\\) of `pc_gens`.
+    ///
+    /// Returns the proof and the commitment.  The vector may be empty,
+    /// in which case the proof shows knowledge of the blinding factor.
+    pub fn prove_with_rng<T: RngCore + CryptoRng>(
+        pc_gens: &PedersenGens,
+        gens: &BulletproofGensShare,
+        transcript: &mut Transcript,
+        values: &[Scalar],
+        blinding: &Scalar,
+        rng: &mut T,
+    ) -> Result<(SmallVecOpeningProof, CompressedRistretto), ProofError> {
+        let n = values.len();
+        check_vector_length(n)?;
+        let G: Vec<RistrettoPoint> = gens.G_checked(n)?.cloned().collect();
+
+        let C = RistrettoPoint::vartime_multiscalar_mul(
+            values.iter().chain(iter::once(blinding)),
+            G.iter().chain(iter::once(&pc_gens.B_blinding)),
+        )
+        .compress();
+
+        let k: Vec<Scalar> = (0..n).map(|_| Scalar::random(rng)).collect();
+        let k_blinding = Scalar::random(rng);
+        let R = RistrettoPoint::vartime_multiscalar_mul(
+            k.iter().chain(iter::once(&k_blinding)),
+            G.iter().chain(iter::once(&pc_gens.B_blinding)),
+        )
+        .compress();
+
+        transcript.small_vec_opening_domain_sep(n);
+        gens.append_fingerprint(transcript);
+        pc_gens.append_fingerprint(transcript);
+        transcript.append_point(b"C", &C);
+        transcript.append_point(b"R", &R);
+        let c = transcript.challenge_scalar(b"c");
+
+        let s = k
+            .iter()
+            .zip(values.iter())
+            .map(|(k_i, v_i)| k_i + c * v_i)
+            .collect();
+        let s_blinding = k_blinding + c * blinding;
+
+        Ok((SmallVecOpeningProof { R, s, s_blinding }, C))
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof of knowledge of the opening of the commitment to
+    /// `values` with `blinding`.
+    /// This is a convenience wrapper around [`SmallVecOpeningProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove(
+        pc_gens: &PedersenGens,
+        gens: &BulletproofGensShare,
+        transcript: &mut Transcript,
+        values: &[Scalar],
+        blinding: &Scalar,
+    ) -> Result<(SmallVecOpeningProof, CompressedRistretto), ProofError> {
+        SmallVecOpeningProof::prove_with_rng(
+            pc_gens,
+            gens,
+            transcript,
+            values,
+            blinding,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that the prover knows an opening of the commitment `C`
+    /// to a vector of length `n`.
+    ///
+    /// Verification runs on a copy of the `transcript`, which is only
+    /// updated if the proof is valid, so a failed verification can be
+    /// retried with another proof against the same transcript.
+    pub fn verify(
+        &self,
+        pc_gens: &PedersenGens,
+        gens: &BulletproofGensShare,
+        transcript: &mut Transcript,
+        C: &CompressedRistretto,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        check_vector_length(n)?;
+        if self.s.len() != n {
+            return Err(ProofError::VerificationError);
+        }
+        let G: Vec<RistrettoPoint> = gens.G_checked(n)?.cloned().collect();
+
+        let mut working_transcript = transcript.clone();
+        working_transcript.small_vec_opening_domain_sep(n);
+        gens.append_fingerprint(&mut working_transcript);
+        pc_gens.append_fingerprint(&mut working_transcript);
+        working_transcript.append_point(b"C", C);
+        working_transcript.validate_and_append_point(b"R", &self.R)?;
+        let c = working_transcript.challenge_scalar(b"c");
+
+        // <s, G> + s_blinding * B_blinding - c * C - R == 0
+        let check = RistrettoPoint::optional_multiscalar_mul(
+            self.s
+                .iter()
+                .cloned()
+                .chain(iter::once(self.s_blinding))
+                .chain(iter::once(-c))
+                .chain(iter::once(-Scalar::ONE)),
+            G.into_iter()
+                .map(Some)
+                .chain(iter::once(Some(pc_gens.B_blinding)))
+                .chain(iter::once(C.decompress()))
+                .chain(iter::once(self.R.decompress())),
+        )
+        .ok_or(ProofError::VerificationError)?;
+
+        if check.is_identity() {
+            *transcript = working_transcript;
+            Ok(())
+        } else {
+            Err(ProofError::VerificationError)
+        }
+    }
+
+    /// Returns the size in bytes required to serialize the proof.
+    ///
+    /// For vectors of length `n` the proof size is
+    /// \\(32 \cdot (n + 2)\\) bytes.
+    pub fn serialized_size(&self) -> usize {
+        (self.s.len() + SMALL_VEC_OPENING_FIXED_ELEMENTS) * ELEMENT_BYTES
+    }
+
+    /// Serializes the proof into a byte array of \\(n + 2\\) 32-byte elements.
+    /// The layout of the proof is:
+    /// * a compressed Ristretto point \\(R\\),
+    /// * \\(n\\) scalars \\(s\_0, \dots, s\_{n-1}\\),
+    /// * a scalar \\(s\_r\\).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.extend_from_slice(self.R.as_bytes());
+        for s_i in &self.s {
+            buf.extend_from_slice(s_i.as_bytes());
+        }
+        buf.extend_from_slice(self.s_blinding.as_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    /// Returns an error in the following cases:
+    /// * the slice does not have \\(n + 2\\) 32-byte elements,
+    /// * \\(n\\) is larger than [`MAX_VECTOR_LENGTH`](crate::MAX_VECTOR_LENGTH),
+    /// * any of \\(n + 1\\) scalars are not canonical scalars modulo Ristretto group order.
+    ///
+    /// Since every scalar must be canonically encoded, a proof has
+    /// exactly one valid byte representation.
+    pub fn from_bytes(slice: &[u8]) -> Result<SmallVecOpeningProof, ProofError> {
+        if !slice.len().is_multiple_of(ELEMENT_BYTES) {
+            return Err(ProofError::FormatError);
+        }
+        let num_elements = slice.len() / ELEMENT_BYTES;
+        if num_elements < SMALL_VEC_OPENING_FIXED_ELEMENTS {
+            return Err(ProofError::FormatError);
+        }
+        check_vector_length(num_elements - SMALL_VEC_OPENING_FIXED_ELEMENTS)?;
+
+        use crate::util::read32;
+
+        let R = CompressedRistretto(read32(slice));
+        let mut scalars = slice[32..]
+            .chunks(32)
+            .map(|chunk| {
+                Option::from(Scalar::from_canonical_bytes(read32(chunk)))
+                    .ok_or(ProofError::FormatError)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        let s_blinding = scalars.pop().ok_or(ProofError::FormatError)?;
+
+        Ok(SmallVecOpeningProof {
+            R,
+            s: scalars,
+            s_blinding,
+        })
+    }
+}
+
+impl Serialize for SmallVecOpeningProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for SmallVecOpeningProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SmallVecOpeningProofVisitor;
+
+        impl<'de> Visitor<'de> for SmallVecOpeningProofVisitor {
+            type Value = SmallVecOpeningProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid SmallVecOpeningProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<SmallVecOpeningProof, E>
+            where
+                E: serde::de::Error,
+            {
+                SmallVecOpeningProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(SmallVecOpeningProofVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::commitments::Commitment;
+    use crate::generators::BulletproofGens;
+    use crate::inner_product_proof::InnerProductProof;
+    use crate::util;
+
+    fn test_helper(n: usize) {
+        let mut rng = rand::thread_rng();
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let gens = bp_gens.try_share(0).unwrap();
+
+        let values: Vec<Scalar> = (0..n).map(|_| Scalar::random(&mut rng)).collect();
+        let blinding = Scalar::random(&mut rng);
+
+        let mut transcript = Transcript::new(b"SmallVecOpeningTest");
+        let (proof, C) =
+            SmallVecOpeningProof::prove(&pc_gens, &gens, &mut transcript, &values, &blinding)
+                .unwrap();
+
+        let mut transcript = Transcript::new(b"SmallVecOpeningTest");
+        assert!(proof
+            .verify(&pc_gens, &gens, &mut transcript, &C, n)
+            .is_ok());
+
+        // The proof does not verify for another commitment or length,
+        // and a failed verification leaves the transcript untouched.
+        let mut transcript = Transcript::new(b"SmallVecOpeningTest");
+        let wrong_C = Commitment(C)
+            .try_add(&pc_gens.B_blinding.into())
+            .unwrap()
+            .into();
+        assert_eq!(
+            proof.verify(&pc_gens, &gens, &mut transcript, &wrong_C, n),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            proof.verify(&pc_gens, &gens, &mut transcript, &C, n - 1),
+            Err(ProofError::VerificationError)
+        );
+        assert!(proof
+            .verify(&pc_gens, &gens, &mut transcript, &C, n)
+            .is_ok());
+
+        // Serialization round-trips and is smaller than an
+        // inner-product proof up to n = 8.
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), proof.serialized_size());
+        assert_eq!(bytes.len(), 32 * (n + 2));
+        let lg_n = n.next_power_of_two().trailing_zeros() as usize;
+        let ipp_size = InnerProductProof::new_unchecked(
+            vec![CompressedRistretto::default(); lg_n],
+            vec![CompressedRistretto::default(); lg_n],
+            Scalar::ZERO,
+            Scalar::ZERO,
+        )
+        .serialized_size();
+        assert!(bytes.len() <= ipp_size + 2 * 32);
+
+        let parsed = SmallVecOpeningProof::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed.to_bytes(), bytes);
+        let mut transcript = Transcript::new(b"SmallVecOpeningTest");
+        assert!(parsed
+            .verify(&pc_gens, &gens, &mut transcript, &C, n)
+            .is_ok());
+
+        let offsets: Vec<usize> = (1..n + 2).map(|i| 32 * i).collect();
+        util::assert_noncanonical_scalars_rejected(
+            &bytes,
+            &offsets,
+            SmallVecOpeningProof::from_bytes,
+        );
+
+        let deserialized = SmallVecOpeningProof::from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn prove_and_verify_small_vectors() {
+        for n in 1..=8 {
+            test_helper(n);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(4, 1);
+        let values = [Scalar::ONE; 8];
+
+        // Too few generators.
+        let mut transcript = Transcript::new(b"SmallVecOpeningTest");
+        assert_eq!(
+            SmallVecOpeningProof::prove(
+                &pc_gens,
+                &bp_gens.try_share(0).unwrap(),
+                &mut transcript,
+                &values,
+                &Scalar::ONE
+            )
+            .map(|_| ()),
+            Err(ProofError::InvalidGeneratorsLength)
+        );
+
+        // Truncated encodings.
+        assert_eq!(
+            SmallVecOpeningProof::from_bytes(&[0u8; 32]).map(|_| ()),
+            Err(ProofError::FormatError)
+        );
+        assert_eq!(
+            SmallVecOpeningProof::from_bytes(&[0u8; 95]).map(|_| ()),
+            Err(ProofError::FormatError)
+        );
+    }
+}
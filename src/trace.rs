@@ -0,0 +1,26 @@
+//! Internal macros that instrument the proving and verification
+//! phases with [`tracing`](https://docs.rs/tracing) spans and events.
+//!
+//! Without the `tracing` feature the macros expand to nothing, so the
+//! field expressions are not even evaluated.
+//!
+//! Review checklist for new call sites: fields may only carry public
+//! sizes, indices and labels.  Never record values, blinding factors,
+//! challenges, witness scalars or anything derived from them, not even
+//! through their `Debug` representation.
+
+/// Enters a span named `$name` until the end of the enclosing block.
+macro_rules! trace_phase {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::trace_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+/// Emits a debug event within the current span.
+macro_rules! trace_event {
+    ($($args:tt)*) => {
+        #[cfg(feature = "tracing")]
+        tracing::debug!($($args)*);
+    };
+}
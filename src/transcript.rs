@@ -1,77 +1,251 @@
 //! Defines a `TranscriptProtocol` trait for using a Merlin transcript.
 
+#![allow(non_snake_case)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::traits::Identity;
+#[cfg(feature = "verifier")]
+use digest::Digest;
 use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use sha3::Sha3_256;
+#[cfg(feature = "verifier")]
+use subtle::{Choice, ConstantTimeEq};
 
+#[cfg(feature = "verifier")]
 use crate::errors::ProofError;
+use crate::util::Hex;
 
+// Lengths and sizes are appended to transcripts as `u64`s, which is
+// lossless only where `usize` is at most 64 bits wide.  Fail to build
+// anywhere else rather than truncate them.
+const _: () = assert!(core::mem::size_of::<usize>() <= core::mem::size_of::<u64>());
+
+/// The messages of the proofs in this crate, in terms of the three
+/// Merlin operations that every transcript provides.
 pub trait TranscriptProtocol {
+    /// Append a `message` with the given `label`.
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]);
+
+    /// Append a `u64` with the given `label`, encoded as 8 little-endian
+    /// bytes.
+    fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        self.append_message(label, &x.to_le_bytes());
+    }
+
+    /// Append a length or size with the given `label`, as a `u64`.
+    fn append_len(&mut self, label: &'static [u8], len: usize) {
+        // Lossless, see the assertion above.
+        self.append_u64(label, len as u64);
+    }
+
+    /// Fill `dest` with challenge bytes for the given `label`.
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]);
+
+    /// Append length-framed application data that the proof should be bound to.
+    fn append_associated_data(&mut self, associated_data: &[u8]) {
+        self.append_len(b"ad-len", associated_data.len());
+        self.append_message(b"ad", associated_data);
+    }
+
     /// Append a domain separator for an `n`-bit, `m`-party range proof.
-    fn rangeproof_domain_sep(&mut self, n: u64, m: u64);
+    #[cfg(feature = "verifier")]
+    fn rangeproof_domain_sep(&mut self, n: usize, m: usize) {
+        self.append_message(b"dom-sep", b"rangeproof v1");
+        self.append_len(b"n", n);
+        self.append_len(b"m", m);
+    }
 
     /// Append a domain separator for a length-`n` inner product proof.
-    fn innerproduct_domain_sep(&mut self, n: u64);
+    #[cfg(feature = "verifier")]
+    fn innerproduct_domain_sep(&mut self, n: usize) {
+        self.append_message(b"dom-sep", b"ipp v1");
+        self.append_len(b"n", n);
+    }
 
-    /// Append a domain separator for a constraint system.
-    fn r1cs_domain_sep(&mut self);
+    /// Append a domain separator for a length-`n` inner product proof
+    /// against a public vector.
+    #[cfg(feature = "verifier")]
+    fn innerproduct_public_b_domain_sep(&mut self, n: usize) {
+        self.append_message(b"dom-sep", b"ipp-public-b v1");
+        self.append_len(b"n", n);
+    }
 
-    /// Commit a domain separator for a CS without randomized constraints.
-    fn r1cs_1phase_domain_sep(&mut self);
+    /// Append a domain separator entering the scope of the subprotocol
+    /// `label`, so that its messages cannot be confused with messages
+    /// of the parent protocol that use the same labels.
+    #[cfg(feature = "verifier")]
+    fn subprotocol_domain_sep(&mut self, label: &'static [u8]) {
+        self.append_message(b"dom-sep", b"subprotocol");
+        self.append_message(b"subprotocol", label);
+    }
 
-    /// Commit a domain separator for a CS with randomized constraints.
-    fn r1cs_2phase_domain_sep(&mut self);
+    /// Append a domain separator for a length-`n` small vector opening proof.
+    #[cfg(feature = "verifier")]
+    fn small_vec_opening_domain_sep(&mut self, n: usize) {
+        self.append_message(b"dom-sep", b"small-vec-opening v1");
+        self.append_len(b"n", n);
+    }
 
-    /// Append a `scalar` with the given `label`.
-    fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar);
+    /// Append a domain separator for a balance proof of `num_inputs`
+    /// inputs and `num_outputs` outputs.
+    #[cfg(feature = "verifier")]
+    fn balance_proof_domain_sep(&mut self, num_inputs: usize, num_outputs: usize) {
+        self.append_message(b"dom-sep", b"balance-proof v1");
+        self.append_len(b"inputs", num_inputs);
+        self.append_len(b"outputs", num_outputs);
+    }
 
-    /// Append a `point` with the given `label`.
-    fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto);
+    /// Append a domain separator for a proof that `m` committed
+    /// `n`-bit values sum to a committed total.
+    #[cfg(feature = "verifier")]
+    fn vec_sum_proof_domain_sep(&mut self, n: usize, m: usize) {
+        self.append_message(b"dom-sep", b"vec-sum-proof v1");
+        self.append_len(b"n", n);
+        self.append_len(b"m", m);
+    }
 
-    /// Check that a point is not the identity, then append it to the
-    /// transcript.  Otherwise, return an error.
-    fn validate_and_append_point(
-        &mut self,
-        label: &'static [u8],
-        point: &CompressedRistretto,
-    ) -> Result<(), ProofError>;
+    /// Append a domain separator for a nonzero proof.
+    #[cfg(feature = "verifier")]
+    fn nonzero_proof_domain_sep(&mut self) {
+        self.append_message(b"dom-sep", b"nonzero-proof v1");
+    }
 
-    /// Compute a `label`ed challenge variable.
-    fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar;
-}
+    /// Append a domain separator for a proof that two commitments under
+    /// different bases commit to the same value.
+    #[cfg(feature = "verifier")]
+    fn cross_commitment_proof_domain_sep(&mut self) {
+        self.append_message(b"dom-sep", b"cross-commitment-proof v1");
+    }
 
-impl TranscriptProtocol for Transcript {
-    fn rangeproof_domain_sep(&mut self, n: u64, m: u64) {
-        self.append_message(b"dom-sep", b"rangeproof v1");
-        self.append_u64(b"n", n);
-        self.append_u64(b"m", m);
+    /// Append a domain separator for an envelope transcript of
+    /// `num_sections` registered sections.
+    fn envelope_transcript_domain_sep(&mut self, num_sections: usize) {
+        self.append_message(b"dom-sep", b"envelope-transcript v1");
+        self.append_len(b"sections", num_sections);
     }
 
-    fn innerproduct_domain_sep(&mut self, n: u64) {
-        self.append_message(b"dom-sep", b"ipp v1");
-        self.append_u64(b"n", n);
+    /// Append a domain separator for a statement session over
+    /// `num_commitments` commitments.
+    fn statement_session_domain_sep(&mut self, num_commitments: usize) {
+        self.append_message(b"dom-sep", b"statement-session v1");
+        self.append_len(b"commitments", num_commitments);
+    }
+
+    /// Append a domain separator binding the proof to imported
+    /// generators with the given `fingerprint`.
+    #[cfg(feature = "verifier")]
+    fn generators_domain_sep(&mut self, fingerprint: &[u8; 32]) {
+        self.append_message(b"dom-sep", b"imported-gens v1");
+        self.append_message(b"gens", fingerprint);
     }
 
+    /// Append a domain separator binding the proof to non-default
+    /// Pedersen generators `B` and `B_blinding`.
+    #[cfg(feature = "verifier")]
+    fn pedersen_gens_domain_sep(
+        &mut self,
+        B: &CompressedRistretto,
+        B_blinding: &CompressedRistretto,
+    ) {
+        self.append_message(b"dom-sep", b"pedersen-gens v1");
+        self.append_message(b"B", B.as_bytes());
+        self.append_message(b"B_blinding", B_blinding.as_bytes());
+    }
+
+    /// Append a domain separator for a constraint system.
+    #[cfg(feature = "yoloproofs")]
     fn r1cs_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs v1");
     }
 
+    /// Commit a domain separator for a CS without randomized constraints.
+    #[cfg(feature = "yoloproofs")]
     fn r1cs_1phase_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs-1phase");
     }
 
+    /// Commit a domain separator for a CS with randomized constraints.
+    #[cfg(feature = "yoloproofs")]
     fn r1cs_2phase_domain_sep(&mut self) {
         self.append_message(b"dom-sep", b"r1cs-2phase");
     }
 
+    /// Append a `scalar` with the given `label`.
     fn append_scalar(&mut self, label: &'static [u8], scalar: &Scalar) {
         self.append_message(label, scalar.as_bytes());
     }
 
+    /// Append the `scalars` of a public vector with the given `label`,
+    /// after a domain separator recording that they are absorbed one by
+    /// one.
+    #[cfg(feature = "verifier")]
+    fn append_scalars(&mut self, label: &'static [u8], scalars: &[Scalar]) {
+        self.append_message(b"dom-sep", b"scalars v1");
+        self.append_len(b"n", scalars.len());
+        for scalar in scalars {
+            self.append_scalar(label, scalar);
+        }
+    }
+
+    /// Append a SHA3-256 hash of the `scalars` of a public vector with
+    /// the given `label`, after a domain separator recording that they
+    /// are hashed.
+    ///
+    /// The hash covers the label and the length of the vector as well
+    /// as its elements, so two vectors absorb the same message only if
+    /// they collide under SHA3-256.  Challenges derived afterwards then
+    /// bind the vector as if every element had been appended, under the
+    /// collision resistance of the hash rather than that of the
+    /// transcript.  The domain separator differs from that of
+    /// [`append_scalars`](TranscriptProtocol::append_scalars), so a
+    /// prover and a verifier using different modes derive different
+    /// challenges.
+    #[cfg(feature = "verifier")]
+    fn append_scalars_hashed(&mut self, label: &'static [u8], scalars: &[Scalar]) {
+        let mut hash = Sha3_256::new();
+        hash.update(b"Bulletproofs hashed scalars");
+        hash.update((label.len() as u64).to_le_bytes());
+        hash.update(label);
+        hash.update((scalars.len() as u64).to_le_bytes());
+        for scalar in scalars {
+            hash.update(scalar.as_bytes());
+        }
+        self.append_message(b"dom-sep", b"scalars-hashed v1");
+        self.append_len(b"n", scalars.len());
+        self.append_message(label, &hash.finalize());
+    }
+
+    /// Append the `scalars` of a public vector as selected by `mode`.
+    #[cfg(feature = "verifier")]
+    fn append_public_scalars(
+        &mut self,
+        label: &'static [u8],
+        scalars: &[Scalar],
+        mode: PublicVectorMode,
+    ) {
+        match mode {
+            PublicVectorMode::Direct => self.append_scalars(label, scalars),
+            PublicVectorMode::Hashed => self.append_scalars_hashed(label, scalars),
+        }
+    }
+
+    /// Append a `point` with the given `label`.
     fn append_point(&mut self, label: &'static [u8], point: &CompressedRistretto) {
         self.append_message(label, point.as_bytes());
     }
 
+    /// Check that a point is not the identity, then append it to the
+    /// transcript.  Otherwise, return an error.
+    #[cfg(feature = "verifier")]
     fn validate_and_append_point(
         &mut self,
         label: &'static [u8],
@@ -82,10 +256,24 @@ impl TranscriptProtocol for Transcript {
         if point.is_identity() {
             Err(ProofError::VerificationError)
         } else {
-            Ok(self.append_message(label, point.as_bytes()))
+            self.append_message(label, point.as_bytes());
+            Ok(())
         }
     }
 
+    /// Append a `point` to the transcript and return whether it is not
+    /// the identity, without branching on the point.
+    #[cfg(feature = "verifier")]
+    fn append_and_check_point(
+        &mut self,
+        label: &'static [u8],
+        point: &CompressedRistretto,
+    ) -> Choice {
+        self.append_message(label, point.as_bytes());
+        !point.ct_eq(&CompressedRistretto::identity())
+    }
+
+    /// Compute a `label`ed challenge variable.
     fn challenge_scalar(&mut self, label: &'static [u8]) -> Scalar {
         let mut buf = [0u8; 64];
         self.challenge_bytes(label, &mut buf);
@@ -93,3 +281,377 @@ impl TranscriptProtocol for Transcript {
         Scalar::from_bytes_mod_order_wide(&buf)
     }
 }
+
+impl TranscriptProtocol for Transcript {
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        Transcript::append_message(self, label, message);
+    }
+
+    fn append_u64(&mut self, label: &'static [u8], x: u64) {
+        Transcript::append_u64(self, label, x);
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        Transcript::challenge_bytes(self, label, dest);
+    }
+}
+
+/// How a public vector is absorbed into a transcript.
+///
+/// Both sides of a proof must use the same mode: it is recorded in a
+/// domain separator, and proofs created with one mode do not verify
+/// with the other.
+#[allow(dead_code)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PublicVectorMode {
+    /// Every element is appended to the transcript.
+    Direct,
+    /// A single SHA3-256 hash of the vector is appended, for very large
+    /// public vectors on targets where the transcript is slow.
+    Hashed,
+}
+
+/// The rules by which a proof is replayed on its transcript.
+///
+/// Only the current rules are used to create proofs.  The earlier ones
+/// can be selected for verification with the `legacy-v0` feature; see
+/// the [`compat`](crate::compat) module.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(not(feature = "legacy-v0"), allow(dead_code))]
+pub enum TranscriptVersion {
+    /// The rules of release 1.0: the inner-product proof shares the
+    /// transcript scope of its parent protocol, and no generators are
+    /// bound to the transcript.
+    V0,
+    /// The current rules: the inner-product proof has its own scope,
+    /// and non-default generators are bound to the transcript.
+    V1,
+}
+
+/// Returns a transcript for the proofs of an application, bound to the
+/// application's `app_label` and to the `version` of its protocol.
+///
+/// The label given to `Transcript::new` is often the only domain
+/// separation between applications, and generic labels are easily
+/// reused, in which case a proof made for one application also verifies
+/// in the other.  This transcript starts from a label fixed by this
+/// crate, followed by the length-framed `app_label` and the `version`,
+/// so applications with different labels, or versions of the same
+/// application, never share a transcript.  Nothing time-dependent is
+/// appended, so the prover and the verifier derive the same transcript
+/// independently.
+///
+/// ```
+/// use bulletproofs::{app_transcript, BitSize, BulletproofGens, PedersenGens, RangeProof};
+/// use curve25519_dalek::scalar::Scalar;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+/// let blinding = Scalar::random(&mut rand::thread_rng());
+///
+/// let mut transcript = app_transcript(b"example wallet", 1);
+/// let (proof, committed_value) =
+///     RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, 1037578891, &blinding, BitSize::B64)?;
+///
+/// let mut transcript = app_transcript(b"example wallet", 1);
+/// proof.verify_single(&bp_gens, &pc_gens, &mut transcript, &committed_value, BitSize::B64)?;
+///
+/// // The proof does not verify for another application.
+/// let mut transcript = app_transcript(b"example exchange", 1);
+/// assert!(proof
+///     .verify_single(&bp_gens, &pc_gens, &mut transcript, &committed_value, BitSize::B64)
+///     .is_err());
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+pub fn app_transcript(app_label: &[u8], version: u64) -> Transcript {
+    let mut transcript = Transcript::new(b"Bulletproofs application");
+    transcript.append_message(b"dom-sep", b"app v1");
+    transcript.append_len(b"app-len", app_label.len());
+    transcript.append_message(b"app", app_label);
+    transcript.append_u64(b"app-version", version);
+    transcript
+}
+
+/// Creates transcripts for proofs made in the same application context,
+/// such as the range proofs of one block, so that a batch of proofs can
+/// be created and verified without repeating the context for each.
+///
+/// Every transcript starts from the `label` and the length-framed
+/// `context` given to [`TranscriptFactory::new`], which is the state in
+/// which [`RangeProof::prove_single_with_ad`](crate::RangeProof::prove_single_with_ad)
+/// creates proofs for the same label and associated data.
+#[derive(Clone)]
+pub struct TranscriptFactory {
+    transcript: Transcript,
+}
+
+impl TranscriptFactory {
+    /// Creates a factory of transcripts with the given `label`, bound
+    /// to the application `context`.
+    pub fn new(label: &'static [u8], context: &[u8]) -> Self {
+        let mut transcript = Transcript::new(label);
+        transcript.append_associated_data(context);
+        TranscriptFactory { transcript }
+    }
+
+    /// Returns a transcript bound to the context of the factory.
+    pub fn fresh(&self) -> Transcript {
+        self.transcript.clone()
+    }
+
+    /// Returns a transcript bound to the context of the factory and to
+    /// the `item_context` of one proof, such as a transaction id.
+    ///
+    /// The item context is appended with its length, so no two item
+    /// contexts give the same transcript, and none gives the transcript
+    /// of [`TranscriptFactory::fresh`].
+    pub fn fresh_with_context(&self, item_context: &[u8]) -> Transcript {
+        let mut transcript = self.fresh();
+        transcript.append_len(b"item-len", item_context.len());
+        transcript.append_message(b"item", item_context);
+        transcript
+    }
+}
+
+/// A transcript bound once to commitments and context shared by several
+/// proofs, such as a range proof and a [`NonZeroProof`](crate::NonZeroProof)
+/// for the same commitment, which hands out one branch per proof.
+///
+/// Every branch starts from the state after the commitments and the
+/// context, followed by the number of branches taken before it and its
+/// label.  The prover and the verifier create the session from the
+/// same transcript, commitments, and context, and take the branches in
+/// the same order, so a proof only verifies in the position it was
+/// made in, and only against the commitments of the session.
+///
+/// ```
+/// use bulletproofs::{
+///     BitSize, BulletproofGens, NonZeroProof, PedersenGens, RangeProof, StatementSession,
+/// };
+/// use curve25519_dalek::scalar::Scalar;
+/// use merlin::Transcript;
+///
+/// let pc_gens = PedersenGens::default();
+/// let bp_gens = BulletproofGens::new(64, 1);
+/// let (v, v_blinding) = (1037578891u64, Scalar::from(7u64));
+/// let commitment = pc_gens.commit(Scalar::from(v), v_blinding).compress();
+///
+/// let transcript = Transcript::new(b"doctest example");
+/// let mut session = StatementSession::new(transcript.clone(), &[commitment], b"payment 17");
+/// let range_proof = RangeProof::prove_in_session(
+///     &bp_gens,
+///     &pc_gens,
+///     &mut session,
+///     0,
+///     v,
+///     &v_blinding,
+///     BitSize::B64,
+/// )?;
+/// let nonzero_proof =
+///     NonZeroProof::prove_in_session(&pc_gens, &mut session, 0, Scalar::from(v), v_blinding)?;
+///
+/// let mut session = StatementSession::new(transcript, &[commitment], b"payment 17");
+/// range_proof.verify_in_session(&bp_gens, &pc_gens, &mut session, 0, BitSize::B64)?;
+/// nonzero_proof.verify_in_session(&pc_gens, &mut session, 0)?;
+/// # Ok::<(), bulletproofs::ProofError>(())
+/// ```
+#[derive(Clone)]
+pub struct StatementSession {
+    transcript: Transcript,
+    commitments: Vec<CompressedRistretto>,
+    branches: u64,
+}
+
+impl StatementSession {
+    /// Starts a session from `transcript` by appending the shared
+    /// `commitments` and the length-framed application `context`.
+    pub fn new(
+        mut transcript: Transcript,
+        commitments: &[CompressedRistretto],
+        context: &[u8],
+    ) -> Self {
+        transcript.statement_session_domain_sep(commitments.len());
+        for commitment in commitments {
+            transcript.append_point(b"V", commitment);
+        }
+        transcript.append_associated_data(context);
+        StatementSession {
+            transcript,
+            commitments: commitments.to_vec(),
+            branches: 0,
+        }
+    }
+
+    /// Returns the commitments shared by the proofs of the session.
+    pub fn commitments(&self) -> &[CompressedRistretto] {
+        &self.commitments
+    }
+
+    /// Returns the `index`-th commitment of the session, or
+    /// [`ProofError::InvalidInputLength`] if there are not that many.
+    #[cfg(feature = "verifier")]
+    pub(crate) fn commitment(&self, index: usize) -> Result<CompressedRistretto, ProofError> {
+        self.commitments
+            .get(index)
+            .copied()
+            .ok_or(ProofError::InvalidInputLength)
+    }
+
+    /// Returns the transcript for the next proof of the session, bound
+    /// to its position in the session and to `label`.
+    ///
+    /// A branch is taken even if the proof made or verified on it
+    /// fails, so that later branches keep their positions.
+    pub fn branch(&mut self, label: &[u8]) -> Transcript {
+        let mut transcript = self.transcript.clone();
+        transcript.append_u64(b"branch", self.branches);
+        transcript.append_len(b"branch-len", label.len());
+        transcript.append_message(b"branch-label", label);
+        self.branches += 1;
+        transcript
+    }
+}
+
+/// One operation performed on a Merlin transcript, as recorded by
+/// [`RangeProof::transcript_trace`](crate::RangeProof::transcript_trace).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TranscriptOp {
+    /// A message appended with the given label.  Integers are appended
+    /// as their 8-byte little-endian encoding.
+    Append {
+        /// The label of the message.
+        label: &'static [u8],
+        /// The message.
+        message: Vec<u8>,
+    },
+    /// Challenge bytes squeezed with the given label.  Challenge scalars
+    /// are the 64 bytes reduced modulo the group order.
+    Challenge {
+        /// The label of the challenge.
+        label: &'static [u8],
+        /// The challenge bytes.
+        bytes: Vec<u8>,
+    },
+}
+
+impl TranscriptOp {
+    /// Returns the operation as a JSON object with the fields `bytes`,
+    /// the message or challenge bytes as lowercase hex, `label`, and
+    /// `op`, which is `"append"` or `"challenge"`.  The keys are sorted
+    /// and there is no whitespace.
+    ///
+    /// The labels used by this crate are printable ASCII without quotes
+    /// or backslashes, so they need no escaping.
+    pub fn to_json(&self) -> String {
+        let (op, label, bytes) = match self {
+            TranscriptOp::Append { label, message } => ("append", label, message),
+            TranscriptOp::Challenge { label, bytes } => ("challenge", label, bytes),
+        };
+        format!(
+            "{{\"bytes\":\"{}\",\"label\":\"{}\",\"op\":\"{}\"}}",
+            Hex(bytes),
+            String::from_utf8_lossy(label),
+            op
+        )
+    }
+}
+
+/// A transcript that records the operations performed on it.
+#[cfg(feature = "verifier")]
+pub(crate) struct TranscriptRecorder {
+    transcript: Transcript,
+    ops: Vec<TranscriptOp>,
+}
+
+#[cfg(feature = "verifier")]
+impl TranscriptRecorder {
+    /// Starts recording from the state of `transcript`.
+    pub(crate) fn new(transcript: &Transcript) -> Self {
+        TranscriptRecorder {
+            transcript: transcript.clone(),
+            ops: Vec::new(),
+        }
+    }
+
+    /// Returns the transcript in its current state and the recorded
+    /// operations.
+    pub(crate) fn into_parts(self) -> (Transcript, Vec<TranscriptOp>) {
+        (self.transcript, self.ops)
+    }
+}
+
+#[cfg(feature = "verifier")]
+impl TranscriptProtocol for TranscriptRecorder {
+    fn append_message(&mut self, label: &'static [u8], message: &[u8]) {
+        self.transcript.append_message(label, message);
+        self.ops.push(TranscriptOp::Append {
+            label,
+            message: message.to_vec(),
+        });
+    }
+
+    fn challenge_bytes(&mut self, label: &'static [u8], dest: &mut [u8]) {
+        self.transcript.challenge_bytes(label, dest);
+        self.ops.push(TranscriptOp::Challenge {
+            label,
+            bytes: dest.to_vec(),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A named way of adding a domain separator to a transcript.
+    type Separator = (&'static str, fn(&mut Transcript));
+
+    #[test]
+    fn empty_statements_have_distinct_domain_separators() {
+        let separators: [Separator; 19] = [
+            ("none", |_| {}),
+            ("rangeproof(0, 0)", |t| t.rangeproof_domain_sep(0, 0)),
+            ("rangeproof(0, 1)", |t| t.rangeproof_domain_sep(0, 1)),
+            ("rangeproof(1, 0)", |t| t.rangeproof_domain_sep(1, 0)),
+            ("ipp(0)", |t| t.innerproduct_domain_sep(0)),
+            ("subprotocol(ipp) ipp(0)", |t| {
+                t.subprotocol_domain_sep(b"ipp");
+                t.innerproduct_domain_sep(0)
+            }),
+            ("ipp-public-b(0)", |t| t.innerproduct_public_b_domain_sep(0)),
+            ("scalars([])", |t| t.append_scalars(b"b", &[])),
+            ("scalars-hashed([])", |t| t.append_scalars_hashed(b"b", &[])),
+            ("small-vec-opening(0)", |t| {
+                t.small_vec_opening_domain_sep(0)
+            }),
+            ("balance-proof(0, 0)", |t| t.balance_proof_domain_sep(0, 0)),
+            ("balance-proof(0, 1)", |t| t.balance_proof_domain_sep(0, 1)),
+            ("balance-proof(1, 0)", |t| t.balance_proof_domain_sep(1, 0)),
+            ("vec-sum-proof(0, 1)", |t| t.vec_sum_proof_domain_sep(0, 1)),
+            ("vec-sum-proof(1, 0)", |t| t.vec_sum_proof_domain_sep(1, 0)),
+            ("nonzero-proof", |t| t.nonzero_proof_domain_sep()),
+            ("cross-commitment-proof", |t| {
+                t.cross_commitment_proof_domain_sep()
+            }),
+            ("statement-session(0)", |t| {
+                t.statement_session_domain_sep(0)
+            }),
+            ("envelope-transcript(0)", |t| {
+                t.envelope_transcript_domain_sep(0)
+            }),
+        ];
+        let challenges: Vec<_> = separators
+            .iter()
+            .map(|(_, domain_sep)| {
+                let mut transcript = Transcript::new(b"DomainSepTest");
+                domain_sep(&mut transcript);
+                transcript.challenge_scalar(b"x")
+            })
+            .collect();
+        for (i, (name_i, _)) in separators.iter().enumerate() {
+            for (j, (name_j, _)) in separators.iter().enumerate().skip(i + 1) {
+                assert_ne!(challenges[i], challenges[j], "{} and {}", name_i, name_j);
+            }
+        }
+    }
+}
@@ -3,19 +3,29 @@
 
 extern crate alloc;
 
+#[cfg(feature = "prover")]
 use alloc::vec;
 use alloc::vec::Vec;
 use clear_on_drop::clear::Clear;
+use core::borrow::Borrow;
+use core::fmt;
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::ristretto::RistrettoPoint;
 use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::MultiscalarMul;
+use subtle::{Choice, ConditionallySelectable};
 
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 use crate::inner_product_proof::inner_product;
 
+#[cfg(feature = "prover")]
 /// Represents a degree-1 vector polynomial \\(\mathbf{a} + \mathbf{b} \cdot x\\).
 pub struct VecPoly1(pub Vec<Scalar>, pub Vec<Scalar>);
 
 /// Represents a degree-3 vector polynomial
 /// \\(\mathbf{a} + \mathbf{b} \cdot x + \mathbf{c} \cdot x^2 + \mathbf{d} \cdot x^3 \\).
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 pub struct VecPoly3(
     pub Vec<Scalar>,
     pub Vec<Scalar>,
@@ -23,12 +33,13 @@ pub struct VecPoly3(
     pub Vec<Scalar>,
 );
 
+#[cfg(feature = "prover")]
 /// Represents a degree-2 scalar polynomial \\(a + b \cdot x + c \cdot x^2\\)
 pub struct Poly2(pub Scalar, pub Scalar, pub Scalar);
 
 /// Represents a degree-6 scalar polynomial, without the zeroth degree
 /// \\(a \cdot x + b \cdot x^2 + c \cdot x^3 + d \cdot x^4 + e \cdot x^5 + f \cdot x^6\\)
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 pub struct Poly6 {
     pub t1: Scalar,
     pub t2: Scalar,
@@ -40,7 +51,8 @@ pub struct Poly6 {
 
 /// Provides an iterator over the powers of a `Scalar`.
 ///
-/// This struct is created by the `exp_iter` function.
+/// This struct is created by the [`exp_iter`] and [`exp_iter_from`]
+/// functions.
 pub struct ScalarExp {
     x: Scalar,
     next_exp_x: Scalar,
@@ -56,60 +68,107 @@ impl Iterator for ScalarExp {
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
-        (usize::max_value(), None)
+        (usize::MAX, None)
     }
 }
 
-/// Return an iterator of the powers of `x`.
+/// Return an iterator of the powers of `x`: \\(1, x, x^2, \dots\\).
 pub fn exp_iter(x: Scalar) -> ScalarExp {
     let next_exp_x = Scalar::ONE;
     ScalarExp { x, next_exp_x }
 }
 
-pub fn add_vec(a: &[Scalar], b: &[Scalar]) -> Vec<Scalar> {
-    if a.len() != b.len() {
-        // throw some error
-        //println!("lengths of vectors don't match for vector addition");
-    }
-    let mut out = vec![Scalar::ZERO; b.len()];
-    for i in 0..a.len() {
-        out[i] = a[i] + b[i];
+/// Return an iterator of the powers of `x` starting at `x^start_exp`:
+/// \\(x^{s}, x^{s+1}, x^{s+2}, \dots\\) for \\(s = \texttt{start\\_exp}\\).
+///
+/// The first power is computed by binary exponentiation, so this
+/// takes \\(O(\lg s)\\) multiplications rather than skipping \\(s\\)
+/// items of [`exp_iter`].
+pub fn exp_iter_from(x: Scalar, start_exp: u64) -> ScalarExp {
+    let next_exp_x = scalar_exp_vartime(&x, start_exp);
+    ScalarExp { x, next_exp_x }
+}
+
+/// Returns the lowest `n` bits of `v` as `Choice`s, least significant
+/// bit first.  Bits above the 64th are zero.
+pub fn bits_to_choices(v: u64, n: usize) -> Vec<Choice> {
+    (0..n)
+        .map(|i| {
+            let bit = v.checked_shr(i as u32).unwrap_or(0) & 1;
+            Choice::from(bit as u8)
+        })
+        .collect()
+}
+
+/// Computes a commitment to a bit vector \\(\mathbf{a}\_L\\) in constant time:
+/// \\[
+/// \langle \mathbf{a}\_L, \mathbf{G} \rangle + \langle \mathbf{a}\_L - \mathbf{1}, \mathbf{H} \rangle + \texttt{blinding\\_term},
+/// \\]
+/// that is, \\(G\_i\\) is added for each set bit and \\(-H\_i\\) for each
+/// unset bit.
+///
+/// The scalars \\(\pm 1\\) and \\(0\\) are chosen with
+/// `ConditionallySelectable`, and the sum is computed with a single
+/// constant-time multiscalar multiplication, so neither the control
+/// flow nor the memory accesses depend on the values of `bits`.
+///
+/// The iterators are consumed in lockstep, and the commitment covers
+/// as many bits as the shortest of them yields.  Generators taken with
+/// [`BulletproofGensShare::G_checked`](crate::BulletproofGensShare::G_checked)
+/// turn a share with too few generators into an error rather than a
+/// commitment to fewer bits.
+pub fn ct_select_commit<IB, IG, IH>(
+    bits: IB,
+    G: IG,
+    H: IH,
+    blinding_term: RistrettoPoint,
+) -> RistrettoPoint
+where
+    IB: IntoIterator,
+    IB::Item: Borrow<Choice>,
+    IG: IntoIterator,
+    IG::Item: Borrow<RistrettoPoint>,
+    IH: IntoIterator,
+    IH::Item: Borrow<RistrettoPoint>,
+{
+    let minus_one = -Scalar::ONE;
+    let (mut scalars, points): (Vec<Scalar>, Vec<RistrettoPoint>) = bits
+        .into_iter()
+        .zip(G.into_iter().zip(H))
+        .flat_map(|(bit, (G_i, H_i))| {
+            let bit = *bit.borrow();
+            // a_L[i] = bit, a_R[i] = bit - 1
+            let a_L_i = Scalar::conditional_select(&Scalar::ZERO, &Scalar::ONE, bit);
+            let a_R_i = Scalar::conditional_select(&minus_one, &Scalar::ZERO, bit);
+            [(a_L_i, *G_i.borrow()), (a_R_i, *H_i.borrow())]
+        })
+        .unzip();
+
+    let commitment = blinding_term + RistrettoPoint::multiscalar_mul(&scalars, &points);
+
+    for e in scalars.iter_mut() {
+        e.clear();
     }
-    out
+
+    commitment
 }
 
+#[cfg(feature = "prover")]
 impl VecPoly1 {
     pub fn zero(n: usize) -> Self {
         VecPoly1(vec![Scalar::ZERO; n], vec![Scalar::ZERO; n])
     }
 
-    pub fn inner_product(&self, rhs: &VecPoly1) -> Poly2 {
-        // Uses Karatsuba's method
-        let l = self;
-        let r = rhs;
-
-        let t0 = inner_product(&l.0, &r.0);
-        let t2 = inner_product(&l.1, &r.1);
-
-        let l0_plus_l1 = add_vec(&l.0, &l.1);
-        let r0_plus_r1 = add_vec(&r.0, &r.1);
-
-        let t1 = inner_product(&l0_plus_l1, &r0_plus_r1) - t0 - t2;
-
-        Poly2(t0, t1, t2)
-    }
-
     pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
-        let n = self.0.len();
-        let mut out = vec![Scalar::ZERO; n];
-        for i in 0..n {
-            out[i] = self.0[i] + self.1[i] * x;
-        }
-        out
+        self.0
+            .iter()
+            .zip(self.1.iter())
+            .map(|(a, b)| a + b * x)
+            .collect()
     }
 }
 
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 impl VecPoly3 {
     pub fn zero(n: usize) -> Self {
         VecPoly3(
@@ -122,7 +181,8 @@ impl VecPoly3 {
 
     /// Compute an inner product of `lhs`, `rhs` which have the property that:
     /// - `lhs.0` is zero;
-    /// - `rhs.2` is zero;
+    /// - `rhs.2` is zero.
+    ///
     /// This is the case in the constraint system proof.
     pub fn special_inner_product(lhs: &Self, rhs: &Self) -> Poly6 {
         // TODO: make checks that l_poly.0 and r_poly.2 are zero.
@@ -145,28 +205,27 @@ impl VecPoly3 {
     }
 
     pub fn eval(&self, x: Scalar) -> Vec<Scalar> {
-        let n = self.0.len();
-        let mut out = vec![Scalar::ZERO; n];
-        for i in 0..n {
-            out[i] = self.0[i] + x * (self.1[i] + x * (self.2[i] + x * self.3[i]));
-        }
-        out
+        (0..self.0.len())
+            .map(|i| self.0[i] + x * (self.1[i] + x * (self.2[i] + x * self.3[i])))
+            .collect()
     }
 }
 
+#[cfg(feature = "prover")]
 impl Poly2 {
     pub fn eval(&self, x: Scalar) -> Scalar {
         self.0 + x * (self.1 + x * self.2)
     }
 }
 
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 impl Poly6 {
     pub fn eval(&self, x: Scalar) -> Scalar {
         x * (self.t1 + x * (self.t2 + x * (self.t3 + x * (self.t4 + x * (self.t5 + x * self.t6)))))
     }
 }
 
+#[cfg(feature = "prover")]
 impl Drop for VecPoly1 {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
@@ -178,6 +237,7 @@ impl Drop for VecPoly1 {
     }
 }
 
+#[cfg(feature = "prover")]
 impl Drop for Poly2 {
     fn drop(&mut self) {
         self.0.clear();
@@ -186,7 +246,7 @@ impl Drop for Poly2 {
     }
 }
 
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 impl Drop for VecPoly3 {
     fn drop(&mut self) {
         for e in self.0.iter_mut() {
@@ -204,7 +264,7 @@ impl Drop for VecPoly3 {
     }
 }
 
-#[cfg(feature = "yoloproofs")]
+#[cfg(all(feature = "yoloproofs", feature = "prover"))]
 impl Drop for Poly6 {
     fn drop(&mut self) {
         self.t1.clear();
@@ -225,21 +285,22 @@ pub fn scalar_exp_vartime(x: &Scalar, mut n: u64) -> Scalar {
     while n > 0 {
         let bit = n & 1;
         if bit == 1 {
-            result = result * aux;
+            result *= aux;
         }
-        n = n >> 1;
+        n >>= 1;
         aux = aux * aux; // FIXME: one unnecessary mult at the last step here!
     }
     result
 }
 
-/// Takes the sum of all the powers of `x`, up to `n`
+/// Takes the sum of the first `n` powers of `x`,
+/// \\(\sum\_{i=0}^{n-1} x^i = \langle \mathbf{1}, \mathbf{x}^n \rangle\\).
 /// If `n` is a power of 2, it uses the efficient algorithm with `2*lg n` multiplications and additions.
 /// If `n` is not a power of 2, it uses the slow algorithm with `n` multiplications and additions.
-/// In the Bulletproofs case, all calls to `sum_of_powers` should have `n` as a power of 2.
-pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
+/// In the Bulletproofs case, all calls to `geometric_sum` should have `n` as a power of 2.
+pub fn geometric_sum(x: &Scalar, n: usize) -> Scalar {
     if !n.is_power_of_two() {
-        return sum_of_powers_slow(x, n);
+        return geometric_sum_slow(x, n);
     }
     if n == 0 || n == 1 {
         return Scalar::from(n as u64);
@@ -250,13 +311,13 @@ pub fn sum_of_powers(x: &Scalar, n: usize) -> Scalar {
     while m > 2 {
         factor = factor * factor;
         result = result + factor * result;
-        m = m / 2;
+        m /= 2;
     }
     result
 }
 
 // takes the sum of all of the powers of x, up to n
-fn sum_of_powers_slow(x: &Scalar, n: usize) -> Scalar {
+fn geometric_sum_slow(x: &Scalar, n: usize) -> Scalar {
     exp_iter(*x).take(n).sum()
 }
 
@@ -267,6 +328,156 @@ pub fn read32(data: &[u8]) -> [u8; 32] {
     buf32
 }
 
+/// Returns the RNG used by convenience wrappers that do not take an
+/// RNG argument: the thread-local RNG with the `std` feature, or the
+/// operating system RNG through `getrandom` otherwise.
+#[cfg(all(feature = "verifier", feature = "std"))]
+pub fn default_rng() -> rand::rngs::ThreadRng {
+    rand::thread_rng()
+}
+
+/// Returns the RNG used by convenience wrappers that do not take an
+/// RNG argument: the thread-local RNG with the `std` feature, or the
+/// operating system RNG through `getrandom` otherwise.
+#[cfg(all(feature = "verifier", not(feature = "std"), feature = "getrandom"))]
+pub fn default_rng() -> rand_core::OsRng {
+    rand_core::OsRng
+}
+
+/// Writes the 32-byte `elements` of an encoding to `writer` one at a
+/// time, and returns the number of bytes written.
+#[cfg(feature = "std")]
+pub fn write_elements<'a, W, I>(mut writer: W, elements: I) -> std::io::Result<usize>
+where
+    W: std::io::Write,
+    I: IntoIterator<Item = &'a [u8; 32]>,
+{
+    let mut written = 0;
+    for element in elements {
+        writer.write_all(element)?;
+        written += 32;
+    }
+    Ok(written)
+}
+
+/// Reads `reader` to the end and parses the bytes with `parse`.
+///
+/// An input longer than `max_len` is rejected once `max_len + 1`
+/// bytes have been read, so no more than that is ever buffered.
+/// Inputs that are too long or that `parse` rejects are reported as
+/// errors of kind [`InvalidData`](std::io::ErrorKind::InvalidData).
+#[cfg(feature = "std")]
+pub fn read_bounded<R, T, E>(
+    reader: R,
+    max_len: usize,
+    parse: fn(&[u8]) -> Result<T, E>,
+) -> std::io::Result<T>
+where
+    R: std::io::Read,
+    E: std::error::Error + Send + Sync + 'static,
+{
+    use std::io::{Error, ErrorKind, Read};
+
+    let mut buf = Vec::new();
+    reader.take(max_len as u64 + 1).read_to_end(&mut buf)?;
+    if buf.len() > max_len {
+        return Err(Error::new(
+            ErrorKind::InvalidData,
+            "proof exceeds the maximum size",
+        ));
+    }
+    parse(&buf).map_err(|e| Error::new(ErrorKind::InvalidData, e))
+}
+
+/// Decompresses `point` without returning early, substituting the
+/// identity for an invalid encoding and clearing `valid` instead.
+#[cfg(feature = "verifier")]
+pub fn decompress_ct(point: &CompressedRistretto, valid: &mut Choice) -> RistrettoPoint {
+    let decompressed = point.decompress();
+    *valid &= Choice::from(decompressed.is_some() as u8);
+    decompressed.unwrap_or_default()
+}
+
+/// Formats the first 8 bytes of the wrapped slice as hex, so that
+/// `Debug` output of a proof stays short however large the proof is.
+pub struct TruncatedHex<'a>(pub &'a [u8]);
+
+impl fmt::Debug for TruncatedHex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0.iter().take(8) {
+            write!(f, "{:02x}", byte)?;
+        }
+        if self.0.len() > 8 {
+            write!(f, "..")?;
+        }
+        Ok(())
+    }
+}
+
+/// Formats the wrapped slice as lowercase hex.
+pub struct Hex<'a>(pub &'a [u8]);
+
+impl fmt::Display for Hex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for byte in self.0 {
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Little-endian encoding of the Ristretto group order \\(\ell\\).
+#[cfg(test)]
+const GROUP_ORDER_BYTES: [u8; 32] = [
+    0xed, 0xd3, 0xf5, 0x5c, 0x1a, 0x63, 0x12, 0x58, 0xd6, 0x9c, 0xf7, 0xa2, 0xde, 0xf9, 0xde, 0x14,
+    0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x10,
+];
+
+/// Test helper: checks that `parse` accepts the valid serialization
+/// `bytes`, and rejects it once any of the 32-byte scalars at the given
+/// byte `offsets` is replaced with a non-canonical encoding, either
+/// of the same scalar plus \\(\ell\\) or with its high byte set.
+#[cfg(test)]
+pub(crate) fn assert_noncanonical_scalars_rejected<T, E>(
+    bytes: &[u8],
+    offsets: &[usize],
+    parse: impl Fn(&[u8]) -> Result<T, E>,
+) {
+    assert!(parse(bytes).is_ok(), "valid encoding must parse");
+
+    for &offset in offsets {
+        let scalar = read32(&bytes[offset..]);
+        assert!(
+            bool::from(Scalar::from_canonical_bytes(scalar).is_some()),
+            "offset {} does not hold a canonical scalar",
+            offset
+        );
+
+        // s + l encodes the same value mod l, but is not canonical.
+        let mut plus_order = bytes.to_vec();
+        let mut carry = 0u16;
+        for i in 0..32 {
+            let sum = scalar[i] as u16 + GROUP_ORDER_BYTES[i] as u16 + carry;
+            plus_order[offset + i] = sum as u8;
+            carry = sum >> 8;
+        }
+        assert_eq!(carry, 0);
+        assert!(
+            parse(&plus_order).is_err(),
+            "scalar + l at offset {} was accepted",
+            offset
+        );
+
+        let mut high_byte = bytes.to_vec();
+        high_byte[offset + 31] |= 0xf0;
+        assert!(
+            parse(&high_byte).is_err(),
+            "scalar with high byte set at offset {} was accepted",
+            offset
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -295,21 +506,24 @@ mod tests {
             Scalar::from(4u64),
             Scalar::from(5u64),
         ];
-        assert_eq!(Scalar::from(40u64), inner_product(&a, &b));
+        assert_eq!(
+            Scalar::from(40u64),
+            crate::inner_product_proof::inner_product(&a, &b)
+        );
     }
 
     /// Raises `x` to the power `n`.
     fn scalar_exp_vartime_slow(x: &Scalar, n: u64) -> Scalar {
         let mut result = Scalar::ONE;
         for _ in 0..n {
-            result = result * x;
+            result *= x;
         }
         result
     }
 
     #[test]
     fn test_scalar_exp() {
-        let x = Scalar::from_bits(
+        let x = Scalar::from_bytes_mod_order(
             *b"\x84\xfc\xbcOx\x12\xa0\x06\xd7\x91\xd9z:'\xdd\x1e!CE\xf7\xb1\xb9Vz\x810sD\x96\x85\xb5\x07",
         );
         assert_eq!(scalar_exp_vartime(&x, 0), Scalar::ONE);
@@ -326,28 +540,62 @@ mod tests {
     }
 
     #[test]
-    fn test_sum_of_powers() {
+    fn exp_iter_from_matches_scalar_exp() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        for _ in 0..32 {
+            let x = Scalar::random(&mut rng);
+            let start_exp = rng.gen_range(0..1u64 << 20);
+            for (i, exp_x) in exp_iter_from(x, start_exp).take(8).enumerate() {
+                assert_eq!(exp_x, scalar_exp_vartime(&x, start_exp + i as u64));
+            }
+        }
+        let x = Scalar::from(3u64);
+        assert!(exp_iter_from(x, 0).take(16).eq(exp_iter(x).take(16)));
+        assert!(exp_iter_from(x, 5)
+            .take(16)
+            .eq(exp_iter(x).skip(5).take(16)));
+    }
+
+    #[test]
+    fn geometric_sum_matches_scalar_exp() {
+        use rand::Rng;
+        let mut rng = rand::thread_rng();
+        for _ in 0..32 {
+            let x = Scalar::random(&mut rng);
+            let n = rng.gen_range(0..200usize);
+            let expected: Scalar = (0..n as u64).map(|i| scalar_exp_vartime(&x, i)).sum();
+            assert_eq!(geometric_sum(&x, n), expected);
+            // Power-of-two lengths take the fast path.
+            let n = n.next_power_of_two();
+            let expected: Scalar = (0..n as u64).map(|i| scalar_exp_vartime(&x, i)).sum();
+            assert_eq!(geometric_sum(&x, n), expected);
+        }
+    }
+
+    #[test]
+    fn test_geometric_sum() {
         let x = Scalar::from(10u64);
-        assert_eq!(sum_of_powers_slow(&x, 0), sum_of_powers(&x, 0));
-        assert_eq!(sum_of_powers_slow(&x, 1), sum_of_powers(&x, 1));
-        assert_eq!(sum_of_powers_slow(&x, 2), sum_of_powers(&x, 2));
-        assert_eq!(sum_of_powers_slow(&x, 4), sum_of_powers(&x, 4));
-        assert_eq!(sum_of_powers_slow(&x, 8), sum_of_powers(&x, 8));
-        assert_eq!(sum_of_powers_slow(&x, 16), sum_of_powers(&x, 16));
-        assert_eq!(sum_of_powers_slow(&x, 32), sum_of_powers(&x, 32));
-        assert_eq!(sum_of_powers_slow(&x, 64), sum_of_powers(&x, 64));
+        assert_eq!(geometric_sum_slow(&x, 0), geometric_sum(&x, 0));
+        assert_eq!(geometric_sum_slow(&x, 1), geometric_sum(&x, 1));
+        assert_eq!(geometric_sum_slow(&x, 2), geometric_sum(&x, 2));
+        assert_eq!(geometric_sum_slow(&x, 4), geometric_sum(&x, 4));
+        assert_eq!(geometric_sum_slow(&x, 8), geometric_sum(&x, 8));
+        assert_eq!(geometric_sum_slow(&x, 16), geometric_sum(&x, 16));
+        assert_eq!(geometric_sum_slow(&x, 32), geometric_sum(&x, 32));
+        assert_eq!(geometric_sum_slow(&x, 64), geometric_sum(&x, 64));
     }
 
     #[test]
-    fn test_sum_of_powers_slow() {
+    fn test_geometric_sum_slow() {
         let x = Scalar::from(10u64);
-        assert_eq!(sum_of_powers_slow(&x, 0), Scalar::ZERO);
-        assert_eq!(sum_of_powers_slow(&x, 1), Scalar::ONE);
-        assert_eq!(sum_of_powers_slow(&x, 2), Scalar::from(11u64));
-        assert_eq!(sum_of_powers_slow(&x, 3), Scalar::from(111u64));
-        assert_eq!(sum_of_powers_slow(&x, 4), Scalar::from(1111u64));
-        assert_eq!(sum_of_powers_slow(&x, 5), Scalar::from(11111u64));
-        assert_eq!(sum_of_powers_slow(&x, 6), Scalar::from(111111u64));
+        assert_eq!(geometric_sum_slow(&x, 0), Scalar::ZERO);
+        assert_eq!(geometric_sum_slow(&x, 1), Scalar::ONE);
+        assert_eq!(geometric_sum_slow(&x, 2), Scalar::from(11u64));
+        assert_eq!(geometric_sum_slow(&x, 3), Scalar::from(111u64));
+        assert_eq!(geometric_sum_slow(&x, 4), Scalar::from(1111u64));
+        assert_eq!(geometric_sum_slow(&x, 5), Scalar::from(11111u64));
+        assert_eq!(geometric_sum_slow(&x, 6), Scalar::from(111111u64));
     }
 
     #[test]
@@ -365,7 +613,7 @@ mod tests {
             unsafe { slice::from_raw_parts(x.as_ptr() as *const u8, mem::size_of_val(x)) }
         }
 
-        assert_eq!(flat_slice(&v.as_slice()), &[0u8; 64][..]);
+        assert_eq!(flat_slice(v.as_slice()), &[0u8; 64][..]);
         assert_eq!(v[0], Scalar::ZERO);
         assert_eq!(v[1], Scalar::ZERO);
     }
@@ -394,4 +642,93 @@ mod tests {
         assert_eq!(v.1, Scalar::ZERO);
         assert_eq!(v.2, Scalar::ZERO);
     }
+
+    fn random_points(n: usize) -> Vec<RistrettoPoint> {
+        let mut rng = rand::thread_rng();
+        (0..n).map(|_| RistrettoPoint::random(&mut rng)).collect()
+    }
+
+    #[test]
+    fn bits_to_choices_matches_bits() {
+        let v = 0b1011_0010u64;
+        let choices: Vec<u8> = bits_to_choices(v, 8)
+            .into_iter()
+            .map(|c| c.unwrap_u8())
+            .collect();
+        assert_eq!(choices, vec![0, 1, 0, 0, 1, 1, 0, 1]);
+
+        let high: Vec<u8> = bits_to_choices(u64::MAX, 66)
+            .into_iter()
+            .map(|c| c.unwrap_u8())
+            .collect();
+        assert_eq!(&high[..64], &[1u8; 64][..]);
+        assert_eq!(&high[64..], &[0u8; 2][..]);
+    }
+
+    #[test]
+    fn ct_select_commit_matches_naive_sum() {
+        let n = 64;
+        let G = random_points(n);
+        let H = random_points(n);
+        let blinding_term = random_points(1)[0];
+
+        for v in [0u64, 1, 0xdead_beef, u64::MAX] {
+            let expected =
+                G.iter()
+                    .zip(H.iter())
+                    .enumerate()
+                    .fold(blinding_term, |acc, (i, (G_i, H_i))| {
+                        if (v >> i) & 1 == 1 {
+                            acc + G_i
+                        } else {
+                            acc - H_i
+                        }
+                    });
+            let commitment = ct_select_commit(bits_to_choices(v, n), &G, &H, blinding_term);
+            assert_eq!(commitment, expected);
+        }
+    }
+
+    #[test]
+    fn ct_select_commit_of_opposite_bits_sums_to_difference() {
+        // Every bit is either selected into the first or the second
+        // commitment, so their sum only depends on the generators.
+        let n = 32;
+        let G = random_points(n);
+        let H = random_points(n);
+        let identity = RistrettoPoint::default();
+
+        let v = 0x5a5a_a5a5u64;
+        let lhs = ct_select_commit(bits_to_choices(v, n), &G, &H, identity);
+        let rhs = ct_select_commit(bits_to_choices(!v, n), &G, &H, identity);
+        let all_G: RistrettoPoint = G.iter().sum();
+        let all_H: RistrettoPoint = H.iter().sum();
+        assert_eq!(lhs + rhs, all_G - all_H);
+    }
+
+    #[test]
+    fn ct_select_commit_accepts_lazy_iterators() {
+        let n = 16;
+        let G = random_points(n);
+        let H = random_points(n);
+        let blinding_term = random_points(1)[0];
+        let bits = bits_to_choices(0x1234, n);
+
+        let from_slices = ct_select_commit(&bits, &G, &H, blinding_term);
+        let from_iters = ct_select_commit(
+            bits.iter().cloned(),
+            G.iter().cloned(),
+            H.iter()
+                .chain(random_points(4).iter())
+                .cloned()
+                .collect::<Vec<_>>(),
+            blinding_term,
+        );
+        assert_eq!(from_slices, from_iters);
+
+        // The commitment covers only as many bits as are given.
+        let truncated = ct_select_commit(&bits[..8], &G, &H, blinding_term);
+        let expected = ct_select_commit(&bits[..8], &G[..8], &H[..8], blinding_term);
+        assert_eq!(truncated, expected);
+    }
 }
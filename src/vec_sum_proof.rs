@@ -0,0 +1,412 @@
+#![allow(non_snake_case)]
+
+use alloc::vec::Vec;
+#[cfg(feature = "verifier")]
+use core::convert::TryFrom;
+
+#[cfg(feature = "verifier")]
+use curve25519_dalek::ristretto::CompressedRistretto;
+#[cfg(feature = "prover")]
+use curve25519_dalek::scalar::Scalar;
+#[cfg(feature = "verifier")]
+use merlin::Transcript;
+#[cfg(feature = "verifier")]
+use rand_core::{CryptoRng, RngCore};
+use serde::de::Visitor;
+use serde::{self, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::balance_proof::BalanceProof;
+use crate::consts::{BALANCE_PROOF_ELEMENTS, ELEMENT_BYTES};
+use crate::errors::ProofError;
+#[cfg(feature = "verifier")]
+use crate::generators::{AsGenerators, PedersenGens};
+#[cfg(feature = "verifier")]
+use crate::range_proof::BitSize;
+use crate::range_proof::RangeProof;
+#[cfg(feature = "verifier")]
+use crate::transcript::TranscriptProtocol;
+#[cfg(all(feature = "verifier", any(feature = "std", feature = "getrandom")))]
+use crate::util;
+
+/// The number of bytes of the balance proof at the start of an encoded
+/// [`VecSumProof`].
+const BALANCE_BYTES: usize = BALANCE_PROOF_ELEMENTS * ELEMENT_BYTES;
+
+/// A proof that a vector of committed \\(n\\)-bit values sums to a
+/// committed total.
+///
+/// The vector is committed entry by entry, as the value commitments
+/// \\(V\_j = v\_j \cdot B + r\_j \cdot \tilde{B}\\) of an aggregated
+/// range proof, which shows that each \\(v\_j\\) is in \\([0, 2^n)\\).
+/// The total \\(T = t \cdot B + r\_T \cdot \tilde{B}\\) is bound by a
+/// [`BalanceProof`] with the \\(V\_j\\) as inputs and \\(T\\) as the
+/// output, which shows that \\(\sum\_j V\_j - T\\) commits to zero.
+/// Since there are at most [`MAX_AGGREGATION`](crate::consts::MAX_AGGREGATION)
+/// values of at most 64 bits, their sum cannot wrap around the order of
+/// the group, so \\(t = \sum\_j v\_j\\) as integers.
+///
+/// This shows, for example, that a committed histogram is consistent
+/// with a committed count.  As with aggregated range proofs, the number
+/// of values must be a power of two, which zero-valued entries can pad
+/// to.
+#[derive(Clone, Debug)]
+pub struct VecSumProof {
+    /// Binds the total to the sum of the values
+    balance: BalanceProof,
+    /// Bounds each value to \\(n\\) bits
+    range: RangeProof,
+}
+
+impl VecSumProof {
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the `values`, committed with the
+    /// `value_blindings`, are \\(n\\)-bit values summing to the total
+    /// committed with `total_blinding`.
+    ///
+    /// Returns the proof, the commitments to the values, and the
+    /// commitment to the total.  Besides the errors of
+    /// [`RangeProof::prove_multiple_with_rng`], returns
+    /// [`ProofError::WrongNumBlindingFactors`] if there is not one
+    /// blinding factor per value.
+    #[allow(clippy::too_many_arguments)]
+    pub fn prove_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        value_blindings: &[Scalar],
+        total_blinding: &Scalar,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(VecSumProof, Vec<CompressedRistretto>, CompressedRistretto), ProofError> {
+        if values.len() != value_blindings.len() {
+            return Err(ProofError::WrongNumBlindingFactors);
+        }
+        let total: Scalar = values.iter().map(|&v| Scalar::from(v)).sum();
+        let T = pc_gens.commit(total, *total_blinding).compress();
+
+        transcript.vec_sum_proof_domain_sep(n, values.len());
+        transcript.append_point(b"T", &T);
+        let (range, V) = RangeProof::prove_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            value_blindings,
+            BitSize::try_from(n)?,
+            rng,
+        )?;
+        let balance = BalanceProof::prove_with_rng(
+            pc_gens,
+            transcript,
+            value_blindings,
+            core::slice::from_ref(total_blinding),
+            rng,
+        );
+
+        Ok((VecSumProof { balance, range }, V, T))
+    }
+
+    #[cfg(feature = "prover")]
+    /// Creates a proof that the committed `values` are \\(n\\)-bit
+    /// values summing to the committed total.
+    /// This is a convenience wrapper around [`VecSumProof::prove_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn prove<G: AsGenerators + ?Sized>(
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        values: &[u64],
+        value_blindings: &[Scalar],
+        total_blinding: &Scalar,
+        n: usize,
+    ) -> Result<(VecSumProof, Vec<CompressedRistretto>, CompressedRistretto), ProofError> {
+        VecSumProof::prove_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            values,
+            value_blindings,
+            total_blinding,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that the `value_commitments` commit to \\(n\\)-bit
+    /// values whose sum is committed by `total_commitment`.
+    ///
+    /// Returns the errors of [`RangeProof::verify_multiple_with_rng`],
+    /// and [`ProofError::FormatError`] if a commitment is not a valid
+    /// point.  Verification runs on a copy of the `transcript`, which
+    /// is only updated if the proof is valid.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_with_rng<G: AsGenerators + ?Sized, T: RngCore + CryptoRng>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        total_commitment: &CompressedRistretto,
+        n: usize,
+        rng: &mut T,
+    ) -> Result<(), ProofError> {
+        let mut working_transcript = transcript.clone();
+        working_transcript.vec_sum_proof_domain_sep(n, value_commitments.len());
+        working_transcript.append_point(b"T", total_commitment);
+        self.range.verify_multiple_with_rng(
+            bp_gens,
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            BitSize::try_from(n)?,
+            rng,
+        )?;
+        self.balance.verify(
+            pc_gens,
+            &mut working_transcript,
+            value_commitments,
+            core::slice::from_ref(total_commitment),
+        )?;
+        *transcript = working_transcript;
+        Ok(())
+    }
+
+    #[cfg(feature = "verifier")]
+    /// Verifies that the committed values are \\(n\\)-bit values
+    /// summing to the committed total.
+    /// This is a convenience wrapper around [`VecSumProof::verify_with_rng`],
+    /// passing in a threadsafe RNG.
+    #[cfg(any(feature = "std", feature = "getrandom"))]
+    pub fn verify<G: AsGenerators + ?Sized>(
+        &self,
+        bp_gens: &G,
+        pc_gens: &PedersenGens,
+        transcript: &mut Transcript,
+        value_commitments: &[CompressedRistretto],
+        total_commitment: &CompressedRistretto,
+        n: usize,
+    ) -> Result<(), ProofError> {
+        self.verify_with_rng(
+            bp_gens,
+            pc_gens,
+            transcript,
+            value_commitments,
+            total_commitment,
+            n,
+            &mut util::default_rng(),
+        )
+    }
+
+    /// Returns the size in bytes required to serialize the proof: the
+    /// 64 bytes of the balance proof and the size of the range proof.
+    pub fn serialized_size(&self) -> usize {
+        BALANCE_BYTES + self.range.serialized_size()
+    }
+
+    /// Serializes the proof into a byte array.  The layout of the proof
+    /// is:
+    /// * the balance proof, as in [`BalanceProof::to_bytes`],
+    /// * the range proof, as in [`RangeProof::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buf = Vec::with_capacity(self.serialized_size());
+        buf.extend_from_slice(&self.balance.to_bytes());
+        buf.extend_from_slice(&self.range.to_bytes());
+        buf
+    }
+
+    /// Deserializes the proof from a byte slice.
+    ///
+    /// Returns [`ProofError::FormatError`] if the slice is shorter than
+    /// a balance proof, or if either part is malformed.
+    pub fn from_bytes(slice: &[u8]) -> Result<VecSumProof, ProofError> {
+        if slice.len() < BALANCE_BYTES {
+            return Err(ProofError::FormatError);
+        }
+        let (balance, range) = slice.split_at(BALANCE_BYTES);
+        Ok(VecSumProof {
+            balance: BalanceProof::from_bytes(balance)?,
+            range: RangeProof::from_bytes(range)?,
+        })
+    }
+}
+
+impl Serialize for VecSumProof {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_bytes(&self.to_bytes()[..])
+    }
+}
+
+impl<'de> Deserialize<'de> for VecSumProof {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VecSumProofVisitor;
+
+        impl<'de> Visitor<'de> for VecSumProofVisitor {
+            type Value = VecSumProof;
+
+            fn expecting(&self, formatter: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                formatter.write_str("a valid VecSumProof")
+            }
+
+            fn visit_bytes<E>(self, v: &[u8]) -> Result<VecSumProof, E>
+            where
+                E: serde::de::Error,
+            {
+                VecSumProof::from_bytes(v).map_err(serde::de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_bytes(VecSumProofVisitor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::BulletproofGens;
+
+    fn blindings(count: usize) -> Vec<Scalar> {
+        let mut rng = rand::thread_rng();
+        (0..count).map(|_| Scalar::random(&mut rng)).collect()
+    }
+
+    #[test]
+    fn histogram_matches_its_count() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(16, 4);
+        let histogram = [3, 0, 65535, 12];
+        let value_blindings = blindings(4);
+        let total_blinding = Scalar::from(5u64);
+
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        let (proof, V, T) = VecSumProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &histogram,
+            &value_blindings,
+            &total_blinding,
+            16,
+        )
+        .unwrap();
+        assert_eq!(
+            T,
+            pc_gens
+                .commit(Scalar::from(65550u64), total_blinding)
+                .compress()
+        );
+
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes.len(), proof.serialized_size());
+        let proof = VecSumProof::from_bytes(&bytes).unwrap();
+
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &V, &T, 16)
+            .is_ok());
+
+        // The proof does not verify for another total, nor for the
+        // values in another order.
+        let other_T = pc_gens
+            .commit(Scalar::from(65551u64), total_blinding)
+            .compress();
+        let swapped = [V[1], V[0], V[2], V[3]];
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &V, &other_T, 16)
+            .unwrap_err()
+            .is_verification_failure());
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &swapped, &T, 16)
+            .unwrap_err()
+            .is_verification_failure());
+    }
+
+    #[test]
+    fn inconsistent_total_is_rejected() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 2);
+        let value_blindings = blindings(2);
+        let total_blinding = Scalar::ONE;
+
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        let (proof, V, _) = VecSumProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[10, 20],
+            &value_blindings,
+            &total_blinding,
+            8,
+        )
+        .unwrap();
+
+        // A total that does not match the values, with the blinding
+        // factor the proof was created for.
+        let T = pc_gens
+            .commit(Scalar::from(31u64), total_blinding)
+            .compress();
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        let before = transcript.clone();
+        assert!(proof
+            .verify(&bp_gens, &pc_gens, &mut transcript, &V, &T, 8)
+            .unwrap_err()
+            .is_verification_failure());
+
+        // The transcript is left unchanged.
+        let (mut a, mut b) = (transcript, before);
+        assert_eq!(a.challenge_scalar(b"x"), b.challenge_scalar(b"x"));
+    }
+
+    #[test]
+    fn rejects_malformed_inputs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 4);
+
+        let mut transcript = Transcript::new(b"VecSumProofTest");
+        assert_eq!(
+            VecSumProof::prove(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[1, 2],
+                &blindings(3),
+                &Scalar::ONE,
+                8
+            )
+            .map(|_| ()),
+            Err(ProofError::WrongNumBlindingFactors)
+        );
+        assert_eq!(
+            VecSumProof::prove(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &[1, 2, 3],
+                &blindings(3),
+                &Scalar::ONE,
+                8
+            )
+            .map(|_| ()),
+            Err(ProofError::InvalidAggregation)
+        );
+
+        assert_eq!(
+            VecSumProof::from_bytes(&[0u8; 63]).map(|_| ()),
+            Err(ProofError::FormatError)
+        );
+        assert_eq!(
+            VecSumProof::from_bytes(&[0u8; 64]).map(|_| ()),
+            Err(ProofError::FormatError)
+        );
+    }
+}
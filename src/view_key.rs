@@ -0,0 +1,129 @@
+//! The `view_key` module contains API for deriving commitment
+//! blinding factors from a view key.
+//!
+//! A prover that derives the blinding factor of its `i`-th commitment
+//! as \\(H(\texttt{view\\_key} \\| i)\\) can hand the view key to an
+//! auditor, who can then recompute the blindings and check claimed
+//! openings of the commitments without the prover's involvement and
+//! without learning anything beyond the opened values.
+
+#![allow(non_snake_case)]
+#![deny(missing_docs)]
+
+use clear_on_drop::clear::Clear;
+use core::fmt;
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use digest::Digest;
+use sha3::Sha3_512;
+
+use crate::generators::PedersenGens;
+
+/// A secret key from which commitment blinding factors are derived.
+#[derive(Clone)]
+pub struct ViewKey([u8; 32]);
+
+impl ViewKey {
+    /// Creates a view key from 32 secret bytes.
+    pub fn from_bytes(bytes: [u8; 32]) -> Self {
+        ViewKey(bytes)
+    }
+
+    /// Derives the blinding factor for the commitment with the given `index`.
+    ///
+    /// The blinding factor is the SHA3-512 hash of a domain separator,
+    /// the view key and the little-endian `index`, reduced modulo the
+    /// group order.
+    pub fn derive_blinding(&self, index: u64) -> Scalar {
+        let hash = Sha3_512::new()
+            .chain_update(b"bulletproofs view key blinding")
+            .chain_update(self.0)
+            .chain_update(index.to_le_bytes());
+        Scalar::from_hash(hash)
+    }
+
+    /// Checks that `commitment` is a commitment to `value` with the
+    /// blinding factor derived for `index`.
+    pub fn verify_opening(
+        &self,
+        pc_gens: &PedersenGens,
+        commitment: &CompressedRistretto,
+        value: u64,
+        index: u64,
+    ) -> bool {
+        pc_gens.verify_opening(commitment, value, self.derive_blinding(index))
+    }
+}
+
+impl fmt::Debug for ViewKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ViewKey(..)")
+    }
+}
+
+impl Drop for ViewKey {
+    fn drop(&mut self) {
+        self.0.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::generators::BulletproofGens;
+    use crate::range_proof::{BitSize, RangeProof};
+    use merlin::Transcript;
+
+    #[test]
+    fn auditor_checks_openings() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(32, 1);
+        let view_key = ViewKey::from_bytes([7u8; 32]);
+        let values = [0u64, 1, 42, 1037578891];
+
+        // The prover derives each blinding from the view key.
+        let commitments: Vec<CompressedRistretto> = values
+            .iter()
+            .enumerate()
+            .map(|(i, &v)| {
+                let mut transcript = Transcript::new(b"ViewKeyTest");
+                let (_, V) = RangeProof::prove_single_with_view_key(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    v,
+                    &view_key,
+                    i as u64,
+                    BitSize::B32,
+                )
+                .unwrap();
+                V
+            })
+            .collect();
+
+        // The auditor recomputes the blindings and checks the values.
+        let auditor_key = ViewKey::from_bytes([7u8; 32]);
+        for (i, (V, &v)) in commitments.iter().zip(values.iter()).enumerate() {
+            assert!(auditor_key.verify_opening(&pc_gens, V, v, i as u64));
+            assert!(!auditor_key.verify_opening(&pc_gens, V, v + 1, i as u64));
+            assert!(!auditor_key.verify_opening(&pc_gens, V, v, i as u64 + 1));
+        }
+
+        let wrong_key = ViewKey::from_bytes([8u8; 32]);
+        for (i, (V, &v)) in commitments.iter().zip(values.iter()).enumerate() {
+            assert!(!wrong_key.verify_opening(&pc_gens, V, v, i as u64));
+        }
+    }
+
+    #[test]
+    fn derived_blindings_are_distinct() {
+        let view_key = ViewKey::from_bytes([1u8; 32]);
+        assert_eq!(view_key.derive_blinding(5), view_key.derive_blinding(5));
+        assert_ne!(view_key.derive_blinding(5), view_key.derive_blinding(6));
+        assert_ne!(
+            view_key.derive_blinding(5),
+            ViewKey::from_bytes([2u8; 32]).derive_blinding(5)
+        );
+    }
+}
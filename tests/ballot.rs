@@ -0,0 +1,110 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::scalar::Scalar;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::contrib::ballot::{self, Ballot};
+use bulletproofs::r1cs::R1CSError;
+use bulletproofs::{BulletproofGens, PedersenGens, ProofError};
+
+#[test]
+fn election_with_one_malformed_ballot() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(4, 1);
+    let mut rng = ChaChaRng::from_seed([3u8; 32]);
+    let candidates = 4;
+
+    let mut ballots = Vec::new();
+    let mut voter_blindings = Vec::new();
+    let mut counts = [0u64; 4];
+    for voter in 0..100 {
+        let choice = (voter * 7 + voter / 10) % candidates;
+        let voter_blinding = Scalar::random(&mut rng);
+        let (ballot, proof) = ballot::cast_with_rng(
+            &bp_gens,
+            &pc_gens,
+            candidates,
+            choice,
+            &voter_blinding,
+            &mut rng,
+        )
+        .unwrap();
+
+        // A ballot voting twice, for its choice and the next candidate.
+        let (ballot, proof) = if voter == 42 {
+            let mut commitments = ballot.commitments().to_vec();
+            let next = (choice + 1) % candidates;
+            let blinding = ballot::blindings(candidates, &voter_blinding)[next];
+            commitments[next] = pc_gens.commit(Scalar::ONE, blinding).compress();
+            (Ballot::new(commitments), proof)
+        } else {
+            (ballot, proof)
+        };
+
+        let proof = ballot::BallotProof::from_bytes(&proof.to_bytes()).unwrap();
+        match ballot.verify_with_rng(&bp_gens, &pc_gens, &proof, &mut rng) {
+            Ok(()) => {
+                ballots.push(ballot);
+                voter_blindings.push(voter_blinding);
+                counts[choice] += 1;
+            }
+            Err(e) => {
+                assert_eq!(voter, 42);
+                assert_eq!(e, R1CSError::VerificationError);
+            }
+        }
+    }
+    assert_eq!(ballots.len(), 99);
+    assert_eq!(counts.iter().sum::<u64>(), 99);
+
+    let tally = ballot::tally(&ballots).unwrap();
+    let blinding_sums = ballot::blinding_sums(candidates, &voter_blindings);
+    let proof =
+        ballot::tally_opening_proof_with_rng(&pc_gens, &tally, &counts, &blinding_sums, &mut rng)
+            .unwrap();
+    assert!(tally.verify_counts(&pc_gens, &counts, &proof).is_ok());
+
+    // Moving a vote to another candidate.
+    let mut wrong_counts = counts;
+    wrong_counts[0] -= 1;
+    wrong_counts[1] += 1;
+    assert_eq!(
+        tally.verify_counts(&pc_gens, &wrong_counts, &proof),
+        Err(ProofError::VerificationError)
+    );
+    let proof = ballot::tally_opening_proof_with_rng(
+        &pc_gens,
+        &tally,
+        &wrong_counts,
+        &blinding_sums,
+        &mut rng,
+    )
+    .unwrap();
+    assert_eq!(
+        tally.verify_counts(&pc_gens, &wrong_counts, &proof),
+        Err(ProofError::VerificationError)
+    );
+}
+
+#[test]
+fn ballots_must_be_well_formed() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(4, 1);
+    let mut rng = ChaChaRng::from_seed([4u8; 32]);
+    let voter_blinding = Scalar::random(&mut rng);
+
+    assert!(matches!(
+        ballot::cast_with_rng(&bp_gens, &pc_gens, 3, 3, &voter_blinding, &mut rng),
+        Err(R1CSError::GadgetError { .. })
+    ));
+
+    let (three, _) =
+        ballot::cast_with_rng(&bp_gens, &pc_gens, 3, 1, &voter_blinding, &mut rng).unwrap();
+    let (four, _) =
+        ballot::cast_with_rng(&bp_gens, &pc_gens, 4, 1, &voter_blinding, &mut rng).unwrap();
+    assert_eq!(
+        ballot::tally(&[three, four]).err(),
+        Some(ProofError::InvalidInputLength)
+    );
+}
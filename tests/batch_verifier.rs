@@ -0,0 +1,484 @@
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::{
+    BatchVerifier, BitSize, BulletproofGens, PedersenGens, ProofError, RangeProof,
+    TranscriptFactory, VerificationOptions,
+};
+
+/// Creates `count` proofs, each of `m` values of `n` bits.
+fn proofs(
+    bp_gens: &BulletproofGens,
+    pc_gens: &PedersenGens,
+    rng: &mut ChaChaRng,
+    count: u64,
+    n: usize,
+    m: usize,
+) -> Vec<(RangeProof, Vec<CompressedRistretto>)> {
+    (0..count)
+        .map(|i| {
+            let values: Vec<u64> = (0..m as u64).map(|j| 3 * i + j).collect();
+            let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(rng)).collect();
+            let mut transcript = Transcript::new(b"BatchVerifierTest");
+            RangeProof::prove_multiple_with_rng(
+                bp_gens,
+                pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::try_from(n).unwrap(),
+                rng,
+            )
+            .unwrap()
+        })
+        .collect()
+}
+
+#[test]
+fn batch_of_mixed_statements_localizes_one_corrupted_proof() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(64, 4);
+    let mut rng = ChaChaRng::from_seed([7u8; 32]);
+
+    let single = proofs(&bp_gens, &pc_gens, &mut rng, 10, 64, 1);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 10, 16, 4);
+
+    // Commit to a value other than the proven one.
+    let mut corrupted = aggregated[3].1.clone();
+    corrupted[2] = pc_gens.commit(Scalar::from(99u64), Scalar::ONE).compress();
+
+    let mut batch = BatchVerifier::new();
+    for ((proof_1, Vs_1), (proof_4, Vs_4)) in single.iter().zip(&aggregated) {
+        batch.queue_range(
+            proof_1,
+            Vs_1,
+            BitSize::B64,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+        batch.queue_range(
+            proof_4,
+            Vs_4,
+            BitSize::B16,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+    }
+    assert_eq!(batch.len(), 20);
+    assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+    assert!(batch
+        .invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng)
+        .is_empty());
+
+    let mut batch = BatchVerifier::new();
+    for (i, ((proof_1, Vs_1), (proof_4, Vs_4))) in single.iter().zip(&aggregated).enumerate() {
+        let Vs_4 = if i == 3 { &corrupted } else { Vs_4 };
+        batch.queue_range(
+            proof_1,
+            Vs_1,
+            BitSize::B64,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+        batch.queue_range(
+            proof_4,
+            Vs_4,
+            BitSize::B16,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+    }
+    assert_eq!(
+        batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng),
+        Err(ProofError::VerificationError)
+    );
+    assert_eq!(
+        batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+        vec![7]
+    );
+}
+
+#[test]
+fn options_do_not_change_the_outcome() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 2);
+    let mut rng = ChaChaRng::from_seed([9u8; 32]);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 4, 32, 2);
+
+    // A commitment that does not decompress.
+    let mut invalid = aggregated[1].1.clone();
+    invalid[0] = CompressedRistretto([0xff; 32]);
+    // A commitment to a value other than the proven one.
+    let mut corrupted = aggregated[2].1.clone();
+    corrupted[1] = pc_gens.commit(Scalar::from(5u64), Scalar::ONE).compress();
+
+    let mut options = vec![VerificationOptions::default()];
+    for &msm_chunk_size in &[None, Some(1), Some(7), Some(64), Some(10_000)] {
+        for &parallel in &[false, true] {
+            options.push(VerificationOptions {
+                msm_chunk_size,
+                parallel,
+                max_memory_bytes: None,
+            });
+        }
+    }
+
+    for options in options {
+        let mut batch = BatchVerifier::with_options(options);
+        for (proof, Vs) in &aggregated {
+            batch.queue_range(
+                proof,
+                Vs,
+                BitSize::B32,
+                Transcript::new(b"BatchVerifierTest"),
+            );
+        }
+        assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+
+        let mut batch = BatchVerifier::with_options(options);
+        for (i, (proof, Vs)) in aggregated.iter().enumerate() {
+            let Vs = match i {
+                1 => &invalid,
+                2 => &corrupted,
+                _ => Vs,
+            };
+            batch.queue_range(
+                proof,
+                Vs,
+                BitSize::B32,
+                Transcript::new(b"BatchVerifierTest"),
+            );
+        }
+        assert_eq!(
+            batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+            vec![1, 2]
+        );
+    }
+}
+
+#[test]
+fn memory_budget_chunks_or_rejects_the_batch() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 2);
+    let mut rng = ChaChaRng::from_seed([11u8; 32]);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 4, 32, 2);
+    let mut corrupted = aggregated[3].1.clone();
+    corrupted[0] = pc_gens.commit(Scalar::from(5u64), Scalar::ONE).compress();
+
+    // Budgets that fit the whole multiplication, and budgets that only
+    // fit Pippenger or Straus chunks of it.
+    for &max_memory_bytes in &[None, Some(1 << 30), Some(200_000), Some(145_000)] {
+        for &parallel in &[false, true] {
+            let options = VerificationOptions {
+                parallel,
+                max_memory_bytes,
+                ..Default::default()
+            };
+            let mut batch = BatchVerifier::with_options(options);
+            for (proof, Vs) in &aggregated {
+                batch.queue_range(
+                    proof,
+                    Vs,
+                    BitSize::B32,
+                    Transcript::new(b"BatchVerifierTest"),
+                );
+            }
+            assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+
+            let mut batch = BatchVerifier::with_options(options);
+            for (i, (proof, Vs)) in aggregated.iter().enumerate() {
+                let Vs = if i == 3 { &corrupted } else { Vs };
+                batch.queue_range(
+                    proof,
+                    Vs,
+                    BitSize::B32,
+                    Transcript::new(b"BatchVerifierTest"),
+                );
+            }
+            assert_eq!(
+                batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng),
+                Err(ProofError::VerificationError)
+            );
+            assert_eq!(
+                batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+                vec![3]
+            );
+        }
+    }
+
+    let mut batch = BatchVerifier::with_options(VerificationOptions {
+        max_memory_bytes: Some(100_000),
+        ..Default::default()
+    });
+    for (proof, Vs) in &aggregated {
+        batch.queue_range(
+            proof,
+            Vs,
+            BitSize::B32,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+    }
+    match batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng) {
+        Err(ProofError::ResourceLimitExceeded { required, limit }) => {
+            assert_eq!(limit, 100_000);
+            assert!(required > limit);
+        }
+        result => panic!("unexpected result {:?}", result),
+    }
+}
+
+#[test]
+fn batch_rejects_statements_beyond_the_generators() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(64, 4);
+    let mut rng = ChaChaRng::from_seed([8u8; 32]);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 1, 32, 4);
+
+    let mut batch = BatchVerifier::new();
+    batch.queue_range(
+        &aggregated[0].0,
+        &aggregated[0].1,
+        BitSize::B32,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    assert_eq!(
+        batch.verify_with_rng(&BulletproofGens::new(64, 2), &pc_gens, &mut rng),
+        Err(ProofError::InvalidGeneratorsLength)
+    );
+    assert!(BatchVerifier::new()
+        .verify_with_rng(&bp_gens, &pc_gens, &mut rng)
+        .is_ok());
+}
+
+#[test]
+fn batch_binds_each_proof_to_its_context() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let mut rng = ChaChaRng::from_seed([10u8; 32]);
+    let transcripts = TranscriptFactory::new(b"BatchVerifierTest", b"block 17");
+    let tx_ids: Vec<Vec<u8>> = (0..4u8).map(|i| vec![i; 32]).collect();
+
+    let proofs: Vec<(RangeProof, CompressedRistretto)> = tx_ids
+        .iter()
+        .enumerate()
+        .map(|(i, tx_id)| {
+            let mut transcript = transcripts.fresh_with_context(tx_id);
+            RangeProof::prove_single_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                i as u64,
+                &Scalar::random(&mut rng),
+                BitSize::B32,
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect();
+
+    let mut batch = BatchVerifier::new();
+    for ((proof, V), tx_id) in proofs.iter().zip(&tx_ids) {
+        batch.queue_range_in_context(
+            proof,
+            core::slice::from_ref(V),
+            BitSize::B32,
+            &transcripts,
+            tx_id,
+        );
+    }
+    assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+
+    // Swap the contexts of the second and third proofs.
+    let mut batch = BatchVerifier::new();
+    for (i, (proof, V)) in proofs.iter().enumerate() {
+        let tx_id = &tx_ids[[0, 2, 1, 3][i]];
+        batch.queue_range_in_context(
+            proof,
+            core::slice::from_ref(V),
+            BitSize::B32,
+            &transcripts,
+            tx_id,
+        );
+    }
+    assert_eq!(
+        batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng),
+        Err(ProofError::VerificationError)
+    );
+    assert_eq!(
+        batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+        vec![1, 2]
+    );
+
+    // The context of the factory matches the associated data of
+    // single-proof creation.
+    let (proof, V) = RangeProof::prove_single_with_ad(
+        &bp_gens,
+        &pc_gens,
+        b"BatchVerifierTest",
+        b"block 17",
+        5,
+        &Scalar::ONE,
+        BitSize::B32,
+    )
+    .unwrap();
+    assert!(proof
+        .verify_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcripts.fresh(),
+            &V,
+            BitSize::B32
+        )
+        .is_ok());
+}
+
+#[test]
+fn duplicate_proofs_are_weighted_independently() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 2);
+    let mut rng = ChaChaRng::from_seed([11u8; 32]);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 2, 32, 2);
+    let (proof, Vs) = &aggregated[0];
+
+    // The same statement queued three times verifies, and the repeats
+    // are reported.
+    let mut batch = BatchVerifier::new();
+    for _ in 0..3 {
+        batch.queue_range(
+            proof,
+            Vs,
+            BitSize::B32,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+    }
+    batch.queue_range(
+        &aggregated[1].0,
+        &aggregated[1].1,
+        BitSize::B32,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+    assert_eq!(batch.duplicate_entries(), vec![1, 2]);
+
+    // The same proof for other commitments or another bitsize is not a
+    // repeat.
+    let mut swapped = Vs.clone();
+    swapped.swap(0, 1);
+    let mut batch = BatchVerifier::new();
+    batch.queue_range(
+        proof,
+        Vs,
+        BitSize::B32,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    batch.queue_range(
+        proof,
+        &swapped,
+        BitSize::B32,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    batch.queue_range(
+        proof,
+        Vs,
+        BitSize::B16,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    assert!(batch.duplicate_entries().is_empty());
+    assert_eq!(
+        batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+        vec![1, 2]
+    );
+}
+
+#[test]
+fn adversarially_identical_entries_do_not_cancel() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 2);
+    let mut rng = ChaChaRng::from_seed([12u8; 32]);
+    let aggregated = proofs(&bp_gens, &pc_gens, &mut rng, 1, 32, 2);
+    let (proof, Vs) = &aggregated[0];
+
+    // An invalid entry queued many times fails under independent
+    // weights, however often it is repeated.
+    let mut wrong = Vs.clone();
+    wrong[1] = pc_gens.commit(Scalar::from(99u64), Scalar::ONE).compress();
+    for copies in [2, 3, 8] {
+        let mut batch = BatchVerifier::new();
+        for _ in 0..copies {
+            batch.queue_range(
+                proof,
+                &wrong,
+                BitSize::B32,
+                Transcript::new(b"BatchVerifierTest"),
+            );
+        }
+        assert_eq!(
+            batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng),
+            Err(ProofError::VerificationError)
+        );
+        assert_eq!(
+            batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+            (0..copies).collect::<Vec<_>>()
+        );
+    }
+
+    // Identical proofs and commitments are still distinct entries: a
+    // repeat under another transcript is invalid on its own.
+    let mut batch = BatchVerifier::new();
+    batch.queue_range(
+        proof,
+        Vs,
+        BitSize::B32,
+        Transcript::new(b"BatchVerifierTest"),
+    );
+    batch.queue_range(proof, Vs, BitSize::B32, Transcript::new(b"another context"));
+    assert_eq!(batch.duplicate_entries(), vec![1]);
+    assert_eq!(
+        batch.invalid_entries_with_rng(&bp_gens, &pc_gens, &mut rng),
+        vec![1]
+    );
+}
+
+#[test]
+fn distinct_proofs_for_the_same_commitment_verify() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let mut rng = ChaChaRng::from_seed([13u8; 32]);
+    let blinding = Scalar::random(&mut rng);
+
+    let proofs: Vec<(RangeProof, CompressedRistretto)> = (0..3)
+        .map(|_| {
+            let mut transcript = Transcript::new(b"BatchVerifierTest");
+            RangeProof::prove_single_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                1037,
+                &blinding,
+                BitSize::B32,
+                &mut rng,
+            )
+            .unwrap()
+        })
+        .collect();
+    assert!(proofs.iter().all(|(_, V)| *V == proofs[0].1));
+
+    let mut batch = BatchVerifier::new();
+    for (proof, V) in &proofs {
+        batch.queue_range(
+            proof,
+            core::slice::from_ref(V),
+            BitSize::B32,
+            Transcript::new(b"BatchVerifierTest"),
+        );
+    }
+    assert!(batch.duplicate_entries().is_empty());
+    assert!(batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).is_ok());
+}
@@ -0,0 +1,75 @@
+//! Range proof test vectors shared by the integration tests.
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+
+/// The value commitments of the test vectors, to the values 0, ..., 7.
+pub fn test_vector_commitments() -> Vec<CompressedRistretto> {
+    Vec::from([
+        CompressedRistretto::from_slice(
+            &hex::decode("90b0c2fe57934dff9f5396e135e7d72b82b3c5393e1843178918eb2cf28a5f3c")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("74256a3e2a7fe948210c4095195ae4db3e3498c6c5fddc2afb226c0f1e97e468")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("7e348def6d03dc7bcbe7e03736ca2898e2efa9f6ff8ae4ed1cb5252ec1744075")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("861859f5d4c14f5d6d7ad88dcf43c9a98064a7d8702ffc9bad9eba2ed766702a")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("4c09b1260c833fefe25b1c3d3becc80979beca5e864d57fcb410bb15c7ba5c14")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("08cf26bfdf2e6b731536f5e48b4c0ac7b5fc846d36aaa3fe0d28f07c207f0814")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("a6e2d1c2770333c9a8a5ac10d9eb28e8609d5954428261335b2fd6ff0e0e8d69")
+                .unwrap(),
+        ),
+        CompressedRistretto::from_slice(
+            &hex::decode("30beef3b58fd2c18dde771d5c77e32f8dc01361e284aef517bce54a5c74c4665")
+                .unwrap(),
+        ),
+    ])
+    .iter()
+    .map(|res| res.unwrap())
+    .collect()
+}
+
+/// Hex-encoded range proofs of the values in
+/// [`test_vector_commitments`], with the transcript label
+/// `Deserialize-And-Verify Test`.  `TEST_VECTOR_PROOFS[i][j]` has
+/// bitsize `n = 8 << i` and aggregation size `m = 1 << j`.
+pub const TEST_VECTOR_PROOFS: [[&[u8]; 4]; 4] = [
+    [
+        b"f4855aead34c7715112281a2ffa9e14a64d177dbeef84dc3fba50f0f4f9dc01640005e9b5658dbf590d7194be18d7971c511ff81f11d1e455ac3cbda52d7e923481e02599604558530774e7f55dd3c1669f31a195169a902395c40ef8ab6c54918a938efef334d6125ee82cef6b0ef7dabfaa0a4ea85761ed538dc54b1cdc3302510c784459a78cf391ecbfdd5809b405b6e064237bba6fa05d53d019fdeb5012f961ddf4b43cc52073ca1d0f9aac859450bd38c17b2c2041781b7d7294fb90c05ed96e11da34f4e6b1b63d9566a7989c1fe458470eef5c6e363e3e83034830128a095a8c61b901c4b72d672a972c3cc661403f9d22cc7a6214a20a22ede7a41d29647c2e07ace7130806d0e938e5fb42fca1f65ee9bde772c40f0869f969f48d08ad15a52cc5a2a5222cb4e1c0e4cd73957870d50e69bcf16e8f4a80494b966e258f2a11a7f0e9074020f5056a32628edfa81c19129d427522b06169a7bc811ae580b4ee6717704a70364dfd5027be28e0abf5ad109e9fd1806eca98f37cb205877dc6273d30ef688dd40d046cbf8f84643966a7a228cbaf039fb4209dfe8796ced7335395337621902f1d7591b4c3989ab0269a084022b4b27536916b14a0773150fea44dc13d1391c9bb0af537db9658705b1b9209f79298d904353189d01",
+        b"ec551977f133b0b3833e7df5cf00ef35a680507077d7a2fbf6498964480951285c2fd3588ca317bc20724f41dd06b9e817e232639cee7e5043cd428f87214046047d26b8ae0d5de9ab1469b82a85292d9051ca2377920e3b183bdf4b9c35ef50a6981127a84da66a42276ee0f05b08f00a6b446db3112e5fc49b4b29834d9460ce11e796fba83bcbeae54f5b45b3338ceb96ed66bb3fc088b092ab9cb6a0a904445b5cb0e050483d0147e2bd206154c09eee03154f934ffbfadbcef14565630425fb211b39593929db62fcd0be49d050fe7f4b8e652196f518a9f011a0e3ae044c39653732bd339e4cc0973e3266f0a18d8f9b9eba390a4cf146060f6d46bd21e0c3fdc60d4acee15f17e18215cacc0dfaa89025d27fc679e18936036578b647d29377d0def5f61ab427eec8afa0ad0623ae51d7e7dd165999d21f395880c663acc57af31f9eb776ebd3ea378716c6973e4dff054c44303645c1e9ce065556210c72ca1ee69565e6655319957766ea4b35ce76545428d84ce089c4731eaf1b10ba7518d4f083cabc0f29863cf63956290c70409c39c8265215df3aaf05e27767e2669eaf3e96334f216affa12f9d977c8f7ae2418111cc2615e16184f5d2455f3ac37aacd2103a29cdeae0e2f573fbb773ba3a3f9cdd2762984bd1fa23a2e23ffcd9499c37189999a7b09176fc47130ca51f45afc41b1c1acab5ce26726e81054f55e86e9754b4b66545b3a2928327070ce539a08ac6546b47faf26db70d9a08",
+        b"88b2398e0dd4fa8089b88a5faa2409b22e096a5efdcfeb426c26eee01e77d86f1491ecc231d9ffdffc6cf8d749d8541f56640420dd4d2852d599705ec2f0075e80568a874fc41399022a977857208b051789e341365e4d8c657b23efc1bdac2de8b5411df55fe4074ca499633083764479e0f3b91848cd72c1daa5159f9bb9598c907ea1ee7bc6a94b9bf342263fd016442f7e107645c3f337216e5d44fc68037024c0bc9ada19dc3aa7d9a58f3985e54bf035d2f06011d811383f4fea9c890fc7b78829d2e1091ce488682ed04ed3e4ffb45cba449ece4f4bd27cf543353f019885a266a7c2dac64091879195115cc18455c7bda1266c8af3d4c60a21874826d6b3c70ecfbe8227fd159536addc05ce46b6050226db992c41609b9395ae262e7c6fe6c512d1cbfcbce357ef911a55cdf4f67ed38c27cb745683dc401745754856991ede76194b3e688f328cc09c27bfcfe891fdba7390b05f4de1e816844b16f230b88bfcb76070c52c6bcb1ac2b80518525d569ede856d318c8db246cfd578e8701f477ee0c805c9b05a872fc221a99e27d4f3b3a17df4f8ab7576d600ac7d6cde7b3a2abda91850b944554279aae4441e94f967359a9ca416eb4821d9fc600ee2b1750a9681f7765fab1efc58c5632c0e378b359ccd95e806c431a53aff08f62de506a0e1f8b3ecae099b9fbc2735874305527b6648bf2e3c4748043c144bca9fa0a75054b2a8d0a1809544d3988ccf530df92d23cdad3e4a8aa3c80050549293025d60094e385f0f585e001fa39d7006485ca3ba6b976b3870a6fdb3d5099812b180cd2ae03dada640f534a641efab728ec92b2218bfd2503af8150cb007",
+        b"024edb316541ab7e00e49a08a61950cc4421753d2aeb743301ff024bcf7f2335f487f944cdcc49f66664ba386ca953201dd33d789b3d801a20cb5b07cf90d342240fed973b7f0e5ac55dbe6217b1cec1421a8e427ea0f8678979b573215a752bbac367037be7d67c8f7e27e1e1cee96dda374b5a00f881f8b81001ae7a724a16fef154521aadd0fc52ac9fa055fa92412acd92ec155f1467e742909c13c31108af779dc537668b81a8ab1b943fcb46aef8c6a5b60f1f6144a6b6c261c91fbb08d74d6f9e414cfb33e202f353669d9f1459c405e3b5bf15a1bba66fcfa3cfe909f22227707c50976b97259d389677073890dc7219104504c7675e62c7a6bc76242888ae69c96ef8e5631b40bbb38f1ab44557e02a8c36048e7036812a8e38e509da9b99f494b47191d83db9a584508b5475a03e8f32cdbb34782ba11125311e5de0fbd1c55319dcc050dc049d4cffea57e35be78f6c521a238985f700ae57257bf888026286185b81f5f56c319972b487bfc165f192b8a6e8bd5586319bd0fd41849434d5d13308b51d0d52ae4dba2ede2a60d94c2767dfbb04513ecd8ba4a538acbae970a15ec1149ab50a5b053ee4047921c649dbff7bd7094b569f243d67277e689a91ee1755a298ed3e72c10ccc403f975817350038f02efb65e1c1c9226214eb8c01f4eaaf0500b271944cf09e581a9eecfddc163e8745dce44bfeb60166566e08b2466417efffa94507794f305dcb36ddeb5f8aca56acefaa6336aa6348b897460b4925b0ff898fe5d26d4389ad086f70397ec99bc42487085baaf3d731b6e238a6cb14efbc493d43d1ba538e1c9b75a45dc3a3b1fbc37ef953b5dc392d4d6c778fd08c55bb33afab8e67b35900ecb10375fe38185ef659b4c155d34f0bd361f33e6ecea640cf98ee676b62958488ef979ab3219f22e88fd67ea55fa708",
+    ],
+    [
+        b"3e4d0182d5b1ed0f2343afc1ddfe823d025f48660be8090e9389177896d9d106081ee72541c66a61251733ab0ca0725737e3e14ff72575dcbde0855005659b14cc45b70849bfe9ce5a9636029935bffc06e5241b3f5d524da6fe94f0956218153c747145f709049770ab7e38c886da7d50337125f626dd9d0937ad4eb30dfa3f0f345300f5b4fa426033d1c2019fb5952caebfb4b17f6e5941b751d82139c50decd242936fda51581f419cf5b00d8392cc396476d7a28b055024dae86bbdb10e96916103a280c49f657c13b944342015ca0e5746695d806a1e8d28d771c94c0386e2507febf2737bb503c75ffbb4ac0f489769f2f30292cd47a2d00cabf8ee39e4a18b2df93e0df7a050b9b16e5e1d679f5fe26024b36e5333abdec28f83421b1636e4ed678e6e0eeb4cba85c828914ec1af92bb81effe0e149b1c19ccce9b738ea7ed45fb80adc707cddfd74c6246d61975015c75bd464bf31d7ac09ced004960d21350320af081efe2d89c1f5e41dafb77890ac26a111e4a1936601880cc211a37484ea3d6ef12e7772f16995aa3caf5be8d8b0e5742dc34a1ae44078aa87978a5f213287cf0ab383c6e8b544f12c208a2111374f61cb9fb40ccc8e571c041784eb20e947511a09b6ea80b0ae3e05f88ce973f0e0484956c0a6088e5401c1af2b676368c1a1bee17c0c12fa7378c3ff5197bb7c0e17e7d3e364c0042bf4a0825f8a66ad3b955e5461430cf1de3c29286045991b5baa558fe8687e0ee958c00",
+        b"2e8fd2efbf99d73c235faf8238a4377967a53da543a153bb3bb8c8517d3d4d47548ffb138783143d31c1aed147c45506eca9bb29b417641263df00e4e20ebb3b4a417efa12a82ae2dd14caa3d7f20c6da252f0895e7174b550d4b7d6e0962723ded42a8848c3fc6f92926071a896e31fd860432a72ecb198bfedb678ecb74d6efda52dd207169e9f37eb8385e4a9d66da639693c851abf5a2a4c8da294934a0e6267a2dab2e2ebfff43ce4e7889c6deaba3fbb8a6596f9302371a52959b5aa043bf88d0c740da42922e6be94d047053494ba2141aefd2a64fab5fa81a242300bcc88f1e1746823cb488eb710b844ef3fae54f9b3eade4f1523835c318935f813140ecca9ac30fbfd7f2ae2a73a8167d11e8ad035eb0e25688e6ee5c4b580267f982a430d98099099ea1916766f0dd1fddb9b098278ec9292b42e950b02419f6ea0735b4bf409c726044a8140dfc2826d62bb1323bf8ed9be10b0dca123ab331bfa937e23ae8771860163994fcbc35a79f9a0f7625b86fbc67b6216ffc31b0936d4bcad8e1992fbf1a2bc586aefb424e92c1918575dd15a61dad1db948afc3978a47393d90f0c978219fe296a7ed668b408e51ca1aba154e05aa8a106ff6654073ae4e723f5f2c0e76865c93e8329a2de05fe7f766f459f4366144c74f573e87b2688d81afbff034242ca59c5fa557aa6d4f1f42890f6e12b26cc997db6d4c8220eed7f98ace8533ae0b7c78ad96810c69b4f829c7a3d39b2645d9cc9c5abf14cced1ac60dbaec3557afc339fbb7c064bc0bf2a6f98941363997a24ff46b0ba088dd3a2731b878ffd3f4057e86a0c552d183e2959c3d8016982c9ce70aaa7f30e",
+        b"10d1815bf9732346bb0d9a14f96329b622dace2c5373e9afbea9fffac1d352644ce276190e9f00bf71823e13dcb22cddc5ef200920a67ae5e002718571729945605d65140c51559ec495519bb954b41dcc3ea1bf5081f484f133d08d044b8a0bc424b3959938e9036cb58b74f205e94279cec448394364d43f918909041cd54d6b2c051a52e4c7e57a14d31b1e699990a3299417b0a1f5af9dcbac8b2d6fbb02449af23dc19ff212a4f7e1ab499769133c2e47847b8e3298e695eb03a476fb0fa96713cab8ac9ac147c271502cf77b10d2cac45f4e525eeb40166fe1f4a8a40922843db32778d4680d94a5a883accb7428fc4aa3512e03c5be6a4d412ba5b402d40f922e411e2259c4fcb848172fcfbd31a2e6526926af2b798a93d746bb964e227547607e0c7d408b85e5e31e7b8e019be287af147b8bafa52a1915f5dabe7c561822458a4e12586bfce045ce735af269d292d8d869cbf6e9dab2e04859fb2b50dcd9fe65a295669076063d7f809cca8337e46d205b5e1d755c63940252f536623f4c8d391e4863f0273b816d5613910572c7aa8490c531f8d411a659d4ea45e8a447010116563e787e196c85c29734229998cd934c7e5e9107dfefc36703683c13f7a24bafaa98e5ba33cf3120a0a53f73cbfe48ef1a53eef4d6c3091aca2174b8f23fd65eba20ea6b6ef35dd66f2c992117ea5b409c9200edc98552b503485415e63a6f25ee106a410010f2e8baaae33419179af385313e28b1c7f1d86b580c922adf21f5bfea82303d6ace6be0bf20297fb0ff817fb2da11d29a496f7279c0df2c8878f6d49bfd5583ed7b16f051d2115729a36e4594a1e68145d32adc34601d5a24b1161a100927e8a7c45db2c37ce2b0cc8fede916760e0690cfd6cc08a0ad1d7ea75cddc10c43c4fe6bfebf0b8c751c5fbd462f5ce4168e45ff6c6d00",
+        b"98cd8831d3e40b90d344fcf195146abdd4b53be1367a8948514293cf8596ef325c5b451258c042afcc3c44362d6e31c671616c408c650171ab60046a3d193c00c8876e7dd1da66ddd9306e5c90e1cab187db35e2f9410c978e588dbcc82cb857383b74cbe4a7e969e0dbfef72825dc7efc22cbe8cc2a7e9b4a338aa786aae13c57d1ca0309bf6239a527e0d04f29c4c7c7b4737e8caae15819d76314b0930109a8471b18133bb3697649c9cde1d2c5b99f5e12cab20194a467c4d768441ff90c549c664aaf645006ea071d0abe7c162aaf00823861b72c7e3c20649ed903fd0082c88dbf1a45f9c78dccd12379a0ce47a9e2db552bb8af167cecc4d6bfaf6f39505b93f6569d1c2bf127cdda8e85e613ef894aba6e65e7e9f3ec073ec3db47790265083ea366ef9dd8d7dac8befabd69c67847ea2a7d79ced4f4b4e4c9c02f272aff84b1768a5a845564e230bdd8fb17500db7fa254a0bf05a7b0bb678ea576c02307e7d559defc8154b8924b54c05b2ac4e08102b270ac8a5db9f4931ec2826e67428372344a536a8466df914d5dba01daae25d480ce866dd39394f6f348151b2a2e2ae92d36a7e7a2c9cc15ff7a6624f7aa80798d73eff29719063aacd342a8656acb7b8839a3b665c4579bd091794176c8e01f1bf7532dd1fe13fad22c00a288b046a3dc3e4d2f8e100e092003120c04f8d1bb4a43650ab6f33862a06de3ea86b273b31d26434b144d6be726f2809015431455198ea607ec0b765416a1d013685c165b7188afa7f538d3227ae6ab4488981a567018e4132ab3d47673a5866b0a075e476adc4135f5032eaf8cb2b10aa4eae5ee5a223a1e763b1085ccd1d4032240838a7d3350126ac4010a3fe9aea6e5b36c9915756957bdc23bd4a10fc35745fe069ef0f677f6dce07bffe1b26a2961b4a0634573beae9f5c9a70f9f391774c54f7c5b292f238dad38c0d660d207a235f9ebb0ecc14db435d75b124c25050ec615e3c7d75112ae886a8e3659181f03871e7ae7e703331fd3327298731c00",
+    ],
+    [
+        b"1cf90ade1d264cf7f813a321c825e2827dee856b49e9696a81511176e05b19215c0d362b356e7bf99f7c2e06bdaaec35ba9dcd2efaf9e66963c74d479309c00466a9af9c1f1e1b24f240080890ff4dbd55e48587bf157f955bd5ff17c181453756878bbf60bd6c8d91940a5a071a68fece4e279465e526a03eddbc5c2a47216044358839de2a51e9369f8e40b1af63cbe97ec58a2024b3d9e737cc226a8066068ebd5da79680197bc7132ac12749163dc810a77dd6c613fe2684fd110f532e0a9458918ce24fe68e3342e609877836613af54f02b654a5f5ea968a4f1f3baa006af6826368c88748c34d0ed05ec072968603c760ab5b2accd90b8825e5811812ccb523eb8aff2c70595aac1e971ad8eb85c347003bb55e7940d36adf9a69cb213a5a8bf68cf07b20aa345d26c0e331a5f25232d730c67e842b8e00676598a3516413a5fc13df20b450c0e1ea4b2e1ff4b3266ec88cf5ab0d9fc9e63f8eb2d14eb8fad5deaf2d11239483d6fdf8179144af27403442c027141ef0bd9fda50eb307655020ce60e1d3471f4ee0fe9050e871c1a7ee4a7b67fce50e584313b3bbf0cc4842dfd7218ae8afc50cef9b39c8a983edfdb3e62b84cf250a4458e2d64e60ed64683e90442c2655ecfe4bec07cc66e9e67de2e55d13f918cac97d92d3734568a09153039c96793f8fef2d067bc84aa8746592888a77440d48e283bf006ac6050e9636ebb3e91ab7c1a7e50d986e8a85897310a2fbbc053827cefe9855c1920414386bd5e34362006c928f9803b4f64ae5b0de6db8e6160a54ee1a4bf57cb0158630b28ac28d33e6ac3c3b496676af240345a01d81be642f39c6644a02dd000",
+        b"cecd634800158a7505a8dad3123bee9c9b179560eccfbbe5fd1762a4aa20152be8355f7bb2f8d440a33aa258567bae422a78bf71e91bfd56cdad5bf27ded3a253665234ecad4edafd57134ae9d18e72daf14e67d6d439a3a2b68c0bb95f9b1301a67053e315030e8ec671d9cfe6d6799f066f125ba3f48a6a0a6fa4543727e023f5ea2db4c35a12fb536b612f583d1483af8f98433de548b9441eec532ca110673bf2431ed64483d0d8f443dc9472e78e9f7ee1cea671c8dff33afa79b4ed1027a01143b3298227e90ae1743695b261200a8cb05c0675057824b2bf01f781c038e1e7e6e1cc8a3a058f8cb64f5cc4829fc22cb0c6d83eecac494078e09d86834ac60b2cab89b0cd8d9f74f8bf2a8add5ed078b7c55c317d40184339d4593a2045e79f2557692aa0ae9a98c57d20e4525fdd217f1f65328b3ef80a0adf7b50e1b02cea6d7578aa6423203c004cf7983bd3c1995f2832cc33cc5f29e64f6134471e6b7f09203f49ab204e2265be2bc5a0b002caae5ac6afd75515bfffd9efed973bc260d85035b5827d44dfa052645dd34b22b257cf11420d504bdc1ca1b2e545ec060de51bf22aac74ff5d34324fe553b14ba8570be12ca37f3c57ecf43afde3b349610a47b789a6371a5b76a11f95f0f30ff84acf263129466036deb9dd4bb026e62bc722d66d336bd251c07402c322279c7de234ec83a118cc9035d0965e06d92ca804e4cee106eddfa9b8858d9d9b461fe9d3a5818253ee98143d7a9f96073687c28ed87da4fda9d2ee04ddc96de0294272539de62775d6298bd9f23055b5d2e2f034c8d0b6bc289ed25dd8c82002ac2bfd9ad519ead39e1e51a5afba21e295d86fb55429b3ec310151b4dcf7f0842db969ba5e9eb9692d15b2be0e4ea110133ef0831948064064e8464044590f93e86b1b9b3bb4cb6eddd7d7a5a2997100f",
+        b"f02891c1e96b157f6be841add335bdf9e5f69a017bf1b4ecef5e63980827af4ee2fe3d05c0306e957cb08fa38c163b1dc25af707a87664db08ed2ad48a468e77f4de3be502150b3cf07b0db4dfa332d18e2b2ed7cf339785165b34fe5e33c928ee03cd7fa9e5529e392971ff657222c91a37a197e115f3d2722f0ef17fb09a1f505a0c0d69ba380643ea70db5c476b867fc998ffff91454804ca48f811a695001709f26b308cb58cf0324cc1cab9bd480df19cf674a9551cc7034f70b85b060a43ee67e29dec80454c7113e1c28a3801ba57e9b5dd4a44f65bac63c436f30208b4db493ab465fb84be9716c7fe82e39d7e65a82c0d56dec3858a2a8b8bf2fd16403c8413bc7ae6183d66e6f7f4945db0f3244fe05662359cb6b2f85868c64364621a76214ad307769447742c1967e207ee5ea67109f447a3ed14a01aebd72c759830d734305463d250b5151fae20735600426a6c337aefd8c640ea8c944bc94daa0f371371e07a7e116baf2394c0a1b83ee7952cb014b97eb3555040d1a96a1eb0c758b6e4a74c797f8ce702fc056ffc9c814f94c6bbf792c90c66fac278ab78ca5fe262b2f6da2a8314a991ae9a3b995feba76609b3254a0b060601bbc1b30bb2e85ebe6f3889182ab371bd06eb7b6287a2d077f7ac52a6c5891f39bc05755bbc763f3b8c572e09389e60526d6a28f836916fe00190654669f2044a1dff4d7076c663bec45126496c937d5a8c858eae98b62be6c49f6a2ce7d58b13968ad013e4c4fd616f10cb6d6e05d9bbaa2b95b88b6250436167da403b4a08506d17fb04482eff1c6b17950ca3f6977e87f5b3560c15ffcafbaef68decae68caf86bbf747ef169d5d1f9af84fc7de065da109d5e920f51df816b5c4b27f360515cfaab49f2d1abfe32710cd33aebeea10c614adc638cd75b9794545fa011346352be2074bc12818741f6de9118cf5ec16b143c95eebc7451989308761e11c75e0a9fdb090fd59c3e9e9ed0dd19ff2821abd6eee85aad53fff34d13bcae7a5a48bb4f3400",
+        b"f6db2c4a994c22eb08830cba9cc8af6c009b4a613d9be130a826f5859c2b2c0a9cded63dfff00a551abed93240f8088d6e295144d207eff9766767e821f5787dfa09ce31b884a14ea1dea76eb811cf9ced4124be3ef2b5efa1a5e1c40b30f718d09257763d9145181480e993fd0cfadcb99a790d8f682fa464a7c5f373b0b0434f5e3165ef06cb8d3e6fcde80bb5a999a083108ae20da9f68692d1176d15490b38ed9a3302cb6d84a6ec6a80cd43ba3aa0a23e7d98b5b54aa6fe52cadac7df092e8e4a327909198507f19a5889d2c2315fe865eb2317a92db56deebd074791087837818f2bfea6a5a1c5cf367f4201f69226044ccf172231189186f94cff107cbe03541f1953280033b2d577d323b9e4856352e2e853b9ba56a1077620a8cb79ba8a51580d84128d78bc5ba4264fc2fd0bff273206dc7dbafcb8935ad8b3fb67cc12b9f789d30c5d2e8bdcaf43a37485e29ecd28345a6941a24af65a81402f7db63e61922b07c3f2d38e061abc4070fb74c428c29fca59a08290cb63dcb6094a589551c8add26b6a558accd4f382970101ae12510df9b62fa656aef526ec391842f9ed0645e2b4cf391d7546a7cf1db35eaa1fd5d2c81a596088c5c858c0225280b8ff16a1cf2c2a4f004d87c6d752a26edcfa88599f3b2b93f8a6e990b1870adeb54f5d80e19ab49a4e0e613d45f70cac07d1b344a4511601bbe63f487c9d345a2412c930ca4dccbeee283a08d59b5a0d3c4389fb3a31ba06eac9db95f6877426babf82722245fdb93792c5c49863c714b22662b3abb1d7cc619436ea28435b1421415d93d34e277d50bb61bb6f20f1d87583b8255cdb3ad4e712334f6baa537ceb9b213cb5bbf8b09a9b3f8f72b6613fdc597773f017e96b0ef0dde1e5275de4822fb9de6ed4c93ced3e114d4311f6c816ad776457ac1e49f6ead4df62866628e3a23a94b203166a2d1f112f90b5178d2cf5da2992c87cb04452dd02ab700d5a3625833e2dd3114ce03c4e42df189a8e3bc88bdf2f156b21a68135798e4678c7ef9d0fb27295c3b6a982eebfcac2a826173ca614f54ff6c460db981d197f06b35bd76134b715d79c56eec647880c37981ef69a654e1f10a520c1bdff9c0a0d",
+    ],
+    [
+        b"247dbd04d1620b3ddb5e4788988a92585f8ffbf82bfdecaa3c968be809b2875d581b57bec83cd22e958f6bd1882fa59fe9e09bd3e25aeb14b9406cce2eb21a3ff47821c7d8ff5859f479310f38f9aab7f09784cf3f84b8623b313b19e488a141f6b69fe0f0cb31f5a6f4025bc5caeaab1f7b8125f6f6f0c92a2b502532412809a1e06dc2d34d2e90ceca44c22c36674aa7c6db2ac60e640f7f899d24c9432d0c237d48418489c6966a2fb6fb2f3a5fc874afe3fca3ba4e041e6c8ac7f54e180d5b9c588e1232f94cb2be85bb3b52c081a72256150d794317e529784eaeedd90f964a44f4140e4034542b5c3e8f20b8eb92f0dbc06c71b21ebc8a80c31be02166cef8788623817ee4f58485bd2021ab6c862f370e827ef833f017750215b5a260d4433112689ae65d9a18d5503d7f8be5be71013421990d0b46777ed51858a226124637355e34da77d1b939375778267b12de9a6ed4d7b2f811bbb550dfee5643900a09d1c55d4f8fe8550e5e0c9bef04c0090a7f77f270e09a598d1a7e93880e781bc369fd364e7f255df1ea85184759a408b0dc0929ae627b58422f106f58586e7fbbab0d29306cdc1bf3f7bc7cdafaadb13958b9f38fa7b0e50d87dd362864fc5e9c49be3ae464287a95c4f46058c6bb4bce38c592458084f28eb6b548942092104bd617a18661905bfbce6f7f7f90909b9f83ed8dbaf0a9f9bed1e7fc7e12b010701eefb1aafa5b5b806795bb61fac6862ea7e33b6cac6ff8e25cfbbc8c1dee033d4dfce45cd28a2b58230e659b7d1e7ddbd922404f8b4d0684fa23269b6b5e9c58b4174423f36e5ec84a18a428386b5898f83064b3d68fbda55cceaa6c16e16a9f080fbf21b8095f6042e19f8591673922ee900cc12d042703f5919ca409371a9ab679ba2393c1ab70b3cd214091c735289a1758c325dc8c94d3b43c3907",
+        b"741905aa3d7a67af821d689dea0f92af2886132ad00a28d2044629fd9c59bb3f7a349b7298be93a23371054477c2456970e71237d7cc1f9751d7bb171110b939848b6396c85cdc61098444e220b5affc0cb55ed9256522026791609bac2e43063eb73e8a0903bca64b238cf6bb38f9b411e7fe2a932f42a8a548124415978f4d4dd1a4f9d0be36b97792829feda4f833ddcbc18955cc90ac5485d343a57f720851e558d764516f34bbc059c2f95518f4e6a03df0d23bd1cc6c91eb7b672032013905be231b30aac5190e7410ebefc9c8e919d9e2eb842767a0fd0ad7606b1f023eb6a8909181fe032c11b38f1bfaa4f8d08975b38c086ff1f70ebd95a645f24d547c76a5afe3694d8972b4a7dba6e8176538d6d431c8550317a0eecef7e6ce35201438db4a5e7e054733cd4bab793291c3f6fd96ce6415b0b20fa73ce90a3b3b9ce108503566bf3b02d73ba9fce9b7a83a92f86bd9963761a4c233cf6712133df869ac6ab2b4022ad0e8da74912e2c120eb29ec89dbe62f8fbe71fbad1ffa80b422b65362f35f80c134498c7aa102f93071f0f0580da8061ff03c27ff06935391cea9f3a4d424a7d3d8ed492396fbe9594c511e2969a599ea73a811eb21c254fbce96ef0b4a6b12c5f0ff2b883dc65aa6e24af038977caf5153ef98dd5f4b7068a0422be78431132f9a7fb3adf2137626305f6ce8cdb54641582f03b3cb2937f4eb03c154d826301942eafbdcfc66072629f5181e63198d0ecf500db8c1a4c2eb64477e01797932c7de688b83e768326c01cad7e9c805a8f24bbfcf66f92dd5c2c3e4c5da9b430c3cb51196bfeda26b8de05ac18912fbab5d2406ac09f4e8343d2559d9eb199d864f3cfa4c304527bf9e831f1e808cc3ce58649c8bf49c6b012bc02b414a1fe1290de836fe85184cc2ea726808e31ba694ee6e0288454eed204936b60507b031a1fa60d499ccc309eacc8ab535d081d889031e95dda0f7358044c9b52211aa562be5a48b44613f1eda6fdafbf73d7a708a28a6cd5f169b7e904",
+        b"14d25e095b1a2aba14ac6a387346a74eb23c5834be16f8883a954044f87fd52bb0ac5d08ea8659f3383a983a5900f3d0d63e16dd4cd9e47838efdc88d87dd815aabf3e66255bb270708b78f2bbfb9eaa3c89cb7685ab00ba6c6d41535b6ccc50ae452c33a9055f587bb3ce658101648d41a99ab1f324f64e8a0e0bea4d820a293f089311ebf2a63518e64b0e5db9a87b2dc3dc831c25abc68100a452a7b5d2016507bd67b1df2e550f66eea893fc8030ad7c0588a3e790f4799224f82db9ea0df7840efbbaef0bd89ac8456204628517588418683d5d60c37e9aad85efd19f03eee9e507d93807b01e45e1f22f2202f7aeeb9fa86086cc3415bc66f026f73f584885c196140abb2f24d6360c05f68150b343b6a9f0e725526e6dbbb6a10d9b2d902fe375a5369a53950aa13c7c5fd84e7bc0a9a50e04667bc611032d0d40176f4e56f351b16702ad2dfcaa4e8b8443560212fb5c69adef37f9bd865172689127188e366f46eba5d243d286a09ce5586e1268bd7dcf7f5baa00c57282fe5852698429976b2347d6744a20b9256243b6d29992d712350283a54559931adad8134c9c2497eb0c08ef5c704c650652356c7203e86b492d9146ea15e7d0dfdc5b551b048598a398d07808df8fb481419ec07950c4eb74fa9a309b4c8a522d83d8a61886a97a8af9ab1e238a3d97ca606e1c5dfff3f116c594a119c075f57e99e7056044b7154279373fb5aa8f5bdcca37921d58389a527a4d7c90d7212b25d744e4157a277e002113e2588aba62b28017d4065f7de507419f63d15ca367dee8214c0470669aaec76bc588382e48e4d2e8c5ca18d47a33e5db4146696ac67850e09e744e7e086892a8fcfe074c1ed72b40a5a05d98e2e3f593c79ac72f5d541f693a253e76ec4a0aa5e993e16f1d83ed1f66e00503527c2ec34fcf43fe8256c54c397b9639dffca64fba7c6325e28d9f355d78c329f7cce21b4581274390f20c01c961ae8b306739ec1fbedfac132b1b1959977be9b2856b1984694937ecd247df2418d503f9db0403d9b636c31dea8c844ce740648cb16826837191ab50081ea8f10a0a1e06cfb49792ed891bde61047d9b11131fa51525c43dd9a7435303f8f30608",
+        b"366d66dd071fe7e3a124a501fb3108dd087afecb0fb838c5887639e492bf4174f2aedf91589584465103d6958479c113957cdd3513a1729a738c367458e1f3788c4e658cb33a4740da206edf59ceb6d4870d1907cd76593cdc5b597d10323f66b8c6a622dbaaabe6d2a8b256c26d2f47567203f8b7a6d8d0562c03fb1cfe6d72950dc72fd94fb51afcc423617f87df317ac17ea273e6cae5ce4295bbd72d1f039d46c5b2082776a5767baeb6a78611d9ce40f74e1648ec60b60f968b89da9c023bfdde3f2fad214188ca09078bf0f98d46c04db32da9292deff3d8d6bfa385042a09e2394e8b8ca03db3682fb2bf6031ae4f2ce82075303b74aa1fc9d222e17e7c7a36aed2146b2d80d81f881a822b56c7a18f4ba8debd4337828588716c3563b44be2f2f074b1a062aa5541e46c964e8da479c0763f6818b4dec9be3f0dce7cb0e17d2a03ec6931f7c446833508faf0bfc29d21b010f1f81ce510a435983c1f381accfd9f938f574b2ffafaeaaff036b9077057c7e4722800a9c48593757e0e9c465b1ec98f8c64efed49391650c274e9543db9810b14c4b967ef2f34ba3b3162af631aaef319def1131eeab40fe0b11a2b1c7b251192252d87e9de7034bf028830ef38cac409ded1954c125294add1249bd72a29dd400430107c84b657832f5ec73f37c6ca30232ffc22f358055ae1bac674465bb43360a2a75f19eda5737ce4367b0db50d112e1a3ba229cd679abca9c50b9e93f160c20ee1aed0a312a513acc944a265b5a76c531cf35729a70b4081e65424117d61c4d383c99f82cf4a74fe4c4bc22a6200c163847986c0639198ea342d1e3e7093488aab11f991945854366963cba1f80f69b1e230a9dead3b6f166f5a4a6ea5ffdbe8c7cf1f23dacd0bcabb5ca5f7d14bed70570ea70044b5eb4f0e18982eb05935fa71fd5de1179c3c1c30bc863ef4b900d5d37b95b1ceb00c18a94f39a39f8b6e24ddff752235f22752241171447f001caf1da80ec1ddb788da85ea843aebde7403826192345b310cc43eff48823b124edd427c592f8ed15e78229ca2f3797e22dd3fcd47a9205712f6066cbe482b1683780b974a80ba6eb918b022669649a1de3be59eaa68096a4e8e9c2548ee013215d0b55a5921880e250fb2aba30c9b488789b0493e13dc0a0131b5cfb37ba5322f7d189170c5f3f81757d3868cf1be99ca4054fd01e999100d",
+    ],
+];
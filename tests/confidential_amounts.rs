@@ -0,0 +1,122 @@
+//! A confidential-amounts flow: outputs committed with derived
+//! blindings, proven in range, and proven to balance the inputs.
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+
+use merlin::Transcript;
+
+use bulletproofs::{
+    blinding_sum, BalanceProof, BitSize, BulletproofGens, PedersenGens, RangeProof,
+};
+
+/// A transaction as seen by a verifier.
+struct Transaction {
+    inputs: Vec<CompressedRistretto>,
+    outputs: Vec<CompressedRistretto>,
+    range_proof: RangeProof,
+    balance_proof: BalanceProof,
+}
+
+fn compress(points: Vec<RistrettoPoint>) -> Vec<CompressedRistretto> {
+    points.iter().map(|point| point.compress()).collect()
+}
+
+/// Spends inputs of `input_values` into outputs of `output_values`.
+fn create(
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    input_values: &[u64],
+    output_values: &[u64],
+) -> Transaction {
+    let to_scalars = |values: &[u64]| values.iter().map(|&v| Scalar::from(v)).collect::<Vec<_>>();
+    let (inputs, input_blindings) =
+        pc_gens.commit_many(&to_scalars(input_values), &Scalar::from(1u64 << 40));
+    let (outputs, output_blindings) =
+        pc_gens.commit_many(&to_scalars(output_values), &Scalar::from(7u64 << 40));
+
+    let mut transcript = Transcript::new(b"ConfidentialAmountsTest");
+    let (range_proof, range_commitments) = RangeProof::prove_multiple(
+        bp_gens,
+        pc_gens,
+        &mut transcript,
+        output_values,
+        &output_blindings,
+        BitSize::B32,
+    )
+    .unwrap();
+    let outputs = compress(outputs);
+    assert_eq!(range_commitments, outputs);
+
+    // Bind the balance proof to the range proof by continuing the transcript.
+    let balance_proof = BalanceProof::prove(
+        pc_gens,
+        &mut transcript,
+        &input_blindings,
+        &output_blindings,
+    );
+
+    Transaction {
+        inputs: compress(inputs),
+        outputs,
+        range_proof,
+        balance_proof,
+    }
+}
+
+fn verify(pc_gens: &PedersenGens, bp_gens: &BulletproofGens, tx: &Transaction) -> bool {
+    let mut transcript = Transcript::new(b"ConfidentialAmountsTest");
+    tx.range_proof
+        .verify_multiple(bp_gens, pc_gens, &mut transcript, &tx.outputs, BitSize::B32)
+        .is_ok()
+        && tx
+            .balance_proof
+            .verify(pc_gens, &mut transcript, &tx.inputs, &tx.outputs)
+            .is_ok()
+}
+
+#[test]
+fn balanced_transaction_verifies() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 4);
+
+    let tx = create(&pc_gens, &bp_gens, &[1000, 250, 5], &[600, 400, 200, 55]);
+    assert!(verify(&pc_gens, &bp_gens, &tx));
+}
+
+#[test]
+fn inflating_transaction_is_rejected() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 4);
+
+    let tx = create(&pc_gens, &bp_gens, &[1000, 250], &[600, 651]);
+    assert!(!verify(&pc_gens, &bp_gens, &tx));
+}
+
+#[test]
+fn balance_proof_is_bound_to_the_transaction() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 4);
+
+    let tx = create(&pc_gens, &bp_gens, &[10, 20], &[15, 15]);
+    let other = create(&pc_gens, &bp_gens, &[10, 20], &[5, 25]);
+
+    let spliced = Transaction {
+        balance_proof: other.balance_proof,
+        ..tx
+    };
+    assert!(!verify(&pc_gens, &bp_gens, &spliced));
+}
+
+#[test]
+fn blinding_sum_of_balanced_commitments() {
+    let pc_gens = PedersenGens::default();
+    let values = [Scalar::from(3u64), Scalar::from(4u64)];
+    let (commitments, blindings) = pc_gens.commit_many(&values, &Scalar::from(99u64));
+
+    let total = commitments[0] + commitments[1];
+    assert_eq!(
+        total,
+        pc_gens.commit(Scalar::from(7u64), blinding_sum(&blindings))
+    );
+}
@@ -0,0 +1,171 @@
+//! Proof sizes computed from `bulletproofs::consts` alone, against the
+//! sizes of serialized proofs.
+
+use std::convert::TryFrom;
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use bulletproofs::consts::*;
+use bulletproofs::{
+    BalanceProof, BitSize, BulletproofGens, CrossCommitmentProof, NonZeroProof, PedersenGens,
+    RangeProof, SmallVecOpeningProof,
+};
+
+/// The binary logarithm of a power of two.
+fn lg(n: usize) -> usize {
+    assert!(n.is_power_of_two());
+    n.trailing_zeros() as usize
+}
+
+fn range_proof_size(n: usize, m: usize) -> usize {
+    (RANGE_HEADER_ELEMENTS + 2 * lg(n * m) + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES
+}
+
+#[test]
+fn range_proof_sizes() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(MAX_BITSIZE, 4);
+    for &n in SUPPORTED_RANGE_BITS {
+        for m in [1, 2, 4] {
+            let values: Vec<u64> = (0..m as u64).collect();
+            let blindings: Vec<Scalar> = (1..=m as u64).map(Scalar::from).collect();
+            let mut transcript = Transcript::new(b"ConstsTest");
+            let (proof, _) = RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::try_from(n).unwrap(),
+            )
+            .unwrap();
+            assert_eq!(
+                proof.to_bytes().len(),
+                range_proof_size(n, m),
+                "n = {}, m = {}",
+                n,
+                m
+            );
+        }
+    }
+}
+
+#[test]
+fn bounds() {
+    assert_eq!(SUPPORTED_RANGE_BITS.last(), Some(&MAX_BITSIZE));
+    assert!(SUPPORTED_RANGE_BITS.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(POINT_BYTES, SCALAR_BYTES);
+    assert_eq!(SUPPORTED_RANGE_BITS[0] * MAX_AGGREGATION, MAX_VECTOR_LENGTH);
+    assert_eq!(
+        range_proof_size(SUPPORTED_RANGE_BITS[0], MAX_AGGREGATION),
+        MAX_RANGE_PROOF_SIZE
+    );
+    assert_eq!(
+        range_proof_size(MAX_BITSIZE, MAX_VECTOR_LENGTH / MAX_BITSIZE),
+        MAX_RANGE_PROOF_SIZE
+    );
+}
+
+#[test]
+fn fixed_size_proofs() {
+    let pc_gens = PedersenGens::default();
+    let blindings = [Scalar::from(3u64), Scalar::from(4u64)];
+
+    let mut transcript = Transcript::new(b"ConstsTest");
+    let proof = BalanceProof::prove(&pc_gens, &mut transcript, &blindings, &blindings[..1]);
+    assert_eq!(
+        proof.to_bytes().len(),
+        BALANCE_PROOF_ELEMENTS * ELEMENT_BYTES
+    );
+
+    let mut transcript = Transcript::new(b"ConstsTest");
+    let proof = NonZeroProof::prove(&pc_gens, &mut transcript, Scalar::ONE, blindings[0]);
+    assert_eq!(
+        proof.to_bytes().len(),
+        NONZERO_PROOF_ELEMENTS * ELEMENT_BYTES
+    );
+
+    let mut transcript = Transcript::new(b"ConstsTest");
+    let (proof, _, _) = CrossCommitmentProof::prove(
+        &pc_gens,
+        pc_gens.B,
+        pc_gens.B_blinding,
+        &mut transcript,
+        Scalar::ONE,
+        blindings[0],
+        blindings[1],
+    )
+    .unwrap();
+    assert_eq!(
+        proof.to_bytes().len(),
+        CROSS_COMMITMENT_PROOF_ELEMENTS * ELEMENT_BYTES
+    );
+}
+
+#[test]
+fn small_vec_opening_proof_sizes() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(16, 1);
+    for k in [1, 3, 16] {
+        let values: Vec<Scalar> = (0..k as u64).map(Scalar::from).collect();
+        let mut transcript = Transcript::new(b"ConstsTest");
+        let (proof, _) = SmallVecOpeningProof::prove(
+            &pc_gens,
+            &bp_gens.try_share(0).unwrap(),
+            &mut transcript,
+            &values,
+            &Scalar::ONE,
+        )
+        .unwrap();
+        assert_eq!(
+            proof.to_bytes().len(),
+            (k + SMALL_VEC_OPENING_FIXED_ELEMENTS) * ELEMENT_BYTES
+        );
+    }
+}
+
+#[cfg(feature = "yoloproofs")]
+#[test]
+fn r1cs_proof_sizes() {
+    use bulletproofs::r1cs::{
+        ConstraintSystem, Prover, R1CSError, RandomizableConstraintSystem,
+        RandomizedConstraintSystem,
+    };
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(16, 1);
+    for multipliers in [1, 3, 8] {
+        for randomized in [false, true] {
+            let mut transcript = Transcript::new(b"ConstsTest");
+            let mut prover = Prover::new(&pc_gens, &mut transcript);
+            let (_, x) = prover.commit(Scalar::from(3u64), Scalar::ONE);
+            for _ in 0..multipliers {
+                let (_, _, out) = prover.multiply(x.into(), x.into());
+                prover.constrain(out - Scalar::from(9u64));
+            }
+            if randomized {
+                prover
+                    .specify_randomized_constraints(move |cs| {
+                        let z = cs.challenge_scalar(b"z");
+                        let (_, _, out) = cs.multiply(x - z, x.into());
+                        cs.constrain(out - (Scalar::from(3u64) - z) * Scalar::from(3u64));
+                        Ok::<(), R1CSError>(())
+                    })
+                    .unwrap();
+            }
+            let proof = prover.prove(&bp_gens).unwrap();
+
+            let header = if randomized {
+                R1CS_TWO_PHASE_HEADER_ELEMENTS
+            } else {
+                R1CS_ONE_PHASE_HEADER_ELEMENTS
+            };
+            let padded = (multipliers + randomized as usize).next_power_of_two();
+            assert_eq!(
+                proof.to_bytes().len(),
+                R1CS_VERSION_BYTES + (header + 2 * lg(padded) + IPP_FINAL_ELEMENTS) * ELEMENT_BYTES
+            );
+        }
+    }
+}
@@ -0,0 +1,344 @@
+//! Degenerate inputs to the public proving and verification entry
+//! points: each must either produce a valid proof or return an error,
+//! and never panic.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::Identity;
+
+use merlin::Transcript;
+
+use bulletproofs::range_proof_mpc::{dealer::Dealer, party::Party, MPCError};
+use bulletproofs::{
+    BitSize, BulletproofGens, Commitment, LinearProof, PedersenGens, ProofError, ProofParameters,
+    RangeProof, SmallVecOpeningProof,
+};
+
+fn gens() -> (PedersenGens, BulletproofGens) {
+    (PedersenGens::default(), BulletproofGens::new(64, 4))
+}
+
+/// A valid single-party proof of `v` in `n` bits and its commitment.
+fn single_proof(n: BitSize, v: u64) -> (RangeProof, CompressedRistretto) {
+    let (pc_gens, bp_gens) = gens();
+    let mut transcript = Transcript::new(b"EdgeCases");
+    RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript, v, &Scalar::ONE, n).unwrap()
+}
+
+#[test]
+fn range_proof_of_zero_and_max_values() {
+    let (pc_gens, bp_gens) = gens();
+    for n in BitSize::ALL {
+        let max = if n == BitSize::B64 {
+            u64::MAX
+        } else {
+            (1 << n.bits()) - 1
+        };
+        for v in [0, max] {
+            let (proof, V) = single_proof(n, v);
+            let mut transcript = Transcript::new(b"EdgeCases");
+            assert!(proof
+                .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, n)
+                .is_ok());
+        }
+    }
+
+    // A commitment to zero with a zero blinding factor is the identity.
+    let mut transcript = Transcript::new(b"EdgeCases");
+    let (proof, V) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        0,
+        &Scalar::ZERO,
+        BitSize::B8,
+    )
+    .unwrap();
+    assert_eq!(V, CompressedRistretto::identity());
+    let mut transcript = Transcript::new(b"EdgeCases");
+    assert!(proof
+        .verify_single(&bp_gens, &pc_gens, &mut transcript, &V, BitSize::B8)
+        .is_ok());
+}
+
+#[test]
+#[allow(deprecated)]
+fn range_proof_rejects_invalid_bitsizes() {
+    let (pc_gens, bp_gens) = gens();
+    let (proof, V) = single_proof(BitSize::B8, 0);
+    for n in [0, 1, 7, 9, 128] {
+        assert!(matches!(
+            BitSize::try_from(n),
+            Err(ProofError::InvalidBitsize) | Err(ProofError::StatementTooLarge { .. })
+        ));
+
+        let mut transcript = Transcript::new(b"EdgeCases");
+        let result =
+            RangeProof::prove_single_usize(&bp_gens, &pc_gens, &mut transcript, 0, &Scalar::ONE, n);
+        assert!(matches!(
+            result,
+            Err(ProofError::InvalidBitsize) | Err(ProofError::StatementTooLarge { .. })
+        ));
+
+        let mut transcript = Transcript::new(b"EdgeCases");
+        let result = proof.verify_single_usize(&bp_gens, &pc_gens, &mut transcript, &V, n);
+        assert!(matches!(
+            result,
+            Err(ProofError::InvalidBitsize) | Err(ProofError::StatementTooLarge { .. })
+        ));
+    }
+}
+
+#[test]
+fn range_proof_rejects_invalid_party_counts() {
+    let (pc_gens, bp_gens) = gens();
+    let (proof, V) = single_proof(BitSize::B8, 0);
+
+    for m in [0, 3] {
+        let values = vec![0u64; m];
+        let blindings = vec![Scalar::ONE; m];
+        let mut transcript = Transcript::new(b"EdgeCases");
+        assert_eq!(
+            RangeProof::prove_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &values,
+                &blindings,
+                BitSize::B8
+            )
+            .err(),
+            Some(ProofError::InvalidAggregation)
+        );
+
+        let commitments = vec![V; m];
+        let mut transcript = Transcript::new(b"EdgeCases");
+        assert_eq!(
+            proof.verify_multiple(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitments,
+                BitSize::B8
+            ),
+            Err(ProofError::InvalidAggregation)
+        );
+    }
+
+    let mut transcript = Transcript::new(b"EdgeCases");
+    assert_eq!(
+        RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[0, 1],
+            &[Scalar::ONE],
+            BitSize::B8
+        )
+        .err(),
+        Some(ProofError::WrongNumBlindingFactors)
+    );
+
+    // More parties than generators.
+    let mut transcript = Transcript::new(b"EdgeCases");
+    assert_eq!(
+        RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[0; 8],
+            &[Scalar::ONE; 8],
+            BitSize::B8
+        )
+        .err(),
+        Some(ProofError::InvalidGeneratorsLength)
+    );
+}
+
+#[test]
+#[allow(deprecated)]
+fn aggregation_rejects_degenerate_parameters() {
+    let (pc_gens, bp_gens) = gens();
+    let mut transcript = Transcript::new(b"EdgeCases");
+
+    assert_eq!(
+        Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 0).err(),
+        Some(MPCError::InvalidAggregation)
+    );
+    assert_eq!(
+        Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 8).err(),
+        Some(MPCError::InsufficientPartyCapacity {
+            required_parties: 8,
+            available: 4
+        })
+    );
+    assert_eq!(
+        Dealer::new_usize(&bp_gens, &pc_gens, &mut transcript, 0, 1).err(),
+        Some(MPCError::InvalidBitsize)
+    );
+    assert_eq!(
+        Party::new_usize(&bp_gens, &pc_gens, 0, Scalar::ONE, 0).err(),
+        Some(MPCError::InvalidBitsize)
+    );
+
+    let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 1).unwrap();
+    assert_eq!(
+        dealer.receive_bit_commitments(vec![]).err(),
+        Some(MPCError::WrongNumBitCommitments)
+    );
+
+    let party = Party::new(&bp_gens, &pc_gens, 0, Scalar::ONE, BitSize::B8).unwrap();
+    assert_eq!(
+        party.assign_position(4).err(),
+        Some(MPCError::InsufficientPartyCapacity {
+            required_parties: 5,
+            available: 4
+        })
+    );
+}
+
+#[test]
+fn proof_parameters_reject_degenerate_sizes() {
+    assert_eq!(
+        ProofParameters::for_range_proofs(0, 1).err(),
+        Some(ProofError::InvalidBitsize)
+    );
+    assert_eq!(
+        ProofParameters::for_range_proofs(64, 0).err(),
+        Some(ProofError::InvalidAggregation)
+    );
+}
+
+#[test]
+fn linear_proof_rejects_empty_vectors() {
+    let mut rng = rand::thread_rng();
+    let pc_gens = PedersenGens::default();
+    let mut transcript = Transcript::new(b"EdgeCases");
+    let C = CompressedRistretto::identity();
+
+    assert_eq!(
+        LinearProof::create(
+            &mut transcript,
+            &mut rng,
+            &C,
+            Scalar::ONE,
+            vec![],
+            vec![],
+            vec![],
+            &pc_gens.B,
+            &pc_gens.B_blinding,
+        )
+        .err(),
+        Some(ProofError::InvalidInputLength)
+    );
+}
+
+#[test]
+fn small_vec_opening_of_empty_and_zero_vectors() {
+    let (pc_gens, bp_gens) = gens();
+    let gens = bp_gens.try_share(0).unwrap();
+
+    // An empty vector proves knowledge of the blinding factor only.
+    for values in [vec![], vec![Scalar::ZERO; 4]] {
+        let n = values.len();
+        let mut transcript = Transcript::new(b"EdgeCases");
+        let (proof, C) =
+            SmallVecOpeningProof::prove(&pc_gens, &gens, &mut transcript, &values, &Scalar::ONE)
+                .unwrap();
+        assert_eq!(proof.serialized_size(), 32 * (n + 2));
+
+        let mut transcript = Transcript::new(b"EdgeCases");
+        assert!(proof
+            .verify(&pc_gens, &gens, &mut transcript, &C, n)
+            .is_ok());
+    }
+
+    // Zero values with a zero blinding factor commit to the identity.
+    let mut transcript = Transcript::new(b"EdgeCases");
+    let (proof, C) = SmallVecOpeningProof::prove(
+        &pc_gens,
+        &gens,
+        &mut transcript,
+        &[Scalar::ZERO; 2],
+        &Scalar::ZERO,
+    )
+    .unwrap();
+    assert_eq!(C, CompressedRistretto::identity());
+    let mut transcript = Transcript::new(b"EdgeCases");
+    assert!(proof
+        .verify(&pc_gens, &gens, &mut transcript, &C, 2)
+        .is_ok());
+}
+
+#[test]
+fn commitment_arithmetic_with_identity() {
+    let pc_gens = PedersenGens::default();
+    let zero = Commitment::from(RistrettoPoint::identity());
+    let C = Commitment::new(&pc_gens, Scalar::from(5u64), Scalar::ONE);
+
+    assert_eq!(C.try_add(&zero), Ok(C));
+    assert_eq!(zero.scale(Scalar::from(7u64)), Ok(zero));
+    assert_eq!(zero.shift_value(0, &pc_gens), Ok(zero));
+}
+
+#[test]
+fn empty_encodings_are_rejected() {
+    assert_eq!(
+        RangeProof::from_bytes(&[]).err(),
+        Some(ProofError::FormatError)
+    );
+    assert_eq!(
+        LinearProof::from_bytes(&[]).err(),
+        Some(ProofError::FormatError)
+    );
+    assert_eq!(
+        SmallVecOpeningProof::from_bytes(&[]).err(),
+        Some(ProofError::FormatError)
+    );
+}
+
+#[cfg(feature = "yoloproofs")]
+mod r1cs {
+    use super::*;
+
+    use bulletproofs::r1cs::{ConstraintSystem, Prover, R1CSProof, Verifier};
+
+    #[test]
+    fn empty_constraint_system() {
+        let (pc_gens, bp_gens) = gens();
+
+        let proof = Prover::new(&pc_gens, Transcript::new(b"EdgeCases"))
+            .prove(&bp_gens)
+            .unwrap();
+
+        let verifier = Verifier::new(Transcript::new(b"EdgeCases"));
+        assert!(verifier.verify(&proof, &pc_gens, &bp_gens).is_ok());
+    }
+
+    #[test]
+    fn constraint_system_over_zero_commitment() {
+        let (pc_gens, bp_gens) = gens();
+
+        let (proof, V) = {
+            let mut prover = Prover::new(&pc_gens, Transcript::new(b"EdgeCases"));
+            let (V, v) = prover.commit(Scalar::ZERO, Scalar::ZERO);
+            prover.constrain(v.into());
+            (prover.prove(&bp_gens).unwrap(), V)
+        };
+        assert_eq!(V, CompressedRistretto::identity());
+
+        let mut verifier = Verifier::new(Transcript::new(b"EdgeCases"));
+        let v = verifier.commit(V);
+        verifier.constrain(v.into());
+        assert!(verifier.verify(&proof, &pc_gens, &bp_gens).is_ok());
+    }
+
+    #[test]
+    fn empty_encoding_is_rejected() {
+        assert!(R1CSProof::from_bytes(&[]).is_err());
+    }
+}
@@ -0,0 +1,394 @@
+//! Triggers every error variant through the public API at least once.
+//!
+//! The `*_kind` functions match exhaustively, so adding a variant
+//! without a way to trigger it here fails to compile.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use bulletproofs::range_proof_mpc::dealer::{Dealer, DealerAwaitingPolyCommitments};
+use bulletproofs::range_proof_mpc::messages::{PolyChallenge, PolyCommitment};
+use bulletproofs::range_proof_mpc::party::{Party, PartyAwaitingPolyChallenge};
+use bulletproofs::range_proof_mpc::MPCError;
+use bulletproofs::{
+    BatchVerifier, BitSize, BulletproofGens, EnvelopeTranscript, PedersenGens, ProofEnvelope,
+    ProofError, ProofKind, ProofParameters, ProveOptions, RangeProof, RangeStatement,
+    SmallVecOpeningProof, VerificationOptions,
+};
+
+const PROOF_ERROR_KINDS: usize = 16;
+
+fn proof_error_kind(e: &ProofError) -> usize {
+    match e {
+        // Reported instead of `VerificationError` with the `diagnostics`
+        // feature.
+        ProofError::VerificationError | ProofError::FailedChecks { .. } => 0,
+        ProofError::FormatError => 1,
+        ProofError::WrongNumBlindingFactors => 2,
+        ProofError::InvalidBitsize => 3,
+        ProofError::InvalidAggregation => 4,
+        ProofError::InvalidGeneratorsLength => 5,
+        ProofError::InvalidGenerators => 6,
+        ProofError::InvalidInputLength => 7,
+        ProofError::StatementTooLarge { .. } => 8,
+        ProofError::BitsizeMismatch { .. } => 9,
+        ProofError::ShareOutOfRange { .. } => 10,
+        ProofError::SelfCheckFailed => 11,
+        ProofError::ProvingError(_) => 12,
+        ProofError::ResourceLimitExceeded { .. } => 13,
+        ProofError::SectionMismatch { .. } => 14,
+        ProofError::ValueOutOfRange => 15,
+    }
+}
+
+const MPC_ERROR_KINDS: usize = 12;
+
+fn mpc_error_kind(e: &MPCError) -> usize {
+    match e {
+        MPCError::MaliciousDealer => 0,
+        MPCError::InvalidBitsize => 1,
+        MPCError::InvalidAggregation => 2,
+        MPCError::InvalidGeneratorsLength => 3,
+        MPCError::InsufficientPartyCapacity { .. } => 4,
+        MPCError::MismatchedPositions { .. } => 5,
+        MPCError::WrongNumBitCommitments => 6,
+        MPCError::WrongNumPolyCommitments => 7,
+        MPCError::WrongNumProofShares => 8,
+        MPCError::MalformedProofShares { .. } => 9,
+        MPCError::Aborted { .. } => 10,
+        MPCError::InvalidMissingParties { .. } => 11,
+    }
+}
+
+/// Asserts that `errors` contains each of the `count` kinds.
+fn assert_all_kinds<E, F: Fn(&E) -> usize>(errors: &[E], kind: F, count: usize) {
+    let mut seen = vec![false; count];
+    for e in errors {
+        seen[kind(e)] = true;
+    }
+    let missing: Vec<usize> = (0..count).filter(|&k| !seen[k]).collect();
+    assert!(missing.is_empty(), "kinds {:?} were not triggered", missing);
+}
+
+/// Runs the aggregation MPC for `values` up to the dealer receiving
+/// the proof shares.
+fn aggregate(values: &[u64], n: usize) -> Result<RangeProof, MPCError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, values.len());
+    let mut transcript = Transcript::new(b"ErrorCoverage");
+
+    let dealer = Dealer::new(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        BitSize::try_from(n).unwrap(),
+        values.len(),
+    )?;
+    let mut parties = Vec::new();
+    let mut bit_commitments = Vec::new();
+    for (j, &v) in values.iter().enumerate() {
+        let party = Party::new(
+            &bp_gens,
+            &pc_gens,
+            v,
+            Scalar::from(j as u64 + 1),
+            BitSize::try_from(n).unwrap(),
+        )?;
+        let (party, bit_commitment) = party.assign_position(j)?;
+        parties.push(party);
+        bit_commitments.push(bit_commitment);
+    }
+    let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments)?;
+    let (parties, poly_commitments): (Vec<_>, Vec<_>) = parties
+        .into_iter()
+        .map(|party| party.apply_challenge(&bit_challenge))
+        .unzip();
+    let (dealer, poly_challenge) = dealer.receive_poly_commitments(poly_commitments)?;
+    let shares = parties
+        .into_iter()
+        .map(|party| party.apply_challenge(&poly_challenge))
+        .collect::<Result<Vec<_>, _>>()?;
+    dealer.receive_shares(&shares)
+}
+
+/// Starts an aggregation of `m` parties proving the value 1 and
+/// returns the dealer and parties awaiting the polynomial commitments.
+fn start_aggregation<'a, 'b>(
+    bp_gens: &'b BulletproofGens,
+    pc_gens: &'b PedersenGens,
+    transcript: &'a mut Transcript,
+    m: usize,
+) -> (
+    DealerAwaitingPolyCommitments<'a, 'b>,
+    Vec<PartyAwaitingPolyChallenge>,
+    Vec<PolyCommitment>,
+) {
+    let dealer = Dealer::new(bp_gens, pc_gens, transcript, BitSize::B8, m).unwrap();
+    let (parties, bit_commitments): (Vec<_>, Vec<_>) = (0..m)
+        .map(|j| {
+            let party = Party::new(bp_gens, pc_gens, 1, Scalar::ONE, BitSize::B8).unwrap();
+            party.assign_position(j).unwrap()
+        })
+        .unzip();
+    let (dealer, bit_challenge) = dealer.receive_bit_commitments(bit_commitments).unwrap();
+    let (parties, poly_commitments) = parties
+        .into_iter()
+        .map(|party| party.apply_challenge(&bit_challenge))
+        .unzip();
+    (dealer, parties, poly_commitments)
+}
+
+#[allow(deprecated)]
+fn mpc_errors() -> Vec<MPCError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 4);
+    let mut transcript = Transcript::new(b"ErrorCoverage");
+    let mut errors = Vec::new();
+
+    let mut push = |result: Result<(), MPCError>| errors.push(result.unwrap_err());
+
+    push(Dealer::new_usize(&bp_gens, &pc_gens, &mut transcript, 7, 1).map(|_| ()));
+    push(Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 3).map(|_| ()));
+    push(Party::new(&bp_gens, &pc_gens, 1, Scalar::ONE, BitSize::B16).map(|_| ()));
+    push(Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 8).map(|_| ()));
+
+    // Two parties assigned the same position.
+    let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 2).unwrap();
+    let bit_commitments = (0..2)
+        .map(|_| {
+            let party = Party::new(&bp_gens, &pc_gens, 1, Scalar::ONE, BitSize::B8).unwrap();
+            party.assign_position(0).unwrap().1
+        })
+        .collect();
+    push(dealer.receive_bit_commitments(bit_commitments).map(|_| ()));
+
+    let dealer = Dealer::new(&bp_gens, &pc_gens, &mut transcript, BitSize::B8, 1).unwrap();
+    push(dealer.receive_bit_commitments(vec![]).map(|_| ()));
+
+    let (dealer, _, _) = start_aggregation(&bp_gens, &pc_gens, &mut transcript, 2);
+    push(dealer.receive_poly_commitments(vec![]).map(|_| ()));
+
+    let (dealer, _, _) = start_aggregation(&bp_gens, &pc_gens, &mut transcript, 2);
+    push(Err(dealer.abort("party 1 stopped responding")));
+
+    let (dealer, _, _) = start_aggregation(&bp_gens, &pc_gens, &mut transcript, 2);
+    push(dealer.proceed_without(&[5]).map(|_| ()));
+
+    let (dealer, mut parties, poly_commitments) =
+        start_aggregation(&bp_gens, &pc_gens, &mut transcript, 1);
+    let (dealer, _) = dealer.receive_poly_commitments(poly_commitments).unwrap();
+    push(dealer.receive_shares(&[]).map(|_| ()));
+
+    // The challenges can only be forged by deserializing them.
+    let zero_challenge: PolyChallenge =
+        serde_json::from_str(&format!("{{\"x\":{:?}}}", [0u8; 32])).unwrap();
+    push(
+        parties
+            .remove(0)
+            .apply_challenge(&zero_challenge)
+            .map(|_| ()),
+    );
+
+    // A value out of range yields a share that fails verification.
+    push(aggregate(&[1, 1 << 8], 8).map(|_| ()));
+
+    errors
+}
+
+#[test]
+fn every_mpc_error_is_triggered() {
+    let errors = mpc_errors();
+    assert_all_kinds(&errors, mpc_error_kind, MPC_ERROR_KINDS);
+    assert!(aggregate(&[1, 2], 8).is_ok());
+}
+
+fn proof_errors() -> Vec<ProofError> {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 2);
+    let blinding = Scalar::ONE;
+    let mut errors = Vec::new();
+
+    let mut push = |result: Result<(), ProofError>| errors.push(result.unwrap_err());
+    let prove = |values: &[u64], blindings: &[Scalar], n: BitSize| {
+        let mut transcript = Transcript::new(b"ErrorCoverage");
+        RangeProof::prove_multiple(&bp_gens, &pc_gens, &mut transcript, values, blindings, n)
+    };
+
+    // A proof checked against the commitment of another proof.
+    let (proof, _) = prove(&[7], &[blinding], BitSize::B8).unwrap();
+    let (_, other) = prove(&[8], &[blinding], BitSize::B8).unwrap();
+    let mut transcript = Transcript::new(b"ErrorCoverage");
+    push(
+        proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &other[0], BitSize::B8)
+            .map(|_| ()),
+    );
+    // An 8-bit proof checked as a 16-bit proof.
+    push(
+        proof
+            .verify_single(&bp_gens, &pc_gens, &mut transcript, &other[0], BitSize::B16)
+            .map(|_| ()),
+    );
+    push(RangeProof::from_bytes(&[]).map(|_| ()));
+    push(bp_gens.try_share(2).map(|_| ()));
+    // A value out of range is caught by the self-check.
+    let mut transcript = Transcript::new(b"ErrorCoverage");
+    push(
+        RangeProof::prove_multiple_with_options(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &[1 << 8],
+            &[blinding],
+            BitSize::B8,
+            &ProveOptions { self_verify: true },
+        )
+        .map(|_| ()),
+    );
+    // A value out of range is caught by the statement builder.
+    push(
+        RangeStatement::builder()
+            .value(1 << 8)
+            .blinding(blinding)
+            .bits(BitSize::B8)
+            .build()
+            .map(|_| ()),
+    );
+    push(prove(&[1, 2], &[blinding], BitSize::B8).map(|_| ()));
+    push(BitSize::try_from(7).map(|_| ()));
+    push(prove(&[1, 2, 3], &[blinding; 3], BitSize::B8).map(|_| ()));
+    push(prove(&[1], &[blinding], BitSize::B16).map(|_| ()));
+    push(BitSize::try_from(1 << 20).map(|_| ()));
+    push(
+        BulletproofGens::from_points(vec![RistrettoPoint::default()], vec![pc_gens.B], 1)
+            .map(|_| ()),
+    );
+
+    // An opening proof in an envelope with two commitments.
+    let params = ProofParameters::for_range_proofs(8, 1).unwrap();
+    let envelope =
+        ProofEnvelope::prove_opening(&params, b"ctx", &[Scalar::ONE], &blinding).unwrap();
+    let C = envelope.commitments()[0];
+    let malformed = ProofEnvelope::<SmallVecOpeningProof>::new(
+        envelope.proof().clone(),
+        envelope.n(),
+        vec![C, C],
+        b"ctx".to_vec(),
+    );
+    push(malformed.verify(&params));
+
+    // A batch with too small a memory budget.
+    let mut batch = BatchVerifier::with_options(VerificationOptions {
+        max_memory_bytes: Some(1),
+        ..Default::default()
+    });
+    batch.queue_range(
+        &proof,
+        &other,
+        BitSize::B8,
+        Transcript::new(b"ErrorCoverage"),
+    );
+    push(batch.verify(&bp_gens, &pc_gens));
+
+    // An envelope section taken under a key that was not registered.
+    let mut sections = EnvelopeTranscript::new(b"ctx");
+    push(sections.section("amount", ProofKind::Range, |_| Ok(())));
+
+    // The single-party API hides the MPC, so its errors only surface
+    // when converted explicitly.
+    errors.push(ProofError::from(aggregate(&[1, 1 << 8], 8).unwrap_err()));
+
+    errors
+}
+
+#[test]
+fn every_proof_error_is_triggered() {
+    assert_all_kinds(&proof_errors(), proof_error_kind, PROOF_ERROR_KINDS);
+}
+
+#[cfg(feature = "yoloproofs")]
+mod r1cs {
+    use super::*;
+
+    use curve25519_dalek::ristretto::CompressedRistretto;
+
+    use bulletproofs::r1cs::{
+        gadgets, ConstraintSystem, Prover, R1CSError, R1CSProof, Variable, Verifier,
+    };
+
+    const R1CS_ERROR_KINDS: usize = 7;
+
+    fn r1cs_error_kind(e: &R1CSError) -> usize {
+        match e {
+            R1CSError::InvalidGeneratorsLength => 0,
+            R1CSError::FormatError => 1,
+            R1CSError::VerificationError => 2,
+            R1CSError::MissingAssignment => 3,
+            R1CSError::StatementTooLarge { .. } => 4,
+            R1CSError::GadgetError { .. } => 5,
+            R1CSError::ProofError(_) => 6,
+        }
+    }
+
+    /// Proves that the committed `x` and `y` multiply to `product`.
+    fn prove(
+        bp_gens: &BulletproofGens,
+        product: u64,
+    ) -> Result<(R1CSProof, Vec<CompressedRistretto>), R1CSError> {
+        let pc_gens = PedersenGens::default();
+        let mut transcript = Transcript::new(b"ErrorCoverage");
+        let mut prover = Prover::new(&pc_gens, &mut transcript);
+        // A wrong `product` must reach the verifier.
+        prover.skip_witness_check();
+        let (X, x) = prover.commit(Scalar::from(2u64), Scalar::ONE);
+        let (Y, y) = prover.commit(Scalar::from(3u64), Scalar::ONE);
+        let (_, _, o) = prover.multiply(x.into(), y.into());
+        prover.constrain(o - Scalar::from(product));
+        Ok((prover.prove(bp_gens)?, vec![X, Y]))
+    }
+
+    #[test]
+    fn every_r1cs_error_is_triggered() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+        let mut errors = Vec::new();
+
+        let (proof, commitments) = prove(&bp_gens, 5).unwrap();
+        let mut transcript = Transcript::new(b"ErrorCoverage");
+        let mut verifier = Verifier::new(&mut transcript);
+        let x = verifier.commit(commitments[0]);
+        let y = verifier.commit(commitments[1]);
+        let (_, _, o) = verifier.multiply(x.into(), y.into());
+        verifier.constrain(o - Scalar::from(5u64));
+        errors.push(verifier.verify(&proof, &pc_gens, &bp_gens).unwrap_err());
+
+        errors.push(prove(&BulletproofGens::new(0, 1), 6).unwrap_err());
+        errors.push(R1CSProof::from_bytes(&[]).unwrap_err());
+
+        // A one-phase proof whose inner-product proof claims 32 rounds.
+        let oversized = vec![0u8; 1 + (11 + 2 * 32 + 2) * 32];
+        errors.push(R1CSProof::from_bytes(&oversized).unwrap_err());
+
+        // The prover needs an assignment for every variable.
+        let mut transcript = Transcript::new(b"ErrorCoverage");
+        let mut prover = Prover::new(&pc_gens, &mut transcript);
+        errors.push(prover.allocate(None).unwrap_err());
+
+        let mut transcript = Transcript::new(b"ErrorCoverage");
+        let mut verifier = Verifier::new(&mut transcript);
+        let selector: Vec<Variable> = Vec::new();
+        errors.push(
+            gadgets::lookup(&mut verifier, &[Scalar::ONE], selector, Scalar::ONE.into())
+                .unwrap_err(),
+        );
+
+        errors.push(R1CSError::from(ProofError::InvalidBitsize));
+
+        assert_all_kinds(&errors, r1cs_error_kind, R1CS_ERROR_KINDS);
+    }
+}
@@ -0,0 +1,26 @@
+// Runs the property checks of the fuzz targets on seeded inputs, so
+// that they are exercised without a fuzzing engine.
+
+use arbitrary::Unstructured;
+use bulletproofs::fuzzing;
+use rand_chacha::ChaChaRng;
+use rand_core::{RngCore, SeedableRng};
+
+/// Runs `check` on `count` inputs of random bytes.
+fn run_seeded(count: u64, check: fn(&mut Unstructured<'_>) -> arbitrary::Result<()>) {
+    for seed in 0..count {
+        let mut data = vec![0u8; 4096];
+        ChaChaRng::seed_from_u64(seed).fill_bytes(&mut data);
+        check(&mut Unstructured::new(&data)).unwrap();
+    }
+}
+
+#[test]
+fn batch_consistency_on_seeded_inputs() {
+    run_seeded(16, fuzzing::check_batch_consistency);
+}
+
+#[test]
+fn envelope_round_trip_on_seeded_inputs() {
+    run_seeded(16, fuzzing::check_envelope_round_trip);
+}
@@ -0,0 +1,149 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+use sha3::Sha3_512;
+
+use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof, SmallVecOpeningProof};
+
+/// `count` points fixed by a hypothetical protocol specification.
+fn spec_points(label: &str, count: usize) -> Vec<RistrettoPoint> {
+    (0..count)
+        .map(|i| RistrettoPoint::hash_from_bytes::<Sha3_512>(format!("{} {}", label, i).as_bytes()))
+        .collect()
+}
+
+fn spec_gens(n: usize, m: usize) -> BulletproofGens {
+    BulletproofGens::from_points(spec_points("G", n * m), spec_points("H", n * m), m).unwrap()
+}
+
+#[test]
+fn range_proofs_verify_with_imported_gens() {
+    let pc_gens = PedersenGens::default();
+    let mut rng = ChaChaRng::from_seed([1u8; 32]);
+
+    for &m in &[1, 2] {
+        let bp_gens = spec_gens(32, m);
+        let values: Vec<u64> = (0..m as u64).map(|i| 1000 + i).collect();
+        let blindings: Vec<Scalar> = (0..m).map(|_| Scalar::random(&mut rng)).collect();
+
+        let mut transcript = Transcript::new(b"ImportedGensTest");
+        let (proof, commitments) = RangeProof::prove_multiple_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &values,
+            &blindings,
+            BitSize::B32,
+            &mut rng,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"ImportedGensTest");
+        assert!(proof
+            .verify_multiple_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &commitments,
+                BitSize::B32,
+                &mut rng
+            )
+            .is_ok());
+
+        // The proof does not verify with the derived generators.
+        let mut transcript = Transcript::new(b"ImportedGensTest");
+        assert!(proof
+            .verify_multiple_with_rng(
+                &BulletproofGens::new(32, m),
+                &pc_gens,
+                &mut transcript,
+                &commitments,
+                BitSize::B32,
+                &mut rng
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn proofs_with_derived_gens_do_not_verify_with_imported_gens() {
+    let pc_gens = PedersenGens::default();
+    let mut rng = ChaChaRng::from_seed([2u8; 32]);
+
+    // The opening proof only uses G, so these imported generators
+    // agree with the derived ones wherever the proof looks.
+    let derived = BulletproofGens::new(4, 1);
+    let G: Vec<RistrettoPoint> = derived.try_share(0).unwrap().G(4).cloned().collect();
+    let imported = BulletproofGens::from_points(G, spec_points("H", 4), 1).unwrap();
+
+    let values: Vec<Scalar> = (0..4).map(|_| Scalar::random(&mut rng)).collect();
+    let blinding = Scalar::random(&mut rng);
+    let mut transcript = Transcript::new(b"ImportedGensTest");
+    let (proof, C) = SmallVecOpeningProof::prove_with_rng(
+        &pc_gens,
+        &derived.try_share(0).unwrap(),
+        &mut transcript,
+        &values,
+        &blinding,
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut transcript = Transcript::new(b"ImportedGensTest");
+    assert!(proof
+        .verify(
+            &pc_gens,
+            &derived.try_share(0).unwrap(),
+            &mut transcript,
+            &C,
+            4
+        )
+        .is_ok());
+
+    // The imported generators bind their fingerprint.
+    let mut transcript = Transcript::new(b"ImportedGensTest");
+    assert!(proof
+        .verify(
+            &pc_gens,
+            &imported.try_share(0).unwrap(),
+            &mut transcript,
+            &C,
+            4
+        )
+        .is_err());
+}
+
+#[cfg(feature = "yoloproofs")]
+#[test]
+fn r1cs_proofs_verify_with_imported_gens() {
+    use bulletproofs::r1cs::{ConstraintSystem, Prover, Verifier};
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = spec_gens(4, 1);
+    let mut rng = ChaChaRng::from_seed([3u8; 32]);
+
+    // Proves knowledge of x, y with x * y = 6.
+    let mut transcript = Transcript::new(b"ImportedGensTest");
+    let mut prover = Prover::new(&pc_gens, &mut transcript);
+    let (X, x) = prover.commit(Scalar::from(2u64), Scalar::random(&mut rng));
+    let (Y, y) = prover.commit(Scalar::from(3u64), Scalar::random(&mut rng));
+    let (_, _, o) = prover.multiply(x.into(), y.into());
+    prover.constrain(o - Scalar::from(6u64));
+    let proof = prover.prove_with_rng(&bp_gens, &mut rng).unwrap();
+
+    let verify = |bp_gens: &BulletproofGens| {
+        let mut transcript = Transcript::new(b"ImportedGensTest");
+        let mut verifier = Verifier::new(&mut transcript);
+        let x = verifier.commit(X);
+        let y = verifier.commit(Y);
+        let (_, _, o) = verifier.multiply(x.into(), y.into());
+        verifier.constrain(o - Scalar::from(6u64));
+        verifier.verify(&proof, &pc_gens, bp_gens)
+    };
+    assert!(verify(&bp_gens).is_ok());
+    assert!(verify(&BulletproofGens::new(4, 1)).is_err());
+}
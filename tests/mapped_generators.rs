@@ -0,0 +1,153 @@
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use merlin::Transcript;
+use sha3::Sha3_512;
+
+use bulletproofs::{
+    BitSize, BulletproofGens, MappedBulletproofGens, PedersenGens, ProofError, RangeProof,
+};
+
+mod common;
+
+use common::{test_vector_commitments, TEST_VECTOR_PROOFS};
+
+/// A table file in the temporary directory, removed when dropped.
+struct TableFile(PathBuf);
+
+impl TableFile {
+    fn new(name: &str) -> Self {
+        TableFile(std::env::temp_dir().join(format!(
+            "bulletproofs-{}-{}.table",
+            name,
+            std::process::id()
+        )))
+    }
+}
+
+impl Drop for TableFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+#[test]
+fn fixture_proofs_verify_against_a_mapped_table() {
+    let file = TableFile::new("fixtures");
+    BulletproofGens::new(64, 8).write_table(&file.0).unwrap();
+    let table = MappedBulletproofGens::open(&file.0).unwrap();
+    assert_eq!((table.gens_capacity(), table.party_capacity()), (64, 8));
+    assert!(table.fingerprint().is_none());
+
+    let pc_gens = PedersenGens::default();
+    let vc = test_vector_commitments();
+    for (i, proofs_i) in TEST_VECTOR_PROOFS.iter().enumerate() {
+        for (j, proof_ij) in proofs_i.iter().enumerate() {
+            let (n, m) = (8 << i, 1 << j);
+            let proof = RangeProof::from_bytes(&hex::decode(proof_ij).unwrap()).unwrap();
+            // Only the generators of the proof size are decompressed.
+            let bp_gens = table.gens(n, m).unwrap();
+            let mut transcript = Transcript::new(b"Deserialize-And-Verify Test");
+            assert_eq!(
+                proof.verify_multiple(
+                    &*bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &vc[0..m],
+                    BitSize::try_from(n).unwrap()
+                ),
+                Ok(())
+            );
+        }
+    }
+
+    // Generators of the same size are reused.
+    assert!(Arc::ptr_eq(
+        &table.gens(64, 8).unwrap(),
+        &table.gens(64, 8).unwrap()
+    ));
+    assert_eq!(
+        table.gens(128, 1).err(),
+        Some(ProofError::InvalidGeneratorsLength)
+    );
+    assert_eq!(
+        table.gens(64, 9).err(),
+        Some(ProofError::InvalidGeneratorsLength)
+    );
+}
+
+#[test]
+fn imported_gens_keep_their_fingerprint() {
+    let points = |label: &str| -> Vec<RistrettoPoint> {
+        (0..32)
+            .map(|i| {
+                RistrettoPoint::hash_from_bytes::<Sha3_512>(format!("{} {}", label, i).as_bytes())
+            })
+            .collect()
+    };
+    let imported = BulletproofGens::from_points(points("G"), points("H"), 2).unwrap();
+
+    let file = TableFile::new("imported");
+    imported.write_table(&file.0).unwrap();
+    let table = MappedBulletproofGens::open(&file.0).unwrap();
+    assert_eq!(table.fingerprint(), imported.fingerprint());
+
+    let pc_gens = PedersenGens::default();
+    let blindings = [1u64.into(), 2u64.into()];
+    let mut transcript = Transcript::new(b"MappedGensTest");
+    let (proof, commitments) = RangeProof::prove_multiple(
+        &imported,
+        &pc_gens,
+        &mut transcript,
+        &[3, 4],
+        &blindings,
+        BitSize::B16,
+    )
+    .unwrap();
+
+    let mut transcript = Transcript::new(b"MappedGensTest");
+    let mapped = table.gens(16, 2).unwrap();
+    assert!(proof
+        .verify_multiple(
+            &*mapped,
+            &pc_gens,
+            &mut transcript,
+            &commitments,
+            BitSize::B16
+        )
+        .is_ok());
+}
+
+#[test]
+fn corrupted_tables_are_rejected() {
+    let file = TableFile::new("corrupted");
+    BulletproofGens::new(8, 2).write_table(&file.0).unwrap();
+    let bytes = fs::read(&file.0).unwrap();
+    assert!(MappedBulletproofGens::open(&file.0).is_ok());
+
+    let open_modified = |modify: &dyn Fn(&mut Vec<u8>)| {
+        let mut modified = bytes.clone();
+        modify(&mut modified);
+        fs::write(&file.0, &modified).unwrap();
+        MappedBulletproofGens::open(&file.0).err().map(|e| e.kind())
+    };
+    let invalid = Some(io::ErrorKind::InvalidData);
+
+    // A flipped bit in the points, the capacities, or the checksum.
+    let last = bytes.len() - 1;
+    assert_eq!(open_modified(&|b| b[last] ^= 1), invalid);
+    assert_eq!(open_modified(&|b| b[100] ^= 0x80), invalid);
+    assert_eq!(open_modified(&|b| b[9] ^= 1), invalid);
+    assert_eq!(open_modified(&|b| b[60] ^= 1), invalid);
+    // Truncated, extended, or not a table at all.
+    assert_eq!(open_modified(&|b| b.truncate(last)), invalid);
+    assert_eq!(open_modified(&|b| b.extend_from_slice(&[0; 64])), invalid);
+    assert_eq!(open_modified(&|b| b.truncate(4)), invalid);
+}
@@ -0,0 +1,560 @@
+//! Hostile inputs to the public API: malformed encodings, out-of-range
+//! sizes and mismatched lengths must be rejected with an error, and
+//! never panic.
+//!
+//! Each test is named `panic_freedom_of_*`, so that the inventory runs
+//! with `cargo test panic_freedom`.
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::MultiscalarMul;
+
+use merlin::Transcript;
+
+use bulletproofs::range_proof_mpc::{dealer::Dealer, party::Party};
+use bulletproofs::{
+    BalanceProof, BatchVerifier, BitSize, BulletproofGens, CrossCommitmentProof,
+    EnvelopeTranscript, LinearProof, NonZeroProof, PedersenGens, ProofEnvelope, ProofError,
+    ProofKind, ProofParameters, RangeProof, SmallVecOpeningProof, StatementSession, VecSumProof,
+};
+
+/// Bitsizes and party counts a verifier may be handed by an attacker.
+const HOSTILE_SIZES: [usize; 7] = [0, 1, 3, 63, 65, 1 << 20, usize::MAX];
+
+/// A compressed point that does not decompress.
+const INVALID_POINT: CompressedRistretto = CompressedRistretto([0xff; 32]);
+
+fn gens() -> (PedersenGens, BulletproofGens) {
+    (PedersenGens::default(), BulletproofGens::new(64, 4))
+}
+
+fn transcript() -> Transcript {
+    Transcript::new(b"PanicFreedom")
+}
+
+/// Malformed variants of the `valid` encoding: every truncation, the
+/// encoding extended by a partial or a whole element, each 32-byte
+/// element replaced by a non-canonical one, and runs of `0xff`.
+fn hostile_encodings(valid: &[u8]) -> Vec<Vec<u8>> {
+    let mut encodings: Vec<Vec<u8>> = (0..valid.len()).map(|i| valid[..i].to_vec()).collect();
+    for extra in [1, 32, 64] {
+        let mut extended = valid.to_vec();
+        extended.resize(valid.len() + extra, 0);
+        encodings.push(extended);
+    }
+    for i in (0..valid.len()).step_by(32) {
+        let mut replaced = valid.to_vec();
+        for byte in replaced.iter_mut().skip(i).take(32) {
+            *byte = 0xff;
+        }
+        encodings.push(replaced);
+    }
+    for elements in [1, 2, 9, 21, 64] {
+        encodings.push(vec![0xff; 32 * elements]);
+    }
+    encodings
+}
+
+#[test]
+fn panic_freedom_of_range_proof_parsing() {
+    let (pc_gens, bp_gens) = gens();
+    let (proof, V) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript(),
+        7,
+        &Scalar::ONE,
+        BitSize::B8,
+    )
+    .unwrap();
+
+    for bytes in hostile_encodings(&proof.to_bytes()) {
+        let from_bytes = RangeProof::from_bytes(&bytes);
+        let read_from = RangeProof::read_from(&bytes[..]);
+        assert_eq!(from_bytes.is_ok(), read_from.is_ok());
+        if let Ok(parsed) = from_bytes {
+            let _ = parsed.bits();
+            let _ = parsed.verify_single(&bp_gens, &pc_gens, &mut transcript(), &V, BitSize::B8);
+            if let Ok(decompressed) = parsed.decompress() {
+                let _ = decompressed.verify_single(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript(),
+                    &V,
+                    BitSize::B8,
+                );
+            }
+        }
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn panic_freedom_of_range_proof_verification() {
+    let (pc_gens, bp_gens) = gens();
+    let (proof, V) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript(),
+        7,
+        &Scalar::ONE,
+        BitSize::B8,
+    )
+    .unwrap();
+    let small_gens = BulletproofGens::new(4, 1);
+
+    let commitment_sets: [Vec<CompressedRistretto>; 4] =
+        [vec![], vec![V; 3], vec![V; 65], vec![INVALID_POINT]];
+    for n in HOSTILE_SIZES.iter().copied().chain([8]) {
+        for commitments in &commitment_sets {
+            for bp_gens in [&bp_gens, &small_gens] {
+                let mut t = transcript();
+                assert!(proof
+                    .verify_multiple_usize(bp_gens, &pc_gens, &mut t, commitments, n)
+                    .is_err());
+            }
+        }
+    }
+    for n in BitSize::ALL {
+        for commitments in &commitment_sets {
+            for bp_gens in [&bp_gens, &small_gens] {
+                let mut t = transcript();
+                assert!(proof
+                    .verify_multiple_ct(bp_gens, &pc_gens, &mut t, commitments, n)
+                    .is_err());
+                assert!(proof
+                    .verify_and_digest(bp_gens, &pc_gens, &mut t, commitments, n)
+                    .is_err());
+                assert!(proof
+                    .verify_with_audit(bp_gens, &pc_gens, &mut t, commitments, n)
+                    .is_err());
+                assert!(proof
+                    .precheck(bp_gens, &pc_gens, &t, commitments, n)
+                    .is_err());
+                // The trace does not decompress the commitments.
+                let _ = proof.transcript_trace(bp_gens, &pc_gens, &t, commitments, n);
+                for shards in [0, 1, 3, usize::MAX] {
+                    assert!(proof
+                        .verify_multiple_sharded(bp_gens, &pc_gens, &mut t, commitments, n, shards)
+                        .is_err());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+#[allow(deprecated)]
+fn panic_freedom_of_range_proving() {
+    let (pc_gens, bp_gens) = gens();
+    let blindings = [Scalar::ONE; 5];
+    for n in HOSTILE_SIZES {
+        for m in [0, 1, 3, 5] {
+            let values = vec![u64::MAX; m];
+            let result = RangeProof::prove_multiple_usize(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript(),
+                &values,
+                &blindings[..m],
+                n,
+            );
+            assert!(result.is_err(), "n = {}, m = {}", n, m);
+        }
+    }
+
+    // A value outside the range is proved in constant time, but the
+    // proof does not verify.
+    for (v, n) in [(256, BitSize::B8), (1 << 32, BitSize::B32)] {
+        if let Ok((proof, V)) =
+            RangeProof::prove_single(&bp_gens, &pc_gens, &mut transcript(), v, &Scalar::ONE, n)
+        {
+            assert!(proof
+                .verify_single(&bp_gens, &pc_gens, &mut transcript(), &V, n)
+                .is_err());
+        }
+    }
+    assert_eq!(
+        RangeProof::prove_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript(),
+            &[1, 2],
+            &blindings[..1],
+            BitSize::B8
+        )
+        .unwrap_err(),
+        ProofError::WrongNumBlindingFactors
+    );
+}
+
+#[test]
+fn panic_freedom_of_batch_verification() {
+    let (pc_gens, bp_gens) = gens();
+    let (proof, V) = RangeProof::prove_single(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript(),
+        7,
+        &Scalar::ONE,
+        BitSize::B8,
+    )
+    .unwrap();
+    let commitments = [V, INVALID_POINT];
+
+    let mut batch = BatchVerifier::new();
+    for n in BitSize::ALL {
+        batch.queue_range(&proof, &commitments[..1], n, transcript());
+        batch.queue_range(&proof, &commitments, n, transcript());
+        batch.queue_range(&proof, &commitments[1..], n, transcript());
+        batch.queue_range(&proof, &[], n, transcript());
+    }
+    assert!(batch.verify(&bp_gens, &pc_gens).is_err());
+    assert!(!batch.invalid_entries(&bp_gens, &pc_gens).is_empty());
+}
+
+#[test]
+#[allow(deprecated)]
+fn panic_freedom_of_aggregation() {
+    let (pc_gens, bp_gens) = gens();
+    for n in HOSTILE_SIZES {
+        for m in HOSTILE_SIZES {
+            assert!(Dealer::new_usize(&bp_gens, &pc_gens, &mut transcript(), n, m).is_err());
+        }
+        assert!(Party::new_usize(&bp_gens, &pc_gens, 7, Scalar::ONE, n).is_err());
+    }
+    for j in [4, usize::MAX] {
+        let party = Party::new(&bp_gens, &pc_gens, 7, Scalar::ONE, BitSize::B8).unwrap();
+        assert!(party.assign_position(j).is_err());
+    }
+}
+
+#[test]
+fn panic_freedom_of_fixed_size_proofs() {
+    let pc_gens = PedersenGens::default();
+    let blinding = Scalar::from(3u64);
+    let C = pc_gens.commit(Scalar::ONE, blinding).compress();
+
+    let balance = BalanceProof::prove(&pc_gens, &mut transcript(), &[blinding], &[blinding]);
+    for bytes in hostile_encodings(&balance.to_bytes()) {
+        if let Ok(parsed) = BalanceProof::from_bytes(&bytes) {
+            let _ = parsed.verify(&pc_gens, &mut transcript(), &[C], &[C]);
+        }
+    }
+    for inputs in [&[][..], &[INVALID_POINT][..], &[C; 64][..]] {
+        assert!(balance
+            .verify(&pc_gens, &mut transcript(), inputs, &[C])
+            .is_err());
+    }
+
+    let nonzero = NonZeroProof::prove(&pc_gens, &mut transcript(), Scalar::ONE, blinding);
+    for bytes in hostile_encodings(&nonzero.to_bytes()) {
+        if let Ok(parsed) = NonZeroProof::from_bytes(&bytes) {
+            let _ = parsed.verify(&pc_gens, &mut transcript(), &C);
+        }
+    }
+    assert!(nonzero
+        .verify(&pc_gens, &mut transcript(), &INVALID_POINT)
+        .is_err());
+
+    let (G, H) = (pc_gens.B, RISTRETTO_BASEPOINT_POINT + pc_gens.B_blinding);
+    let (cross, C_1, C_2) = CrossCommitmentProof::prove(
+        &pc_gens,
+        G,
+        H,
+        &mut transcript(),
+        Scalar::ONE,
+        blinding,
+        blinding,
+    )
+    .unwrap();
+    for bytes in hostile_encodings(&cross.to_bytes()) {
+        if let Ok(parsed) = CrossCommitmentProof::from_bytes(&bytes) {
+            let _ = parsed.verify(&pc_gens, G, H, &mut transcript(), &C_1, &C_2);
+        }
+    }
+    for (C_1, C_2) in [(INVALID_POINT, C_2), (C_1, INVALID_POINT)] {
+        assert!(cross
+            .verify(&pc_gens, G, H, &mut transcript(), &C_1, &C_2)
+            .is_err());
+    }
+}
+
+#[test]
+fn panic_freedom_of_linear_proofs() {
+    let pc_gens = PedersenGens::default();
+    let G: Vec<RistrettoPoint> = BulletproofGens::new(4, 1)
+        .try_share(0)
+        .unwrap()
+        .G_checked(4)
+        .unwrap()
+        .copied()
+        .collect();
+    let a: Vec<Scalar> = (1..=4u64).map(Scalar::from).collect();
+    let b: Vec<Scalar> = (5..=8u64).map(Scalar::from).collect();
+    let C = (RistrettoPoint::multiscalar_mul(&a, &G) + pc_gens.B_blinding).compress();
+
+    let mut rng = rand::thread_rng();
+    for (len_a, len_b, len_G) in [(0, 0, 0), (3, 3, 3), (4, 4, 2), (2, 4, 4), (4, 2, 4)] {
+        assert!(LinearProof::create(
+            &mut transcript(),
+            &mut rng,
+            &C,
+            Scalar::ONE,
+            a[..len_a].to_vec(),
+            b[..len_b].to_vec(),
+            G[..len_G].to_vec(),
+            &pc_gens.B,
+            &pc_gens.B_blinding,
+        )
+        .is_err());
+    }
+
+    let proof = LinearProof::create(
+        &mut transcript(),
+        &mut rng,
+        &C,
+        Scalar::ONE,
+        a.clone(),
+        b.clone(),
+        G.clone(),
+        &pc_gens.B,
+        &pc_gens.B_blinding,
+    )
+    .unwrap();
+    for bytes in hostile_encodings(&proof.to_bytes()) {
+        if let Ok(parsed) = LinearProof::from_bytes(&bytes) {
+            let _ = parsed.verify(
+                &mut transcript(),
+                &C,
+                &G,
+                &pc_gens.B,
+                &pc_gens.B_blinding,
+                b.clone(),
+            );
+        }
+    }
+    for (len_b, len_G) in [(0, 0), (2, 2), (4, 2), (2, 4)] {
+        assert!(proof
+            .verify(
+                &mut transcript(),
+                &C,
+                &G[..len_G],
+                &pc_gens.B,
+                &pc_gens.B_blinding,
+                b[..len_b].to_vec(),
+            )
+            .is_err());
+    }
+}
+
+#[test]
+fn panic_freedom_of_small_vec_opening_proofs() {
+    let (pc_gens, bp_gens) = gens();
+    let share = bp_gens.try_share(0).unwrap();
+    let values: Vec<Scalar> = (0..4u64).map(Scalar::from).collect();
+
+    let too_many = vec![Scalar::ONE; 65];
+    assert!(SmallVecOpeningProof::prove(
+        &pc_gens,
+        &share,
+        &mut transcript(),
+        &too_many,
+        &Scalar::ONE
+    )
+    .is_err());
+
+    let (proof, C) =
+        SmallVecOpeningProof::prove(&pc_gens, &share, &mut transcript(), &values, &Scalar::ONE)
+            .unwrap();
+    for bytes in hostile_encodings(&proof.to_bytes()) {
+        if let Ok(parsed) = SmallVecOpeningProof::from_bytes(&bytes) {
+            let _ = parsed.verify(&pc_gens, &share, &mut transcript(), &C, values.len());
+        }
+    }
+    for n in HOSTILE_SIZES {
+        for C in [C, INVALID_POINT] {
+            assert!(proof
+                .verify(&pc_gens, &share, &mut transcript(), &C, n)
+                .is_err());
+        }
+    }
+}
+
+#[test]
+fn panic_freedom_of_vec_sum_proofs() {
+    let (pc_gens, bp_gens) = gens();
+    let values = [1u64, 2];
+    let blindings = [Scalar::ONE, Scalar::ONE];
+    let total = Scalar::from(2u64);
+
+    for n in HOSTILE_SIZES {
+        assert!(VecSumProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript(),
+            &values,
+            &blindings,
+            &total,
+            n
+        )
+        .is_err());
+    }
+    assert!(VecSumProof::prove(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript(),
+        &values,
+        &blindings[..1],
+        &total,
+        8
+    )
+    .is_err());
+
+    let (proof, Vs, T) = VecSumProof::prove(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript(),
+        &values,
+        &blindings,
+        &total,
+        8,
+    )
+    .unwrap();
+    for bytes in hostile_encodings(&proof.to_bytes()) {
+        if let Ok(parsed) = VecSumProof::from_bytes(&bytes) {
+            let _ = parsed.verify(&bp_gens, &pc_gens, &mut transcript(), &Vs, &T, 8);
+        }
+    }
+    for n in HOSTILE_SIZES {
+        for commitments in [&[][..], &Vs[..1], &[INVALID_POINT; 2][..]] {
+            assert!(proof
+                .verify(&bp_gens, &pc_gens, &mut transcript(), commitments, &T, n)
+                .is_err());
+        }
+    }
+}
+
+#[test]
+fn panic_freedom_of_envelopes() {
+    let params = ProofParameters::for_range_proofs(64, 2).unwrap();
+    let range =
+        ProofEnvelope::prove_range(&params, b"ctx", &[7], &[Scalar::ONE], BitSize::B8).unwrap();
+    for bytes in hostile_encodings(&range.to_bytes()) {
+        let _ = ProofKind::of_envelope(&bytes);
+        if let Ok(parsed) = ProofEnvelope::<RangeProof>::from_bytes(&bytes) {
+            let _ = parsed.verify(&params);
+        }
+        if let Ok(parsed) = ProofEnvelope::<SmallVecOpeningProof>::from_bytes(&bytes) {
+            let _ = parsed.verify(&params);
+        }
+    }
+
+    let opening =
+        ProofEnvelope::prove_opening(&params, b"ctx", &[Scalar::ONE; 3], &Scalar::ONE).unwrap();
+    for bytes in hostile_encodings(&opening.to_bytes()) {
+        let _ = ProofKind::of_envelope(&bytes);
+        if let Ok(parsed) = ProofEnvelope::<SmallVecOpeningProof>::from_bytes(&bytes) {
+            let _ = parsed.verify(&params);
+        }
+    }
+
+    // Sections that do not follow their registration.
+    let mut envelope = EnvelopeTranscript::new(b"ctx");
+    envelope.register("a", ProofKind::Range).unwrap();
+    assert!(envelope.register("a", ProofKind::Range).is_err());
+    assert!(envelope.section("b", ProofKind::Range, |_| Ok(())).is_err());
+    assert!(envelope.clone().finish().is_err());
+}
+
+#[test]
+fn panic_freedom_of_statement_sessions() {
+    let (pc_gens, bp_gens) = gens();
+    let mut session = StatementSession::new(transcript(), &[], b"ctx");
+    for index in [0, 1, usize::MAX] {
+        assert!(RangeProof::prove_in_session(
+            &bp_gens,
+            &pc_gens,
+            &mut session,
+            index,
+            7,
+            &Scalar::ONE,
+            BitSize::B8
+        )
+        .is_err());
+    }
+}
+
+#[test]
+fn panic_freedom_of_generators_and_parameters() {
+    let bp_gens = BulletproofGens::new(0, 0);
+    assert!(bp_gens.try_share(0).is_err());
+
+    let bp_gens = BulletproofGens::new(8, 2);
+    for j in [2, usize::MAX] {
+        assert!(bp_gens.try_share(j).is_err());
+    }
+    let share = bp_gens.try_share(1).unwrap();
+    for n in [9, usize::MAX] {
+        assert!(share.G_checked(n).is_err());
+    }
+
+    let points = vec![RISTRETTO_BASEPOINT_POINT; 4];
+    for (G, H, parties) in [(4, 2, 1), (4, 4, 0), (4, 4, 3)] {
+        assert!(
+            BulletproofGens::from_points(points[..G].to_vec(), points[..H].to_vec(), parties)
+                .is_err()
+        );
+    }
+
+    for (bits, parties) in [
+        (0, 1),
+        (65, 1),
+        (64, 0),
+        (64, usize::MAX),
+        (64, 1 << 62),
+        (usize::MAX, 1),
+    ] {
+        assert!(ProofParameters::for_range_proofs(bits, parties).is_err());
+    }
+}
+
+#[cfg(feature = "yoloproofs")]
+mod r1cs {
+    use super::*;
+
+    use bulletproofs::r1cs::{ConstraintSystem, Prover, R1CSProof, Verifier};
+
+    #[test]
+    fn panic_freedom_of_r1cs_proofs() {
+        let pc_gens = PedersenGens::default();
+        let bp_gens = BulletproofGens::new(8, 1);
+
+        let mut prover = Prover::new(&pc_gens, transcript());
+        let (C, x) = prover.commit(Scalar::from(3u64), Scalar::ONE);
+        let (_, _, out) = prover.multiply(x.into(), x.into());
+        prover.constrain(out - Scalar::from(9u64));
+        let proof = prover.prove(&bp_gens).unwrap();
+
+        let verify = |proof: R1CSProof, bp_gens: &BulletproofGens| {
+            let mut verifier = Verifier::new(transcript());
+            let x = verifier.commit(C);
+            let (_, _, out) = verifier.multiply(x.into(), x.into());
+            verifier.constrain(out - Scalar::from(9u64));
+            verifier.verify(&proof, &pc_gens, bp_gens)
+        };
+        for bytes in hostile_encodings(&proof.to_bytes()) {
+            if let Ok(parsed) = R1CSProof::from_bytes(&bytes) {
+                let _ = verify(parsed, &bp_gens);
+            }
+        }
+        assert!(verify(proof, &BulletproofGens::new(0, 1)).is_err());
+
+        for max_multipliers in [bulletproofs::MAX_VECTOR_LENGTH + 1, usize::MAX] {
+            assert!(ProofParameters::for_r1cs(max_multipliers).is_err());
+        }
+    }
+}
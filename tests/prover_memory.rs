@@ -0,0 +1,85 @@
+//! Counts the memory allocated by the phases of a range proof party.
+//!
+//! The allocator is global, so this file holds a single test.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::range_proof_mpc::dealer::Dealer;
+use bulletproofs::range_proof_mpc::party::Party;
+use bulletproofs::{BitSize, BulletproofGens, PedersenGens};
+
+/// Counts the bytes allocated through it.
+struct CountingAllocator;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+/// Returns the bytes allocated by an `n`-bit party computing its
+/// polynomial commitments.
+fn poly_commitment_allocation(n: usize) -> usize {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, 1);
+    let mut rng = ChaChaRng::from_seed([5u8; 32]);
+
+    let mut transcript = Transcript::new(b"ProverMemoryTest");
+    let dealer = Dealer::new(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        BitSize::try_from(n).unwrap(),
+        1,
+    )
+    .unwrap();
+    let (party, bit_commitment) = Party::new(
+        &bp_gens,
+        &pc_gens,
+        123,
+        Scalar::ONE,
+        BitSize::try_from(n).unwrap(),
+    )
+    .unwrap()
+    .assign_position_with_rng(0, &mut rng)
+    .unwrap();
+    let (_dealer, bit_challenge) = dealer
+        .receive_bit_commitments(vec![bit_commitment])
+        .unwrap();
+
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let (party, _poly_commitment) = party.apply_challenge_with_rng(&bit_challenge, &mut rng);
+    let allocated = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop(party);
+    allocated
+}
+
+#[test]
+fn poly_commitment_stores_only_the_right_polynomial() {
+    // Committing to the coefficients allocates the same amount for
+    // every bitsize, so the difference is in the stored polynomials:
+    // the two coefficient vectors of r(x), and none for l(x), whose
+    // constant terms are recomputed from the bits of the value.
+    let growth = poly_commitment_allocation(64) - poly_commitment_allocation(8);
+    assert_eq!(growth, 2 * (64 - 8) * 32);
+}
@@ -6,12 +6,15 @@ extern crate merlin;
 extern crate rand;
 
 use bulletproofs::r1cs::*;
-use bulletproofs::{BulletproofGens, PedersenGens};
+use bulletproofs::{BulletproofGens, PedersenGens, ProofError};
 use curve25519_dalek::ristretto::CompressedRistretto;
 use curve25519_dalek::scalar::Scalar;
 use merlin::Transcript;
 use rand::seq::SliceRandom;
 use rand::thread_rng;
+use rand_chacha::ChaChaRng;
+use rand_core::{CryptoRng, RngCore, SeedableRng};
+use std::iter;
 
 // Shuffle gadget (documented in markdown file)
 
@@ -65,10 +68,10 @@ impl ShuffleProof {
     /// Attempt to construct a proof that `output` is a permutation of `input`.
     ///
     /// Returns a tuple `(proof, input_commitments || output_commitments)`.
-    pub fn prove<'a, 'b>(
+    pub fn prove<'b>(
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
-        transcript: &'a mut Transcript,
+        transcript: &mut Transcript,
         input: &[Scalar],
         output: &[Scalar],
     ) -> Result<
@@ -78,6 +81,33 @@ impl ShuffleProof {
             Vec<CompressedRistretto>,
         ),
         R1CSError,
+    > {
+        ShuffleProof::prove_with_rng(
+            pc_gens,
+            bp_gens,
+            transcript,
+            input,
+            output,
+            &mut thread_rng(),
+        )
+    }
+
+    /// Attempt to construct a proof that `output` is a permutation of
+    /// `input`, using `rng` for the blinding factors and the proof.
+    pub fn prove_with_rng<'b, R: RngCore + CryptoRng>(
+        pc_gens: &'b PedersenGens,
+        bp_gens: &'b BulletproofGens,
+        transcript: &mut Transcript,
+        input: &[Scalar],
+        output: &[Scalar],
+        rng: &mut R,
+    ) -> Result<
+        (
+            ShuffleProof,
+            Vec<CompressedRistretto>,
+            Vec<CompressedRistretto>,
+        ),
+        R1CSError,
     > {
         // Apply a domain separator with the shuffle parameters to the transcript
         // XXX should this be part of the gadget?
@@ -85,25 +115,23 @@ impl ShuffleProof {
         transcript.append_message(b"dom-sep", b"ShuffleProof");
         transcript.append_u64(b"k", k as u64);
 
-        let mut prover = Prover::new(&pc_gens, transcript);
+        let mut prover = Prover::new(pc_gens, transcript);
 
         // Construct blinding factors using an RNG.
         // Note: a non-example implementation would want to operate on existing commitments.
-        let mut blinding_rng = rand::thread_rng();
-
         let (input_commitments, input_vars): (Vec<_>, Vec<_>) = input
-            .into_iter()
-            .map(|v| prover.commit(*v, Scalar::random(&mut blinding_rng)))
+            .iter()
+            .map(|v| prover.commit(*v, Scalar::random(rng)))
             .unzip();
 
         let (output_commitments, output_vars): (Vec<_>, Vec<_>) = output
-            .into_iter()
-            .map(|v| prover.commit(*v, Scalar::random(&mut blinding_rng)))
+            .iter()
+            .map(|v| prover.commit(*v, Scalar::random(rng)))
             .unzip();
 
         ShuffleProof::gadget(&mut prover, input_vars, output_vars)?;
 
-        let proof = prover.prove(&bp_gens)?;
+        let proof = prover.prove_with_rng(bp_gens, rng)?;
 
         Ok((ShuffleProof(proof), input_commitments, output_commitments))
     }
@@ -111,13 +139,13 @@ impl ShuffleProof {
 
 impl ShuffleProof {
     /// Attempt to verify a `ShuffleProof`.
-    pub fn verify<'a, 'b>(
+    pub fn verify<'b>(
         &self,
         pc_gens: &'b PedersenGens,
         bp_gens: &'b BulletproofGens,
-        transcript: &'a mut Transcript,
-        input_commitments: &Vec<CompressedRistretto>,
-        output_commitments: &Vec<CompressedRistretto>,
+        transcript: &mut Transcript,
+        input_commitments: &[CompressedRistretto],
+        output_commitments: &[CompressedRistretto],
     ) -> Result<(), R1CSError> {
         // Apply a domain separator with the shuffle parameters to the transcript
         // XXX should this be part of the gadget?
@@ -139,9 +167,46 @@ impl ShuffleProof {
 
         ShuffleProof::gadget(&mut verifier, input_vars, output_vars)?;
 
-        verifier.verify(&self.0, &pc_gens, &bp_gens)?;
+        verifier.verify(&self.0, pc_gens, bp_gens)?;
         Ok(())
     }
+
+    /// Compiles the shuffle gadget over `k` inputs and `k` outputs.
+    pub fn compile(k: usize) -> Result<CompiledCircuit, R1CSError> {
+        CompiledCircuit::compile(2 * k, 0, |cs, vars, _| {
+            ShuffleProof::gadget(cs, vars[..k].to_vec(), vars[k..].to_vec())
+        })
+    }
+
+    /// Attempt to verify a `ShuffleProof` against a compiled shuffle circuit.
+    pub fn verify_compiled<'b>(
+        &self,
+        circuit: &CompiledCircuit,
+        pc_gens: &'b PedersenGens,
+        bp_gens: &'b BulletproofGens,
+        transcript: &mut Transcript,
+        input_commitments: &[CompressedRistretto],
+        output_commitments: &[CompressedRistretto],
+    ) -> Result<(), R1CSError> {
+        let k = input_commitments.len();
+        transcript.append_message(b"dom-sep", b"ShuffleProof");
+        transcript.append_u64(b"k", k as u64);
+
+        let commitments: Vec<_> = input_commitments
+            .iter()
+            .chain(output_commitments)
+            .cloned()
+            .collect();
+
+        Verifier::new(transcript).verify_compiled(
+            circuit,
+            &[],
+            &self.0,
+            &commitments,
+            pc_gens,
+            bp_gens,
+        )
+    }
 }
 
 fn kshuffle_helper(k: usize) {
@@ -154,7 +219,7 @@ fn kshuffle_helper(k: usize) {
     let (proof, input_commitments, output_commitments) = {
         // Randomly generate inputs and outputs to kshuffle
         let mut rng = rand::thread_rng();
-        let (min, max) = (0u64, std::u64::MAX);
+        let (min, max) = (0u64, u64::MAX);
         let input: Vec<Scalar> = (0..k)
             .map(|_| Scalar::from(rng.gen_range(min..max)))
             .collect();
@@ -224,6 +289,53 @@ fn shuffle_gadget_test_42() {
     kshuffle_helper(42);
 }
 
+#[test]
+fn compiled_shuffle_agrees_with_gadget() {
+    let pc_gens = PedersenGens::default();
+    let mut rng = thread_rng();
+
+    for k in [1usize, 2, 3, 4, 7, 16] {
+        let bp_gens = BulletproofGens::new((2 * k).next_power_of_two(), 1);
+        let circuit = ShuffleProof::compile(k).unwrap();
+
+        for _ in 0..3 {
+            let input: Vec<Scalar> = (0..k).map(|_| Scalar::from(rng.next_u64())).collect();
+            let mut output = input.clone();
+            output.shuffle(&mut rng);
+
+            let mut prover_transcript = Transcript::new(b"ShuffleProofTest");
+            let (proof, input_commitments, output_commitments) =
+                ShuffleProof::prove(&pc_gens, &bp_gens, &mut prover_transcript, &input, &output)
+                    .unwrap();
+
+            let mut wrong_commitments = output_commitments.clone();
+            wrong_commitments[0] = pc_gens
+                .commit(Scalar::from(rng.next_u64()), Scalar::random(&mut rng))
+                .compress();
+
+            for (outputs, valid) in [(&output_commitments, true), (&wrong_commitments, false)] {
+                let uncompiled = proof.verify(
+                    &pc_gens,
+                    &bp_gens,
+                    &mut Transcript::new(b"ShuffleProofTest"),
+                    &input_commitments,
+                    outputs,
+                );
+                let compiled = proof.verify_compiled(
+                    &circuit,
+                    &pc_gens,
+                    &bp_gens,
+                    &mut Transcript::new(b"ShuffleProofTest"),
+                    &input_commitments,
+                    outputs,
+                );
+                assert_eq!(uncompiled.is_ok(), valid);
+                assert_eq!(compiled.is_ok(), valid);
+            }
+        }
+    }
+}
+
 /// Constrains (a1 + a2) * (b1 + b2) = (c1 + c2)
 fn example_gadget<CS: ConstraintSystem>(
     cs: &mut CS,
@@ -239,6 +351,7 @@ fn example_gadget<CS: ConstraintSystem>(
 }
 
 // Prover's scope
+#[allow(clippy::too_many_arguments)]
 fn example_gadget_proof(
     pc_gens: &PedersenGens,
     bp_gens: &BulletproofGens,
@@ -251,12 +364,14 @@ fn example_gadget_proof(
 ) -> Result<(R1CSProof, Vec<CompressedRistretto>), R1CSError> {
     let mut transcript = Transcript::new(b"R1CSExampleGadget");
 
-    // 1. Create a prover
+    // 1. Create a prover, which some tests give an unsatisfying
+    // assignment to check that its proof fails to verify
     let mut prover = Prover::new(pc_gens, &mut transcript);
+    prover.skip_witness_check();
 
     // 2. Commit high-level variables
     let (commitments, vars): (Vec<_>, Vec<_>) = [a1, a2, b1, b2, c1]
-        .into_iter()
+        .iter()
         .map(|x| prover.commit(Scalar::from(*x), Scalar::random(&mut thread_rng())))
         .unzip();
 
@@ -306,10 +421,67 @@ fn example_gadget_verify(
 
     // 4. Verify the proof
     verifier
-        .verify(&proof, &pc_gens, &bp_gens)
+        .verify(&proof, pc_gens, bp_gens)
         .map_err(|_| R1CSError::VerificationError)
 }
 
+// Verifier logic, with the gadget compiled once and `c2` as a public input
+fn example_gadget_verify_compiled(
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    circuit: &CompiledCircuit,
+    c2: &[Scalar],
+    proof: &R1CSProof,
+    commitments: &[CompressedRistretto],
+) -> Result<(), R1CSError> {
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    Verifier::new(&mut transcript).verify_compiled(
+        circuit,
+        c2,
+        proof,
+        commitments,
+        pc_gens,
+        bp_gens,
+    )
+}
+
+#[test]
+fn compiled_example_gadget_checks_public_inputs() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+    let circuit = CompiledCircuit::compile(5, 1, |cs, vars, public_inputs| {
+        example_gadget(
+            cs,
+            vars[0].into(),
+            vars[1].into(),
+            vars[2].into(),
+            vars[3].into(),
+            vars[4].into(),
+            public_inputs[0].into(),
+        );
+        Ok(())
+    })
+    .unwrap();
+
+    // (3 + 4) * (6 + 1) = 40 + 9
+    let (proof, commitments) = example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+
+    let verify = |c2: &[Scalar]| {
+        example_gadget_verify_compiled(&pc_gens, &bp_gens, &circuit, c2, &proof, &commitments)
+    };
+    assert!(verify(&[Scalar::from(9u64)]).is_ok());
+    assert_eq!(
+        verify(&[Scalar::from(10u64)]),
+        Err(R1CSError::VerificationError)
+    );
+    assert!(matches!(verify(&[]), Err(R1CSError::GadgetError { .. })));
+
+    assert!(
+        example_gadget_verify(&pc_gens, &bp_gens, 9, proof.clone(), commitments.clone()).is_ok()
+    );
+    assert!(example_gadget_verify(&pc_gens, &bp_gens, 10, proof, commitments).is_err());
+}
+
 fn example_gadget_roundtrip_helper(
     a1: u64,
     a2: u64,
@@ -356,6 +528,72 @@ fn example_gadget_test() {
     assert!(example_gadget_roundtrip_helper(3, 4, 6, 1, 40, 10).is_err());
 }
 
+#[test]
+fn example_gadget_needs_a_generator_share() {
+    let pc_gens = PedersenGens::default();
+    let no_parties = BulletproofGens::new(128, 0);
+    let out_of_range = R1CSError::ProofError(ProofError::ShareOutOfRange {
+        index: 0,
+        party_capacity: 0,
+    });
+
+    assert_eq!(
+        example_gadget_proof(&pc_gens, &no_parties, 3, 4, 6, 1, 40, 9).err(),
+        Some(out_of_range.clone())
+    );
+
+    let bp_gens = BulletproofGens::new(128, 1);
+    let (proof, commitments) = example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    let mut verifier = Verifier::new(&mut transcript);
+    let vars: Vec<_> = commitments.iter().map(|V| verifier.commit(*V)).collect();
+    example_gadget(
+        &mut verifier,
+        vars[0].into(),
+        vars[1].into(),
+        vars[2].into(),
+        vars[3].into(),
+        vars[4].into(),
+        Scalar::from(9u64).into(),
+    );
+    assert_eq!(
+        verifier.verify(&proof, &pc_gens, &no_parties),
+        Err(out_of_range)
+    );
+}
+
+#[test]
+fn example_gadget_retry_after_failed_verification() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(128, 1);
+
+    // (3 + 4) * (6 + 1) = (40 + 9), proven twice with different blindings.
+    let (proof_a, _) = example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+    let (proof_b, commitments_b) =
+        example_gadget_proof(&pc_gens, &bp_gens, 3, 4, 6, 1, 40, 9).unwrap();
+
+    let verify = |transcript: &mut Transcript, proof: &R1CSProof| {
+        let mut verifier = Verifier::new(transcript);
+        let vars: Vec<_> = commitments_b.iter().map(|V| verifier.commit(*V)).collect();
+        example_gadget(
+            &mut verifier,
+            vars[0].into(),
+            vars[1].into(),
+            vars[2].into(),
+            vars[3].into(),
+            vars[4].into(),
+            Scalar::from(9u64).into(),
+        );
+        verifier.verify(proof, &pc_gens, &bp_gens)
+    };
+
+    // A failed verification leaves the transcript as it was, so the
+    // same transcript can be used to verify another proof.
+    let mut transcript = Transcript::new(b"R1CSExampleGadget");
+    assert!(verify(&mut transcript, &proof_a).is_err());
+    assert!(verify(&mut transcript, &proof_b).is_ok());
+}
+
 #[test]
 fn example_gadget_serialization_test() {
     // (3 + 4) * (6 + 1) = (40 + 9)
@@ -364,6 +602,138 @@ fn example_gadget_serialization_test() {
     assert!(example_gadget_roundtrip_serialization_helper(3, 4, 6, 1, 40, 10).is_err());
 }
 
+/// The input and output of the shuffle test vector.
+fn shuffle_test_vector_values() -> (Vec<Scalar>, Vec<Scalar>) {
+    let input = [1u64, 2, 3, 4].iter().map(|&v| Scalar::from(v)).collect();
+    let output = [3u64, 1, 4, 2].iter().map(|&v| Scalar::from(v)).collect();
+    (input, output)
+}
+
+/// The shuffle test vector: a two-phase proof of a shuffle of four
+/// values, and the input and output commitments.
+fn shuffle_test_vector() -> (
+    ShuffleProof,
+    Vec<CompressedRistretto>,
+    Vec<CompressedRistretto>,
+) {
+    let decode = |hex_points: &[&[u8]]| -> Vec<CompressedRistretto> {
+        hex_points
+            .iter()
+            .map(|p| CompressedRistretto::from_slice(&hex::decode(p).unwrap()).unwrap())
+            .collect()
+    };
+    let proof = R1CSProof::from_bytes(&hex::decode(SHUFFLE_PROOF).unwrap()).unwrap();
+    (
+        ShuffleProof(proof),
+        decode(&SHUFFLE_INPUT_COMMITMENTS),
+        decode(&SHUFFLE_OUTPUT_COMMITMENTS),
+    )
+}
+
+const SHUFFLE_PROOF: &[u8] = b"01c436d27747eadaa809347b419acdaac7350de522a8652d25762d8dd3cbd0af69befc98179fa873e5a50408cdbec1ef7ede5b7cc58ddad2b86ac6d006410ff23a5e889da6737bf178f055760ff43c29cd15b8a0364798acadec266580f96eab57161108137cf361d81db220b4b17f04286ac85726e5679b2e84f62a2bfddb00216c61cd4aa4dea65fc0def811c0b3a1f348cccdb63a6cf1e81951b1a0a125a14bc49e785fa8e82af5cad73cb771e971845db6be90fd936f67dab3ebbf7413f93b407e86f37921bc3b55f308adf3fefa13dc2854a167fe94285e2d16a7c56a042be2af29f5c3eb1ed5f39a4fb487cfc7f075839d40f6b367461e8b9be20d3e7b15d4bb2f70eca51b297ad33635064411899b594b3816a9082b83ef193d09783148c01d8133bc8400b58deac6dc18b54dc5bbb4e7ab5a120801d5cf5f3508e7223400aa0304a43cdb5e7c5766f36fa86b757f4d02866fbcec18d07b4665c8fcb04f36541f94ccff7c11296ef72925ae2833b0301255d58d8587b2d63e94d3047d0e3d03eee4f631d8db60a9cddc459da788d6767c2b14e91cf50c2e7c2082f6080dc27ef3a876b1e99b93e52ab8d0991c352d8ffdcb4046112503b0c436917bc50ed8b5540e02b3fc4df111ed9ac3c847b3b3ceea940357b0ea42595199b5703a5d6ca70fc13a56043aa76ceeee8eb5cbd9e772103dde2fc678ddaf860d92bfd74432f9cf777871475feab69bc06a526f7f39825176caed2acfd2d7214e79b0ab1f2c1b91d0590b09764847ec585ab25dfc47e03287e99f8f2fcfebede00334177d9af3f90b8aabadffbae0f4b1b4a35abeebacf74f7185e0da809351ec128b3f5ed2665b0c31543c9be2cdd087cbb548cfecf0490e26a55a1cb6724c9047b0da73f1b8fdf0084f93bc256e9f5f562ca9d7f092c2205222f2efc32cfae31367f705c609df3b39881af29960df327ff9d3850b7bf1aa9ed618202626dea4e8597605";
+
+const SHUFFLE_INPUT_COMMITMENTS: [&[u8]; 4] = [
+    b"cc1fcc30f54c314a304393650e404c6780d322f8825e2137b9013b6d4234b82e",
+    b"e8d258860ef4bd4771e1aa766492ff37647a55a16de028a631bc748efae86a44",
+    b"08ca146525e9d79cb0c41dac7c5f8e6e4acbee96c497d9dd96ab16ed701f3f71",
+    b"30a8a7b29828ffdfe77b73e46883c553d5c2861877752b79de32cb00c84de10d",
+];
+
+const SHUFFLE_OUTPUT_COMMITMENTS: [&[u8]; 4] = [
+    b"b0387fa524a65b6cba6afe7a649dd91814037515a6d4b5368aed0fb7d0b5cc68",
+    b"42b5370b26099ef2d4361cd2e1693bc3ac06053f6078f2bbf92c43ff46dd4c46",
+    b"58de75cdd6bfea6a80e20867f257f3ee14d775717ea73b7e5f053692dba3e61e",
+    b"12298b8639b8a2658064b47ff99af0118659251f61477d93e95b8c5a2e39b104",
+];
+
+/// Verifies the shuffle test vector, returning any error.
+fn verify_shuffle_test_vector(proof_bytes: &[u8]) -> Result<(), R1CSError> {
+    let (_, input_commitments, output_commitments) = shuffle_test_vector();
+    let proof = ShuffleProof(R1CSProof::from_bytes(proof_bytes)?);
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+    let mut transcript = Transcript::new(b"ShuffleProofTestVector");
+    proof.verify(
+        &pc_gens,
+        &bp_gens,
+        &mut transcript,
+        &input_commitments,
+        &output_commitments,
+    )
+}
+
+// Tests that shuffle proofs created by earlier versions continue to
+// deserialize and verify.
+#[test]
+fn shuffle_test_vector_verifies() {
+    let (proof, _, _) = shuffle_test_vector();
+    let bytes = proof.0.to_bytes();
+    assert_eq!(bytes, hex::decode(SHUFFLE_PROOF).unwrap());
+    assert_eq!(bytes.len(), proof.0.serialized_size());
+    assert!(verify_shuffle_test_vector(&bytes).is_ok());
+}
+
+#[test]
+fn corrupted_shuffle_proofs_are_rejected() {
+    let bytes = hex::decode(SHUFFLE_PROOF).unwrap();
+
+    // The version byte, then every 32-byte point or scalar.
+    let offsets = iter::once(0).chain((1..bytes.len()).step_by(32));
+    for offset in offsets {
+        // A flipped bit, and an invalid point or non-canonical scalar.
+        for &corruption in &[0x01, 0xff] {
+            let mut corrupted = bytes.clone();
+            corrupted[offset] ^= corruption;
+            assert!(
+                verify_shuffle_test_vector(&corrupted).is_err(),
+                "corruption {:#x} at offset {} was accepted",
+                corruption,
+                offset
+            );
+        }
+    }
+
+    // Every truncation, and trailing data.
+    for len in 0..bytes.len() {
+        assert!(verify_shuffle_test_vector(&bytes[..len]).is_err());
+    }
+    let mut extended = bytes.clone();
+    extended.extend_from_slice(&[0u8; 64]);
+    assert!(verify_shuffle_test_vector(&extended).is_err());
+}
+
+// This function generates the shuffle test vector and dumps it to
+// stdout.  It can be run by uncommenting the #[test] annotation.
+// We allow(dead_code) to ensure that it continues to compile.
+//#[test]
+#[allow(dead_code)]
+fn generate_shuffle_test_vector() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+    let (input, output) = shuffle_test_vector_values();
+
+    let mut rng = ChaChaRng::from_seed([24u8; 32]);
+    let mut transcript = Transcript::new(b"ShuffleProofTestVector");
+    let (proof, input_commitments, output_commitments) = ShuffleProof::prove_with_rng(
+        &pc_gens,
+        &bp_gens,
+        &mut transcript,
+        &input,
+        &output,
+        &mut rng,
+    )
+    .unwrap();
+
+    println!("proof: {}", hex::encode(proof.0.to_bytes()));
+    for V in input_commitments {
+        println!("input: {}", hex::encode(V.as_bytes()));
+    }
+    for V in output_commitments {
+        println!("output: {}", hex::encode(V.as_bytes()));
+    }
+}
+
 // Range Proof gadget
 
 /// Enforces that the quantity of v is in the range [0, 2^n).
@@ -413,9 +783,9 @@ fn range_proof_gadget() {
         let (min, max) = (0u64, ((1u128 << n) - 1) as u64);
         let values: Vec<u64> = (0..m).map(|_| rng.gen_range(min..max)).collect();
         for v in values {
-            assert!(range_proof_helper(v.into(), *n).is_ok());
+            assert!(range_proof_helper(v, *n).is_ok());
         }
-        assert!(range_proof_helper((max + 1).into(), *n).is_err());
+        assert!(range_proof_helper(max + 1, *n).is_err());
     }
 }
 
@@ -431,6 +801,8 @@ fn range_proof_helper(v_val: u64, n: usize) -> Result<(), R1CSError> {
         let mut rng = rand::thread_rng();
 
         let mut prover = Prover::new(&pc_gens, &mut prover_transcript);
+        // Out-of-range values must reach the verifier.
+        prover.skip_witness_check();
 
         let (com, var) = prover.commit(v_val.into(), Scalar::random(&mut rng));
         assert!(range_proof(&mut prover, var.into(), Some(v_val), n).is_ok());
@@ -452,3 +824,44 @@ fn range_proof_helper(v_val: u64, n: usize) -> Result<(), R1CSError> {
     // Verifier verifies proof
     verifier.verify(&proof, &pc_gens, &bp_gens)
 }
+
+/// Proves that the committed `x` squares to `square`, checking that
+/// the assignment satisfies the constraints if `check_witness` is set.
+#[cfg(debug_assertions)]
+fn square_proof(
+    pc_gens: &PedersenGens,
+    bp_gens: &BulletproofGens,
+    x: u64,
+    square: u64,
+    check_witness: bool,
+) -> Result<(R1CSProof, CompressedRistretto), R1CSError> {
+    let mut transcript = Transcript::new(b"WitnessCheckTest");
+    let mut prover = Prover::new(pc_gens, &mut transcript);
+    if !check_witness {
+        prover.skip_witness_check();
+    }
+    let (commitment, x_var) = prover.commit(Scalar::from(x), Scalar::ONE);
+    let (_, _, o) = prover.multiply(x_var.into(), x_var.into());
+    prover.constrain(o - Scalar::from(square));
+    Ok((prover.prove(bp_gens)?, commitment))
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn unsatisfied_constraints_are_caught_in_debug_builds() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+
+    assert!(square_proof(&pc_gens, &bp_gens, 3, 9, true).is_ok());
+    let result = std::panic::catch_unwind(|| square_proof(&pc_gens, &bp_gens, 3, 10, true));
+    assert!(result.is_err());
+
+    // Without the check, the proof is created and fails to verify.
+    let (proof, commitment) = square_proof(&pc_gens, &bp_gens, 3, 10, false).unwrap();
+    let mut transcript = Transcript::new(b"WitnessCheckTest");
+    let mut verifier = Verifier::new(&mut transcript);
+    let x_var = verifier.commit(commitment);
+    let (_, _, o) = verifier.multiply(x_var.into(), x_var.into());
+    verifier.constrain(o - Scalar::from(10u64));
+    assert!(verifier.verify(&proof, &pc_gens, &bp_gens).is_err());
+}
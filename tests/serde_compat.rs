@@ -0,0 +1,161 @@
+//! The encodings of proofs through `serde` in the supported bincode
+//! configurations.  Proofs serialize as a byte string holding their
+//! `to_bytes` encoding, so each configuration only changes the length
+//! prefix in front of it.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use bincode2::config::{self, Config};
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use bulletproofs::{
+    BalanceProof, BitSize, BulletproofGens, PedersenGens, RangeProof, SmallVecOpeningProof,
+};
+
+fn range_proof(n: usize, m: usize) -> RangeProof {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let values: Vec<u64> = (0..m as u64).collect();
+    let blindings: Vec<Scalar> = (1..=m as u64).map(Scalar::from).collect();
+    let mut transcript = Transcript::new(b"SerdeCompatTest");
+    RangeProof::prove_multiple(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        &values,
+        &blindings,
+        BitSize::try_from(n).unwrap(),
+    )
+    .unwrap()
+    .0
+}
+
+fn bincode2_roundtrip<P, C>(proof: &P, to_bytes: fn(&P) -> Vec<u8>, config: C) -> Vec<u8>
+where
+    P: Serialize + DeserializeOwned,
+    C: Config,
+{
+    let bytes = to_bytes(proof);
+    let encoded = bincode2::serde::encode_to_vec(proof, config).unwrap();
+    assert!(encoded.ends_with(&bytes));
+    let (decoded, read): (P, usize) = bincode2::serde::decode_from_slice(&encoded, config).unwrap();
+    assert_eq!(read, encoded.len());
+    assert_eq!(to_bytes(&decoded), bytes);
+    encoded[..encoded.len() - bytes.len()].to_vec()
+}
+
+/// Checks that `proof` round-trips through every supported
+/// configuration with the expected length prefix before its
+/// `to_bytes` encoding.
+fn check_configs<P>(proof: &P, to_bytes: fn(&P) -> Vec<u8>)
+where
+    P: Serialize + DeserializeOwned,
+{
+    let bytes = &to_bytes(proof)[..];
+    let len = bytes.len() as u64;
+
+    // bincode 1 with its default options: a little-endian u64 length.
+    let encoded = bincode::serialize(proof).unwrap();
+    assert_eq!(encoded[..8], len.to_le_bytes());
+    assert_eq!(&encoded[8..], bytes);
+    assert_eq!(to_bytes(&bincode::deserialize(&encoded).unwrap()), bytes);
+
+    // bincode 2's legacy configuration is bincode 1's.
+    assert_eq!(
+        bincode2::serde::encode_to_vec(proof, config::legacy()).unwrap(),
+        encoded
+    );
+    bincode2_roundtrip(proof, to_bytes, config::legacy());
+
+    let fixed = bincode2_roundtrip(
+        proof,
+        to_bytes,
+        config::standard().with_fixed_int_encoding(),
+    );
+    assert_eq!(fixed, len.to_le_bytes());
+    let fixed_be = bincode2_roundtrip(
+        proof,
+        to_bytes,
+        config::standard()
+            .with_fixed_int_encoding()
+            .with_big_endian(),
+    );
+    assert_eq!(fixed_be, len.to_be_bytes());
+
+    // The standard configuration uses variable-length integers.
+    let varint = |big_endian: bool| {
+        let len = bytes.len() as u16;
+        match len {
+            0..=250 => vec![len as u8],
+            _ if big_endian => [&[251u8][..], &len.to_be_bytes()].concat(),
+            _ => [&[251u8][..], &len.to_le_bytes()].concat(),
+        }
+    };
+    let standard = bincode2_roundtrip(proof, to_bytes, config::standard());
+    assert_eq!(standard, varint(false));
+    let standard_be = bincode2_roundtrip(proof, to_bytes, config::standard().with_big_endian());
+    assert_eq!(standard_be, varint(true));
+}
+
+#[test]
+fn range_proofs_in_bincode_configs() {
+    for (n, m) in [(8, 1), (32, 1), (64, 4)] {
+        let proof = range_proof(n, m);
+        check_configs(&proof, RangeProof::to_bytes);
+    }
+
+    // A 32-bit proof is 608 bytes, encoded as a varint with a u16 marker.
+    let proof = range_proof(32, 1);
+    let encoded = bincode2::serde::encode_to_vec(&proof, config::standard()).unwrap();
+    assert_eq!(encoded[..3], [0xfb, 0x60, 0x02]);
+}
+
+#[test]
+fn small_proofs_in_bincode_configs() {
+    let pc_gens = PedersenGens::default();
+    let mut transcript = Transcript::new(b"SerdeCompatTest");
+    let balance = BalanceProof::prove(
+        &pc_gens,
+        &mut transcript,
+        &[Scalar::from(3u64)],
+        &[Scalar::from(1u64), Scalar::from(2u64)],
+    );
+    check_configs(&balance, |proof| proof.to_bytes().to_vec());
+
+    let bp_gens = BulletproofGens::new(4, 1);
+    let values: Vec<Scalar> = (0..4u64).map(Scalar::from).collect();
+    let mut transcript = Transcript::new(b"SerdeCompatTest");
+    let (opening, _) = SmallVecOpeningProof::prove(
+        &pc_gens,
+        &bp_gens.try_share(0).unwrap(),
+        &mut transcript,
+        &values,
+        &Scalar::ONE,
+    )
+    .unwrap();
+    check_configs(&opening, SmallVecOpeningProof::to_bytes);
+}
+
+#[test]
+fn noncanonical_scalars_are_rejected_in_bincode_configs() {
+    let mut bytes = range_proof(8, 1).to_bytes();
+    // t_x is the scalar after the four points.
+    bytes[4 * 32..5 * 32].copy_from_slice(&[0xff; 32]);
+    assert!(RangeProof::from_bytes(&bytes).is_err());
+
+    let prefixed = [&(bytes.len() as u64).to_le_bytes()[..], &bytes].concat();
+    assert!(bincode::deserialize::<RangeProof>(&prefixed).is_err());
+    assert!(
+        bincode2::serde::decode_from_slice::<RangeProof, _>(&prefixed, config::legacy()).is_err()
+    );
+
+    let varint = [&[251u8][..], &(bytes.len() as u16).to_le_bytes(), &bytes].concat();
+    assert!(
+        bincode2::serde::decode_from_slice::<RangeProof, _>(&varint, config::standard()).is_err()
+    );
+}
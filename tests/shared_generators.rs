@@ -0,0 +1,107 @@
+#![allow(non_snake_case)]
+
+use std::thread;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::{
+    AsGenerators, BitSize, BulletproofGens, PedersenGens, RangeProof, SharedBulletproofGens,
+};
+
+fn first_G(gens: &SharedBulletproofGens, j: usize) -> *const RistrettoPoint {
+    gens.try_share(j).unwrap().G(1).next().unwrap()
+}
+
+#[test]
+fn threads_prove_against_one_shared_table() {
+    let shared = BulletproofGens::new(64, 2).into_shared();
+    let tables: Vec<_> = (0..2).map(|j| first_G(&shared, j)).collect();
+
+    let handles: Vec<_> = (0..8u8)
+        .map(|i| {
+            let gens = shared.clone();
+            thread::spawn(move || {
+                let pc_gens = PedersenGens::default();
+                let mut rng = ChaChaRng::from_seed([i; 32]);
+                let values = [u64::from(i), 1000 + u64::from(i)];
+                let blindings = [Scalar::random(&mut rng), Scalar::random(&mut rng)];
+
+                let mut transcript = Transcript::new(b"SharedGensTest");
+                let (proof, commitments) = RangeProof::prove_multiple_with_rng(
+                    &gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &values,
+                    &blindings,
+                    BitSize::B32,
+                    &mut rng,
+                )
+                .unwrap();
+
+                let mut transcript = Transcript::new(b"SharedGensTest");
+                proof
+                    .verify_multiple_with_rng(
+                        &gens,
+                        &pc_gens,
+                        &mut transcript,
+                        &commitments,
+                        BitSize::B32,
+                        &mut rng,
+                    )
+                    .unwrap();
+
+                (0..2)
+                    .map(|j| first_G(&gens, j) as usize)
+                    .collect::<Vec<_>>()
+            })
+        })
+        .collect();
+
+    for handle in handles {
+        let seen = handle.join().unwrap();
+        let expected: Vec<usize> = tables.iter().map(|&p| p as usize).collect();
+        assert_eq!(seen, expected);
+    }
+}
+
+#[test]
+fn owned_and_shared_gens_produce_interchangeable_proofs() {
+    let owned = BulletproofGens::new(32, 1);
+    let shared = owned.clone().into_shared();
+    assert!(core::ptr::eq(
+        shared.as_generators(),
+        shared.clone().as_generators()
+    ));
+
+    let pc_gens = PedersenGens::default();
+    let mut rng = ChaChaRng::from_seed([9u8; 32]);
+    let blinding = Scalar::random(&mut rng);
+
+    let mut transcript = Transcript::new(b"SharedGensTest");
+    let (proof, commitment) = RangeProof::prove_single_with_rng(
+        &owned,
+        &pc_gens,
+        &mut transcript,
+        7,
+        &blinding,
+        BitSize::B32,
+        &mut rng,
+    )
+    .unwrap();
+
+    let mut transcript = Transcript::new(b"SharedGensTest");
+    assert!(proof
+        .verify_single_with_rng(
+            &shared,
+            &pc_gens,
+            &mut transcript,
+            &commitment,
+            BitSize::B32,
+            &mut rng
+        )
+        .is_ok());
+}
@@ -0,0 +1,190 @@
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use bulletproofs::contrib::small_alphabet::SmallAlphabetProof;
+use bulletproofs::r1cs::R1CSError;
+use bulletproofs::{BulletproofGens, PedersenGens};
+
+/// A proof for six trits with the counts `[2, 1, 3]`: the entries
+/// `[2, 0, 1, 2, 2, 0]`, committed to with the blinding factors
+/// `1, ..., 6`, and proven with 32 generators on the transcript
+/// `SmallAlphabetTest`.
+const FIXTURE_PROOF: &str = concat!(
+    "0072b924f1d6523ac8340b7c8f36ccef446a13c42a57c71f8420d23ec5ec1c14",
+    "35565eb9cf08b360119e58b2661a9dddc7f8099c7fbaf7567a388c3176ab2925",
+    "5be0e91ce18903e42896c02c0027a42d2b55c2d0258afde185798a80d24e18f9",
+    "35960233943fa6e74bcc4febf86914a57ea305530fe90adfafa386bee3ff9655",
+    "7beebceed96094addbf999c762e3c319c3022c839d4da1cd03d214d84c47543c",
+    "2e288b445d0c0bd29670c6f61737afa5457d2d90112106c61966b1e8b6e62be9",
+    "481cbf53a768447a14e118e9ac7338c5b361f94dabbcabe26c342a062496bde0",
+    "5d066fc92752ed83f871812236d40e51b207e2ea1e58fea280184ab106af780f",
+    "5e055a123bca1208ad3884c47d9e0d31878625463fdb5738fc83cc773ace3aa5",
+    "068fbda4ed47336c969bd87fc145a9329c3a355db0628a6c0c4389d0605c9727",
+    "083388f420fb7dd2b52b206a7cad4887bc5c32be3494ca5e171c0a63633128ac",
+    "0280bb7eefa575ce21c25efd20c624156cfc3d8dda4516b73516f68c61cdb687",
+    "2e4cea32bac6e4daec2af9654b9e0b4aaf0d7f98295af404c9a9f77352bee701",
+    "014654100f5318c9229e669ae3538409f900239b332009847e5dc36727e36f72",
+    "5970ecb2589187e71052fe848dbe5cf6cebc0f507692a2ddd4d0252c23b915fe",
+    "7570815a43bc89ae4e9c1b6bd80638cb77d0ad8e67f763699df5e03d29397d95",
+    "6e0ae1938b7c94c9ebcf5664cb356cd59d9bd070e49906c9e5ae77fbf39388af",
+    "496aa0ada02bfec74ff1a229f38309e2c8fc185ea5347c66c3e914403ce041d9",
+    "3c028a7e16627a1f42ab74ed78269647235b481c3490e4d0ae9bf652c5799fa7",
+    "09ee351466859ea7844305653086c834cb9a4b88c12ea7f3962725ee821070aa",
+    "703a0c8feac12c976b6d77fa47e2cc9d91a7f6e29d7cbf5f675ac3d18f8d9a3a",
+    "3c6a2b2893f7a218e8c4a03ffc414d67d51c420e29373946c1aecb3493fd429b",
+    "0009b0b210c755776a00427917ac0d93dca0ee599f18f0646190ebbd3afc71d2",
+    "02",
+);
+
+const FIXTURE_COMMITMENTS: [&str; 6] = [
+    "c025864cb4d49e28ac40db3cb6e793eb0e99de80fda577288b046ef71f008350",
+    "10f21a8723d8943e2b37207a4815638fcc0b5efc9dc3346445ca985c6d5c2207",
+    "4e5342e7bcdb1e714fe21304b4f3810740c7381d46ec456e122a89684376ec65",
+    "26c3875b4dcea28119816d39286210ec49efdeedd9e1cdc36f898c0156db8e10",
+    "58fdbc22d9582b09797c30d584d78f526c3e244523af9bf95bd5ce94f5c7db75",
+    "844c0f39d5b92254a3cffd1089761a2f12e01e9f0b6f899fc4d041c9e0d6e547",
+];
+
+fn fixture_commitments() -> Vec<CompressedRistretto> {
+    FIXTURE_COMMITMENTS
+        .iter()
+        .map(|V| CompressedRistretto::from_slice(&hex::decode(V).unwrap()).unwrap())
+        .collect()
+}
+
+#[test]
+fn fixture_verifies() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let proof = SmallAlphabetProof::from_bytes(&hex::decode(FIXTURE_PROOF).unwrap()).unwrap();
+    let commitments = fixture_commitments();
+
+    // The commitments are to the documented entries.
+    for ((V, v), blinding) in commitments.iter().zip([2u64, 0, 1, 2, 2, 0]).zip(1u64..) {
+        assert_eq!(
+            *V,
+            pc_gens
+                .commit(Scalar::from(v), Scalar::from(blinding))
+                .compress()
+        );
+    }
+
+    let verify = |label: &'static [u8], counts: &[u64], commitments: &[CompressedRistretto]| {
+        let mut transcript = Transcript::new(label);
+        proof.verify(&bp_gens, &pc_gens, &mut transcript, counts, commitments)
+    };
+    assert!(verify(b"SmallAlphabetTest", &[2, 1, 3], &commitments).is_ok());
+    assert!(verify(b"OtherTest", &[2, 1, 3], &commitments).is_err());
+
+    // The same commitments with other multiplicities.
+    for counts in [&[3, 0, 3][..], &[2, 1, 3, 0], &[2, 1, 2], &[2, 4]] {
+        assert!(verify(b"SmallAlphabetTest", counts, &commitments).is_err());
+    }
+
+    // Swapped commitments keep the multiset, but not the order bound
+    // to the proof.
+    let mut swapped = commitments.clone();
+    swapped.swap(0, 1);
+    assert!(verify(b"SmallAlphabetTest", &[2, 1, 3], &swapped).is_err());
+}
+
+#[test]
+fn k_hot_vectors() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(64, 1);
+    let mut rng = ChaChaRng::from_seed([12u8; 32]);
+
+    // A 3-hot vector of 16 bits.
+    let mut entries = vec![0u64; 16];
+    for &i in &[1, 7, 12] {
+        entries[i] = 1;
+    }
+    let blindings: Vec<Scalar> = entries.iter().map(|_| Scalar::random(&mut rng)).collect();
+    let (proof, commitments) = SmallAlphabetProof::prove_with_rng(
+        &bp_gens,
+        &pc_gens,
+        &mut Transcript::new(b"SmallAlphabetTest"),
+        &[13, 3],
+        &entries,
+        &blindings,
+        &mut rng,
+    )
+    .unwrap();
+    let mut verify = |counts: &[u64], commitments: &[CompressedRistretto]| {
+        let mut transcript = Transcript::new(b"SmallAlphabetTest");
+        proof.verify_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            counts,
+            commitments,
+            &mut rng,
+        )
+    };
+    assert!(verify(&[13, 3], &commitments).is_ok());
+    assert!(verify(&[12, 4], &commitments).is_err());
+
+    // Replace a one by a two, which keeps the sum of the entries.
+    let mut forged = commitments.clone();
+    forged[7] = pc_gens.commit(Scalar::from(2u64), blindings[7]).compress();
+    forged[12] = pc_gens.commit(Scalar::ZERO, blindings[12]).compress();
+    assert!(verify(&[13, 3], &forged).is_err());
+}
+
+#[test]
+fn prover_rejects_invalid_witnesses() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let blindings = [Scalar::ONE; 4];
+    let prove = |counts: &[u64], entries: &[u64], blindings: &[Scalar]| {
+        let mut transcript = Transcript::new(b"SmallAlphabetTest");
+        SmallAlphabetProof::prove(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            counts,
+            entries,
+            blindings,
+        )
+        .map(|_| ())
+    };
+
+    let gadget_error = |description: &str| {
+        Err(R1CSError::GadgetError {
+            description: description.into(),
+        })
+    };
+    assert_eq!(
+        prove(&[1, 1, 2], &[0, 3, 2, 1], &blindings),
+        gadget_error("entry is not in the alphabet")
+    );
+    assert_eq!(
+        prove(&[1, 1, 2], &[0, 1, 1, 2], &blindings),
+        gadget_error("entries do not have the given multiplicities")
+    );
+    assert_eq!(
+        prove(&[1, 1, 2], &[0, 1, 2, 2], &blindings[..3]),
+        gadget_error("there must be one blinding factor per entry")
+    );
+    assert_eq!(prove(&[1, 1, 2], &[2, 1, 2, 0], &blindings), Ok(()));
+
+    // 4 entries of 3 symbols need 18 multipliers, more than 16 generators.
+    assert_eq!(SmallAlphabetProof::multipliers(4, 3), 18);
+    assert_eq!(
+        SmallAlphabetProof::prove(
+            &BulletproofGens::new(16, 1),
+            &pc_gens,
+            &mut Transcript::new(b"SmallAlphabetTest"),
+            &[1, 1, 2],
+            &[2, 1, 2, 0],
+            &blindings
+        )
+        .map(|_| ()),
+        Err(R1CSError::InvalidGeneratorsLength)
+    );
+}
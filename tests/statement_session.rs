@@ -0,0 +1,185 @@
+//! Several proofs about the same commitments on branches of one
+//! `StatementSession`.
+
+#![allow(non_snake_case)]
+
+use curve25519_dalek::ristretto::CompressedRistretto;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use bulletproofs::{
+    BitSize, BulletproofGens, NonZeroProof, PedersenGens, ProofError, RangeProof, StatementSession,
+};
+
+const CONTEXT: &[u8] = b"payment 17";
+
+struct Proofs {
+    commitments: Vec<CompressedRistretto>,
+    range_proof: RangeProof,
+    nonzero_proof: NonZeroProof,
+}
+
+fn session(commitments: &[CompressedRistretto]) -> StatementSession {
+    StatementSession::new(
+        Transcript::new(b"StatementSessionTest"),
+        commitments,
+        CONTEXT,
+    )
+}
+
+/// Proves that the second of two commitments is to a nonzero 32-bit
+/// value, with a range proof followed by a nonzero proof.
+fn prove(pc_gens: &PedersenGens, bp_gens: &BulletproofGens) -> Proofs {
+    let openings = [(0u64, Scalar::from(3u64)), (5000, Scalar::from(11u64))];
+    let commitments: Vec<CompressedRistretto> = openings
+        .iter()
+        .map(|&(v, v_blinding)| pc_gens.commit(Scalar::from(v), v_blinding).compress())
+        .collect();
+
+    let mut session = session(&commitments);
+    let (v, v_blinding) = openings[1];
+    let range_proof = RangeProof::prove_in_session(
+        bp_gens,
+        pc_gens,
+        &mut session,
+        1,
+        v,
+        &v_blinding,
+        BitSize::B32,
+    )
+    .unwrap();
+    let nonzero_proof =
+        NonZeroProof::prove_in_session(pc_gens, &mut session, 1, Scalar::from(v), v_blinding)
+            .unwrap();
+    Proofs {
+        commitments,
+        range_proof,
+        nonzero_proof,
+    }
+}
+
+#[test]
+fn proofs_verify_over_one_session() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let proofs = prove(&pc_gens, &bp_gens);
+
+    let mut session = session(&proofs.commitments);
+    let verified = proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .unwrap();
+    assert_eq!(verified.bits, 32);
+    proofs
+        .nonzero_proof
+        .verify_in_session(&pc_gens, &mut session, 1)
+        .unwrap();
+
+    // The session transcript does not verify the proofs on its own.
+    let mut transcript = Transcript::new(b"StatementSessionTest");
+    assert!(proofs
+        .range_proof
+        .verify_single(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            &proofs.commitments[1],
+            BitSize::B32
+        )
+        .is_err());
+}
+
+#[test]
+fn reordered_branches_are_rejected() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let proofs = prove(&pc_gens, &bp_gens);
+
+    let mut session = session(&proofs.commitments);
+    assert!(proofs
+        .nonzero_proof
+        .verify_in_session(&pc_gens, &mut session, 1)
+        .is_err());
+    assert!(proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .is_err());
+
+    // An extra branch before the proofs moves both of them.
+    let mut session = self::session(&proofs.commitments);
+    session.branch(b"range-proof");
+    assert!(proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .is_err());
+    assert!(proofs
+        .nonzero_proof
+        .verify_in_session(&pc_gens, &mut session, 1)
+        .is_err());
+
+    // Taking the first branch by hand leaves the second in place.
+    let mut session = self::session(&proofs.commitments);
+    session.branch(b"range-proof");
+    proofs
+        .nonzero_proof
+        .verify_in_session(&pc_gens, &mut session, 1)
+        .unwrap();
+}
+
+#[test]
+fn substituted_commitments_are_rejected() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let proofs = prove(&pc_gens, &bp_gens);
+
+    // The other commitment of the session.
+    let mut session = self::session(&proofs.commitments);
+    assert!(proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 0, BitSize::B32)
+        .is_err());
+
+    // A session with a different commitment in place of one that the
+    // branch does not even use.
+    let mut commitments = proofs.commitments.clone();
+    commitments[0] = pc_gens.commit(Scalar::ZERO, Scalar::from(4u64)).compress();
+    let mut session = self::session(&commitments);
+    assert!(proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .is_err());
+    assert!(proofs
+        .nonzero_proof
+        .verify_in_session(&pc_gens, &mut session, 1)
+        .is_err());
+
+    // Another context.
+    let mut session = StatementSession::new(
+        Transcript::new(b"StatementSessionTest"),
+        &proofs.commitments,
+        b"payment 18",
+    );
+    assert!(proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .is_err());
+}
+
+#[test]
+fn missing_commitments_take_no_branch() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let proofs = prove(&pc_gens, &bp_gens);
+
+    let mut session = session(&proofs.commitments);
+    assert_eq!(
+        proofs
+            .range_proof
+            .verify_in_session(&bp_gens, &pc_gens, &mut session, 2, BitSize::B32),
+        Err(ProofError::InvalidInputLength)
+    );
+    proofs
+        .range_proof
+        .verify_in_session(&bp_gens, &pc_gens, &mut session, 1, BitSize::B32)
+        .unwrap();
+}
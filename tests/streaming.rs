@@ -0,0 +1,247 @@
+//! Writing proofs to and reading them from `std::io` streams, over
+//! cursors and over in-memory pipes that deliver a few bytes at a time.
+
+#![allow(non_snake_case)]
+
+use std::convert::TryFrom;
+
+use std::io::{self, Cursor, Read, Write};
+use std::sync::mpsc;
+use std::thread;
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use bulletproofs::{
+    BitSize, BulletproofGens, PedersenGens, ProofError, RangeProof, MAX_RANGE_PROOF_SIZE,
+};
+
+/// The writing end of an in-memory pipe, which accepts at most
+/// `chunk` bytes per write.
+struct PipeWriter {
+    sender: mpsc::Sender<Vec<u8>>,
+    chunk: usize,
+}
+
+/// The reading end of an in-memory pipe, which returns at most the
+/// bytes of one write per read, and is interrupted before every other
+/// read.
+struct PipeReader {
+    receiver: mpsc::Receiver<Vec<u8>>,
+    pending: Vec<u8>,
+    interrupt: bool,
+}
+
+fn pipe(chunk: usize) -> (PipeWriter, PipeReader) {
+    let (sender, receiver) = mpsc::channel();
+    (
+        PipeWriter { sender, chunk },
+        PipeReader {
+            receiver,
+            pending: Vec::new(),
+            interrupt: false,
+        },
+    )
+}
+
+impl Write for PipeWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.chunk);
+        self.sender
+            .send(buf[..len].to_vec())
+            .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Read for PipeReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.interrupt = !self.interrupt;
+        if self.interrupt {
+            return Err(io::Error::from(io::ErrorKind::Interrupted));
+        }
+        if self.pending.is_empty() {
+            match self.receiver.recv() {
+                Ok(chunk) => self.pending = chunk,
+                // The writer was dropped.
+                Err(_) => return Ok(0),
+            }
+        }
+        let len = buf.len().min(self.pending.len());
+        buf[..len].copy_from_slice(&self.pending[..len]);
+        self.pending.drain(..len);
+        Ok(len)
+    }
+}
+
+/// Writes to a pipe with `write_to` on another thread, in chunks of
+/// at most `chunk` bytes, and reads the other end with `read_from`.
+/// Returns what was read and the number of bytes written.
+fn through_pipe<T>(
+    chunk: usize,
+    write_to: impl FnOnce(PipeWriter) -> io::Result<usize> + Send + 'static,
+    read_from: fn(PipeReader) -> io::Result<T>,
+) -> (io::Result<T>, usize) {
+    let (writer, reader) = pipe(chunk);
+    let handle = thread::spawn(move || write_to(writer).unwrap());
+    let result = read_from(reader);
+    (result, handle.join().unwrap())
+}
+
+fn range_proof(n: usize, m: usize) -> RangeProof {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(n, m);
+    let values: Vec<u64> = (0..m as u64).collect();
+    let blindings: Vec<Scalar> = (1..=m as u64).map(Scalar::from).collect();
+    let mut transcript = Transcript::new(b"StreamingTest");
+    RangeProof::prove_multiple(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        &values,
+        &blindings,
+        BitSize::try_from(n).unwrap(),
+    )
+    .unwrap()
+    .0
+}
+
+fn invalid_data<T>(result: io::Result<T>) -> io::Error {
+    match result {
+        Ok(_) => panic!("expected an error"),
+        Err(e) => {
+            assert_eq!(e.kind(), io::ErrorKind::InvalidData);
+            e
+        }
+    }
+}
+
+#[test]
+fn range_proofs_stream_like_to_bytes() {
+    for (n, m) in [(8, 1), (64, 1), (32, 8)] {
+        let proof = range_proof(n, m);
+        let bytes = proof.to_bytes();
+
+        let mut cursor = Cursor::new(Vec::new());
+        assert_eq!(proof.write_to(&mut cursor).unwrap(), bytes.len());
+        assert_eq!(cursor.get_ref(), &bytes);
+        assert_eq!(RangeProof::read_from(Cursor::new(&bytes)).unwrap(), proof);
+
+        for chunk in [1, 5, 32, 100] {
+            let sent = proof.clone();
+            let (received, written) =
+                through_pipe(chunk, move |w| sent.write_to(w), RangeProof::read_from);
+            assert_eq!(written, bytes.len());
+            assert_eq!(received.unwrap(), proof);
+        }
+    }
+}
+
+#[test]
+fn consecutive_range_proofs_are_framed_with_take() {
+    let proofs = [range_proof(8, 1), range_proof(16, 2), range_proof(64, 4)];
+    let mut stream = Vec::new();
+    for proof in proofs.iter() {
+        proof.write_to(&mut stream).unwrap();
+    }
+
+    let mut reader = Cursor::new(&stream);
+    for (proof, (n, m)) in proofs.iter().zip([(8, 1), (16, 2), (64, 4)]) {
+        let size = RangeProof::size_const(n, m) as u64;
+        let read = RangeProof::read_from((&mut reader).take(size)).unwrap();
+        assert_eq!(&read, proof);
+    }
+    assert_eq!(reader.position(), stream.len() as u64);
+}
+
+#[test]
+fn malformed_range_proof_streams_are_rejected() {
+    let bytes = range_proof(8, 1).to_bytes();
+
+    // Truncated, including within an element.
+    for len in [0, 7 * 32, bytes.len() - 32, bytes.len() - 1] {
+        invalid_data(RangeProof::read_from(&bytes[..len]));
+    }
+
+    // t_x is the scalar after the four points.
+    let mut noncanonical = bytes.clone();
+    noncanonical[4 * 32..5 * 32].copy_from_slice(&[0xff; 32]);
+    let e = invalid_data(RangeProof::read_from(Cursor::new(&noncanonical)));
+    let source = e.get_ref().unwrap().downcast_ref::<ProofError>();
+    assert_eq!(source, Some(&ProofError::FormatError));
+
+    // An endless stream is rejected after reading just past the limit.
+    let mut endless = io::repeat(0).take(u64::MAX);
+    invalid_data(RangeProof::read_from(&mut endless));
+    assert_eq!(u64::MAX - endless.limit(), MAX_RANGE_PROOF_SIZE as u64 + 1);
+}
+
+#[test]
+fn write_errors_are_returned() {
+    let (writer, reader) = pipe(32);
+    drop(reader);
+    let e = range_proof(8, 1).write_to(writer).unwrap_err();
+    assert_eq!(e.kind(), io::ErrorKind::BrokenPipe);
+}
+
+#[cfg(feature = "yoloproofs")]
+#[test]
+fn r1cs_proofs_stream_like_to_bytes() {
+    use bulletproofs::r1cs::{
+        ConstraintSystem, Prover, R1CSError, R1CSProof, RandomizableConstraintSystem,
+        RandomizedConstraintSystem,
+    };
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(8, 1);
+    // Proofs with and without second-phase commitments, whose
+    // encodings differ in length.
+    let prove = |randomized: bool| -> R1CSProof {
+        let mut transcript = Transcript::new(b"StreamingTest");
+        let mut prover = Prover::new(&pc_gens, &mut transcript);
+        let (_, x) = prover.commit(Scalar::from(3u64), Scalar::from(5u64));
+        let (_, _, out) = prover.multiply(x.into(), x.into());
+        prover.constrain(out - Scalar::from(9u64));
+        if randomized {
+            prover
+                .specify_randomized_constraints(move |cs| {
+                    let z = cs.challenge_scalar(b"z");
+                    let (_, _, out) = cs.multiply(x - z, x - z);
+                    cs.constrain(out - (Scalar::from(3u64) - z) * (Scalar::from(3u64) - z));
+                    Ok::<(), R1CSError>(())
+                })
+                .unwrap();
+        }
+        prover.prove(&bp_gens).unwrap()
+    };
+
+    for randomized in [false, true] {
+        let proof = prove(randomized);
+        let bytes = proof.to_bytes();
+        assert_eq!(bytes[0], randomized as u8);
+
+        let mut cursor = Cursor::new(Vec::new());
+        assert_eq!(proof.write_to(&mut cursor).unwrap(), bytes.len());
+        assert_eq!(cursor.get_ref(), &bytes);
+        let read = R1CSProof::read_from(Cursor::new(&bytes)).unwrap();
+        assert_eq!(read.to_bytes(), bytes);
+
+        for chunk in [1, 31, 64] {
+            let sent = proof.clone();
+            let (received, written) =
+                through_pipe(chunk, move |w| sent.write_to(w), R1CSProof::read_from);
+            assert_eq!(written, bytes.len());
+            assert_eq!(received.unwrap().to_bytes(), bytes);
+        }
+
+        invalid_data(R1CSProof::read_from(&bytes[..bytes.len() - 1]));
+    }
+
+    let mut endless = io::repeat(1).take(u64::MAX);
+    invalid_data(R1CSProof::read_from(&mut endless));
+    assert!(u64::MAX - endless.limit() < 4096);
+}
@@ -0,0 +1,223 @@
+#![allow(non_snake_case)]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use bulletproofs::{
+    BatchVerifier, BitSize, BulletproofGens, PedersenGens, RangeProof, VerificationOptions,
+};
+
+/// Everything a [`Recorder`] has seen.
+#[derive(Default)]
+struct Log {
+    /// The path of span names from the root, for each span.
+    spans: Vec<String>,
+    /// The path of the enclosing span and the message, for each event.
+    events: Vec<String>,
+    /// The `Debug` output of every recorded field.
+    fields: Vec<String>,
+    stack: Vec<usize>,
+}
+
+impl Log {
+    fn current_path(&self) -> String {
+        self.stack
+            .last()
+            .map(|&i| self.spans[i].clone())
+            .unwrap_or_default()
+    }
+}
+
+struct FieldVisitor<'a> {
+    fields: &'a mut Vec<String>,
+    message: Option<String>,
+}
+
+impl<'a> Visit for FieldVisitor<'a> {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        let value = format!("{:?}", value);
+        if field.name() == "message" {
+            self.message = Some(value.clone());
+        }
+        self.fields.push(value);
+    }
+}
+
+/// A minimal subscriber that records the span tree of one thread.
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Log>>);
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        let mut log = self.0.lock().unwrap();
+        let path = match log.current_path().as_str() {
+            "" => span.metadata().name().to_string(),
+            parent => format!("{}/{}", parent, span.metadata().name()),
+        };
+        span.record(&mut FieldVisitor {
+            fields: &mut log.fields,
+            message: None,
+        });
+        log.spans.push(path);
+        Id::from_u64(log.spans.len() as u64)
+    }
+
+    fn record(&self, _: &Id, values: &Record<'_>) {
+        let mut log = self.0.lock().unwrap();
+        values.record(&mut FieldVisitor {
+            fields: &mut log.fields,
+            message: None,
+        });
+    }
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut log = self.0.lock().unwrap();
+        let mut visitor = FieldVisitor {
+            fields: &mut log.fields,
+            message: None,
+        };
+        event.record(&mut visitor);
+        let message = visitor.message.unwrap_or_default();
+        let path = log.current_path();
+        log.events.push(format!("{}: {}", path, message));
+    }
+
+    fn enter(&self, span: &Id) {
+        self.0
+            .lock()
+            .unwrap()
+            .stack
+            .push(span.into_u64() as usize - 1);
+    }
+
+    fn exit(&self, _: &Id) {
+        self.0.lock().unwrap().stack.pop();
+    }
+}
+
+#[test]
+fn range_proof_phases_are_traced_without_secrets() {
+    let value = 3141592653u64;
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let mut rng = ChaChaRng::from_seed([43u8; 32]);
+    let blinding = Scalar::random(&mut rng);
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        let mut transcript = Transcript::new(b"TraceTest");
+        let (proof, V) = RangeProof::prove_single_with_rng(
+            &bp_gens,
+            &pc_gens,
+            &mut transcript,
+            value,
+            &blinding,
+            BitSize::B32,
+            &mut rng,
+        )
+        .unwrap();
+
+        let mut transcript = Transcript::new(b"TraceTest");
+        proof
+            .verify_single_with_rng(
+                &bp_gens,
+                &pc_gens,
+                &mut transcript,
+                &V,
+                BitSize::B32,
+                &mut rng,
+            )
+            .unwrap();
+    });
+
+    let log = recorder.0.lock().unwrap();
+    for path in &[
+        "rangeproof.prove/rangeproof.commit_bits",
+        "rangeproof.prove/rangeproof.commit_poly",
+        "rangeproof.prove/rangeproof.proof_share",
+        "rangeproof.prove/rangeproof.assemble_shares/ipp.create",
+        "rangeproof.verify/ipp.verification_scalars",
+        "rangeproof.verify/rangeproof.msm",
+    ] {
+        assert!(
+            log.spans.iter().any(|span| span == path),
+            "missing span {}",
+            path
+        );
+    }
+    let ipp_rounds = log
+        .events
+        .iter()
+        .filter(|event| event.ends_with("/ipp.create: ipp round"))
+        .count();
+    assert_eq!(ipp_rounds, 5);
+
+    // No recorded field may reveal the value or the blinding factor.
+    let secrets = [
+        value.to_string(),
+        format!("{:?}", blinding),
+        hex::encode(blinding.as_bytes()),
+    ];
+    for field in &log.fields {
+        for secret in &secrets {
+            assert!(!field.contains(secret.as_str()), "{} leaks a secret", field);
+        }
+    }
+}
+
+#[test]
+fn memory_budget_fallback_is_traced() {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(32, 1);
+    let mut rng = ChaChaRng::from_seed([44u8; 32]);
+    let mut transcript = Transcript::new(b"TraceTest");
+    let (proof, V) = RangeProof::prove_single_with_rng(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        7,
+        &Scalar::random(&mut rng),
+        BitSize::B32,
+        &mut rng,
+    )
+    .unwrap();
+
+    let recorder = Recorder::default();
+    tracing::subscriber::with_default(recorder.clone(), || {
+        for max_memory_bytes in [None, Some(30_000)] {
+            let mut batch = BatchVerifier::with_options(VerificationOptions {
+                max_memory_bytes,
+                ..Default::default()
+            });
+            batch.queue_range(
+                &proof,
+                std::slice::from_ref(&V),
+                BitSize::B32,
+                Transcript::new(b"TraceTest"),
+            );
+            batch.verify_with_rng(&bp_gens, &pc_gens, &mut rng).unwrap();
+        }
+    });
+
+    let log = recorder.0.lock().unwrap();
+    let fallbacks = log
+        .events
+        .iter()
+        .filter(|event| event.ends_with("batch.verify: low-memory multiscalar multiplication"))
+        .count();
+    assert_eq!(fallbacks, 1);
+}
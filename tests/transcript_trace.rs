@@ -0,0 +1,162 @@
+//! Checks the transcript traces of the test vector proofs against the
+//! golden files in `tests/transcripts`, which document the Fiat-Shamir
+//! transcript for verifiers in other languages.
+//!
+//! Run with `REGENERATE_TRANSCRIPTS=1` to rewrite the golden files
+//! after a deliberate transcript change.
+
+#![allow(non_snake_case)]
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use merlin::Transcript;
+
+use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof, TranscriptOp};
+
+mod common;
+
+use common::{test_vector_commitments, TEST_VECTOR_PROOFS};
+
+const LABEL: &str = "Deserialize-And-Verify Test";
+
+/// Returns the golden file of the `n`-bit, `m`-value test vector: the
+/// statement, the transcript label, and the trace with one operation
+/// per line.
+fn golden_file(proof: &RangeProof, n: BitSize, m: usize) -> String {
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(64, 8);
+    let vc = &test_vector_commitments()[0..m];
+    let trace = proof
+        .transcript_trace(
+            &bp_gens,
+            &pc_gens,
+            &Transcript::new(LABEL.as_bytes()),
+            vc,
+            n,
+        )
+        .unwrap();
+
+    let ops: Vec<String> = trace.iter().map(TranscriptOp::to_json).collect();
+    format!(
+        "{{\"statement\":{},\n\"trace\":[\n{}\n],\n\"transcript_label\":\"{}\"}}\n",
+        proof.to_json_statement(n, vc),
+        ops.join(",\n"),
+        LABEL
+    )
+}
+
+#[test]
+fn golden_transcripts_are_up_to_date() {
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/transcripts");
+    let regenerate = env::var_os("REGENERATE_TRANSCRIPTS").is_some();
+
+    for (i, proofs_i) in TEST_VECTOR_PROOFS.iter().enumerate() {
+        for (j, proof_ij) in proofs_i.iter().enumerate() {
+            let (n, m) = (BitSize::ALL[i], 1 << j);
+            let proof = RangeProof::from_bytes(&hex::decode(proof_ij).unwrap()).unwrap();
+            let contents = golden_file(&proof, n, m);
+            let path = dir.join(format!("rangeproof_n{}_m{}.json", n.bits(), m));
+            if regenerate {
+                fs::write(&path, contents).unwrap();
+            } else {
+                assert_eq!(
+                    fs::read_to_string(&path).unwrap(),
+                    contents,
+                    "{} is out of date",
+                    path.display()
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn golden_transcripts_are_canonical_json() {
+    let proof = RangeProof::from_bytes(&hex::decode(TEST_VECTOR_PROOFS[1][2]).unwrap()).unwrap();
+    let vc = &test_vector_commitments()[0..4];
+
+    let statement = proof.to_json_statement(BitSize::B16, vc);
+    let value: serde_json::Value = serde_json::from_str(&statement).unwrap();
+    assert_eq!(serde_json::to_string(&value).unwrap(), statement);
+    assert_eq!(value["n"], 16);
+    assert_eq!(value["m"], 4);
+    assert_eq!(value["proof"], hex::encode(proof.to_bytes()));
+    assert_eq!(value["commitments"][3], hex::encode(vc[3].as_bytes()));
+
+    let golden: serde_json::Value =
+        serde_json::from_str(&golden_file(&proof, BitSize::B16, 4)).unwrap();
+    assert_eq!(golden["statement"], value);
+    assert_eq!(golden["trace"][0]["label"], "dom-sep");
+    assert_eq!(
+        golden["trace"][0]["bytes"],
+        hex::encode(b"rangeproof v1".as_ref())
+    );
+}
+
+/// Replays `trace` on `transcript`, checking each challenge.
+fn replay(transcript: &mut Transcript, trace: &[TranscriptOp]) {
+    for op in trace {
+        match op {
+            TranscriptOp::Append { label, message } => transcript.append_message(label, message),
+            TranscriptOp::Challenge { label, bytes } => {
+                let mut challenge = vec![0u8; bytes.len()];
+                transcript.challenge_bytes(label, &mut challenge);
+                assert_eq!(&challenge, bytes);
+            }
+        }
+    }
+}
+
+#[test]
+fn trace_replays_to_the_verifier_transcript() {
+    // Non-default Pedersen generators add their own domain separator.
+    let pc_gens = PedersenGens::with_value_generator(RistrettoPoint::hash_from_bytes::<
+        sha3::Sha3_512,
+    >(b"asset"))
+    .unwrap();
+    let bp_gens = BulletproofGens::new(32, 2);
+    let blindings = [Scalar::from(5u64), Scalar::from(6u64)];
+
+    let mut transcript = Transcript::new(b"TraceTest");
+    let (proof, V) = RangeProof::prove_multiple(
+        &bp_gens,
+        &pc_gens,
+        &mut transcript,
+        &[17, 1 << 31],
+        &blindings,
+        BitSize::B32,
+    )
+    .unwrap();
+
+    let transcript = Transcript::new(b"TraceTest");
+    let trace = proof
+        .transcript_trace(&bp_gens, &pc_gens, &transcript, &V, BitSize::B32)
+        .unwrap();
+    assert!(trace.contains(&TranscriptOp::Append {
+        label: b"dom-sep",
+        message: b"pedersen-gens v1".to_vec(),
+    }));
+
+    let mut verifier_transcript = transcript.clone();
+    proof
+        .verify_multiple(
+            &bp_gens,
+            &pc_gens,
+            &mut verifier_transcript,
+            &V,
+            BitSize::B32,
+        )
+        .unwrap();
+    let mut replayed = transcript;
+    replay(&mut replayed, &trace);
+
+    let mut expected = [0u8; 32];
+    let mut actual = [0u8; 32];
+    verifier_transcript.challenge_bytes(b"check", &mut expected);
+    replayed.challenge_bytes(b"check", &mut actual);
+    assert_eq!(actual, expected);
+}
@@ -0,0 +1,62 @@
+//! Checks that a verifier-only build verifies proofs created by a
+//! full build.
+//!
+//! Run with `cargo test --no-default-features --features verifier,alloc --test verifier_only`.
+
+use std::convert::TryFrom;
+
+use rand_chacha::ChaChaRng;
+use rand_core::SeedableRng;
+
+use merlin::Transcript;
+
+use bulletproofs::{BitSize, BulletproofGens, PedersenGens, RangeProof};
+
+mod common;
+
+use common::{test_vector_commitments, TEST_VECTOR_PROOFS};
+
+#[test]
+fn verify_test_vectors() {
+    let vc = test_vector_commitments();
+
+    let pc_gens = PedersenGens::default();
+    let bp_gens = BulletproofGens::new(64, 8);
+    let mut rng = ChaChaRng::from_seed([7u8; 32]);
+
+    for (i, proofs_i) in TEST_VECTOR_PROOFS.iter().enumerate() {
+        for (j, proof_ij) in proofs_i.iter().enumerate() {
+            let (n, m) = (8 << i, 1 << j);
+            let proof = RangeProof::from_bytes(&hex::decode(proof_ij).unwrap())
+                .expect("Rangeproof deserialization failed");
+
+            let mut transcript = Transcript::new(b"Deserialize-And-Verify Test");
+            assert_eq!(
+                proof.verify_multiple_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &vc[0..m],
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng
+                ),
+                Ok(())
+            );
+
+            // The proofs do not verify for the wrong values.
+            let mut wrong = vc[0..m].to_vec();
+            wrong[0] = vc[7];
+            let mut transcript = Transcript::new(b"Deserialize-And-Verify Test");
+            assert!(proof
+                .verify_multiple_with_rng(
+                    &bp_gens,
+                    &pc_gens,
+                    &mut transcript,
+                    &wrong,
+                    BitSize::try_from(n).unwrap(),
+                    &mut rng
+                )
+                .is_err());
+        }
+    }
+}